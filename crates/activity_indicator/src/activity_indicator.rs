@@ -201,6 +201,32 @@ impl ActivityIndicator {
                 })),
             });
         }
+        // Show any toolchain installations started from the toolchain selector.
+        if let Some((first, rest)) = self
+            .project
+            .read(cx)
+            .pending_toolchain_installs()
+            .split_first()
+        {
+            let mut message = format!("Installing {}…", first.name);
+            if !rest.is_empty() {
+                write!(&mut message, " + {} more", rest.len()).unwrap();
+            }
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+            });
+        }
         // Show any language server has pending activity.
         let mut pending_work = self.pending_language_server_work(cx);
         if let Some(PendingWork {
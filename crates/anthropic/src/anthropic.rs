@@ -6,7 +6,7 @@ use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use futures::{io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, Stream, StreamExt};
 use http_client::http::{HeaderMap, HeaderValue};
-use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest, ResponseStatusError};
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, EnumString};
 use thiserror::Error;
@@ -326,11 +326,17 @@ pub async fn stream_completion_with_rate_limit_info(
             Ok(_) => Err(AnthropicError::Other(anyhow!(
                 "Unexpected success response while expecting an error: '{body_str}'",
             ))),
-            Err(_) => Err(AnthropicError::Other(anyhow!(
-                "Failed to connect to API: {} {}",
-                response.status(),
-                body_str,
-            ))),
+            Err(_) => Err(AnthropicError::Other(
+                ResponseStatusError::new(
+                    &response,
+                    format!(
+                        "Failed to connect to API: {} {}",
+                        response.status(),
+                        body_str
+                    ),
+                )
+                .into(),
+            )),
         }
     }
 }
@@ -471,6 +477,14 @@ pub enum ToolChoice {
     Tool { name: String },
 }
 
+/// A system prompt, which is split into content blocks when parts of it should be cached.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum System {
+    Text(String),
+    Content(Vec<RequestContent>),
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Request {
     pub model: String,
@@ -481,7 +495,7 @@ pub struct Request {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
+    pub system: Option<System>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Metadata>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
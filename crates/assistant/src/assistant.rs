@@ -2,24 +2,30 @@
 
 pub mod assistant_panel;
 pub mod assistant_settings;
+mod commit_message;
 mod context;
 pub mod context_store;
 mod inline_assistant;
+mod model_comparison;
 mod patch;
 mod prompt_library;
 mod prompts;
+mod request_queue;
+mod share_to_channel;
 mod slash_command;
 pub(crate) mod slash_command_picker;
 pub mod slash_command_settings;
 mod slash_command_working_set;
 mod streaming_diff;
 mod terminal_inline_assistant;
+mod voice_input;
 
 use crate::slash_command::project_command::ProjectSlashCommandFeatureFlag;
 pub use crate::slash_command_working_set::{SlashCommandId, SlashCommandWorkingSet};
 pub use assistant_panel::{AssistantPanel, AssistantPanelEvent};
 use assistant_settings::AssistantSettings;
 use assistant_slash_command::SlashCommandRegistry;
+use assistant_tool::ToolRegistry;
 use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub use context::*;
@@ -40,9 +46,10 @@ use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::search_command::SearchSlashCommandFeatureFlag;
 use slash_command::{
-    auto_command, cargo_workspace_command, default_command, delta_command, diagnostics_command,
-    docs_command, fetch_command, file_command, now_command, project_command, prompt_command,
-    search_command, selection_command, symbols_command, tab_command, terminal_command,
+    auto_command, cargo_workspace_command, custom_command, default_command, delta_command,
+    diagnostics_command, docs_command, fetch_command, file_command, kernel_command, now_command,
+    project_command, prompt_command, search_command, selection_command, symbols_command,
+    tab_command, task_command, terminal_command,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -51,6 +58,7 @@ use util::ResultExt;
 
 use crate::slash_command::streaming_example_command;
 use crate::slash_command_settings::SlashCommandSettings;
+use crate::voice_input::VoiceInputSettings;
 
 actions!(
     assistant,
@@ -70,7 +78,9 @@ actions!(
         NewContext,
         ToggleModelSelector,
         CycleNextInlineAssist,
-        CyclePreviousInlineAssist
+        CyclePreviousInlineAssist,
+        ToggleVoiceInput,
+        ShareToChannel
     ]
 );
 
@@ -198,6 +208,7 @@ pub fn init(
     cx.set_global(Assistant::default());
     AssistantSettings::register(cx);
     SlashCommandSettings::register(cx);
+    VoiceInputSettings::register(cx);
 
     // TODO: remove this when 0.148.0 is released.
     if AssistantSettings::get_global(cx).using_outdated_settings_version {
@@ -241,6 +252,7 @@ pub fn init(
     init_language_model_settings(cx);
     assistant_slash_command::init(cx);
     assistant_tool::init(cx);
+    ToolRegistry::global(cx).register_tool(task_command::RunTaskTool);
     assistant_panel::init(cx);
     context_server::init(cx);
 
@@ -267,6 +279,7 @@ pub fn init(
         client.telemetry().clone(),
         cx,
     );
+    commit_message::init(fs.clone(), prompt_builder.clone(), cx);
     indexed_docs::init(cx);
 
     CommandPaletteFilter::update_global(cx, |filter, _cx| {
@@ -340,6 +353,8 @@ fn register_slash_commands(prompt_builder: Option<Arc<PromptBuilder>>, cx: &mut
     slash_command_registry.register_command(selection_command::SelectionCommand, true);
     slash_command_registry.register_command(default_command::DefaultSlashCommand, false);
     slash_command_registry.register_command(terminal_command::TerminalSlashCommand, true);
+    slash_command_registry.register_command(task_command::TaskSlashCommand, true);
+    slash_command_registry.register_command(kernel_command::KernelSlashCommand, true);
     slash_command_registry.register_command(now_command::NowSlashCommand, false);
     slash_command_registry.register_command(diagnostics_command::DiagnosticsSlashCommand, true);
     slash_command_registry.register_command(fetch_command::FetchSlashCommand, true);
@@ -415,6 +430,8 @@ fn update_slash_commands_from_settings(cx: &mut AppContext) {
         slash_command_registry
             .unregister_command(cargo_workspace_command::CargoWorkspaceSlashCommand);
     }
+
+    custom_command::update_custom_slash_commands(cx);
 }
 
 pub fn humanize_token_count(count: usize) -> String {
@@ -1,24 +1,29 @@
 use crate::slash_command::file_command::codeblock_fence_for_path;
 use crate::slash_command_working_set::SlashCommandWorkingSet;
 use crate::{
-    assistant_settings::{AssistantDockPosition, AssistantSettings},
+    assistant_settings::{AssistantDockPosition, AssistantSettings, ToolPermission},
     humanize_token_count,
+    model_comparison::{ModelComparison, ModelComparisonEvent},
     prompt_library::open_prompt_library,
     prompts::PromptBuilder,
+    request_queue::{render_queued_requests, RequestQueue, RequestSource},
+    share_to_channel::{ShareToChannelModal, SharedConversation},
     slash_command::{
         default_command::DefaultSlashCommand,
         docs_command::{DocsSlashCommand, DocsSlashCommandArgs},
-        file_command, SlashCommandCompletionProvider,
+        file_command, kernel_command, SlashCommandCompletionProvider,
     },
     slash_command_picker,
     terminal_inline_assistant::TerminalInlineAssistant,
+    voice_input::{render_voice_input_button, VoiceInput, VoiceInputEvent},
     Assist, AssistantPatch, AssistantPatchStatus, CacheStatus, ConfirmCommand, Content, Context,
-    ContextEvent, ContextId, ContextStore, ContextStoreEvent, CopyCode, CycleMessageRole,
-    DeployHistory, DeployPromptLibrary, Edit, InlineAssistant, InsertDraggedFiles,
-    InsertIntoEditor, InvokedSlashCommandId, InvokedSlashCommandStatus, Message, MessageId,
-    MessageMetadata, MessageStatus, NewContext, ParsedSlashCommand, PendingSlashCommandStatus,
-    QuoteSelection, RemoteContextMetadata, RequestType, SavedContextMetadata, Split, ToggleFocus,
-    ToggleModelSelector,
+    ContextBranch, ContextEvent, ContextId, ContextStore, ContextStoreEvent, CopyCode,
+    CycleMessageRole, DeployHistory, DeployPromptLibrary, Edit, InlineAssistant,
+    InsertDraggedFiles, InsertIntoEditor, InvokedSlashCommandId, InvokedSlashCommandStatus,
+    Message, MessageId, MessageMetadata, MessageStatus, NewContext, ParsedSlashCommand,
+    PendingSlashCommandStatus, PendingToolUse, QuoteSelection, RemoteContextMetadata, RequestType,
+    SavedContextMetadata, ShareToChannel, Split, ToggleFocus, ToggleModelSelector,
+    ToggleVoiceInput,
 };
 use anyhow::Result;
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
@@ -50,7 +55,9 @@ use indexed_docs::IndexedDocsStore;
 use language::{
     language_settings::SoftWrap, BufferSnapshot, LanguageRegistry, LspAdapterDelegate, ToOffset,
 };
-use language_model::{LanguageModelImage, LanguageModelToolUse};
+use language_model::{
+    LanguageModel, LanguageModelImage, LanguageModelToolUse, LanguageModelToolUseId, TokenUsage,
+};
 use language_model::{
     LanguageModelProvider, LanguageModelProviderId, LanguageModelRegistry, Role,
     ZED_CLOUD_PROVIDER_ID,
@@ -68,7 +75,7 @@ use smol::stream::StreamExt;
 use std::{
     any::TypeId,
     borrow::Cow,
-    cmp,
+    cmp, iter,
     ops::{ControlFlow, Range},
     path::PathBuf,
     sync::Arc,
@@ -93,7 +100,7 @@ use workspace::{
     ToolbarItemLocation, ToolbarItemView, Workspace,
 };
 use workspace::{searchable::SearchableItemHandle, DraggedTab};
-use zed_actions::InlineAssist;
+use zed_actions::{assistant::AskAboutKernelHistory, InlineAssist};
 
 pub fn init(cx: &mut AppContext) {
     workspace::FollowableViewRegistry::register::<ContextEditor>(cx);
@@ -111,7 +118,9 @@ pub fn init(cx: &mut AppContext) {
                 .register_action(ContextEditor::quote_selection)
                 .register_action(ContextEditor::insert_selection)
                 .register_action(ContextEditor::copy_code)
+                .register_action(ContextEditor::share_to_channel)
                 .register_action(ContextEditor::insert_dragged_files)
+                .register_action(ContextEditor::ask_about_kernel_history)
                 .register_action(AssistantPanel::show_configuration)
                 .register_action(AssistantPanel::create_new_context)
                 .register_action(AssistantPanel::restart_context_servers);
@@ -479,6 +488,7 @@ impl AssistantPanel {
                                         .action("New Chat", Box::new(NewContext))
                                         .action("History", Box::new(DeployHistory))
                                         .action("Prompt Library", Box::new(DeployPromptLibrary))
+                                        .action("Share to Channel…", Box::new(ShareToChannel))
                                         .action("Configure", Box::new(ShowConfiguration))
                                         .action(zoom_label, Box::new(ToggleZoom))
                                 }))
@@ -1302,6 +1312,67 @@ impl AssistantPanel {
         })
     }
 
+    /// Opens a new branch of the context that ends at the given message, leaving the context as
+    /// it is.
+    fn fork_context(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.project.read(cx).is_via_collab() {
+            log::error!("only the host can fork contexts");
+            return;
+        }
+
+        let branch = self
+            .context_store
+            .update(cx, |store, cx| store.fork(context, message_id, cx));
+        branch.update(cx, |branch, cx| {
+            // Leave room for the message that takes the conversation in the new direction.
+            if let Some(last_message) = branch.messages(cx).last() {
+                if last_message.role != Role::User {
+                    branch.insert_message_after(
+                        last_message.id,
+                        Role::User,
+                        MessageStatus::Done,
+                        cx,
+                    );
+                }
+            }
+        });
+        self.show_branch(branch, cx);
+    }
+
+    /// Shows the editor of a loaded context, opening one if there's none yet.
+    fn show_branch(&mut self, context: Model<Context>, cx: &mut ViewContext<Self>) {
+        let existing_context = self.pane.read(cx).items().find_map(|item| {
+            item.downcast::<ContextEditor>()
+                .filter(|editor| editor.read(cx).context == context)
+        });
+        if let Some(existing_context) = existing_context {
+            self.show_context(existing_context, cx);
+            return;
+        }
+
+        let lsp_adapter_delegate = make_lsp_adapter_delegate(&self.project, cx)
+            .log_err()
+            .flatten();
+        let assistant_panel = cx.view().downgrade();
+        let editor = cx.new_view(|cx| {
+            ContextEditor::for_context(
+                context,
+                self.fs.clone(),
+                self.workspace.clone(),
+                self.project.clone(),
+                lsp_adapter_delegate,
+                assistant_panel,
+                cx,
+            )
+        });
+        self.show_context(editor, cx);
+    }
+
     fn is_authenticated(&mut self, cx: &mut ViewContext<Self>) -> bool {
         LanguageModelRegistry::read_global(cx)
             .active_provider()
@@ -1531,6 +1602,8 @@ pub struct ContextEditor {
     // the file is opened. In order to keep the worktree alive for the duration of the
     // context editor, we keep a reference here.
     dragged_file_worktrees: Vec<Model<Worktree>>,
+    voice_input: Model<VoiceInput>,
+    model_comparison: Option<(View<ModelComparison>, Subscription)>,
 }
 
 const DEFAULT_TAB_TITLE: &str = "New Chat";
@@ -1567,11 +1640,28 @@ impl ContextEditor {
             editor
         });
 
+        // New contexts use the profile that was selected last.
+        if context.read(cx).profile_name().is_none() {
+            let settings = AssistantSettings::get_global(cx);
+            let profile = settings
+                .default_profile
+                .clone()
+                .filter(|name| settings.profile(name).is_some());
+            if profile.is_some() {
+                context.update(cx, |context, cx| context.set_profile(profile, cx));
+            }
+        }
+
+        let voice_input = cx.new_model(|_| VoiceInput::new());
+        let request_queue = RequestQueue::global(cx);
         let _subscriptions = vec![
             cx.observe(&context, |_, _, cx| cx.notify()),
             cx.subscribe(&context, Self::handle_context_event),
             cx.subscribe(&editor, Self::handle_editor_event),
             cx.subscribe(&editor, Self::handle_editor_search_event),
+            cx.observe(&voice_input, |_, _, cx| cx.notify()),
+            cx.subscribe(&voice_input, Self::handle_voice_input_event),
+            cx.observe(&request_queue, |_, _, cx| cx.notify()),
         ];
 
         let sections = context.read(cx).slash_command_output_sections().to_vec();
@@ -1602,6 +1692,8 @@ impl ContextEditor {
             show_accept_terms: false,
             slash_menu_handle: Default::default(),
             dragged_file_worktrees: Vec::new(),
+            voice_input,
+            model_comparison: None,
         };
         this.update_message_headers(cx);
         this.update_image_blocks(cx);
@@ -1610,6 +1702,65 @@ impl ContextEditor {
         this
     }
 
+    fn toggle_voice_input(&mut self, _: &ToggleVoiceInput, cx: &mut ViewContext<Self>) {
+        self.voice_input
+            .update(cx, |voice_input, cx| voice_input.toggle(cx));
+    }
+
+    fn handle_voice_input_event(
+        &mut self,
+        _: Model<VoiceInput>,
+        event: &VoiceInputEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let VoiceInputEvent::Transcribed(transcript) = event;
+        self.editor.update(cx, |editor, cx| {
+            editor.insert(transcript, cx);
+            editor.focus(cx);
+        });
+    }
+
+    /// Sends the conversation to both the context's model and the given one, to continue it from
+    /// whichever response is better.
+    fn compare_with(&mut self, model: Arc<dyn LanguageModel>, cx: &mut ViewContext<Self>) {
+        let context = self.context.read(cx);
+        let Some(context_model) = context.model(cx) else {
+            return;
+        };
+        let request = context.to_completion_request(RequestType::Chat, cx);
+        let comparison =
+            cx.new_view(|cx| ModelComparison::new([context_model, model], request, cx));
+        let subscription = cx.subscribe(&comparison, Self::handle_model_comparison_event);
+        self.model_comparison = Some((comparison, subscription));
+        cx.notify();
+    }
+
+    fn handle_model_comparison_event(
+        &mut self,
+        _: View<ModelComparison>,
+        event: &ModelComparisonEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let ModelComparisonEvent::Picked(response) = event {
+            let user_message = self.context.update(cx, |context, cx| {
+                context.insert_assistant_response(response, cx)
+            });
+            if let Some(user_message) = user_message {
+                let cursor = user_message
+                    .start
+                    .to_offset(self.context.read(cx).buffer().read(cx));
+                self.editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                        selections.select_ranges([cursor..cursor])
+                    });
+                    editor.focus(cx);
+                });
+            }
+        }
+        self.model_comparison = None;
+        cx.notify();
+    }
+
     fn insert_default_prompt(&mut self, cx: &mut ViewContext<Self>) {
         let command_name = DefaultSlashCommand.name();
         self.editor.update(cx, |editor, cx| {
@@ -1721,6 +1872,15 @@ impl ContextEditor {
         });
     }
 
+    fn fork_from_message(&mut self, message_id: MessageId, cx: &mut ViewContext<Self>) {
+        let context = self.context.clone();
+        self.assistant_panel
+            .update(cx, |assistant_panel, cx| {
+                assistant_panel.fork_context(&context, message_id, cx)
+            })
+            .ok();
+    }
+
     fn cursors(&self, cx: &mut WindowContext) -> Vec<usize> {
         let selections = self
             .editor
@@ -1908,8 +2068,20 @@ impl ContextEditor {
                                 ),
                                 ..Default::default()
                             };
-                            let render_trailer =
-                                move |_row, _unfold, _cx: &mut WindowContext| Empty.into_any();
+                            let render_trailer = {
+                                let context_editor = context_editor.clone();
+                                let context = self.context.downgrade();
+                                let tool_use_id = tool_use.id.clone();
+                                move |row, _unfold, cx: &mut WindowContext| {
+                                    render_tool_use_confirmation(
+                                        row,
+                                        context_editor.clone(),
+                                        context.clone(),
+                                        &tool_use_id,
+                                        cx,
+                                    )
+                                }
+                            };
 
                             let start = buffer
                                 .anchor_in_excerpt(excerpt_id, tool_use.source_range.start)
@@ -2063,12 +2235,15 @@ impl ContextEditor {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
-
-                        self.context.update(cx, |context, cx| {
-                            context.insert_tool_output(tool_use.id.clone(), task, cx);
-                        });
+                    match AssistantSettings::get_global(cx).tool_permission(&tool_use.name) {
+                        ToolPermission::Allow => self.run_tool_use(&tool_use, cx),
+                        ToolPermission::Confirm => {
+                            self.context.update(cx, |context, cx| {
+                                context.request_tool_use_confirmation(&tool_use.id, cx);
+                            });
+                            self.editor.update(cx, |_, cx| cx.notify());
+                        }
+                        ToolPermission::Deny => self.deny_tool_use(&tool_use, cx),
                     }
                 }
             }
@@ -2125,6 +2300,50 @@ impl ContextEditor {
         }
     }
 
+    fn run_tool_use(&mut self, tool_use: &PendingToolUse, cx: &mut ViewContext<Self>) {
+        if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
+            let task = tool.run(tool_use.input.clone(), self.workspace.clone(), cx);
+
+            self.context.update(cx, |context, cx| {
+                context.insert_tool_output(tool_use.id.clone(), task, cx);
+            });
+        }
+    }
+
+    /// Tells the model that the tool was not run, so that it can go on without its output.
+    fn deny_tool_use(&mut self, tool_use: &PendingToolUse, cx: &mut ViewContext<Self>) {
+        let output = Task::ready(Ok(format!(
+            "The user did not allow running the `{}` tool.",
+            tool_use.name
+        )));
+        self.context.update(cx, |context, cx| {
+            context.insert_tool_output(tool_use.id.clone(), output, cx);
+        });
+    }
+
+    fn confirm_tool_use(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        allow: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(tool_use) = self
+            .context
+            .read(cx)
+            .get_tool_use_by_id(tool_use_id)
+            .filter(|tool_use| tool_use.status.needs_confirmation())
+            .cloned()
+        else {
+            return;
+        };
+        if allow {
+            self.run_tool_use(&tool_use, cx);
+        } else {
+            self.deny_tool_use(&tool_use, cx);
+        }
+        self.editor.update(cx, |_, cx| cx.notify());
+    }
+
     fn update_invoked_slash_command(
         &mut self,
         command_id: InvokedSlashCommandId,
@@ -2642,6 +2861,7 @@ impl ContextEditor {
     }
 
     fn update_message_headers(&mut self, cx: &mut ViewContext<Self>) {
+        let context_editor = cx.view().downgrade();
         self.editor.update(cx, |editor, cx| {
             let buffer = editor.buffer().read(cx).snapshot(cx);
 
@@ -2656,6 +2876,7 @@ impl ContextEditor {
             let render_block = |message: MessageMetadata| -> RenderBlock {
                 Arc::new({
                     let context = self.context.clone();
+                    let context_editor = context_editor.clone();
 
                     move |cx| {
                         let message_id = MessageId(message.timestamp);
@@ -2831,6 +3052,32 @@ impl ContextEditor {
                                 ),
                                 _ => None,
                             })
+                            .when(message.status == MessageStatus::Done, |header| {
+                                header.child(
+                                    IconButton::new("fork-from-message", IconName::GitBranch)
+                                        .icon_size(IconSize::XSmall)
+                                        .icon_color(Color::Muted)
+                                        .tooltip(|cx| {
+                                            Tooltip::with_meta(
+                                                "Fork from Here",
+                                                None,
+                                                "Continue this conversation up to this message in a new branch",
+                                                cx,
+                                            )
+                                        })
+                                        .on_click({
+                                            let context_editor = context_editor.clone();
+                                            move |_, cx| {
+                                                context_editor
+                                                    .update(cx, |context_editor, cx| {
+                                                        context_editor
+                                                            .fork_from_message(message_id, cx)
+                                                    })
+                                                    .ok();
+                                            }
+                                        }),
+                                )
+                            })
                             .into_any_element()
                     }
                 })
@@ -2981,6 +3228,53 @@ impl ContextEditor {
         );
     }
 
+    fn share_to_channel(
+        workspace: &mut Workspace,
+        _: &ShareToChannel,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+        let Some(context_editor) = panel.read(cx).active_context_editor(cx) else {
+            return;
+        };
+        let author = workspace
+            .user_store()
+            .read(cx)
+            .current_user()
+            .map(|user| user.github_login.clone());
+        let context_editor = context_editor.read(cx);
+        let selection = context_editor
+            .editor
+            .read(cx)
+            .selections
+            .newest::<usize>(cx);
+        let conversation = SharedConversation::new(
+            context_editor.context.read(cx),
+            selection.range(),
+            author.as_deref(),
+            cx,
+        );
+        let Some(conversation) = conversation else {
+            struct NothingToShareToast;
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NothingToShareToast>(),
+                    "There's nothing to share in this conversation.",
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, |cx| {
+            ShareToChannelModal::new(conversation, workspace_handle, cx)
+        });
+    }
+
     fn insert_dragged_files(
         workspace: &mut Workspace,
         action: &InsertDraggedFiles,
@@ -3055,6 +3349,41 @@ impl ContextEditor {
         .detach();
     }
 
+    /// Brings the recent inputs and outputs of the workspace's REPL session
+    /// into the active context, through the `/kernel` slash command.
+    fn ask_about_kernel_history(
+        workspace: &mut Workspace,
+        _: &AskAboutKernelHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+        if !panel.focus_handle(cx).contains_focused(cx) {
+            workspace.toggle_panel_focus::<AssistantPanel>(cx);
+        }
+
+        panel.update(cx, |_, cx| {
+            // Wait to create a new context until the workspace is no longer
+            // being updated.
+            cx.defer(move |panel, cx| {
+                if let Some(context_editor) = panel
+                    .active_context_editor(cx)
+                    .or_else(|| panel.new_context(cx))
+                {
+                    context_editor.update(cx, |context_editor, cx| {
+                        let cmd_name = kernel_command::KernelSlashCommand.name();
+                        context_editor.editor.update(cx, |editor, cx| {
+                            editor.insert("\n", cx);
+                            editor.insert(&format!("/{cmd_name}"), cx);
+                        });
+                        context_editor.confirm_command(&ConfirmCommand, cx);
+                    });
+                }
+            });
+        });
+    }
+
     fn quote_selection(
         workspace: &mut Workspace,
         _: &QuoteSelection,
@@ -3085,49 +3414,96 @@ impl ContextEditor {
                     .or_else(|| panel.new_context(cx))
                 {
                     context.update(cx, |context, cx| {
-                        context.editor.update(cx, |editor, cx| {
-                            editor.insert("\n", cx);
-                            for (text, crease_title) in creases {
-                                let point = editor.selections.newest::<Point>(cx).head();
-                                let start_row = MultiBufferRow(point.row);
-
-                                editor.insert(&text, cx);
-
-                                let snapshot = editor.buffer().read(cx).snapshot(cx);
-                                let anchor_before = snapshot.anchor_after(point);
-                                let anchor_after = editor
-                                    .selections
-                                    .newest_anchor()
-                                    .head()
-                                    .bias_left(&snapshot);
+                        context.insert_quoted_selections(creases, cx)
+                    });
+                };
+            });
+        });
+    }
 
-                                editor.insert("\n", cx);
+    /// Starts a new context that quotes the selections of the active editor followed by the given
+    /// prompt, leaving it to the user to edit the prompt and send it.
+    pub(crate) fn quote_selection_with_prompt(
+        workspace: &mut Workspace,
+        prompt: String,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+        let Some(creases) = selections_creases(workspace, cx) else {
+            return;
+        };
+        if !panel.focus_handle(cx).contains_focused(cx) {
+            workspace.toggle_panel_focus::<AssistantPanel>(cx);
+        }
 
-                                let fold_placeholder = quote_selection_fold_placeholder(
-                                    crease_title,
-                                    cx.view().downgrade(),
-                                );
-                                let crease = Crease::inline(
-                                    anchor_before..anchor_after,
-                                    fold_placeholder,
-                                    render_quote_selection_output_toggle,
-                                    |_, _, _| Empty.into_any(),
-                                );
-                                editor.insert_creases(vec![crease], cx);
-                                editor.fold_at(
-                                    &FoldAt {
-                                        buffer_row: start_row,
-                                    },
-                                    cx,
-                                );
-                            }
-                        })
+        panel.update(cx, |_, cx| {
+            // Wait to create a new context until the workspace is no longer
+            // being updated.
+            cx.defer(move |panel, cx| {
+                if let Some(context) = panel.new_context(cx) {
+                    context.update(cx, |context, cx| {
+                        context.insert_quoted_selections_with_prompt(creases, &prompt, cx)
                     });
-                };
+                }
             });
         });
     }
 
+    fn insert_quoted_selections_with_prompt(
+        &mut self,
+        creases: Vec<(String, String)>,
+        prompt: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.insert_quoted_selections(creases, cx);
+        self.editor
+            .update(cx, |editor, cx| editor.insert(prompt, cx));
+    }
+
+    fn insert_quoted_selections(
+        &mut self,
+        creases: Vec<(String, String)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.insert("\n", cx);
+            for (text, crease_title) in creases {
+                let point = editor.selections.newest::<Point>(cx).head();
+                let start_row = MultiBufferRow(point.row);
+
+                editor.insert(&text, cx);
+
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let anchor_before = snapshot.anchor_after(point);
+                let anchor_after = editor
+                    .selections
+                    .newest_anchor()
+                    .head()
+                    .bias_left(&snapshot);
+
+                editor.insert("\n", cx);
+
+                let fold_placeholder =
+                    quote_selection_fold_placeholder(crease_title, cx.view().downgrade());
+                let crease = Crease::inline(
+                    anchor_before..anchor_after,
+                    fold_placeholder,
+                    render_quote_selection_output_toggle,
+                    |_, _, _| Empty.into_any(),
+                );
+                editor.insert_creases(vec![crease], cx);
+                editor.fold_at(
+                    &FoldAt {
+                        buffer_row: start_row,
+                    },
+                    cx,
+                );
+            }
+        })
+    }
+
     fn copy(&mut self, _: &editor::actions::Copy, cx: &mut ViewContext<Self>) {
         if self.editor.read(cx).selections.count() == 1 {
             let (copied_text, metadata, _) = self.get_clipboard_contents(cx);
@@ -3653,6 +4029,13 @@ impl ContextEditor {
                 ButtonStyle::Tinted(TintColor::Negative),
                 Some(Tooltip::text("Token limit reached", cx)),
             ),
+            Some(TokenState::OverBudget { .. }) => (
+                ButtonStyle::Tinted(TintColor::Warning),
+                Some(Tooltip::text(
+                    "Token budget exceeded, the oldest messages will be left out",
+                    cx,
+                )),
+            ),
             Some(TokenState::HasMoreTokens {
                 over_warn_threshold,
                 ..
@@ -3710,6 +4093,13 @@ impl ContextEditor {
                 ButtonStyle::Tinted(TintColor::Negative),
                 Some(Tooltip::text("Token limit reached", cx)),
             ),
+            Some(TokenState::OverBudget { .. }) => (
+                ButtonStyle::Tinted(TintColor::Warning),
+                Some(Tooltip::text(
+                    "Token budget exceeded, the oldest messages will be left out",
+                    cx,
+                )),
+            ),
             Some(TokenState::HasMoreTokens {
                 over_warn_threshold,
                 ..
@@ -3753,6 +4143,51 @@ impl ContextEditor {
             })
     }
 
+    fn render_compare_menu(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let context_model = self.context.read(cx).model(cx)?;
+        let models = LanguageModelRegistry::read_global(cx)
+            .available_models(cx)
+            .filter(|model| {
+                model.provider_id() != context_model.provider_id()
+                    || model.id() != context_model.id()
+            })
+            .collect::<Vec<_>>();
+        if models.is_empty() {
+            return None;
+        }
+        let context_editor = cx.view().downgrade();
+
+        Some(
+            PopoverMenu::new("compare-menu")
+                .trigger(
+                    IconButton::new("compare-models", IconName::Split)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .tooltip(|cx| Tooltip::text("Compare With Another Model", cx)),
+                )
+                .menu(move |cx| {
+                    let models = models.clone();
+                    let context_editor = context_editor.clone();
+                    Some(ContextMenu::build(cx, move |mut menu, _| {
+                        menu = menu.header("Compare With");
+                        for model in models {
+                            let context_editor = context_editor.clone();
+                            menu = menu.entry(model.name().0, None, move |cx| {
+                                context_editor
+                                    .update(cx, |context_editor, cx| {
+                                        context_editor.compare_with(model.clone(), cx)
+                                    })
+                                    .ok();
+                            });
+                        }
+                        menu
+                    }))
+                })
+                .attach(gpui::Corner::TopLeft)
+                .anchor(gpui::Corner::BottomLeft),
+        )
+    }
+
     fn render_inject_context_menu(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         slash_command_picker::SlashCommandSelector::new(
             self.slash_commands.clone(),
@@ -4148,6 +4583,7 @@ impl Render for ContextEditor {
             .on_action(cx.listener(ContextEditor::edit))
             .on_action(cx.listener(ContextEditor::assist))
             .on_action(cx.listener(ContextEditor::split))
+            .on_action(cx.listener(ContextEditor::toggle_voice_input))
             .size_full()
             .children(self.render_notice(cx))
             .child(
@@ -4172,6 +4608,11 @@ impl Render for ContextEditor {
                 )
             })
             .children(self.render_last_error(cx))
+            .children(
+                self.model_comparison
+                    .as_ref()
+                    .map(|(comparison, _)| comparison.clone()),
+            )
             .child(
                 h_flex().w_full().relative().child(
                     h_flex()
@@ -4180,7 +4621,14 @@ impl Render for ContextEditor {
                         .border_t_1()
                         .border_color(cx.theme().colors().border_variant)
                         .bg(cx.theme().colors().editor_background)
-                        .child(h_flex().gap_1().child(self.render_inject_context_menu(cx)))
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(self.render_inject_context_menu(cx))
+                                .child(render_voice_input_button(&self.voice_input, cx))
+                                .children(self.render_compare_menu(cx))
+                                .children(render_queued_requests(RequestSource::Panel, cx)),
+                        )
                         .child(
                             h_flex()
                                 .w_full()
@@ -4457,6 +4905,7 @@ impl FollowableItem for ContextEditor {
 
 pub struct ContextEditorToolbarItem {
     active_context_editor: Option<WeakView<ContextEditor>>,
+    fs: Arc<dyn Fs>,
     model_summary_editor: View<Editor>,
     language_model_selector: View<LanguageModelSelector>,
     language_model_selector_menu_handle: PopoverMenuHandle<LanguageModelSelector>,
@@ -4471,6 +4920,7 @@ impl ContextEditorToolbarItem {
     ) -> Self {
         Self {
             active_context_editor: None,
+            fs: workspace.app_state().fs.clone(),
             model_summary_editor,
             language_model_selector: cx.new_view(|cx| {
                 let fs = workspace.app_state().fs.clone();
@@ -4489,6 +4939,182 @@ impl ContextEditorToolbarItem {
         }
     }
 
+    fn render_profile_menu(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let profile_names = AssistantSettings::get_global(cx)
+            .profiles
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        if profile_names.is_empty() {
+            return None;
+        }
+        let context = self
+            .active_context_editor
+            .as_ref()?
+            .upgrade()?
+            .read(cx)
+            .context
+            .clone();
+        let active_profile = context.read(cx).profile_name().map(ToString::to_string);
+        let fs = self.fs.clone();
+
+        Some(
+            PopoverMenu::new("profile-menu")
+                .trigger(
+                    ButtonLike::new("active-profile")
+                        .style(ButtonStyle::Subtle)
+                        .child(
+                            h_flex()
+                                .gap_0p5()
+                                .child(
+                                    Label::new(
+                                        active_profile.clone().unwrap_or("No Profile".into()),
+                                    )
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .child(
+                                    Icon::new(IconName::ChevronDown)
+                                        .color(Color::Muted)
+                                        .size(IconSize::XSmall),
+                                ),
+                        )
+                        .tooltip(|cx| Tooltip::text("Change Profile", cx)),
+                )
+                .menu(move |cx| {
+                    let context = context.clone();
+                    let fs = fs.clone();
+                    let active_profile = active_profile.clone();
+                    let profile_names = profile_names.clone();
+                    Some(ContextMenu::build(cx, move |mut menu, _| {
+                        for profile_name in
+                            iter::once(None).chain(profile_names.into_iter().map(Some))
+                        {
+                            let label = profile_name.clone().unwrap_or("No Profile".into());
+                            let toggled = profile_name == active_profile;
+                            let context = context.clone();
+                            let fs = fs.clone();
+                            menu = menu.toggleable_entry(
+                                label,
+                                toggled,
+                                IconPosition::Start,
+                                None,
+                                move |cx| {
+                                    let profile_name = profile_name.clone();
+                                    context.update(cx, |context, cx| {
+                                        context.set_profile(profile_name.clone(), cx)
+                                    });
+                                    // New contexts use the profile that was selected last.
+                                    update_settings_file::<AssistantSettings>(
+                                        fs.clone(),
+                                        cx,
+                                        move |settings, _| settings.set_profile(profile_name),
+                                    );
+                                },
+                            );
+                        }
+                        menu
+                    }))
+                }),
+        )
+    }
+
+    fn render_branch_menu(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let context_editor = self.active_context_editor.as_ref()?.upgrade()?;
+        let context_editor = context_editor.read(cx);
+        let assistant_panel = context_editor.assistant_panel.clone();
+        let context = context_editor.context.clone();
+        let branches = assistant_panel
+            .upgrade()?
+            .read(cx)
+            .context_store
+            .read(cx)
+            .branches(&context, cx)
+            .into_iter()
+            .map(|(depth, branch)| {
+                let summary = match &branch {
+                    ContextBranch::Loaded(branch) => branch
+                        .read(cx)
+                        .summary()
+                        .map(|summary| summary.text.clone())
+                        .unwrap_or_else(|| DEFAULT_TAB_TITLE.into()),
+                    ContextBranch::Saved(metadata) => metadata.title.clone(),
+                };
+                (format!("{}{summary}", "  ".repeat(depth)), branch)
+            })
+            .collect::<Vec<_>>();
+        if branches.len() < 2 {
+            return None;
+        }
+        let branch_ix = branches
+            .iter()
+            .position(
+                |(_, branch)| matches!(branch, ContextBranch::Loaded(branch) if *branch == context),
+            )
+            .unwrap_or_default();
+        let branch_count = branches.len();
+
+        Some(
+            PopoverMenu::new("branch-menu")
+                .trigger(
+                    ButtonLike::new("active-branch")
+                        .style(ButtonStyle::Subtle)
+                        .child(
+                            h_flex()
+                                .gap_0p5()
+                                .child(
+                                    Icon::new(IconName::GitBranch)
+                                        .color(Color::Muted)
+                                        .size(IconSize::XSmall),
+                                )
+                                .child(
+                                    Label::new(format!("{}/{branch_count}", branch_ix + 1))
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    Icon::new(IconName::ChevronDown)
+                                        .color(Color::Muted)
+                                        .size(IconSize::XSmall),
+                                ),
+                        )
+                        .tooltip(|cx| Tooltip::text("Switch Branch", cx)),
+                )
+                .menu(move |cx| {
+                    let context = context.clone();
+                    let assistant_panel = assistant_panel.clone();
+                    let branches = branches.clone();
+                    Some(ContextMenu::build(cx, move |mut menu, _| {
+                        for (label, branch) in branches {
+                            let toggled =
+                                matches!(&branch, ContextBranch::Loaded(branch) if *branch == context);
+                            let assistant_panel = assistant_panel.clone();
+                            menu = menu.toggleable_entry(
+                                label,
+                                toggled,
+                                IconPosition::Start,
+                                None,
+                                move |cx| {
+                                    let branch = branch.clone();
+                                    assistant_panel
+                                        .update(cx, |assistant_panel, cx| match branch {
+                                            ContextBranch::Loaded(branch) => {
+                                                assistant_panel.show_branch(branch, cx)
+                                            }
+                                            ContextBranch::Saved(metadata) => assistant_panel
+                                                .open_saved_context(metadata.path, cx)
+                                                .detach_and_log_err(cx),
+                                        })
+                                        .ok();
+                                },
+                            );
+                        }
+                        menu
+                    }))
+                }),
+        )
+    }
+
     fn render_remaining_tokens(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
         let context = &self
             .active_context_editor
@@ -4501,6 +5127,10 @@ impl ContextEditorToolbarItem {
                 max_token_count,
                 token_count,
             } => (Color::Error, token_count, max_token_count),
+            TokenState::OverBudget {
+                token_budget,
+                token_count,
+            } => (Color::Warning, token_count, token_budget),
             TokenState::HasMoreTokens {
                 max_token_count,
                 token_count,
@@ -4514,6 +5144,7 @@ impl ContextEditorToolbarItem {
                 (color, token_count, max_token_count)
             }
         };
+        let token_usage = context.read(cx).token_usage();
         Some(
             h_flex()
                 .gap_0p5()
@@ -4527,7 +5158,40 @@ impl ContextEditorToolbarItem {
                     Label::new(humanize_token_count(max_token_count))
                         .size(LabelSize::Small)
                         .color(Color::Muted),
-                ),
+                )
+                .children(token_usage.and_then(Self::render_prompt_cache_usage)),
+        )
+    }
+
+    fn render_prompt_cache_usage(usage: TokenUsage) -> Option<impl IntoElement> {
+        let cached_tokens = usage.cache_read_input_tokens as usize;
+        let cache_writes = usage.cache_creation_input_tokens as usize;
+        if cached_tokens == 0 && cache_writes == 0 {
+            return None;
+        }
+
+        let input_tokens = usage.total_input_tokens() as usize;
+        let cached_percentage = cached_tokens * 100 / input_tokens.max(1);
+        let tooltip = format!(
+            "The last response read {} of its {} input tokens from the prompt cache, and wrote {} to it. Cached tokens are cheaper and faster to process.",
+            humanize_token_count(cached_tokens),
+            humanize_token_count(input_tokens),
+            humanize_token_count(cache_writes),
+        );
+        Some(
+            div()
+                .id("prompt-cache-usage")
+                .pl_1()
+                .child(
+                    Label::new(format!("{cached_percentage}% cached"))
+                        .size(LabelSize::Small)
+                        .color(if cached_tokens > 0 {
+                            Color::Success
+                        } else {
+                            Color::Muted
+                        }),
+                )
+                .tooltip(move |cx| Tooltip::with_meta("Prompt Cache", None, tooltip.clone(), cx)),
         )
     }
 }
@@ -4556,8 +5220,75 @@ impl Render for ContextEditorToolbarItem {
                         })),
                 ),
             );
-        let active_provider = LanguageModelRegistry::read_global(cx).active_provider();
-        let active_model = LanguageModelRegistry::read_global(cx).active_model();
+        // While the context's profile sets the model, it can only be changed by switching profiles.
+        let profile_model = self
+            .active_context_editor
+            .as_ref()
+            .and_then(|editor| editor.upgrade())
+            .and_then(|editor| {
+                let context = editor.read(cx).context.read(cx);
+                Some((
+                    context.profile_name()?.to_string(),
+                    context.profile_model(cx)?,
+                ))
+            });
+        let active_model = profile_model
+            .as_ref()
+            .map(|(_, model)| model.clone())
+            .or_else(|| LanguageModelRegistry::read_global(cx).active_model());
+        let active_provider = active_model.as_ref().and_then(|model| {
+            LanguageModelRegistry::read_global(cx).provider(&model.provider_id())
+        });
+        let active_model_label = div()
+            .overflow_x_hidden()
+            .flex_grow()
+            .whitespace_nowrap()
+            .child(match (active_provider, active_model) {
+                (Some(provider), Some(model)) => h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(model.icon().unwrap_or_else(|| provider.icon()))
+                            .color(Color::Muted)
+                            .size(IconSize::XSmall),
+                    )
+                    .child(
+                        Label::new(model.name().0)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element(),
+                _ => Label::new("No model selected")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .into_any_element(),
+            });
+        let model_selector = if let Some((profile_name, _)) = profile_model {
+            ButtonLike::new("active-model")
+                .style(ButtonStyle::Subtle)
+                .child(active_model_label)
+                .tooltip(move |cx| {
+                    Tooltip::text(format!("Set by the \"{profile_name}\" profile"), cx)
+                })
+                .into_any_element()
+        } else {
+            LanguageModelSelectorPopoverMenu::new(
+                self.language_model_selector.clone(),
+                ButtonLike::new("active-model")
+                    .style(ButtonStyle::Subtle)
+                    .child(
+                        h_flex().w_full().gap_0p5().child(active_model_label).child(
+                            Icon::new(IconName::ChevronDown)
+                                .color(Color::Muted)
+                                .size(IconSize::XSmall),
+                        ),
+                    )
+                    .tooltip(move |cx| {
+                        Tooltip::for_action("Change Model", &ToggleModelSelector, cx)
+                    }),
+            )
+            .with_handle(self.language_model_selector_menu_handle.clone())
+            .into_any_element()
+        };
         let right_side = h_flex()
             .gap_2()
             // TODO display this in a nicer way, once we have a design for it.
@@ -4573,56 +5304,9 @@ impl Render for ContextEditorToolbarItem {
             //     scan_items_remaining
             //         .map(|remaining_items| format!("Files to scan: {}", remaining_items))
             // })
-            .child(
-                LanguageModelSelectorPopoverMenu::new(
-                    self.language_model_selector.clone(),
-                    ButtonLike::new("active-model")
-                        .style(ButtonStyle::Subtle)
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .gap_0p5()
-                                .child(
-                                    div()
-                                        .overflow_x_hidden()
-                                        .flex_grow()
-                                        .whitespace_nowrap()
-                                        .child(match (active_provider, active_model) {
-                                            (Some(provider), Some(model)) => h_flex()
-                                                .gap_1()
-                                                .child(
-                                                    Icon::new(
-                                                        model
-                                                            .icon()
-                                                            .unwrap_or_else(|| provider.icon()),
-                                                    )
-                                                    .color(Color::Muted)
-                                                    .size(IconSize::XSmall),
-                                                )
-                                                .child(
-                                                    Label::new(model.name().0)
-                                                        .size(LabelSize::Small)
-                                                        .color(Color::Muted),
-                                                )
-                                                .into_any_element(),
-                                            _ => Label::new("No model selected")
-                                                .size(LabelSize::Small)
-                                                .color(Color::Muted)
-                                                .into_any_element(),
-                                        }),
-                                )
-                                .child(
-                                    Icon::new(IconName::ChevronDown)
-                                        .color(Color::Muted)
-                                        .size(IconSize::XSmall),
-                                ),
-                        )
-                        .tooltip(move |cx| {
-                            Tooltip::for_action("Change Model", &ToggleModelSelector, cx)
-                        }),
-                )
-                .with_handle(self.language_model_selector_menu_handle.clone()),
-            )
+            .children(self.render_branch_menu(cx))
+            .children(self.render_profile_menu(cx))
+            .child(model_selector)
             .children(self.render_remaining_tokens(cx));
 
         h_flex()
@@ -5179,7 +5863,65 @@ fn invoked_slash_command_fold_placeholder(
     }
 }
 
+fn render_tool_use_confirmation(
+    row: MultiBufferRow,
+    context_editor: WeakView<ContextEditor>,
+    context: WeakModel<Context>,
+    tool_use_id: &LanguageModelToolUseId,
+    cx: &mut WindowContext,
+) -> AnyElement {
+    let needs_confirmation = context.upgrade().map_or(false, |context| {
+        context
+            .read(cx)
+            .get_tool_use_by_id(tool_use_id)
+            .map_or(false, |tool_use| tool_use.status.needs_confirmation())
+    });
+    if !needs_confirmation {
+        return Empty.into_any();
+    }
+
+    h_flex()
+        .gap_1()
+        .child(
+            Button::new(("run-tool", row.0), "Run")
+                .size(ButtonSize::Compact)
+                .style(ButtonStyle::Filled)
+                .on_click({
+                    let context_editor = context_editor.clone();
+                    let tool_use_id = tool_use_id.clone();
+                    move |_, cx| {
+                        context_editor
+                            .update(cx, |context_editor, cx| {
+                                context_editor.confirm_tool_use(&tool_use_id, true, cx)
+                            })
+                            .ok();
+                    }
+                }),
+        )
+        .child(
+            Button::new(("deny-tool", row.0), "Deny")
+                .size(ButtonSize::Compact)
+                .on_click({
+                    let tool_use_id = tool_use_id.clone();
+                    move |_, cx| {
+                        context_editor
+                            .update(cx, |context_editor, cx| {
+                                context_editor.confirm_tool_use(&tool_use_id, false, cx)
+                            })
+                            .ok();
+                    }
+                }),
+        )
+        .into_any_element()
+}
+
 enum TokenState {
+    /// The context exceeds the token budget from the settings, so its oldest messages will be
+    /// left out of the request.
+    OverBudget {
+        token_budget: usize,
+        token_count: usize,
+    },
     NoTokensLeft {
         max_token_count: usize,
         token_count: usize,
@@ -5194,9 +5936,25 @@ enum TokenState {
 fn token_state(context: &Model<Context>, cx: &AppContext) -> Option<TokenState> {
     const WARNING_TOKEN_THRESHOLD: f32 = 0.8;
 
-    let model = LanguageModelRegistry::read_global(cx).active_model()?;
+    let model = context.read(cx).model(cx)?;
     let token_count = context.read(cx).token_count()?;
     let max_token_count = model.max_token_count();
+    if let Some(token_budget) = AssistantSettings::get_global(cx).context_token_budget {
+        if token_budget < max_token_count {
+            if token_count > token_budget {
+                return Some(TokenState::OverBudget {
+                    token_budget,
+                    token_count,
+                });
+            }
+            return Some(TokenState::HasMoreTokens {
+                max_token_count: token_budget,
+                token_count,
+                over_warn_threshold: token_count as f32 / token_budget as f32
+                    >= WARNING_TOKEN_THRESHOLD,
+            });
+        }
+    }
 
     let remaining_tokens = max_token_count as isize - token_count as isize;
     let token_state = if remaining_tokens <= 0 {
@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ::open_ai::Model as OpenAiModel;
 use anthropic::Model as AnthropicModel;
+use collections::HashMap;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
 use gpui::{AppContext, Pixels};
@@ -25,6 +27,52 @@ pub enum AssistantDockPosition {
     Bottom,
 }
 
+/// A named preset of the model, temperature and system prompt that a context is sent with.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AssistantProfile {
+    /// The model to use, instead of the one selected in the assistant panel.
+    pub model: Option<LanguageModelSelection>,
+    /// The temperature to sample the model's responses with.
+    pub temperature: Option<f32>,
+    /// The system prompt to send before the messages of the context.
+    pub system_prompt: Option<String>,
+}
+
+/// A prompt-based action offered among the code actions of the selected code.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AssistantCodeAction {
+    /// The title of the action in the code actions menu.
+    pub title: String,
+    /// The prompt to send along with the selected code.
+    pub prompt: String,
+    /// Where the action takes the selected code.
+    #[serde(default)]
+    pub target: AssistantCodeActionTarget,
+}
+
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistantCodeActionTarget {
+    /// Transform the selected code with the inline assistant.
+    #[default]
+    InlineAssist,
+    /// Start a conversation about the selected code in the assistant panel.
+    Panel,
+}
+
+/// Whether the assistant may run a tool that the model asks to use.
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking.
+    Allow,
+    /// Ask before running the tool.
+    #[default]
+    Confirm,
+    /// Never run the tool.
+    Deny,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(tag = "name", rename_all = "snake_case")]
 pub enum AssistantProviderContentV1 {
@@ -59,12 +107,28 @@ pub struct AssistantSettings {
     pub inline_alternatives: Vec<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
     pub enable_experimental_live_diffs: bool,
+    pub context_token_budget: Option<usize>,
+    pub tool_permissions: HashMap<String, ToolPermission>,
+    pub profiles: BTreeMap<String, AssistantProfile>,
+    pub default_profile: Option<String>,
+    pub code_actions: Vec<AssistantCodeAction>,
 }
 
 impl AssistantSettings {
     pub fn are_live_diffs_enabled(&self, cx: &AppContext) -> bool {
         cx.is_staff() || self.enable_experimental_live_diffs
     }
+
+    pub fn profile(&self, name: &str) -> Option<&AssistantProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn tool_permission(&self, tool_name: &str) -> ToolPermission {
+        self.tool_permissions
+            .get(tool_name)
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 /// Assistant panel settings
@@ -179,6 +243,7 @@ impl AssistantSettingsContent {
                                             OpenAiSettingsContentV1 {
                                                 api_url,
                                                 available_models,
+                                                include_usage: None,
                                             },
                                         ),
                                     ));
@@ -236,6 +301,11 @@ impl AssistantSettingsContent {
                         }),
                     inline_alternatives: None,
                     enable_experimental_live_diffs: None,
+                    context_token_budget: None,
+                    tool_permissions: None,
+                    profiles: None,
+                    default_profile: None,
+                    code_actions: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -256,6 +326,11 @@ impl AssistantSettingsContent {
                 }),
                 inline_alternatives: None,
                 enable_experimental_live_diffs: None,
+                context_token_budget: None,
+                tool_permissions: None,
+                profiles: None,
+                default_profile: None,
+                code_actions: None,
             },
         }
     }
@@ -276,6 +351,20 @@ impl AssistantSettingsContent {
         }
     }
 
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        match self {
+            AssistantSettingsContent::Versioned(VersionedAssistantSettingsContent::V2(
+                settings,
+            )) => {
+                settings.default_profile = profile;
+            }
+            AssistantSettingsContent::Versioned(VersionedAssistantSettingsContent::V1(_))
+            | AssistantSettingsContent::Legacy(_) => {
+                log::warn!("attempted to set the default profile on outdated settings");
+            }
+        }
+    }
+
     pub fn set_model(&mut self, language_model: Arc<dyn LanguageModel>) {
         let model = language_model.id().0.to_string();
         let provider = language_model.provider_id().0.to_string();
@@ -360,6 +449,11 @@ impl Default for VersionedAssistantSettingsContent {
             default_model: None,
             inline_alternatives: None,
             enable_experimental_live_diffs: None,
+            context_token_budget: None,
+            tool_permissions: None,
+            profiles: None,
+            default_profile: None,
+            code_actions: None,
         })
     }
 }
@@ -394,6 +488,29 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: false
     enable_experimental_live_diffs: Option<bool>,
+    /// The maximum number of tokens to send with each request in the assistant panel.
+    /// When the context exceeds it, the oldest messages are left out of the request.
+    ///
+    /// Default: none (limited by the model's context window only)
+    context_token_budget: Option<usize>,
+    /// Whether to run, confirm or deny each tool that the model asks to use, by the name of the
+    /// tool.
+    ///
+    /// Default: {} (every tool is confirmed before it runs)
+    tool_permissions: Option<HashMap<String, ToolPermission>>,
+    /// Named presets of the model, temperature and system prompt to send contexts with, which can
+    /// be switched between in the assistant panel.
+    ///
+    /// Default: {}
+    profiles: Option<BTreeMap<String, AssistantProfile>>,
+    /// The profile that new contexts use, which is the one selected last in the assistant panel.
+    ///
+    /// Default: none
+    default_profile: Option<String>,
+    /// Prompt-based actions offered among the code actions of the selected code, which either
+    /// transform it with the inline assistant or start a conversation about it in the assistant
+    /// panel.
+    code_actions: Option<Vec<AssistantCodeAction>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -520,6 +637,17 @@ impl Settings for AssistantSettings {
                 &mut settings.enable_experimental_live_diffs,
                 value.enable_experimental_live_diffs,
             );
+            merge(
+                &mut settings.context_token_budget,
+                value.context_token_budget.map(Some),
+            );
+            merge(&mut settings.tool_permissions, value.tool_permissions);
+            merge(&mut settings.profiles, value.profiles);
+            merge(&mut settings.code_actions, value.code_actions);
+            merge(
+                &mut settings.default_profile,
+                value.default_profile.map(Some),
+            );
         }
 
         Ok(settings)
@@ -579,6 +707,11 @@ mod tests {
                             default_width: None,
                             default_height: None,
                             enable_experimental_live_diffs: None,
+                            context_token_budget: None,
+                            tool_permissions: None,
+                            profiles: None,
+                            default_profile: None,
+                            code_actions: None,
                         }),
                     )
                 },
@@ -600,4 +733,42 @@ mod tests {
 
         assert!(!assistant_settings.assistant.is_version_outdated());
     }
+
+    #[gpui::test]
+    fn test_tool_permissions(cx: &mut AppContext) {
+        let test_settings = settings::SettingsStore::test(cx);
+        cx.set_global(test_settings);
+        AssistantSettings::register(cx);
+
+        let settings = AssistantSettings::get_global(cx);
+        assert_eq!(
+            settings.tool_permission("read_file"),
+            ToolPermission::Confirm
+        );
+        assert_eq!(
+            settings.tool_permission("run_task"),
+            ToolPermission::Confirm
+        );
+
+        cx.update_global::<settings::SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "2",
+                            "tool_permissions": {
+                                "read_file": "allow",
+                                "run_task": "deny"
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        let settings = AssistantSettings::get_global(cx);
+        assert_eq!(settings.tool_permission("read_file"), ToolPermission::Allow);
+        assert_eq!(settings.tool_permission("run_task"), ToolPermission::Deny);
+        assert_eq!(settings.tool_permission("search"), ToolPermission::Confirm);
+    }
 }
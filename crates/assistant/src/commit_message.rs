@@ -0,0 +1,443 @@
+use crate::prompts::PromptBuilder;
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use fs::Fs;
+use futures::StreamExt;
+use git::repository::GitRepository;
+use git_ui::{git_panel::GitPanel, GenerateCommitMessage};
+use gpui::{AppContext, AsyncAppContext, EntityId, Global, Model, Task, UpdateGlobal, ViewContext};
+use language_model::{
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::{path::Path, sync::Arc};
+use util::post_inc;
+use workspace::{notifications::NotificationId, Toast, Workspace};
+
+/// Which changes a generated commit message describes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitMessageScope {
+    /// Only the changes that are staged, for committing with "Commit".
+    #[default]
+    Staged,
+    /// All uncommitted changes to tracked files, for committing with "Commit All".
+    AllChanges,
+}
+
+/// Settings for generating commit messages in the git panel.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct CommitMessageSettings {
+    /// Which changes to describe.
+    ///
+    /// Default: staged
+    #[serde(default)]
+    pub scope: CommitMessageScope,
+    /// Whether to write messages in the Conventional Commits format, like
+    /// `fix(parser): handle empty input`. A project's style guide takes
+    /// precedence over this.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub conventional_commits: bool,
+}
+
+impl Settings for CommitMessageSettings {
+    const KEY: Option<&'static str> = Some("commit_message");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
+
+pub fn init(fs: Arc<dyn Fs>, prompt_builder: Arc<PromptBuilder>, cx: &mut AppContext) {
+    CommitMessageSettings::register(cx);
+    cx.set_global(CommitMessageAssistant::new(fs, prompt_builder));
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &GenerateCommitMessage, cx| {
+                CommitMessageAssistant::update_global(cx, |assistant, cx| {
+                    assistant.generate(workspace, cx)
+                })
+            });
+        },
+    )
+    .detach();
+}
+
+/// Writes commit messages for the staged changes into the git panel's commit
+/// message editor.
+pub struct CommitMessageAssistant {
+    fs: Arc<dyn Fs>,
+    prompt_builder: Arc<PromptBuilder>,
+    /// The message being generated for each git panel, by its generation id. Generating
+    /// a new one replaces, and so cancels, the previous one.
+    generations: HashMap<EntityId, (usize, Task<()>)>,
+    next_generation_id: usize,
+}
+
+impl Global for CommitMessageAssistant {}
+
+impl CommitMessageAssistant {
+    fn new(fs: Arc<dyn Fs>, prompt_builder: Arc<PromptBuilder>) -> Self {
+        Self {
+            fs,
+            prompt_builder,
+            generations: HashMap::default(),
+            next_generation_id: 0,
+        }
+    }
+
+    fn generate(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(git_panel) = workspace.panel::<GitPanel>(cx) else {
+            return;
+        };
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            show_error(
+                workspace,
+                "Configure a language model in the assistant panel to generate commit messages",
+                cx,
+            );
+            return;
+        };
+        let Some((work_directory, repository)) = local_repository(workspace.project(), cx) else {
+            show_error(workspace, "This project has no git repository", cx);
+            return;
+        };
+
+        let settings = CommitMessageSettings::get_global(cx).clone();
+        let fs = self.fs.clone();
+        let prompt_builder = self.prompt_builder.clone();
+        let panel = git_panel.downgrade();
+        let panel_id = git_panel.entity_id();
+        let generation_id = post_inc(&mut self.next_generation_id);
+        let task = cx.spawn(|workspace, mut cx| async move {
+            let generate = async {
+                let prompt = commit_message_prompt(
+                    repository,
+                    work_directory,
+                    fs,
+                    prompt_builder,
+                    settings,
+                    &cx,
+                )
+                .await?;
+                let mut panel_cx = cx.clone();
+                stream_commit_message(model, prompt, &cx, |message| {
+                    panel.update(&mut panel_cx, |panel, cx| {
+                        panel.set_commit_message(message, cx)
+                    })
+                })
+                .await
+            };
+
+            if let Err(error) = generate.await {
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        show_error(workspace, format!("{error:#}"), cx)
+                    })
+                    .ok();
+            }
+            cx.update(|cx| {
+                CommitMessageAssistant::update_global(cx, |assistant, _| {
+                    if assistant
+                        .generations
+                        .get(&panel_id)
+                        .map_or(false, |(id, _)| *id == generation_id)
+                    {
+                        assistant.generations.remove(&panel_id);
+                    }
+                })
+            })
+            .ok();
+        });
+        self.generations.insert(panel_id, (generation_id, task));
+    }
+}
+
+/// The prompt to write a commit message for the changes in the scope with.
+async fn commit_message_prompt(
+    repository: Arc<dyn GitRepository>,
+    work_directory: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    prompt_builder: Arc<PromptBuilder>,
+    settings: CommitMessageSettings,
+    cx: &AsyncAppContext,
+) -> Result<String> {
+    let (diff, branch_name) = cx
+        .background_executor()
+        .spawn(async move {
+            let diff = changes_to_describe(repository.as_ref(), settings.scope)?;
+            anyhow::Ok((diff, repository.branch_name()))
+        })
+        .await?;
+    let style_guide = fs
+        .load(&work_directory.join(paths::local_commit_message_style_guide_relative_path()))
+        .await
+        .ok()
+        .filter(|style_guide| !style_guide.trim().is_empty());
+    let prompt = prompt_builder.generate_commit_message_prompt(
+        diff,
+        settings.scope,
+        branch_name,
+        style_guide,
+        settings.conventional_commits,
+    )?;
+    Ok(prompt)
+}
+
+/// Streams the model's commit message for the prompt, passing the message written so far
+/// to `on_progress` as it grows. Returns the whole message.
+async fn stream_commit_message(
+    model: Arc<dyn LanguageModel>,
+    prompt: String,
+    cx: &AsyncAppContext,
+    mut on_progress: impl FnMut(&str) -> Result<()>,
+) -> Result<String> {
+    let request = LanguageModelRequest {
+        messages: vec![LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![prompt.into()],
+            cache: false,
+        }],
+        tools: Vec::new(),
+        stop: Vec::new(),
+        temperature: None,
+    };
+
+    let mut chunks = model.stream_completion_text(request, cx).await?.stream;
+    let mut message = String::new();
+    while let Some(chunk) = chunks.next().await {
+        message.push_str(&chunk?);
+        on_progress(message.trim())?;
+    }
+    Ok(message.trim().to_string())
+}
+
+/// The diff of the changes in the scope, or an error if there are none.
+fn changes_to_describe(
+    repository: &dyn GitRepository,
+    scope: CommitMessageScope,
+) -> Result<String> {
+    let diff = match scope {
+        CommitMessageScope::Staged => repository.staged_diff()?,
+        CommitMessageScope::AllChanges => repository.uncommitted_diff()?,
+    };
+    if diff.trim().is_empty() {
+        return Err(match scope {
+            CommitMessageScope::Staged => anyhow!("There are no staged changes to describe"),
+            CommitMessageScope::AllChanges => {
+                anyhow!("There are no uncommitted changes to describe")
+            }
+        });
+    }
+    Ok(diff)
+}
+
+/// The repository of the first visible local worktree that has one, along
+/// with that worktree's root, where the style guide is looked up.
+fn local_repository(
+    project: &Model<Project>,
+    cx: &AppContext,
+) -> Option<(Arc<Path>, Arc<dyn GitRepository>)> {
+    project.read(cx).visible_worktrees(cx).find_map(|worktree| {
+        let worktree = worktree.read(cx);
+        let local_worktree = worktree.as_local()?;
+        let (_, repository) = local_worktree.repositories().next()?;
+        let repository = local_worktree.get_local_repo(repository)?.repo().clone();
+        Some((worktree.abs_path(), repository))
+    })
+}
+
+fn show_error(
+    workspace: &mut Workspace,
+    message: impl Into<String>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::unique::<CommitMessageAssistant>(),
+            message.into(),
+        )
+        .autohide(),
+        cx,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::{FakeGitRepository, FakeGitRepositoryState};
+    use gpui::TestAppContext;
+    use language_model::fake_provider::FakeLanguageModel;
+    use parking_lot::Mutex;
+    use project::FakeFs;
+    use serde_json::json;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_changes_to_describe() {
+        let (event_emitter, _) = smol::channel::unbounded();
+        let state = Arc::new(Mutex::new(FakeGitRepositoryState::new(
+            "/project/.git".into(),
+            event_emitter,
+        )));
+        let repository = FakeGitRepository::open(state.clone());
+        let changes = |scope| {
+            changes_to_describe(repository.as_ref(), scope).map_err(|error| error.to_string())
+        };
+
+        assert_eq!(
+            changes(CommitMessageScope::Staged),
+            Err("There are no staged changes to describe".to_string())
+        );
+        assert_eq!(
+            changes(CommitMessageScope::AllChanges),
+            Err("There are no uncommitted changes to describe".to_string())
+        );
+
+        {
+            let mut state = state.lock();
+            state.staged_diff = "+staged\n".to_string();
+            state.uncommitted_diff = "+staged\n+unstaged\n".to_string();
+        }
+        assert_eq!(
+            changes(CommitMessageScope::Staged),
+            Ok("+staged\n".to_string())
+        );
+        assert_eq!(
+            changes(CommitMessageScope::AllChanges),
+            Ok("+staged\n+unstaged\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_commit_message_prompt() {
+        let prompt_builder = PromptBuilder::new(None).unwrap();
+        let prompt = |scope, conventional_commits| {
+            prompt_builder
+                .generate_commit_message_prompt(
+                    "+fn main() {}\n".to_string(),
+                    scope,
+                    Some("fix-parser".to_string()),
+                    None,
+                    conventional_commits,
+                )
+                .unwrap()
+        };
+
+        let staged = prompt(CommitMessageScope::Staged, false);
+        assert!(staged.contains("the changes that are staged for the next commit"));
+        assert!(staged.contains("Here are the staged changes:"));
+        assert!(!staged.contains("Conventional Commits"));
+        assert!(staged.contains("on the branch 'fix-parser'"));
+        assert!(staged.contains("<diff>\n+fn main() {}\n"));
+
+        let all_changes = prompt(CommitMessageScope::AllChanges, true);
+        assert!(all_changes.contains("all the uncommitted changes, staged or not"));
+        assert!(all_changes.contains("Here are the uncommitted changes:"));
+        assert!(!all_changes.contains("staged changes:"));
+        assert!(all_changes.contains("follow the Conventional Commits format"));
+    }
+
+    #[gpui::test]
+    async fn test_generating_from_staged_diff(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".git": {},
+                "src": { "main.rs": "fn main() {}" },
+            }),
+        )
+        .await;
+        let (event_emitter, _) = smol::channel::unbounded();
+        let state = Arc::new(Mutex::new(FakeGitRepositoryState::new(
+            "/project/.git".into(),
+            event_emitter,
+        )));
+        {
+            let mut state = state.lock();
+            state.staged_diff = "+fn main() {}\n".to_string();
+            state.uncommitted_diff = "+fn main() {}\n+// unstaged\n".to_string();
+            state.current_branch_name = Some("fix-parser".to_string());
+        }
+
+        let prompt = commit_message_prompt(
+            FakeGitRepository::open(state),
+            Path::new("/project").into(),
+            fs,
+            Arc::new(PromptBuilder::new(None).unwrap()),
+            CommitMessageSettings::default(),
+            &cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert!(prompt.contains("<diff>\n+fn main() {}\n\n</diff>"));
+        assert!(!prompt.contains("// unstaged"));
+        assert!(prompt.contains("on the branch 'fix-parser'"));
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let message = cx.spawn({
+            let model = model.clone();
+            let progress = progress.clone();
+            |cx| async move {
+                stream_commit_message(model, prompt, &cx, |message| {
+                    progress.borrow_mut().push(message.to_string());
+                    Ok(())
+                })
+                .await
+            }
+        });
+        cx.run_until_parked();
+        model.stream_last_completion_response("Add the main ".to_string());
+        cx.run_until_parked();
+        model.stream_last_completion_response("function\n".to_string());
+        cx.run_until_parked();
+        model.end_last_completion_stream();
+
+        assert_eq!(message.await.unwrap(), "Add the main function");
+        assert_eq!(
+            *progress.borrow(),
+            ["Add the main", "Add the main function"]
+        );
+    }
+
+    #[test]
+    fn test_commit_message_prompt_truncates_long_diffs() {
+        let prompt_builder = PromptBuilder::new(None).unwrap();
+        let prompt = |diff: &str| {
+            prompt_builder
+                .generate_commit_message_prompt(
+                    diff.to_string(),
+                    CommitMessageScope::Staged,
+                    None,
+                    None,
+                    false,
+                )
+                .unwrap()
+        };
+        const TRUNCATION_NOTE: &str = "The diff is too long to include in full";
+
+        let short_diff = "+fn main() {}\n";
+        assert!(!prompt(short_diff).contains(TRUNCATION_NOTE));
+
+        // The limit falls within a multi-byte character, which is dropped whole.
+        let long_diff = format!("+{}", "é".repeat(30_000));
+        let truncated = prompt(&long_diff);
+        assert!(truncated.contains(TRUNCATION_NOTE));
+        assert!(!truncated.contains(&long_diff));
+        assert!(truncated.contains(&format!("<diff>\n+{}\n</diff>", "é".repeat(24_999))));
+    }
+}
@@ -3,7 +3,9 @@ mod context_tests;
 
 use crate::slash_command_working_set::SlashCommandWorkingSet;
 use crate::{
+    assistant_settings::{AssistantProfile, AssistantSettings},
     prompts::PromptBuilder,
+    request_queue::{RequestQueue, RequestSource},
     slash_command::{file_command::FileCommandMetadata, SlashCommandLine},
     AssistantEdit, AssistantPatch, AssistantPatchStatus, MessageId, MessageStatus,
 };
@@ -28,7 +30,7 @@ use language_model::{
     LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionEvent,
     LanguageModelImage, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelRequestTool, LanguageModelToolResult, LanguageModelToolUse,
-    LanguageModelToolUseId, MessageContent, Role, StopReason,
+    LanguageModelToolUseId, MessageContent, Role, StopReason, TokenUsage,
 };
 use language_models::{
     provider::cloud::{MaxMonthlySpendReachedError, PaymentRequiredError},
@@ -38,6 +40,7 @@ use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
 use project::Project;
 use serde::{Deserialize, Serialize};
+use settings::{Settings as _, SettingsStore};
 use smallvec::SmallVec;
 use std::{
     cmp::{max, Ordering},
@@ -556,6 +559,13 @@ pub struct Context {
     pending_completions: Vec<PendingCompletion>,
     token_count: Option<usize>,
     pending_token_count: Task<Option<()>>,
+    /// The tokens used by the last completion, as reported by the provider.
+    token_usage: Option<TokenUsage>,
+    /// The name of the profile the context is sent with. Its settings are looked up whenever the
+    /// context is sent, so that edits to the profile apply right away.
+    profile: Option<String>,
+    /// The context this one was forked from, if it's a branch of another context.
+    forked_from: Option<ContextId>,
     pending_save: Task<Result<()>>,
     pending_cache_warming_task: Task<Option<()>>,
     path: Option<PathBuf>,
@@ -661,8 +671,18 @@ impl Context {
             pending_completions: Default::default(),
             token_count: None,
             pending_token_count: Task::ready(None),
+            token_usage: None,
+            profile: None,
+            forked_from: None,
             pending_cache_warming_task: Task::ready(None),
-            _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
+            _subscriptions: vec![
+                cx.subscribe(&buffer, Self::handle_buffer_event),
+                cx.observe_global::<SettingsStore>(|this, cx| {
+                    if this.profile.is_some() {
+                        this.count_remaining_tokens(cx);
+                    }
+                }),
+            ],
             pending_save: Task::ready(Ok(())),
             path: None,
             buffer,
@@ -737,13 +757,34 @@ impl Context {
                     }
                 })
                 .collect(),
+            forked_from: self.forked_from.clone(),
         }
     }
 
+    /// Serializes the context up to and including the given message as a new context, so that
+    /// the conversation can be continued in another direction without losing this one.
+    pub(crate) fn serialize_fork(&self, message_id: MessageId, cx: &AppContext) -> SavedContext {
+        let mut saved_context = self.serialize(cx);
+        let end = self
+            .messages(cx)
+            .find(|message| message.id == message_id)
+            .map_or(saved_context.text.len(), |message| message.offset_range.end);
+        saved_context.id = Some(ContextId::new());
+        saved_context.forked_from = Some(self.id.clone());
+        saved_context.text.truncate(end);
+        saved_context
+            .messages
+            .retain(|message| message.start < end || message.id == message_id);
+        saved_context
+            .slash_command_output_sections
+            .retain(|section| section.range.end <= end);
+        saved_context
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn deserialize(
         saved_context: SavedContext,
-        path: PathBuf,
+        path: Option<PathBuf>,
         language_registry: Arc<LanguageRegistry>,
         prompt_builder: Arc<PromptBuilder>,
         slash_commands: Arc<SlashCommandWorkingSet>,
@@ -765,7 +806,8 @@ impl Context {
             telemetry,
             cx,
         );
-        this.path = Some(path);
+        this.path = path;
+        this.forked_from = saved_context.forked_from.clone();
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
@@ -1043,6 +1085,10 @@ impl Context {
         self.path.as_deref()
     }
 
+    pub fn forked_from(&self) -> Option<&ContextId> {
+        self.forked_from.as_ref()
+    }
+
     pub fn summary(&self) -> Option<&ContextSummary> {
         self.summary.as_ref()
     }
@@ -1168,11 +1214,54 @@ impl Context {
         self.token_count
     }
 
+    fn profile<'a>(&self, cx: &'a AppContext) -> Option<&'a AssistantProfile> {
+        AssistantSettings::get_global(cx).profile(self.profile.as_deref()?)
+    }
+
+    fn system_prompt<'a>(&self, cx: &'a AppContext) -> Option<&'a str> {
+        self.profile(cx)?
+            .system_prompt
+            .as_deref()
+            .filter(|system_prompt| !system_prompt.trim().is_empty())
+    }
+
+    pub(crate) fn token_usage(&self) -> Option<TokenUsage> {
+        self.token_usage
+    }
+
+    pub fn profile_name(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn set_profile(&mut self, profile: Option<String>, cx: &mut ModelContext<Self>) {
+        self.profile = profile;
+        self.count_remaining_tokens(cx);
+        cx.notify();
+    }
+
+    /// The model set by the context's profile, when it's available.
+    pub fn profile_model(&self, cx: &AppContext) -> Option<Arc<dyn LanguageModel>> {
+        let selection = self.profile(cx)?.model.as_ref()?;
+        LanguageModelRegistry::read_global(cx)
+            .available_models(cx)
+            .find(|model| {
+                model.provider_id().0.as_ref() == selection.provider
+                    && model.id().0.as_ref() == selection.model
+            })
+    }
+
+    /// The model the context is sent to: the model of its profile when it's available, or else
+    /// the active model.
+    pub fn model(&self, cx: &AppContext) -> Option<Arc<dyn LanguageModel>> {
+        self.profile_model(cx)
+            .or_else(|| LanguageModelRegistry::read_global(cx).active_model())
+    }
+
     pub(crate) fn count_remaining_tokens(&mut self, cx: &mut ModelContext<Self>) {
         // Assume it will be a Chat request, even though that takes fewer tokens (and risks going over the limit),
         // because otherwise you see in the UI that your empty message has a bunch of tokens already used.
         let request = self.to_completion_request(RequestType::Chat, cx);
-        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+        let Some(model) = self.model(cx) else {
             return;
         };
         self.pending_token_count = cx.spawn(|this, mut cx| {
@@ -1222,8 +1311,10 @@ impl Context {
             // If we have't hit the minimum threshold to enable caching, don't cache anything.
             0
         } else {
-            // Save 1 anchor for the inline assistant to use.
-            max(cache_configuration.max_cache_anchors, 1) - 1
+            // Save 1 anchor for the inline assistant to use, and 1 for the system prompt, which
+            // stays cached even when earlier messages are edited.
+            (max(cache_configuration.max_cache_anchors, 1) - 1)
+                .saturating_sub(self.system_prompt(cx).is_some() as usize)
         };
         sorted_messages.truncate(cache_anchors);
 
@@ -2151,6 +2242,17 @@ impl Context {
         );
     }
 
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
     pub fn insert_tool_output(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -2231,9 +2333,8 @@ impl Context {
         request_type: RequestType,
         cx: &mut ModelContext<Self>,
     ) -> Option<MessageAnchor> {
-        let model_registry = LanguageModelRegistry::read_global(cx);
-        let provider = model_registry.active_provider()?;
-        let model = model_registry.active_model()?;
+        let model = self.model(cx)?;
+        let provider = LanguageModelRegistry::read_global(cx).provider(&model.provider_id())?;
         let last_message_id = self.get_last_valid_message_id(cx)?;
 
         if !provider.is_authenticated(cx) {
@@ -2244,6 +2345,18 @@ impl Context {
         self.mark_cache_anchors(&model.cache_configuration(), false, cx);
 
         let mut request = self.to_completion_request(request_type, cx);
+        if let Some((token_budget, token_count)) = AssistantSettings::get_global(cx)
+            .context_token_budget
+            .zip(self.token_count)
+        {
+            let trimmed_messages =
+                trim_request_to_token_budget(&mut request, token_count, token_budget);
+            if trimmed_messages > 0 {
+                log::info!(
+                    "left {trimmed_messages} messages out of the request to fit the token budget"
+                );
+            }
+        }
 
         if cx.has_flag::<ToolUseFeatureFlag>() {
             request.tools = self
@@ -2271,7 +2384,12 @@ impl Context {
 
         let task = cx.spawn({
             |this, mut cx| async move {
-                let stream = model.stream_completion(request, &cx);
+                let stream = RequestQueue::stream_completion(
+                    model,
+                    request,
+                    RequestSource::Panel,
+                    cx.clone(),
+                );
                 let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
                 let stream_completion = async {
@@ -2303,6 +2421,9 @@ impl Context {
                                     LanguageModelCompletionEvent::Stop(reason) => {
                                         stop_reason = reason;
                                     }
+                                    LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                        this.token_usage = Some(usage);
+                                    }
                                     LanguageModelCompletionEvent::Text(chunk) => {
                                         buffer.edit(
                                             [(
@@ -2454,6 +2575,23 @@ impl Context {
         Some(user_message)
     }
 
+    /// Appends a response that wasn't streamed into the context, like the one picked when comparing
+    /// models, as an assistant message. Returns the user message that's queued up after it.
+    pub fn insert_assistant_response(
+        &mut self,
+        response: &str,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<MessageAnchor> {
+        let last_message_id = self.get_last_valid_message_id(cx)?;
+        let assistant_message =
+            self.insert_message_after(last_message_id, Role::Assistant, MessageStatus::Done, cx)?;
+        self.buffer.update(cx, |buffer, cx| {
+            let offset = assistant_message.start.to_offset(buffer);
+            buffer.edit([(offset..offset, response)], None, cx);
+        });
+        self.insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
+    }
+
     pub fn to_completion_request(
         &self,
         request_type: RequestType,
@@ -2547,6 +2685,26 @@ impl Context {
             completion_request.messages.push(request_message);
         }
 
+        if let Some(profile) = self.profile(cx) {
+            completion_request.temperature = profile.temperature;
+        }
+        if let Some(system_prompt) = self.system_prompt(cx) {
+            // Only cache the system prompt once the context is big enough for its messages to be
+            // cached too.
+            let cache = completion_request
+                .messages
+                .iter()
+                .any(|message| message.cache);
+            completion_request.messages.insert(
+                0,
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: vec![system_prompt.to_string().into()],
+                    cache,
+                },
+            );
+        }
+
         if let RequestType::SuggestEdits = request_type {
             if let Ok(preamble) = self.prompt_builder.generate_suggest_edits_prompt() {
                 let last_elem_index = completion_request.messages.len();
@@ -2879,10 +3037,11 @@ impl Context {
     }
 
     pub(super) fn summarize(&mut self, replace_old: bool, cx: &mut ModelContext<Self>) {
-        let Some(provider) = LanguageModelRegistry::read_global(cx).active_provider() else {
+        let Some(model) = self.model(cx) else {
             return;
         };
-        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+        let Some(provider) = LanguageModelRegistry::read_global(cx).provider(&model.provider_id())
+        else {
             return;
         };
 
@@ -3145,6 +3304,66 @@ fn trimmed_text_in_range(buffer: &BufferSnapshot, range: Range<text::Anchor>) ->
     content
 }
 
+/// Leaves the oldest messages out of the request until it fits in `token_budget`, always keeping
+/// the system messages and the last message. The `token_count` of the whole request is spread
+/// over its messages by their length, since counting each of them with the model's tokenizer
+/// would be too slow to do on every send. Returns how many messages were left out.
+pub(crate) fn trim_request_to_token_budget(
+    request: &mut LanguageModelRequest,
+    token_count: usize,
+    token_budget: usize,
+) -> usize {
+    if token_count <= token_budget || request.messages.is_empty() {
+        return 0;
+    }
+
+    let message_lens = request
+        .messages
+        .iter()
+        .map(|message| message.string_contents().len().max(1))
+        .collect::<Vec<_>>();
+    let total_len = message_lens.iter().sum::<usize>();
+    let last_ix = request.messages.len() - 1;
+    let mut left_out = vec![false; request.messages.len()];
+    let mut estimated_token_count = token_count;
+    for (ix, message) in request.messages.iter().enumerate().take(last_ix) {
+        if estimated_token_count <= token_budget {
+            break;
+        }
+        if message.role == Role::System {
+            continue;
+        }
+        estimated_token_count =
+            estimated_token_count.saturating_sub(token_count * message_lens[ix] / total_len);
+        left_out[ix] = true;
+    }
+    if !left_out.contains(&true) {
+        return 0;
+    }
+
+    // The remaining conversation has to start with a user message, and not with the results of
+    // tools whose use was left out.
+    for (ix, message) in request.messages.iter().enumerate().take(last_ix) {
+        if left_out[ix] || message.role == Role::System {
+            continue;
+        }
+        let is_tool_result = message
+            .content
+            .iter()
+            .any(|content| matches!(content, MessageContent::ToolResult(_)));
+        if message.role == Role::User && !is_tool_result {
+            break;
+        }
+        left_out[ix] = true;
+    }
+
+    let mut left_out = left_out.into_iter();
+    request
+        .messages
+        .retain(|_| !left_out.next().unwrap_or(false));
+    last_ix + 1 - request.messages.len()
+}
+
 #[derive(Debug, Default)]
 pub struct ContextVersion {
     context: clock::Global,
@@ -3212,7 +3431,11 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
-    Running { _task: Shared<Task<()>> },
+    /// The tool is waiting for the user to allow running it.
+    NeedsConfirmation,
+    Running {
+        _task: Shared<Task<()>>,
+    },
     Error(String),
 }
 
@@ -3220,6 +3443,10 @@ impl PendingToolUseStatus {
     pub fn is_idle(&self) -> bool {
         matches!(self, PendingToolUseStatus::Idle)
     }
+
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::NeedsConfirmation)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -3239,6 +3466,8 @@ pub struct SavedContext {
     pub summary: String,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
+    #[serde(default)]
+    pub forked_from: Option<ContextId>,
 }
 
 impl SavedContext {
@@ -3414,6 +3643,7 @@ impl SavedContextV0_3_0 {
                 .collect(),
             summary: self.summary,
             slash_command_output_sections: self.slash_command_output_sections,
+            forked_from: None,
         }
     }
 }
@@ -3482,4 +3712,7 @@ pub struct SavedContextMetadata {
     pub title: String,
     pub path: PathBuf,
     pub mtime: chrono::DateTime<chrono::Local>,
+    pub id: Option<ContextId>,
+    /// The context this one was forked from, if it's a branch of another context.
+    pub forked_from: Option<ContextId>,
 }
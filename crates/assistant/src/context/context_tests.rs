@@ -1,9 +1,9 @@
-use super::{AssistantEdit, MessageCacheMetadata};
+use super::{trim_request_to_token_budget, AssistantEdit, MessageCacheMetadata};
 use crate::slash_command_working_set::SlashCommandWorkingSet;
 use crate::{
     assistant_panel, prompt_library, slash_command::file_command, AssistantEditKind, CacheStatus,
     Context, ContextEvent, ContextId, ContextOperation, InvokedSlashCommandId, MessageId,
-    MessageStatus, PromptBuilder,
+    MessageStatus, PromptBuilder, RequestType,
 };
 use anyhow::Result;
 use assistant_slash_command::{
@@ -19,7 +19,10 @@ use futures::{
 };
 use gpui::{prelude::*, AppContext, Model, SharedString, Task, TestAppContext, WeakView};
 use language::{Buffer, BufferSnapshot, LanguageRegistry, LspAdapterDelegate};
-use language_model::{LanguageModelCacheConfiguration, LanguageModelRegistry, Role};
+use language_model::{
+    LanguageModelCacheConfiguration, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, Role,
+};
 use parking_lot::Mutex;
 use pretty_assertions::assert_eq;
 use project::Project;
@@ -384,6 +387,46 @@ fn test_messages_for_offsets(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_insert_assistant_response(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry,
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    let buffer = context.read(cx).buffer.clone();
+    let message_1 = context.read(cx).message_anchors[0].clone();
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "aaa")], None, cx));
+
+    let message_3 = context
+        .update(cx, |context, cx| {
+            context.insert_assistant_response("bbb", cx)
+        })
+        .unwrap();
+    let message_2 = context.read(cx).message_anchors[1].clone();
+    assert_eq!(buffer.read(cx).text(), "aaa\nbbb\n");
+    assert_eq!(
+        messages(&context, cx),
+        vec![
+            (message_1.id, Role::User, 0..4),
+            (message_2.id, Role::Assistant, 4..8),
+            (message_3.id, Role::User, 8..8),
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_slash_commands(cx: &mut TestAppContext) {
     let settings_store = cx.update(SettingsStore::test);
@@ -1157,6 +1200,86 @@ async fn test_serialization(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_fork(cx: &mut TestAppContext) {
+    let settings_store = cx.update(SettingsStore::test);
+    cx.set_global(settings_store);
+    cx.update(LanguageModelRegistry::test);
+    cx.update(assistant_panel::init);
+    let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry.clone(),
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+    let message_0 = context.read_with(cx, |context, _| context.message_anchors[0].id);
+    let message_1 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    let message_2 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "a"), (1..1, "b"), (2..2, "c")], None, cx);
+    });
+    assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "a\nb\nc");
+
+    let forked_context =
+        context.read_with(cx, |context, cx| context.serialize_fork(message_1.id, cx));
+    let fork = cx.new_model(|cx| {
+        Context::deserialize(
+            forked_context,
+            None,
+            registry.clone(),
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            None,
+            None,
+            cx,
+        )
+    });
+    let fork_buffer = fork.read_with(cx, |context, _| context.buffer.clone());
+    assert_eq!(
+        fork_buffer.read_with(cx, |buffer, _| buffer.text()),
+        "a\nb\n"
+    );
+    assert_eq!(
+        cx.read(|cx| messages(&fork, cx)),
+        [
+            (message_0, Role::User, 0..2),
+            (message_1.id, Role::Assistant, 2..4),
+        ]
+    );
+    fork.read_with(cx, |fork, cx| {
+        assert_ne!(fork.id(), context.read(cx).id());
+        assert_eq!(fork.forked_from(), Some(context.read(cx).id()));
+        assert_eq!(fork.path(), None);
+    });
+
+    // The context it was forked from is left as is.
+    assert_eq!(
+        cx.read(|cx| messages(&context, cx)),
+        [
+            (message_0, Role::User, 0..2),
+            (message_1.id, Role::Assistant, 2..4),
+            (message_2.id, Role::User, 4..5),
+        ]
+    );
+}
+
 #[gpui::test(iterations = 100)]
 async fn test_random_context_collaboration(cx: &mut TestAppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -1603,6 +1726,244 @@ fn test_mark_cache_anchors(cx: &mut AppContext) {
     );
 }
 
+#[gpui::test]
+fn test_profile_in_completion_request(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry,
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    context.update(cx, |context, cx| {
+        context
+            .buffer
+            .update(cx, |buffer, cx| buffer.edit([(0..0, "Hello")], None, cx))
+    });
+
+    let request = context
+        .read(cx)
+        .to_completion_request(RequestType::Chat, cx);
+    assert_eq!(request.temperature, None);
+    assert_eq!(
+        request
+            .messages
+            .iter()
+            .map(|message| (message.role, message.string_contents()))
+            .collect::<Vec<_>>(),
+        vec![(Role::User, "Hello".to_string())]
+    );
+
+    cx.update_global::<SettingsStore, _>(|store, cx| {
+        store
+            .set_user_settings(
+                r#"{
+                    "assistant": {
+                        "version": "2",
+                        "profiles": {
+                            "Code review": {
+                                "temperature": 0.2,
+                                "system_prompt": "Look for bugs."
+                            }
+                        }
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+    });
+    context.update(cx, |context, cx| {
+        context.set_profile(Some("Code review".into()), cx)
+    });
+    let request = context
+        .read(cx)
+        .to_completion_request(RequestType::Chat, cx);
+    assert_eq!(request.temperature, Some(0.2));
+    assert_eq!(
+        request
+            .messages
+            .iter()
+            .map(|message| (message.role, message.string_contents()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Role::System, "Look for bugs.".to_string()),
+            (Role::User, "Hello".to_string())
+        ]
+    );
+
+    // Edits to the profile apply to contexts that already use it.
+    cx.update_global::<SettingsStore, _>(|store, cx| {
+        store
+            .set_user_settings(
+                r#"{
+                    "assistant": {
+                        "version": "2",
+                        "profiles": {
+                            "Code review": {
+                                "temperature": 0.5
+                            }
+                        }
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+    });
+    let request = context
+        .read(cx)
+        .to_completion_request(RequestType::Chat, cx);
+    assert_eq!(request.temperature, Some(0.5));
+    assert_eq!(
+        request
+            .messages
+            .iter()
+            .map(|message| message.role)
+            .collect::<Vec<_>>(),
+        vec![Role::User]
+    );
+}
+
+#[gpui::test]
+fn test_caching_profile_system_prompt(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry,
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    context.update(cx, |context, cx| {
+        context
+            .buffer
+            .update(cx, |buffer, cx| buffer.edit([(0..0, "Hello")], None, cx))
+    });
+    cx.update_global::<SettingsStore, _>(|store, cx| {
+        store
+            .set_user_settings(
+                r#"{
+                    "assistant": {
+                        "version": "2",
+                        "profiles": {
+                            "Code review": {
+                                "system_prompt": "Look for bugs."
+                            }
+                        }
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+    });
+    context.update(cx, |context, cx| {
+        context.set_profile(Some("Code review".into()), cx)
+    });
+    let message_caching = |context: &Model<Context>, cx: &AppContext| {
+        context
+            .read(cx)
+            .to_completion_request(RequestType::Chat, cx)
+            .messages
+            .iter()
+            .map(|message| (message.role, message.cache))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        message_caching(&context, cx),
+        vec![(Role::System, false), (Role::User, false)]
+    );
+
+    // Once the context is big enough to be cached, the system prompt is cached along with it,
+    // using one of the cache anchors.
+    context.update(cx, |context, cx| {
+        context.token_count = Some(20);
+        context.mark_cache_anchors(
+            &Some(LanguageModelCacheConfiguration {
+                max_cache_anchors: 3,
+                should_speculate: false,
+                min_total_token: 10,
+            }),
+            false,
+            cx,
+        );
+    });
+    assert_eq!(
+        message_caching(&context, cx),
+        vec![(Role::System, true), (Role::User, true)]
+    );
+}
+
+#[test]
+fn test_trim_request_to_token_budget() {
+    let request = LanguageModelRequest {
+        messages: [
+            (Role::System, 's'),
+            (Role::User, 'a'),
+            (Role::Assistant, 'b'),
+            (Role::User, 'c'),
+            (Role::Assistant, 'd'),
+            (Role::User, 'e'),
+        ]
+        .into_iter()
+        .map(|(role, letter)| LanguageModelRequestMessage {
+            role,
+            content: vec![letter.to_string().repeat(100).into()],
+            cache: false,
+        })
+        .collect(),
+        tools: Vec::new(),
+        stop: Vec::new(),
+        temperature: None,
+    };
+    let first_chars = |request: &LanguageModelRequest| {
+        request
+            .messages
+            .iter()
+            .map(|message| message.string_contents().chars().next().unwrap())
+            .collect::<String>()
+    };
+
+    let mut within_budget = request.clone();
+    assert_eq!(
+        trim_request_to_token_budget(&mut within_budget, 600, 600),
+        0
+    );
+    assert_eq!(first_chars(&within_budget), "sabcde");
+
+    // The oldest exchange is left out, while the system message is kept.
+    let mut trimmed = request.clone();
+    assert_eq!(trim_request_to_token_budget(&mut trimmed, 600, 450), 2);
+    assert_eq!(first_chars(&trimmed), "scde");
+
+    // The conversation doesn't start with an assistant message after trimming.
+    let mut trimmed = request.clone();
+    assert_eq!(trim_request_to_token_budget(&mut trimmed, 600, 350), 4);
+    assert_eq!(first_chars(&trimmed), "se");
+
+    // The last message is always sent.
+    let mut trimmed = request.clone();
+    assert_eq!(trim_request_to_token_budget(&mut trimmed, 600, 10), 4);
+    assert_eq!(first_chars(&trimmed), "se");
+}
+
 fn messages(context: &Model<Context>, cx: &AppContext) -> Vec<(MessageId, Role, Range<usize>)> {
     context
         .read(cx)
@@ -2,13 +2,14 @@ use crate::slash_command::context_server_command;
 use crate::SlashCommandId;
 use crate::{
     prompts::PromptBuilder, slash_command_working_set::SlashCommandWorkingSet, Context,
-    ContextEvent, ContextId, ContextOperation, ContextVersion, SavedContext, SavedContextMetadata,
+    ContextEvent, ContextId, ContextOperation, ContextVersion, MessageId, SavedContext,
+    SavedContextMetadata,
 };
 use anyhow::{anyhow, Context as _, Result};
 use assistant_tool::{ToolId, ToolWorkingSet};
 use client::{proto, telemetry::Telemetry, Client, TypedEnvelope};
 use clock::ReplicaId;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use context_server::manager::ContextServerManager;
 use context_server::{ContextServerFactoryRegistry, ContextServerTool};
 use fs::Fs;
@@ -22,6 +23,7 @@ use paths::contexts_dir;
 use project::Project;
 use regex::Regex;
 use rpc::AnyProtoClient;
+use serde::Deserialize;
 use std::sync::LazyLock;
 use std::{
     cmp::Reverse,
@@ -47,6 +49,22 @@ pub struct RemoteContextMetadata {
     pub summary: Option<String>,
 }
 
+/// A context in a tree of branches, which may not be loaded yet.
+#[derive(Clone)]
+pub enum ContextBranch {
+    Loaded(Model<Context>),
+    Saved(SavedContextMetadata),
+}
+
+/// The fields of a saved context that place it in a tree of branches, which can be read without
+/// loading the whole context.
+#[derive(Deserialize)]
+struct SavedContextBranchMetadata {
+    id: Option<ContextId>,
+    #[serde(default)]
+    forked_from: Option<ContextId>,
+}
+
 pub struct ContextStore {
     contexts: Vec<ContextHandle>,
     contexts_metadata: Vec<SavedContextMetadata>,
@@ -377,6 +395,110 @@ impl ContextStore {
         context
     }
 
+    /// Creates a branch of the context that contains its messages up to and including the given
+    /// one.
+    pub fn fork(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Model<Context> {
+        let saved_context = context.read(cx).serialize_fork(message_id, cx);
+        let context = cx.new_model(|cx| {
+            Context::deserialize(
+                saved_context,
+                None,
+                self.languages.clone(),
+                self.prompt_builder.clone(),
+                self.slash_commands.clone(),
+                self.tools.clone(),
+                Some(self.project.clone()),
+                Some(self.telemetry.clone()),
+                cx,
+            )
+        });
+        self.register_context(&context, cx);
+        context
+    }
+
+    /// The contexts in the same tree of branches as the given one, along with their depth in the
+    /// tree, starting with the context all of them were forked from. Saved contexts are included
+    /// even when they aren't loaded.
+    pub fn branches(
+        &self,
+        context: &Model<Context>,
+        cx: &AppContext,
+    ) -> Vec<(usize, ContextBranch)> {
+        let mut nodes = self
+            .contexts
+            .iter()
+            .filter_map(ContextHandle::upgrade)
+            .map(|context| {
+                let context_ref = context.read(cx);
+                (
+                    Some(context_ref.id().clone()),
+                    context_ref.forked_from().cloned(),
+                    ContextBranch::Loaded(context),
+                )
+            })
+            .collect::<Vec<_>>();
+        for metadata in &self.contexts_metadata {
+            let is_loaded = nodes.iter().any(|(id, _, branch)| match branch {
+                ContextBranch::Loaded(context) => {
+                    (metadata.id.is_some() && *id == metadata.id)
+                        || context.read(cx).path() == Some(metadata.path.as_path())
+                }
+                ContextBranch::Saved(_) => false,
+            });
+            if !is_loaded {
+                nodes.push((
+                    metadata.id.clone(),
+                    metadata.forked_from.clone(),
+                    ContextBranch::Saved(metadata.clone()),
+                ));
+            }
+        }
+        let node_for_id = |id: &ContextId| {
+            nodes
+                .iter()
+                .position(|(node_id, _, _)| node_id.as_ref() == Some(id))
+        };
+
+        let Some(mut root) = nodes.iter().position(
+            |(_, _, branch)| matches!(branch, ContextBranch::Loaded(loaded) if loaded == context),
+        ) else {
+            return Vec::new();
+        };
+        let mut ancestors = HashSet::from_iter([root]);
+        while let Some(parent) = nodes[root].1.as_ref().and_then(node_for_id) {
+            if !ancestors.insert(parent) {
+                break;
+            }
+            root = parent;
+        }
+
+        let mut branches = Vec::new();
+        let mut visited = HashSet::default();
+        let mut stack = vec![(0, root)];
+        while let Some((depth, node)) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            if let Some(id) = nodes[node].0.as_ref() {
+                stack.extend(
+                    nodes
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .filter(|(_, (_, forked_from, _))| forked_from.as_ref() == Some(id))
+                        .map(|(child, _)| (depth + 1, child)),
+                );
+            }
+            branches.push((depth, nodes[node].2.clone()));
+        }
+        branches
+    }
+
     pub fn create_remote_context(
         &mut self,
         cx: &mut ModelContext<Self>,
@@ -465,7 +587,7 @@ impl ContextStore {
             let context = cx.new_model(|cx| {
                 Context::deserialize(
                     saved_context,
-                    path.clone(),
+                    Some(path.clone()),
                     languages,
                     prompt_builder,
                     slash_commands,
@@ -743,7 +865,8 @@ impl ContextStore {
 
     fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
         let fs = self.fs.clone();
-        cx.spawn(|this, mut cx| async move {
+        // Reading which context each saved one was forked from parses every saved context.
+        let load = cx.background_executor().spawn(async move {
             fs.create_dir(contexts_dir()).await?;
 
             let mut paths = fs.read_dir(contexts_dir()).await?;
@@ -773,16 +896,24 @@ impl ContextStore {
                         .lines()
                         .next()
                     {
+                        let title = title.to_string();
+                        let branch = Self::load_branch_metadata(fs.as_ref(), &path).await;
                         contexts.push(SavedContextMetadata {
-                            title: title.to_string(),
+                            title,
                             path,
                             mtime: metadata.mtime.timestamp_for_user().into(),
+                            id: branch.as_ref().and_then(|branch| branch.id.clone()),
+                            forked_from: branch.and_then(|branch| branch.forked_from),
                         });
                     }
                 }
             }
             contexts.sort_unstable_by_key(|context| Reverse(context.mtime));
+            anyhow::Ok(contexts)
+        });
 
+        cx.spawn(|this, mut cx| async move {
+            let contexts = load.await?;
             this.update(&mut cx, |this, cx| {
                 this.contexts_metadata = contexts;
                 cx.notify();
@@ -790,6 +921,11 @@ impl ContextStore {
         })
     }
 
+    async fn load_branch_metadata(fs: &dyn Fs, path: &Path) -> Option<SavedContextBranchMetadata> {
+        let saved_context = fs.load(path).await.log_err()?;
+        serde_json::from_str(&saved_context).log_err()
+    }
+
     pub fn restart_context_servers(&mut self, cx: &mut ModelContext<Self>) {
         cx.update_model(
             &self.context_server_manager,
@@ -1,7 +1,14 @@
 use crate::{
-    assistant_settings::AssistantSettings, humanize_token_count, prompts::PromptBuilder,
+    assistant_panel::ContextEditor,
+    assistant_settings::{AssistantCodeAction, AssistantCodeActionTarget, AssistantSettings},
+    humanize_token_count,
+    prompts::PromptBuilder,
+    request_queue::{render_queued_requests, RequestQueue, RequestSource},
+    slash_command::prompt_command,
+    voice_input::{render_voice_input_button, VoiceInput, VoiceInputEvent},
     AssistantPanel, AssistantPanelEvent, CharOperation, CycleNextInlineAssist,
     CyclePreviousInlineAssist, LineDiff, LineOperation, RequestType, StreamingDiff,
+    ToggleVoiceInput,
 };
 use anyhow::{anyhow, Context as _, Result};
 use client::{telemetry::Telemetry, ErrorExt};
@@ -834,6 +841,47 @@ impl InlineAssistant {
         }
     }
 
+    fn accept_hunk(&mut self, assist_id: InlineAssistId, position: Anchor, cx: &mut WindowContext) {
+        let Some(assist) = self.assists.get(&assist_id) else {
+            return;
+        };
+        let codegen = assist.codegen.clone();
+        codegen.update(cx, |codegen, cx| codegen.accept_hunk(position, cx));
+        self.finish_assist_if_resolved(assist_id, &codegen, cx);
+    }
+
+    fn reject_hunk(&mut self, assist_id: InlineAssistId, position: Anchor, cx: &mut WindowContext) {
+        let Some(assist) = self.assists.get(&assist_id) else {
+            return;
+        };
+        let codegen = assist.codegen.clone();
+        let reapply_diff = codegen.update(cx, |codegen, cx| codegen.reject_hunk(position, cx));
+        cx.spawn(|mut cx| async move {
+            reapply_diff.await;
+            cx.update(|cx| {
+                InlineAssistant::update_global(cx, |this, cx| {
+                    this.finish_assist_if_resolved(assist_id, &codegen, cx)
+                })
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Finishes the assist once all of its hunks were accepted or rejected, undoing it when none
+    /// of them was accepted.
+    fn finish_assist_if_resolved(
+        &mut self,
+        assist_id: InlineAssistId,
+        codegen: &Model<Codegen>,
+        cx: &mut WindowContext,
+    ) {
+        if self.assists.contains_key(&assist_id) && codegen.read(cx).diff(cx).is_empty() {
+            let undo = !codegen.read(cx).has_accepted_hunks(cx);
+            self.finish_assist(assist_id, undo, cx);
+        }
+    }
+
     fn dismiss_assist(&mut self, assist_id: InlineAssistId, cx: &mut WindowContext) -> bool {
         let Some(assist) = self.assists.get_mut(&assist_id) else {
             return false;
@@ -847,6 +895,7 @@ impl InlineAssistant {
 
         editor.update(cx, |editor, cx| {
             let mut to_remove = decorations.removed_line_block_ids;
+            to_remove.extend(decorations.hunk_controls_block_ids);
             to_remove.insert(decorations.prompt_block_id);
             to_remove.insert(decorations.end_block_id);
             editor.remove_blocks(to_remove, None, cx);
@@ -1019,12 +1068,59 @@ impl InlineAssistant {
 
         let assistant_panel_context = assist.assistant_panel_context(cx);
 
-        assist
-            .codegen
-            .update(cx, |codegen, cx| {
-                codegen.start(user_prompt, assistant_panel_context, cx)
-            })
-            .log_err();
+        let workspace = assist
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.upgrade());
+        let library_prompt =
+            workspace
+                .clone()
+                .zip(assist.editor.upgrade())
+                .and_then(|(workspace, editor)| {
+                    prompt_command::expand_inline_assist_prompt(
+                        &user_prompt,
+                        &workspace,
+                        &editor,
+                        assist.range.clone(),
+                        cx,
+                    )
+                });
+        let Some(library_prompt) = library_prompt else {
+            assist
+                .codegen
+                .update(cx, |codegen, cx| {
+                    codegen.start(user_prompt, assistant_panel_context, cx)
+                })
+                .log_err();
+            return;
+        };
+
+        let codegen = assist.codegen.clone();
+        cx.spawn(|mut cx| async move {
+            let result: Result<()> = async {
+                let prompt = library_prompt.await?;
+                codegen.update(&mut cx, |codegen, cx| {
+                    codegen.start(prompt, assistant_panel_context, cx)
+                })?
+            }
+            .await;
+            if let (Err(error), Some(workspace)) = (result, workspace) {
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        struct InlineAssistPromptError;
+
+                        workspace.show_toast(
+                            Toast::new(
+                                NotificationId::composite::<InlineAssistPromptError>(assist_id.0),
+                                format!("Inline assistant error: {error:#}"),
+                            ),
+                            cx,
+                        )
+                    })
+                    .ok();
+            }
+        })
+        .detach();
     }
 
     pub fn stop_assist(&mut self, assist_id: InlineAssistId, cx: &mut WindowContext) {
@@ -1145,11 +1241,41 @@ impl InlineAssistant {
         let old_snapshot = codegen.snapshot(cx);
         let old_buffer = codegen.old_buffer(cx);
         let deleted_row_ranges = codegen.diff(cx).deleted_row_ranges.clone();
+        // Once the transformation is done, its hunks can be accepted or rejected one by one.
+        let hunk_positions = if matches!(codegen.status(cx), CodegenStatus::Done) {
+            let snapshot = codegen.buffer(cx).read(cx).snapshot(cx);
+            codegen
+                .diff(cx)
+                .hunks(&snapshot)
+                .into_iter()
+                .map(|hunk| hunk.position)
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         editor.update(cx, |editor, cx| {
-            let old_blocks = mem::take(&mut decorations.removed_line_block_ids);
+            let mut old_blocks = mem::take(&mut decorations.removed_line_block_ids);
+            old_blocks.extend(mem::take(&mut decorations.hunk_controls_block_ids));
             editor.remove_blocks(old_blocks, None, cx);
 
+            // Inserted first, so that they are above the deleted lines at the same position.
+            let hunk_controls_blocks = hunk_positions
+                .into_iter()
+                .enumerate()
+                .map(|(ix, position)| BlockProperties {
+                    placement: BlockPlacement::Above(position),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render: build_hunk_controls_renderer(assist_id, ix, position),
+                    priority: 0,
+                })
+                .collect::<Vec<_>>();
+            decorations.hunk_controls_block_ids = editor
+                .insert_blocks(hunk_controls_blocks, None, cx)
+                .into_iter()
+                .collect();
+
             let mut new_blocks = Vec::new();
             for (new_row, old_row_range) in deleted_row_ranges {
                 let (_, buffer_start) = old_snapshot
@@ -1319,6 +1445,46 @@ impl InlineAssistGroup {
     }
 }
 
+fn build_hunk_controls_renderer(
+    assist_id: InlineAssistId,
+    hunk_ix: usize,
+    position: Anchor,
+) -> RenderBlock {
+    Arc::new(move |cx| {
+        h_flex()
+            .block_mouse_down()
+            .size_full()
+            .h(cx.line_height())
+            .pl(cx.gutter_dimensions.full_width())
+            .gap_1()
+            .child(
+                Button::new(("accept-hunk", hunk_ix), "Accept")
+                    .icon(IconName::Check)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .label_size(LabelSize::Small)
+                    .on_click(move |_, cx| {
+                        InlineAssistant::update_global(cx, |this, cx| {
+                            this.accept_hunk(assist_id, position, cx)
+                        })
+                    }),
+            )
+            .child(
+                Button::new(("reject-hunk", hunk_ix), "Reject")
+                    .icon(IconName::Close)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .label_size(LabelSize::Small)
+                    .on_click(move |_, cx| {
+                        InlineAssistant::update_global(cx, |this, cx| {
+                            this.reject_hunk(assist_id, position, cx)
+                        })
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
 fn build_assist_editor_renderer(editor: &View<PromptEditor>) -> RenderBlock {
     let editor = editor.clone();
     Arc::new(move |cx: &mut BlockContext| {
@@ -1374,6 +1540,9 @@ struct PromptEditor {
     _token_count_subscriptions: Vec<Subscription>,
     workspace: Option<WeakView<Workspace>>,
     show_rate_limit_notice: bool,
+    voice_input: Model<VoiceInput>,
+    _voice_input_subscriptions: Vec<Subscription>,
+    _request_queue_subscription: Subscription,
 }
 
 #[derive(Copy, Clone)]
@@ -1505,6 +1674,7 @@ impl Render for PromptEditor {
             .on_action(cx.listener(Self::restart))
             .on_action(cx.listener(Self::move_up))
             .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::toggle_voice_input))
             .capture_action(cx.listener(Self::cycle_prev))
             .capture_action(cx.listener(Self::cycle_next))
             .child(
@@ -1580,7 +1750,9 @@ impl Render for PromptEditor {
                 h_flex()
                     .gap_2()
                     .pr_6()
+                    .children(render_queued_requests(RequestSource::InlineAssist, cx))
                     .children(self.render_token_count(cx))
+                    .child(render_voice_input_button(&self.voice_input, cx))
                     .children(buttons),
             )
     }
@@ -1635,6 +1807,13 @@ impl PromptEditor {
                 .push(cx.subscribe(assistant_panel, Self::handle_assistant_panel_event));
         }
 
+        let voice_input = cx.new_model(|_| VoiceInput::new());
+        let voice_input_subscriptions = vec![
+            cx.observe(&voice_input, |_, _, cx| cx.notify()),
+            cx.subscribe(&voice_input, Self::handle_voice_input_event),
+        ];
+
+        let request_queue = RequestQueue::global(cx);
         let mut this = Self {
             id,
             editor: prompt_editor,
@@ -1664,12 +1843,33 @@ impl PromptEditor {
             _token_count_subscriptions: token_count_subscriptions,
             workspace,
             show_rate_limit_notice: false,
+            voice_input,
+            _voice_input_subscriptions: voice_input_subscriptions,
+            _request_queue_subscription: cx.observe(&request_queue, |_, _, cx| cx.notify()),
         };
         this.count_tokens(cx);
         this.subscribe_to_editor(cx);
         this
     }
 
+    fn toggle_voice_input(&mut self, _: &ToggleVoiceInput, cx: &mut ViewContext<Self>) {
+        self.voice_input
+            .update(cx, |voice_input, cx| voice_input.toggle(cx));
+    }
+
+    fn handle_voice_input_event(
+        &mut self,
+        _: Model<VoiceInput>,
+        event: &VoiceInputEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let VoiceInputEvent::Transcribed(transcript) = event;
+        self.editor.update(cx, |editor, cx| {
+            editor.insert(transcript, cx);
+            editor.focus(cx);
+        });
+    }
+
     fn subscribe_to_editor(&mut self, cx: &mut ViewContext<Self>) {
         self.editor_subscriptions.clear();
         self.editor_subscriptions
@@ -2242,6 +2442,7 @@ impl InlineAssist {
                 prompt_block_id,
                 prompt_editor: prompt_editor.clone(),
                 removed_line_block_ids: HashSet::default(),
+                hunk_controls_block_ids: HashSet::default(),
                 end_block_id,
             }),
             range,
@@ -2358,6 +2559,7 @@ struct InlineAssistDecorations {
     prompt_block_id: CustomBlockId,
     prompt_editor: View<PromptEditor>,
     removed_line_block_ids: HashSet<CustomBlockId>,
+    hunk_controls_block_ids: HashSet<CustomBlockId>,
     end_block_id: CustomBlockId,
 }
 
@@ -2562,6 +2764,20 @@ impl Codegen {
         &self.active_alternative().read(cx).diff
     }
 
+    fn has_accepted_hunks(&self, cx: &AppContext) -> bool {
+        !self.active_alternative().read(cx).accepted_hunks.is_empty()
+    }
+
+    pub fn accept_hunk(&mut self, position: Anchor, cx: &mut ModelContext<Self>) {
+        self.active_alternative()
+            .update(cx, |codegen, cx| codegen.accept_hunk(position, cx));
+    }
+
+    pub fn reject_hunk(&mut self, position: Anchor, cx: &mut ModelContext<Self>) -> Task<()> {
+        self.active_alternative()
+            .update(cx, |codegen, cx| codegen.reject_hunk(position, cx))
+    }
+
     pub fn last_equal_ranges<'a>(&self, cx: &'a AppContext) -> &'a [Range<Anchor>] {
         self.active_alternative().read(cx).last_equal_ranges()
     }
@@ -2586,6 +2802,8 @@ pub struct CodegenAlternative {
     active: bool,
     edits: Vec<(Range<Anchor>, String)>,
     line_operations: Vec<LineOperation>,
+    /// The positions of the hunks whose changes were accepted one by one.
+    accepted_hunks: Vec<Anchor>,
     request: Option<LanguageModelRequest>,
     elapsed_time: Option<f64>,
     completion: Option<String>,
@@ -2609,6 +2827,54 @@ impl Diff {
     fn is_empty(&self) -> bool {
         self.deleted_row_ranges.is_empty() && self.inserted_row_ranges.is_empty()
     }
+
+    /// Groups the deleted and inserted lines into hunks, ordered by their position in the buffer.
+    fn hunks(&self, snapshot: &MultiBufferSnapshot) -> Vec<DiffHunk> {
+        let mut hunks = self
+            .deleted_row_ranges
+            .iter()
+            .map(|(position, old_row_range)| DiffHunk {
+                position: *position,
+                deleted_row_range: Some(old_row_range.clone()),
+                inserted_row_range: None,
+            })
+            .collect::<Vec<_>>();
+        for inserted_row_range in &self.inserted_row_ranges {
+            if let Some(hunk) = hunks.iter_mut().find(|hunk| {
+                hunk.inserted_row_range.is_none()
+                    && hunk
+                        .position
+                        .cmp(&inserted_row_range.start, snapshot)
+                        .is_eq()
+            }) {
+                hunk.inserted_row_range = Some(inserted_row_range.clone());
+            } else {
+                hunks.push(DiffHunk {
+                    position: inserted_row_range.start,
+                    deleted_row_range: None,
+                    inserted_row_range: Some(inserted_row_range.clone()),
+                });
+            }
+        }
+        hunks.sort_by(|a, b| a.position.cmp(&b.position, snapshot));
+        hunks
+    }
+
+    fn remove_hunk(&mut self, position: &Anchor, snapshot: &MultiBufferSnapshot) {
+        self.deleted_row_ranges
+            .retain(|(row, _)| !row.cmp(position, snapshot).is_eq());
+        self.inserted_row_ranges
+            .retain(|range| !range.start.cmp(position, snapshot).is_eq());
+    }
+}
+
+/// The lines deleted and inserted by the model at one position of the buffer.
+#[derive(Clone, Debug)]
+struct DiffHunk {
+    position: Anchor,
+    /// The deleted rows, in the buffer before the transformation.
+    deleted_row_range: Option<RangeInclusive<u32>>,
+    inserted_row_range: Option<Range<Anchor>>,
 }
 
 impl EventEmitter<CodegenEvent> for CodegenAlternative {}
@@ -2661,6 +2927,7 @@ impl CodegenAlternative {
             active,
             edits: Vec::new(),
             line_operations: Vec::new(),
+            accepted_hunks: Vec::new(),
             range,
             request: None,
             elapsed_time: None,
@@ -2682,6 +2949,7 @@ impl CodegenAlternative {
                     self.reapply_batch_diff(cx).detach();
                 }
             } else if let Some(transaction_id) = self.transformation_transaction_id.take() {
+                self.accepted_hunks.clear();
                 self.buffer.update(cx, |buffer, cx| {
                     buffer.undo_transaction(transaction_id, cx);
                     buffer.forget_transaction(transaction_id, cx);
@@ -2764,8 +3032,16 @@ impl CodegenAlternative {
                 let request = self.build_request(user_prompt, assistant_panel_context, cx)?;
                 self.request = Some(request.clone());
 
-                cx.spawn(|_, cx| async move { model.stream_completion_text(request, &cx).await })
-                    .boxed_local()
+                cx.spawn(|_, cx| async move {
+                    RequestQueue::stream_completion_text(
+                        model,
+                        request,
+                        RequestSource::InlineAssist,
+                        cx,
+                    )
+                    .await
+                })
+                .boxed_local()
             };
         self.handle_stream(telemetry_id, provider_id.to_string(), api_key, stream, cx);
         Ok(())
@@ -3112,6 +3388,76 @@ impl CodegenAlternative {
         });
     }
 
+    /// Keeps the changes of the hunk at the given position and stops showing them as a diff.
+    pub fn accept_hunk(&mut self, position: Anchor, cx: &mut ModelContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        self.diff.remove_hunk(&position, &snapshot);
+        self.accepted_hunks.push(position);
+        cx.notify();
+    }
+
+    /// Restores the lines the hunk at the given position replaced, as part of the transformation,
+    /// so that undoing the transformation still restores the whole range.
+    pub fn reject_hunk(&mut self, position: Anchor, cx: &mut ModelContext<Self>) -> Task<()> {
+        let old_snapshot = &self.snapshot;
+        let new_snapshot = self.buffer.read(cx).snapshot(cx);
+        let Some(hunk) = self
+            .diff
+            .hunks(&new_snapshot)
+            .into_iter()
+            .find(|hunk| hunk.position.cmp(&position, &new_snapshot).is_eq())
+        else {
+            return Task::ready(());
+        };
+
+        let old_text = hunk.deleted_row_range.map(|old_row_range| {
+            old_snapshot
+                .text_for_range(
+                    Point::new(*old_row_range.start(), 0)
+                        ..Point::new(
+                            *old_row_range.end(),
+                            old_snapshot.line_len(MultiBufferRow(*old_row_range.end())),
+                        ),
+                )
+                .collect::<String>()
+        });
+        let inserted_range = hunk.inserted_row_range.map(|range| {
+            Point::new(range.start.to_point(&new_snapshot).row, 0)
+                ..range.end.to_point(&new_snapshot)
+        });
+        let (range, new_text) = match (old_text, inserted_range) {
+            (Some(old_text), Some(inserted_range)) => (inserted_range, old_text),
+            (Some(old_text), None) => {
+                let position = hunk.position.to_point(&new_snapshot);
+                if position.column == 0 {
+                    (position..position, old_text + "\n")
+                } else {
+                    (position..position, format!("\n{old_text}"))
+                }
+            }
+            (None, Some(inserted_range)) => {
+                // Remove the inserted lines along with one of the newlines around them.
+                let range = if inserted_range.end.row < new_snapshot.max_point().row {
+                    inserted_range.start..Point::new(inserted_range.end.row + 1, 0)
+                } else if inserted_range.start.row > 0 {
+                    let previous_row = inserted_range.start.row - 1;
+                    Point::new(
+                        previous_row,
+                        new_snapshot.line_len(MultiBufferRow(previous_row)),
+                    )..inserted_range.end
+                } else {
+                    inserted_range
+                };
+                (range, String::new())
+            }
+            (None, None) => return Task::ready(()),
+        };
+
+        let range = new_snapshot.anchor_after(range.start)..new_snapshot.anchor_before(range.end);
+        self.apply_edits([(range, new_text)], cx);
+        self.reapply_batch_diff(cx)
+    }
+
     fn apply_edits(
         &mut self,
         edits: impl IntoIterator<Item = (Range<Anchor>, String)>,
@@ -3281,6 +3627,10 @@ impl CodegenAlternative {
                 .update(&mut cx, |codegen, cx| {
                     codegen.diff.deleted_row_ranges = deleted_row_ranges;
                     codegen.diff.inserted_row_ranges = inserted_row_ranges;
+                    let snapshot = codegen.buffer.read(cx).snapshot(cx);
+                    for position in &codegen.accepted_hunks {
+                        codegen.diff.remove_hunk(position, &snapshot);
+                    }
                     cx.notify();
                 })
                 .ok();
@@ -3434,11 +3784,29 @@ impl CodeActionProvider for AssistantCodeActionProvider {
 
         let snapshot = buffer.read(cx).snapshot();
         let mut range = range.to_point(&snapshot);
+        let has_selection = !range.is_empty();
 
         // Expand the range to line boundaries.
         range.start.column = 0;
         range.end.column = snapshot.line_len(range.end.row);
 
+        // The prompt-based actions are only offered for selected code, so that they don't show up
+        // on every line.
+        let mut actions = Vec::new();
+        if has_selection {
+            for code_action in &AssistantSettings::get_global(cx).code_actions {
+                actions.push(CodeAction {
+                    server_id: language::LanguageServerId(0),
+                    range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                    lsp_action: lsp::CodeAction {
+                        title: code_action.title.clone(),
+                        data: serde_json::to_value(code_action).ok(),
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+
         let mut has_diagnostics = false;
         for diagnostic in snapshot.diagnostics_in_range::<_, Point>(range.clone(), false) {
             range.start = cmp::min(range.start, diagnostic.range.start);
@@ -3460,17 +3828,16 @@ impl CodeActionProvider for AssistantCodeActionProvider {
                 }
             }
 
-            Task::ready(Ok(vec![CodeAction {
+            actions.push(CodeAction {
                 server_id: language::LanguageServerId(0),
                 range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
                 lsp_action: lsp::CodeAction {
                     title: "Fix with Assistant".into(),
                     ..Default::default()
                 },
-            }]))
-        } else {
-            Task::ready(Ok(Vec::new()))
+            });
         }
+        Task::ready(Ok(actions))
     }
 
     fn apply_code_action(
@@ -3483,6 +3850,11 @@ impl CodeActionProvider for AssistantCodeActionProvider {
     ) -> Task<Result<ProjectTransaction>> {
         let editor = self.editor.clone();
         let workspace = self.workspace.clone();
+        let code_action = action
+            .lsp_action
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<AssistantCodeAction>(data).ok());
         cx.spawn(|mut cx| async move {
             let editor = editor.upgrade().context("editor was released")?;
             let range = editor
@@ -3521,24 +3893,50 @@ impl CodeActionProvider for AssistantCodeActionProvider {
                     })
                 })?
                 .context("invalid range")?;
+
+            if let Some(AssistantCodeAction {
+                prompt,
+                target: AssistantCodeActionTarget::Panel,
+                ..
+            }) = &code_action
+            {
+                editor.update(&mut cx, |editor, cx| {
+                    editor.change_selections(None, cx, |selections| {
+                        selections.select_anchor_ranges([range])
+                    });
+                })?;
+                workspace.update(&mut cx, |workspace, cx| {
+                    ContextEditor::quote_selection_with_prompt(workspace, prompt.clone(), cx)
+                })?;
+                return Ok(ProjectTransaction::default());
+            }
+
             let assistant_panel = workspace.update(&mut cx, |workspace, cx| {
                 workspace
                     .panel::<AssistantPanel>(cx)
                     .context("assistant panel was released")
             })??;
 
+            // The prompt of a configured action is left to the user to edit and confirm, while
+            // diagnostics are fixed right away.
+            let (prompt, start) = match code_action {
+                Some(action) => (action.prompt, false),
+                None => ("Fix Diagnostics".into(), true),
+            };
             cx.update_global(|assistant: &mut InlineAssistant, cx| {
                 let assist_id = assistant.suggest_assist(
                     &editor,
                     range,
-                    "Fix Diagnostics".into(),
+                    prompt,
                     None,
                     true,
                     Some(workspace),
                     Some(&assistant_panel),
                     cx,
                 );
-                assistant.start_assist(assist_id, cx);
+                if start {
+                    assistant.start_assist(assist_id, cx);
+                }
             })?;
 
             Ok(ProjectTransaction::default())
@@ -3576,17 +3974,19 @@ fn merge_ranges(ranges: &mut Vec<Range<Anchor>>, buffer: &MultiBufferSnapshot) {
 mod tests {
     use super::*;
     use futures::stream::{self};
-    use gpui::{Context, TestAppContext};
+    use gpui::{Context, TestAppContext, VisualTestContext};
     use indoc::indoc;
     use language::{
         language_settings, tree_sitter_rust, Buffer, Language, LanguageConfig, LanguageMatcher,
         Point,
     };
     use language_model::LanguageModelRegistry;
+    use project::{FakeFs, Project};
     use rand::prelude::*;
     use serde::Serialize;
     use settings::SettingsStore;
     use std::{future, sync::Arc};
+    use workspace::AppState;
 
     #[derive(Serialize)]
     pub struct DummyCompletionRequest {
@@ -3912,6 +4312,97 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_accept_and_reject_hunks(cx: &mut TestAppContext) {
+        cx.update(LanguageModelRegistry::test);
+        cx.set_global(cx.update(SettingsStore::test));
+        cx.update(language_settings::init);
+
+        let text = indoc! {"
+            fn main() {
+                let x = 0;
+                let y = 1;
+            }
+        "};
+        let buffer =
+            cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx));
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let range = buffer.read_with(cx, |buffer, cx| {
+            let snapshot = buffer.snapshot(cx);
+            snapshot.anchor_before(Point::new(1, 0))..snapshot.anchor_after(Point::new(2, 14))
+        });
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let codegen = cx.new_model(|cx| {
+            CodegenAlternative::new(
+                buffer.clone(),
+                range.clone(),
+                true,
+                None,
+                prompt_builder,
+                cx,
+            )
+        });
+
+        let chunks_tx = simulate_response_stream(codegen.clone(), cx);
+        chunks_tx
+            .unbounded_send("let mut x = 0;\nlet y = 1;\nlet z = 2;".to_string())
+            .unwrap();
+        drop(chunks_tx);
+        cx.run_until_parked();
+
+        let hunk_positions = |cx: &mut TestAppContext| {
+            codegen.read_with(cx, |codegen, cx| {
+                let snapshot = codegen.buffer.read(cx).snapshot(cx);
+                codegen
+                    .diff
+                    .hunks(&snapshot)
+                    .into_iter()
+                    .map(|hunk| hunk.position)
+                    .collect::<Vec<_>>()
+            })
+        };
+        let hunks = hunk_positions(cx);
+        assert_eq!(hunks.len(), 2);
+
+        // Rejecting a hunk restores the lines it replaced.
+        codegen
+            .update(cx, |codegen, cx| codegen.reject_hunk(hunks[0], cx))
+            .await;
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            indoc! {"
+                fn main() {
+                    let x = 0;
+                    let y = 1;
+                    let z = 2;
+                }
+            "}
+        );
+        assert_eq!(hunk_positions(cx).len(), 1);
+
+        // Accepting a hunk keeps its changes, which aren't part of the diff anymore.
+        codegen.update(cx, |codegen, cx| codegen.accept_hunk(hunks[1], cx));
+        assert!(codegen.read_with(cx, |codegen, _| codegen.diff.is_empty()));
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            indoc! {"
+                fn main() {
+                    let x = 0;
+                    let y = 1;
+                    let z = 2;
+                }
+            "}
+        );
+
+        // Undoing the transformation restores the original text.
+        codegen.update(cx, |codegen, cx| codegen.undo(cx));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            text
+        );
+    }
+
     #[gpui::test]
     async fn test_strip_invalid_spans_from_codeblock() {
         assert_chunks("Lorem ipsum dolor", "Lorem ipsum dolor").await;
@@ -3952,6 +4443,107 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    async fn test_code_actions_for_selection(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let buffer = cx.new_model(|cx| {
+            Buffer::local("fn main() {\n    let x = 0;\n}\n", cx)
+                .with_language(Arc::new(rust_lang()), cx)
+        });
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer.clone(), Some(project), cx));
+        let provider = AssistantCodeActionProvider {
+            editor: editor.downgrade(),
+            workspace: workspace.downgrade(),
+        };
+
+        let actions =
+            code_actions(&provider, &buffer, Point::new(1, 4)..Point::new(1, 4), cx).await;
+        assert!(
+            actions.is_empty(),
+            "Without a selection or diagnostics, no action should be offered"
+        );
+
+        let actions =
+            code_actions(&provider, &buffer, Point::new(1, 4)..Point::new(1, 14), cx).await;
+        assert_eq!(
+            actions
+                .iter()
+                .map(|action| action.lsp_action.title.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Explain with Assistant",
+                "Generate Tests with Assistant",
+                "Add Doc Comment with Assistant"
+            ],
+        );
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store
+                    .set_user_settings(
+                        r#"{
+                            "assistant": {
+                                "version": "2",
+                                "code_actions": [
+                                    {
+                                        "title": "Convert to Async",
+                                        "prompt": "Rewrite this code to use async/await."
+                                    }
+                                ]
+                            }
+                        }"#,
+                        cx,
+                    )
+                    .unwrap();
+            })
+        });
+        let actions =
+            code_actions(&provider, &buffer, Point::new(1, 4)..Point::new(1, 14), cx).await;
+        assert_eq!(
+            actions.len(),
+            1,
+            "Configured actions should replace the default ones"
+        );
+        assert_eq!(actions[0].lsp_action.title, "Convert to Async");
+        assert_eq!(
+            serde_json::from_value::<AssistantCodeAction>(
+                actions[0].lsp_action.data.clone().unwrap()
+            )
+            .unwrap(),
+            AssistantCodeAction {
+                title: "Convert to Async".into(),
+                prompt: "Rewrite this code to use async/await.".into(),
+                target: AssistantCodeActionTarget::InlineAssist,
+            },
+            "The action should carry its prompt, transforming the code inline by default"
+        );
+    }
+
+    async fn code_actions(
+        provider: &AssistantCodeActionProvider,
+        buffer: &Model<Buffer>,
+        range: Range<Point>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<CodeAction> {
+        cx.update(|cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            let range = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+            provider.code_actions(buffer, range, cx)
+        })
+        .await
+        .unwrap()
+    }
+
     fn simulate_response_stream(
         codegen: Model<CodegenAlternative>,
         cx: &mut TestAppContext,
@@ -0,0 +1,192 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use gpui::{percentage, Animation, AnimationExt, EventEmitter, Task, Transformation};
+use language_model::{LanguageModel, LanguageModelRequest};
+use ui::{prelude::*, Tooltip};
+
+use crate::request_queue::{RequestQueue, RequestSource};
+
+pub enum ModelComparisonEvent {
+    /// A response was picked to continue the conversation from.
+    Picked(String),
+    Dismissed,
+}
+
+enum ResponseStatus {
+    Pending,
+    Done,
+    Error(SharedString),
+}
+
+struct ComparedResponse {
+    model: Arc<dyn LanguageModel>,
+    text: String,
+    status: ResponseStatus,
+    _task: Task<()>,
+}
+
+/// Sends the same request to two models and shows their responses side by side, so that the
+/// conversation can be continued from whichever is better.
+pub struct ModelComparison {
+    responses: Vec<ComparedResponse>,
+}
+
+impl EventEmitter<ModelComparisonEvent> for ModelComparison {}
+
+impl ModelComparison {
+    pub fn new(
+        models: [Arc<dyn LanguageModel>; 2],
+        request: LanguageModelRequest,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let responses = models
+            .into_iter()
+            .enumerate()
+            .map(|(ix, model)| ComparedResponse {
+                _task: Self::stream_response(ix, model.clone(), request.clone(), cx),
+                model,
+                text: String::new(),
+                status: ResponseStatus::Pending,
+            })
+            .collect();
+        Self { responses }
+    }
+
+    fn stream_response(
+        ix: usize,
+        model: Arc<dyn LanguageModel>,
+        request: LanguageModelRequest,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<()> {
+        cx.spawn(|this, mut cx| async move {
+            let stream = async {
+                let mut chunks = RequestQueue::stream_completion_text(
+                    model,
+                    request,
+                    RequestSource::Panel,
+                    (*cx).clone(),
+                )
+                .await?
+                .stream;
+                while let Some(chunk) = chunks.next().await {
+                    let chunk = chunk?;
+                    this.update(&mut cx, |this, cx| {
+                        this.responses[ix].text.push_str(&chunk);
+                        cx.notify();
+                    })?;
+                }
+                anyhow::Ok(())
+            };
+            let result = stream.await;
+            this.update(&mut cx, |this, cx| {
+                this.responses[ix].status = match result {
+                    Ok(()) => ResponseStatus::Done,
+                    Err(error) => ResponseStatus::Error(format!("{error:#}").into()),
+                };
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn render_response(&self, ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let response = &self.responses[ix];
+        let status = match &response.status {
+            ResponseStatus::Pending => Icon::new(IconName::ArrowCircle)
+                .size(IconSize::Small)
+                .color(Color::Muted)
+                .with_animation(
+                    ("compared-response-pending", ix),
+                    Animation::new(Duration::from_secs(2)).repeat(),
+                    |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                )
+                .into_any_element(),
+            ResponseStatus::Done => Icon::new(IconName::Check)
+                .size(IconSize::Small)
+                .color(Color::Success)
+                .into_any_element(),
+            ResponseStatus::Error(error) => {
+                let error = error.clone();
+                div()
+                    .id(("compared-response-error", ix))
+                    .child(
+                        Icon::new(IconName::XCircle)
+                            .size(IconSize::Small)
+                            .color(Color::Error),
+                    )
+                    .tooltip(move |cx| Tooltip::text(error.clone(), cx))
+                    .into_any_element()
+            }
+        };
+        let is_done = matches!(response.status, ResponseStatus::Done);
+
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .h_full()
+            .gap_1()
+            .child(
+                h_flex().gap_1().child(status).child(
+                    Label::new(response.model.name().0)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .child(
+                div()
+                    .id(("compared-response", ix))
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .font_buffer(cx)
+                    .text_ui_sm(cx)
+                    .child(response.text.clone()),
+            )
+            .child(
+                Button::new(
+                    ("pick-compared-response", ix),
+                    "Continue with This Response",
+                )
+                .label_size(LabelSize::Small)
+                .disabled(!is_done)
+                .on_click(cx.listener(move |this, _, cx| {
+                    let text = this.responses[ix].text.trim().to_string();
+                    cx.emit(ModelComparisonEvent::Picked(text));
+                })),
+            )
+    }
+}
+
+impl Render for ModelComparison {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .h(rems(20.))
+            .p_2()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Comparing Responses").size(LabelSize::Small))
+                    .child(
+                        IconButton::new("dismiss-model-comparison", IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Discard Both Responses", cx))
+                            .on_click(
+                                cx.listener(|_, _, cx| cx.emit(ModelComparisonEvent::Dismissed)),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .gap_3()
+                    .child(self.render_response(0, cx))
+                    .child(div().w_px().h_full().bg(cx.theme().colors().border_variant))
+                    .child(self.render_response(1, cx)),
+            )
+    }
+}
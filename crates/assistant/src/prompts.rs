@@ -1,3 +1,4 @@
+use crate::commit_message::CommitMessageScope;
 use anyhow::Result;
 use assets::Assets;
 use fs::Fs;
@@ -40,6 +41,16 @@ pub struct TerminalAssistantPromptContext {
     pub user_prompt: String,
 }
 
+#[derive(Serialize)]
+pub struct CommitMessagePromptContext {
+    pub diff: String,
+    pub is_truncated: bool,
+    pub includes_unstaged_changes: bool,
+    pub branch_name: Option<String>,
+    pub style_guide: Option<String>,
+    pub conventional_commits: bool,
+}
+
 #[derive(Serialize)]
 pub struct ProjectSlashCommandPromptContext {
     pub context_buffer: String,
@@ -310,6 +321,35 @@ impl PromptBuilder {
             .render("terminal_assistant_prompt", &context)
     }
 
+    pub fn generate_commit_message_prompt(
+        &self,
+        mut diff: String,
+        scope: CommitMessageScope,
+        branch_name: Option<String>,
+        style_guide: Option<String>,
+        conventional_commits: bool,
+    ) -> Result<String, RenderError> {
+        const MAX_DIFF_LEN: usize = 50000;
+        let is_truncated = diff.len() > MAX_DIFF_LEN;
+        if is_truncated {
+            let mut len = MAX_DIFF_LEN;
+            while !diff.is_char_boundary(len) {
+                len -= 1;
+            }
+            diff.truncate(len);
+        }
+
+        let context = CommitMessagePromptContext {
+            diff,
+            is_truncated,
+            includes_unstaged_changes: scope == CommitMessageScope::AllChanges,
+            branch_name,
+            style_guide,
+            conventional_commits,
+        };
+        self.handlebars.lock().render("commit_message", &context)
+    }
+
     pub fn generate_suggest_edits_prompt(&self) -> Result<String, RenderError> {
         self.handlebars.lock().render("suggest_edits", &())
     }
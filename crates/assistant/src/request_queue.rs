@@ -0,0 +1,303 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use anyhow::Result;
+use collections::HashMap;
+use futures::{stream::BoxStream, Stream, StreamExt};
+use gpui::{AnyElement, AppContext, AsyncAppContext, Global, Model};
+use language_model::{
+    is_retryable_error, retry_after, LanguageModel, LanguageModelCompletionEvent,
+    LanguageModelProviderId, LanguageModelRequest, LanguageModelTextStream,
+    MAX_CONCURRENT_REQUESTS_PER_MODEL,
+};
+use smol::lock::{Semaphore, SemaphoreGuardArc};
+use ui::{prelude::*, Tooltip};
+
+/// How many requests each source may have in flight to a single provider at once.
+///
+/// Models allow [`MAX_CONCURRENT_REQUESTS_PER_MODEL`] requests in flight, so the sources split that
+/// between them: otherwise a burst from one source would fill up the model's limit and the other
+/// source would wait behind it there.
+const MAX_CONCURRENT_REQUESTS: usize = MAX_CONCURRENT_REQUESTS_PER_MODEL / RequestSource::ALL.len();
+const MAX_ATTEMPTS: usize = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Where a request comes from. Each source gets its own concurrency limit per provider, so that
+/// a burst of inline assists can't starve the assistant panel, or vice versa.
+///
+/// Requests that don't go through the queue, like context summaries, still share the model's limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestSource {
+    Panel,
+    InlineAssist,
+}
+
+impl RequestSource {
+    const ALL: [Self; 2] = [Self::Panel, Self::InlineAssist];
+}
+
+struct GlobalRequestQueue(Model<RequestQueue>);
+
+impl Global for GlobalRequestQueue {}
+
+/// Sends completion requests to language models, waiting for a free slot when too many requests
+/// are in flight and retrying the ones that are rate limited or fail temporarily.
+///
+/// Observe the queue to know when the number of queued requests changes.
+#[derive(Default)]
+pub struct RequestQueue {
+    slots: HashMap<(LanguageModelProviderId, RequestSource), Arc<Semaphore>>,
+    queued: HashMap<RequestSource, Arc<AtomicUsize>>,
+}
+
+impl RequestQueue {
+    pub fn global(cx: &mut AppContext) -> Model<Self> {
+        if let Some(queue) = cx.try_global::<GlobalRequestQueue>() {
+            return queue.0.clone();
+        }
+        let queue = cx.new_model(|_| Self::default());
+        cx.set_global(GlobalRequestQueue(queue.clone()));
+        queue
+    }
+
+    /// Returns how many requests from the given source are waiting for a slot or for a retry.
+    pub fn queued_count(source: RequestSource, cx: &AppContext) -> usize {
+        cx.try_global::<GlobalRequestQueue>()
+            .and_then(|queue| queue.0.read(cx).queued.get(&source))
+            .map_or(0, |queued| queued.load(SeqCst))
+    }
+
+    pub async fn stream_completion(
+        model: Arc<dyn LanguageModel>,
+        request: LanguageModelRequest,
+        source: RequestSource,
+        cx: AsyncAppContext,
+    ) -> Result<BoxStream<'static, Result<LanguageModelCompletionEvent>>> {
+        let (stream, slot) = Self::send(model.provider_id(), source, &cx, || {
+            model.stream_completion(request.clone(), &cx)
+        })
+        .await?;
+        Ok(SlotStream {
+            inner: stream,
+            _slot: slot,
+        }
+        .boxed())
+    }
+
+    pub async fn stream_completion_text(
+        model: Arc<dyn LanguageModel>,
+        request: LanguageModelRequest,
+        source: RequestSource,
+        cx: AsyncAppContext,
+    ) -> Result<LanguageModelTextStream> {
+        let (response, slot) = Self::send(model.provider_id(), source, &cx, || {
+            model.stream_completion_text(request.clone(), &cx)
+        })
+        .await?;
+        Ok(LanguageModelTextStream {
+            message_id: response.message_id,
+            stream: SlotStream {
+                inner: response.stream,
+                _slot: slot,
+            }
+            .boxed(),
+        })
+    }
+
+    async fn send<T, F, Fut>(
+        provider_id: LanguageModelProviderId,
+        source: RequestSource,
+        cx: &AsyncAppContext,
+        mut send_request: F,
+    ) -> Result<(T, SemaphoreGuardArc)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let (queue, slots, queued) = cx.update(|cx| {
+            let queue = Self::global(cx);
+            let (slots, queued) = queue.update(cx, |queue, _| {
+                let slots = queue
+                    .slots
+                    .entry((provider_id.clone(), source))
+                    .or_insert_with(|| Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)))
+                    .clone();
+                let queued = queue.queued.entry(source).or_default().clone();
+                (slots, queued)
+            });
+            (queue, slots, queued)
+        })?;
+
+        let slot = {
+            let _queued = QueuedGuard::new(queued.clone(), queue.clone(), cx);
+            slots.acquire_arc().await
+        };
+
+        let mut attempt = 1;
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        loop {
+            match send_request().await {
+                Ok(response) => return Ok((response, slot)),
+                Err(error) if attempt < MAX_ATTEMPTS && is_retryable_error(&error) => {
+                    let delay = retry_after(&error)
+                        .unwrap_or(retry_delay)
+                        .min(MAX_RETRY_DELAY);
+                    log::warn!(
+                        "retrying request to {provider_id} in {delay:?} after attempt {attempt} failed: {error:#}"
+                    );
+                    let _queued = QueuedGuard::new(queued.clone(), queue.clone(), cx);
+                    cx.background_executor().timer(delay).await;
+                    attempt += 1;
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Counts a request as queued for as long as it's alive, even if the request is dropped while
+/// it's waiting.
+struct QueuedGuard {
+    queued: Arc<AtomicUsize>,
+    queue: Model<RequestQueue>,
+    cx: AsyncAppContext,
+}
+
+impl QueuedGuard {
+    fn new(queued: Arc<AtomicUsize>, queue: Model<RequestQueue>, cx: &AsyncAppContext) -> Self {
+        let mut cx = cx.clone();
+        queued.fetch_add(1, SeqCst);
+        queue.update(&mut cx, |_, cx| cx.notify()).ok();
+        Self { queued, queue, cx }
+    }
+}
+
+impl Drop for QueuedGuard {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, SeqCst);
+        // Dropping can happen while the app is being updated, so notify observers afterwards.
+        let queue = self.queue.clone();
+        self.cx
+            .spawn(|mut cx| async move { queue.update(&mut cx, |_, cx| cx.notify()).ok() })
+            .detach();
+    }
+}
+
+/// Shows how many requests from the given source are waiting for a slot or for a retry, if any.
+pub fn render_queued_requests(source: RequestSource, cx: &WindowContext) -> Option<AnyElement> {
+    let queued_count = RequestQueue::queued_count(source, cx);
+    if queued_count == 0 {
+        return None;
+    }
+    let label = if queued_count == 1 {
+        "1 request queued".to_string()
+    } else {
+        format!("{queued_count} requests queued")
+    };
+    Some(
+        h_flex()
+            .id("queued-requests")
+            .gap_1()
+            .child(
+                Icon::new(IconName::CountdownTimer)
+                    .size(IconSize::Small)
+                    .color(Color::Warning),
+            )
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .tooltip(|cx| {
+                Tooltip::with_meta(
+                    "Waiting to Send",
+                    None,
+                    "Requests are retried when the provider is rate limiting or temporarily failing",
+                    cx,
+                )
+            })
+            .into_any_element(),
+    )
+}
+
+/// A response stream that holds onto its request's slot until the response is done.
+struct SlotStream<S> {
+    inner: S,
+    _slot: SemaphoreGuardArc,
+}
+
+impl<S: Stream + Unpin> Stream for SlotStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language_model::fake_provider::FakeLanguageModel;
+    use std::{cell::Cell, rc::Rc};
+
+    #[gpui::test]
+    async fn test_concurrency_limits(cx: &mut TestAppContext) {
+        let model = Arc::new(FakeLanguageModel::default());
+        let send = |ix: usize, source: RequestSource, cx: &mut TestAppContext| {
+            let model = model.clone();
+            // Requests need to differ so the fake model can tell them apart.
+            let request = LanguageModelRequest {
+                stop: vec![ix.to_string()],
+                ..Default::default()
+            };
+            cx.spawn(|cx| RequestQueue::stream_completion(model, request, source, cx))
+        };
+
+        let notifications = Rc::new(Cell::new(0));
+        cx.update(|cx| {
+            let notifications = notifications.clone();
+            cx.observe(&RequestQueue::global(cx), move |_, _| {
+                notifications.set(notifications.get() + 1)
+            })
+            .detach();
+        });
+
+        let mut panel_requests = (0..=MAX_CONCURRENT_REQUESTS)
+            .map(|ix| send(ix, RequestSource::Panel, cx))
+            .collect::<Vec<_>>();
+        cx.run_until_parked();
+        assert_eq!(model.completion_count(), MAX_CONCURRENT_REQUESTS);
+        cx.update(|cx| assert_eq!(RequestQueue::queued_count(RequestSource::Panel, cx), 1));
+        assert!(
+            notifications.get() > 0,
+            "observers should be notified when requests are queued"
+        );
+
+        // Inline assists don't wait for the panel's requests.
+        let _inline_request = send(100, RequestSource::InlineAssist, cx);
+        cx.run_until_parked();
+        assert_eq!(model.completion_count(), MAX_CONCURRENT_REQUESTS + 1);
+
+        // Finishing a response lets the queued request through.
+        let first_request = model.pending_completions()[0].clone();
+        let first_response = panel_requests.remove(0).await.unwrap();
+        model.end_completion_stream(&first_request);
+        drop(first_response);
+        cx.run_until_parked();
+        assert_eq!(model.completion_count(), MAX_CONCURRENT_REQUESTS + 1);
+        cx.update(|cx| assert_eq!(RequestQueue::queued_count(RequestSource::Panel, cx), 0));
+
+        // Both sources together stay within the model's own limit, so neither waits behind the
+        // other there.
+        assert!(
+            MAX_CONCURRENT_REQUESTS * RequestSource::ALL.len() <= MAX_CONCURRENT_REQUESTS_PER_MODEL
+        );
+    }
+}
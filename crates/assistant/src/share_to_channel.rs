@@ -0,0 +1,546 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Result;
+use channel::{Channel, ChannelStore, MessageParams};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, View, WeakView,
+};
+use language::OffsetRangeExt as _;
+use language_model::Role;
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{
+    notifications::{DetachAndPromptErr, NotificationId},
+    ModalView, Toast, Workspace,
+};
+
+use crate::Context;
+
+/// Channel chat messages can't be longer than 1024 characters, so the conversation's title is
+/// shortened in the message that links to it.
+const MAX_CHAT_TITLE_LEN: usize = 200;
+
+/// A conversation, or part of one, rendered as Markdown so that it can be appended to a channel's
+/// notes, where teammates can read it and pick it up in their own assistant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedConversation {
+    pub title: String,
+    pub markdown: String,
+    /// Whether the shared messages contain the output of slash commands, such as the contents of
+    /// files, which the user should look over before sharing.
+    pub includes_command_output: bool,
+}
+
+impl SharedConversation {
+    /// Renders the messages that intersect the selection, or every message when nothing is
+    /// selected. Returns `None` when none of those messages have any text.
+    pub fn new(
+        context: &Context,
+        selection: Range<usize>,
+        author: Option<&str>,
+        cx: &AppContext,
+    ) -> Option<Self> {
+        let buffer = context.buffer().read(cx);
+        let command_output_ranges = context
+            .slash_command_output_sections()
+            .iter()
+            .filter(|section| section.is_valid(buffer))
+            .map(|section| section.range.to_offset(buffer))
+            .collect::<Vec<_>>();
+        let mut messages = String::new();
+        let mut includes_command_output = false;
+        for message in context.messages(cx) {
+            if !selection.is_empty()
+                && (message.offset_range.end <= selection.start
+                    || message.offset_range.start >= selection.end)
+            {
+                continue;
+            }
+            let text = buffer
+                .text_for_range(message.offset_range.clone())
+                .collect::<String>();
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            includes_command_output |= command_output_ranges.iter().any(|range| {
+                range.start < message.offset_range.end && range.end > message.offset_range.start
+            });
+            let speaker = match message.role {
+                Role::User => author.map_or("User".to_string(), |author| format!("@{author}")),
+                Role::Assistant => "Assistant".to_string(),
+                Role::System => "System".to_string(),
+            };
+            messages.push_str(&format!("**{speaker}:**\n\n{text}\n\n"));
+        }
+        if messages.is_empty() {
+            return None;
+        }
+
+        let title = context
+            .summary()
+            .map(|summary| summary.text.trim())
+            .filter(|title| !title.is_empty())
+            .unwrap_or("Assistant Conversation")
+            .to_string();
+        let mut attribution = match author {
+            Some(author) => format!("Shared by @{author} from the assistant"),
+            None => "Shared from the assistant".to_string(),
+        };
+        if let Some(model) = context.model(cx) {
+            attribution.push_str(&format!(", answered by {}", model.name().0));
+        }
+        let excerpt = if selection.is_empty() {
+            ""
+        } else {
+            " (excerpt)"
+        };
+
+        Some(Self {
+            markdown: format!("## {title}\n\n_{attribution}{excerpt}_\n\n{messages}"),
+            title,
+            includes_command_output,
+        })
+    }
+
+    /// Appends the conversation to the channel's notes and posts a link to it in the channel's
+    /// chat.
+    fn share(
+        self,
+        channel: Arc<Channel>,
+        workspace: WeakView<Workspace>,
+        cx: &mut AppContext,
+    ) -> Task<Result<()>> {
+        let channel_store = ChannelStore::global(cx);
+        let open_notes = channel_store.update(cx, |channel_store, cx| {
+            channel_store.open_channel_buffer(channel.id, cx)
+        });
+        let open_chat = channel_store.update(cx, |channel_store, cx| {
+            channel_store.open_channel_chat(channel.id, cx)
+        });
+        cx.spawn(|mut cx| async move {
+            let notes = open_notes.await?;
+            notes.update(&mut cx, |notes, cx| {
+                notes.buffer().update(cx, |buffer, cx| {
+                    let end = buffer.len();
+                    let trailing_newlines = buffer
+                        .reversed_chars_at(end)
+                        .take_while(|char| *char == '\n')
+                        .take(2)
+                        .count();
+                    let separator = if end == 0 {
+                        ""
+                    } else {
+                        &"\n\n"[trailing_newlines..]
+                    };
+                    buffer.edit(
+                        [(end..end, format!("{separator}{}", self.markdown))],
+                        None,
+                        cx,
+                    );
+                })
+            })?;
+
+            let link = cx.update(|cx| channel.notes_link(Some(self.title.clone()), cx))?;
+            let chat = open_chat.await?;
+            let text = chat_message(&self.title, &link);
+            chat.update(&mut cx, |chat, cx| {
+                chat.send_message(
+                    MessageParams {
+                        text,
+                        mentions: Vec::new(),
+                        reply_to_message_id: None,
+                    },
+                    cx,
+                )
+            })??
+            .await?;
+
+            workspace.update(&mut cx, |workspace, cx| {
+                struct SharedToChannelToast;
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<SharedToChannelToast>(),
+                        format!("Shared to #{}", channel.name),
+                    )
+                    .autohide(),
+                    cx,
+                );
+            })
+        })
+    }
+}
+
+fn chat_message(title: &str, link: &str) -> String {
+    let title = util::truncate_and_trailoff(title, MAX_CHAT_TITLE_LEN);
+    format!("Shared an assistant conversation: {title}\n{link}")
+}
+
+/// Picks the channel to share a conversation to, then shows what will be shared there before
+/// sharing it.
+pub struct ShareToChannelModal {
+    picker: View<Picker<ChannelPickerDelegate>>,
+    conversation: SharedConversation,
+    workspace: WeakView<Workspace>,
+    /// The picked channel, while the conversation is previewed before sharing it there.
+    channel_to_confirm: Option<Arc<Channel>>,
+    focus_handle: FocusHandle,
+    _subscription: Subscription,
+}
+
+impl ShareToChannelModal {
+    pub fn new(
+        conversation: SharedConversation,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = ChannelPickerDelegate {
+            modal: cx.view().downgrade(),
+            previewing: false,
+            channel_store: ChannelStore::global(cx),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            conversation,
+            workspace,
+            channel_to_confirm: None,
+            focus_handle: cx.focus_handle(),
+            _subscription,
+        }
+    }
+
+    fn preview(&mut self, channel: Arc<Channel>, cx: &mut ViewContext<Self>) {
+        self.channel_to_confirm = Some(channel);
+        self.focus_handle.focus(cx);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some(channel) = self.channel_to_confirm.take() else {
+            return;
+        };
+        self.conversation
+            .clone()
+            .share(channel, self.workspace.clone(), cx)
+            .detach_and_prompt_err("Failed to share conversation", cx, |_, _| None);
+        cx.emit(DismissEvent);
+    }
+
+    /// Goes back to picking a channel from the preview.
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.channel_to_confirm.take().is_some() {
+            self.picker.update(cx, |picker, cx| {
+                picker.delegate.previewing = false;
+                picker.focus(cx);
+            });
+            cx.notify();
+        } else {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn render_preview(&self, channel: &Channel, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("ShareToChannelPreview")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .elevation_3(cx)
+            .w(rems(34.))
+            .p_2()
+            .gap_2()
+            .child(
+                Headline::new(format!("Share to #{}", channel.name)).size(HeadlineSize::XSmall),
+            )
+            .when(self.conversation.includes_command_output, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Icon::new(IconName::Warning)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                        .child(
+                            Label::new(
+                                "This includes the output of slash commands, such as file contents.",
+                            )
+                            .size(LabelSize::Small),
+                        ),
+                )
+            })
+            .child(
+                div()
+                    .id("share-to-channel-preview")
+                    .max_h(rems(20.))
+                    .overflow_y_scroll()
+                    .p_2()
+                    .border_1()
+                    .rounded_md()
+                    .border_color(cx.theme().colors().border)
+                    .bg(cx.theme().colors().editor_background)
+                    .font_buffer(cx)
+                    .child(Label::new(self.conversation.markdown.clone()).size(LabelSize::Small)),
+            )
+            .child(
+                h_flex()
+                    .justify_end()
+                    .gap_1()
+                    .child(
+                        Button::new("back", "Back")
+                            .style(ButtonStyle::Subtle)
+                            .on_click(cx.listener(|this, _, cx| this.cancel(&menu::Cancel, cx))),
+                    )
+                    .child(
+                        Button::new("share", "Share")
+                            .style(ButtonStyle::Filled)
+                            .color(Color::Accent)
+                            .on_click(
+                                cx.listener(|this, _, cx| this.confirm(&menu::Confirm, cx)),
+                            ),
+                    ),
+            )
+    }
+}
+
+impl ModalView for ShareToChannelModal {}
+
+impl EventEmitter<DismissEvent> for ShareToChannelModal {}
+
+impl FocusableView for ShareToChannelModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        if self.channel_to_confirm.is_some() {
+            self.focus_handle.clone()
+        } else {
+            self.picker.focus_handle(cx)
+        }
+    }
+}
+
+impl Render for ShareToChannelModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if let Some(channel) = self.channel_to_confirm.clone() {
+            self.render_preview(&channel, cx).into_any_element()
+        } else {
+            v_flex()
+                .w(rems(34.))
+                .child(self.picker.clone())
+                .into_any_element()
+        }
+    }
+}
+
+struct ChannelMatch {
+    channel: Arc<Channel>,
+    depth: usize,
+    string_match: StringMatch,
+}
+
+pub struct ChannelPickerDelegate {
+    modal: WeakView<ShareToChannelModal>,
+    /// Whether the picked channel is being previewed, during which the picker losing focus
+    /// mustn't dismiss the modal.
+    previewing: bool,
+    channel_store: Model<ChannelStore>,
+    matches: Vec<ChannelMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for ChannelPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Share to channel...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No channels found. Sign in and join a channel to share conversations.".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let channels = self
+            .channel_store
+            .read(cx)
+            .ordered_channels()
+            .map(|(depth, channel)| (depth, channel.clone()))
+            .collect::<Vec<_>>();
+        let candidates = channels
+            .iter()
+            .enumerate()
+            .map(|(ix, (_, channel))| StringMatchCandidate::new(ix, &channel.name))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches
+                        .into_iter()
+                        .map(|string_match| {
+                            let (depth, channel) = channels[string_match.candidate_id].clone();
+                            ChannelMatch {
+                                channel,
+                                // Nesting only makes sense when every channel is listed.
+                                depth: if query.is_empty() { depth } else { 0 },
+                                string_match,
+                            }
+                        })
+                        .collect();
+                    delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(channel_match) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let channel = channel_match.channel.clone();
+        self.previewing = true;
+        self.modal
+            .update(cx, |modal, cx| modal.preview(channel, cx))
+            .log_err();
+    }
+
+    fn should_dismiss(&self) -> bool {
+        !self.previewing
+    }
+
+    fn dismissed(&mut self, _cx: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let channel_match = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .indent_level(channel_match.depth)
+                .start_slot(Icon::new(IconName::Hash).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    channel_match.channel.name.clone(),
+                    channel_match.string_match.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assistant_panel, prompts::PromptBuilder, MessageStatus, SlashCommandWorkingSet};
+    use assistant_tool::ToolWorkingSet;
+    use gpui::Context as _;
+    use language::{LanguageRegistry, ToOffset};
+    use language_model::LanguageModelRegistry;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    fn test_shared_conversation(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        LanguageModelRegistry::test(cx);
+        cx.set_global(settings_store);
+        assistant_panel::init(cx);
+        let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let context = cx.new_model(|cx| {
+            Context::local(
+                registry,
+                None,
+                None,
+                prompt_builder,
+                Arc::new(SlashCommandWorkingSet::default()),
+                Arc::new(ToolWorkingSet::default()),
+                cx,
+            )
+        });
+        let buffer = context.read(cx).buffer().clone();
+        let message_1 = context.read(cx).messages(cx).next().unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "How do I sort?")], None, cx)
+        });
+        let message_2 = context.update(cx, |context, cx| {
+            context
+                .insert_message_after(message_1.id, Role::Assistant, MessageStatus::Done, cx)
+                .unwrap()
+        });
+        let message_2_start = message_2.start.to_offset(buffer.read(cx));
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                [(message_2_start..message_2_start, "Call sort()")],
+                None,
+                cx,
+            )
+        });
+
+        assert_eq!(
+            SharedConversation::new(context.read(cx), 0..0, Some("alice"), cx),
+            Some(SharedConversation {
+                title: "Assistant Conversation".into(),
+                markdown: "## Assistant Conversation\n\n\
+                    _Shared by @alice from the assistant_\n\n\
+                    **@alice:**\n\nHow do I sort?\n\n\
+                    **Assistant:**\n\nCall sort()\n\n"
+                    .into(),
+                includes_command_output: false,
+            })
+        );
+
+        // Only the selected messages are shared.
+        let selection = message_2_start + 1..message_2_start + 2;
+        assert_eq!(
+            SharedConversation::new(context.read(cx), selection, None, cx)
+                .unwrap()
+                .markdown,
+            "## Assistant Conversation\n\n\
+            _Shared from the assistant (excerpt)_\n\n\
+            **Assistant:**\n\nCall sort()\n\n"
+        );
+    }
+}
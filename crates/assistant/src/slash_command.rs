@@ -5,7 +5,7 @@ use assistant_slash_command::AfterCompletion;
 pub use assistant_slash_command::{SlashCommand, SlashCommandOutput};
 use editor::{CompletionProvider, Editor};
 use fuzzy::{match_strings, StringMatchCandidate};
-use gpui::{AppContext, Model, Task, ViewContext, WeakView, WindowContext};
+use gpui::{AppContext, BackgroundExecutor, Model, Task, ViewContext, WeakView, WindowContext};
 use language::{Anchor, Buffer, CodeLabel, Documentation, HighlightId, LanguageServerId, ToPoint};
 use parking_lot::Mutex;
 use project::CompletionIntent;
@@ -24,12 +24,14 @@ use workspace::Workspace;
 pub mod auto_command;
 pub mod cargo_workspace_command;
 pub mod context_server_command;
+pub mod custom_command;
 pub mod default_command;
 pub mod delta_command;
 pub mod diagnostics_command;
 pub mod docs_command;
 pub mod fetch_command;
 pub mod file_command;
+pub mod kernel_command;
 pub mod now_command;
 pub mod project_command;
 pub mod prompt_command;
@@ -38,6 +40,7 @@ pub mod selection_command;
 pub mod streaming_example_command;
 pub mod symbols_command;
 pub mod tab_command;
+pub mod task_command;
 pub mod terminal_command;
 
 pub(crate) struct SlashCommandCompletionProvider {
@@ -418,6 +421,29 @@ impl SlashCommandLine {
     }
 }
 
+/// Fuzzy-matches argument candidates against the argument typed so far, best matches first.
+/// When nothing has been typed yet, every candidate is returned in its original order.
+pub(crate) async fn fuzzy_match_arguments(
+    candidates: Vec<String>,
+    query: &str,
+    cancel: &AtomicBool,
+    executor: BackgroundExecutor,
+) -> Vec<String> {
+    if query.is_empty() {
+        return candidates;
+    }
+    let candidates = candidates
+        .iter()
+        .enumerate()
+        .map(|(ix, candidate)| StringMatchCandidate::new(ix, candidate))
+        .collect::<Vec<_>>();
+    match_strings(&candidates, query, false, 100, cancel, executor)
+        .await
+        .into_iter()
+        .map(|string_match| string_match.string)
+        .collect()
+}
+
 pub fn create_label_for_command(
     command_name: &str,
     arguments: &[&str],
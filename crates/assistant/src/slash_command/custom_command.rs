@@ -0,0 +1,240 @@
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
+    SlashCommandRegistry, SlashCommandResult,
+};
+use collections::HashMap;
+use futures::{AsyncRead, AsyncReadExt as _, FutureExt as _};
+use gpui::{AppContext, Global, Task, WeakView};
+use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use settings::Settings;
+use ui::prelude::*;
+use workspace::Workspace;
+
+use super::create_label_for_command;
+use crate::slash_command_settings::{CustomSlashCommandSettings, SlashCommandSettings};
+
+/// How much of a failed command's error output is shown in the error.
+const MAX_ERROR_OUTPUT_BYTES: usize = 4096;
+
+/// A slash command defined in the settings, which runs a local command and inserts its output.
+pub(crate) struct CustomSlashCommand {
+    name: String,
+    settings: CustomSlashCommandSettings,
+}
+
+/// The custom slash commands that were registered from the settings, with their settings.
+#[derive(Default)]
+struct RegisteredCustomSlashCommands(HashMap<String, CustomSlashCommandSettings>);
+
+impl Global for RegisteredCustomSlashCommands {}
+
+/// Registers the custom slash commands from the settings, replacing the ones that were changed
+/// and unregistering the ones that were removed.
+pub(crate) fn update_custom_slash_commands(cx: &mut AppContext) {
+    let slash_command_registry = SlashCommandRegistry::global(cx);
+    let custom_commands = SlashCommandSettings::get_global(cx).custom.clone();
+    let registered = cx.default_global::<RegisteredCustomSlashCommands>();
+
+    registered.0.retain(|name, settings| {
+        let keep = custom_commands.get(name) == Some(settings);
+        if !keep {
+            slash_command_registry.unregister_command_by_name(name);
+        }
+        keep
+    });
+    for (name, settings) in custom_commands {
+        if registered.0.contains_key(&name) {
+            continue;
+        }
+        if slash_command_registry.command(&name).is_some() {
+            log::error!("custom slash command /{name} conflicts with a built-in slash command");
+            continue;
+        }
+        slash_command_registry.register_command(
+            CustomSlashCommand {
+                name: name.clone(),
+                settings: settings.clone(),
+            },
+            true,
+        );
+        registered.0.insert(name, settings);
+    }
+}
+
+impl SlashCommand for CustomSlashCommand {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn label(&self, cx: &AppContext) -> CodeLabel {
+        create_label_for_command(&self.name, &["[args]"], cx)
+    }
+
+    fn description(&self) -> String {
+        self.settings
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Insert the output of `{}`", self.settings.command))
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Terminal
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakView<Workspace>>,
+        _cx: &mut WindowContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let cwd = workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path());
+
+        let mut command = util::command::new_smol_command(&self.settings.command);
+        command
+            .args(&self.settings.args)
+            .args(arguments)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
+
+        let timeout = Duration::from_secs(self.settings.timeout_secs);
+        let timer = cx.background_executor().timer(timeout);
+        let max_output_bytes = self.settings.max_output_bytes;
+        let name = self.name.clone();
+        let command_name = self.settings.command.clone();
+        cx.background_executor().spawn(async move {
+            let mut child = command
+                .spawn()
+                .with_context(|| format!("failed to run `{command_name}`"))?;
+            let mut stdout = child.stdout.take().context("no stdout")?;
+            let mut stderr = child.stderr.take().context("no stderr")?;
+
+            let run = async {
+                // Read one byte more than allowed to know whether the output is cut off.
+                let (output, error_output) = futures::try_join!(
+                    read_capped(&mut stdout, max_output_bytes + 1),
+                    read_capped(&mut stderr, MAX_ERROR_OUTPUT_BYTES),
+                )?;
+                let status = child.status().await?;
+                anyhow::Ok((output, error_output, status))
+            };
+            let (mut output, error_output, status) = futures::select_biased! {
+                result = run.fuse() => result?,
+                _ = timer.fuse() => {
+                    return Err(anyhow!(
+                        "`{command_name}` did not finish within {} seconds",
+                        timeout.as_secs()
+                    ));
+                }
+            };
+            if !status.success() {
+                return Err(anyhow!(
+                    "`{command_name}` failed with {status}: {}",
+                    String::from_utf8_lossy(&error_output).trim()
+                ));
+            }
+            let truncated = output.len() > max_output_bytes;
+            output.truncate(max_output_bytes);
+
+            let mut text = String::from_utf8_lossy(&output).into_owned();
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            if truncated {
+                text.push_str(&format!("(output truncated to {max_output_bytes} bytes)\n"));
+            }
+
+            Ok(SlashCommandOutput {
+                sections: vec![SlashCommandOutputSection {
+                    range: 0..text.len(),
+                    icon: IconName::Terminal,
+                    label: format!("/{name}").into(),
+                    metadata: None,
+                }],
+                text,
+                run_commands_in_text: false,
+            }
+            .to_event_stream())
+        })
+    }
+}
+
+/// Reads up to `max_bytes` from the reader, and discards the rest so that the command does not
+/// block on a full pipe.
+async fn read_capped(
+    reader: &mut (impl AsyncRead + Unpin),
+    max_bytes: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    (&mut *reader)
+        .take(max_bytes as u64)
+        .read_to_end(&mut output)
+        .await?;
+    futures::io::copy(reader, &mut futures::io::sink()).await?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_capped() {
+        let mut reader = futures::io::Cursor::new(b"hello world".to_vec());
+        let output = read_capped(&mut reader, 5).await.unwrap();
+        assert_eq!(output, b"hello");
+        assert_eq!(
+            reader.position(),
+            11,
+            "The rest of the output should be read and discarded"
+        );
+
+        let mut reader = futures::io::Cursor::new(b"hi".to_vec());
+        let output = read_capped(&mut reader, 5).await.unwrap();
+        assert_eq!(output, b"hi");
+    }
+}
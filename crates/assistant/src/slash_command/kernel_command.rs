@@ -0,0 +1,210 @@
+use std::fmt::Write as _;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::Result;
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
+    SlashCommandResult,
+};
+use gpui::{AppContext, Task, WeakView};
+use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use repl::ExecutionRecord;
+use ui::prelude::*;
+use workspace::Workspace;
+
+use super::create_label_for_command;
+
+pub(crate) struct KernelSlashCommand;
+
+/// How many executions are inserted when no count is given.
+const DEFAULT_EXECUTION_COUNT: usize = 5;
+/// The most of an execution's output that is inserted, in bytes. Longer outputs keep their end.
+const MAX_OUTPUT_LEN: usize = 4096;
+
+impl SlashCommand for KernelSlashCommand {
+    fn name(&self) -> String {
+        "kernel".into()
+    }
+
+    fn label(&self, cx: &AppContext) -> CodeLabel {
+        create_label_for_command("kernel", &["[count]"], cx)
+    }
+
+    fn description(&self) -> String {
+        "Insert recent REPL inputs and outputs".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ReplNeutral
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakView<Workspace>>,
+        _cx: &mut WindowContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow::anyhow!("workspace was dropped")));
+        };
+
+        let Some(session) = repl::session_for_workspace(&workspace, cx) else {
+            return Task::ready(Err(anyhow::anyhow!("no REPL session")));
+        };
+
+        let execution_count = arguments
+            .first()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_EXECUTION_COUNT);
+
+        let session = session.read(cx);
+        let history = session.history(cx);
+        if history.is_empty() {
+            return Task::ready(Err(anyhow::anyhow!("nothing was run in the REPL session")));
+        }
+
+        let text = history_text(
+            &session.kernel_specification.name(),
+            &session.kernel_specification.language(),
+            &history[history.len().saturating_sub(execution_count)..],
+        );
+        let range = 0..text.len();
+
+        Task::ready(Ok(SlashCommandOutput {
+            text,
+            sections: vec![SlashCommandOutputSection {
+                range,
+                icon: IconName::ReplNeutral,
+                label: "REPL".into(),
+                metadata: None,
+            }],
+            run_commands_in_text: false,
+        }
+        .to_event_stream()))
+    }
+}
+
+/// The inputs and outputs of the executions, each in a code block.
+fn history_text(kernel_name: &str, language: &str, executions: &[ExecutionRecord]) -> String {
+    let mut text = format!("REPL session ({kernel_name}, {language} kernel):\n");
+    let fence_language = language.to_lowercase();
+    for execution in executions {
+        let code = execution.code.trim_end();
+        let fence = code_fence(code);
+        writeln!(text, "\nInput:\n{fence}{fence_language}\n{code}\n{fence}").ok();
+        if execution.outputs.is_empty() {
+            text.push_str("No output.\n");
+        } else {
+            let output = truncate_output(execution.outputs.join("\n"));
+            let fence = code_fence(&output);
+            writeln!(text, "Output:\n{fence}\n{output}\n{fence}").ok();
+        }
+    }
+    text
+}
+
+/// A fence longer than any run of backticks in the content, so that the content can't end the code block.
+fn code_fence(content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat((longest_run + 1).max(3))
+}
+
+fn truncate_output(output: String) -> String {
+    if output.len() <= MAX_OUTPUT_LEN {
+        return output;
+    }
+    let mut start = output.len() - MAX_OUTPUT_LEN;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!(
+        "[{start} bytes of earlier output omitted]\n{}",
+        &output[start..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution(code: &str, outputs: &[&str]) -> ExecutionRecord {
+        ExecutionRecord {
+            code: code.to_string(),
+            outputs: outputs.iter().map(|output| output.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_history_text() {
+        let text = history_text(
+            "python3",
+            "Python",
+            &[execution("print(1 + 1)\n", &["2"]), execution("x = 1", &[])],
+        );
+        assert_eq!(
+            text,
+            "REPL session (python3, Python kernel):\n\
+            \nInput:\n```python\nprint(1 + 1)\n```\n\
+            Output:\n```\n2\n```\n\
+            \nInput:\n```python\nx = 1\n```\n\
+            No output.\n"
+        );
+    }
+
+    #[test]
+    fn test_outputs_with_code_fences() {
+        let text = history_text(
+            "python3",
+            "Python",
+            &[execution(
+                "print(markdown)",
+                &["```rust\nfn main() {}\n```", "`````"],
+            )],
+        );
+        assert!(
+            text.ends_with("Output:\n``````\n```rust\nfn main() {}\n```\n`````\n``````\n"),
+            "unexpected text: {text}"
+        );
+        assert_eq!(code_fence("no backticks"), "```");
+        assert_eq!(code_fence("a ``` b ```` c"), "`````");
+    }
+
+    #[test]
+    fn test_long_outputs_are_truncated() {
+        let output = format!("{}{}", "é".repeat(MAX_OUTPUT_LEN), "the end");
+        let text = history_text("python3", "Python", &[execution("run()", &[&output])]);
+        assert!(
+            text.len() < MAX_OUTPUT_LEN + 200,
+            "text is too long: {}",
+            text.len()
+        );
+        assert!(text.contains("bytes of earlier output omitted]"));
+        assert!(text.contains("éthe end\n```\n"));
+    }
+}
@@ -1,17 +1,33 @@
+use super::fuzzy_match_arguments;
 use crate::prompt_library::PromptStore;
 use anyhow::{anyhow, Context, Result};
 use assistant_slash_command::{
     ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
     SlashCommandResult,
 };
-use gpui::{Task, WeakView};
+use editor::{Anchor, Editor};
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{AppContext, Task, View, WeakView};
 use language::{BufferSnapshot, LspAdapterDelegate};
-use std::sync::{atomic::AtomicBool, Arc};
+use multi_buffer::ToOffset;
+use std::{
+    ops::Range,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+};
 use ui::prelude::*;
-use workspace::Workspace;
+use workspace::{item::ItemHandle as _, Workspace};
 
 pub(crate) struct PromptSlashCommand;
 
+/// A prompt stored as a Markdown file in a project's `.zed/prompts` folder, titled after its
+/// file name.
+struct ProjectPrompt {
+    title: String,
+    path: PathBuf,
+}
+
 impl SlashCommand for PromptSlashCommand {
     fn name(&self) -> String {
         "prompt".into()
@@ -36,24 +52,45 @@ impl SlashCommand for PromptSlashCommand {
     fn complete_argument(
         self: Arc<Self>,
         arguments: &[String],
-        _cancellation_flag: Arc<AtomicBool>,
-        _workspace: Option<WeakView<Workspace>>,
+        cancellation_flag: Arc<AtomicBool>,
+        workspace: Option<WeakView<Workspace>>,
         cx: &mut WindowContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
         let store = PromptStore::global(cx);
+        let project_prompts = workspace
+            .and_then(|workspace| workspace.upgrade())
+            .map(|workspace| project_prompts(workspace.read(cx), cx));
         let query = arguments.to_owned().join(" ");
+        let executor = cx.background_executor().clone();
         cx.background_executor().spawn(async move {
-            let prompts = store.await?.search(query).await;
-            Ok(prompts
+            let project_prompts = match project_prompts {
+                Some(project_prompts) => project_prompts.await,
+                None => Vec::new(),
+            };
+            let project_titles = fuzzy_match_arguments(
+                project_prompts
+                    .into_iter()
+                    .map(|prompt| prompt.title)
+                    .collect(),
+                &query,
+                &cancellation_flag,
+                executor,
+            )
+            .await;
+            let library_titles = store
+                .await?
+                .search(query)
+                .await
                 .into_iter()
-                .filter_map(|prompt| {
-                    let prompt_title = prompt.title?.to_string();
-                    Some(ArgumentCompletion {
-                        label: prompt_title.clone().into(),
-                        new_text: prompt_title,
-                        after_completion: true.into(),
-                        replace_previous_arguments: true,
-                    })
+                .filter_map(|prompt| Some(prompt.title?.to_string()));
+            Ok(project_titles
+                .into_iter()
+                .chain(library_titles)
+                .map(|prompt_title| ArgumentCompletion {
+                    label: prompt_title.clone().into(),
+                    new_text: prompt_title,
+                    after_completion: true.into(),
+                    replace_previous_arguments: true,
                 })
                 .collect())
         })
@@ -64,7 +101,7 @@ impl SlashCommand for PromptSlashCommand {
         arguments: &[String],
         _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
         _context_buffer: BufferSnapshot,
-        _workspace: WeakView<Workspace>,
+        workspace: WeakView<Workspace>,
         _delegate: Option<Arc<dyn LspAdapterDelegate>>,
         cx: &mut WindowContext,
     ) -> Task<SlashCommandResult> {
@@ -72,22 +109,15 @@ impl SlashCommand for PromptSlashCommand {
         if title.trim().is_empty() {
             return Task::ready(Err(anyhow!("missing prompt name")));
         };
-
-        let store = PromptStore::global(cx);
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let workspace = workspace.read(cx);
+        let variables = PromptVariables::for_active_editor(workspace, cx);
         let title = SharedString::from(title.clone());
-        let prompt = cx.background_executor().spawn({
-            let title = title.clone();
-            async move {
-                let store = store.await?;
-                let prompt_id = store
-                    .id_for_title(&title)
-                    .with_context(|| format!("no prompt found with title {:?}", title))?;
-                let body = store.load(prompt_id).await?;
-                anyhow::Ok(body)
-            }
-        });
+        let prompt = load_prompt(title.clone(), workspace, cx);
         cx.foreground_executor().spawn(async move {
-            let mut prompt = prompt.await?;
+            let mut prompt = variables.expand(&prompt.await?)?;
 
             if prompt.starts_with('/') {
                 // Prevent an edge case where the inserted prompt starts with a slash command (that leads to funky rendering).
@@ -111,3 +141,209 @@ impl SlashCommand for PromptSlashCommand {
         })
     }
 }
+
+/// Loads the prompt with the title given, from the project's prompts or else from the library.
+fn load_prompt(
+    title: SharedString,
+    workspace: &Workspace,
+    cx: &AppContext,
+) -> Task<Result<String>> {
+    let project_prompts = project_prompts(workspace, cx);
+    let fs = workspace.project().read(cx).fs().clone();
+    let store = PromptStore::global(cx);
+    cx.background_executor().spawn(async move {
+        // Prompts in the project take precedence over the ones in the library.
+        if let Some(project_prompt) = project_prompts
+            .await
+            .into_iter()
+            .find(|prompt| prompt.title == title.as_ref())
+        {
+            return fs.load(&project_prompt.path).await;
+        }
+        let store = store.await?;
+        let prompt_id = store
+            .id_for_title(&title)
+            .with_context(|| format!("no prompt found with title {:?}", title))?;
+        store.load(prompt_id).await
+    })
+}
+
+/// For an inline assist prompt of the form `/prompt <title>`, loads the prompt with that title,
+/// with its variables expanded for the assisted range of the editor. Other prompts are sent as typed.
+pub(crate) fn expand_inline_assist_prompt(
+    user_prompt: &str,
+    workspace: &View<Workspace>,
+    editor: &View<Editor>,
+    range: Range<Anchor>,
+    cx: &AppContext,
+) -> Option<Task<Result<String>>> {
+    let title = user_prompt.trim().strip_prefix("/prompt ")?.trim();
+    let variables = PromptVariables::for_editor_range(editor, range, cx);
+    let prompt = load_prompt(title.to_string().into(), workspace.read(cx), cx);
+    Some(
+        cx.foreground_executor()
+            .spawn(async move { variables.expand(&prompt.await?) }),
+    )
+}
+
+/// The prompts in the `.zed/prompts` folder of the project's local worktrees.
+fn project_prompts(workspace: &Workspace, cx: &AppContext) -> Task<Vec<ProjectPrompt>> {
+    let project = workspace.project().read(cx);
+    let fs = project.fs().clone();
+    let prompt_dirs = project
+        .visible_worktrees(cx)
+        .filter(|worktree| worktree.read(cx).is_local())
+        .map(|worktree| {
+            worktree
+                .read(cx)
+                .abs_path()
+                .join(paths::local_prompts_folder_relative_path())
+        })
+        .collect::<Vec<_>>();
+    cx.background_executor().spawn(async move {
+        let mut prompts = Vec::new();
+        for prompt_dir in prompt_dirs {
+            prompts.extend(prompts_in_dir(fs.as_ref(), prompt_dir).await);
+        }
+        prompts.sort_by(|a, b| a.title.cmp(&b.title));
+        prompts
+    })
+}
+
+async fn prompts_in_dir(fs: &dyn Fs, prompt_dir: PathBuf) -> Vec<ProjectPrompt> {
+    let Ok(mut entries) = fs.read_dir(&prompt_dir).await else {
+        return Vec::new();
+    };
+    let mut prompts = Vec::new();
+    while let Some(path) = entries.next().await {
+        let Ok(path) = path else {
+            continue;
+        };
+        if path.extension().map_or(true, |extension| extension != "md")
+            || path.ends_with(paths::local_commit_message_style_guide_relative_path())
+        {
+            continue;
+        }
+        if let Some(title) = path.file_stem() {
+            prompts.push(ProjectPrompt {
+                title: title.to_string_lossy().into_owned(),
+                path,
+            });
+        }
+    }
+    prompts
+}
+
+/// The values of the variables that prompts can refer to.
+#[derive(Default)]
+struct PromptVariables {
+    /// The text of the newest selection in the active editor, for `{selection}`.
+    selection: Option<String>,
+    /// The path of the file open in the active editor, for `{file}`.
+    file: Option<String>,
+}
+
+impl PromptVariables {
+    fn for_active_editor(workspace: &Workspace, cx: &AppContext) -> Self {
+        let Some(active_item) = workspace.active_item(cx) else {
+            return Self::default();
+        };
+        match active_item.act_as::<Editor>(cx) {
+            Some(editor) => {
+                let selection = editor.read(cx).selections.newest_anchor();
+                let range = selection.start..selection.end;
+                Self::for_editor_range(&editor, range, cx)
+            }
+            None => Self {
+                selection: None,
+                file: active_item
+                    .project_path(cx)
+                    .map(|project_path| project_path.path.to_string_lossy().into_owned()),
+            },
+        }
+    }
+
+    fn for_editor_range(editor: &View<Editor>, range: Range<Anchor>, cx: &AppContext) -> Self {
+        let file = editor
+            .project_path(cx)
+            .map(|project_path| project_path.path.to_string_lossy().into_owned());
+        let buffer = editor.read(cx).buffer().read(cx).read(cx);
+        let range = range.start.to_offset(&buffer)..range.end.to_offset(&buffer);
+        let selection =
+            Some(buffer.text_for_range(range).collect::<String>()).filter(|text| !text.is_empty());
+        Self { selection, file }
+    }
+
+    /// Replaces the `{selection}` and `{file}` variables in the prompt, and `{{selection}}` and
+    /// `{{file}}` with the variables' literal names. Other text in braces is left as is, as
+    /// prompts often contain code.
+    fn expand(&self, prompt: &str) -> Result<String> {
+        let mut expanded = String::with_capacity(prompt.len());
+        let mut rest = prompt;
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(escaped) = ["{{selection}}", "{{file}}"]
+                .into_iter()
+                .find(|escaped| rest.starts_with(escaped))
+            {
+                expanded.push_str(&escaped[1..escaped.len() - 1]);
+                rest = &rest[escaped.len()..];
+                continue;
+            }
+            let (variable, value) = if rest.starts_with("{selection}") {
+                ("{selection}", &self.selection)
+            } else if rest.starts_with("{file}") {
+                ("{file}", &self.file)
+            } else {
+                expanded.push('{');
+                rest = &rest[1..];
+                continue;
+            };
+            let value = value.as_deref().with_context(|| match variable {
+                "{selection}" => "the prompt uses {selection}, but no text is selected",
+                _ => "the prompt uses {file}, but no file is open",
+            })?;
+            expanded.push_str(value);
+            rest = &rest[variable.len()..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_prompt_variables() {
+        let variables = PromptVariables {
+            selection: Some("fn main() {}".into()),
+            file: Some("src/main.rs".into()),
+        };
+        assert_eq!(
+            variables
+                .expand("Explain {selection} from {file}, not {other} or {}.")
+                .unwrap(),
+            "Explain fn main() {} from src/main.rs, not {other} or {}."
+        );
+
+        let variables = PromptVariables {
+            selection: None,
+            file: Some("src/main.rs".into()),
+        };
+        assert_eq!(
+            variables.expand("Review {file}").unwrap(),
+            "Review src/main.rs"
+        );
+        assert!(variables.expand("Review {selection}").is_err());
+        assert_eq!(
+            variables
+                .expand("Replace {{selection}} and {{file}} in {file}, keeping {{other}}")
+                .unwrap(),
+            "Replace {selection} and {file} in src/main.rs, keeping {{other}}",
+            "Doubled braces should escape the variables"
+        );
+    }
+}
@@ -1,16 +1,24 @@
 use anyhow::{anyhow, Context as _, Result};
 use assistant_slash_command::{
-    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
-    SlashCommandResult,
+    AfterCompletion, ArgumentCompletion, SlashCommand, SlashCommandOutput,
+    SlashCommandOutputSection, SlashCommandResult,
 };
 use editor::Editor;
-use gpui::{Task, WeakView};
-use language::{BufferSnapshot, LspAdapterDelegate};
+use gpui::{AppContext, Model, Task, WeakView};
+use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use project::{Project, Symbol};
+use project_symbols::{match_symbols, symbol_match_candidates};
+use std::fmt::Write as _;
 use std::sync::Arc;
 use std::{path::Path, sync::atomic::AtomicBool};
 use ui::{IconName, WindowContext};
 use workspace::Workspace;
 
+use super::create_label_for_command;
+
+/// How many matching project symbols are inserted or offered as completions.
+const MAX_PROJECT_SYMBOLS: usize = 100;
+
 pub(crate) struct OutlineSlashCommand;
 
 impl SlashCommand for OutlineSlashCommand {
@@ -18,8 +26,12 @@ impl SlashCommand for OutlineSlashCommand {
         "symbols".into()
     }
 
+    fn label(&self, cx: &AppContext) -> CodeLabel {
+        create_label_for_command("symbols", &["[query]"], cx)
+    }
+
     fn description(&self) -> String {
-        "Insert symbols for active tab".into()
+        "Insert symbols for active tab or matching project symbols".into()
     }
 
     fn icon(&self) -> IconName {
@@ -32,27 +44,53 @@ impl SlashCommand for OutlineSlashCommand {
 
     fn complete_argument(
         self: Arc<Self>,
-        _arguments: &[String],
-        _cancel: Arc<AtomicBool>,
-        _workspace: Option<WeakView<Workspace>>,
-        _cx: &mut WindowContext,
+        arguments: &[String],
+        cancel: Arc<AtomicBool>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut WindowContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
-        Task::ready(Err(anyhow!("this command does not require argument")))
+        let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let symbols = search_project_symbols(project, arguments.join(" "), cancel, cx);
+
+        cx.spawn(|_| async move {
+            Ok(symbols
+                .await?
+                .into_iter()
+                .map(|symbol| ArgumentCompletion {
+                    new_text: symbol.name.clone(),
+                    label: symbol.label,
+                    after_completion: AfterCompletion::Run,
+                    replace_previous_arguments: true,
+                })
+                .collect())
+        })
     }
 
     fn requires_argument(&self) -> bool {
         false
     }
 
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
     fn run(
         self: Arc<Self>,
-        _arguments: &[String],
+        arguments: &[String],
         _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
         _context_buffer: BufferSnapshot,
         workspace: WeakView<Workspace>,
         _delegate: Option<Arc<dyn LspAdapterDelegate>>,
         cx: &mut WindowContext,
     ) -> Task<SlashCommandResult> {
+        let query = arguments.join(" ");
+        if !query.is_empty() {
+            return run_project_symbols(query, workspace, cx);
+        }
+
         let output = workspace.update(cx, |workspace, cx| {
             let Some(active_item) = workspace.active_item(cx) else {
                 return Task::ready(Err(anyhow!("no active tab")));
@@ -97,3 +135,89 @@ impl SlashCommand for OutlineSlashCommand {
         output.unwrap_or_else(|error| Task::ready(Err(error)))
     }
 }
+
+/// Inserts the project symbols matching the query, with the location of each of them.
+fn run_project_symbols(
+    query: String,
+    workspace: WeakView<Workspace>,
+    cx: &mut WindowContext,
+) -> Task<SlashCommandResult> {
+    let Some(workspace) = workspace.upgrade() else {
+        return Task::ready(Err(anyhow!("workspace was dropped")));
+    };
+    let project = workspace.read(cx).project().clone();
+    let symbols = search_project_symbols(project.clone(), query.clone(), Arc::default(), cx);
+
+    cx.spawn(|mut cx| async move {
+        let symbols = symbols.await?;
+        if symbols.is_empty() {
+            return Err(anyhow!("no project symbols match {query:?}"));
+        }
+
+        let mut text = format!("Symbols matching {query:?}:\n");
+        project.read_with(&cx, |project, cx| {
+            for symbol in &symbols {
+                let path = project
+                    .worktree_for_id(symbol.path.worktree_id, cx)
+                    .map(|worktree| worktree.read(cx).root_name().to_string())
+                    .map_or_else(
+                        || symbol.path.path.to_path_buf(),
+                        |root_name| Path::new(&root_name).join(&symbol.path.path),
+                    );
+                writeln!(
+                    text,
+                    "- {} ({}:{})",
+                    symbol.label.text(),
+                    path.display(),
+                    symbol.range.start.0.row + 1
+                )
+                .ok();
+            }
+        })?;
+
+        Ok(SlashCommandOutput {
+            sections: vec![SlashCommandOutputSection {
+                range: 0..text.len(),
+                icon: IconName::ListTree,
+                label: format!("Symbols: {query}").into(),
+                metadata: None,
+            }],
+            text,
+            run_commands_in_text: false,
+        }
+        .to_event_stream())
+    })
+}
+
+/// Queries the language servers for the project symbols and fuzzy matches them like the project
+/// symbols picker does.
+fn search_project_symbols(
+    project: Model<Project>,
+    query: String,
+    cancel: Arc<AtomicBool>,
+    cx: &mut WindowContext,
+) -> Task<Result<Vec<Symbol>>> {
+    let symbols = project.update(cx, |project, cx| project.symbols(&query, cx));
+    cx.spawn(|mut cx| async move {
+        let symbols = symbols.await?;
+        let (visible_match_candidates, external_match_candidates) = project
+            .read_with(&cx, |project, cx| {
+                symbol_match_candidates(project, &symbols, cx)
+            })?;
+        let executor = cx.background_executor().clone();
+        let matches = match_symbols(
+            &symbols,
+            &visible_match_candidates,
+            &external_match_candidates,
+            &query,
+            MAX_PROJECT_SYMBOLS,
+            &cancel,
+            executor,
+        )
+        .await;
+        Ok(matches
+            .into_iter()
+            .map(|mat| symbols[mat.candidate_id].clone())
+            .collect())
+    })
+}
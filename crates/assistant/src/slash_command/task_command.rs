@@ -0,0 +1,444 @@
+use std::fmt::Write as _;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{
+    AfterCompletion, ArgumentCompletion, SlashCommand, SlashCommandOutput,
+    SlashCommandOutputSection, SlashCommandResult,
+};
+use assistant_tool::Tool;
+use editor::Editor;
+use futures::{channel::oneshot, FutureExt as _};
+use gpui::{AppContext, Task, View, WeakView};
+use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use project::TaskSourceKind;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::TaskTemplate;
+use terminal::TaskStatus;
+use ui::prelude::*;
+use workspace::{tasks::schedule_resolved_task, Workspace};
+
+use super::{create_label_for_command, fuzzy_match_arguments};
+
+pub(crate) struct TaskSlashCommand;
+
+/// How many of the last lines of the task's output are inserted.
+const MAX_OUTPUT_LINES: usize = 1000;
+/// How long to wait for the task's terminal to be spawned, after which the task is considered not
+/// started, e.g. when the terminal panel could not spawn it or it was already running.
+const TASK_START_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl SlashCommand for TaskSlashCommand {
+    fn name(&self) -> String {
+        "task".into()
+    }
+
+    fn label(&self, cx: &AppContext) -> CodeLabel {
+        create_label_for_command("task", &["[name]"], cx)
+    }
+
+    fn description(&self) -> String {
+        "Run a task and insert its output".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Play
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        arguments: &[String],
+        cancel: Arc<AtomicBool>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let query = arguments.join(" ");
+        let labels = available_tasks(workspace.read(cx), cx)
+            .into_iter()
+            .map(|(_, template)| template.label)
+            .collect();
+        let executor = cx.background_executor().clone();
+        cx.background_executor().spawn(async move {
+            Ok(fuzzy_match_arguments(labels, &query, &cancel, executor)
+                .await
+                .into_iter()
+                .map(|label| ArgumentCompletion {
+                    label: CodeLabel::plain(label.clone(), None),
+                    new_text: label,
+                    after_completion: AfterCompletion::Run,
+                    replace_previous_arguments: true,
+                })
+                .collect())
+        })
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let task_output = run_task(workspace, arguments.join(" "), cx);
+        cx.foreground_executor().spawn(async move {
+            let (label, text) = task_output.await?;
+            let range = 0..text.len();
+
+            Ok(SlashCommandOutput {
+                text,
+                sections: vec![SlashCommandOutputSection {
+                    range,
+                    icon: IconName::Play,
+                    label: format!("Task: {label}").into(),
+                    metadata: None,
+                }],
+                run_commands_in_text: false,
+            }
+            .to_event_stream())
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunTaskToolInput {
+    /// The name of the task to run, as listed in the tasks modal.
+    task_name: String,
+}
+
+/// A tool with which the model runs one of the project's tasks and reads its output.
+pub(crate) struct RunTaskTool;
+
+impl Tool for RunTaskTool {
+    fn name(&self) -> String {
+        "run_task".into()
+    }
+
+    fn description(&self) -> String {
+        "Runs one of the tasks defined for the project, like a build or its tests, and returns its exit status and the last lines of its output. Use this tool to check whether changes work.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RunTaskToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: RunTaskToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        if input.task_name.is_empty() {
+            return Task::ready(Err(anyhow!("missing task name")));
+        }
+        let task_output = run_task(workspace, input.task_name, cx);
+        cx.foreground_executor()
+            .spawn(async move { Ok(task_output.await?.1) })
+    }
+}
+
+/// Runs the task with the given name, or reruns the last task when the name is empty, and returns
+/// its label along with its status and output once it finishes.
+fn run_task(
+    workspace: View<Workspace>,
+    task_name: String,
+    cx: &mut WindowContext,
+) -> Task<Result<(String, String)>> {
+    let task_store = workspace.read(cx).project().read(cx).task_store().clone();
+    let Some(inventory) = task_store.read(cx).task_inventory().cloned() else {
+        return Task::ready(Err(anyhow!("tasks are not available in this project")));
+    };
+
+    // Without a name, the last task run is rerun as it was, like `task: rerun` does.
+    let task_to_run = if task_name.is_empty() {
+        let Some((task_source_kind, task)) = inventory.read(cx).last_scheduled_task(None) else {
+            return Task::ready(Err(anyhow!("no task was run yet")));
+        };
+        if task.original_task().password_inputs().next().is_some() {
+            return Task::ready(Err(anyhow!(
+                "the last task asks for password inputs, rerun it from the tasks modal"
+            )));
+        }
+        Task::ready(Ok((task_source_kind, task)))
+    } else {
+        let Some((task_source_kind, template)) = available_tasks(workspace.read(cx), cx)
+            .into_iter()
+            .find(|(_, template)| template.label == task_name)
+        else {
+            return Task::ready(Err(anyhow!("no task named {task_name:?}")));
+        };
+        if !template.inputs.is_empty() {
+            return Task::ready(Err(anyhow!(
+                "task {task_name:?} asks for inputs, spawn it from the tasks modal"
+            )));
+        }
+        let task_context = workspace.update(cx, |workspace, cx| {
+            editor::tasks::task_context(workspace, cx)
+        });
+        cx.spawn(|_| async move {
+            let task_context = task_context.await;
+            let task = template
+                .resolve_task(&task_source_kind.to_id_base(), &task_context)
+                .with_context(|| format!("could not resolve task {task_name:?}"))?;
+            anyhow::Ok((task_source_kind, task))
+        })
+    };
+
+    cx.spawn(|mut cx| async move {
+        let (task_source_kind, task) = task_to_run.await?;
+        let task_id = task.id.clone();
+        let label = task.resolved_label.clone();
+
+        // A run of the same task that was already going on is not the one to wait for.
+        let (terminal_tx, terminal_rx) = oneshot::channel();
+        let _spawned_terminal_subscription = cx.update(|cx| {
+            let previous_terminal = task_store
+                .read(cx)
+                .running_task(&task_id)
+                .map(|running_task| running_task.terminal.entity_id());
+            let mut terminal_tx = Some(terminal_tx);
+            let task_id = task_id.clone();
+            cx.observe(&task_store, move |task_store, cx| {
+                let terminal = task_store
+                    .read(cx)
+                    .running_task(&task_id)
+                    .filter(|running_task| {
+                        Some(running_task.terminal.entity_id()) != previous_terminal
+                    })
+                    .map(|running_task| running_task.terminal.clone());
+                if let Some(terminal) = terminal {
+                    if let Some(terminal_tx) = terminal_tx.take() {
+                        terminal_tx.send(terminal).ok();
+                    }
+                }
+            })
+        })?;
+        workspace.update(&mut cx, |workspace, cx| {
+            schedule_resolved_task(workspace, task_source_kind, task, false, cx)
+        })?;
+
+        let mut timeout = cx.background_executor().timer(TASK_START_TIMEOUT).fuse();
+        let terminal = futures::select_biased! {
+            terminal = terminal_rx.fuse() => terminal.ok(),
+            _ = timeout => None,
+        }
+        .with_context(|| format!("task {label:?} was not started"))?;
+        // The terminal is only held weakly, so that closing it while the task runs stops the wait.
+        terminal
+            .read_with(&cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+            .with_context(|| format!("the terminal of task {label:?} was closed"))?
+            .await;
+
+        let text = terminal.read_with(&cx, |terminal, _| {
+            let mut text = format!("Task: {label}\n");
+            if let Some(task) = terminal.task() {
+                let status = match (task.status, task.exit_code) {
+                    (TaskStatus::Completed { .. }, Some(exit_code)) => {
+                        format!("Exit code: {exit_code}")
+                    }
+                    (TaskStatus::Completed { success: true }, None) => "Succeeded".to_string(),
+                    (TaskStatus::Completed { success: false }, None) => "Failed".to_string(),
+                    (TaskStatus::Running | TaskStatus::Unknown, _) => "Did not finish".to_string(),
+                };
+                writeln!(text, "Command: {}\n{status}", task.command_label).ok();
+            }
+            let output = terminal.last_n_lines(MAX_OUTPUT_LINES);
+            if output.is_empty() {
+                text.push_str("No output.\n");
+            } else {
+                writeln!(text, "Output:\n```\n{}\n```", output.join("\n")).ok();
+            }
+            text
+        })?;
+        Ok((label, text))
+    })
+}
+
+/// The tasks available for the active item, as listed in the tasks modal.
+fn available_tasks(workspace: &Workspace, cx: &AppContext) -> Vec<(TaskSourceKind, TaskTemplate)> {
+    let Some(inventory) = workspace
+        .project()
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .cloned()
+    else {
+        return Vec::new();
+    };
+    let active_item = workspace.active_item(cx);
+    let worktree = active_item
+        .as_ref()
+        .and_then(|item| item.project_path(cx))
+        .map(|path| path.worktree_id);
+    let (file, language) = active_item
+        .and_then(|item| item.act_as::<Editor>(cx))
+        .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        .map(|buffer| {
+            let buffer = buffer.read(cx);
+            (buffer.file().cloned(), buffer.language().cloned())
+        })
+        .unwrap_or_default();
+    inventory.read(cx).list_tasks(file, language, worktree, cx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    async fn open_workspace(cx: &mut TestAppContext) -> (View<Workspace>, &mut VisualTestContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        { "label": "build", "command": "cargo build" },
+                        {
+                            "label": "deploy",
+                            "command": "deploy",
+                            "inputs": [{ "env": "TARGET" }]
+                        }
+                    ]"#,
+                },
+                "a.rs": "fn main() {}"
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.rs"), true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        (workspace, cx)
+    }
+
+    #[gpui::test]
+    async fn test_task_argument_completions(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = open_workspace(cx).await;
+        let complete = |query: &str, cx: &mut VisualTestContext| {
+            let arguments = query
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            let workspace = workspace.downgrade();
+            cx.update(|cx| {
+                Arc::new(TaskSlashCommand).complete_argument(
+                    &arguments,
+                    Arc::new(AtomicBool::new(false)),
+                    Some(workspace),
+                    cx,
+                )
+            })
+        };
+        let labels = |completions: Vec<ArgumentCompletion>| {
+            completions
+                .into_iter()
+                .map(|completion| completion.new_text)
+                .collect::<Vec<_>>()
+        };
+
+        // Without an argument, every task is offered.
+        let completions = complete("", cx).await.unwrap();
+        assert_eq!(labels(completions), vec!["build", "deploy"]);
+
+        // The argument is fuzzy-matched against the task labels.
+        let completions = complete("bld", cx).await.unwrap();
+        assert_eq!(labels(completions), vec!["build"]);
+        let completions = complete("DPL", cx).await.unwrap();
+        assert_eq!(labels(completions), vec!["deploy"]);
+        let completions = complete("test", cx).await.unwrap();
+        assert!(completions.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_tasks_that_cannot_run(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = open_workspace(cx).await;
+
+        let error = cx
+            .update(|cx| run_task(workspace.clone(), "test".to_string(), cx))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "no task named \"test\"");
+
+        let error = cx
+            .update(|cx| run_task(workspace.clone(), "deploy".to_string(), cx))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "task \"deploy\" asks for inputs, spawn it from the tasks modal"
+        );
+
+        let error = cx
+            .update(|cx| run_task(workspace.clone(), String::new(), cx))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "no task was run yet");
+    }
+
+    #[gpui::test]
+    async fn test_task_that_is_not_started(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = open_workspace(cx).await;
+
+        // Without a terminal panel, nothing spawns the task's terminal.
+        let task_output = cx.update(|cx| run_task(workspace.clone(), "build".to_string(), cx));
+        cx.run_until_parked();
+        cx.executor().advance_clock(TASK_START_TIMEOUT);
+        let error = task_output.await.unwrap_err();
+        assert_eq!(error.to_string(), "task \"build\" was not started");
+    }
+}
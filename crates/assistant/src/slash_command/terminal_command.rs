@@ -1,3 +1,4 @@
+use std::fmt::Write as _;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
@@ -81,15 +82,14 @@ impl SlashCommand for TerminalSlashCommand {
             .and_then(|s| s.parse::<usize>().ok())
             .unwrap_or(DEFAULT_CONTEXT_LINES);
 
-        let lines = active_terminal
-            .read(cx)
-            .model()
-            .read(cx)
-            .last_n_non_empty_lines(line_count);
+        let terminal = active_terminal.read(cx).model().read(cx);
+        let lines = terminal.last_n_lines(line_count);
 
-        let mut text = String::new();
-        text.push_str("Terminal output:\n");
-        text.push_str(&lines.join("\n"));
+        let mut text = match terminal.working_directory() {
+            Some(cwd) => format!("Terminal output (in {}):\n", cwd.display()),
+            None => "Terminal output:\n".to_string(),
+        };
+        writeln!(text, "```\n{}\n```", lines.join("\n")).ok();
         let range = 0..text.len();
 
         Task::ready(Ok(SlashCommandOutput {
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use collections::HashMap;
 use gpui::AppContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,10 @@ pub struct SlashCommandSettings {
     /// Settings for the `/cargo-workspace` slash command.
     #[serde(default)]
     pub cargo_workspace: CargoWorkspaceCommandSettings,
+    /// Custom slash commands that run a local command and insert what it prints,
+    /// keyed by the name of the slash command.
+    #[serde(default)]
+    pub custom: HashMap<String, CustomSlashCommandSettings>,
 }
 
 /// Settings for the `/docs` slash command.
@@ -31,6 +36,37 @@ pub struct CargoWorkspaceCommandSettings {
     pub enabled: bool,
 }
 
+/// Settings for a custom slash command.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct CustomSlashCommandSettings {
+    /// The command to run. The arguments typed after the slash command are appended to `args`.
+    pub command: String,
+    /// The arguments to pass to the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The description shown in the slash command menu.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How many seconds the command may run for before it is killed.
+    ///
+    /// Default: 10
+    #[serde(default = "default_custom_command_timeout_secs")]
+    pub timeout_secs: u64,
+    /// How many bytes of the command's output are inserted at most.
+    ///
+    /// Default: 65536
+    #[serde(default = "default_custom_command_max_output_bytes")]
+    pub max_output_bytes: usize,
+}
+
+fn default_custom_command_timeout_secs() -> u64 {
+    10
+}
+
+fn default_custom_command_max_output_bytes() -> usize {
+    64 * 1024
+}
+
 impl Settings for SlashCommandSettings {
     const KEY: Option<&'static str> = Some("slash_commands");
 
@@ -1,7 +1,9 @@
 use crate::assistant_settings::AssistantSettings;
 use crate::{
-    humanize_token_count, prompts::PromptBuilder, AssistantPanel, AssistantPanelEvent, RequestType,
-    DEFAULT_CONTEXT_LINES,
+    humanize_token_count,
+    prompts::PromptBuilder,
+    request_queue::{RequestQueue, RequestSource},
+    AssistantPanel, AssistantPanelEvent, RequestType, DEFAULT_CONTEXT_LINES,
 };
 use anyhow::{Context as _, Result};
 use client::telemetry::Telemetry;
@@ -28,8 +30,9 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use task::Shell;
 use telemetry_events::{AssistantEvent, AssistantKind, AssistantPhase};
-use terminal::Terminal;
+use terminal::{terminal_settings::TerminalSettings, Terminal};
 use terminal_view::TerminalView;
 use theme::ThemeSettings;
 use ui::{prelude::*, text_for_action, IconButtonShape, Tooltip};
@@ -197,15 +200,6 @@ impl TerminalInlineAssistant {
             self.prompt_history.pop_front();
         }
 
-        assist
-            .terminal
-            .update(cx, |terminal, cx| {
-                terminal
-                    .terminal()
-                    .update(cx, |terminal, _| terminal.input(CLEAR_INPUT.to_string()));
-            })
-            .log_err();
-
         let codegen = assist.codegen.clone();
         let Some(request) = self.request_for_inline_assist(assist_id, cx).log_err() else {
             return;
@@ -231,7 +225,10 @@ impl TerminalInlineAssistant {
     ) -> Result<LanguageModelRequest> {
         let assist = self.assists.get(&assist_id).context("invalid assist")?;
 
-        let shell = std::env::var("SHELL").ok();
+        let shell = match &TerminalSettings::get_global(cx).shell {
+            Shell::Program(program) | Shell::WithArguments { program, .. } => Some(program.clone()),
+            Shell::System => std::env::var("SHELL").ok(),
+        };
         let (latest_output, working_directory) = assist
             .terminal
             .update(cx, |terminal, cx| {
@@ -339,8 +336,8 @@ impl TerminalInlineAssistant {
             assist.codegen.update(cx, |codegen, cx| {
                 if undo {
                     codegen.undo(cx);
-                } else if execute {
-                    codegen.complete(cx);
+                } else {
+                    codegen.accept(execute, cx);
                 }
             });
         }
@@ -653,7 +650,12 @@ impl Render for PromptEditor {
                         },
                     ),
             )
-            .child(div().flex_1().child(self.render_prompt_editor(cx)))
+            .child(
+                v_flex()
+                    .flex_1()
+                    .child(self.render_prompt_editor(cx))
+                    .children(self.render_proposed_command(cx)),
+            )
             .child(
                 h_flex()
                     .gap_1()
@@ -761,7 +763,7 @@ impl PromptEditor {
     }
 
     fn count_lines(&mut self, cx: &mut ViewContext<Self>) {
-        let height_in_lines = cmp::max(
+        let mut height_in_lines = cmp::max(
             2, // Make the editor at least two lines tall, to account for padding and buttons.
             cmp::min(
                 self.editor
@@ -769,6 +771,9 @@ impl PromptEditor {
                 Self::MAX_LINES as u32,
             ),
         ) as u8;
+        if self.codegen.read(cx).proposed_command().is_some() {
+            height_in_lines += 1;
+        }
 
         if height_in_lines != self.height_in_lines {
             self.height_in_lines = height_in_lines;
@@ -838,6 +843,7 @@ impl PromptEditor {
     }
 
     fn handle_codegen_changed(&mut self, _: Model<Codegen>, cx: &mut ViewContext<Self>) {
+        self.count_lines(cx);
         match &self.codegen.read(cx).status {
             CodegenStatus::Idle => {
                 self.editor
@@ -992,6 +998,25 @@ impl PromptEditor {
         Some(token_count)
     }
 
+    fn render_proposed_command(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let command = self.codegen.read(cx).proposed_command()?;
+        let settings = ThemeSettings::get_global(cx);
+        Some(
+            h_flex()
+                .gap_2()
+                .font_family(settings.buffer_font.family.clone())
+                .child(Label::new("$").color(Color::Muted))
+                .child(
+                    div()
+                        .flex_1()
+                        .overflow_x_hidden()
+                        .whitespace_nowrap()
+                        .text_ellipsis()
+                        .child(Label::new(command.to_string())),
+                ),
+        )
+    }
+
     fn render_prompt_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let settings = ThemeSettings::get_global(cx);
         let text_style = TextStyle {
@@ -1029,31 +1054,32 @@ impl EventEmitter<CodegenEvent> for Codegen {}
 const CLEAR_INPUT: &str = "\x15";
 const CARRIAGE_RETURN: &str = "\x0d";
 
+/// The command proposed by the model, which is only written to the terminal once it's accepted.
 struct TerminalTransaction {
     terminal: Model<Terminal>,
+    command: String,
 }
 
 impl TerminalTransaction {
     pub fn start(terminal: Model<Terminal>) -> Self {
-        Self { terminal }
+        Self {
+            terminal,
+            command: String::new(),
+        }
     }
 
-    pub fn push(&mut self, hunk: String, cx: &mut AppContext) {
+    pub fn push(&mut self, hunk: String) {
         // Ensure that the assistant cannot accidentally execute commands that are streamed into the terminal
-        let input = Self::sanitize_input(hunk);
-        self.terminal
-            .update(cx, |terminal, _| terminal.input(input));
+        self.command.push_str(&Self::sanitize_input(hunk));
     }
 
-    pub fn undo(&self, cx: &mut AppContext) {
+    pub fn accept(&self, execute: bool, cx: &mut AppContext) {
+        let mut input = format!("{CLEAR_INPUT}{}", self.command.trim());
+        if execute {
+            input.push_str(CARRIAGE_RETURN);
+        }
         self.terminal
-            .update(cx, |terminal, _| terminal.input(CLEAR_INPUT.to_string()));
-    }
-
-    pub fn complete(&self, cx: &mut AppContext) {
-        self.terminal.update(cx, |terminal, _| {
-            terminal.input(CARRIAGE_RETURN.to_string())
-        });
+            .update(cx, |terminal, _| terminal.input(input));
     }
 
     fn sanitize_input(input: String) -> String {
@@ -1095,7 +1121,13 @@ impl Codegen {
         self.generation = cx.spawn(|this, mut cx| async move {
             let model_telemetry_id = model.telemetry_id();
             let model_provider_id = model.provider_id();
-            let response = model.stream_completion_text(prompt, &cx).await;
+            let response = RequestQueue::stream_completion_text(
+                model,
+                prompt,
+                RequestSource::InlineAssist,
+                cx.clone(),
+            )
+            .await;
             let generate = async {
                 let message_id = response
                     .as_ref()
@@ -1156,7 +1188,7 @@ impl Codegen {
                 while let Some(hunk) = hunks_rx.next().await {
                     this.update(&mut cx, |this, cx| {
                         if let Some(transaction) = &mut this.transaction {
-                            transaction.push(hunk, cx);
+                            transaction.push(hunk);
                             cx.notify();
                         }
                     })?;
@@ -1189,16 +1221,23 @@ impl Codegen {
         cx.notify();
     }
 
-    pub fn complete(&mut self, cx: &mut ModelContext<Self>) {
+    /// The command generated so far, which is shown for confirmation before it's written to
+    /// the terminal.
+    pub fn proposed_command(&self) -> Option<&str> {
+        let command = self.transaction.as_ref()?.command.trim();
+        (!command.is_empty()).then_some(command)
+    }
+
+    /// Writes the proposed command to the terminal's input, running it when `execute` is true.
+    pub fn accept(&mut self, execute: bool, cx: &mut ModelContext<Self>) {
         if let Some(transaction) = self.transaction.take() {
-            transaction.complete(cx);
+            transaction.accept(execute, cx);
         }
     }
 
     pub fn undo(&mut self, cx: &mut ModelContext<Self>) {
-        if let Some(transaction) = self.transaction.take() {
-            transaction.undo(cx);
-        }
+        self.transaction.take();
+        cx.notify();
     }
 }
 
@@ -0,0 +1,314 @@
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use audio::Recording;
+use gpui::{AppContext, EventEmitter, Model, ModelContext, MouseButton, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use ui::{prelude::*, IconButtonShape, Tooltip};
+
+use crate::ToggleVoiceInput;
+
+const OPENAI_API_KEY_VAR: &str = "OPENAI_API_KEY";
+/// Holding the voice input button down for longer than this records until it's released, while
+/// shorter clicks start and stop recording.
+const PUSH_TO_TALK_THRESHOLD: Duration = Duration::from_millis(300);
+
+/// Settings for transcribing spoken prompts.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct VoiceInputSettings {
+    /// The URL of an API that is compatible with OpenAI's `audio/transcriptions` endpoint, like
+    /// `https://api.openai.com/v1` or a local Whisper server. Voice input is off until it's set,
+    /// so that no audio is sent anywhere without asking for it.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// The model to transcribe with.
+    ///
+    /// Default: "whisper-1"
+    #[serde(default = "default_model")]
+    pub model: String,
+}
+
+fn default_model() -> String {
+    "whisper-1".into()
+}
+
+impl Settings for VoiceInputSettings {
+    const KEY: Option<&'static str> = Some("voice_input");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
+
+pub enum VoiceInputEvent {
+    Transcribed(String),
+}
+
+enum VoiceInputState {
+    Idle,
+    /// Waiting for the microphone to start recording, which may be asked to stop meanwhile.
+    Starting {
+        _task: Task<()>,
+        started_at: Instant,
+        stop_requested: bool,
+    },
+    Recording {
+        recording: Recording,
+        started_at: Instant,
+    },
+    Transcribing(Task<()>),
+}
+
+/// Records a spoken prompt from the microphone and transcribes it, so that the transcript can be
+/// reviewed in the prompt editor before it's sent.
+pub struct VoiceInput {
+    state: VoiceInputState,
+    last_error: Option<SharedString>,
+    /// The API key read from the credentials saved for an API URL, so that they're read once.
+    cached_api_key: Option<(String, Option<String>)>,
+    /// Whether the voice input button is held down.
+    pressed: bool,
+}
+
+impl EventEmitter<VoiceInputEvent> for VoiceInput {}
+
+impl VoiceInput {
+    pub fn new() -> Self {
+        Self {
+            state: VoiceInputState::Idle,
+            last_error: None,
+            cached_api_key: None,
+            pressed: false,
+        }
+    }
+
+    /// Starts recording, or stops recording and transcribes what was said.
+    pub fn toggle(&mut self, cx: &mut ModelContext<Self>) {
+        match self.state {
+            VoiceInputState::Idle => self.start(cx),
+            VoiceInputState::Starting { .. } | VoiceInputState::Recording { .. } => self.stop(cx),
+            VoiceInputState::Transcribing(_) => {}
+        }
+    }
+
+    fn press(&mut self, cx: &mut ModelContext<Self>) {
+        self.pressed = true;
+        self.toggle(cx);
+    }
+
+    /// Stops recording when the voice input button is released after being held down, to talk
+    /// while holding it.
+    fn release(&mut self, cx: &mut ModelContext<Self>) {
+        if !mem::take(&mut self.pressed) {
+            return;
+        }
+        let started_at = match &self.state {
+            VoiceInputState::Starting { started_at, .. }
+            | VoiceInputState::Recording { started_at, .. } => *started_at,
+            VoiceInputState::Idle | VoiceInputState::Transcribing(_) => return,
+        };
+        if started_at.elapsed() >= PUSH_TO_TALK_THRESHOLD {
+            self.stop(cx);
+        }
+    }
+
+    fn start(&mut self, cx: &mut ModelContext<Self>) {
+        if VoiceInputSettings::get_global(cx).api_url.is_none() {
+            self.last_error = Some(
+                "Voice input is off, set `voice_input.api_url` to a transcription API to use it"
+                    .into(),
+            );
+            cx.notify();
+            return;
+        }
+
+        let started_at = Instant::now();
+        let recording = Recording::start();
+        let task = cx.spawn(|this, mut cx| async move {
+            let recording = recording.await;
+            this.update(&mut cx, |this, cx| {
+                let VoiceInputState::Starting { stop_requested, .. } = this.state else {
+                    return;
+                };
+                this.state = match recording {
+                    Ok(recording) if stop_requested => {
+                        VoiceInputState::Transcribing(this.transcribe(recording, cx))
+                    }
+                    Ok(recording) => VoiceInputState::Recording {
+                        recording,
+                        started_at,
+                    },
+                    Err(error) => {
+                        this.last_error = Some(format!("{error:#}").into());
+                        VoiceInputState::Idle
+                    }
+                };
+                cx.notify();
+            })
+            .ok();
+        });
+        self.last_error = None;
+        self.state = VoiceInputState::Starting {
+            _task: task,
+            started_at,
+            stop_requested: false,
+        };
+        cx.notify();
+    }
+
+    fn stop(&mut self, cx: &mut ModelContext<Self>) {
+        self.state = match mem::replace(&mut self.state, VoiceInputState::Idle) {
+            VoiceInputState::Starting {
+                _task, started_at, ..
+            } => VoiceInputState::Starting {
+                _task,
+                started_at,
+                stop_requested: true,
+            },
+            VoiceInputState::Recording { recording, .. } => {
+                VoiceInputState::Transcribing(self.transcribe(recording, cx))
+            }
+            state => state,
+        };
+        cx.notify();
+    }
+
+    fn transcribe(&mut self, recording: Recording, cx: &mut ModelContext<Self>) -> Task<()> {
+        let wav = recording.finish();
+        let settings = VoiceInputSettings::get_global(cx).clone();
+        let Some(api_url) = settings.api_url else {
+            return Task::ready(());
+        };
+        let http_client = cx.http_client();
+        let cached_api_key = self
+            .cached_api_key
+            .as_ref()
+            .filter(|(url, _)| *url == api_url)
+            .map(|(_, api_key)| api_key.clone());
+        let credentials = cached_api_key
+            .is_none()
+            .then(|| cx.read_credentials(&api_url));
+        cx.spawn(|this, mut cx| async move {
+            // Local Whisper servers don't need an API key, so it's only sent when there is one.
+            let api_key = match std::env::var(OPENAI_API_KEY_VAR) {
+                Ok(api_key) => Some(api_key),
+                Err(_) => match (cached_api_key, credentials) {
+                    (Some(api_key), _) => api_key,
+                    (None, Some(credentials)) => {
+                        let api_key = credentials
+                            .await
+                            .ok()
+                            .flatten()
+                            .and_then(|(_, api_key)| String::from_utf8(api_key).ok());
+                        this.update(&mut cx, |this, _| {
+                            this.cached_api_key = Some((api_url.clone(), api_key.clone()));
+                        })
+                        .ok();
+                        api_key
+                    }
+                    (None, None) => None,
+                },
+            };
+            let transcript = open_ai::transcribe(
+                http_client.as_ref(),
+                &api_url,
+                api_key.as_deref(),
+                &settings.model,
+                wav,
+            )
+            .await;
+            this.update(&mut cx, |this, cx| {
+                this.state = VoiceInputState::Idle;
+                match transcript {
+                    Ok(transcript) if !transcript.is_empty() => {
+                        cx.emit(VoiceInputEvent::Transcribed(transcript))
+                    }
+                    Ok(_) => {}
+                    Err(error) => this.last_error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+}
+
+pub fn render_voice_input_button(
+    voice_input: &Model<VoiceInput>,
+    cx: &mut WindowContext,
+) -> impl IntoElement {
+    let (icon, color, tooltip) = match &voice_input.read(cx).state {
+        VoiceInputState::Idle => match voice_input.read(cx).last_error.clone() {
+            Some(error) => (IconName::Mic, Color::Error, error),
+            None => (IconName::Mic, Color::Muted, "Click or Hold to Talk".into()),
+        },
+        VoiceInputState::Starting { .. } | VoiceInputState::Recording { .. } => (
+            IconName::MicMute,
+            Color::Accent,
+            "Stop Recording and Transcribe".into(),
+        ),
+        VoiceInputState::Transcribing(_) => {
+            (IconName::Mic, Color::Disabled, "Transcribing…".into())
+        }
+    };
+    let voice_input = voice_input.clone();
+    // The button is clicked to start and stop recording, or held down to record until it's released.
+    div()
+        .on_mouse_down(MouseButton::Left, {
+            let voice_input = voice_input.clone();
+            move |_, cx| voice_input.update(cx, |voice_input, cx| voice_input.press(cx))
+        })
+        .on_mouse_up(MouseButton::Left, {
+            let voice_input = voice_input.clone();
+            move |_, cx| voice_input.update(cx, |voice_input, cx| voice_input.release(cx))
+        })
+        .on_mouse_up_out(MouseButton::Left, move |_, cx| {
+            voice_input.update(cx, |voice_input, cx| voice_input.release(cx))
+        })
+        .child(
+            IconButton::new("voice-input", icon)
+                .shape(IconButtonShape::Square)
+                .icon_color(color)
+                .tooltip(move |cx| Tooltip::for_action(tooltip.clone(), &ToggleVoiceInput, cx)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext};
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    fn test_voice_input_is_off_without_api_url(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            VoiceInputSettings::register(cx);
+        });
+
+        let voice_input = cx.new_model(|_| VoiceInput::new());
+        voice_input.update(cx, |voice_input, cx| voice_input.toggle(cx));
+        voice_input.read_with(cx, |voice_input, _| {
+            assert!(matches!(voice_input.state, VoiceInputState::Idle));
+            assert_eq!(
+                voice_input.last_error.as_deref(),
+                Some("Voice input is off, set `voice_input.api_url` to a transcription API to use it")
+            );
+        });
+    }
+}
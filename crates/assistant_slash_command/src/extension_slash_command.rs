@@ -32,7 +32,12 @@ impl ExtensionSlashCommandProxy for SlashCommandRegistryProxy {
         command: extension::SlashCommand,
     ) {
         self.slash_command_registry
-            .register_command(ExtensionSlashCommand::new(extension, command), false)
+            .register_command(ExtensionSlashCommand::new(extension, command), true)
+    }
+
+    fn unregister_slash_command(&self, command_name: Arc<str>) {
+        self.slash_command_registry
+            .unregister_command_by_name(&command_name)
     }
 }
 
@@ -93,6 +98,10 @@ impl SlashCommand for ExtensionSlashCommand {
         self.command.requires_argument
     }
 
+    fn accepts_arguments(&self) -> bool {
+        self.command.requires_argument || self.command.accepts_argument
+    }
+
     fn complete_argument(
         self: Arc<Self>,
         arguments: &[String],
@@ -1,13 +1,22 @@
+mod diagnostics_tool;
 mod now_tool;
+mod read_file_tool;
+mod search_tool;
 
 use assistant_tool::ToolRegistry;
 use gpui::AppContext;
 
+use crate::diagnostics_tool::DiagnosticsTool;
 use crate::now_tool::NowTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::search_tool::SearchTool;
 
 pub fn init(cx: &mut AppContext) {
     assistant_tool::init(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(SearchTool);
+    registry.register_tool(DiagnosticsTool);
 }
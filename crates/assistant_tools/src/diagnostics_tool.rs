@@ -0,0 +1,105 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::{DiagnosticSeverity, OffsetRangeExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The path of the file to list the errors and warnings of, starting with the name of one of
+    /// the project's root directories. When omitted, the number of errors and warnings in each
+    /// file of the project is returned.
+    pub path: Option<PathBuf>,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors and warnings that language servers report for the project, or for one of its files. Use this tool to find out why code does not compile or what needs to be fixed.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+
+        let Some(path) = input.path else {
+            let project = project.read(cx);
+            let mut output = String::new();
+            for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+                let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+                    continue;
+                };
+                let path = worktree.read(cx).root_name().to_string() + "/";
+                writeln!(
+                    output,
+                    "{}{}: {} errors, {} warnings",
+                    path,
+                    project_path.path.display(),
+                    summary.error_count,
+                    summary.warning_count
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output.push_str("The project has no errors or warnings.");
+            }
+            return Task::ready(Ok(output));
+        };
+
+        let Some(project_path) = project.read(cx).find_project_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("no file at {} in the project", path.display())));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+            let mut output = String::new();
+            for (_, group) in snapshot.diagnostic_groups(None) {
+                let entry = &group.entries[group.primary_ix];
+                let severity = match entry.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => "error",
+                    DiagnosticSeverity::WARNING => "warning",
+                    _ => continue,
+                };
+                let range = entry.range.to_point(&snapshot);
+                writeln!(
+                    output,
+                    "line {}: {severity}: {}",
+                    range.start.row + 1,
+                    entry.diagnostic.message
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output = format!("{} has no errors or warnings.", path.display());
+            }
+            Ok(output)
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file to read, starting with the name of one of the project's root
+    /// directories.
+    ///
+    /// For example, `zed/crates/editor/src/editor.rs` in a project with a `zed` root directory.
+    pub path: PathBuf,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the contents of a file in the project. Use this tool to look at code that the user refers to, when it is not already part of the conversation.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        if !is_project_relative(&input.path) {
+            return Task::ready(Err(anyhow!(
+                "{} is not a path in the project",
+                input.path.display()
+            )));
+        }
+        let project = workspace.read(cx).project().clone();
+        // Only read the files the project has entries for, rather than whatever the path leads to on disk.
+        let project_path = {
+            let project = project.read(cx);
+            project
+                .find_project_path(&input.path, cx)
+                .filter(|project_path| {
+                    project
+                        .entry_for_path(project_path, cx)
+                        .map_or(false, |entry| entry.is_file())
+                })
+        };
+        let Some(project_path) = project_path else {
+            return Task::ready(Err(anyhow!(
+                "no file at {} in the project",
+                input.path.display()
+            )));
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            buffer.read_with(&cx, |buffer, _| buffer.text())
+        })
+    }
+}
+
+/// Whether the path can only point inside of the project, rather than escaping
+/// its root directories with `..` or being absolute.
+fn is_project_relative(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_outside_of_the_project_are_rejected() {
+        assert!(is_project_relative(Path::new(
+            "zed/crates/editor/src/editor.rs"
+        )));
+        assert!(is_project_relative(Path::new("./zed/Cargo.toml")));
+
+        assert!(!is_project_relative(Path::new("zed/../../.ssh/id_rsa")));
+        assert!(!is_project_relative(Path::new("../secrets.txt")));
+        assert!(!is_project_relative(Path::new("/etc/passwd")));
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::{Point, ToPoint as _};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How many matching lines are returned at most, to keep the output within the context window.
+const MAX_MATCHES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchToolInput {
+    /// The regular expression to search the project's files for.
+    pub query: String,
+}
+
+pub struct SearchTool;
+
+impl Tool for SearchTool {
+    fn name(&self) -> String {
+        "search".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the files of the project for a regular expression, and returns the matching lines along with their paths and line numbers. Use this tool to find where something is defined or used.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SearchToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let query = match SearchQuery::regex(
+            &input.query,
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ) {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let results = workspace
+            .read(cx)
+            .project()
+            .update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            while let Ok(result) = results.recv().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    break;
+                };
+                buffer.read_with(&cx, |buffer, cx| {
+                    let Some(path) = buffer.file().map(|file| file.full_path(cx)) else {
+                        return;
+                    };
+                    let mut last_row = None;
+                    for range in ranges {
+                        let row = range.start.to_point(buffer).row;
+                        if last_row == Some(row) || match_count == MAX_MATCHES {
+                            continue;
+                        }
+                        last_row = Some(row);
+                        match_count += 1;
+                        let line_range = Point::new(row, 0)..Point::new(row, buffer.line_len(row));
+                        let line = buffer.text_for_range(line_range).collect::<String>();
+                        writeln!(output, "{}:{}: {}", path.display(), row + 1, line.trim()).ok();
+                    }
+                })?;
+                if match_count == MAX_MATCHES {
+                    break;
+                }
+            }
+
+            if match_count == 0 {
+                output = format!("No matches found for {:?}.", input.query);
+            } else if match_count == MAX_MATCHES {
+                output.push_str(&format!(
+                    "Only the first {MAX_MATCHES} matches are shown, \
+                    use a more specific query to see the others.\n"
+                ));
+            }
+            Ok(output)
+        })
+    }
+}
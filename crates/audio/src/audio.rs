@@ -5,6 +5,9 @@ use rodio::{OutputStream, OutputStreamHandle};
 use util::ResultExt;
 
 mod assets;
+mod recording;
+
+pub use recording::Recording;
 
 pub fn init(source: impl AssetSource, cx: &mut AppContext) {
     SoundRegistry::set_global(source, cx);
@@ -0,0 +1,147 @@
+use std::{future::Future, sync::Arc, thread};
+
+use anyhow::{anyhow, Context as _, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait as _};
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+
+/// A recording from the default microphone, which goes on until it's finished.
+pub struct Recording {
+    samples: Arc<Mutex<Vec<i16>>>,
+    sample_rate: u32,
+    channels: u16,
+    // Dropping the sender ends the thread that holds onto the input stream.
+    _stop_tx: std::sync::mpsc::Sender<()>,
+}
+
+impl Recording {
+    /// Starts recording on a thread of its own, as opening the input device can take a while.
+    pub fn start() -> impl Future<Output = Result<Self>> {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+        let (started_tx, started_rx) = oneshot::channel();
+
+        // Input streams can't be sent between threads on every platform, so the stream lives on
+        // its own thread until the recording is finished.
+        thread::spawn({
+            let samples = samples.clone();
+            move || {
+                let stream = match record_input(samples) {
+                    Ok((stream, sample_rate, channels)) => {
+                        started_tx.send(Ok((sample_rate, channels))).ok();
+                        stream
+                    }
+                    Err(error) => {
+                        started_tx.send(Err(error)).ok();
+                        return;
+                    }
+                };
+                stop_rx.recv().ok();
+                drop(stream);
+            }
+        });
+
+        async move {
+            let (sample_rate, channels) = started_rx
+                .await
+                .context("recording thread exited unexpectedly")??;
+            Ok(Self {
+                samples,
+                sample_rate,
+                channels,
+                _stop_tx: stop_tx,
+            })
+        }
+    }
+
+    /// Stops recording and returns what was recorded as a WAV file.
+    pub fn finish(self) -> Vec<u8> {
+        let samples = std::mem::take(&mut *self.samples.lock());
+        encode_wav(&samples, self.sample_rate, self.channels)
+    }
+}
+
+/// Starts recording from the default microphone into the given samples, returning the stream
+/// along with its sample rate and channel count.
+fn record_input(samples: Arc<Mutex<Vec<i16>>>) -> Result<(cpal::Stream, u32, u16)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| anyhow!("no audio input device available"))?;
+    let config = device
+        .default_input_config()
+        .context("failed to get default input config")?;
+    let on_error = |error| log::error!("error recording audio: {:?}", error);
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config.config(),
+            move |data: &[i16], _: &_| samples.lock().extend_from_slice(data),
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config.config(),
+            move |data: &[f32], _: &_| {
+                samples.lock().extend(
+                    data.iter()
+                        .map(|sample| (sample.clamp(-1., 1.) * i16::MAX as f32) as i16),
+                )
+            },
+            on_error,
+            None,
+        ),
+        sample_format => return Err(anyhow!("unsupported sample format {sample_format:?}")),
+    }
+    .context("failed to build input stream")?;
+    stream.play()?;
+    Ok((stream, config.sample_rate().0, config.channels()))
+}
+
+/// Encodes 16-bit PCM samples as a WAV file.
+fn encode_wav(samples: &[i16], sample_rate: u32, channels: u16) -> Vec<u8> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = channels * BITS_PER_SAMPLE / 8;
+    let data_len = (samples.len() * 2) as u32;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_wav() {
+        let wav = encode_wav(&[0, 1, -1, i16::MAX], 16_000, 2);
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        // PCM format, 2 channels at 16kHz, 4 bytes per frame.
+        assert_eq!(u16::from_le_bytes(wav[20..22].try_into().unwrap()), 1);
+        assert_eq!(u16::from_le_bytes(wav[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 16_000);
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 64_000);
+        assert_eq!(u16::from_le_bytes(wav[32..34].try_into().unwrap()), 4);
+        assert_eq!(u16::from_le_bytes(wav[34..36].try_into().unwrap()), 16);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 8);
+        assert_eq!(&wav[44..], &[0, 0, 1, 0, 0xff, 0xff, 0xff, 0x7f]);
+    }
+}
@@ -310,10 +310,12 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::OpenBufferByPath>)
             .add_request_handler(forward_read_only_project_request::<proto::GitBranches>)
             .add_request_handler(forward_read_only_project_request::<proto::GetStagedText>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetGuestTasks>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::RegisterBufferWithLanguageServers>,
             )
             .add_request_handler(forward_mutating_project_request::<proto::UpdateGitBranch>)
+            .add_request_handler(forward_mutating_project_request::<proto::RunGuestTask>)
             .add_request_handler(forward_mutating_project_request::<proto::GetCompletions>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::ApplyCompletionAdditionalEdits>,
@@ -379,6 +381,7 @@ impl Server {
             .add_request_handler(follow)
             .add_message_handler(unfollow)
             .add_message_handler(update_followers)
+            .add_message_handler(update_whiteboard)
             .add_request_handler(get_private_user_info)
             .add_request_handler(get_llm_api_token)
             .add_request_handler(accept_terms_of_service)
@@ -2397,6 +2400,26 @@ async fn update_followers(request: proto::UpdateFollowers, session: Session) ->
     Ok(())
 }
 
+/// Forwards whiteboard changes to the other participants in the room.
+async fn update_whiteboard(request: proto::UpdateWhiteboard, session: Session) -> Result<()> {
+    let room_id = RoomId::from_proto(request.room_id);
+    let connection_ids = session
+        .db()
+        .await
+        .room_connection_ids(room_id, session.connection_id)
+        .await?;
+    broadcast(
+        Some(session.connection_id),
+        connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    Ok(())
+}
+
 /// Get public data about users.
 async fn get_users(
     request: proto::GetUsers,
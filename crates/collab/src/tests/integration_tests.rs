@@ -10,6 +10,7 @@ use assistant::{ContextStore, PromptBuilder, SlashCommandWorkingSet};
 use assistant_tool::ToolWorkingSet;
 use call::{room, ActiveCall, ParticipantLocation, Room};
 use client::{User, RECEIVE_TIMEOUT};
+use collab_ui::guest_tasks::{AllowedTask, GuestTasks};
 use collections::{HashMap, HashSet};
 use fs::{FakeFs, Fs as _, RemoveOptions};
 use futures::{channel::mpsc, StreamExt as _};
@@ -33,6 +34,7 @@ use project::{
     HoverBlockKind, Project, ProjectPath,
 };
 use rand::prelude::*;
+use rpc::proto;
 use serde_json::json;
 use settings::SettingsStore;
 use std::{
@@ -6738,3 +6740,201 @@ async fn test_remote_git_branches(
 
     assert_eq!(host_branch.as_ref(), "totally-new-branch");
 }
+
+#[gpui::test]
+async fn test_guest_tasks(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree("/guest-tasks", json!({ "a.txt": "a" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project("/guest-tasks", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let _project_b = client_b.join_remote_project(project_id, cx_b).await;
+    // Guests' tasks run in the host's workspace.
+    let (_workspace_a, cx_a) = client_a.build_workspace(&project_a, cx_a);
+    executor.run_until_parked();
+
+    let guest_tasks_a = cx_a.update(|cx| GuestTasks::global(cx));
+    guest_tasks_a.update(cx_a, |guest_tasks, cx| {
+        guest_tasks.set_allowed(
+            &project_a,
+            AllowedTask::Command("echo hello".to_string()),
+            true,
+            cx,
+        )
+    });
+
+    let labels = client_b
+        .client()
+        .request(proto::GetGuestTasks { project_id })
+        .await
+        .unwrap()
+        .labels;
+    assert_eq!(labels, ["echo hello"]);
+
+    // The host runs the tasks it allowed...
+    client_b
+        .client()
+        .request(proto::RunGuestTask {
+            project_id,
+            label: "echo hello".to_string(),
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    project_a.read_with(cx_a, |project, cx| {
+        let inventory = project.task_store().read(cx).task_inventory().unwrap();
+        let (_, task) = inventory.read(cx).last_scheduled_task(None).unwrap();
+        assert_eq!(task.resolved_label, "echo hello");
+    });
+
+    // ...and refuses to run anything else.
+    let error = client_b
+        .client()
+        .request(proto::RunGuestTask {
+            project_id,
+            label: "rm -rf /".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("has not allowed"),
+        "unexpected error: {error}"
+    );
+    executor.run_until_parked();
+    project_a.read_with(cx_a, |project, cx| {
+        let inventory = project.task_store().read(cx).task_inventory().unwrap();
+        let (_, task) = inventory.read(cx).last_scheduled_task(None).unwrap();
+        assert_eq!(task.resolved_label, "echo hello");
+    });
+
+    // Both attempts are kept for the host to review.
+    guest_tasks_a.read_with(cx_a, |guest_tasks, _| {
+        let runs = guest_tasks
+            .runs(project_id)
+            .map(|run| {
+                (
+                    run.label.as_str(),
+                    run.allowed,
+                    run.guest.as_ref().map(|guest| guest.github_login.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runs,
+            [
+                ("rm -rf /", false, Some("user_b")),
+                ("echo hello", true, Some("user_b"))
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_guest_tasks_changed_after_approval(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/guest-tasks",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[{ "label": "build", "command": "echo", "args": ["build"] }]"#,
+                },
+                "a.txt": "a",
+            }),
+        )
+        .await;
+    let (project_a, _) = client_a.build_local_project("/guest-tasks", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let _project_b = client_b.join_remote_project(project_id, cx_b).await;
+    let (_workspace_a, cx_a) = client_a.build_workspace(&project_a, cx_a);
+    executor.run_until_parked();
+
+    let guest_tasks_a = cx_a.update(|cx| GuestTasks::global(cx));
+    let build_task = cx_a.update(|cx| {
+        AllowedTask::project_tasks(&project_a, cx)
+            .into_iter()
+            .find(|task| task.label() == "build")
+            .unwrap()
+    });
+    guest_tasks_a.update(cx_a, |guest_tasks, cx| {
+        guest_tasks.set_allowed(&project_a, build_task, true, cx)
+    });
+
+    client_b
+        .client()
+        .request(proto::RunGuestTask {
+            project_id,
+            label: "build".to_string(),
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    // Editing the task files must not change what the guests were allowed to run.
+    client_a
+        .fs()
+        .insert_file(
+            "/guest-tasks/.zed/tasks.json",
+            r#"[{ "label": "build", "command": "curl", "args": ["evil.sh"] }]"#.into(),
+        )
+        .await;
+    executor.run_until_parked();
+
+    let error = client_b
+        .client()
+        .request(proto::RunGuestTask {
+            project_id,
+            label: "build".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("has changed"),
+        "unexpected error: {error}"
+    );
+    executor.run_until_parked();
+    project_a.read_with(cx_a, |project, cx| {
+        let inventory = project.task_store().read(cx).task_inventory().unwrap();
+        let (_, task) = inventory.read(cx).last_scheduled_task(None).unwrap();
+        assert_eq!(task.original_task().command, "echo");
+    });
+
+    guest_tasks_a.read_with(cx_a, |guest_tasks, _| {
+        let runs = guest_tasks
+            .runs(project_id)
+            .map(|run| (run.label.as_str(), run.allowed))
+            .collect::<Vec<_>>();
+        assert_eq!(runs, [("build", false), ("build", true)]);
+    });
+}
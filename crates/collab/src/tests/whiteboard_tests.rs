@@ -0,0 +1,97 @@
+use crate::tests::TestServer;
+use call::ActiveCall;
+use collab_ui::whiteboard::{ElementKind, Whiteboard};
+use gpui::{point, BackgroundExecutor, Model, TestAppContext};
+
+#[gpui::test]
+async fn test_whiteboard_sync(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    executor.run_until_parked();
+
+    let whiteboard_a = cx_a.update(|cx| Whiteboard::global(cx));
+    let whiteboard_b = cx_b.update(|cx| Whiteboard::global(cx));
+    let room_id = whiteboard_a.read_with(cx_a, |whiteboard, _| whiteboard.room_id());
+    assert!(room_id.is_some());
+    assert_eq!(
+        whiteboard_b.read_with(cx_b, |whiteboard, _| whiteboard.room_id()),
+        room_id
+    );
+
+    // Elements drawn by either participant are added for the other one.
+    let stroke = ElementKind::Stroke(vec![point(0., 0.), point(10., 10.)]);
+    let text = ElementKind::Text(point(5., 5.), "hello".to_string());
+    let rectangle = ElementKind::Rectangle(point(20., 20.), point(40., 40.));
+    whiteboard_a.update(cx_a, |whiteboard, cx| {
+        whiteboard.add(stroke.clone(), cx);
+        whiteboard.add(text.clone(), cx);
+    });
+    executor.run_until_parked();
+    whiteboard_b.update(cx_b, |whiteboard, cx| whiteboard.add(rectangle.clone(), cx));
+    executor.run_until_parked();
+    let all_elements = [stroke.clone(), text.clone(), rectangle.clone()];
+    assert_eq!(element_kinds(&whiteboard_a, cx_a), all_elements);
+    assert_eq!(element_kinds(&whiteboard_b, cx_b), all_elements);
+
+    // Undoing removes the latest element of whoever undoes, for everyone.
+    whiteboard_a.update(cx_a, |whiteboard, cx| whiteboard.undo(cx));
+    executor.run_until_parked();
+    let remaining_elements = [stroke.clone(), rectangle.clone()];
+    assert_eq!(element_kinds(&whiteboard_a, cx_a), remaining_elements);
+    assert_eq!(element_kinds(&whiteboard_b, cx_b), remaining_elements);
+
+    // Participants joining later request the elements drawn so far.
+    server
+        .make_contacts(&mut [(&client_a, cx_a), (&client_c, cx_c)])
+        .await;
+    let user_id_c = client_c.current_user_id(cx_c).to_proto();
+    let active_call_a = cx_a.read(ActiveCall::global);
+    active_call_a
+        .update(cx_a, |call, cx| call.invite(user_id_c, None, cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let active_call_c = cx_c.read(ActiveCall::global);
+    active_call_c
+        .update(cx_c, |call, cx| call.accept_incoming(cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let whiteboard_c = cx_c.update(|cx| Whiteboard::global(cx));
+    assert_eq!(
+        whiteboard_c.read_with(cx_c, |whiteboard, _| whiteboard.room_id()),
+        room_id
+    );
+    assert_eq!(
+        element_kinds(&whiteboard_c, cx_c),
+        remaining_elements,
+        "elements sent by several participants should only be added once"
+    );
+
+    // Clearing removes every element, for everyone.
+    whiteboard_c.update(cx_c, |whiteboard, cx| whiteboard.clear(cx));
+    executor.run_until_parked();
+    assert!(element_kinds(&whiteboard_a, cx_a).is_empty());
+    assert!(element_kinds(&whiteboard_b, cx_b).is_empty());
+    assert!(element_kinds(&whiteboard_c, cx_c).is_empty());
+}
+
+fn element_kinds(whiteboard: &Model<Whiteboard>, cx: &mut TestAppContext) -> Vec<ElementKind> {
+    whiteboard.read_with(cx, |whiteboard, _| {
+        whiteboard
+            .elements()
+            .iter()
+            .map(|element| element.kind().clone())
+            .collect()
+    })
+}
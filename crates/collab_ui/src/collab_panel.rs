@@ -2,7 +2,12 @@ mod channel_modal;
 mod contact_finder;
 
 use self::channel_modal::ChannelModal;
-use crate::{channel_view::ChannelView, chat_panel::ChatPanel, CollaborationPanelSettings};
+use crate::{
+    channel_view::ChannelView,
+    chat_panel::ChatPanel,
+    guest_tasks::{self, GuestTaskRun, GuestTasks},
+    CollaborationPanelSettings,
+};
 use call::ActiveCall;
 use channel::{Channel, ChannelEvent, ChannelStore};
 use client::{ChannelId, Client, Contact, User, UserStore};
@@ -29,6 +34,7 @@ use settings::Settings;
 use smallvec::SmallVec;
 use std::{mem, sync::Arc};
 use theme::{ActiveTheme, ThemeSettings};
+use time::{OffsetDateTime, UtcOffset};
 use ui::{
     prelude::*, tooltip_container, Avatar, AvatarAvailabilityIndicator, Button, Color, ContextMenu,
     Facepile, Icon, IconButton, IconName, IconSize, Indicator, Label, ListHeader, ListItem,
@@ -61,6 +67,8 @@ struct ChannelMoveClipboard {
 }
 
 const COLLABORATION_PANEL_KEY: &str = "CollaborationPanel";
+/// How many of the guests' latest attempts to run tasks are listed.
+const MAX_LISTED_GUEST_TASK_RUNS: usize = 20;
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, _| {
@@ -138,6 +146,7 @@ struct SerializedCollabPanel {
 #[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord)]
 enum Section {
     ActiveCall,
+    GuestTasks,
     Channels,
     ChannelInvites,
     ContactRequests,
@@ -165,6 +174,7 @@ enum ListEntry {
         peer_id: Option<PeerId>,
         is_last: bool,
     },
+    GuestTaskRun(GuestTaskRun),
     IncomingRequest(Arc<User>),
     OutgoingRequest(Arc<User>),
     ChannelInvite(Arc<Channel>),
@@ -278,6 +288,10 @@ impl CollabPanel {
                 }));
             this.subscriptions
                 .push(cx.observe(&active_call, |this, _, cx| this.update_entries(true, cx)));
+            this.subscriptions
+                .push(cx.observe(&GuestTasks::global(cx), |this, _, cx| {
+                    this.update_entries(true, cx)
+                }));
             this.subscriptions.push(cx.subscribe(
                 &this.channel_store,
                 |this, _channel_store, e, cx| match e {
@@ -505,6 +519,24 @@ impl CollabPanel {
                         role: proto::ChannelRole::Member,
                     }));
             }
+
+            // List what guests ran in the project this panel's workspace shares.
+            let project = self.project.read(cx);
+            if let Some(project_id) = project.remote_id().filter(|_| project.is_local()) {
+                if query.is_empty() {
+                    self.entries.push(ListEntry::Header(Section::GuestTasks));
+                    if !self.collapsed_sections.contains(&Section::GuestTasks) {
+                        self.entries.extend(
+                            GuestTasks::global(cx)
+                                .read(cx)
+                                .runs(project_id)
+                                .take(MAX_LISTED_GUEST_TASK_RUNS)
+                                .cloned()
+                                .map(ListEntry::GuestTaskRun),
+                        );
+                    }
+                }
+            }
         }
 
         let mut request_entries = Vec::new();
@@ -916,6 +948,69 @@ impl CollabPanel {
             })
     }
 
+    fn render_guest_task_run(
+        &self,
+        run: &GuestTaskRun,
+        is_selected: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let now = OffsetDateTime::now_utc();
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let timezone = UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC);
+        let relative_timestamp = time_format::format_localized_timestamp(
+            run.timestamp,
+            now,
+            timezone,
+            time_format::TimestampFormat::Relative,
+        );
+        let guest = run
+            .guest
+            .as_ref()
+            .map_or("A guest".to_string(), |user| user.github_login.clone());
+        let tooltip = if run.allowed {
+            format!("{guest} ran \"{}\"", run.label)
+        } else {
+            format!(
+                "{guest} tried to run \"{}\", which is not allowed",
+                run.label
+            )
+        };
+
+        ListItem::new(("guest-task-run", run.id))
+            .toggle_state(is_selected)
+            .start_slot(
+                h_flex()
+                    .gap_1()
+                    .child(render_tree_branch(false, false, cx))
+                    .child(match &run.guest {
+                        Some(user) => Avatar::new(user.avatar_uri.clone()).into_any_element(),
+                        None => Icon::new(IconName::Person).into_any_element(),
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(run.label.clone()).single_line())
+                    .when(!run.allowed, |this| {
+                        this.child(
+                            Label::new("blocked")
+                                .size(LabelSize::Small)
+                                .color(Color::Error),
+                        )
+                    }),
+            )
+            .end_slot(
+                Label::new(relative_timestamp)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+    }
+
+    fn manage_guest_tasks(&mut self, cx: &mut ViewContext<Self>) {
+        guest_tasks::manage_allowlist(self.workspace.clone(), self.project.clone(), cx);
+    }
+
     fn take_editing_state(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if self.channel_editing_state.take().is_some() {
             self.channel_name_editor.update(cx, |editor, cx| {
@@ -1383,6 +1478,7 @@ impl CollabPanel {
                 match entry {
                     ListEntry::Header(section) => match section {
                         Section::ActiveCall => Self::leave_call(cx),
+                        Section::GuestTasks => self.manage_guest_tasks(cx),
                         Section::Channels => self.new_root_channel(cx),
                         Section::Contacts => self.toggle_contact_finder(cx),
                         Section::ContactRequests
@@ -1468,6 +1564,7 @@ impl CollabPanel {
                     }
                     ListEntry::OutgoingRequest(_) => {}
                     ListEntry::ChannelEditor { .. } => {}
+                    ListEntry::GuestTaskRun(_) => {}
                 }
             }
         }
@@ -2073,6 +2170,9 @@ impl CollabPanel {
             ListEntry::ParticipantScreen { peer_id, is_last } => self
                 .render_participant_screen(*peer_id, *is_last, is_selected, cx)
                 .into_any_element(),
+            ListEntry::GuestTaskRun(run) => self
+                .render_guest_task_run(run, is_selected, cx)
+                .into_any_element(),
             ListEntry::ChannelNotes { channel_id } => self
                 .render_channel_notes(*channel_id, is_selected, cx)
                 .into_any_element(),
@@ -2169,6 +2269,7 @@ impl CollabPanel {
                     SharedString::from("Current Call")
                 }
             }
+            Section::GuestTasks => SharedString::from("Guest Tasks"),
             Section::ContactRequests => SharedString::from("Requests"),
             Section::Contacts => SharedString::from("Contacts"),
             Section::Channels => SharedString::from("Channels"),
@@ -2197,6 +2298,12 @@ impl CollabPanel {
                     .tooltip(|cx| Tooltip::text("Search for new contact", cx))
                     .into_any_element(),
             ),
+            Section::GuestTasks => Some(
+                IconButton::new("manage-guest-tasks", IconName::Settings)
+                    .on_click(cx.listener(|this, _, cx| this.manage_guest_tasks(cx)))
+                    .tooltip(|cx| Tooltip::text("Choose what guests can run", cx))
+                    .into_any_element(),
+            ),
             Section::Channels => Some(
                 IconButton::new("add-channel", IconName::Plus)
                     .on_click(cx.listener(|this, _, cx| this.new_root_channel(cx)))
@@ -2208,7 +2315,8 @@ impl CollabPanel {
 
         let can_collapse = match section {
             Section::ActiveCall | Section::Channels | Section::Contacts => false,
-            Section::ChannelInvites
+            Section::GuestTasks
+            | Section::ChannelInvites
             | Section::ContactRequests
             | Section::Online
             | Section::Offline => true,
@@ -2832,6 +2940,11 @@ impl PartialEq for ListEntry {
                     return channel_id == other_id;
                 }
             }
+            ListEntry::GuestTaskRun(run_1) => {
+                if let ListEntry::GuestTaskRun(run_2) = other {
+                    return run_1.id == run_2.id;
+                }
+            }
             ListEntry::ChannelInvite(channel_1) => {
                 if let ListEntry::ChannelInvite(channel_2) = other {
                     return channel_1.id == channel_2.id;
@@ -1,9 +1,11 @@
 pub mod channel_view;
 pub mod chat_panel;
 pub mod collab_panel;
+pub mod guest_tasks;
 pub mod notification_panel;
 pub mod notifications;
 mod panel_settings;
+pub mod whiteboard;
 
 use std::{rc::Rc, sync::Arc};
 
@@ -30,9 +32,11 @@ pub fn init(app_state: &Arc<AppState>, cx: &mut AppContext) {
     channel_view::init(cx);
     chat_panel::init(cx);
     collab_panel::init(cx);
+    guest_tasks::init(app_state.client.clone(), app_state.user_store.clone(), cx);
     notification_panel::init(cx);
     notifications::init(app_state, cx);
     title_bar::init(cx);
+    whiteboard::init(app_state.client.clone(), cx);
 }
 
 fn notification_window_options(
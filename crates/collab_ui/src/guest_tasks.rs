@@ -0,0 +1,591 @@
+mod allowlist_picker;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use client::{Client, User, UserStore};
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, AppContext, AsyncAppContext, DismissEvent, Global, Model, ModelContext,
+    Subscription, Task, ViewContext, WeakView, WindowHandle,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, TaskSourceKind};
+use rpc::{proto, TypedEnvelope};
+use serde::{Deserialize, Serialize};
+use task::{ResolvedTask, SpawnInTerminal, TaskContext, TaskTemplate, TaskVariables, VariableName};
+use time::OffsetDateTime;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{
+    notifications::{DetachAndPromptErr, NotificationId},
+    tasks::{resolve_dependencies, schedule_resolved_task},
+    Toast, Workspace,
+};
+
+pub use allowlist_picker::GuestTaskAllowlistDelegate;
+
+actions!(collab, [RunTaskOnHost]);
+
+const ALLOWLIST_KEY_PREFIX: &str = "guest_task_allowlist";
+/// Older runs are dropped from the audit trail past this many.
+const MAX_RECORDED_RUNS: usize = 200;
+
+pub fn init(client: Arc<Client>, user_store: Model<UserStore>, cx: &mut AppContext) {
+    let guest_tasks = cx.new_model(|cx| GuestTasks::new(client, user_store, cx));
+    cx.set_global(GlobalGuestTasks(guest_tasks));
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &RunTaskOnHost, cx| run_task_on_host(workspace, cx));
+    })
+    .detach();
+}
+
+/// An entry of a project's allowlist, which the host manages.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum AllowedTask {
+    /// A task from the project's or the host's task files, as it was when the host allowed it.
+    Task(ApprovedTask),
+    /// A command line, run as a oneshot task.
+    Command(String),
+}
+
+impl AllowedTask {
+    /// The name guests pick the task by.
+    pub fn label(&self) -> &str {
+        match self {
+            AllowedTask::Task(task) => &task.label,
+            AllowedTask::Command(label) => label,
+        }
+    }
+
+    /// The tasks of the project's worktrees and the host's global tasks, as they would be allowed now.
+    pub fn project_tasks(project: &Model<Project>, cx: &AppContext) -> Vec<AllowedTask> {
+        let project = project.read(cx);
+        project_tasks(project, cx)
+            .into_iter()
+            .filter_map(|(task_source_kind, resolved_task)| {
+                let dependencies =
+                    resolve_dependencies(project, &task_source_kind, &resolved_task, cx).ok()?;
+                Some(AllowedTask::Task(ApprovedTask::new(
+                    &resolved_task,
+                    &dependencies,
+                )))
+            })
+            .collect()
+    }
+}
+
+/// What a task spawned when the host allowed it. The project's task files can be edited by
+/// guests, so a task only runs for them while it still spawns exactly that.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ApprovedTask {
+    pub label: String,
+    /// The commands of the tasks it depends on, then its own.
+    pub spawns: Vec<ApprovedSpawn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ApprovedSpawn {
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub env: BTreeMap<String, String>,
+    pub env_files: Vec<PathBuf>,
+}
+
+impl ApprovedTask {
+    fn new(resolved_task: &ResolvedTask, dependencies: &[(TaskSourceKind, ResolvedTask)]) -> Self {
+        let spawns = dependencies
+            .iter()
+            .map(|(_, dependency)| dependency)
+            .chain([resolved_task])
+            .flat_map(|task| {
+                task.resolved
+                    .iter()
+                    .chain(&task.parallel)
+                    .map(|spawn_in_terminal| {
+                        ApprovedSpawn::new(spawn_in_terminal, task.env_files())
+                    })
+            })
+            .collect();
+        Self {
+            label: resolved_task.original_task().label.clone(),
+            spawns,
+        }
+    }
+}
+
+impl ApprovedSpawn {
+    fn new(spawn_in_terminal: &SpawnInTerminal, env_files: &[PathBuf]) -> Self {
+        Self {
+            command: spawn_in_terminal.command.clone(),
+            args: spawn_in_terminal.args.clone(),
+            cwd: spawn_in_terminal.cwd.clone(),
+            env: spawn_in_terminal
+                .env
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            env_files: env_files.to_vec(),
+        }
+    }
+}
+
+/// A guest's attempt to run a task on the host, kept for the audit trail.
+#[derive(Clone, Debug)]
+pub struct GuestTaskRun {
+    pub id: usize,
+    pub project_id: u64,
+    pub guest: Option<Arc<User>>,
+    pub label: String,
+    /// Whether the task was on the allowlist, unchanged, and so was run.
+    pub allowed: bool,
+    pub timestamp: OffsetDateTime,
+}
+
+struct GlobalGuestTasks(Model<GuestTasks>);
+
+impl Global for GlobalGuestTasks {}
+
+/// Lets guests of a shared project run the tasks its host has allowed on the
+/// host's machine, and records every attempt to do so.
+pub struct GuestTasks {
+    user_store: Model<UserStore>,
+    /// Allowlists by project, keyed by the project's worktree paths. Loaded
+    /// from the database on first use.
+    allowlists: HashMap<String, Vec<AllowedTask>>,
+    runs: Vec<GuestTaskRun>,
+    next_run_id: usize,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GuestTasks {
+    pub fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalGuestTasks>().0.clone()
+    }
+
+    fn new(client: Arc<Client>, user_store: Model<UserStore>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            user_store,
+            allowlists: HashMap::default(),
+            runs: Vec::new(),
+            next_run_id: 0,
+            _subscriptions: vec![
+                client.add_request_handler(cx.weak_model(), Self::handle_get_guest_tasks),
+                client.add_request_handler(cx.weak_model(), Self::handle_run_guest_task),
+            ],
+        }
+    }
+
+    /// What guests may run in the project, in the order it was allowed in.
+    pub fn allowlist(&mut self, project: &Model<Project>, cx: &AppContext) -> &[AllowedTask] {
+        let Some(key) = allowlist_key(project, cx) else {
+            return &[];
+        };
+        self.allowlists.entry(key).or_insert_with_key(|key| {
+            KEY_VALUE_STORE
+                .read_kvp(key)
+                .log_err()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).log_err())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn set_allowed(
+        &mut self,
+        project: &Model<Project>,
+        task: AllowedTask,
+        allowed: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(key) = allowlist_key(project, cx) else {
+            return;
+        };
+        let mut allowlist = self.allowlist(project, cx).to_vec();
+        allowlist.retain(|allowed_task| allowed_task != &task);
+        if allowed {
+            allowlist.push(task);
+        }
+        if let Some(json) = serde_json::to_string(&allowlist).log_err() {
+            cx.background_executor()
+                .spawn(KEY_VALUE_STORE.write_kvp(key.clone(), json))
+                .detach_and_log_err(cx);
+        }
+        self.allowlists.insert(key, allowlist);
+        cx.notify();
+    }
+
+    /// The guests' attempts to run tasks in the given project, newest first.
+    pub fn runs(&self, project_id: u64) -> impl Iterator<Item = &GuestTaskRun> {
+        self.runs
+            .iter()
+            .rev()
+            .filter(move |run| run.project_id == project_id)
+    }
+
+    async fn handle_get_guest_tasks(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGuestTasks>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGuestTasksResponse> {
+        let project_id = envelope.payload.project_id;
+        cx.update(|cx| {
+            let (_, project) =
+                shared_project(project_id, cx).context("project is not shared anymore")?;
+            let labels = this.update(cx, |this, cx| {
+                let mut labels = Vec::new();
+                for task in this.allowlist(&project, cx) {
+                    if !labels.iter().any(|label| label == task.label()) {
+                        labels.push(task.label().to_string());
+                    }
+                }
+                labels
+            });
+            Ok(proto::GetGuestTasksResponse { labels })
+        })?
+    }
+
+    async fn handle_run_guest_task(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RunGuestTask>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let guest_peer_id = envelope.original_sender_id()?;
+        let project_id = envelope.payload.project_id;
+        let label = envelope.payload.label;
+        cx.update(|cx| {
+            let (workspace, project) =
+                shared_project(project_id, cx).context("project is not shared anymore")?;
+            let guest = project
+                .read(cx)
+                .collaborators()
+                .get(&guest_peer_id)
+                .and_then(|collaborator| {
+                    this.read(cx)
+                        .user_store
+                        .read(cx)
+                        .get_cached_user(collaborator.user_id)
+                });
+            let mut allowed_tasks = this.update(cx, |this, cx| {
+                this.allowlist(&project, cx)
+                    .iter()
+                    .filter(|task| task.label() == label)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            });
+            allowed_tasks.sort();
+            let task_to_run = allowed_tasks
+                .iter()
+                .find_map(|allowed_task| resolve_allowed_task(&project, allowed_task, cx));
+            this.update(cx, |this, cx| {
+                this.record_run(project_id, guest, &label, task_to_run.is_some(), cx)
+            });
+            if allowed_tasks.is_empty() {
+                bail!("the host has not allowed running {label:?}");
+            }
+
+            let (task_source_kind, resolved_task) = task_to_run.ok_or_else(|| {
+                anyhow!(
+                    "{label:?} has changed since the host allowed it, \
+                    or cannot run in the host's project anymore"
+                )
+            })?;
+            workspace.update(cx, |workspace, cx| {
+                schedule_resolved_task(workspace, task_source_kind, resolved_task, false, cx)
+            })?;
+            Ok(proto::Ack {})
+        })?
+    }
+
+    fn record_run(
+        &mut self,
+        project_id: u64,
+        guest: Option<Arc<User>>,
+        label: &str,
+        allowed: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.runs.push(GuestTaskRun {
+            id: post_inc(&mut self.next_run_id),
+            project_id,
+            guest,
+            label: label.to_string(),
+            allowed,
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        if self.runs.len() > MAX_RECORDED_RUNS {
+            self.runs.remove(0);
+        }
+        cx.notify();
+    }
+}
+
+fn post_inc(value: &mut usize) -> usize {
+    let prev = *value;
+    *value += 1;
+    prev
+}
+
+/// Allowlists belong to the host's checkout rather than to a share of it, so
+/// that they survive unsharing and reopening the project.
+fn allowlist_key(project: &Model<Project>, cx: &AppContext) -> Option<String> {
+    let mut paths = project
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        return None;
+    }
+    paths.sort();
+    Some(format!("{ALLOWLIST_KEY_PREFIX}:{}", paths.join(":")))
+}
+
+/// The local workspace sharing the project with the given id.
+fn shared_project(
+    project_id: u64,
+    cx: &AppContext,
+) -> Option<(WindowHandle<Workspace>, Model<Project>)> {
+    workspace::local_workspace_windows(cx)
+        .into_iter()
+        .find_map(|window| {
+            let project = window.read(cx).ok()?.project().clone();
+            (project.read(cx).remote_id() == Some(project_id)).then_some((window, project))
+        })
+}
+
+/// The tasks of the project, each resolved in the first worktree it is defined for.
+/// Guests have no say in the context, so tasks run in their worktree's root, and tasks
+/// that need an open file can't run at all.
+fn project_tasks(project: &Project, cx: &AppContext) -> Vec<(TaskSourceKind, ResolvedTask)> {
+    let Some(inventory) = project.task_store().read(cx).task_inventory() else {
+        return Vec::new();
+    };
+    let inventory = inventory.read(cx);
+    let mut tasks = Vec::<(TaskSourceKind, ResolvedTask)>::new();
+    for worktree in project.visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        let task_context = worktree_task_context(&worktree.abs_path());
+        for (task_source_kind, template) in
+            inventory.list_tasks(None, None, Some(worktree.id()), cx)
+        {
+            if tasks
+                .iter()
+                .any(|(_, task)| task.original_task().label == template.label)
+            {
+                continue;
+            }
+            if let Some(resolved_task) =
+                template.resolve_task(&task_source_kind.to_id_base(), &task_context)
+            {
+                tasks.push((task_source_kind, resolved_task));
+            }
+        }
+    }
+    tasks
+}
+
+fn worktree_task_context(root: &Path) -> TaskContext {
+    TaskContext {
+        cwd: Some(root.to_path_buf()),
+        task_variables: TaskVariables::from_iter([(
+            VariableName::WorktreeRoot,
+            root.to_string_lossy().into_owned(),
+        )]),
+        ..TaskContext::default()
+    }
+}
+
+/// Resolves an allowed task, unless it does not spawn what the host allowed anymore.
+fn resolve_allowed_task(
+    project: &Model<Project>,
+    allowed_task: &AllowedTask,
+    cx: &AppContext,
+) -> Option<(TaskSourceKind, ResolvedTask)> {
+    let project = project.read(cx);
+    match allowed_task {
+        AllowedTask::Task(approved_task) => {
+            let (task_source_kind, resolved_task) = project_tasks(project, cx)
+                .into_iter()
+                .find(|(_, task)| task.original_task().label == approved_task.label)?;
+            let dependencies =
+                resolve_dependencies(project, &task_source_kind, &resolved_task, cx).ok()?;
+            (&ApprovedTask::new(&resolved_task, &dependencies) == approved_task)
+                .then_some((task_source_kind, resolved_task))
+        }
+        AllowedTask::Command(command) => {
+            let worktree = project.visible_worktrees(cx).next()?;
+            let template = TaskTemplate {
+                label: command.clone(),
+                command: command.clone(),
+                ..TaskTemplate::default()
+            };
+            let task_context = worktree_task_context(&worktree.read(cx).abs_path());
+            let resolved_task =
+                template.resolve_task(&TaskSourceKind::UserInput.to_id_base(), &task_context)?;
+            Some((TaskSourceKind::UserInput, resolved_task))
+        }
+    }
+}
+
+fn run_task_on_host(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().read(cx);
+    let Some(project_id) = project.remote_id().filter(|_| project.is_via_collab()) else {
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<RunTaskOnHost>(),
+                "Join a shared project to run its host's tasks",
+            )
+            .autohide(),
+            cx,
+        );
+        return;
+    };
+    let client = workspace.client().clone();
+    let get_tasks = client.request(proto::GetGuestTasks { project_id });
+    cx.spawn(|workspace, mut cx| async move {
+        let labels = get_tasks.await?.labels;
+        workspace.update(&mut cx, |workspace, cx| {
+            let delegate = HostTasksDelegate::new(client, project_id, labels);
+            workspace.toggle_modal(cx, |cx| Picker::uniform_list(delegate, cx).width(rems(34.)));
+        })
+    })
+    .detach_and_prompt_err("Failed to list the host's tasks", cx, |_, _| None);
+}
+
+/// Lists the tasks a guest may run on the host.
+struct HostTasksDelegate {
+    client: Arc<Client>,
+    project_id: u64,
+    labels: Vec<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl HostTasksDelegate {
+    fn new(client: Arc<Client>, project_id: u64, labels: Vec<String>) -> Self {
+        Self {
+            client,
+            project_id,
+            labels,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for HostTasksDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Run a task on the host...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.labels.is_empty() {
+            "The host hasn't allowed guests to run any tasks".into()
+        } else {
+            "No matching tasks".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| StringMatchCandidate::new(id, label))
+            .collect::<Vec<_>>();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                100,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.matches = matches;
+                    picker.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(label) = self
+            .matches
+            .get(self.selected_index)
+            .map(|task_match| self.labels[task_match.candidate_id].clone())
+        else {
+            return;
+        };
+        let run_task = self.client.request(proto::RunGuestTask {
+            project_id: self.project_id,
+            label,
+        });
+        cx.spawn(|_, _| async move { run_task.await.map(drop) })
+            .detach_and_prompt_err("Failed to run the task on the host", cx, |_, _| None);
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let task_match = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Play).color(Color::Muted))
+                .child(Label::new(self.labels[task_match.candidate_id].clone())),
+        )
+    }
+}
+
+/// Opens the picker the host manages a project's allowlist with.
+pub fn manage_allowlist(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut WindowContext,
+) {
+    let Some(workspace) = workspace.upgrade() else {
+        return;
+    };
+    workspace.update(cx, |workspace, cx| {
+        let delegate = GuestTaskAllowlistDelegate::new(GuestTasks::global(cx), project, cx);
+        workspace.toggle_modal(cx, |cx| Picker::uniform_list(delegate, cx).width(rems(34.)));
+    });
+}
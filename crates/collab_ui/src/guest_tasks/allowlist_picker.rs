@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use fuzzy::StringMatchCandidate;
+use gpui::{AppContext, Model, Task, ViewContext};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+
+use super::{AllowedTask, GuestTasks};
+
+/// Lets the host pick what guests may run in a project: any of the project's
+/// tasks, or a command typed into the query.
+pub struct GuestTaskAllowlistDelegate {
+    guest_tasks: Model<GuestTasks>,
+    project: Model<Project>,
+    project_tasks: Vec<AllowedTask>,
+    matches: Vec<AllowlistMatch>,
+    selected_index: usize,
+}
+
+struct AllowlistMatch {
+    task: AllowedTask,
+    positions: Vec<usize>,
+}
+
+impl GuestTaskAllowlistDelegate {
+    pub fn new(guest_tasks: Model<GuestTasks>, project: Model<Project>, cx: &AppContext) -> Self {
+        let project_tasks = AllowedTask::project_tasks(&project, cx);
+        Self {
+            guest_tasks,
+            project,
+            project_tasks,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    /// The project's tasks, followed by whatever else is allowed, such as
+    /// commands and tasks that were changed or removed in the project since.
+    fn candidates(&self, cx: &mut AppContext) -> Vec<AllowedTask> {
+        let mut candidates = self.project_tasks.clone();
+        let allowlist = self.guest_tasks.update(cx, |guest_tasks, cx| {
+            guest_tasks.allowlist(&self.project, cx).to_vec()
+        });
+        for allowed_task in allowlist {
+            if !candidates.contains(&allowed_task) {
+                candidates.push(allowed_task);
+            }
+        }
+        candidates
+    }
+
+    fn is_allowed(&self, task: &AllowedTask, cx: &mut AppContext) -> bool {
+        self.guest_tasks.update(cx, |guest_tasks, cx| {
+            guest_tasks.allowlist(&self.project, cx).contains(task)
+        })
+    }
+}
+
+impl PickerDelegate for GuestTaskAllowlistDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Allow guests to run a task, or type a command...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self.candidates(cx);
+        let match_candidates = candidates
+            .iter()
+            .enumerate()
+            .map(|(id, task)| StringMatchCandidate::new(id, task.label()))
+            .collect::<Vec<_>>();
+        cx.spawn(|picker, mut cx| async move {
+            let string_matches = fuzzy::match_strings(
+                &match_candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            let mut matches = string_matches
+                .into_iter()
+                .map(|string_match| AllowlistMatch {
+                    task: candidates[string_match.candidate_id].clone(),
+                    positions: string_match.positions,
+                })
+                .collect::<Vec<_>>();
+            let command = query.trim();
+            if !command.is_empty() && !candidates.iter().any(|task| task.label() == command) {
+                matches.push(AllowlistMatch {
+                    task: AllowedTask::Command(command.to_string()),
+                    positions: Vec::new(),
+                });
+            }
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.matches = matches;
+                    picker.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    /// Toggles whether the selected task is allowed, keeping the picker open
+    /// so that several can be toggled in a row.
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(task) = self
+            .matches
+            .get(self.selected_index)
+            .map(|allowlist_match| allowlist_match.task.clone())
+        else {
+            return;
+        };
+        let allowed = !self.is_allowed(&task, cx);
+        self.guest_tasks.update(cx, |guest_tasks, cx| {
+            guest_tasks.set_allowed(&self.project, task, allowed, cx)
+        });
+        cx.notify();
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let allowlist_match = self.matches.get(ix)?;
+        let is_allowed = self.is_allowed(&allowlist_match.task, cx);
+        let kind = match &allowlist_match.task {
+            AllowedTask::Command(_) => Some("Command"),
+            task if !self.project_tasks.contains(task) => Some("Outdated"),
+            AllowedTask::Task(_) => None,
+        };
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Check).color(if is_allowed {
+                    Color::Accent
+                } else {
+                    Color::Hidden
+                }))
+                .child(HighlightedLabel::new(
+                    allowlist_match.task.label().to_string(),
+                    allowlist_match.positions.clone(),
+                ))
+                .end_slot::<Label>(
+                    kind.map(|kind| Label::new(kind).size(LabelSize::Small).color(Color::Muted)),
+                ),
+        )
+    }
+}
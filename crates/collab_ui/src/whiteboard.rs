@@ -0,0 +1,792 @@
+mod export;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use base64::prelude::*;
+use call::ActiveCall;
+use client::{proto::PeerId, Client};
+use editor::Editor;
+use gpui::{
+    canvas, point, px, AppContext, AsyncAppContext, Bounds, EventEmitter, FocusHandle,
+    FocusableView, Global, Hsla, Model, ModelContext, MouseButton, MouseDownEvent, MouseMoveEvent,
+    Path, Pixels, Point, Render, Rgba, Subscription, View, ViewContext, WeakView,
+};
+use rpc::{proto, TypedEnvelope};
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::{item::Item, notifications::DetachAndPromptErr, OpenWhiteboard, Workspace};
+
+use crate::channel_view::ChannelView;
+
+const STROKE_WIDTH: f32 = 2.;
+const FONT_SIZE: f32 = 14.;
+const ARROW_HEAD_LENGTH: f32 = 12.;
+const ARROW_HEAD_ANGLE: f32 = 0.5;
+/// Rectangles and arrows smaller than this are treated as stray clicks.
+const MIN_SHAPE_SIZE: f32 = 4.;
+
+pub fn init(client: Arc<Client>, cx: &mut AppContext) {
+    let whiteboard = cx.new_model(|cx| Whiteboard::new(client, cx));
+    cx.set_global(GlobalWhiteboard(whiteboard));
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &OpenWhiteboard, cx| {
+            if let Some(existing) = workspace.items_of_type::<WhiteboardView>(cx).next() {
+                workspace.activate_item(&existing, true, true, cx);
+                return;
+            }
+            let whiteboard = Whiteboard::global(cx);
+            let weak_workspace = cx.view().downgrade();
+            let view = cx.new_view(|cx| WhiteboardView::new(whiteboard, weak_workspace, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ElementId {
+    author: PeerId,
+    local_id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElementKind {
+    Stroke(Vec<Point<f32>>),
+    Rectangle(Point<f32>, Point<f32>),
+    Arrow(Point<f32>, Point<f32>),
+    Text(Point<f32>, String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Element {
+    id: ElementId,
+    kind: ElementKind,
+}
+
+impl Element {
+    pub fn kind(&self) -> &ElementKind {
+        &self.kind
+    }
+
+    fn to_proto(&self) -> proto::WhiteboardElement {
+        use proto::whiteboard_element::Kind;
+
+        let shape = |start: &Point<f32>, end: &Point<f32>| proto::WhiteboardShape {
+            start: Some(point_to_proto(*start)),
+            end: Some(point_to_proto(*end)),
+        };
+        let kind = match &self.kind {
+            ElementKind::Stroke(points) => Kind::Stroke(proto::WhiteboardStroke {
+                points: points.iter().copied().map(point_to_proto).collect(),
+            }),
+            ElementKind::Rectangle(start, end) => Kind::Rectangle(shape(start, end)),
+            ElementKind::Arrow(start, end) => Kind::Arrow(shape(start, end)),
+            ElementKind::Text(position, text) => Kind::Text(proto::WhiteboardText {
+                position: Some(point_to_proto(*position)),
+                text: text.clone(),
+            }),
+        };
+        proto::WhiteboardElement {
+            id: Some(element_id_to_proto(self.id)),
+            kind: Some(kind),
+        }
+    }
+
+    fn from_proto(element: proto::WhiteboardElement) -> Option<Self> {
+        use proto::whiteboard_element::Kind;
+
+        let shape = |shape: proto::WhiteboardShape| {
+            Some((point_from_proto(shape.start?), point_from_proto(shape.end?)))
+        };
+        let kind = match element.kind? {
+            Kind::Stroke(stroke) => {
+                ElementKind::Stroke(stroke.points.into_iter().map(point_from_proto).collect())
+            }
+            Kind::Rectangle(rectangle) => {
+                let (start, end) = shape(rectangle)?;
+                ElementKind::Rectangle(start, end)
+            }
+            Kind::Arrow(arrow) => {
+                let (start, end) = shape(arrow)?;
+                ElementKind::Arrow(start, end)
+            }
+            Kind::Text(text) => ElementKind::Text(point_from_proto(text.position?), text.text),
+        };
+        Some(Self {
+            id: element_id_from_proto(element.id?)?,
+            kind,
+        })
+    }
+}
+
+fn point_to_proto(point: Point<f32>) -> proto::WhiteboardPoint {
+    proto::WhiteboardPoint {
+        x: point.x,
+        y: point.y,
+    }
+}
+
+fn point_from_proto(point: proto::WhiteboardPoint) -> Point<f32> {
+    Point::new(point.x, point.y)
+}
+
+fn element_id_to_proto(id: ElementId) -> proto::WhiteboardElementId {
+    proto::WhiteboardElementId {
+        author: Some(id.author),
+        local_id: id.local_id,
+    }
+}
+
+fn element_id_from_proto(id: proto::WhiteboardElementId) -> Option<ElementId> {
+    Some(ElementId {
+        author: id.author?,
+        local_id: id.local_id,
+    })
+}
+
+/// The two short strokes that make up the head of an arrow pointing at `end`.
+fn arrow_head(start: Point<f32>, end: Point<f32>) -> Option<[Point<f32>; 2]> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    if dx == 0. && dy == 0. {
+        return None;
+    }
+    let angle = dy.atan2(dx) + std::f32::consts::PI;
+    Some(
+        [angle - ARROW_HEAD_ANGLE, angle + ARROW_HEAD_ANGLE].map(|angle| {
+            Point::new(
+                end.x + ARROW_HEAD_LENGTH * angle.cos(),
+                end.y + ARROW_HEAD_LENGTH * angle.sin(),
+            )
+        }),
+    )
+}
+
+struct GlobalWhiteboard(Model<Whiteboard>);
+
+impl Global for GlobalWhiteboard {}
+
+/// The drawing shared by the participants of the current call. Every client
+/// keeps a copy, so that participants who join later can be caught up by
+/// whoever is already in the room.
+pub struct Whiteboard {
+    client: Arc<Client>,
+    room_id: Option<u64>,
+    elements: Vec<Element>,
+    next_local_id: u64,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Whiteboard {
+    pub fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalWhiteboard>().0.clone()
+    }
+
+    fn new(client: Arc<Client>, cx: &mut ModelContext<Self>) -> Self {
+        let mut subscriptions =
+            vec![client.add_message_handler(cx.weak_model(), Self::handle_update_whiteboard)];
+        if let Some(active_call) = ActiveCall::try_global(cx) {
+            subscriptions.push(cx.observe(&active_call, |this, _, cx| this.room_changed(cx)));
+        }
+
+        Self {
+            client,
+            room_id: None,
+            elements: Vec::new(),
+            next_local_id: 0,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn room_id(&self) -> Option<u64> {
+        self.room_id
+    }
+
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    fn room_changed(&mut self, cx: &mut ModelContext<Self>) {
+        let room_id = ActiveCall::global(cx)
+            .read(cx)
+            .room()
+            .map(|room| room.read(cx).id());
+        if room_id == self.room_id {
+            return;
+        }
+
+        self.room_id = room_id;
+        self.elements.clear();
+        if room_id.is_some() {
+            self.send(proto::update_whiteboard::Variant::RequestElements(
+                proto::RequestWhiteboardElements {},
+            ));
+        }
+        cx.notify();
+    }
+
+    fn send(&self, variant: proto::update_whiteboard::Variant) {
+        let Some(room_id) = self.room_id else {
+            return;
+        };
+        self.client
+            .send(proto::UpdateWhiteboard {
+                room_id,
+                variant: Some(variant),
+            })
+            .log_err();
+    }
+
+    pub fn add(&mut self, kind: ElementKind, cx: &mut ModelContext<Self>) {
+        let Some(author) = self.client.peer_id().filter(|_| self.room_id.is_some()) else {
+            return;
+        };
+        let element = Element {
+            id: ElementId {
+                author,
+                local_id: post_inc(&mut self.next_local_id),
+            },
+            kind,
+        };
+        self.send(proto::update_whiteboard::Variant::AddElements(
+            proto::WhiteboardElements {
+                elements: vec![element.to_proto()],
+            },
+        ));
+        self.elements.push(element);
+        cx.notify();
+    }
+
+    /// Removes the latest element drawn by this participant.
+    pub fn undo(&mut self, cx: &mut ModelContext<Self>) {
+        let author = self.client.peer_id();
+        let Some(ix) = self
+            .elements
+            .iter()
+            .rposition(|element| Some(element.id.author) == author)
+        else {
+            return;
+        };
+        let element = self.elements.remove(ix);
+        self.send(proto::update_whiteboard::Variant::RemoveElements(
+            proto::WhiteboardElementIds {
+                ids: vec![element_id_to_proto(element.id)],
+            },
+        ));
+        cx.notify();
+    }
+
+    /// Removes every element, for all participants.
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        let ids = self
+            .elements
+            .drain(..)
+            .map(|element| element_id_to_proto(element.id))
+            .collect();
+        self.send(proto::update_whiteboard::Variant::RemoveElements(
+            proto::WhiteboardElementIds { ids },
+        ));
+        cx.notify();
+    }
+
+    async fn handle_update_whiteboard(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateWhiteboard>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            if this.room_id != Some(envelope.payload.room_id) {
+                return;
+            }
+            match envelope.payload.variant {
+                Some(proto::update_whiteboard::Variant::AddElements(message)) => {
+                    // Several participants may answer the same request for
+                    // elements, so skip the ones we already have.
+                    for element in message.elements.into_iter().filter_map(Element::from_proto) {
+                        if !this
+                            .elements
+                            .iter()
+                            .any(|existing| existing.id == element.id)
+                        {
+                            this.elements.push(element);
+                        }
+                    }
+                }
+                Some(proto::update_whiteboard::Variant::RemoveElements(message)) => {
+                    let ids = message
+                        .ids
+                        .into_iter()
+                        .filter_map(element_id_from_proto)
+                        .collect::<Vec<_>>();
+                    this.elements.retain(|element| !ids.contains(&element.id));
+                }
+                Some(proto::update_whiteboard::Variant::RequestElements(_)) => {
+                    if !this.elements.is_empty() {
+                        this.send(proto::update_whiteboard::Variant::AddElements(
+                            proto::WhiteboardElements {
+                                elements: this.elements.iter().map(Element::to_proto).collect(),
+                            },
+                        ));
+                    }
+                }
+                None => {}
+            }
+            cx.notify();
+        })
+    }
+}
+
+fn post_inc(value: &mut u64) -> u64 {
+    let prev = *value;
+    *value += 1;
+    prev
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Pen,
+    Rectangle,
+    Arrow,
+    Text,
+}
+
+impl Tool {
+    const ALL: [Tool; 4] = [Tool::Pen, Tool::Rectangle, Tool::Arrow, Tool::Text];
+
+    fn icon(&self) -> IconName {
+        match self {
+            Tool::Pen => IconName::Pencil,
+            Tool::Rectangle => IconName::SquareDot,
+            Tool::Arrow => IconName::ArrowUpRight,
+            Tool::Text => IconName::Font,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Tool::Pen => "Freehand",
+            Tool::Rectangle => "Box",
+            Tool::Arrow => "Arrow",
+            Tool::Text => "Text",
+        }
+    }
+}
+
+/// An element that is still being drawn.
+struct Drag {
+    tool: Tool,
+    points: Vec<Point<f32>>,
+}
+
+impl Drag {
+    fn element_kind(&self) -> Option<ElementKind> {
+        let start = *self.points.first()?;
+        let end = *self.points.last()?;
+        let is_large_enough =
+            (end.x - start.x).abs().max((end.y - start.y).abs()) >= MIN_SHAPE_SIZE;
+        match self.tool {
+            Tool::Pen => (self.points.len() > 1).then(|| ElementKind::Stroke(self.points.clone())),
+            Tool::Rectangle => is_large_enough.then_some(ElementKind::Rectangle(start, end)),
+            Tool::Arrow => is_large_enough.then_some(ElementKind::Arrow(start, end)),
+            Tool::Text => None,
+        }
+    }
+}
+
+pub struct WhiteboardView {
+    whiteboard: Model<Whiteboard>,
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    text_editor: View<Editor>,
+    tool: Tool,
+    drag: Option<Drag>,
+    canvas_bounds: Bounds<Pixels>,
+    _subscription: Subscription,
+}
+
+impl WhiteboardView {
+    fn new(
+        whiteboard: Model<Whiteboard>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let text_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Type, then click where the text goes", cx);
+            editor
+        });
+        let subscription = cx.observe(&whiteboard, |_, _, cx| cx.notify());
+        Self {
+            whiteboard,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            text_editor,
+            tool: Tool::Pen,
+            drag: None,
+            canvas_bounds: Bounds::default(),
+            _subscription: subscription,
+        }
+    }
+
+    fn set_tool(&mut self, tool: Tool, cx: &mut ViewContext<Self>) {
+        self.tool = tool;
+        if tool == Tool::Text {
+            cx.focus_view(&self.text_editor);
+        }
+        cx.notify();
+    }
+
+    fn canvas_position(&self, position: Point<Pixels>) -> Point<f32> {
+        let position = position - self.canvas_bounds.origin;
+        Point::new(position.x.0, position.y.0)
+    }
+
+    fn mouse_down(&mut self, event: &MouseDownEvent, cx: &mut ViewContext<Self>) {
+        let position = self.canvas_position(event.position);
+        if self.tool == Tool::Text {
+            let text = self.text_editor.read(cx).text(cx);
+            if text.trim().is_empty() {
+                return;
+            }
+            self.whiteboard.update(cx, |whiteboard, cx| {
+                whiteboard.add(ElementKind::Text(position, text), cx)
+            });
+            self.text_editor
+                .update(cx, |editor, cx| editor.set_text("", cx));
+        } else {
+            self.drag = Some(Drag {
+                tool: self.tool,
+                points: vec![position],
+            });
+        }
+        cx.notify();
+    }
+
+    fn mouse_move(&mut self, event: &MouseMoveEvent, cx: &mut ViewContext<Self>) {
+        if event.pressed_button != Some(MouseButton::Left) {
+            return;
+        }
+        let position = self.canvas_position(event.position);
+        let Some(drag) = self.drag.as_mut() else {
+            return;
+        };
+        if drag.tool == Tool::Pen || drag.points.len() < 2 {
+            drag.points.push(position);
+        } else if let Some(end) = drag.points.last_mut() {
+            *end = position;
+        }
+        cx.notify();
+    }
+
+    fn mouse_up(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(kind) = self.drag.take().and_then(|drag| drag.element_kind()) else {
+            cx.notify();
+            return;
+        };
+        self.whiteboard
+            .update(cx, |whiteboard, cx| whiteboard.add(kind, cx));
+    }
+
+    /// The color of the participant who drew an element, matching their
+    /// cursor color.
+    fn author_color(&self, author: PeerId, cx: &AppContext) -> Hsla {
+        let players = cx.theme().players();
+        if Some(author) == self.whiteboard.read(cx).client.peer_id() {
+            return players.local().cursor;
+        }
+        ActiveCall::global(cx)
+            .read(cx)
+            .room()
+            .and_then(|room| {
+                let participant = room.read(cx).remote_participant_for_peer_id(author)?;
+                Some(players.color_for_participant(participant.participant_index.0))
+            })
+            .unwrap_or_else(|| players.absent())
+            .cursor
+    }
+
+    fn colored_elements(&self, cx: &AppContext) -> Vec<(ElementKind, Hsla)> {
+        self.whiteboard
+            .read(cx)
+            .elements()
+            .iter()
+            .map(|element| {
+                (
+                    element.kind.clone(),
+                    self.author_color(element.id.author, cx),
+                )
+            })
+            .collect()
+    }
+
+    /// Appends the drawing to the notes of the call's channel, as an SVG image.
+    fn export_to_channel_notes(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(channel_id) = ActiveCall::global(cx)
+            .read(cx)
+            .room()
+            .and_then(|room| room.read(cx).channel_id())
+        else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let elements = self
+            .colored_elements(cx)
+            .into_iter()
+            .map(|(kind, color)| (kind, Rgba::from(color)))
+            .collect::<Vec<_>>();
+        let Some(svg) = export::to_svg(&elements) else {
+            return;
+        };
+        let markdown = format!(
+            "\n![Whiteboard](data:image/svg+xml;base64,{})\n",
+            BASE64_STANDARD.encode(svg)
+        );
+
+        let open_notes = ChannelView::open(channel_id, None, workspace, cx);
+        cx.spawn(|_, mut cx| async move {
+            let channel_view = open_notes.await?;
+            channel_view.update(&mut cx, |channel_view, cx| {
+                channel_view.editor.update(cx, |editor, cx| {
+                    editor.buffer().update(cx, |buffer, cx| {
+                        let end = buffer.len(cx);
+                        buffer.edit([(end..end, markdown)], None, cx);
+                    });
+                });
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to add the whiteboard to channel notes",
+            cx,
+            |_, _| None,
+        );
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let whiteboard = self.whiteboard.read(cx);
+        let is_empty = whiteboard.elements().is_empty();
+        let has_channel = ActiveCall::global(cx)
+            .read(cx)
+            .room()
+            .map_or(false, |room| room.read(cx).channel_id().is_some());
+
+        h_flex()
+            .p_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(Tool::ALL.into_iter().map(|tool| {
+                IconButton::new(tool.label(), tool.icon())
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.tool == tool)
+                    .tooltip(move |cx| Tooltip::text(tool.label(), cx))
+                    .on_click(cx.listener(move |this, _, cx| this.set_tool(tool, cx)))
+            }))
+            .child(
+                div()
+                    .flex_1()
+                    .px_2()
+                    .when(self.tool != Tool::Text, |this| this.invisible())
+                    .child(self.text_editor.clone()),
+            )
+            .child(
+                IconButton::new("undo", IconName::Undo)
+                    .icon_size(IconSize::Small)
+                    .disabled(is_empty)
+                    .tooltip(|cx| Tooltip::text("Undo Your Last Change", cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.whiteboard
+                            .update(cx, |whiteboard, cx| whiteboard.undo(cx))
+                    })),
+            )
+            .child(
+                IconButton::new("clear", IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .disabled(is_empty)
+                    .tooltip(|cx| Tooltip::text("Clear for Everyone", cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.whiteboard
+                            .update(cx, |whiteboard, cx| whiteboard.clear(cx))
+                    })),
+            )
+            .child(
+                Button::new("export", "Add to Channel Notes")
+                    .icon(IconName::FileText)
+                    .icon_size(IconSize::Small)
+                    .icon_position(IconPosition::Start)
+                    .label_size(LabelSize::Small)
+                    .disabled(is_empty || !has_channel)
+                    .on_click(cx.listener(|this, _, cx| this.export_to_channel_notes(cx))),
+            )
+    }
+
+    fn render_canvas(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut elements = self.colored_elements(cx);
+        if let Some(kind) = self.drag.as_ref().and_then(Drag::element_kind) {
+            elements.push((kind, cx.theme().players().local().cursor));
+        }
+        let texts = elements
+            .iter()
+            .filter_map(|(kind, color)| match kind {
+                ElementKind::Text(position, text) => Some((*position, text.clone(), *color)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let view = cx.view().clone();
+
+        div()
+            .id("whiteboard-canvas")
+            .relative()
+            .flex_1()
+            .size_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().editor_background)
+            .cursor_crosshair()
+            .on_mouse_down(MouseButton::Left, cx.listener(Self::mouse_down))
+            .on_mouse_move(cx.listener(Self::mouse_move))
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, cx| this.mouse_up(cx)),
+            )
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, _, cx| this.mouse_up(cx)),
+            )
+            .child(
+                canvas(
+                    move |bounds, cx| {
+                        view.update(cx, |this, _| this.canvas_bounds = bounds);
+                    },
+                    move |bounds, _, cx| {
+                        for (kind, color) in &elements {
+                            paint_element(kind, *color, bounds.origin, cx);
+                        }
+                    },
+                )
+                .size_full(),
+            )
+            .children(texts.into_iter().map(|(position, text, color)| {
+                div()
+                    .absolute()
+                    .left(px(position.x))
+                    .top(px(position.y))
+                    .text_size(px(FONT_SIZE))
+                    .text_color(color)
+                    .whitespace_nowrap()
+                    .child(text)
+            }))
+    }
+}
+
+fn paint_element(kind: &ElementKind, color: Hsla, origin: Point<Pixels>, cx: &mut WindowContext) {
+    let mut paint_line = |from: Point<f32>, to: Point<f32>| {
+        paint_segment(from, to, color, origin, cx);
+    };
+    match kind {
+        ElementKind::Stroke(points) => {
+            for segment in points.windows(2) {
+                paint_line(segment[0], segment[1]);
+            }
+        }
+        ElementKind::Rectangle(start, end) => {
+            let corners = [
+                *start,
+                Point::new(end.x, start.y),
+                *end,
+                Point::new(start.x, end.y),
+            ];
+            for ix in 0..corners.len() {
+                paint_line(corners[ix], corners[(ix + 1) % corners.len()]);
+            }
+        }
+        ElementKind::Arrow(start, end) => {
+            paint_line(*start, *end);
+            if let Some([left, right]) = arrow_head(*start, *end) {
+                paint_line(left, *end);
+                paint_line(right, *end);
+            }
+        }
+        // Text is laid out by regular elements on top of the canvas.
+        ElementKind::Text(..) => {}
+    }
+}
+
+/// Paints a straight line as a thin quadrilateral, as paths are filled.
+fn paint_segment(
+    from: Point<f32>,
+    to: Point<f32>,
+    color: Hsla,
+    origin: Point<Pixels>,
+    cx: &mut WindowContext,
+) {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0. {
+        return;
+    }
+    let offset = Point::new(
+        -dy / length * STROKE_WIDTH / 2.,
+        dx / length * STROKE_WIDTH / 2.,
+    );
+    let to_window = |position: Point<f32>| origin + point(px(position.x), px(position.y));
+    let mut path = Path::new(to_window(from + offset));
+    path.line_to(to_window(to + offset));
+    path.line_to(to_window(to - offset));
+    path.line_to(to_window(from - offset));
+    cx.paint_path(path, color);
+}
+
+impl EventEmitter<()> for WhiteboardView {}
+
+impl FocusableView for WhiteboardView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for WhiteboardView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.whiteboard.read(cx).room_id().is_some() {
+            v_flex()
+                .size_full()
+                .child(self.render_toolbar(cx))
+                .child(self.render_canvas(cx))
+                .into_any_element()
+        } else {
+            v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(
+                    Label::new("Join a call to draw together with its participants.")
+                        .color(Color::Muted),
+                )
+                .into_any_element()
+        };
+
+        div()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(content)
+    }
+}
+
+impl Item for WhiteboardView {
+    type Event = ();
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some("Whiteboard".into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Pencil))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt::Write as _;
+
+use gpui::{Point, Rgba};
+
+use super::{arrow_head, ElementKind, FONT_SIZE, STROKE_WIDTH};
+
+/// Space left around the drawing in the exported image.
+const MARGIN: f32 = 16.;
+
+/// Renders the elements as an SVG image, cropped to the area they cover.
+/// Returns `None` when there is nothing to render.
+pub(super) fn to_svg(elements: &[(ElementKind, Rgba)]) -> Option<String> {
+    let (min, max) = elements.iter().flat_map(|(kind, _)| extent(kind)).fold(
+        None,
+        |bounds: Option<(Point<f32>, Point<f32>)>, point| {
+            Some(match bounds {
+                Some((min, max)) => (min.min(&point), max.max(&point)),
+                None => (point, point),
+            })
+        },
+    )?;
+    let origin = Point::new(min.x - MARGIN, min.y - MARGIN);
+    let width = max.x - min.x + 2. * MARGIN;
+    let height = max.y - min.y + 2. * MARGIN;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"{:.1} {:.1} {width:.1} {height:.1}\">",
+        origin.x, origin.y
+    );
+    write!(
+        svg,
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{height:.1}\" fill=\"#ffffff\"/>",
+        origin.x, origin.y
+    )
+    .ok();
+    for (kind, color) in elements {
+        let color = hex_color(*color);
+        let stroke = format!(
+            "fill=\"none\" stroke=\"{color}\" stroke-width=\"{STROKE_WIDTH}\" stroke-linecap=\"round\" stroke-linejoin=\"round\""
+        );
+        match kind {
+            ElementKind::Stroke(points) => {
+                write!(
+                    svg,
+                    "<polyline points=\"{}\" {stroke}/>",
+                    points_attr(points)
+                )
+                .ok();
+            }
+            ElementKind::Rectangle(start, end) => {
+                let min = start.min(end);
+                let max = start.max(end);
+                write!(
+                    svg,
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" {stroke}/>",
+                    min.x,
+                    min.y,
+                    max.x - min.x,
+                    max.y - min.y
+                )
+                .ok();
+            }
+            ElementKind::Arrow(start, end) => {
+                write!(
+                    svg,
+                    "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {stroke}/>",
+                    start.x, start.y, end.x, end.y
+                )
+                .ok();
+                if let Some([left, right]) = arrow_head(*start, *end) {
+                    write!(
+                        svg,
+                        "<polyline points=\"{}\" {stroke}/>",
+                        points_attr(&[left, *end, right])
+                    )
+                    .ok();
+                }
+            }
+            ElementKind::Text(position, text) => {
+                // SVG positions text by its baseline rather than its top.
+                write!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{color}\" font-family=\"sans-serif\" font-size=\"{FONT_SIZE}\">{}</text>",
+                    position.x,
+                    position.y + FONT_SIZE,
+                    escape(text)
+                )
+                .ok();
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    Some(svg)
+}
+
+/// The points an element's bounding box has to contain. Text is measured
+/// roughly, as the image is rendered with whatever font the viewer has.
+fn extent(kind: &ElementKind) -> Vec<Point<f32>> {
+    match kind {
+        ElementKind::Stroke(points) => points.clone(),
+        ElementKind::Rectangle(start, end) | ElementKind::Arrow(start, end) => vec![*start, *end],
+        ElementKind::Text(position, text) => {
+            let width = text.chars().count() as f32 * FONT_SIZE * 0.6;
+            vec![
+                *position,
+                Point::new(position.x + width, position.y + FONT_SIZE * 1.2),
+            ]
+        }
+    }
+}
+
+fn points_attr(points: &[Point<f32>]) -> String {
+    let mut attr = String::new();
+    for (ix, point) in points.iter().enumerate() {
+        if ix > 0 {
+            attr.push(' ');
+        }
+        write!(attr, "{:.1},{:.1}", point.x, point.y).ok();
+    }
+    attr
+}
+
+fn hex_color(color: Rgba) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        assert_eq!(to_svg(&[]), None);
+
+        let red = Rgba {
+            r: 1.,
+            g: 0.,
+            b: 0.,
+            a: 1.,
+        };
+        let svg = to_svg(&[
+            (
+                ElementKind::Rectangle(Point::new(100., 50.), Point::new(20., 10.)),
+                red,
+            ),
+            (
+                ElementKind::Text(Point::new(20., 60.), "a < b".to_string()),
+                red,
+            ),
+        ])
+        .unwrap();
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"112\" height=\"99\" viewBox=\"4.0 -6.0 112.0 98.8\">"
+        ));
+        assert!(svg.contains("<rect x=\"20.0\" y=\"10.0\" width=\"80.0\" height=\"40.0\" fill=\"none\" stroke=\"#ff0000\""));
+        assert!(svg.contains(">a &lt; b</text>"));
+        assert!(svg.ends_with("</svg>"));
+    }
+}
@@ -4990,8 +4990,9 @@ impl Editor {
             let context = task_context.await?;
             let (task_source_kind, mut resolved_task) = tasks.resolve(&context).next()?;
 
-            let resolved = resolved_task.resolved.as_mut()?;
-            resolved.reveal = reveal_strategy;
+            for resolved in resolved_task.spawns_mut() {
+                resolved.reveal = reveal_strategy;
+            }
 
             workspace
                 .update(&mut cx, |workspace, cx| {
@@ -8,7 +8,7 @@ use multi_buffer::{
     Anchor, AnchorRangeExt, ExcerptRange, MultiBuffer, MultiBufferDiffHunk, MultiBufferRow,
     MultiBufferSnapshot, ToOffset, ToPoint,
 };
-use project::buffer_store::BufferChangeSet;
+use project::{buffer_store::BufferChangeSet, ProjectTransaction};
 use std::{ops::Range, sync::Arc};
 use sum_tree::TreeMap;
 use text::OffsetRangeExt;
@@ -480,15 +480,21 @@ impl Editor {
         cx: &mut ViewContext<Self>,
     ) {
         let buffers = self.buffer.read(cx).all_buffers();
+        let mut applied_changes = ProjectTransaction::default();
         for branch_buffer in buffers {
-            branch_buffer.update(cx, |branch_buffer, cx| {
-                branch_buffer.merge_into_base(Vec::new(), cx);
+            let base_buffer = branch_buffer.read(cx).base_buffer();
+            let transaction = branch_buffer.update(cx, |branch_buffer, cx| {
+                branch_buffer.merge_into_base(Vec::new(), cx)
             });
+            if let Some((base_buffer, transaction)) = base_buffer.zip(transaction) {
+                applied_changes.0.insert(base_buffer, transaction);
+            }
         }
 
         if let Some(project) = self.project.clone() {
             self.save(true, project, cx).detach_and_log_err(cx);
         }
+        self.open_applied_changes(applied_changes, cx);
     }
 
     pub(crate) fn apply_selected_diff_hunks(
@@ -499,6 +505,7 @@ impl Editor {
         let snapshot = self.snapshot(cx);
         let hunks = hunks_for_selections(&snapshot, &self.selections.all(cx));
         let mut ranges_by_buffer = HashMap::default();
+        let mut applied_changes = ProjectTransaction::default();
         self.transact(cx, |editor, cx| {
             for hunk in hunks {
                 if let Some(buffer) = editor.buffer.read(cx).buffer(hunk.buffer_id) {
@@ -510,15 +517,46 @@ impl Editor {
             }
 
             for (buffer, ranges) in ranges_by_buffer {
-                buffer.update(cx, |buffer, cx| {
-                    buffer.merge_into_base(ranges, cx);
-                });
+                let base_buffer = buffer.read(cx).base_buffer();
+                let transaction =
+                    buffer.update(cx, |buffer, cx| buffer.merge_into_base(ranges, cx));
+                if let Some((base_buffer, transaction)) = base_buffer.zip(transaction) {
+                    applied_changes.0.insert(base_buffer, transaction);
+                }
             }
         });
 
         if let Some(project) = self.project.clone() {
             self.save(true, project, cx).detach_and_log_err(cx);
         }
+        self.open_applied_changes(applied_changes, cx);
+    }
+
+    /// Opens the changes applied to several files in a multibuffer, where they can be
+    /// undone all at once.
+    fn open_applied_changes(
+        &mut self,
+        applied_changes: ProjectTransaction,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if applied_changes.0.len() < 2 {
+            return;
+        }
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let workspace = workspace.downgrade();
+        cx.spawn(|editor, cx| async move {
+            Self::open_project_transaction(
+                &editor,
+                workspace,
+                applied_changes,
+                "Applied Changes".to_string(),
+                cx,
+            )
+            .await
+        })
+        .detach_and_log_err(cx);
     }
 
     fn has_multiple_hunks(&self, cx: &AppContext) -> bool {
@@ -144,6 +144,8 @@ pub struct ContextServerManifestEntry {}
 pub struct SlashCommandManifestEntry {
     pub description: String,
     pub requires_argument: bool,
+    #[serde(default)]
+    pub accepts_argument: bool,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
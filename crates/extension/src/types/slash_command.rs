@@ -11,6 +11,8 @@ pub struct SlashCommand {
     pub tooltip_text: String,
     /// Whether this slash command requires an argument.
     pub requires_argument: bool,
+    /// Whether this slash command accepts an argument, even if it does not require one.
+    pub accepts_argument: bool,
 }
 
 /// The output of a slash command.
@@ -1039,6 +1039,10 @@ impl ExtensionStore {
                 continue;
             };
             grammars_to_remove.extend(extension.manifest.grammars.keys().cloned());
+            for slash_command_name in extension.manifest.slash_commands.keys() {
+                self.proxy
+                    .unregister_slash_command(slash_command_name.clone());
+            }
             for (language_server_name, config) in extension.manifest.language_servers.iter() {
                 for language in config.languages() {
                     self.proxy
@@ -1205,11 +1209,10 @@ impl ExtensionStore {
                             extension::SlashCommand {
                                 name: slash_command_name.to_string(),
                                 description: slash_command.description.to_string(),
-                                // We don't currently expose this as a configurable option, as it currently drives
-                                // the `menu_text` on the `SlashCommand` trait, which is not used for slash commands
-                                // defined in extensions, as they are not able to be added to the menu.
-                                tooltip_text: String::new(),
+                                // The description is also shown in the slash command menu.
+                                tooltip_text: slash_command.description.to_string(),
                                 requires_argument: slash_command.requires_argument,
+                                accepts_argument: slash_command.accepts_argument,
                             },
                         );
                     }
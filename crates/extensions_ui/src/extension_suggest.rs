@@ -5,9 +5,11 @@ use std::sync::{Arc, OnceLock};
 use db::kvp::KEY_VALUE_STORE;
 use editor::Editor;
 use extension_host::ExtensionStore;
-use gpui::{Model, VisualContext};
+use gpui::{DismissEvent, EventEmitter, Model, Render, Subscription, VisualContext};
 use language::Buffer;
-use ui::{SharedString, ViewContext};
+use project::WorktreeId;
+use ui::{prelude::*, SharedString, ViewContext};
+use util::ResultExt;
 use workspace::{
     notifications::{simple_message_notification, NotificationId},
     Workspace,
@@ -198,6 +200,233 @@ pub(crate) fn suggest(buffer: Model<Buffer>, cx: &mut ViewContext<Workspace>) {
     })
 }
 
+/// The most extensions recommended for a project at once.
+const MAX_PROJECT_SUGGESTIONS: usize = 3;
+
+/// Returns the extensions suggested for the given files, along with how many
+/// of the files each one applies to, most common first.
+fn suggested_extensions_for_paths<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<(Arc<str>, usize)> {
+    let mut counts = HashMap::<Arc<str>, usize>::default();
+    for path in paths {
+        if let Some(suggestion) = suggested_extension(path) {
+            *counts.entry(suggestion.extension_id).or_default() += 1;
+        }
+    }
+    let mut suggestions = counts.into_iter().collect::<Vec<_>>();
+    suggestions.sort_by(|(a_id, a_count), (b_id, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_id.cmp(b_id))
+    });
+    suggestions
+}
+
+/// Once a newly added worktree has been scanned, recommends extensions for
+/// the languages it mostly consists of.
+pub(crate) fn suggest_for_worktree(
+    workspace: &Workspace,
+    worktree_id: WorktreeId,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(worktree) = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(worktree_id, cx)
+    else {
+        return;
+    };
+    if !worktree.read(cx).is_visible() {
+        return;
+    }
+    let Some(scan_complete) = worktree
+        .read(cx)
+        .as_local()
+        .map(|worktree| worktree.scan_complete())
+    else {
+        return;
+    };
+
+    cx.spawn(|workspace, mut cx| async move {
+        scan_complete.await;
+        let snapshot = worktree.update(&mut cx, |worktree, _| worktree.snapshot())?;
+        let suggestions = cx
+            .background_executor()
+            .spawn(async move {
+                suggested_extensions_for_paths(
+                    snapshot.files(false, 0).map(|entry| entry.path.as_ref()),
+                )
+            })
+            .await;
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let store = ExtensionStore::global(cx);
+            let suggestions = {
+                let store = store.read(cx);
+                suggestions
+                    .into_iter()
+                    .filter(|(extension_id, _)| {
+                        !store.installed_extensions().contains_key(extension_id)
+                            && !store.outstanding_operations().contains_key(extension_id)
+                            && matches!(
+                                KEY_VALUE_STORE.read_kvp(&language_extension_key(extension_id)),
+                                Ok(None)
+                            )
+                    })
+                    .take(MAX_PROJECT_SUGGESTIONS)
+                    .collect::<Vec<_>>()
+            };
+            if suggestions.is_empty() {
+                return;
+            }
+
+            struct ProjectExtensionSuggestions;
+
+            workspace.show_notification(
+                NotificationId::composite::<ProjectExtensionSuggestions>(worktree_id.to_usize()),
+                cx,
+                |cx| cx.new_view(|cx| ExtensionRecommendations::new(suggestions, store, cx)),
+            );
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// A card offering to install the extensions recommended for a project.
+struct ExtensionRecommendations {
+    suggestions: Vec<(Arc<str>, usize)>,
+    store: Model<ExtensionStore>,
+    _store_subscription: Subscription,
+}
+
+impl ExtensionRecommendations {
+    fn new(
+        suggestions: Vec<(Arc<str>, usize)>,
+        store: Model<ExtensionStore>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self {
+            suggestions,
+            _store_subscription: cx.observe(&store, |_, _, cx| cx.notify()),
+            store,
+        }
+    }
+
+    fn is_installed_or_installing(&self, extension_id: &str, cx: &AppContext) -> bool {
+        let store = self.store.read(cx);
+        store.installed_extensions().contains_key(extension_id)
+            || store.outstanding_operations().contains_key(extension_id)
+    }
+
+    fn install(&mut self, extension_id: Arc<str>, cx: &mut ViewContext<Self>) {
+        if !self.is_installed_or_installing(&extension_id, cx) {
+            self.store.update(cx, |store, cx| {
+                store.install_latest_extension(extension_id, cx);
+            });
+        }
+    }
+
+    fn install_all(&mut self, cx: &mut ViewContext<Self>) {
+        for (extension_id, _) in self.suggestions.clone() {
+            self.install(extension_id, cx);
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn never_suggest(&mut self, cx: &mut ViewContext<Self>) {
+        let keys = self
+            .suggestions
+            .iter()
+            .map(|(extension_id, _)| language_extension_key(extension_id))
+            .collect::<Vec<_>>();
+        cx.background_executor()
+            .spawn(async move {
+                for key in keys {
+                    KEY_VALUE_STORE
+                        .write_kvp(key, "dismissed".to_string())
+                        .await
+                        .log_err();
+                }
+            })
+            .detach();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ExtensionRecommendations {}
+
+impl Render for ExtensionRecommendations {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let rows = self
+            .suggestions
+            .iter()
+            .map(|(extension_id, file_count)| {
+                let store = self.store.read(cx);
+                let button = if store.installed_extensions().contains_key(extension_id) {
+                    Button::new(SharedString::from(extension_id.clone()), "Installed")
+                        .disabled(true)
+                } else if store.outstanding_operations().contains_key(extension_id) {
+                    Button::new(SharedString::from(extension_id.clone()), "Installing…")
+                        .disabled(true)
+                } else {
+                    let extension_id = extension_id.clone();
+                    Button::new(SharedString::from(extension_id.clone()), "Install").on_click(
+                        cx.listener(move |this, _, cx| this.install(extension_id.clone(), cx)),
+                    )
+                };
+                h_flex()
+                    .justify_between()
+                    .child(
+                        v_flex().child(Label::new(extension_id.to_string())).child(
+                            Label::new(match file_count {
+                                1 => "1 file".to_string(),
+                                count => format!("{count} files"),
+                            })
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        ),
+                    )
+                    .child(button)
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .elevation_3(cx)
+            .p_4()
+            .gap_2()
+            .w(rems(24.))
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Recommended Extensions"))
+                    .child(
+                        IconButton::new("dismiss", IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+            .child(
+                Label::new("This project uses languages that these extensions support.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .children(rows)
+            .child(
+                h_flex()
+                    .justify_end()
+                    .gap_2()
+                    .child(
+                        Button::new("never-suggest", "Don't Suggest Again")
+                            .on_click(cx.listener(|this, _, cx| this.never_suggest(cx))),
+                    )
+                    .child(
+                        Button::new("install-all", "Install All")
+                            .style(ButtonStyle::Filled)
+                            .on_click(cx.listener(|this, _, cx| this.install_all(cx))),
+                    ),
+            )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +469,26 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    pub fn test_suggested_extensions_for_paths() {
+        let paths = [
+            "Dockerfile",
+            "src/main.zig",
+            "src/lib.zig",
+            "build.zig",
+            "README.md",
+            "schema.graphql",
+            "queries/user.gql",
+        ]
+        .map(Path::new);
+        assert_eq!(
+            suggested_extensions_for_paths(paths),
+            vec![
+                ("zig".into(), 3),
+                ("graphql".into(), 2),
+                ("dockerfile".into(), 1)
+            ]
+        );
+    }
 }
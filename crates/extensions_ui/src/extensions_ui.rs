@@ -94,10 +94,14 @@ pub fn init(cx: &mut AppContext) {
                     .detach();
             });
 
-        cx.subscribe(workspace.project(), |_, _, event, cx| {
-            if let project::Event::LanguageNotFound(buffer) = event {
+        cx.subscribe(workspace.project(), |workspace, _, event, cx| match event {
+            project::Event::LanguageNotFound(buffer) => {
                 extension_suggest::suggest(buffer.clone(), cx);
             }
+            project::Event::WorktreeAdded(worktree_id) => {
+                extension_suggest::suggest_for_worktree(workspace, *worktree_id, cx);
+            }
+            _ => {}
         })
         .detach();
     })
@@ -46,6 +46,13 @@ pub trait GitRepository: Send + Sync {
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
 
+    /// Returns the changes staged in the index, relative to HEAD, as a patch.
+    fn staged_diff(&self) -> Result<String>;
+
+    /// Returns all the uncommitted changes to tracked files, whether they're staged or not,
+    /// relative to HEAD, as a patch.
+    fn uncommitted_diff(&self) -> Result<String>;
+
     /// Returns the path to the repository, typically the `.git` folder.
     fn dot_git_dir(&self) -> PathBuf;
 }
@@ -225,6 +232,33 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn staged_diff(&self) -> Result<String> {
+        let repo = self.repository.lock();
+        // A repository without commits has no HEAD, so everything staged is new.
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        diff_to_patch(&diff)
+    }
+
+    fn uncommitted_diff(&self) -> Result<String> {
+        let repo = self.repository.lock();
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let diff = repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), None)?;
+        diff_to_patch(&diff)
+    }
+}
+
+fn diff_to_patch(diff: &git2::Diff) -> Result<String> {
+    let mut patch = String::new();
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
 }
 
 #[derive(Debug, Clone)]
@@ -241,6 +275,8 @@ pub struct FakeGitRepositoryState {
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    pub staged_diff: String,
+    pub uncommitted_diff: String,
 }
 
 impl FakeGitRepository {
@@ -259,6 +295,8 @@ impl FakeGitRepositoryState {
             worktree_statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            staged_diff: Default::default(),
+            uncommitted_diff: Default::default(),
         }
     }
 }
@@ -358,6 +396,16 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn staged_diff(&self) -> Result<String> {
+        let state = self.state.lock();
+        Ok(state.staged_diff.clone())
+    }
+
+    fn uncommitted_diff(&self) -> Result<String> {
+        let state = self.state.lock();
+        Ok(state.uncommitted_diff.clone())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
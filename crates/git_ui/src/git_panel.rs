@@ -8,9 +8,9 @@ use editor::{
 use git::{diff::DiffHunk, repository::GitFileStatus};
 use gpui::{
     actions, prelude::*, uniform_list, Action, AppContext, AsyncWindowContext, ClickEvent,
-    CursorStyle, EventEmitter, FocusHandle, FocusableView, KeyContext,
-    ListHorizontalSizingBehavior, ListSizingBehavior, Model, Modifiers, ModifiersChangedEvent,
-    MouseButton, ScrollStrategy, Stateful, Task, UniformListScrollHandle, View, WeakView,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, ListHorizontalSizingBehavior,
+    ListSizingBehavior, Model, Modifiers, ModifiersChangedEvent, MouseButton, ScrollStrategy,
+    Stateful, Task, UniformListScrollHandle, View, WeakView,
 };
 use language::{Buffer, BufferRow, OffsetRangeExt};
 use menu::{SelectNext, SelectPrev};
@@ -39,7 +39,9 @@ use workspace::{
 };
 
 use crate::{git_status_icon, settings::GitPanelSettings};
-use crate::{CommitAllChanges, CommitStagedChanges, DiscardAll, StageAll, UnstageAll};
+use crate::{
+    CommitAllChanges, CommitStagedChanges, DiscardAll, GenerateCommitMessage, StageAll, UnstageAll,
+};
 
 actions!(git_panel, [ToggleFocus]);
 
@@ -110,6 +112,7 @@ pub struct GitPanel {
     git_diff_editor: View<Editor>,
     git_diff_editor_updates: Task<()>,
     reveal_in_editor: Task<()>,
+    commit_editor: View<Editor>,
 }
 
 #[derive(Debug, Clone)]
@@ -214,6 +217,7 @@ impl GitPanel {
                 git_diff_editor: diff_display_editor(project.clone(), cx),
                 git_diff_editor_updates: Task::ready(()),
                 reveal_in_editor: Task::ready(()),
+                commit_editor: commit_message_editor(cx),
                 project,
             };
             this.update_visible_entries(None, None, cx);
@@ -396,6 +400,11 @@ impl GitPanel {
 }
 
 impl GitPanel {
+    pub fn set_commit_message(&mut self, message: &str, cx: &mut ViewContext<Self>) {
+        self.commit_editor
+            .update(cx, |editor, cx| editor.set_text(message, cx));
+    }
+
     fn stage_all(&mut self, _: &StageAll, _cx: &mut ViewContext<Self>) {
         // TODO: Implement stage all
         println!("Stage all triggered");
@@ -836,6 +845,7 @@ impl GitPanel {
     pub fn render_commit_editor(&self, cx: &ViewContext<Self>) -> impl IntoElement {
         let focus_handle_1 = self.focus_handle(cx).clone();
         let focus_handle_2 = self.focus_handle(cx).clone();
+        let focus_handle_3 = self.focus_handle(cx).clone();
 
         let commit_staged_button = self
             .panel_button("commit-staged-changes", "Commit")
@@ -867,6 +877,21 @@ impl GitPanel {
                 this.commit_all_changes(&CommitAllChanges, cx)
             }));
 
+        let generate_message_button = IconButton::new("generate-commit-message", IconName::Sparkle)
+            .icon_size(IconSize::Small)
+            .tooltip(move |cx| {
+                let focus_handle = focus_handle_3.clone();
+                Tooltip::for_action_in(
+                    "Generate a commit message",
+                    &GenerateCommitMessage,
+                    &focus_handle,
+                    cx,
+                )
+            })
+            .on_click(cx.listener(|_, _: &ClickEvent, cx| {
+                cx.dispatch_action(GenerateCommitMessage.boxed_clone())
+            }));
+
         div().w_full().h(px(140.)).px_2().pt_1().pb_2().child(
             v_flex()
                 .h_full()
@@ -875,19 +900,24 @@ impl GitPanel {
                 .bg(cx.theme().colors().editor_background)
                 .font_buffer(cx)
                 .text_ui_sm(cx)
-                .text_color(cx.theme().colors().text_muted)
-                .child("Add a message")
                 .gap_1()
-                .child(div().flex_grow())
-                .child(h_flex().child(div().gap_1().flex_grow()).child(
-                    if self.current_modifiers.alt {
-                        commit_all_button
-                    } else {
-                        commit_staged_button
-                    },
-                ))
-                .cursor(CursorStyle::OperationNotAllowed)
-                .opacity(0.5),
+                .child(
+                    div()
+                        .flex_grow()
+                        .overflow_hidden()
+                        .child(self.commit_editor.clone()),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(generate_message_button)
+                        .child(div().flex_grow())
+                        .child(if self.current_modifiers.alt {
+                            commit_all_button
+                        } else {
+                            commit_staged_button
+                        }),
+                ),
         )
     }
 
@@ -1236,6 +1266,14 @@ impl Panel for GitPanel {
     }
 }
 
+fn commit_message_editor(cx: &mut WindowContext) -> View<Editor> {
+    cx.new_view(|cx| {
+        let mut editor = Editor::auto_height(6, cx);
+        editor.set_placeholder_text("Add a message", cx);
+        editor
+    })
+}
+
 fn diff_display_editor(project: Model<Project>, cx: &mut WindowContext) -> View<Editor> {
     cx.new_view(|cx| {
         let multi_buffer = cx.new_model(|cx| {
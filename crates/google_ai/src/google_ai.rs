@@ -2,7 +2,7 @@ mod supported_countries;
 
 use anyhow::{anyhow, Result};
 use futures::{io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, Stream, StreamExt};
-use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest, ResponseStatusError};
 use serde::{Deserialize, Serialize};
 
 pub use supported_countries::*;
@@ -51,11 +51,12 @@ pub async fn stream_generate_content(
     } else {
         let mut text = String::new();
         response.body_mut().read_to_string(&mut text).await?;
-        Err(anyhow!(
+        let message = format!(
             "error during streamGenerateContent, status code: {:?}, body: {}",
             response.status(),
             text
-        ))
+        );
+        Err(ResponseStatusError::new(&response, message).into())
     }
 }
 
@@ -8,11 +8,11 @@ pub use http::{self, Method, Request, Response, StatusCode, Uri};
 
 use futures::future::BoxFuture;
 use http::request::Builder;
-#[cfg(feature = "test-support")]
-use std::fmt;
 use std::{
     any::type_name,
+    fmt,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 pub use url::Url;
 
@@ -85,6 +85,45 @@ pub trait HttpClient: 'static + Send + Sync {
     fn proxy(&self) -> Option<&Uri>;
 }
 
+/// An error for a response with an unsuccessful status, which callers can downcast to in order to
+/// decide whether the request is worth retrying.
+#[derive(Debug)]
+pub struct ResponseStatusError {
+    pub status: StatusCode,
+    /// How long the server asked us to wait before retrying, from the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+    message: String,
+}
+
+impl ResponseStatusError {
+    pub fn new<T>(response: &Response<T>, message: impl Into<String>) -> Self {
+        let retry_after = response
+            .headers()
+            .get(http::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        Self {
+            status: response.status(),
+            retry_after,
+            message: message.into(),
+        }
+    }
+
+    /// Whether the server is rate limiting us or failed in a way that may not happen again.
+    pub fn is_retryable(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS || self.status.is_server_error()
+    }
+}
+
+impl fmt::Display for ResponseStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ResponseStatusError {}
+
 /// An [`HttpClient`] that may have a proxy.
 #[derive(Deref)]
 pub struct HttpClientWithProxy {
@@ -829,11 +829,16 @@ impl Buffer {
     /// given `ranges` to its base buffer.
     ///
     /// If `ranges` is empty, then all changes will be applied. This buffer must
-    /// be a branch buffer to call this method.
-    pub fn merge_into_base(&mut self, ranges: Vec<Range<usize>>, cx: &mut ModelContext<Self>) {
+    /// be a branch buffer to call this method. Returns the transaction of the
+    /// base buffer that applied the changes, if there were any.
+    pub fn merge_into_base(
+        &mut self,
+        ranges: Vec<Range<usize>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Transaction> {
         let Some(base_buffer) = self.base_buffer() else {
             debug_panic!("not a branch buffer");
-            return;
+            return None;
         };
 
         let mut ranges = if ranges.is_empty() {
@@ -866,9 +871,13 @@ impl Buffer {
             }
         }
 
-        let operation = base_buffer.update(cx, |base_buffer, cx| {
+        let (operation, transaction) = base_buffer.update(cx, |base_buffer, cx| {
             // cx.emit(BufferEvent::DiffBaseChanged);
-            base_buffer.edit(edits, None, cx)
+            let operation = base_buffer.edit(edits, None, cx);
+            // Keep the merge from being grouped with later edits, so it can be undone on its own.
+            let transaction =
+                operation.and_then(|_| base_buffer.finalize_last_transaction().cloned());
+            (operation, transaction)
         });
 
         if let Some(operation) = operation {
@@ -879,6 +888,8 @@ impl Buffer {
                 merged_operations.push(operation);
             }
         }
+
+        transaction
     }
 
     fn on_base_buffer_event(
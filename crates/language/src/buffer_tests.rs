@@ -2718,6 +2718,28 @@ fn test_undo_after_merge_into_base(cx: &mut TestAppContext) {
     branch.read_with(cx, |branch, _| assert_eq!(branch.text(), "ABCdefgHIjk"));
 }
 
+#[gpui::test]
+fn test_merge_into_base_transaction(cx: &mut TestAppContext) {
+    cx.update(|cx| init_settings(cx, |_| {}));
+
+    let base = cx.new_model(|cx| Buffer::local("abcdefghijk", cx));
+    let branch = base.update(cx, |buffer, cx| buffer.branch(cx));
+
+    // Merging returns the base buffer's transaction, which undoes the merge.
+    let transaction = branch.update(cx, |branch, cx| {
+        branch.edit([(0..3, "ABC"), (7..9, "HI")], None, cx);
+        branch.merge_into_base(Vec::new(), cx)
+    });
+    let transaction = transaction.expect("merging changes creates a transaction");
+    base.read_with(cx, |base, _| assert_eq!(base.text(), "ABCdefgHIjk"));
+    base.update(cx, |base, cx| base.undo_transaction(transaction.id, cx));
+    base.read_with(cx, |base, _| assert_eq!(base.text(), "abcdefghijk"));
+
+    // Merging without any changes in the given ranges creates no transaction.
+    let transaction = branch.update(cx, |branch, cx| branch.merge_into_base(vec![4..5], cx));
+    assert!(transaction.is_none());
+}
+
 #[gpui::test(iterations = 100)]
 fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
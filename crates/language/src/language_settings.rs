@@ -24,6 +24,7 @@ use settings::{
     add_references_to_properties, Settings, SettingsLocation, SettingsSources, SettingsStore,
 };
 use std::{borrow::Cow, num::NonZeroU32, path::Path, sync::Arc};
+use task::TaskTemplate;
 use util::serde::default_true;
 
 /// Initializes the language settings.
@@ -831,7 +832,14 @@ fn scroll_debounce_ms() -> u64 {
 #[derive(Debug, Clone, Deserialize, PartialEq, Serialize, JsonSchema)]
 pub struct LanguageTaskConfig {
     /// Extra task variables to set for a particular language.
+    #[serde(default)]
     pub variables: HashMap<String, String>,
+    /// Tasks to list for the files of the language, along with the ones provided for it.
+    /// A task with the same label as a provided one replaces it.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub templates: Vec<TaskTemplate>,
 }
 
 impl InlayHintSettings {
@@ -6,6 +6,7 @@
 
 use std::{path::PathBuf, sync::Arc};
 
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use collections::HashMap;
 use gpui::{AsyncAppContext, SharedString};
@@ -24,6 +25,20 @@ pub struct Toolchain {
     pub as_json: serde_json::Value,
 }
 
+impl Toolchain {
+    /// Whether this toolchain is present on the machine.
+    ///
+    /// Listers may report toolchains that a project asks for but that are not installed yet
+    /// (e.g. a channel pinned in `rust-toolchain.toml`) by setting `"installed": false` in
+    /// [`Toolchain::as_json`]; such toolchains can be set up with [`ToolchainLister::install`].
+    pub fn is_installed(&self) -> bool {
+        self.as_json
+            .get("installed")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(true)
+    }
+}
+
 impl PartialEq for Toolchain {
     fn eq(&self, other: &Self) -> bool {
         // Do not use as_json for comparisons; it shouldn't impact equality, as it's not user-surfaced.
@@ -45,6 +60,44 @@ pub trait ToolchainLister: Send + Sync {
     ) -> ToolchainList;
     // Returns a term which we should use in UI to refer to a toolchain.
     fn term(&self) -> SharedString;
+    /// Installs a toolchain that was listed as missing (see [`Toolchain::is_installed`]),
+    /// returning the toolchain as it should be activated once installed.
+    async fn install(
+        &self,
+        toolchain: Toolchain,
+        _worktree_root: PathBuf,
+        _project_env: Option<HashMap<String, String>>,
+    ) -> Result<Toolchain> {
+        Err(anyhow!(
+            "installing {} toolchains is not supported",
+            toolchain.language_name.0
+        ))
+    }
+    /// Environment variables that activating the toolchain sets (e.g. `VIRTUAL_ENV`).
+    fn activation_environment(&self, _toolchain: &Toolchain) -> HashMap<String, String> {
+        HashMap::default()
+    }
+    /// Gathers information that helps telling similar-looking toolchains apart.
+    async fn details(
+        &self,
+        toolchain: Toolchain,
+        _project_env: Option<HashMap<String, String>>,
+    ) -> ToolchainDetails {
+        ToolchainDetails {
+            version: None,
+            environment: self.activation_environment(&toolchain),
+            packages: None,
+        }
+    }
+}
+
+/// A closer look at a single toolchain, as shown in the toolchain selector.
+#[derive(Clone, Debug, Default)]
+pub struct ToolchainDetails {
+    pub version: Option<SharedString>,
+    pub environment: HashMap<String, String>,
+    /// Names of the packages installed into the toolchain, for languages that have them.
+    pub packages: Option<Vec<SharedString>>,
 }
 
 #[async_trait(?Send)]
@@ -1,13 +1,19 @@
+use anthropic::{AnthropicError, ApiErrorCode};
 use anyhow::Result;
 use futures::Stream;
+use http_client::ResponseStatusError;
 use smol::lock::{Semaphore, SemaphoreGuardArc};
 use std::{
     future::Future,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
+/// How many requests the providers allow to be in flight to each of their models at once.
+pub const MAX_CONCURRENT_REQUESTS_PER_MODEL: usize = 4;
+
 #[derive(Clone)]
 pub struct RateLimiter {
     semaphore: Arc<Semaphore>,
@@ -68,3 +74,33 @@ impl RateLimiter {
         }
     }
 }
+
+/// Returns whether a request that failed with the given error is worth sending again, because the
+/// provider rate limited it or failed in a way that may not happen again.
+pub fn is_retryable_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(error) = cause.downcast_ref::<ResponseStatusError>() {
+            error.is_retryable()
+        } else if let Some(AnthropicError::ApiError(error)) = cause.downcast_ref() {
+            matches!(
+                error.code(),
+                Some(
+                    ApiErrorCode::RateLimitError
+                        | ApiErrorCode::ApiError
+                        | ApiErrorCode::OverloadedError
+                )
+            )
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns how long the provider asked us to wait before retrying a request that failed with the
+/// given error.
+pub fn retry_after(error: &anyhow::Error) -> Option<Duration> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ResponseStatusError>())
+        .and_then(|error| error.retry_after)
+}
@@ -261,6 +261,9 @@ impl LanguageModelRequest {
                 })
                 .collect(),
             stream,
+            stream_options: stream.then_some(open_ai::StreamOptions {
+                include_usage: true,
+            }),
             stop: self.stop,
             temperature: self.temperature.unwrap_or(1.0),
             max_tokens: max_output_tokens,
@@ -306,6 +309,7 @@ impl LanguageModelRequest {
     ) -> anthropic::Request {
         let mut new_messages: Vec<anthropic::Message> = Vec::new();
         let mut system_message = String::new();
+        let mut cache_system_message = false;
 
         for message in self.messages {
             if message.contents_empty() {
@@ -384,15 +388,27 @@ impl LanguageModelRequest {
                         system_message.push_str("\n\n");
                     }
                     system_message.push_str(&message.string_contents());
+                    cache_system_message |= message.cache;
                 }
             }
         }
 
+        let system = if cache_system_message {
+            anthropic::System::Content(vec![anthropic::RequestContent::Text {
+                text: system_message,
+                cache_control: Some(anthropic::CacheControl {
+                    cache_type: anthropic::CacheControlType::Ephemeral,
+                }),
+            }])
+        } else {
+            anthropic::System::Text(system_message)
+        };
+
         anthropic::Request {
             model,
             messages: new_messages,
             max_tokens: max_output_tokens,
-            system: Some(system_message),
+            system: Some(system),
             tools: self
                 .tools
                 .into_iter()
@@ -1,5 +1,5 @@
 use crate::AllLanguageModelSettings;
-use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent};
+use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent, Usage};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap};
 use editor::{Editor, EditorElement, EditorStyle};
@@ -14,8 +14,9 @@ use language_model::{
     LanguageModel, LanguageModelCacheConfiguration, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    MAX_CONCURRENT_REQUESTS_PER_MODEL,
 };
-use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
+use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
@@ -214,7 +215,7 @@ impl LanguageModelProvider for AnthropicLanguageModelProvider {
                     model,
                     state: self.state.clone(),
                     http_client: self.http_client.clone(),
-                    request_limiter: RateLimiter::new(4),
+                    request_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
@@ -444,14 +445,27 @@ pub fn map_to_language_model_completion_events(
     struct State {
         events: Pin<Box<dyn Send + Stream<Item = Result<Event, AnthropicError>>>>,
         tool_uses_by_index: HashMap<usize, RawToolUse>,
+        usage: TokenUsage,
+        // Usage is reported alongside other events, so it's sent as its own event right after them.
+        pending_usage_update: bool,
     }
 
     futures::stream::unfold(
         State {
             events,
             tool_uses_by_index: HashMap::default(),
+            usage: TokenUsage::default(),
+            pending_usage_update: false,
         },
         |mut state| async move {
+            if state.pending_usage_update {
+                state.pending_usage_update = false;
+                return Some((
+                    Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                    state,
+                ));
+            }
+
             while let Some(event) = state.events.next().await {
                 match event {
                     Ok(event) => match event {
@@ -516,14 +530,18 @@ pub fn map_to_language_model_completion_events(
                             }
                         }
                         Event::MessageStart { message } => {
+                            update_usage(&mut state.usage, &message.usage);
+                            state.pending_usage_update = true;
                             return Some((
                                 Some(Ok(LanguageModelCompletionEvent::StartMessage {
                                     message_id: message.id,
                                 })),
                                 state,
-                            ))
+                            ));
                         }
-                        Event::MessageDelta { delta, .. } => {
+                        Event::MessageDelta { delta, usage } => {
+                            update_usage(&mut state.usage, &usage);
+                            state.pending_usage_update = true;
                             if let Some(stop_reason) = delta.stop_reason.as_deref() {
                                 let stop_reason = match stop_reason {
                                     "end_turn" => StopReason::EndTurn,
@@ -537,6 +555,11 @@ pub fn map_to_language_model_completion_events(
                                     state,
                                 ));
                             }
+                            state.pending_usage_update = false;
+                            return Some((
+                                Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                                state,
+                            ));
                         }
                         Event::Error { error } => {
                             return Some((
@@ -558,6 +581,22 @@ pub fn map_to_language_model_completion_events(
     .filter_map(|event| async move { event })
 }
 
+/// Updates the usage with the counts in an event, which only includes the ones that changed.
+fn update_usage(usage: &mut TokenUsage, new: &Usage) {
+    if let Some(input_tokens) = new.input_tokens {
+        usage.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = new.output_tokens {
+        usage.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = new.cache_creation_input_tokens {
+        usage.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = new.cache_read_input_tokens {
+        usage.cache_read_input_tokens = cache_read_input_tokens;
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: View<Editor>,
     state: gpui::Model<State>,
@@ -740,3 +779,90 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn completion_events(events: Vec<serde_json::Value>) -> Vec<LanguageModelCompletionEvent> {
+        let events = events
+            .into_iter()
+            .map(|event| Ok(serde_json::from_value::<Event>(event).unwrap()))
+            .collect::<Vec<_>>();
+        smol::block_on(
+            map_to_language_model_completion_events(futures::stream::iter(events).boxed())
+                .map(|event| event.unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_usage_updates() {
+        let events = completion_events(vec![
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [],
+                    "model": "claude-3-5-sonnet-latest",
+                    "usage": {
+                        "input_tokens": 10,
+                        "output_tokens": 1,
+                        "cache_creation_input_tokens": 200,
+                        "cache_read_input_tokens": 1000
+                    }
+                }
+            }),
+            json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": { "type": "text", "text": "" }
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "text_delta", "text": "Hello" }
+            }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({
+                "type": "message_delta",
+                "delta": { "stop_reason": null, "stop_sequence": null },
+                "usage": { "output_tokens": 8 }
+            }),
+            json!({
+                "type": "message_delta",
+                "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+                "usage": { "output_tokens": 15 }
+            }),
+            json!({ "type": "message_stop" }),
+        ]);
+
+        // Usage is reported right after the event it came with, keeping the counts that the
+        // later events leave out.
+        let usage = |output_tokens| {
+            LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                input_tokens: 10,
+                output_tokens,
+                cache_creation_input_tokens: 200,
+                cache_read_input_tokens: 1000,
+            })
+        };
+        assert_eq!(
+            events,
+            vec![
+                LanguageModelCompletionEvent::StartMessage {
+                    message_id: "msg_1".into()
+                },
+                usage(1),
+                LanguageModelCompletionEvent::Text(String::new()),
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                usage(8),
+                LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+                usage(15),
+            ]
+        );
+    }
+}
@@ -15,11 +15,11 @@ use gpui::{
     AnyElement, AnyView, AppContext, AsyncAppContext, EventEmitter, FontWeight, Global, Model,
     ModelContext, ReadGlobal, Subscription, Task,
 };
-use http_client::{AsyncBody, HttpClient, Method, Response, StatusCode};
+use http_client::{AsyncBody, HttpClient, Method, Response, ResponseStatusError, StatusCode};
 use language_model::{
     CloudModel, LanguageModel, LanguageModelCacheConfiguration, LanguageModelId, LanguageModelName,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter, ZED_CLOUD_PROVIDER_ID,
+    LanguageModelRequest, RateLimiter, MAX_CONCURRENT_REQUESTS_PER_MODEL, ZED_CLOUD_PROVIDER_ID,
 };
 use language_model::{
     LanguageModelAvailability, LanguageModelCompletionEvent, LanguageModelProvider,
@@ -349,7 +349,7 @@ impl LanguageModelProvider for CloudLanguageModelProvider {
                     model,
                     llm_api_token: llm_api_token.clone(),
                     client: self.client.clone(),
-                    request_limiter: RateLimiter::new(4),
+                    request_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
@@ -512,10 +512,11 @@ impl CloudLanguageModel {
             } else {
                 let mut body = String::new();
                 response.body_mut().read_to_string(&mut body).await?;
-                break Err(anyhow!(
+                let message = format!(
                     "cloud language model completion failed with status {}: {body}",
                     response.status()
-                ))?;
+                );
+                break Err(ResponseStatusError::new(&response, message))?;
             }
         };
 
@@ -650,15 +651,11 @@ impl LanguageModel for CloudLanguageModel {
                         },
                     )
                     .await?;
-                    Ok(open_ai::extract_text_from_events(response_lines(response)))
+                    Ok(super::open_ai::map_to_language_model_completion_events(
+                        response_lines(response),
+                    ))
                 });
-                async move {
-                    Ok(future
-                        .await?
-                        .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                        .boxed())
-                }
-                .boxed()
+                async move { Ok(future.await?.boxed()) }.boxed()
             }
             CloudModel::Google(model) => {
                 let client = self.client.clone();
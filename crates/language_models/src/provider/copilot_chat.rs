@@ -18,6 +18,7 @@ use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    MAX_CONCURRENT_REQUESTS_PER_MODEL,
 };
 use settings::SettingsStore;
 use std::time::Duration;
@@ -93,7 +94,7 @@ impl LanguageModelProvider for CopilotChatLanguageModelProvider {
             .map(|model| {
                 Arc::new(CopilotChatLanguageModel {
                     model,
-                    request_limiter: RateLimiter::new(4),
+                    request_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
@@ -12,7 +12,7 @@ use language_model::LanguageModelCompletionEvent;
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter,
+    LanguageModelRequest, RateLimiter, MAX_CONCURRENT_REQUESTS_PER_MODEL,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -185,7 +185,7 @@ impl LanguageModelProvider for GoogleLanguageModelProvider {
                     model,
                     state: self.state.clone(),
                     http_client: self.http_client.clone(),
-                    rate_limiter: RateLimiter::new(4),
+                    rate_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
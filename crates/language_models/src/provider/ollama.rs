@@ -6,7 +6,7 @@ use language_model::LanguageModelCompletionEvent;
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter, Role,
+    LanguageModelRequest, RateLimiter, Role, MAX_CONCURRENT_REQUESTS_PER_MODEL,
 };
 use ollama::{
     get_models, preload_model, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest,
@@ -54,6 +54,8 @@ pub struct OllamaLanguageModelProvider {
 pub struct State {
     http_client: Arc<dyn HttpClient>,
     available_models: Vec<ollama::Model>,
+    /// Why the models could not be fetched the last time, e.g. because Ollama is not running.
+    fetch_models_error: Option<SharedString>,
     fetch_model_task: Option<Task<Result<()>>>,
     _subscription: Subscription,
 }
@@ -70,7 +72,20 @@ impl State {
 
         // As a proxy for the server being "authenticated", we'll check if its up by fetching the models
         cx.spawn(|this, mut cx| async move {
-            let models = get_models(http_client.as_ref(), &api_url, None).await?;
+            let models = match get_models(http_client.as_ref(), &api_url, None).await {
+                Ok(models) => models,
+                Err(error) => {
+                    // Models that were fetched before can't be used while Ollama is unreachable.
+                    this.update(&mut cx, |this, cx| {
+                        this.available_models.clear();
+                        this.fetch_models_error = Some(
+                            format!("Could not connect to Ollama at {api_url}: {error}").into(),
+                        );
+                        cx.notify();
+                    })?;
+                    return Err(error);
+                }
+            };
 
             let mut models: Vec<ollama::Model> = models
                 .into_iter()
@@ -85,6 +100,7 @@ impl State {
 
             this.update(&mut cx, |this, cx| {
                 this.available_models = models;
+                this.fetch_models_error = None;
                 cx.notify();
             })
         })
@@ -124,6 +140,7 @@ impl OllamaLanguageModelProvider {
                 State {
                     http_client,
                     available_models: Default::default(),
+                    fetch_models_error: None,
                     fetch_model_task: None,
                     _subscription: subscription,
                 }
@@ -188,7 +205,7 @@ impl LanguageModelProvider for OllamaLanguageModelProvider {
                     id: LanguageModelId::from(model.name.clone()),
                     model: model.clone(),
                     http_client: self.http_client.clone(),
-                    request_limiter: RateLimiter::new(4),
+                    request_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
@@ -445,7 +462,14 @@ impl ConfigurationView {
 
 impl Render for ConfigurationView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let is_authenticated = self.state.read(cx).is_authenticated();
+        let state = self.state.read(cx);
+        let is_authenticated = state.is_authenticated();
+        let fetch_models_error = state.fetch_models_error.clone();
+        let available_models = state
+            .available_models
+            .iter()
+            .map(|model| SharedString::from(model.display_name().to_string()))
+            .collect::<Vec<_>>();
 
         let ollama_intro = "Get up and running with Llama 3.3, Mistral, Gemma 2, and other large language models with Ollama.";
         let ollama_reqs =
@@ -480,6 +504,40 @@ impl Render for ConfigurationView {
                                 ),
                         ),
                 )
+                .when(!available_models.is_empty(), |this| {
+                    this.child(
+                        v_flex()
+                            .gap_1()
+                            .p_1()
+                            .child(Label::new("Available models:").color(Color::Muted))
+                            .children(available_models.into_iter().map(|model| {
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Icon::new(IconName::Check)
+                                            .size(IconSize::Small)
+                                            .color(Color::Success),
+                                    )
+                                    .child(Label::new(model))
+                            })),
+                    )
+                })
+                .when_some(
+                    fetch_models_error.filter(|_| !is_authenticated),
+                    |this, error| {
+                        this.child(
+                            h_flex()
+                                .gap_2()
+                                .p_1()
+                                .child(
+                                    Icon::new(IconName::Warning)
+                                        .size(IconSize::Small)
+                                        .color(Color::Warning),
+                                )
+                                .child(Label::new(error).size(LabelSize::Small)),
+                        )
+                    },
+                )
                 .child(
                     h_flex()
                         .w_full()
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
 use gpui::{
     AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
     View, WhiteSpace,
@@ -10,7 +10,8 @@ use http_client::HttpClient;
 use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role, TokenUsage,
+    MAX_CONCURRENT_REQUESTS_PER_MODEL,
 };
 use open_ai::{
     stream_completion, FunctionDefinition, ResponseStreamEvent, ToolChoice, ToolDefinition,
@@ -33,6 +34,7 @@ const PROVIDER_NAME: &str = "OpenAI";
 pub struct OpenAiSettings {
     pub api_url: String,
     pub available_models: Vec<AvailableModel>,
+    pub include_usage: bool,
     pub needs_setting_migration: bool,
 }
 
@@ -188,7 +190,7 @@ impl LanguageModelProvider for OpenAiLanguageModelProvider {
                     model,
                     state: self.state.clone(),
                     http_client: self.http_client.clone(),
-                    request_limiter: RateLimiter::new(4),
+                    request_limiter: RateLimiter::new(MAX_CONCURRENT_REQUESTS_PER_MODEL),
                 }) as Arc<dyn LanguageModel>
             })
             .collect()
@@ -223,17 +225,24 @@ pub struct OpenAiLanguageModel {
 impl OpenAiLanguageModel {
     fn stream_completion(
         &self,
-        request: open_ai::Request,
+        mut request: open_ai::Request,
         cx: &AsyncAppContext,
     ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<ResponseStreamEvent>>>>
     {
         let http_client = self.http_client.clone();
-        let Ok((api_key, api_url)) = cx.read_model(&self.state, |state, cx| {
+        let Ok((api_key, api_url, include_usage)) = cx.read_model(&self.state, |state, cx| {
             let settings = &AllLanguageModelSettings::get_global(cx).openai;
-            (state.api_key.clone(), settings.api_url.clone())
+            (
+                state.api_key.clone(),
+                settings.api_url.clone(),
+                settings.include_usage,
+            )
         }) else {
             return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
         };
+        if !include_usage {
+            request.stream_options = None;
+        }
 
         let future = self.request_limiter.stream(async move {
             let api_key = api_key.ok_or_else(|| anyhow!("Missing OpenAI API Key"))?;
@@ -293,12 +302,8 @@ impl LanguageModel for OpenAiLanguageModel {
     > {
         let request = request.into_open_ai(self.model.id().into(), self.max_output_tokens());
         let completions = self.stream_completion(request, cx);
-        async move {
-            Ok(open_ai::extract_text_from_events(completions.await?)
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_to_language_model_completion_events(completions.await?).boxed()) }
+            .boxed()
     }
 
     fn use_any_tool(
@@ -339,6 +344,37 @@ impl LanguageModel for OpenAiLanguageModel {
     }
 }
 
+pub fn map_to_language_model_completion_events(
+    events: impl Stream<Item = Result<ResponseStreamEvent>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    events.flat_map(|event| {
+        let events = match event {
+            Ok(mut event) => {
+                let text = event
+                    .choices
+                    .pop()
+                    .and_then(|choice| choice.delta.content)
+                    .map(|text| Ok(LanguageModelCompletionEvent::Text(text)));
+                let usage = event.usage.map(|usage| {
+                    // OpenAI counts the cached tokens as part of the prompt tokens.
+                    let cached_tokens = usage
+                        .prompt_tokens_details
+                        .map_or(0, |details| details.cached_tokens);
+                    Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                        input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
+                        output_tokens: usage.completion_tokens,
+                        cache_creation_input_tokens: 0,
+                        cache_read_input_tokens: cached_tokens,
+                    }))
+                });
+                text.into_iter().chain(usage).collect::<Vec<_>>()
+            }
+            Err(error) => vec![Err(error)],
+        };
+        futures::stream::iter(events)
+    })
+}
+
 pub fn count_open_ai_tokens(
     request: LanguageModelRequest,
     model: open_ai::Model,
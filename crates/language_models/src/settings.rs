@@ -185,6 +185,7 @@ impl OpenAiSettingsContent {
                             })
                             .collect()
                     }),
+                    include_usage: None,
                 },
                 true,
             ),
@@ -212,6 +213,12 @@ pub enum VersionedOpenAiSettingsContent {
 pub struct OpenAiSettingsContentV1 {
     pub api_url: Option<String>,
     pub available_models: Option<Vec<provider::open_ai::AvailableModel>>,
+    /// Whether to ask the API to report the tokens used by a response, by sending
+    /// `stream_options`, which some OpenAI compatible APIs reject.
+    ///
+    /// Default: true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_usage: Option<bool>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -294,6 +301,10 @@ impl settings::Settings for AllLanguageModelSettings {
                 &mut settings.openai.available_models,
                 openai.as_ref().and_then(|s| s.available_models.clone()),
             );
+            merge(
+                &mut settings.openai.include_usage,
+                openai.as_ref().and_then(|s| s.include_usage),
+            );
             merge(
                 &mut settings.zed_dot_dev.available_models,
                 value
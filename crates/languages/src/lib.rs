@@ -9,10 +9,14 @@ use rust_embed::RustEmbed;
 use settings::SettingsStore;
 use smol::stream::StreamExt;
 use std::{str, sync::Arc};
-use typescript::typescript_task_context;
+use typescript::{typescript_task_context, NodeToolchainProvider};
 use util::{asset_str, ResultExt};
 
-use crate::{bash::bash_task_context, go::GoContextProvider, rust::RustContextProvider};
+use crate::{
+    bash::bash_task_context,
+    go::GoContextProvider,
+    rust::{RustContextProvider, RustToolchainProvider},
+};
 
 mod bash;
 mod c;
@@ -189,7 +193,8 @@ pub fn init(languages: Arc<LanguageRegistry>, node_runtime: NodeRuntime, cx: &mu
     language!(
         "rust",
         vec![Arc::new(rust::RustLspAdapter)],
-        RustContextProvider
+        RustContextProvider::default(),
+        Arc::new(RustToolchainProvider::default()) as Arc<dyn ToolchainLister>
     );
     language!(
         "tsx",
@@ -197,7 +202,8 @@ pub fn init(languages: Arc<LanguageRegistry>, node_runtime: NodeRuntime, cx: &mu
             Arc::new(typescript::TypeScriptLspAdapter::new(node_runtime.clone())),
             Arc::new(vtsls::VtslsLspAdapter::new(node_runtime.clone()))
         ],
-        typescript_task_context()
+        typescript_task_context(),
+        Arc::new(NodeToolchainProvider::new(LanguageName::new("TSX"))) as Arc<dyn ToolchainLister>
     );
     language!(
         "typescript",
@@ -205,7 +211,9 @@ pub fn init(languages: Arc<LanguageRegistry>, node_runtime: NodeRuntime, cx: &mu
             Arc::new(typescript::TypeScriptLspAdapter::new(node_runtime.clone())),
             Arc::new(vtsls::VtslsLspAdapter::new(node_runtime.clone()))
         ],
-        typescript_task_context()
+        typescript_task_context(),
+        Arc::new(NodeToolchainProvider::new(LanguageName::new("TypeScript")))
+            as Arc<dyn ToolchainLister>
     );
     language!(
         "javascript",
@@ -213,7 +221,9 @@ pub fn init(languages: Arc<LanguageRegistry>, node_runtime: NodeRuntime, cx: &mu
             Arc::new(typescript::TypeScriptLspAdapter::new(node_runtime.clone())),
             Arc::new(vtsls::VtslsLspAdapter::new(node_runtime.clone()))
         ],
-        typescript_task_context()
+        typescript_task_context(),
+        Arc::new(NodeToolchainProvider::new(LanguageName::new("JavaScript")))
+            as Arc<dyn ToolchainLister>
     );
     language!(
         "jsdoc",
@@ -317,6 +327,21 @@ pub fn language(name: &str, grammar: tree_sitter::Language) -> Arc<Language> {
     )
 }
 
+/// Runs a command of a version manager, like rustup or fnm, giving `None` without logging when the
+/// version manager isn't installed, as most users only have some of them.
+async fn version_manager_output(
+    mut command: smol::process::Command,
+) -> Option<std::process::Output> {
+    match command.output().await {
+        Ok(output) => Some(output),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            log::error!("failed to run {command:?}: {error}");
+            None
+        }
+    }
+}
+
 fn load_config(name: &str) -> LanguageConfig {
     let config_toml = String::from_utf8(
         LanguageDir::get(&format!("{}/config.toml", name))
@@ -8,6 +8,7 @@ use language::language_settings::language_settings;
 use language::LanguageName;
 use language::LanguageToolchainStore;
 use language::Toolchain;
+use language::ToolchainDetails;
 use language::ToolchainList;
 use language::ToolchainLister;
 use language::{ContextProvider, LspAdapter, LspAdapterDelegate};
@@ -575,7 +576,7 @@ impl ToolchainLister for PythonToolchainProvider {
             &environment,
         );
         let mut config = Configuration::default();
-        config.workspace_directories = Some(vec![worktree_root]);
+        config.workspace_directories = Some(vec![worktree_root.clone()]);
         for locator in locators.iter() {
             locator.configure(&config);
         }
@@ -589,9 +590,21 @@ impl ToolchainLister for PythonToolchainProvider {
             .ok()
             .map_or(Vec::new(), |mut guard| std::mem::take(&mut guard));
 
+        // Environments that belong to this worktree (e.g. `.venv`, or a Poetry/Pipenv
+        // environment created for it) are the most likely choice, so list them first.
+        let belongs_to_worktree = |env: &pet_core::python_environment::PythonEnvironment| {
+            env.project
+                .as_ref()
+                .map_or(false, |project| project.starts_with(&worktree_root))
+                || env
+                    .executable
+                    .as_ref()
+                    .map_or(false, |executable| executable.starts_with(&worktree_root))
+        };
         toolchains.sort_by(|lhs, rhs| {
-            env_priority(lhs.kind)
-                .cmp(&env_priority(rhs.kind))
+            belongs_to_worktree(rhs)
+                .cmp(&belongs_to_worktree(lhs))
+                .then_with(|| env_priority(lhs.kind).cmp(&env_priority(rhs.kind)))
                 .then_with(|| {
                     if lhs.kind == Some(PythonEnvironmentKind::Conda) {
                         environment
@@ -617,30 +630,264 @@ impl ToolchainLister for PythonToolchainProvider {
         let mut toolchains: Vec<_> = toolchains
             .into_iter()
             .filter_map(|toolchain| {
+                let is_local = belongs_to_worktree(&toolchain);
                 let name = if let Some(version) = &toolchain.version {
                     format!("Python {version} ({:?})", toolchain.kind?)
                 } else {
                     format!("{:?}", toolchain.kind?)
                 }
                 .into();
-                Some(Toolchain {
-                    name,
-                    path: toolchain.executable.as_ref()?.to_str()?.to_owned().into(),
-                    language_name: LanguageName::new("Python"),
-                    as_json: serde_json::to_value(toolchain).ok()?,
-                })
+                Some((
+                    is_local,
+                    Toolchain {
+                        name,
+                        path: toolchain.executable.as_ref()?.to_str()?.to_owned().into(),
+                        language_name: LanguageName::new("Python"),
+                        as_json: serde_json::to_value(toolchain).ok()?,
+                    },
+                ))
             })
             .collect();
-        toolchains.dedup();
+        toolchains.dedup_by(|(_, lhs), (_, rhs)| lhs == rhs);
+
+        // A `.python-version` pin that none of the discovered environments satisfy can be
+        // installed through pyenv from the selector.
+        if let Some((version, version_file)) = pinned_python_version(&worktree_root).await {
+            let is_satisfied = toolchains
+                .iter()
+                .any(|(_, toolchain)| python_version_matches(toolchain, &version));
+            if !is_satisfied {
+                toolchains.extend(version_file.to_str().map(|version_file| {
+                    (
+                        true,
+                        Toolchain {
+                            name: format!("Python {version} (Pyenv)").into(),
+                            path: version_file.to_owned().into(),
+                            language_name: LanguageName::new("Python"),
+                            as_json: json!({ "version": version, "installed": false }),
+                        },
+                    )
+                }));
+                toolchains.sort_by_key(|(is_local, _)| !is_local);
+            }
+        }
+
+        let local_count = toolchains
+            .iter()
+            .take_while(|(is_local, _)| *is_local)
+            .count();
+        let mut groups = Vec::new();
+        if local_count > 0 {
+            groups.push((0, SharedString::new_static("Project")));
+        }
+        if local_count < toolchains.len() {
+            groups.push((local_count, SharedString::new_static("Global")));
+        }
+        let default = toolchains
+            .iter()
+            .position(|(is_local, toolchain)| *is_local && toolchain.is_installed());
         ToolchainList {
-            toolchains,
-            default: None,
-            groups: Default::default(),
+            toolchains: toolchains
+                .into_iter()
+                .map(|(_, toolchain)| toolchain)
+                .collect(),
+            default,
+            groups: groups.into(),
         }
     }
     fn term(&self) -> SharedString {
         self.term.clone()
     }
+    async fn install(
+        &self,
+        toolchain: Toolchain,
+        worktree_root: PathBuf,
+        project_env: Option<HashMap<String, String>>,
+    ) -> Result<Toolchain> {
+        let version = toolchain
+            .as_json
+            .get("version")
+            .and_then(|version| version.as_str())
+            .ok_or_else(|| anyhow!("toolchain {} has no Python version", toolchain.name))?;
+        let pyenv = |args: &[&str]| {
+            let mut command = util::command::new_smol_command("pyenv");
+            if let Some(env) = &project_env {
+                command.envs(env);
+            }
+            command.current_dir(&worktree_root).args(args);
+            command
+        };
+
+        let output = pyenv(&["install", "--skip-existing", version])
+            .output()
+            .await
+            .map_err(|e| anyhow!("failed to run pyenv: {e}"))?;
+        ensure!(
+            output.status.success(),
+            "pyenv failed to install Python {version}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let output = pyenv(&["prefix", version])
+            .output()
+            .await
+            .map_err(|e| anyhow!("failed to run pyenv: {e}"))?;
+        ensure!(
+            output.status.success(),
+            "pyenv could not locate Python {version}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let prefix = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+        // Prefer the environment as reported by pet, so that it's indistinguishable from one
+        // that was installed before Zed was started.
+        let listed = self
+            .list(worktree_root, project_env.clone())
+            .await
+            .toolchains
+            .into_iter()
+            .find(|listed| {
+                listed.is_installed() && Path::new(listed.path.as_ref()).starts_with(&prefix)
+            });
+        if let Some(listed) = listed {
+            return Ok(listed);
+        }
+
+        let executable = if cfg!(windows) {
+            prefix.join("python.exe")
+        } else {
+            prefix.join("bin").join("python")
+        };
+        Ok(Toolchain {
+            name: toolchain.name,
+            path: executable
+                .to_str()
+                .ok_or_else(|| anyhow!("invalid Python path {executable:?}"))?
+                .to_owned()
+                .into(),
+            language_name: toolchain.language_name,
+            as_json: json!({
+                "version": version,
+                "prefix": prefix,
+                "executable": executable,
+            }),
+        })
+    }
+    fn activation_environment(&self, toolchain: &Toolchain) -> HashMap<String, String> {
+        let mut environment = HashMap::default();
+        if !toolchain.is_installed() {
+            return environment;
+        }
+        if let Some(bin) = Path::new(toolchain.path.as_ref()).parent() {
+            environment.insert("PATH".to_owned(), bin.to_string_lossy().into_owned());
+        }
+        let Some(prefix) = toolchain
+            .as_json
+            .get("prefix")
+            .and_then(|prefix| prefix.as_str())
+        else {
+            return environment;
+        };
+        let is_kind = |kind: PythonEnvironmentKind| {
+            serde_json::to_value(kind).ok().as_ref() == toolchain.as_json.get("kind")
+        };
+        if is_kind(PythonEnvironmentKind::Conda) {
+            environment.insert("CONDA_PREFIX".to_owned(), prefix.to_owned());
+            if let Some(name) = toolchain.as_json.get("name").and_then(|name| name.as_str()) {
+                environment.insert("CONDA_DEFAULT_ENV".to_owned(), name.to_owned());
+            }
+        } else if [
+            PythonEnvironmentKind::Poetry,
+            PythonEnvironmentKind::Pipenv,
+            PythonEnvironmentKind::VirtualEnvWrapper,
+            PythonEnvironmentKind::Venv,
+            PythonEnvironmentKind::VirtualEnv,
+        ]
+        .into_iter()
+        .any(is_kind)
+        {
+            environment.insert("VIRTUAL_ENV".to_owned(), prefix.to_owned());
+        }
+        environment
+    }
+    async fn details(
+        &self,
+        toolchain: Toolchain,
+        project_env: Option<HashMap<String, String>>,
+    ) -> ToolchainDetails {
+        let version = toolchain
+            .as_json
+            .get("version")
+            .and_then(|version| version.as_str())
+            .map(|version| SharedString::from(format!("Python {version}")));
+        let environment = self.activation_environment(&toolchain);
+        if !toolchain.is_installed() {
+            return ToolchainDetails {
+                version,
+                environment,
+                packages: None,
+            };
+        }
+
+        let mut command = util::command::new_smol_command(toolchain.path.as_ref());
+        if let Some(env) = &project_env {
+            command.envs(env);
+        }
+        let packages = command
+            .args([
+                "-m",
+                "pip",
+                "list",
+                "--format=freeze",
+                "--disable-pip-version-check",
+            ])
+            .output()
+            .await
+            .log_err()
+            .filter(|output| output.status.success())
+            .map(|output| parse_pip_freeze(&String::from_utf8_lossy(&output.stdout)));
+        ToolchainDetails {
+            version,
+            environment,
+            packages,
+        }
+    }
+}
+
+/// Parses `pip list --format=freeze` output (`name==version` per line) into `name version` labels.
+fn parse_pip_freeze(output: &str) -> Vec<SharedString> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, version) = line.trim().split_once("==")?;
+            Some(format!("{name} {version}").into())
+        })
+        .collect()
+}
+
+/// Returns the Python version pinned by a pyenv `.python-version` file in the worktree root.
+async fn pinned_python_version(worktree_root: &Path) -> Option<(String, PathBuf)> {
+    let path = worktree_root.join(".python-version");
+    let contents = smol::fs::read_to_string(&path).await.ok()?;
+    let version = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    // `system` defers to whatever Python is on the PATH, so there's nothing to install.
+    (version != "system").then(|| (version.to_owned(), path))
+}
+
+/// Whether a toolchain's Python version satisfies a pin such as `3.12` or `3.12.1`.
+fn python_version_matches(toolchain: &Toolchain, pinned_version: &str) -> bool {
+    toolchain
+        .as_json
+        .get("version")
+        .and_then(|version| version.as_str())
+        .map_or(false, |version| {
+            version == pinned_version
+                || version
+                    .strip_prefix(pinned_version)
+                    .map_or(false, |suffix| suffix.starts_with('.'))
+        })
 }
 
 pub struct EnvironmentApi<'a> {
@@ -1109,4 +1356,13 @@ mod tests {
             buffer
         });
     }
+
+    #[test]
+    fn test_parse_pip_freeze() {
+        let output = "numpy==2.1.0\n-e git+https://example.com/pkg.git#egg=pkg\npandas==2.2.3\n";
+        assert_eq!(
+            super::parse_pip_freeze(output),
+            vec!["numpy 2.1.0".to_string(), "pandas 2.2.3".to_string()]
+        );
+    }
 }
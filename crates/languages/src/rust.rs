@@ -2,12 +2,13 @@ use anyhow::{anyhow, Context, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_trait::async_trait;
 use collections::HashMap;
-use futures::{io::BufReader, StreamExt};
-use gpui::{AppContext, AsyncAppContext, Task};
+use futures::{future::Shared, io::BufReader, FutureExt, StreamExt};
+use gpui::{AppContext, AsyncAppContext, SharedString, Task};
 use http_client::github::AssetKind;
 use http_client::github::{latest_github_release, GitHubLspBinaryVersion};
 pub use language::*;
 use lsp::{LanguageServerBinary, LanguageServerName};
+use parking_lot::Mutex;
 use regex::Regex;
 use smol::fs::{self};
 use std::fmt::Display;
@@ -16,11 +17,13 @@ use std::{
     borrow::Cow,
     path::{Path, PathBuf},
     sync::{Arc, LazyLock},
+    time::SystemTime,
 };
 use task::{TaskTemplate, TaskTemplates, TaskVariables, VariableName};
 use util::{fs::remove_matching, maybe, ResultExt};
 
 use crate::language_settings::language_settings;
+use crate::version_manager_output;
 
 pub struct RustLspAdapter;
 
@@ -77,11 +80,14 @@ impl LspAdapter for RustLspAdapter {
     async fn check_if_user_installed(
         &self,
         delegate: &dyn LspAdapterDelegate,
-        _: Arc<dyn LanguageToolchainStore>,
-        _: &AsyncAppContext,
+        toolchains: Arc<dyn LanguageToolchainStore>,
+        cx: &AsyncAppContext,
     ) -> Option<LanguageServerBinary> {
         let path = delegate.which("rust-analyzer".as_ref()).await?;
-        let env = delegate.shell_env().await;
+        let mut env = delegate.shell_env().await;
+        if let Some(channel) = active_rustup_channel(delegate, toolchains, cx).await {
+            env.insert("RUSTUP_TOOLCHAIN".to_string(), channel);
+        }
 
         // It is surprisingly common for ~/.cargo/bin/rust-analyzer to be a symlink to
         // /usr/bin/rust-analyzer that fails when you run it; so we need to test it.
@@ -436,7 +442,20 @@ impl LspAdapter for RustLspAdapter {
     }
 }
 
-pub(crate) struct RustContextProvider;
+#[derive(Default)]
+pub(crate) struct RustContextProvider {
+    /// Tasks for the targets of the Cargo workspaces in the worktree roots, by worktree root.
+    cargo_targets: Arc<Mutex<HashMap<PathBuf, CargoTargets>>>,
+}
+
+#[derive(Default)]
+struct CargoTargets {
+    /// The manifests the tasks were read from, with their modification time at the time.
+    manifests: Vec<(PathBuf, Option<SystemTime>)>,
+    tasks: Vec<TaskTemplate>,
+    /// The last time the tasks were read again, which is still pending if it hasn't completed.
+    refresh: Option<Shared<Task<()>>>,
+}
 
 const RUST_PACKAGE_TASK_VARIABLE: VariableName =
     VariableName::Custom(Cow::Borrowed("RUST_PACKAGE"));
@@ -461,44 +480,26 @@ impl ContextProvider for RustContextProvider {
         _: Arc<dyn LanguageToolchainStore>,
         cx: &mut gpui::AppContext,
     ) -> Task<Result<TaskVariables>> {
-        let local_abs_path = location
-            .buffer
-            .read(cx)
-            .file()
+        let file = location.buffer.read(cx).file().cloned();
+        let local_abs_path = file
+            .as_ref()
             .and_then(|file| Some(file.as_local()?.abs_path(cx)));
+        let variables = rust_task_variables(
+            task_variables,
+            local_abs_path.as_deref(),
+            project_env.as_ref(),
+        );
 
-        let local_abs_path = local_abs_path.as_deref();
-
-        let is_main_function = task_variables
-            .get(&RUST_MAIN_FUNCTION_TASK_VARIABLE)
-            .is_some();
-
-        if is_main_function {
-            if let Some(target) = local_abs_path.and_then(|path| {
-                package_name_and_bin_name_from_abs_path(path, project_env.as_ref())
-            }) {
-                return Task::ready(Ok(TaskVariables::from_iter([
-                    (RUST_PACKAGE_TASK_VARIABLE.clone(), target.package_name),
-                    (RUST_BIN_NAME_TASK_VARIABLE.clone(), target.target_name),
-                    (
-                        RUST_BIN_KIND_TASK_VARIABLE.clone(),
-                        target.target_kind.to_string(),
-                    ),
-                ])));
-            }
-        }
-
-        if let Some(package_name) = local_abs_path
-            .and_then(|local_abs_path| local_abs_path.parent())
-            .and_then(|path| human_readable_package_name(path, project_env.as_ref()))
-        {
-            return Task::ready(Ok(TaskVariables::from_iter([(
-                RUST_PACKAGE_TASK_VARIABLE.clone(),
-                package_name,
-            )])));
-        }
-
-        Task::ready(Ok(TaskVariables::default()))
+        // The tasks are listed once the context is built, so make sure that they include the
+        // current Cargo targets.
+        let Some(worktree_root) = file.as_deref().and_then(|file| worktree_root(file, cx)) else {
+            return Task::ready(Ok(variables));
+        };
+        let refresh = self.refresh_cargo_targets(worktree_root, cx);
+        cx.background_executor().spawn(async move {
+            refresh.await;
+            Ok(variables)
+        })
     }
 
     fn associated_tasks(
@@ -517,7 +518,7 @@ impl ContextProvider for RustContextProvider {
         } else {
             vec!["run".into()]
         };
-        Some(TaskTemplates(vec![
+        let mut tasks = vec![
             TaskTemplate {
                 label: format!(
                     "cargo check -p {}",
@@ -622,8 +623,201 @@ impl ContextProvider for RustContextProvider {
                 cwd: Some("$ZED_DIRNAME".to_owned()),
                 ..TaskTemplate::default()
             },
-        ]))
+        ];
+        if let Some(worktree_root) = file.as_deref().and_then(|file| worktree_root(file, cx)) {
+            tasks.extend(self.cargo_target_tasks(worktree_root, cx));
+        }
+        Some(TaskTemplates(tasks))
+    }
+}
+
+impl RustContextProvider {
+    /// Returns the tasks for the Cargo targets known for the worktree root,
+    /// and reads them again in the background if any of their manifests changed since.
+    fn cargo_target_tasks(&self, worktree_root: PathBuf, cx: &AppContext) -> Vec<TaskTemplate> {
+        self.refresh_cargo_targets(worktree_root.clone(), cx);
+        self.cargo_targets
+            .lock()
+            .get(&worktree_root)
+            .map(|worktree_targets| worktree_targets.tasks.clone())
+            .unwrap_or_default()
+    }
+
+    /// Reads the tasks for the Cargo targets of the worktree root again if any of their
+    /// manifests changed, unless that's already pending.
+    fn refresh_cargo_targets(&self, worktree_root: PathBuf, cx: &AppContext) -> Shared<Task<()>> {
+        let mut cargo_targets = self.cargo_targets.lock();
+        let worktree_targets = cargo_targets.entry(worktree_root.clone()).or_default();
+        if let Some(refresh) = &worktree_targets.refresh {
+            if refresh.peek().is_none() {
+                return refresh.clone();
+            }
+        }
+
+        let known_manifests = worktree_targets.manifests.clone();
+        let cargo_targets = self.cargo_targets.clone();
+        let refresh = cx
+            .background_executor()
+            .spawn(async move {
+                let refreshed = read_cargo_targets(&worktree_root, known_manifests).await;
+                if let Some((manifests, tasks)) = refreshed {
+                    let mut cargo_targets = cargo_targets.lock();
+                    let worktree_targets = cargo_targets.entry(worktree_root).or_default();
+                    worktree_targets.manifests = manifests;
+                    worktree_targets.tasks = tasks;
+                }
+            })
+            .shared();
+        worktree_targets.refresh = Some(refresh.clone());
+        refresh
+    }
+}
+
+fn rust_task_variables(
+    task_variables: &TaskVariables,
+    local_abs_path: Option<&Path>,
+    project_env: Option<&HashMap<String, String>>,
+) -> TaskVariables {
+    let is_main_function = task_variables
+        .get(&RUST_MAIN_FUNCTION_TASK_VARIABLE)
+        .is_some();
+
+    if is_main_function {
+        if let Some(target) = local_abs_path
+            .and_then(|path| package_name_and_bin_name_from_abs_path(path, project_env))
+        {
+            return TaskVariables::from_iter([
+                (RUST_PACKAGE_TASK_VARIABLE.clone(), target.package_name),
+                (RUST_BIN_NAME_TASK_VARIABLE.clone(), target.target_name),
+                (
+                    RUST_BIN_KIND_TASK_VARIABLE.clone(),
+                    target.target_kind.to_string(),
+                ),
+            ]);
+        }
+    }
+
+    if let Some(package_name) = local_abs_path
+        .and_then(|local_abs_path| local_abs_path.parent())
+        .and_then(|path| human_readable_package_name(path, project_env))
+    {
+        return TaskVariables::from_iter([(RUST_PACKAGE_TASK_VARIABLE.clone(), package_name)]);
+    }
+
+    TaskVariables::default()
+}
+
+/// The absolute path of the root of the worktree that contains the file.
+fn worktree_root(file: &dyn language::File, cx: &AppContext) -> Option<PathBuf> {
+    let abs_path = file.as_local()?.abs_path(cx);
+    Some(
+        abs_path
+            .ancestors()
+            .nth(file.path().components().count())?
+            .to_path_buf(),
+    )
+}
+
+async fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Runs `cargo metadata` in the worktree root, unless none of the manifests known changed.
+/// Returns the manifests of the workspace found, with the tasks for its targets.
+async fn read_cargo_targets(
+    worktree_root: &Path,
+    known_manifests: Vec<(PathBuf, Option<SystemTime>)>,
+) -> Option<(Vec<(PathBuf, Option<SystemTime>)>, Vec<TaskTemplate>)> {
+    let mut manifests_changed = known_manifests.is_empty();
+    for (manifest, modified) in &known_manifests {
+        if modification_time(manifest).await != *modified {
+            manifests_changed = true;
+            break;
+        }
+    }
+    if !manifests_changed {
+        return None;
+    }
+
+    let root_manifest = worktree_root.join("Cargo.toml");
+    let mut manifests = vec![(
+        root_manifest.clone(),
+        modification_time(&root_manifest).await,
+    )];
+    if manifests[0].1.is_none() {
+        return Some((manifests, Vec::new()));
+    }
+
+    let output = util::command::new_smol_command("cargo")
+        .current_dir(worktree_root)
+        .arg("metadata")
+        .arg("--no-deps")
+        .arg("--format-version")
+        .arg("1")
+        .output()
+        .await
+        .log_err()?;
+    let metadata = match serde_json::from_slice::<CargoMetadata>(&output.stdout) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            log::debug!("Failed to read Cargo metadata in {worktree_root:?}: {e}");
+            return Some((manifests, Vec::new()));
+        }
+    };
+    for package in &metadata.packages {
+        if let Some(manifest) = &package.manifest_path {
+            if manifest != &root_manifest {
+                manifests.push((manifest.clone(), modification_time(manifest).await));
+            }
+        }
     }
+    Some((manifests, cargo_target_tasks(&metadata)))
+}
+
+/// Tasks to run, test or bench each target of the packages in the Cargo metadata given.
+fn cargo_target_tasks(metadata: &CargoMetadata) -> Vec<TaskTemplate> {
+    let cargo_task = |label: String, args: Vec<String>| TaskTemplate {
+        label,
+        command: "cargo".into(),
+        args,
+        cwd: Some(VariableName::WorktreeRoot.template_value()),
+        ..TaskTemplate::default()
+    };
+
+    let mut tasks = Vec::new();
+    for package in &metadata.packages {
+        let Some(package_name) = package_name_from_pkgid(&package.id) else {
+            continue;
+        };
+        tasks.push(cargo_task(
+            format!("test crate {package_name}"),
+            vec!["test".into(), "-p".into(), package_name.into()],
+        ));
+        for target in &package.targets {
+            let (label, command, flag) = if target.kind.iter().any(|kind| kind == "bin") {
+                (format!("run bin {}", target.name), "run", "--bin")
+            } else if target.kind.iter().any(|kind| kind == "example") {
+                (format!("run example {}", target.name), "run", "--example")
+            } else if target.kind.iter().any(|kind| kind == "test") {
+                (format!("test {}", target.name), "test", "--test")
+            } else if target.kind.iter().any(|kind| kind == "bench") {
+                (format!("bench {}", target.name), "bench", "--bench")
+            } else {
+                continue;
+            };
+            tasks.push(cargo_task(
+                label,
+                vec![
+                    command.into(),
+                    "-p".into(),
+                    package_name.into(),
+                    flag.into(),
+                    target.name.clone(),
+                ],
+            ));
+        }
+    }
+    tasks
 }
 
 /// Part of the data structure of Cargo metadata
@@ -636,6 +830,8 @@ struct CargoMetadata {
 struct CargoPackage {
     id: String,
     targets: Vec<CargoTarget>,
+    #[serde(default)]
+    manifest_path: Option<PathBuf>,
 }
 
 #[derive(serde::Deserialize)]
@@ -786,6 +982,320 @@ fn package_name_from_pkgid(pkgid: &str) -> Option<&str> {
     Some(package_name)
 }
 
+pub(crate) struct RustToolchainProvider {
+    term: SharedString,
+}
+
+impl Default for RustToolchainProvider {
+    fn default() -> Self {
+        Self {
+            term: SharedString::new_static("Rust Toolchain"),
+        }
+    }
+}
+
+/// Files that rustup reads a toolchain override from, in order of precedence.
+const RUST_TOOLCHAIN_FILES: [&str; 2] = ["rust-toolchain.toml", "rust-toolchain"];
+
+#[derive(Debug, PartialEq)]
+struct RustupToolchain {
+    name: String,
+    path: Option<PathBuf>,
+    is_default: bool,
+}
+
+#[async_trait]
+impl ToolchainLister for RustToolchainProvider {
+    async fn list(
+        &self,
+        worktree_root: PathBuf,
+        project_env: Option<HashMap<String, String>>,
+    ) -> ToolchainList {
+        let pinned = pinned_rust_toolchain(&worktree_root).await;
+
+        let mut command = util::command::new_smol_command("rustup");
+        if let Some(env) = &project_env {
+            command.envs(env);
+        }
+        command.args(["toolchain", "list", "--verbose"]);
+        let installed = version_manager_output(command)
+            .await
+            .filter(|output| output.status.success())
+            .map(|output| parse_rustup_toolchain_list(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default();
+
+        let mut toolchains = Vec::new();
+        let mut default = None;
+        if let Some((channel, toolchain_file)) = &pinned {
+            let pinned_installed = installed
+                .iter()
+                .find(|toolchain| rustup_toolchain_matches_channel(&toolchain.name, channel));
+            let toolchain = match pinned_installed {
+                Some(toolchain) => rust_toolchain(
+                    format!("{} (pinned)", toolchain.name),
+                    toolchain
+                        .path
+                        .as_deref()
+                        .unwrap_or(toolchain_file.as_path()),
+                    &toolchain.name,
+                    Some(toolchain_file.as_path()),
+                    true,
+                ),
+                None => rust_toolchain(
+                    format!("{channel} (pinned)"),
+                    toolchain_file,
+                    channel,
+                    Some(toolchain_file.as_path()),
+                    false,
+                ),
+            };
+            default = Some(0);
+            toolchains.extend(toolchain);
+        }
+        for toolchain in &installed {
+            let is_pinned = pinned.as_ref().map_or(false, |(channel, _)| {
+                rustup_toolchain_matches_channel(&toolchain.name, channel)
+            });
+            if is_pinned {
+                continue;
+            }
+            if toolchain.is_default && default.is_none() {
+                default = Some(toolchains.len());
+            }
+            let name = if toolchain.is_default {
+                format!("{} (default)", toolchain.name)
+            } else {
+                toolchain.name.clone()
+            };
+            let path = toolchain
+                .path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(&toolchain.name));
+            toolchains.extend(rust_toolchain(name, &path, &toolchain.name, None, true));
+        }
+
+        ToolchainList {
+            toolchains,
+            default,
+            groups: Default::default(),
+        }
+    }
+
+    fn term(&self) -> SharedString {
+        self.term.clone()
+    }
+
+    async fn install(
+        &self,
+        toolchain: Toolchain,
+        worktree_root: PathBuf,
+        project_env: Option<HashMap<String, String>>,
+    ) -> Result<Toolchain> {
+        let channel = toolchain
+            .as_json
+            .get("channel")
+            .and_then(|channel| channel.as_str())
+            .with_context(|| format!("toolchain {} has no rustup channel", toolchain.name))?;
+        let mut command = util::command::new_smol_command("rustup");
+        if let Some(env) = &project_env {
+            command.envs(env);
+        }
+        let output = command
+            .current_dir(&worktree_root)
+            .args(["toolchain", "install", channel])
+            .output()
+            .await
+            .context("failed to run rustup")?;
+        anyhow::ensure!(
+            output.status.success(),
+            "rustup failed to install {channel}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Re-list so that the installed toolchain points at its rustup directory.
+        self.list(worktree_root, project_env)
+            .await
+            .toolchains
+            .into_iter()
+            .find(|installed| {
+                installed.is_installed()
+                    && installed
+                        .as_json
+                        .get("channel")
+                        .and_then(|installed| installed.as_str())
+                        .map_or(false, |installed| {
+                            rustup_toolchain_matches_channel(installed, channel)
+                        })
+            })
+            .with_context(|| format!("rustup did not report {channel} as installed"))
+    }
+
+    fn activation_environment(&self, toolchain: &Toolchain) -> HashMap<String, String> {
+        let mut environment = HashMap::default();
+        if !toolchain.is_installed() {
+            return environment;
+        }
+        if let Some(channel) = toolchain
+            .as_json
+            .get("channel")
+            .and_then(|channel| channel.as_str())
+        {
+            environment.insert("RUSTUP_TOOLCHAIN".to_owned(), channel.to_owned());
+        }
+        let bin = Path::new(toolchain.path.as_ref()).join("bin");
+        if bin.is_absolute() {
+            environment.insert("PATH".to_owned(), bin.to_string_lossy().into_owned());
+        }
+        environment
+    }
+
+    async fn details(
+        &self,
+        toolchain: Toolchain,
+        project_env: Option<HashMap<String, String>>,
+    ) -> ToolchainDetails {
+        let environment = self.activation_environment(&toolchain);
+        let version = match environment.get("RUSTUP_TOOLCHAIN") {
+            Some(channel) => {
+                let mut command = util::command::new_smol_command("rustc");
+                if let Some(env) = &project_env {
+                    command.envs(env);
+                }
+                command
+                    .env("RUSTUP_TOOLCHAIN", channel)
+                    .arg("--version")
+                    .output()
+                    .await
+                    .log_err()
+                    .filter(|output| output.status.success())
+                    .map(|output| {
+                        String::from_utf8_lossy(&output.stdout)
+                            .trim()
+                            .to_owned()
+                            .into()
+                    })
+            }
+            None => None,
+        };
+        ToolchainDetails {
+            version,
+            environment,
+            packages: None,
+        }
+    }
+}
+
+fn rust_toolchain(
+    name: String,
+    path: &Path,
+    channel: &str,
+    toolchain_file: Option<&Path>,
+    installed: bool,
+) -> Option<Toolchain> {
+    Some(Toolchain {
+        name: name.into(),
+        path: path.to_str()?.to_owned().into(),
+        language_name: LanguageName::new("Rust"),
+        as_json: serde_json::json!({
+            "channel": channel,
+            "toolchain_file": toolchain_file,
+            "installed": installed,
+        }),
+    })
+}
+
+/// Returns the rustup channel of the toolchain selected for the delegate's worktree, so that
+/// rust-analyzer (and the cargo it invokes) use the same toolchain.
+async fn active_rustup_channel(
+    delegate: &dyn LspAdapterDelegate,
+    toolchains: Arc<dyn LanguageToolchainStore>,
+    cx: &AsyncAppContext,
+) -> Option<String> {
+    let toolchain = toolchains
+        .active_toolchain(
+            delegate.worktree_id(),
+            LanguageName::new("Rust"),
+            &mut cx.clone(),
+        )
+        .await?;
+    if !toolchain.is_installed() {
+        return None;
+    }
+    Some(toolchain.as_json.get("channel")?.as_str()?.to_owned())
+}
+
+/// Returns the channel pinned by a rustup toolchain file in the worktree root, if any.
+async fn pinned_rust_toolchain(worktree_root: &Path) -> Option<(String, PathBuf)> {
+    for file_name in RUST_TOOLCHAIN_FILES {
+        let path = worktree_root.join(file_name);
+        if let Ok(contents) = fs::read_to_string(&path).await {
+            return Some((parse_rust_toolchain_file(&contents)?, path));
+        }
+    }
+    None
+}
+
+fn parse_rust_toolchain_file(contents: &str) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct ToolchainFile {
+        toolchain: ToolchainSection,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ToolchainSection {
+        channel: Option<String>,
+    }
+
+    let contents = contents.trim();
+    // The legacy `rust-toolchain` file may contain just the channel name.
+    if !contents.contains('\n') && !contents.contains('=') && !contents.is_empty() {
+        return Some(contents.to_owned());
+    }
+    toml::from_str::<ToolchainFile>(contents)
+        .log_err()?
+        .toolchain
+        .channel
+}
+
+/// Parses the output of `rustup toolchain list --verbose`, where each line looks like
+/// `stable-x86_64-unknown-linux-gnu (default) /home/user/.rustup/toolchains/stable-x86_64-unknown-linux-gnu`.
+fn parse_rustup_toolchain_list(output: &str) -> Vec<RustupToolchain> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?.to_owned();
+            if name == "no" {
+                // "no installed toolchains"
+                return None;
+            }
+            let mut is_default = false;
+            let mut path = None;
+            for part in parts {
+                if part.starts_with('(') || part.ends_with(')') {
+                    is_default |= part.trim_matches(|c| matches!(c, '(' | ')' | ',')) == "default";
+                } else {
+                    path = Some(PathBuf::from(part));
+                }
+            }
+            Some(RustupToolchain {
+                name,
+                path,
+                is_default,
+            })
+        })
+        .collect()
+}
+
+/// Whether a rustup toolchain name (e.g. `1.81-x86_64-unknown-linux-gnu`) satisfies a channel
+/// as written in a toolchain file (e.g. `1.81`).
+fn rustup_toolchain_matches_channel(toolchain_name: &str, channel: &str) -> bool {
+    toolchain_name == channel
+        || toolchain_name
+            .strip_prefix(channel)
+            .map_or(false, |suffix| suffix.starts_with('-'))
+}
+
 async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServerBinary> {
     maybe!(async {
         let mut last = None;
@@ -1112,6 +1622,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_rust_toolchain_file() {
+        assert_eq!(
+            parse_rust_toolchain_file(
+                "[toolchain]\nchannel = \"1.81\"\ncomponents = [\"clippy\"]\n"
+            ),
+            Some("1.81".to_string())
+        );
+        assert_eq!(
+            parse_rust_toolchain_file("nightly-2024-09-01\n"),
+            Some("nightly-2024-09-01".to_string())
+        );
+        assert_eq!(
+            parse_rust_toolchain_file("[toolchain]\npath = \"/opt/rust\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_rustup_toolchain_list() {
+        let output = "stable-x86_64-unknown-linux-gnu (default) /home/user/.rustup/toolchains/stable-x86_64-unknown-linux-gnu\n\
+            1.81-x86_64-unknown-linux-gnu (active) /home/user/.rustup/toolchains/1.81-x86_64-unknown-linux-gnu\n";
+        assert_eq!(
+            parse_rustup_toolchain_list(output),
+            vec![
+                RustupToolchain {
+                    name: "stable-x86_64-unknown-linux-gnu".into(),
+                    path: Some(
+                        "/home/user/.rustup/toolchains/stable-x86_64-unknown-linux-gnu".into()
+                    ),
+                    is_default: true,
+                },
+                RustupToolchain {
+                    name: "1.81-x86_64-unknown-linux-gnu".into(),
+                    path: Some(
+                        "/home/user/.rustup/toolchains/1.81-x86_64-unknown-linux-gnu".into()
+                    ),
+                    is_default: false,
+                },
+            ]
+        );
+        assert!(parse_rustup_toolchain_list("no installed toolchains\n").is_empty());
+
+        assert!(rustup_toolchain_matches_channel(
+            "1.81-x86_64-unknown-linux-gnu",
+            "1.81"
+        ));
+        assert!(!rustup_toolchain_matches_channel(
+            "1.81.0-x86_64-unknown-linux-gnu",
+            "1.81"
+        ));
+        assert!(rustup_toolchain_matches_channel("stable", "stable"));
+    }
+
     #[test]
     fn test_retrieve_package_id_and_bin_name_from_metadata() {
         for (input, absolute_path, expected) in [
@@ -1158,4 +1722,33 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_cargo_target_tasks() {
+        let metadata: CargoMetadata = serde_json::from_str(
+            r#"{"packages":[{"id":"path+file:///path/to/zed/crates/zed#0.131.0","targets":[{"name":"zed","kind":["lib"],"src_path":"/path/to/zed/crates/zed/src/zed.rs"},{"name":"zed","kind":["bin"],"src_path":"/path/to/zed/crates/zed/src/main.rs"},{"name":"hello","kind":["example"],"src_path":"/path/to/zed/crates/zed/examples/hello.rs"},{"name":"integration","kind":["test"],"src_path":"/path/to/zed/crates/zed/tests/integration.rs"},{"name":"startup","kind":["bench"],"src_path":"/path/to/zed/crates/zed/benches/startup.rs"},{"name":"build-script-build","kind":["custom-build"],"src_path":"/path/to/zed/crates/zed/build.rs"}]}]}"#,
+        )
+        .unwrap();
+
+        let tasks = cargo_target_tasks(&metadata);
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| (task.label.as_str(), task.args.join(" ")))
+                .collect::<Vec<_>>(),
+            vec![
+                ("test crate zed", "test -p zed".to_string()),
+                ("run bin zed", "run -p zed --bin zed".to_string()),
+                (
+                    "run example hello",
+                    "run -p zed --example hello".to_string()
+                ),
+                (
+                    "test integration",
+                    "test -p zed --test integration".to_string()
+                ),
+                ("bench startup", "bench -p zed --bench startup".to_string()),
+            ]
+        );
+    }
 }
@@ -3,9 +3,12 @@ use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use async_trait::async_trait;
 use collections::HashMap;
-use gpui::AsyncAppContext;
+use gpui::{AsyncAppContext, SharedString};
 use http_client::github::{build_asset_url, AssetKind, GitHubLspBinaryVersion};
-use language::{LanguageToolchainStore, LspAdapter, LspAdapterDelegate};
+use language::{
+    LanguageName, LanguageToolchainStore, LspAdapter, LspAdapterDelegate, Toolchain,
+    ToolchainDetails, ToolchainList, ToolchainLister,
+};
 use lsp::{CodeActionKind, LanguageServerBinary, LanguageServerName};
 use node_runtime::NodeRuntime;
 use project::lsp_store::language_server_settings;
@@ -21,6 +24,8 @@ use std::{
 use task::{TaskTemplate, TaskTemplates, VariableName};
 use util::{fs::remove_matching, maybe, ResultExt};
 
+use crate::version_manager_output;
+
 pub(super) fn typescript_task_context() -> ContextProviderWithTasks {
     ContextProviderWithTasks::new(TaskTemplates(vec![
         TaskTemplate {
@@ -548,6 +553,277 @@ impl LspAdapter for EsLintLspAdapter {
     }
 }
 
+pub(crate) struct NodeToolchainProvider {
+    language_name: LanguageName,
+    term: SharedString,
+}
+
+impl NodeToolchainProvider {
+    pub fn new(language_name: LanguageName) -> Self {
+        Self {
+            language_name,
+            term: SharedString::new_static("Node.js Version"),
+        }
+    }
+
+    fn fnm(
+        &self,
+        worktree_root: &Path,
+        project_env: &Option<HashMap<String, String>>,
+        args: &[&str],
+    ) -> smol::process::Command {
+        let mut command = util::command::new_smol_command("fnm");
+        if let Some(env) = project_env {
+            command.envs(env);
+        }
+        command.current_dir(worktree_root).args(args);
+        command
+    }
+
+    fn toolchain(
+        &self,
+        name: String,
+        path: &Path,
+        version: &str,
+        version_file: Option<&Path>,
+        installed: bool,
+    ) -> Option<Toolchain> {
+        Some(Toolchain {
+            name: name.into(),
+            path: path.to_str()?.to_owned().into(),
+            language_name: self.language_name.clone(),
+            as_json: json!({
+                "version": version,
+                "version_file": version_file,
+                "installed": installed,
+            }),
+        })
+    }
+}
+
+/// Files that fnm (and nvm) read a Node.js version pin from, in order of precedence.
+const NODE_VERSION_FILES: [&str; 2] = [".node-version", ".nvmrc"];
+
+#[derive(Debug, PartialEq)]
+struct FnmNodeVersion {
+    version: String,
+    is_default: bool,
+}
+
+#[async_trait]
+impl ToolchainLister for NodeToolchainProvider {
+    async fn list(
+        &self,
+        worktree_root: PathBuf,
+        project_env: Option<HashMap<String, String>>,
+    ) -> ToolchainList {
+        let pinned = pinned_node_version(&worktree_root).await;
+        let installed = version_manager_output(self.fnm(&worktree_root, &project_env, &["list"]))
+            .await
+            .filter(|output| output.status.success())
+            .map(|output| parse_fnm_list(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default();
+        let fnm_dir =
+            version_manager_output(self.fnm(&worktree_root, &project_env, &["env", "--json"]))
+                .await
+                .filter(|output| output.status.success())
+                .and_then(|output| {
+                    serde_json::from_slice::<HashMap<String, String>>(&output.stdout).ok()
+                })
+                .and_then(|env| env.get("FNM_DIR").map(PathBuf::from));
+        let node_path = |version: &str| match &fnm_dir {
+            Some(fnm_dir) => fnm_node_binary(fnm_dir, version),
+            None => PathBuf::from(version),
+        };
+
+        let mut toolchains = Vec::new();
+        let mut default = None;
+        let mut pinned_installed = None;
+        if let Some((pinned_version, version_file)) = &pinned {
+            pinned_installed = installed
+                .iter()
+                .filter(|installed| node_version_matches(&installed.version, pinned_version))
+                .last();
+            let toolchain = match pinned_installed {
+                Some(installed) => self.toolchain(
+                    format!("Node.js {} (pinned)", installed.version),
+                    &node_path(&installed.version),
+                    &installed.version,
+                    Some(version_file),
+                    true,
+                ),
+                None => self.toolchain(
+                    format!("Node.js {pinned_version} (pinned)"),
+                    version_file,
+                    pinned_version,
+                    Some(version_file),
+                    false,
+                ),
+            };
+            default = Some(0);
+            toolchains.extend(toolchain);
+        }
+        for installed_version in &installed {
+            let is_pinned = pinned_installed == Some(installed_version);
+            if is_pinned {
+                continue;
+            }
+            if installed_version.is_default && default.is_none() {
+                default = Some(toolchains.len());
+            }
+            let name = if installed_version.is_default {
+                format!("Node.js {} (default)", installed_version.version)
+            } else {
+                format!("Node.js {}", installed_version.version)
+            };
+            toolchains.extend(self.toolchain(
+                name,
+                &node_path(&installed_version.version),
+                &installed_version.version,
+                None,
+                true,
+            ));
+        }
+
+        ToolchainList {
+            toolchains,
+            default,
+            groups: Default::default(),
+        }
+    }
+
+    fn term(&self) -> SharedString {
+        self.term.clone()
+    }
+
+    async fn install(
+        &self,
+        toolchain: Toolchain,
+        worktree_root: PathBuf,
+        project_env: Option<HashMap<String, String>>,
+    ) -> Result<Toolchain> {
+        let version = toolchain
+            .as_json
+            .get("version")
+            .and_then(|version| version.as_str())
+            .with_context(|| format!("toolchain {} has no Node.js version", toolchain.name))?;
+        let output = self
+            .fnm(&worktree_root, &project_env, &["install", version])
+            .output()
+            .await
+            .context("failed to run fnm")?;
+        anyhow::ensure!(
+            output.status.success(),
+            "fnm failed to install Node.js {version}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Re-list so that the installed version points at its fnm directory.
+        self.list(worktree_root, project_env)
+            .await
+            .toolchains
+            .into_iter()
+            .find(|installed| {
+                installed.is_installed()
+                    && installed
+                        .as_json
+                        .get("version")
+                        .and_then(|installed| installed.as_str())
+                        .map_or(false, |installed| node_version_matches(installed, version))
+            })
+            .with_context(|| format!("fnm did not report Node.js {version} as installed"))
+    }
+
+    fn activation_environment(&self, toolchain: &Toolchain) -> HashMap<String, String> {
+        let mut environment = HashMap::default();
+        if toolchain.is_installed() {
+            if let Some(bin) = Path::new(toolchain.path.as_ref()).parent() {
+                environment.insert("PATH".to_owned(), bin.to_string_lossy().into_owned());
+            }
+        }
+        environment
+    }
+
+    async fn details(
+        &self,
+        toolchain: Toolchain,
+        _: Option<HashMap<String, String>>,
+    ) -> ToolchainDetails {
+        ToolchainDetails {
+            version: toolchain
+                .as_json
+                .get("version")
+                .and_then(|version| version.as_str())
+                .map(|version| format!("Node.js {version}").into()),
+            environment: self.activation_environment(&toolchain),
+            packages: None,
+        }
+    }
+}
+
+/// Returns the Node.js version pinned by a version file in the worktree root, if any.
+///
+/// Aliases such as `lts/iron` are skipped, as there's no way to tell whether an installed
+/// version satisfies them without asking the network.
+async fn pinned_node_version(worktree_root: &Path) -> Option<(String, PathBuf)> {
+    for file_name in NODE_VERSION_FILES {
+        let path = worktree_root.join(file_name);
+        if let Ok(contents) = fs::read_to_string(&path).await {
+            let version = contents
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())?;
+            let version = version.strip_prefix('v').unwrap_or(version);
+            if !version.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            return Some((format!("v{version}"), path));
+        }
+    }
+    None
+}
+
+/// Parses the output of `fnm list`, where each line looks like `* v20.10.0 default`.
+fn parse_fnm_list(output: &str) -> Vec<FnmNodeVersion> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line
+                .trim_start_matches(|c: char| c == '*' || c.is_whitespace())
+                .split_whitespace();
+            let version = parts.next()?;
+            // The `system` entry is the Node.js found on the PATH, which fnm doesn't manage.
+            if !version.starts_with('v') {
+                return None;
+            }
+            Some(FnmNodeVersion {
+                version: version.to_owned(),
+                is_default: parts.any(|alias| alias.trim_end_matches(',') == "default"),
+            })
+        })
+        .collect()
+}
+
+fn fnm_node_binary(fnm_dir: &Path, version: &str) -> PathBuf {
+    let installation = fnm_dir
+        .join("node-versions")
+        .join(version)
+        .join("installation");
+    if cfg!(windows) {
+        installation.join("node.exe")
+    } else {
+        installation.join("bin").join("node")
+    }
+}
+
+/// Whether an installed version (e.g. `v20.10.0`) satisfies a pin (e.g. `v20` or `v20.10`).
+fn node_version_matches(version: &str, pinned_version: &str) -> bool {
+    version == pinned_version
+        || version
+            .strip_prefix(pinned_version)
+            .map_or(false, |suffix| suffix.starts_with('.'))
+}
+
 #[cfg(target_os = "windows")]
 async fn handle_symlink(src_dir: PathBuf, dest_dir: PathBuf) -> Result<()> {
     if fs::metadata(&src_dir).await.is_err() {
@@ -569,7 +845,9 @@ async fn handle_symlink(src_dir: PathBuf, dest_dir: PathBuf) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use collections::HashMap;
     use gpui::{Context, TestAppContext};
+    use language::{LanguageName, ToolchainLister as _};
     use unindent::Unindent;
 
     #[gpui::test]
@@ -612,4 +890,53 @@ mod tests {
             ]
         );
     }
+    #[test]
+    fn test_node_toolchains_without_fnm() {
+        let dir = util::test::temp_tree(serde_json::json!({ "bin": {}, "project": {} }));
+        let worktree_root = dir.path().join("project");
+        // fnm can't be found on this PATH.
+        let project_env = Some(HashMap::from_iter([(
+            "PATH".to_string(),
+            dir.path().join("bin").to_string_lossy().into_owned(),
+        )]));
+        let provider = super::NodeToolchainProvider::new(LanguageName::new("TypeScript"));
+
+        let toolchains = smol::block_on(provider.list(worktree_root.clone(), project_env.clone()));
+        assert!(toolchains.toolchains.is_empty());
+        assert_eq!(toolchains.default, None);
+
+        // A pinned version is still offered for installing.
+        std::fs::write(worktree_root.join(".nvmrc"), "20\n").unwrap();
+        let toolchains = smol::block_on(provider.list(worktree_root, project_env));
+        assert_eq!(toolchains.default, Some(0));
+        assert_eq!(
+            toolchains
+                .toolchains
+                .iter()
+                .map(|toolchain| (toolchain.name.as_ref(), toolchain.is_installed()))
+                .collect::<Vec<_>>(),
+            [("Node.js v20 (pinned)", false)]
+        );
+    }
+
+    #[test]
+    fn test_parse_fnm_list() {
+        let output = "* v18.17.0\n* v20.10.0 default, lts-latest\n* system\n";
+        assert_eq!(
+            super::parse_fnm_list(output),
+            vec![
+                super::FnmNodeVersion {
+                    version: "v18.17.0".into(),
+                    is_default: false,
+                },
+                super::FnmNodeVersion {
+                    version: "v20.10.0".into(),
+                    is_default: true,
+                },
+            ]
+        );
+        assert!(super::node_version_matches("v20.10.0", "v20"));
+        assert!(super::node_version_matches("v20.10.0", "v20.10.0"));
+        assert!(!super::node_version_matches("v20.10.0", "v2"));
+    }
 }
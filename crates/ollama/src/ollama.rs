@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Context, Result};
 use futures::{io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, StreamExt};
-use http_client::{http, AsyncBody, HttpClient, Method, Request as HttpRequest};
+use http_client::{
+    http, AsyncBody, HttpClient, Method, Request as HttpRequest, ResponseStatusError,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
@@ -290,11 +292,12 @@ pub async fn stream_chat_completion(
         let mut body = String::new();
         response.body_mut().read_to_string(&mut body).await?;
 
-        Err(anyhow!(
+        let message = format!(
             "Failed to connect to Ollama API: {} {}",
             response.status(),
             body,
-        ))
+        );
+        Err(ResponseStatusError::new(&response, message).into())
     }
 }
 
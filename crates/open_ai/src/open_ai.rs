@@ -6,7 +6,7 @@ use futures::{
     stream::{self, BoxStream},
     AsyncBufReadExt, AsyncReadExt, Stream, StreamExt,
 };
-use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest, ResponseStatusError};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
@@ -159,6 +159,8 @@ pub struct Request {
     pub messages: Vec<RequestMessage>,
     pub stream: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stop: Vec<String>,
@@ -169,6 +171,12 @@ pub struct Request {
     pub tools: Vec<ToolDefinition>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Whether to send the usage for the whole request in a final chunk, which has no choices.
+    pub include_usage: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompletionRequest {
     pub model: String,
@@ -279,6 +287,15 @@ pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PromptTokensDetails {
+    /// How many of the prompt tokens were read from the prompt cache.
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -373,18 +390,18 @@ pub async fn complete(
             message: String,
         }
 
-        match serde_json::from_str::<OpenAiResponse>(&body) {
-            Ok(response) if !response.error.message.is_empty() => Err(anyhow!(
+        let message = match serde_json::from_str::<OpenAiResponse>(&body) {
+            Ok(error_response) if !error_response.error.message.is_empty() => format!(
                 "Failed to connect to OpenAI API: {}",
-                response.error.message,
-            )),
-
-            _ => Err(anyhow!(
+                error_response.error.message,
+            ),
+            _ => format!(
                 "Failed to connect to OpenAI API: {} {}",
                 response.status(),
                 body,
-            )),
-        }
+            ),
+        };
+        Err(ResponseStatusError::new(&response, message).into())
     }
 }
 
@@ -423,18 +440,18 @@ pub async fn complete_text(
             message: String,
         }
 
-        match serde_json::from_str::<OpenAiResponse>(&body) {
-            Ok(response) if !response.error.message.is_empty() => Err(anyhow!(
+        let message = match serde_json::from_str::<OpenAiResponse>(&body) {
+            Ok(error_response) if !error_response.error.message.is_empty() => format!(
                 "Failed to connect to OpenAI API: {}",
-                response.error.message,
-            )),
-
-            _ => Err(anyhow!(
+                error_response.error.message,
+            ),
+            _ => format!(
                 "Failed to connect to OpenAI API: {} {}",
                 response.status(),
                 body,
-            )),
-        }
+            ),
+        };
+        Err(ResponseStatusError::new(&response, message).into())
     }
 }
 
@@ -528,18 +545,18 @@ pub async fn stream_completion(
             message: String,
         }
 
-        match serde_json::from_str::<OpenAiResponse>(&body) {
-            Ok(response) if !response.error.message.is_empty() => Err(anyhow!(
+        let message = match serde_json::from_str::<OpenAiResponse>(&body) {
+            Ok(error_response) if !error_response.error.message.is_empty() => format!(
                 "Failed to connect to OpenAI API: {}",
-                response.error.message,
-            )),
-
-            _ => Err(anyhow!(
+                error_response.error.message,
+            ),
+            _ => format!(
                 "Failed to connect to OpenAI API: {} {}",
                 response.status(),
                 body,
-            )),
-        }
+            ),
+        };
+        Err(ResponseStatusError::new(&response, message).into())
     }
 }
 
@@ -608,6 +625,66 @@ pub fn embed<'a>(
     }
 }
 
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Transcribes the speech in a WAV recording with the `audio/transcriptions` endpoint, which is
+/// also served by local Whisper servers.
+pub fn transcribe(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    wav: Vec<u8>,
+) -> impl 'static + Future<Output = Result<String>> {
+    let uri = format!("{api_url}/audio/transcriptions");
+
+    const BOUNDARY: &str = "zed-transcription-boundary";
+    let mut body = format!(
+        "--{BOUNDARY}\r\n\
+         Content-Disposition: form-data; name=\"model\"\r\n\r\n\
+         {model}\r\n\
+         --{BOUNDARY}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"prompt.wav\"\r\n\
+         Content-Type: audio/wav\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&wav);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+    let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", content_type);
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let request = request_builder
+        .body(AsyncBody::from(body))
+        .map(|request| client.send(request));
+
+    async move {
+        let mut response = request?.await?;
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        if response.status().is_success() {
+            let response: TranscriptionResponse = serde_json::from_str(&body)
+                .context("failed to parse OpenAI transcription response")?;
+            Ok(response.text.trim().to_string())
+        } else {
+            Err(anyhow!(
+                "error during transcription, status: {:?}, body: {:?}",
+                response.status(),
+                body
+            ))
+        }
+    }
+}
+
 pub async fn extract_tool_args_from_events(
     tool_name: String,
     mut events: Pin<Box<dyn Send + Stream<Item = Result<ResponseStreamEvent>>>>,
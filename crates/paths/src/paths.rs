@@ -305,6 +305,16 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".zed/tasks.json")
 }
 
+/// Returns the relative path to the folder of prompts within a project.
+pub fn local_prompts_folder_relative_path() -> &'static Path {
+    Path::new(".zed/prompts")
+}
+
+/// Returns the relative path to the commit message style guide within a project.
+pub fn local_commit_message_style_guide_relative_path() -> &'static Path {
+    Path::new(".zed/prompts/commit_message.md")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
@@ -438,6 +438,13 @@ impl<D: PickerDelegate> Picker<D> {
         }
     }
 
+    /// Masks the query text, e.g. while a password is typed into it.
+    pub fn set_masked(&mut self, masked: bool, cx: &mut WindowContext<'_>) {
+        if let Head::Editor(view) = &self.head {
+            view.update(cx, |editor, cx| editor.set_masked(masked, cx));
+        }
+    }
+
     pub fn refresh(&mut self, cx: &mut ViewContext<Self>) {
         let query = self.query(cx);
         self.update_matches(query, cx);
@@ -0,0 +1,466 @@
+//! Tasks that are not defined in any tasks file, but detected from the project's build files.
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use serde::Deserialize;
+use task::{TaskTemplate, VariableName};
+
+pub(crate) const PACKAGE_JSON: &str = "package.json";
+/// The makefile names `make` looks for, in the order it does.
+pub(crate) const MAKEFILES: [&str; 3] = ["GNUmakefile", "makefile", "Makefile"];
+/// The justfile names `just` looks for.
+pub(crate) const JUSTFILES: [&str; 3] = ["justfile", "Justfile", ".justfile"];
+
+/// The package manager used to run the `scripts` of a worktree's `package.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    pub const ALL: [Self; 4] = [Self::Npm, Self::Pnpm, Self::Yarn, Self::Bun];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Bun => "bun",
+        }
+    }
+
+    pub fn lockfile(&self) -> &'static str {
+        match self {
+            Self::Npm => "package-lock.json",
+            Self::Pnpm => "pnpm-lock.yaml",
+            Self::Yarn => "yarn.lock",
+            Self::Bun => "bun.lockb",
+        }
+    }
+
+    /// Picks the package manager whose lockfile is next to `package.json`, npm if there is none.
+    pub(crate) fn detect(has_file: impl Fn(&Path) -> bool) -> Self {
+        [Self::Pnpm, Self::Yarn, Self::Bun, Self::Npm]
+            .into_iter()
+            .find(|package_manager| has_file(Path::new(package_manager.lockfile())))
+            .unwrap_or(Self::Npm)
+    }
+
+    /// Whether a change of the worktree's file at `path` may change its detected scripts.
+    pub(crate) fn affects_scripts(path: &Path) -> bool {
+        path == Path::new(PACKAGE_JSON)
+            || Self::ALL
+                .iter()
+                .any(|package_manager| path == Path::new(package_manager.lockfile()))
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    scripts: HashMap<String, String>,
+}
+
+/// Turns each of the `scripts` of a `package.json` into a task run with the package manager given,
+/// in the worktree root.
+pub(crate) fn package_json_scripts(
+    package_json: &str,
+    package_manager: PackageManager,
+) -> Result<Vec<TaskTemplate>> {
+    let package_json = serde_json::from_str::<PackageJson>(package_json)
+        .context("parsing package.json content")?;
+    let mut scripts = package_json.scripts.into_keys().collect::<Vec<_>>();
+    scripts.sort();
+    Ok(scripts
+        .into_iter()
+        .map(|script| TaskTemplate {
+            label: format!("{} run {script}", package_manager.name()),
+            command: package_manager.name().to_string(),
+            args: vec!["run".to_string(), script],
+            cwd: Some(VariableName::WorktreeRoot.template_value()),
+            ..TaskTemplate::default()
+        })
+        .collect())
+}
+
+/// Whether the worktree's file at `path` is a makefile `make` would pick up in its root.
+pub(crate) fn is_makefile(path: &Path) -> bool {
+    MAKEFILES.iter().any(|makefile| path == Path::new(makefile))
+}
+
+/// Turns each of the explicit targets of a makefile into a task running `make` with it, in the worktree root.
+/// Special targets (e.g. `.PHONY`), pattern rules and targets named with variables are skipped.
+pub(crate) fn makefile_targets(makefile: &str) -> Vec<TaskTemplate> {
+    let mut targets = Vec::<String>::new();
+    let mut in_define = false;
+    let mut logical_line = String::new();
+    for line in makefile.lines() {
+        if let Some(continued) = line.strip_suffix('\\') {
+            logical_line.push_str(continued);
+            logical_line.push(' ');
+            continue;
+        }
+        logical_line.push_str(line);
+        let line = std::mem::take(&mut logical_line);
+
+        // Recipe lines, which may contain anything.
+        if line.starts_with('\t') {
+            continue;
+        }
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let directive = line.split_whitespace().next().unwrap_or_default();
+        if in_define {
+            in_define = directive != "endef";
+            continue;
+        }
+        if directive == "define" {
+            in_define = true;
+            continue;
+        }
+
+        let Some((rule_targets, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let is_assignment = rule_targets.contains('=')
+            || rest.starts_with('=')
+            || rest.starts_with(":=")
+            || rest.starts_with("::=");
+        if is_assignment {
+            continue;
+        }
+        for target in rule_targets.split_whitespace() {
+            let is_explicit =
+                !target.starts_with('.') && !target.contains('%') && !target.contains('$');
+            if is_explicit && !targets.iter().any(|known| known == target) {
+                targets.push(target.to_string());
+            }
+        }
+    }
+
+    targets
+        .into_iter()
+        .map(|target| TaskTemplate {
+            label: format!("make {target}"),
+            command: "make".to_string(),
+            args: vec![target],
+            cwd: Some(VariableName::WorktreeRoot.template_value()),
+            ..TaskTemplate::default()
+        })
+        .collect()
+}
+
+/// Whether the worktree's file at `path` is a justfile `just` would pick up in its root.
+pub(crate) fn is_justfile(path: &Path) -> bool {
+    JUSTFILES.iter().any(|justfile| path == Path::new(justfile))
+}
+
+/// Turns each of the public recipes of a justfile into a task running `just` with it, in the worktree root.
+/// Recipes are labelled with their doc comment, if any, and ask for their arguments when they have parameters.
+pub(crate) fn justfile_recipes(justfile: &str) -> Vec<TaskTemplate> {
+    let mut recipes = Vec::new();
+    let mut doc = None::<String>;
+    let mut private = false;
+    for line in justfile.lines() {
+        // Recipe bodies, and blank lines, which detach comments from the recipes after them.
+        if line.starts_with([' ', '\t']) || line.trim().is_empty() {
+            doc = None;
+            private = false;
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if !comment.starts_with('!') {
+                doc = Some(comment.trim().to_string());
+            }
+            continue;
+        }
+        if let Some(attributes) = line
+            .trim_end()
+            .strip_prefix('[')
+            .and_then(|attributes| attributes.strip_suffix(']'))
+        {
+            private |= attributes
+                .split(',')
+                .any(|attribute| attribute.trim() == "private");
+            if let Some(doc_attribute) = attributes
+                .find("doc(")
+                .map(|start| &attributes[start + "doc(".len()..])
+            {
+                if let Some(quote @ ('"' | '\'')) = doc_attribute.chars().next() {
+                    doc = doc_attribute[1..]
+                        .split_once(quote)
+                        .map(|(doc, _)| doc.to_string());
+                }
+            }
+            continue;
+        }
+
+        let header = line.strip_prefix('@').unwrap_or(line);
+        let name_len = header
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(header.len());
+        let (name, rest) = header.split_at(name_len);
+        let parameters = recipe_parameters(rest);
+        let is_public = !name.is_empty() && !name.starts_with('_') && !private;
+        if let Some(parameters) = parameters.filter(|_| is_public) {
+            recipes.push(TaskTemplate {
+                label: doc.take().unwrap_or_else(|| format!("just {name}")),
+                command: "just".to_string(),
+                args: vec![name.to_string()],
+                cwd: Some(VariableName::WorktreeRoot.template_value()),
+                prompt_for_args: !parameters.is_empty(),
+                ..TaskTemplate::default()
+            });
+        }
+        doc = None;
+        private = false;
+    }
+    recipes
+}
+
+/// The parameters of a recipe header, after the recipe name and up to the colon.
+/// Returns `None` for lines that are not recipe headers, such as assignments and settings.
+fn recipe_parameters(header_rest: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut chars = header_rest.char_indices().peekable();
+    while let Some((ix, c)) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, ':') => {
+                if chars.peek().is_some_and(|(_, next)| *next == '=') {
+                    return None;
+                }
+                return Some(header_rest[..ix].trim());
+            }
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// The offset of the JSON object key given, quoted, in a JSON text.
+pub(crate) fn json_key_offset(json: &str, key: &str) -> Option<usize> {
+    let quoted_key = serde_json::to_string(key).ok()?;
+    json.match_indices(&quoted_key)
+        .map(|(ix, _)| ix)
+        .find(|ix| json[ix + quoted_key.len()..].trim_start().starts_with(':'))
+}
+
+/// The offset of the line of a makefile with the rule for the target given.
+pub(crate) fn makefile_target_offset(makefile: &str, target: &str) -> Option<usize> {
+    line_offsets(makefile).find_map(|(offset, line)| {
+        if line.starts_with('\t') {
+            return None;
+        }
+        let (rule_targets, _) = line.split_once(':')?;
+        rule_targets
+            .split_whitespace()
+            .any(|rule_target| rule_target == target)
+            .then_some(offset)
+    })
+}
+
+/// The offset of the header line of a justfile recipe.
+pub(crate) fn justfile_recipe_offset(justfile: &str, recipe: &str) -> Option<usize> {
+    line_offsets(justfile).find_map(|(offset, line)| {
+        let header = line.strip_prefix('@').unwrap_or(line);
+        let rest = header.strip_prefix(recipe)?;
+        let is_whole_name =
+            !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '-');
+        (is_whole_name && recipe_parameters(rest).is_some()).then_some(offset)
+    })
+}
+
+fn line_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let line_offset = *offset;
+        *offset += line.len();
+        Some((line_offset, line.trim_end_matches(['\r', '\n'])))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_manager_detection() {
+        assert_eq!(PackageManager::detect(|_| false), PackageManager::Npm);
+        assert_eq!(
+            PackageManager::detect(|path| path == Path::new("yarn.lock")),
+            PackageManager::Yarn
+        );
+        assert_eq!(
+            PackageManager::detect(|path| {
+                path == Path::new("package-lock.json") || path == Path::new("pnpm-lock.yaml")
+            }),
+            PackageManager::Pnpm
+        );
+        assert!(PackageManager::affects_scripts(Path::new("package.json")));
+        assert!(PackageManager::affects_scripts(Path::new("bun.lockb")));
+        assert!(!PackageManager::affects_scripts(Path::new(
+            "packages/app/package.json"
+        )));
+    }
+
+    #[test]
+    fn test_package_json_scripts() {
+        let package_json = r#"{
+            "name": "app",
+            "scripts": {
+                "test": "vitest",
+                "build": "vite build"
+            }
+        }"#;
+        let tasks = package_json_scripts(package_json, PackageManager::Pnpm).unwrap();
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["pnpm run build", "pnpm run test"]
+        );
+        assert_eq!(tasks[0].command, "pnpm");
+        assert_eq!(tasks[0].args, vec!["run", "build"]);
+        assert_eq!(tasks[0].cwd.as_deref(), Some("$ZED_WORKTREE_ROOT"));
+
+        assert!(
+            package_json_scripts(r#"{"name": "app"}"#, PackageManager::Npm)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(package_json_scripts("not json", PackageManager::Npm).is_err());
+    }
+
+    #[test]
+    fn test_makefile_targets() {
+        let makefile = "\
+CC := gcc
+FLAGS = -O2 \\
+    -Wall
+.PHONY: all test clean
+
+# Builds everything: the app and the docs.
+all: app docs
+
+app docs: main.c
+\techo \"building: $@\"
+
+test::
+\t./run-tests
+
+%.o: %.c
+\t$(CC) -c $<
+
+$(BUILD_DIR)/out: app
+
+define RECIPE
+generated: all
+endef
+
+clean:
+\trm -rf build
+app: extra
+";
+        let tasks = makefile_targets(makefile);
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.label.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "make all",
+                "make app",
+                "make docs",
+                "make test",
+                "make clean"
+            ]
+        );
+        assert_eq!(tasks[0].command, "make");
+        assert_eq!(tasks[0].args, vec!["all"]);
+        assert!(is_makefile(Path::new("GNUmakefile")));
+        assert!(!is_makefile(Path::new("docs/Makefile")));
+    }
+
+    #[test]
+    fn test_justfile_recipes() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+version := `git describe`
+alias b := build
+
+# Build the project
+build:
+    cargo build
+
+[private]
+helper:
+    echo hidden
+
+_also_hidden:
+    echo hidden
+
+# Runs the server
+[no-cd]
+@serve addr='localhost:8080' *flags: build
+    ./serve {{addr}} {{flags}}
+
+[doc('Deploys to an environment')]
+deploy env:
+    ./deploy {{env}}
+
+test: build
+    cargo test
+";
+        let recipes = justfile_recipes(justfile);
+        assert_eq!(
+            recipes
+                .iter()
+                .map(|recipe| (
+                    recipe.label.as_str(),
+                    recipe.args[0].as_str(),
+                    recipe.prompt_for_args
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Build the project", "build", false),
+                ("Runs the server", "serve", true),
+                ("Deploys to an environment", "deploy", true),
+                ("just test", "test", false),
+            ]
+        );
+        assert_eq!(recipes[0].command, "just");
+        assert!(is_justfile(Path::new(".justfile")));
+    }
+
+    #[test]
+    fn test_definition_offsets() {
+        let package_json = r#"{"scripts": {"build": "vite build", "test": "vitest"}}"#;
+        assert_eq!(
+            json_key_offset(package_json, "test"),
+            package_json.find(r#""test""#)
+        );
+        assert_eq!(json_key_offset(package_json, "vitest"), None);
+
+        let makefile = "all: app\n\techo all: done\n\napp docs: main.c\n";
+        assert_eq!(makefile_target_offset(makefile, "all"), Some(0));
+        assert_eq!(
+            makefile_target_offset(makefile, "docs"),
+            makefile.find("app docs")
+        );
+        assert_eq!(makefile_target_offset(makefile, "done"), None);
+
+        let justfile = "build-all:\n    just build\n\n@build target='x':\n    cargo build\n";
+        assert_eq!(
+            justfile_recipe_offset(justfile, "build"),
+            justfile.find("@build")
+        );
+        assert_eq!(justfile_recipe_offset(justfile, "build-all"), Some(0));
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
     prettier_store::{self, PrettierStore, PrettierStoreEvent},
     project_settings::{LspSettings, ProjectSettings},
     relativize_path, resolve_path,
+    terminals::activate_toolchain_environment,
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
@@ -146,6 +147,10 @@ pub struct LocalLspStore {
     fs: Arc<dyn Fs>,
     languages: Arc<LanguageRegistry>,
     language_server_ids: HashMap<(WorktreeId, LanguageServerName), LanguageServerId>,
+    /// The environment of the toolchain active for the language each language server was started
+    /// for, so that servers are only restarted when it changes.
+    language_server_toolchain_environments:
+        HashMap<(WorktreeId, LanguageServerName), HashMap<String, String>>,
     yarn: Model<YarnPathStore>,
     pub language_servers: HashMap<LanguageServerId, LanguageServerState>,
     buffers_being_formatted: HashSet<BufferId>,
@@ -211,7 +216,24 @@ impl LocalLspStore {
             adapter.name.0
         );
 
-        let binary = self.get_language_server_binary(adapter.clone(), delegate.clone(), true, cx);
+        let toolchain_environment = self
+            .toolchain_store
+            .read(cx)
+            .active_toolchain_environments(worktree_id, cx)
+            .into_iter()
+            .filter(|(toolchain, _)| toolchain.language_name == language)
+            .flat_map(|(_, environment)| environment)
+            .collect::<HashMap<_, _>>();
+        self.language_server_toolchain_environments
+            .insert(key.clone(), toolchain_environment.clone());
+
+        let binary = self.get_language_server_binary(
+            adapter.clone(),
+            delegate.clone(),
+            toolchain_environment,
+            true,
+            cx,
+        );
 
         let pending_server = cx.spawn({
             let adapter = adapter.clone();
@@ -432,10 +454,13 @@ impl LocalLspStore {
             .reorder_language_servers(&language, enabled_lsp_adapters);
     }
 
+    /// The binary to start the language server with, run in the shell environment of the worktree
+    /// with the active toolchain's environment on top.
     fn get_language_server_binary(
         &self,
         adapter: Arc<CachedLspAdapter>,
         delegate: Arc<dyn LspAdapterDelegate>,
+        toolchain_environment: HashMap<String, String>,
         allow_binary_download: bool,
         cx: &mut ModelContext<LspStore>,
     ) -> Task<Result<LanguageServerBinary>> {
@@ -453,9 +478,11 @@ impl LocalLspStore {
         if settings.as_ref().is_some_and(|b| b.path.is_some()) {
             let settings = settings.unwrap();
             return cx.spawn(|_, _| async move {
+                let mut env = delegate.shell_env().await;
+                activate_toolchain_environment(&mut env, &toolchain_environment);
                 Ok(LanguageServerBinary {
                     path: PathBuf::from(&settings.path.unwrap()),
-                    env: Some(delegate.shell_env().await),
+                    env: Some(env),
                     arguments: settings
                         .arguments
                         .unwrap_or_default()
@@ -492,6 +519,7 @@ impl LocalLspStore {
             }
 
             let mut shell_env = delegate.shell_env().await;
+            activate_toolchain_environment(&mut shell_env, &toolchain_environment);
             shell_env.extend(binary.env.unwrap_or_default());
             binary.env = Some(shell_env);
             Ok(binary)
@@ -2937,6 +2965,7 @@ impl LspStore {
                 supplementary_language_servers: Default::default(),
                 languages: languages.clone(),
                 language_server_ids: Default::default(),
+                language_server_toolchain_environments: Default::default(),
                 language_servers: Default::default(),
                 last_workspace_edits_by_language_server: Default::default(),
                 language_server_watched_paths: Default::default(),
@@ -3130,12 +3159,57 @@ impl LspStore {
         &mut self,
         _: Model<ToolchainStore>,
         event: &ToolchainStoreEvent,
-        _: &mut ModelContext<Self>,
+        cx: &mut ModelContext<Self>,
     ) {
         match event {
             ToolchainStoreEvent::ToolchainActivated { .. } => {
                 self.request_workspace_config_refresh()
             }
+            ToolchainStoreEvent::ActivationEnvironmentChanged {
+                worktree_id,
+                language_name,
+            } => self.restart_language_servers_for_toolchain(*worktree_id, language_name, cx),
+        }
+    }
+
+    /// Restarts the language servers of the language that were started with another toolchain
+    /// environment than the one now active for the worktree.
+    fn restart_language_servers_for_toolchain(
+        &mut self,
+        worktree_id: WorktreeId,
+        language_name: &LanguageName,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let Some(worktree) = local
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        else {
+            return;
+        };
+        let toolchain_environment = local
+            .toolchain_store
+            .read(cx)
+            .active_toolchain_environments(worktree_id, cx)
+            .into_iter()
+            .filter(|(toolchain, _)| &toolchain.language_name == language_name)
+            .flat_map(|(_, environment)| environment)
+            .collect::<HashMap<_, _>>();
+        let is_outdated = self
+            .languages
+            .lsp_adapters(language_name)
+            .iter()
+            .any(|adapter| {
+                let key = (worktree_id, adapter.name.clone());
+                local.language_server_ids.contains_key(&key)
+                    && local.language_server_toolchain_environments.get(&key)
+                        != Some(&toolchain_environment)
+            });
+        if is_outdated {
+            self.restart_local_language_servers(worktree, language_name.clone(), cx);
         }
     }
 
@@ -2,6 +2,7 @@ pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
 pub mod debounced_delay;
+mod detected_tasks;
 pub mod image_store;
 pub mod lsp_command;
 pub mod lsp_ext_command;
@@ -33,7 +34,7 @@ use debounced_delay::DebouncedDelay;
 pub use environment::ProjectEnvironment;
 use futures::{
     channel::mpsc::{self, UnboundedReceiver},
-    future::try_join_all,
+    future::{try_join_all, Shared},
     StreamExt,
 };
 pub use image_store::{ImageItem, ImageStore};
@@ -48,8 +49,8 @@ use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent,
     CachedLspAdapter, Capability, CodeLabel, Documentation, File as _, Language, LanguageName,
-    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction,
-    Unclipped,
+    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainDetails,
+    ToolchainList, Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServer,
@@ -81,7 +82,8 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use task_store::TaskStore;
+use task::SpawnInTerminal;
+use task_store::{FinishedTask, TaskStore};
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use toolchain_store::EmptyToolchainStore;
@@ -89,12 +91,13 @@ use util::{paths::compare_paths, ResultExt as _};
 use worktree::{CreatedEntry, Snapshot, Traversal};
 use worktree_store::{WorktreeStore, WorktreeStoreEvent};
 
+pub use detected_tasks::PackageManager;
 pub use fs::*;
 pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use task_inventory::{
-    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
+    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskRunStats, TaskSourceKind,
 };
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
@@ -168,6 +171,7 @@ pub struct Project {
     environment: Model<ProjectEnvironment>,
     settings_observer: Model<SettingsObserver>,
     toolchain_store: Option<Model<ToolchainStore>>,
+    pending_toolchain_installs: Vec<Toolchain>,
 }
 
 #[derive(Default)]
@@ -274,6 +278,10 @@ pub enum Event {
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
+    /// A task in watch mode has to be spawned again, as the files it watches changed.
+    RestartTask(Box<SpawnInTerminal>),
+    /// A task finished running in one of the project's terminals.
+    TaskFinished(FinishedTask),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -661,6 +669,8 @@ impl Project {
             });
             cx.subscribe(&settings_observer, Self::on_settings_observer_event)
                 .detach();
+            cx.subscribe(&task_store, Self::on_task_store_event)
+                .detach();
 
             let lsp_store = cx.new_model(|cx| {
                 LspStore::new_local(
@@ -701,6 +711,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_completion_listeners: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -711,6 +722,7 @@ impl Project {
                 search_excluded_history: Self::new_search_history(),
 
                 toolchain_store: Some(toolchain_store),
+                pending_toolchain_installs: Vec::new(),
             }
         })
     }
@@ -776,6 +788,8 @@ impl Project {
             });
             cx.subscribe(&settings_observer, Self::on_settings_observer_event)
                 .detach();
+            cx.subscribe(&task_store, Self::on_task_store_event)
+                .detach();
 
             let environment = ProjectEnvironment::new(&worktree_store, None, cx);
 
@@ -834,6 +848,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_completion_listeners: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -844,6 +859,7 @@ impl Project {
                 search_excluded_history: Self::new_search_history(),
 
                 toolchain_store: Some(toolchain_store),
+                pending_toolchain_installs: Vec::new(),
             };
 
             let ssh = ssh.read(cx);
@@ -1009,6 +1025,8 @@ impl Project {
             cx.subscribe(&lsp_store, Self::on_lsp_store_event).detach();
             cx.subscribe(&settings_observer, Self::on_settings_observer_event)
                 .detach();
+            cx.subscribe(&task_store, Self::on_task_store_event)
+                .detach();
 
             let mut this = Self {
                 buffer_ordered_messages_tx: tx,
@@ -1039,6 +1057,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_completion_listeners: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -1047,6 +1066,7 @@ impl Project {
                 environment: ProjectEnvironment::new(&worktree_store, None, cx),
                 remotely_created_models: Arc::new(Mutex::new(RemotelyCreatedModels::default())),
                 toolchain_store: None,
+                pending_toolchain_installs: Vec::new(),
             };
             this.set_role(role, cx);
             for worktree in worktrees {
@@ -1342,6 +1362,15 @@ impl Project {
         &self.task_store
     }
 
+    /// Re-reads all task sources of the project: the global and worktree tasks files, and the detected tasks.
+    pub fn reload_tasks(&mut self, cx: &mut ModelContext<Self>) {
+        self.settings_observer.update(cx, |settings_observer, cx| {
+            settings_observer.reload_local_tasks(cx)
+        });
+        self.task_store
+            .update(cx, |task_store, cx| task_store.reload_tasks(cx));
+    }
+
     pub fn snippets(&self) -> &Model<SnippetProvider> {
         &self.snippets
     }
@@ -2275,6 +2304,18 @@ impl Project {
         }
     }
 
+    fn on_task_store_event(
+        &mut self,
+        _: Model<TaskStore>,
+        event: &Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            Event::RestartTask(_) | Event::TaskFinished(_) => cx.emit(event.clone()),
+            _ => {}
+        }
+    }
+
     fn on_worktree_store_event(
         &mut self,
         _: Model<WorktreeStore>,
@@ -2568,6 +2609,81 @@ impl Project {
             this.activate_toolchain(worktree_id, toolchain, cx)
         })
     }
+    pub fn install_toolchain(
+        &mut self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Toolchain>> {
+        let Some(toolchain_store) = self.toolchain_store.clone() else {
+            return Task::ready(Err(anyhow!("toolchains are not available in this project")));
+        };
+        let toolchain_store = toolchain_store.read(cx);
+        let install = toolchain_store.install_toolchain(worktree_id, toolchain.clone(), cx);
+        self.pending_toolchain_installs.push(toolchain.clone());
+        cx.notify();
+        cx.spawn(|this, mut cx| async move {
+            let result = install.await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(ix) = this
+                    .pending_toolchain_installs
+                    .iter()
+                    .position(|pending| pending == &toolchain)
+                {
+                    this.pending_toolchain_installs.remove(ix);
+                }
+                cx.notify();
+            })?;
+            result
+        })
+    }
+    /// Toolchains that are currently being installed via [`Project::install_toolchain`].
+    pub fn pending_toolchain_installs(&self) -> &[Toolchain] {
+        &self.pending_toolchain_installs
+    }
+    /// The toolchains active in the given worktree, along with the environment variables
+    /// that activating them sets.
+    pub fn active_toolchain_environments(
+        &self,
+        worktree_id: WorktreeId,
+        cx: &AppContext,
+    ) -> Vec<(Toolchain, HashMap<String, String>)> {
+        self.toolchain_store
+            .as_ref()
+            .map(|toolchain_store| {
+                toolchain_store
+                    .read(cx)
+                    .active_toolchain_environments(worktree_id, cx)
+            })
+            .unwrap_or_default()
+    }
+    /// The environment of a shell in the given worktree, the one its tasks and language servers
+    /// run with.
+    pub fn shell_environment(
+        &mut self,
+        worktree_id: WorktreeId,
+        cx: &mut ModelContext<Self>,
+    ) -> Shared<Task<Option<HashMap<String, String>>>> {
+        let worktree_abs_path = self
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path());
+        self.environment.update(cx, |environment, cx| {
+            environment.get_environment(Some(worktree_id), worktree_abs_path, cx)
+        })
+    }
+    pub fn toolchain_details(
+        &self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &AppContext,
+    ) -> Task<Option<ToolchainDetails>> {
+        let Some(toolchain_store) = self.toolchain_store.clone() else {
+            return Task::ready(None);
+        };
+        toolchain_store
+            .read(cx)
+            .toolchain_details(worktree_id, toolchain, cx)
+    }
     pub fn active_toolchain(
         &self,
         worktree_id: WorktreeId,
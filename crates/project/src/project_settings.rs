@@ -56,6 +56,21 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Env files whose variables are added to the environment of every task,
+    /// relative to the worktree root. The tasks' own `env_files` and `env` override them.
+    /// Default: []
+    #[serde(default)]
+    pub task_env_files: Vec<String>,
+
+    /// Tasks to hide from the tasks modal, as globs matched against the task labels
+    /// and against `<source>:<label>` strings, where the source is one of `zed`, `vscode`, `global`,
+    /// `npm`, `pnpm`, `yarn`, `bun`, `make`, `just` or `language:<language name>`,
+    /// e.g. `"language:rust:*"` hides all tasks provided for Rust.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub hidden_tasks: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -369,6 +384,26 @@ impl SettingsObserver {
         }
     }
 
+    /// Re-reads the `.zed/tasks.json` and `.vscode/tasks.json` files of the local worktrees,
+    /// as if all of them were changed on disk.
+    pub fn reload_local_tasks(&mut self, cx: &mut ModelContext<Self>) {
+        let worktrees = self.worktree_store.read(cx).worktrees().collect::<Vec<_>>();
+        for worktree in worktrees {
+            let changes: UpdatedEntriesSet = worktree
+                .read(cx)
+                .files(false, 0)
+                .filter(|entry| {
+                    entry.path.ends_with(local_tasks_file_relative_path())
+                        || entry
+                            .path
+                            .ends_with(local_vscode_tasks_file_relative_path())
+                })
+                .map(|entry| (entry.path.clone(), entry.id, PathChange::Updated))
+                .collect();
+            self.update_local_worktree_settings(&worktree, &changes, cx);
+        }
+    }
+
     fn update_local_worktree_settings(
         &mut self,
         worktree: &Model<Worktree>,
@@ -1,4 +1,4 @@
-use crate::{Event, *};
+use crate::{terminals::TerminalKind, Event, *};
 use fs::FakeFs;
 use futures::{future, StreamExt};
 use git::diff::assert_hunks;
@@ -22,7 +22,10 @@ use std::os;
 use std::{str::FromStr, sync::OnceLock};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{
+    HideStrategy, ResolvedTask, RevealStrategy, RevealTarget, SpawnInTerminal, TaskConcurrency,
+    TaskContext, TaskId, TaskShell,
+};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree, TryFutureExt as _};
 
@@ -97,6 +100,223 @@ async fn test_symlinks(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_running_tasks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(terminal::init);
+    cx.executor().allow_parking();
+
+    let dir = temp_tree(json!({ "a.txt": "" }));
+    let project = Project::test(Arc::new(RealFs::default()), [dir.path()], cx).await;
+    let task_store = project.read_with(cx, |project, _| project.task_store().clone());
+    let window = cx.add_empty_window().handle();
+    let task_id = TaskId("sleep".to_string());
+
+    let terminal = project
+        .update(cx, |project, cx| {
+            project.create_terminal_with_venv(
+                TerminalKind::Task(SpawnInTerminal {
+                    id: task_id.clone(),
+                    full_label: "sleep".to_string(),
+                    label: "sleep".to_string(),
+                    command: "sleep".to_string(),
+                    args: vec!["30".to_string()],
+                    command_label: "sleep 30".to_string(),
+                    cwd: Some(dir.path().to_path_buf()),
+                    env: HashMap::default(),
+                    use_new_terminal: false,
+                    allow_concurrent_runs: false,
+                    reveal: RevealStrategy::default(),
+                    reveal_target: RevealTarget::default(),
+                    hide: HideStrategy::default(),
+                    shell: TaskShell::default(),
+                    show_summary: false,
+                    show_command: false,
+                    file_link_patterns: Vec::new(),
+                }),
+                None,
+                window,
+                cx,
+            )
+        })
+        .unwrap();
+    task_store.read_with(cx, |task_store, _| {
+        let running_task = task_store
+            .running_task(&task_id)
+            .expect("spawned task should be running");
+        assert_eq!(running_task.terminal, terminal.downgrade());
+        assert_eq!(task_store.running_tasks().count(), 1);
+    });
+
+    task_store.update(cx, |task_store, cx| task_store.kill_task(&task_id, cx));
+    let mut task_finished = false;
+    for _ in 0..500 {
+        cx.executor().advance_clock(Duration::from_millis(10));
+        cx.run_until_parked();
+        task_finished = task_store.read_with(cx, |task_store, _| {
+            task_store.running_task(&task_id).is_none()
+        });
+        if task_finished {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(
+        task_finished,
+        "killed task should no longer be tracked as running"
+    );
+    terminal.read_with(cx, |terminal, _| {
+        assert_ne!(
+            terminal.task().map(|task| task.status),
+            Some(terminal::TaskStatus::Running),
+            "the terminal should stay open with the task finished"
+        );
+    });
+}
+
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_queued_task_concurrency(cx: &mut gpui::TestAppContext) {
+    let (task_store, _terminal, task_id) = spawn_long_running_task(cx).await;
+
+    let can_spawn = task_store.update(cx, |task_store, cx| {
+        task_store.apply_concurrency(&[task_id.clone()], TaskConcurrency::Queue, cx)
+    });
+    assert!(
+        can_spawn,
+        "queued tasks should be spawned after the running one"
+    );
+    assert_task_running(&task_store, &task_id, cx);
+
+    task_store.update(cx, |task_store, cx| task_store.kill_task(&task_id, cx));
+    wait_for_task_to_finish(&task_store, &task_id, cx);
+    let can_spawn = task_store.update(cx, |task_store, cx| {
+        task_store.apply_concurrency(&[task_id.clone()], TaskConcurrency::Queue, cx)
+    });
+    assert!(can_spawn);
+}
+
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_singleton_task_concurrency(cx: &mut gpui::TestAppContext) {
+    let (task_store, _terminal, task_id) = spawn_long_running_task(cx).await;
+
+    let can_spawn = task_store.update(cx, |task_store, cx| {
+        task_store.apply_concurrency(&[task_id.clone()], TaskConcurrency::Singleton, cx)
+    });
+    assert!(
+        !can_spawn,
+        "singleton tasks should not be spawned while running"
+    );
+    assert_task_running(&task_store, &task_id, cx);
+
+    task_store.update(cx, |task_store, cx| task_store.kill_task(&task_id, cx));
+    wait_for_task_to_finish(&task_store, &task_id, cx);
+    let can_spawn = task_store.update(cx, |task_store, cx| {
+        task_store.apply_concurrency(&[task_id.clone()], TaskConcurrency::Singleton, cx)
+    });
+    assert!(can_spawn, "singleton tasks should be spawned once finished");
+}
+
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_kill_previous_task_concurrency(cx: &mut gpui::TestAppContext) {
+    let (task_store, terminal, task_id) = spawn_long_running_task(cx).await;
+
+    let can_spawn = task_store.update(cx, |task_store, cx| {
+        task_store.apply_concurrency(&[task_id.clone()], TaskConcurrency::KillPrevious, cx)
+    });
+    assert!(can_spawn);
+    wait_for_task_to_finish(&task_store, &task_id, cx);
+    terminal.read_with(cx, |terminal, _| {
+        assert_ne!(
+            terminal.task().map(|task| task.status),
+            Some(terminal::TaskStatus::Running),
+            "the running task should be killed"
+        );
+    });
+}
+
+/// Spawns a task that runs until it is killed, in a terminal of a real shell.
+#[cfg(not(windows))]
+async fn spawn_long_running_task(
+    cx: &mut gpui::TestAppContext,
+) -> (Model<TaskStore>, Model<terminal::Terminal>, TaskId) {
+    init_test(cx);
+    cx.update(terminal::init);
+    cx.executor().allow_parking();
+
+    let dir = temp_tree(json!({ "a.txt": "" }));
+    let project = Project::test(Arc::new(RealFs::default()), [dir.path()], cx).await;
+    let task_store = project.read_with(cx, |project, _| project.task_store().clone());
+    let window = cx.add_empty_window().handle();
+    let task_id = TaskId("sleep".to_string());
+    let terminal = project
+        .update(cx, |project, cx| {
+            project.create_terminal_with_venv(
+                TerminalKind::Task(SpawnInTerminal {
+                    id: task_id.clone(),
+                    full_label: "sleep".to_string(),
+                    label: "sleep".to_string(),
+                    command: "sleep".to_string(),
+                    args: vec!["30".to_string()],
+                    command_label: "sleep 30".to_string(),
+                    cwd: Some(dir.path().to_path_buf()),
+                    env: HashMap::default(),
+                    use_new_terminal: false,
+                    allow_concurrent_runs: false,
+                    reveal: RevealStrategy::default(),
+                    reveal_target: RevealTarget::default(),
+                    hide: HideStrategy::default(),
+                    shell: TaskShell::default(),
+                    show_summary: false,
+                    show_command: false,
+                    file_link_patterns: Vec::new(),
+                }),
+                None,
+                window,
+                cx,
+            )
+        })
+        .unwrap();
+    assert_task_running(&task_store, &task_id, cx);
+    (task_store, terminal, task_id)
+}
+
+#[cfg(not(windows))]
+fn assert_task_running(
+    task_store: &Model<TaskStore>,
+    task_id: &TaskId,
+    cx: &mut gpui::TestAppContext,
+) {
+    task_store.read_with(cx, |task_store, _| {
+        assert!(
+            task_store.running_task(task_id).is_some(),
+            "task {task_id:?} should be running"
+        );
+    });
+}
+
+#[cfg(not(windows))]
+fn wait_for_task_to_finish(
+    task_store: &Model<TaskStore>,
+    task_id: &TaskId,
+    cx: &mut gpui::TestAppContext,
+) {
+    for _ in 0..500 {
+        cx.executor().advance_clock(Duration::from_millis(10));
+        cx.run_until_parked();
+        if task_store.read_with(cx, |task_store, _| {
+            task_store.running_task(task_id).is_none()
+        }) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("task {task_id:?} should have finished");
+}
+
 #[gpui::test]
 async fn test_editorconfig_support(cx: &mut gpui::TestAppContext) {
     init_test(cx);
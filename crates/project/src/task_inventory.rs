@@ -4,36 +4,81 @@ use std::{
     borrow::Cow,
     cmp::{self, Reverse},
     collections::hash_map,
+    mem,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
 use collections::{HashMap, HashSet, VecDeque};
+use git::repository::GitRepository;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{AppContext, Context as _, Model, Task};
 use itertools::Itertools;
-use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
-use settings::{parse_json_with_comments, SettingsLocation};
+use language::{
+    language_settings::language_settings, ContextProvider, File, Language, LanguageToolchainStore,
+    Location,
+};
+use settings::{parse_json_with_comments, Settings, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    ResolvedTask, TaskContext, TaskGroup, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
+    VariableName,
 };
 use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
-use worktree::WorktreeId;
+use worktree::{Worktree, WorktreeId};
 
-use crate::worktree_store::WorktreeStore;
+use crate::{
+    detected_tasks::{
+        json_key_offset, justfile_recipe_offset, makefile_target_offset, PackageManager, JUSTFILES,
+        MAKEFILES, PACKAGE_JSON,
+    },
+    project_settings::ProjectSettings,
+    worktree_store::WorktreeStore,
+};
+
+/// The directory of the VS Code settings, whose tasks.json gets imported.
+const VSCODE_DIRECTORY: &str = ".vscode";
+/// The name of the files defining tasks, in the settings directories of worktrees.
+const TASKS_FILE_NAME: &str = "tasks.json";
+/// How many scheduled tasks the history keeps, dropping the least recently scheduled ones beyond that.
+const MAX_SCHEDULED_TASKS: usize = 5_000;
 
 /// Inventory tracks available tasks for a given project.
 #[derive(Debug, Default)]
 pub struct Inventory {
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
+    /// Tasks pinned in the tasks modal of each worktree, by their [`TaskSourceKind::source_name`] and template label,
+    /// which unlike source kinds stay the same across restarts.
+    pinned_tasks: HashMap<Option<WorktreeId>, Vec<(String, String)>>,
+    /// Tasks set as the default ones of their groups in each worktree, by their source kind and template label.
+    group_defaults: HashMap<(Option<WorktreeId>, TaskGroup), (TaskSourceKind, String)>,
+    /// Statistics of the finished runs of each task in this session, by its source kind and template label.
+    run_stats: HashMap<(TaskSourceKind, String), TaskRunStats>,
+    /// Statistics of the finished runs of the history entries in this session, by their resolved task ids.
+    history_run_stats: HashMap<TaskId, TaskRunStats>,
     templates_from_settings: ParsedTemplates,
 }
 
+/// How the runs of a task went so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskRunStats {
+    /// How many runs of the task finished.
+    pub run_count: usize,
+    pub last_duration: Duration,
+    pub last_success: bool,
+    /// The exit code of the last run's process, if it reported one.
+    pub last_exit_code: Option<i32>,
+}
+
 #[derive(Debug, Default)]
 struct ParsedTemplates {
     global: Vec<TaskTemplate>,
     worktree: HashMap<WorktreeId, HashMap<Arc<Path>, Vec<TaskTemplate>>>,
+    detected_scripts: HashMap<WorktreeId, (PackageManager, Vec<TaskTemplate>)>,
+    makefile_targets: HashMap<WorktreeId, Vec<TaskTemplate>>,
+    justfile_recipes: HashMap<WorktreeId, Vec<TaskTemplate>>,
 }
 
 /// Kind of a source the tasks are fetched from, used to display more source information in the UI.
@@ -47,6 +92,11 @@ pub enum TaskSourceKind {
         directory_in_worktree: PathBuf,
         id_base: Cow<'static, str>,
     },
+    /// Tasks imported from the worktree's .vscode/tasks.json
+    VsCode {
+        id: WorktreeId,
+        directory_in_worktree: PathBuf,
+    },
     /// ~/.config/zed/task.json - like global files with task definitions, applicable to any path
     AbsPath {
         id_base: Cow<'static, str>,
@@ -54,9 +104,104 @@ pub enum TaskSourceKind {
     },
     /// Languages-specific tasks coming from extensions.
     Language { name: Arc<str> },
+    /// Languages-specific tasks defined in the language's settings, `languages.<name>.tasks.templates`
+    LanguageSettings { name: Arc<str> },
+    /// Scripts of the worktree's root package.json, run with the package manager its lockfile belongs to
+    DetectedScript {
+        id: WorktreeId,
+        package_manager: PackageManager,
+    },
+    /// Targets of the worktree's root Makefile
+    MakefileTarget { id: WorktreeId },
+    /// Recipes of the worktree's root justfile
+    JustfileRecipe { id: WorktreeId },
 }
 
 impl TaskSourceKind {
+    /// The worktree the tasks of this kind belong to, if any.
+    pub fn worktree(&self) -> Option<WorktreeId> {
+        match self {
+            TaskSourceKind::Worktree { id, .. }
+            | TaskSourceKind::VsCode { id, .. }
+            | TaskSourceKind::DetectedScript { id, .. }
+            | TaskSourceKind::MakefileTarget { id }
+            | TaskSourceKind::JustfileRecipe { id } => Some(*id),
+            TaskSourceKind::UserInput
+            | TaskSourceKind::AbsPath { .. }
+            | TaskSourceKind::Language { .. }
+            | TaskSourceKind::LanguageSettings { .. } => None,
+        }
+    }
+
+    /// The files that may define the tasks of this kind, in the order they are looked up:
+    /// relative to the root of [`Self::worktree`], or absolute for tasks that do not belong to a worktree.
+    pub fn definition_paths(&self) -> Vec<PathBuf> {
+        match self {
+            TaskSourceKind::Worktree {
+                directory_in_worktree,
+                ..
+            }
+            | TaskSourceKind::VsCode {
+                directory_in_worktree,
+                ..
+            } => vec![directory_in_worktree.join(TASKS_FILE_NAME)],
+            TaskSourceKind::AbsPath { abs_path, .. } => vec![abs_path.clone()],
+            TaskSourceKind::DetectedScript { .. } => vec![PathBuf::from(PACKAGE_JSON)],
+            TaskSourceKind::MakefileTarget { .. } => MAKEFILES.iter().map(PathBuf::from).collect(),
+            TaskSourceKind::JustfileRecipe { .. } => JUSTFILES.iter().map(PathBuf::from).collect(),
+            TaskSourceKind::UserInput
+            | TaskSourceKind::Language { .. }
+            | TaskSourceKind::LanguageSettings { .. } => Vec::new(),
+        }
+    }
+
+    /// The offset of the task's definition in the text of the file defining it, one of [`Self::definition_paths`].
+    pub fn definition_offset(&self, template: &TaskTemplate, text: &str) -> Option<usize> {
+        match self {
+            TaskSourceKind::Worktree { .. }
+            | TaskSourceKind::VsCode { .. }
+            | TaskSourceKind::AbsPath { .. } => {
+                let quoted_label = serde_json::to_string(&template.label).ok()?;
+                text.match_indices(&quoted_label)
+                    .map(|(ix, _)| ix)
+                    .find(|ix| {
+                        text[..*ix]
+                            .trim_end()
+                            .strip_suffix(':')
+                            .is_some_and(|key| key.trim_end().ends_with("\"label\""))
+                    })
+            }
+            TaskSourceKind::DetectedScript { .. } => json_key_offset(text, template.args.get(1)?),
+            TaskSourceKind::MakefileTarget { .. } => {
+                makefile_target_offset(text, template.args.first()?)
+            }
+            TaskSourceKind::JustfileRecipe { .. } => {
+                justfile_recipe_offset(text, template.args.first()?)
+            }
+            TaskSourceKind::UserInput
+            | TaskSourceKind::Language { .. }
+            | TaskSourceKind::LanguageSettings { .. } => None,
+        }
+    }
+
+    /// The name of the source, as matched by the globs of the hidden tasks in the project settings.
+    pub fn source_name(&self) -> Cow<'static, str> {
+        match self {
+            TaskSourceKind::UserInput => Cow::Borrowed("oneshot"),
+            TaskSourceKind::AbsPath { .. } => Cow::Borrowed("global"),
+            TaskSourceKind::Worktree { .. } => Cow::Borrowed("zed"),
+            TaskSourceKind::VsCode { .. } => Cow::Borrowed("vscode"),
+            TaskSourceKind::Language { name } | TaskSourceKind::LanguageSettings { name } => {
+                Cow::Owned(format!("language:{}", name.to_lowercase()))
+            }
+            TaskSourceKind::DetectedScript {
+                package_manager, ..
+            } => Cow::Borrowed(package_manager.name()),
+            TaskSourceKind::MakefileTarget { .. } => Cow::Borrowed("make"),
+            TaskSourceKind::JustfileRecipe { .. } => Cow::Borrowed("just"),
+        }
+    }
+
     pub fn to_id_base(&self) -> String {
         match self {
             TaskSourceKind::UserInput => "oneshot".to_string(),
@@ -70,7 +215,18 @@ impl TaskSourceKind {
             } => {
                 format!("{id_base}_{id}_{}", directory_in_worktree.display())
             }
+            TaskSourceKind::VsCode {
+                id,
+                directory_in_worktree,
+            } => format!("vscode_tasks_{id}_{}", directory_in_worktree.display()),
             TaskSourceKind::Language { name } => format!("language_{name}"),
+            TaskSourceKind::LanguageSettings { name } => format!("language_settings_{name}"),
+            TaskSourceKind::DetectedScript {
+                id,
+                package_manager,
+            } => format!("{}_scripts_{id}", package_manager.name()),
+            TaskSourceKind::MakefileTarget { id } => format!("makefile_targets_{id}"),
+            TaskSourceKind::JustfileRecipe { id } => format!("justfile_recipes_{id}"),
         }
     }
 }
@@ -81,8 +237,8 @@ impl Inventory {
     }
 
     /// Pulls its task sources relevant to the worktree and the language given,
-    /// returns all task templates with their source kinds, worktree tasks first, language tasks second
-    /// and global tasks last. No specific order inside source kinds groups.
+    /// returns all task templates with their source kinds, worktree tasks first, detected scripts, Makefile targets and justfile recipes second,
+    /// language tasks third and global tasks last. No specific order inside source kinds groups.
     pub fn list_tasks(
         &self,
         file: Option<Arc<dyn File>>,
@@ -90,18 +246,17 @@ impl Inventory {
         worktree: Option<WorktreeId>,
         cx: &AppContext,
     ) -> Vec<(TaskSourceKind, TaskTemplate)> {
-        let task_source_kind = language.as_ref().map(|language| TaskSourceKind::Language {
-            name: language.name().0,
-        });
         let global_tasks = self.global_templates_from_settings();
         let language_tasks = language
-            .and_then(|language| language.context_provider()?.associated_tasks(file, cx))
+            .map(|language| language_templates(&language, file, cx))
             .into_iter()
-            .flat_map(|tasks| tasks.0.into_iter())
-            .flat_map(|task| Some((task_source_kind.clone()?, task)))
+            .flatten()
             .chain(global_tasks);
 
         self.worktree_templates_from_settings(worktree)
+            .chain(self.detected_script_templates(worktree))
+            .chain(self.makefile_target_templates(worktree))
+            .chain(self.justfile_recipe_templates(worktree))
             .chain(language_tasks)
             .collect()
     }
@@ -123,25 +278,32 @@ impl Inventory {
         let language = location
             .as_ref()
             .and_then(|location| location.buffer.read(cx).language_at(location.range.start));
-        let task_source_kind = language.as_ref().map(|language| TaskSourceKind::Language {
-            name: language.name().0,
-        });
+        let language_name = language.as_ref().map(|language| language.name().0);
         let file = location
             .as_ref()
             .and_then(|location| location.buffer.read(cx).file().cloned());
 
+        let hidden_tasks = hidden_tasks(worktree, cx);
+        let is_enabled = |task_kind: &TaskSourceKind, template: &TaskTemplate| {
+            hidden_tasks.as_ref().map_or(true, |hidden_tasks| {
+                !is_hidden(hidden_tasks, task_kind, template)
+            })
+        };
+
         let mut task_labels_to_ids = HashMap::<String, HashSet<TaskId>>::default();
         let mut lru_score = 0_u32;
         let previously_spawned_tasks = self
             .last_scheduled_tasks
             .iter()
             .rev()
-            .filter(|(task_kind, _)| {
-                if matches!(task_kind, TaskSourceKind::Language { .. }) {
-                    Some(task_kind) == task_source_kind.as_ref()
-                } else {
-                    true
+            .filter(|(task_kind, _)| match task_kind {
+                TaskSourceKind::Language { name } | TaskSourceKind::LanguageSettings { name } => {
+                    Some(name) == language_name.as_ref()
                 }
+                _ => true,
+            })
+            .filter(|(task_kind, resolved_task)| {
+                is_enabled(task_kind, resolved_task.original_task())
             })
             .filter(|(_, resolved_task)| {
                 match task_labels_to_ids.entry(resolved_task.resolved_label.clone()) {
@@ -170,16 +332,19 @@ impl Inventory {
         let not_used_score = post_inc(&mut lru_score);
         let global_tasks = self.global_templates_from_settings();
         let language_tasks = language
-            .and_then(|language| language.context_provider()?.associated_tasks(file, cx))
+            .map(|language| language_templates(&language, file, cx))
             .into_iter()
-            .flat_map(|tasks| tasks.0.into_iter())
-            .flat_map(|task| Some((task_source_kind.clone()?, task)))
+            .flatten()
             .chain(global_tasks);
         let worktree_tasks = self
             .worktree_templates_from_settings(worktree)
+            .chain(self.detected_script_templates(worktree))
+            .chain(self.makefile_target_templates(worktree))
+            .chain(self.justfile_recipe_templates(worktree))
             .chain(language_tasks);
 
         let new_resolved_tasks = worktree_tasks
+            .filter(|(kind, task)| is_enabled(kind, task))
             .filter_map(|(kind, task)| {
                 let id_base = kind.to_id_base();
                 Some((
@@ -231,8 +396,24 @@ impl Inventory {
     ) {
         self.last_scheduled_tasks
             .push_back((task_source_kind, resolved_task));
-        if self.last_scheduled_tasks.len() > 5_000 {
-            self.last_scheduled_tasks.pop_front();
+        if self.last_scheduled_tasks.len() > MAX_SCHEDULED_TASKS {
+            if let Some((_, evicted)) = self.last_scheduled_tasks.pop_front() {
+                if !self
+                    .last_scheduled_tasks
+                    .iter()
+                    .any(|(_, task)| task.id == evicted.id)
+                {
+                    self.history_run_stats.remove(&evicted.id);
+                }
+            }
+        }
+    }
+
+    /// Adds tasks used in earlier sessions to the history, as used before all tasks scheduled in this one.
+    /// The tasks are given from the least to the most recently used.
+    pub fn restore_scheduled_tasks(&mut self, tasks: Vec<(TaskSourceKind, ResolvedTask)>) {
+        for task in tasks.into_iter().rev() {
+            self.last_scheduled_tasks.push_front(task);
         }
     }
 
@@ -240,6 +421,175 @@ impl Inventory {
     /// A similar may still resurface in `used_and_current_resolved_tasks` when its [`TaskTemplate`] is resolved again.
     pub fn delete_previously_used(&mut self, id: &TaskId) {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
+        self.history_run_stats.remove(id);
+    }
+
+    /// Deletes the tasks scheduled in the worktree given from history: the ones from the worktree's task sources,
+    /// and the ones not bound to any worktree that ran in its root directory or below it.
+    /// Without a worktree, the tasks not bound to any worktree are deleted.
+    pub fn clear_history(&mut self, worktree: Option<(WorktreeId, &Path)>) {
+        let (cleared, kept) = mem::take(&mut self.last_scheduled_tasks)
+            .into_iter()
+            .partition::<VecDeque<_>, _>(|(kind, task)| match (kind.worktree(), worktree) {
+                (Some(task_worktree), Some((worktree, _))) => task_worktree == worktree,
+                (Some(_), None) => false,
+                (None, Some((_, worktree_root))) => task
+                    .resolved
+                    .as_ref()
+                    .and_then(|spawn_in_terminal| spawn_in_terminal.cwd.as_deref())
+                    .is_some_and(|cwd| cwd.starts_with(worktree_root)),
+                (None, None) => true,
+            });
+        self.last_scheduled_tasks = kept;
+        for (_, task) in cleared {
+            self.history_run_stats.remove(&task.id);
+        }
+    }
+
+    /// Records a finished run of the scheduled task with the given id in the statistics of its template and its history entry.
+    /// Runs of the tasks that are not in the history, such as ephemeral ones, are not recorded.
+    pub fn task_finished(
+        &mut self,
+        task_id: &TaskId,
+        exit_code: Option<i32>,
+        success: bool,
+        duration: Duration,
+    ) {
+        let Some((task_source_kind, task)) = self
+            .last_scheduled_tasks
+            .iter()
+            .rev()
+            .find(|(_, task)| &task.id == task_id)
+        else {
+            return;
+        };
+        let stats = self
+            .run_stats
+            .entry((task_source_kind.clone(), task.original_task().label.clone()))
+            .or_default();
+        let history_stats = self.history_run_stats.entry(task_id.clone()).or_default();
+        for stats in [stats, history_stats] {
+            stats.run_count += 1;
+            stats.last_duration = duration;
+            stats.last_success = success;
+            stats.last_exit_code = exit_code;
+        }
+    }
+
+    /// The statistics of the finished runs of the task with the source kind and template label given.
+    pub fn run_stats(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        label: &str,
+    ) -> Option<&TaskRunStats> {
+        self.run_stats
+            .get(&(task_source_kind.clone(), label.to_string()))
+    }
+
+    /// The statistics of the finished runs of the history entry with the resolved task id given.
+    pub fn history_run_stats(&self, task_id: &TaskId) -> Option<&TaskRunStats> {
+        self.history_run_stats.get(task_id)
+    }
+
+    /// How many tasks were scheduled after the last one resolved from the task with the source kind and template label given,
+    /// or `None` if it is not in the history.
+    pub fn scheduled_since(&self, task_source_kind: &TaskSourceKind, label: &str) -> Option<usize> {
+        self.last_scheduled_tasks
+            .iter()
+            .rev()
+            .position(|(kind, task)| {
+                kind == task_source_kind && task.original_task().label == label
+            })
+    }
+
+    /// Whether the task with the source kind and template label given is pinned in the worktree's tasks modal.
+    pub fn is_pinned(
+        &self,
+        worktree: Option<WorktreeId>,
+        task_source_kind: &TaskSourceKind,
+        label: &str,
+    ) -> bool {
+        let source_name = task_source_kind.source_name();
+        self.pinned_tasks
+            .get(&worktree)
+            .is_some_and(|pinned_tasks| {
+                pinned_tasks
+                    .iter()
+                    .any(|(pinned_source_name, pinned_label)| {
+                        pinned_source_name == &source_name && pinned_label == label
+                    })
+            })
+    }
+
+    /// Pins the task with the source kind and template label given in the worktree's tasks modal,
+    /// or unpins it if it was pinned already. Returns whether the task is pinned now.
+    pub fn toggle_pinned(
+        &mut self,
+        worktree: Option<WorktreeId>,
+        task_source_kind: &TaskSourceKind,
+        label: String,
+    ) -> bool {
+        let source_name = task_source_kind.source_name();
+        let pinned_tasks = self.pinned_tasks.entry(worktree).or_default();
+        let pinned_count = pinned_tasks.len();
+        pinned_tasks.retain(|(pinned_source_name, pinned_label)| {
+            pinned_source_name != &source_name || pinned_label != &label
+        });
+        let pinned = pinned_tasks.len() == pinned_count;
+        if pinned {
+            pinned_tasks.push((source_name.into_owned(), label));
+        }
+        pinned
+    }
+
+    /// Pins the tasks pinned in the worktree's tasks modal in an earlier session, by their source name and template label.
+    pub fn restore_pinned_tasks(
+        &mut self,
+        worktree: Option<WorktreeId>,
+        pinned: impl IntoIterator<Item = (String, String)>,
+    ) {
+        let pinned_tasks = self.pinned_tasks.entry(worktree).or_default();
+        for pinned_task in pinned {
+            if !pinned_tasks.contains(&pinned_task) {
+                pinned_tasks.push(pinned_task);
+            }
+        }
+    }
+
+    /// Sets the task with the source kind and template label given as the default one of the group in the worktree.
+    pub fn set_group_default(
+        &mut self,
+        worktree: Option<WorktreeId>,
+        group: TaskGroup,
+        task_source_kind: TaskSourceKind,
+        label: String,
+    ) {
+        self.group_defaults
+            .insert((worktree, group), (task_source_kind, label));
+    }
+
+    /// The index of the group's default task among the tasks given: the task set as the default one
+    /// in the worktree, or the first task of the group if that one is not among them.
+    pub fn group_default_index<'a>(
+        &self,
+        worktree: Option<WorktreeId>,
+        group: TaskGroup,
+        tasks: impl IntoIterator<Item = (&'a TaskSourceKind, &'a TaskTemplate)>,
+    ) -> Option<usize> {
+        let group_default = self.group_defaults.get(&(worktree, group));
+        let mut first_index = None;
+        for (ix, (task_source_kind, template)) in tasks.into_iter().enumerate() {
+            if template.group != Some(group) {
+                continue;
+            }
+            if group_default.is_some_and(|(default_kind, default_label)| {
+                default_kind == task_source_kind && default_label == &template.label
+            }) {
+                return Some(ix);
+            }
+            first_index.get_or_insert(ix);
+        }
+        first_index
     }
 
     fn global_templates_from_settings(
@@ -274,20 +624,131 @@ impl Inventory {
                     templates.iter().map(move |template| (directory, template))
                 })
                 .map(move |(directory, template)| {
-                    (
+                    let kind = if directory.ends_with(VSCODE_DIRECTORY) {
+                        TaskSourceKind::VsCode {
+                            id: worktree,
+                            directory_in_worktree: directory.to_path_buf(),
+                        }
+                    } else {
                         TaskSourceKind::Worktree {
                             id: worktree,
                             directory_in_worktree: directory.to_path_buf(),
                             id_base: Cow::Owned(format!(
                                 "local worktree tasks from directory {directory:?}"
                             )),
-                        },
+                        }
+                    };
+                    (kind, template.clone())
+                })
+        })
+    }
+
+    fn detected_script_templates(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
+        worktree.into_iter().flat_map(|worktree| {
+            self.templates_from_settings
+                .detected_scripts
+                .get(&worktree)
+                .into_iter()
+                .flat_map(move |(package_manager, templates)| {
+                    templates.iter().map(move |template| {
+                        (
+                            TaskSourceKind::DetectedScript {
+                                id: worktree,
+                                package_manager: *package_manager,
+                            },
+                            template.clone(),
+                        )
+                    })
+                })
+        })
+    }
+
+    fn makefile_target_templates(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
+        worktree.into_iter().flat_map(|worktree| {
+            self.templates_from_settings
+                .makefile_targets
+                .get(&worktree)
+                .into_iter()
+                .flatten()
+                .map(move |template| {
+                    (
+                        TaskSourceKind::MakefileTarget { id: worktree },
                         template.clone(),
                     )
                 })
         })
     }
 
+    fn justfile_recipe_templates(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
+        worktree.into_iter().flat_map(|worktree| {
+            self.templates_from_settings
+                .justfile_recipes
+                .get(&worktree)
+                .into_iter()
+                .flatten()
+                .map(move |template| {
+                    (
+                        TaskSourceKind::JustfileRecipe { id: worktree },
+                        template.clone(),
+                    )
+                })
+        })
+    }
+
+    /// Replaces the scripts detected in the worktree's package.json, removing them for no scripts given.
+    pub(crate) fn update_detected_scripts(
+        &mut self,
+        worktree: WorktreeId,
+        scripts: Option<(PackageManager, Vec<TaskTemplate>)>,
+    ) {
+        let detected_scripts = &mut self.templates_from_settings.detected_scripts;
+        match scripts {
+            Some((package_manager, templates)) if !templates.is_empty() => {
+                detected_scripts.insert(worktree, (package_manager, templates));
+            }
+            _ => {
+                detected_scripts.remove(&worktree);
+            }
+        }
+    }
+
+    /// Replaces the targets of the worktree's Makefile.
+    pub(crate) fn update_makefile_targets(
+        &mut self,
+        worktree: WorktreeId,
+        targets: Vec<TaskTemplate>,
+    ) {
+        let makefile_targets = &mut self.templates_from_settings.makefile_targets;
+        if targets.is_empty() {
+            makefile_targets.remove(&worktree);
+        } else {
+            makefile_targets.insert(worktree, targets);
+        }
+    }
+
+    /// Replaces the recipes of the worktree's justfile.
+    pub(crate) fn update_justfile_recipes(
+        &mut self,
+        worktree: WorktreeId,
+        recipes: Vec<TaskTemplate>,
+    ) {
+        let justfile_recipes = &mut self.templates_from_settings.justfile_recipes;
+        if recipes.is_empty() {
+            justfile_recipes.remove(&worktree);
+        } else {
+            justfile_recipes.insert(worktree, recipes);
+        }
+    }
+
     /// Updates in-memory task metadata from the JSON string given.
     /// Will fail if the JSON is not a valid array of objects, but will continue if any object will not parse into a [`TaskTemplate`].
     ///
@@ -328,6 +789,41 @@ impl Inventory {
     }
 }
 
+/// The globs of the tasks hidden in the project settings of the worktree, if there are any.
+fn hidden_tasks(worktree: Option<WorktreeId>, cx: &AppContext) -> Option<GlobSet> {
+    let hidden = &ProjectSettings::get(
+        worktree.map(|worktree_id| SettingsLocation {
+            worktree_id,
+            path: Path::new(""),
+        }),
+        cx,
+    )
+    .hidden_tasks;
+    if hidden.is_empty() {
+        return None;
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in hidden {
+        if let Some(glob) = Glob::new(glob)
+            .with_context(|| format!("parsing hidden task glob {glob:?}"))
+            .log_err()
+        {
+            builder.add(glob);
+        }
+    }
+    builder.build().log_err()
+}
+
+/// Whether the task matches any of the hidden task globs, by its label or by its source name and label.
+/// Oneshot tasks are never hidden.
+fn is_hidden(hidden_tasks: &GlobSet, task_kind: &TaskSourceKind, template: &TaskTemplate) -> bool {
+    if task_kind == &TaskSourceKind::UserInput {
+        return false;
+    }
+    hidden_tasks.is_match(&template.label)
+        || hidden_tasks.is_match(format!("{}:{}", task_kind.source_name(), template.label))
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -351,12 +847,53 @@ fn task_lru_comparator(
         })
 }
 
+/// The tasks of the language's context provider, along with the ones defined in the language's settings,
+/// which replace the provided tasks with the same labels.
+fn language_templates(
+    language: &Arc<Language>,
+    file: Option<Arc<dyn File>>,
+    cx: &AppContext,
+) -> Vec<(TaskSourceKind, TaskTemplate)> {
+    let name = language.name();
+    let settings_templates = language_settings(Some(name.clone()), file.as_ref(), cx)
+        .tasks
+        .templates
+        .clone();
+    let provided_templates = language
+        .context_provider()
+        .and_then(|provider| provider.associated_tasks(file, cx))
+        .map(|templates| templates.0)
+        .unwrap_or_default();
+    let provided_kind = TaskSourceKind::Language {
+        name: name.0.clone(),
+    };
+    let settings_kind = TaskSourceKind::LanguageSettings { name: name.0 };
+    provided_templates
+        .into_iter()
+        .filter(|template| {
+            !settings_templates
+                .iter()
+                .any(|settings_template| settings_template.label == template.label)
+        })
+        .map(|template| (provided_kind.clone(), template))
+        .chain(
+            settings_templates
+                .into_iter()
+                .map(|template| (settings_kind.clone(), template)),
+        )
+        .collect()
+}
+
 fn task_source_kind_preference(kind: &TaskSourceKind) -> u32 {
     match kind {
-        TaskSourceKind::Language { .. } => 1,
+        TaskSourceKind::Language { .. } | TaskSourceKind::LanguageSettings { .. } => 1,
         TaskSourceKind::UserInput => 2,
         TaskSourceKind::Worktree { .. } => 3,
-        TaskSourceKind::AbsPath { .. } => 4,
+        TaskSourceKind::VsCode { .. } => 4,
+        TaskSourceKind::DetectedScript { .. } => 5,
+        TaskSourceKind::MakefileTarget { .. } => 6,
+        TaskSourceKind::JustfileRecipe { .. } => 7,
+        TaskSourceKind::AbsPath { .. } => 8,
     }
 }
 
@@ -515,6 +1052,20 @@ impl ContextProvider for BasicContextProvider {
             }
         }
 
+        let mut git_repository = None;
+        if let Some(file) = buffer.file() {
+            if let Some(worktree) = self
+                .worktree_store
+                .read(cx)
+                .worktree_for_id(file.worktree_id(cx), cx)
+            {
+                let (git_task_variables, repository) =
+                    git_variables(worktree.read(cx), file.path());
+                task_variables.extend(git_task_variables);
+                git_repository = repository;
+            }
+        }
+
         if let Some(path_as_string) = current_file {
             let path = Path::new(&path_as_string);
             if let Some(filename) = path.file_name().and_then(|f| f.to_str()) {
@@ -532,8 +1083,56 @@ impl ContextProvider for BasicContextProvider {
             task_variables.insert(VariableName::File, path_as_string);
         }
 
-        Task::ready(Ok(task_variables))
+        let Some(git_repository) = git_repository else {
+            return Task::ready(Ok(task_variables));
+        };
+        // Reading HEAD goes to the disk, so it's done off the main thread.
+        cx.background_executor().spawn(async move {
+            if let Some(head_sha) = git_repository.head_sha() {
+                task_variables.insert(
+                    VariableName::CommitSha,
+                    head_sha.chars().take(SHORT_COMMIT_SHA_LENGTH).collect(),
+                );
+            }
+            Ok(task_variables)
+        })
+    }
+}
+
+/// How many characters of the commit SHA [`VariableName::CommitSha`] has.
+const SHORT_COMMIT_SHA_LENGTH: usize = 7;
+
+/// The variables of the git repository that contains the worktree path given, if there is one, except for the commit.
+/// The repository to read the commit from is returned along with them, for the repositories of local worktrees.
+fn git_variables(
+    worktree: &Worktree,
+    path: &Path,
+) -> (TaskVariables, Option<Arc<dyn GitRepository>>) {
+    let mut task_variables = TaskVariables::default();
+    let Some(repository) = worktree.repository_for_path(path) else {
+        return (task_variables, None);
+    };
+    if let Some(branch) = repository.branch() {
+        task_variables.insert(VariableName::Branch, branch.to_string());
+    }
+    if let Some(work_directory) = repository.work_directory(worktree) {
+        // When the repository is in a parent directory of the worktree, its work directory is the worktree root,
+        // and the worktree root is relativized to its location in the repository.
+        if let Ok(location_in_repo) = repository.relativize(worktree, work_directory.as_ref()) {
+            let mut repo_root = worktree.abs_path().join(work_directory.as_ref());
+            for _ in location_in_repo.components() {
+                repo_root.pop();
+            }
+            task_variables.insert(
+                VariableName::RepoRoot,
+                repo_root.to_string_lossy().into_owned(),
+            );
+        }
     }
+    let git_repository = worktree
+        .as_local()
+        .and_then(|worktree| worktree.local_git_repo(path));
+    (task_variables, git_repository)
 }
 
 /// A ContextProvider that doesn't provide any task variables on it's own, though it has some associated tasks.
@@ -851,6 +1450,336 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_task_definition_offset() {
+        let tasks_json = r#"[
+            { "label": "build", "command": "cargo build" },
+            { "label": "test", "command": "cargo test", "depends_on": ["build"] }
+        ]"#;
+        let kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(1),
+            directory_in_worktree: PathBuf::from(".zed"),
+            id_base: "local worktree tasks from directory \".zed\"".into(),
+        };
+        assert_eq!(
+            kind.definition_paths(),
+            vec![PathBuf::from(".zed/tasks.json")]
+        );
+        let template = |label: &str| TaskTemplate {
+            label: label.to_string(),
+            ..TaskTemplate::default()
+        };
+        assert_eq!(
+            kind.definition_offset(&template("build"), tasks_json),
+            tasks_json.find(r#""build""#)
+        );
+        assert_eq!(
+            kind.definition_offset(&template("test"), tasks_json),
+            tasks_json.find(r#""test""#)
+        );
+        assert_eq!(kind.definition_offset(&template("lint"), tasks_json), None);
+    }
+
+    #[test]
+    fn test_pinned_tasks() {
+        let mut inventory = Inventory::default();
+        let worktree = Some(WorktreeId::from_usize(1));
+        let kind = TaskSourceKind::MakefileTarget {
+            id: WorktreeId::from_usize(1),
+        };
+        assert!(!inventory.is_pinned(worktree, &kind, "make all"));
+
+        assert!(inventory.toggle_pinned(worktree, &kind, "make all".to_string()));
+        assert!(inventory.is_pinned(worktree, &kind, "make all"));
+        assert!(!inventory.is_pinned(worktree, &kind, "make test"));
+        assert!(
+            !inventory.is_pinned(None, &kind, "make all"),
+            "Tasks should be pinned per worktree"
+        );
+
+        assert!(!inventory.toggle_pinned(worktree, &kind, "make all".to_string()));
+        assert!(!inventory.is_pinned(worktree, &kind, "make all"));
+
+        // Pins restored from an earlier session match the tasks of the worktree's new id.
+        let restored_worktree = Some(WorktreeId::from_usize(2));
+        let restored_kind = TaskSourceKind::MakefileTarget {
+            id: WorktreeId::from_usize(2),
+        };
+        inventory.restore_pinned_tasks(
+            restored_worktree,
+            [("make".to_string(), "make test".to_string())],
+        );
+        assert!(inventory.is_pinned(restored_worktree, &restored_kind, "make test"));
+        assert!(!inventory.is_pinned(worktree, &kind, "make test"));
+    }
+
+    #[test]
+    fn test_hidden_tasks() {
+        let mut builder = GlobSetBuilder::new();
+        for glob in ["cargo clean", "language:rust:*", "npm:lint*"] {
+            builder.add(Glob::new(glob).unwrap());
+        }
+        let hidden_tasks = builder.build().unwrap();
+        let template = |label: &str| TaskTemplate {
+            label: label.to_string(),
+            command: label.to_string(),
+            ..TaskTemplate::default()
+        };
+        let worktree = WorktreeId::from_usize(1);
+        let rust = TaskSourceKind::Language {
+            name: "Rust".into(),
+        };
+        let npm = TaskSourceKind::DetectedScript {
+            id: worktree,
+            package_manager: PackageManager::Npm,
+        };
+        let yarn = TaskSourceKind::DetectedScript {
+            id: worktree,
+            package_manager: PackageManager::Yarn,
+        };
+        let make = TaskSourceKind::MakefileTarget { id: worktree };
+
+        assert!(is_hidden(&hidden_tasks, &make, &template("cargo clean")));
+        assert!(is_hidden(&hidden_tasks, &rust, &template("cargo test")));
+        assert!(is_hidden(&hidden_tasks, &npm, &template("lint:fix")));
+        assert!(!is_hidden(&hidden_tasks, &yarn, &template("lint:fix")));
+        assert!(!is_hidden(&hidden_tasks, &make, &template("make test")));
+        assert!(
+            !is_hidden(
+                &hidden_tasks,
+                &TaskSourceKind::UserInput,
+                &template("cargo clean")
+            ),
+            "Oneshot tasks should never be disabled"
+        );
+    }
+
+    #[test]
+    fn test_group_defaults() {
+        let mut inventory = Inventory::default();
+        let worktree = Some(WorktreeId::from_usize(1));
+        let make = TaskSourceKind::MakefileTarget {
+            id: WorktreeId::from_usize(1),
+        };
+        let template = |label: &str, group: Option<TaskGroup>| TaskTemplate {
+            label: label.to_string(),
+            command: label.to_string(),
+            group,
+            ..TaskTemplate::default()
+        };
+        let tasks = vec![
+            (make.clone(), template("make lint", None)),
+            (make.clone(), template("make all", Some(TaskGroup::Build))),
+            (make.clone(), template("make test", Some(TaskGroup::Test))),
+            (
+                make.clone(),
+                template("make release", Some(TaskGroup::Build)),
+            ),
+        ];
+        let group_default_index = |inventory: &Inventory, worktree, group| {
+            inventory.group_default_index(
+                worktree,
+                group,
+                tasks.iter().map(|(kind, template)| (kind, template)),
+            )
+        };
+        assert_eq!(
+            group_default_index(&inventory, worktree, TaskGroup::Build),
+            Some(1),
+            "The first task of the group should be its default one"
+        );
+        assert_eq!(
+            group_default_index(&inventory, worktree, TaskGroup::Run),
+            None
+        );
+
+        inventory.set_group_default(
+            worktree,
+            TaskGroup::Build,
+            make.clone(),
+            "make release".to_string(),
+        );
+        assert_eq!(
+            group_default_index(&inventory, worktree, TaskGroup::Build),
+            Some(3)
+        );
+        assert_eq!(
+            group_default_index(&inventory, None, TaskGroup::Build),
+            Some(1),
+            "Group defaults should be set per worktree"
+        );
+        assert_eq!(
+            group_default_index(&inventory, worktree, TaskGroup::Test),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_clear_history() {
+        let mut inventory = Inventory::default();
+        let worktree = WorktreeId::from_usize(1);
+        let other_worktree = WorktreeId::from_usize(2);
+        let schedule = |inventory: &mut Inventory, kind: TaskSourceKind, label: &str, cwd: &str| {
+            let template = TaskTemplate {
+                label: label.to_string(),
+                command: label.to_string(),
+                ..TaskTemplate::default()
+            };
+            let task_context = TaskContext {
+                cwd: Some(PathBuf::from(cwd)),
+                ..TaskContext::default()
+            };
+            let task = template
+                .resolve_task(&kind.to_id_base(), &task_context)
+                .unwrap();
+            inventory.task_scheduled(kind, task);
+        };
+        schedule(
+            &mut inventory,
+            TaskSourceKind::MakefileTarget { id: worktree },
+            "make",
+            "/dir",
+        );
+        schedule(
+            &mut inventory,
+            TaskSourceKind::MakefileTarget { id: other_worktree },
+            "make",
+            "/other",
+        );
+        schedule(&mut inventory, TaskSourceKind::UserInput, "ls", "/dir/src");
+        schedule(&mut inventory, TaskSourceKind::UserInput, "pwd", "/other");
+
+        inventory.clear_history(Some((worktree, Path::new("/dir"))));
+        assert_eq!(
+            inventory
+                .last_scheduled_tasks
+                .iter()
+                .map(|(kind, task)| (kind.clone(), task.resolved_label.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    TaskSourceKind::MakefileTarget { id: other_worktree },
+                    "make"
+                ),
+                (TaskSourceKind::UserInput, "pwd"),
+            ],
+            "Only the tasks of the worktree and the ones spawned in its directory should be cleared"
+        );
+
+        inventory.clear_history(None);
+        assert_eq!(
+            inventory
+                .last_scheduled_tasks
+                .iter()
+                .map(|(kind, task)| (kind.clone(), task.resolved_label.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(
+                TaskSourceKind::MakefileTarget { id: other_worktree },
+                "make"
+            )],
+        );
+    }
+
+    #[test]
+    fn test_task_run_stats() {
+        let mut inventory = Inventory::default();
+        let kind = TaskSourceKind::MakefileTarget {
+            id: WorktreeId::from_usize(1),
+        };
+        let template = TaskTemplate {
+            label: "make test".to_string(),
+            command: "make".to_string(),
+            args: vec!["test".to_string()],
+            ..TaskTemplate::default()
+        };
+        let task = template
+            .resolve_task("make", &TaskContext::default())
+            .unwrap();
+        let task_id = task.id.clone();
+        inventory.task_finished(&task_id, Some(0), true, Duration::from_secs(1));
+        assert_eq!(
+            inventory.run_stats(&kind, "make test"),
+            None,
+            "Runs of the tasks not in the history should not be recorded"
+        );
+
+        inventory.task_scheduled(kind.clone(), task);
+        inventory.task_finished(&task_id, Some(0), true, Duration::from_secs(1));
+        inventory.task_finished(&task_id, Some(2), false, Duration::from_secs(3));
+        assert_eq!(
+            inventory.run_stats(&kind, "make test"),
+            Some(&TaskRunStats {
+                run_count: 2,
+                last_duration: Duration::from_secs(3),
+                last_success: false,
+                last_exit_code: Some(2),
+            })
+        );
+        assert_eq!(inventory.run_stats(&kind, "make all"), None);
+        assert_eq!(
+            inventory.history_run_stats(&task_id),
+            inventory.run_stats(&kind, "make test"),
+        );
+
+        let other_task = template
+            .resolve_task(
+                "make",
+                &TaskContext {
+                    task_variables: TaskVariables::from_iter([(
+                        VariableName::File,
+                        "/dir/Makefile".to_string(),
+                    )]),
+                    ..TaskContext::default()
+                },
+            )
+            .unwrap();
+        let other_task_id = other_task.id.clone();
+        inventory.task_scheduled(kind.clone(), other_task);
+        inventory.task_finished(&other_task_id, Some(0), true, Duration::from_secs(2));
+        assert_eq!(
+            inventory
+                .run_stats(&kind, "make test")
+                .map(|stats| stats.run_count),
+            Some(3),
+            "Runs of all resolutions should be counted for the template"
+        );
+        assert_eq!(
+            inventory.history_run_stats(&other_task_id),
+            Some(&TaskRunStats {
+                run_count: 1,
+                last_duration: Duration::from_secs(2),
+                last_success: true,
+                last_exit_code: Some(0),
+            }),
+            "History entries should only count their own runs"
+        );
+
+        inventory.delete_previously_used(&task_id);
+        assert_eq!(inventory.history_run_stats(&task_id), None);
+
+        // Entries dropped from the full history take their statistics with them,
+        // unless the task is still in the history.
+        let task = template
+            .resolve_task("make", &TaskContext::default())
+            .unwrap();
+        inventory.task_scheduled(kind.clone(), task);
+        inventory.task_finished(&task_id, Some(0), true, Duration::from_secs(1));
+        let other_task = inventory
+            .last_scheduled_task(Some(&other_task_id))
+            .unwrap()
+            .1;
+        for _ in 0..MAX_SCHEDULED_TASKS {
+            inventory.task_scheduled(kind.clone(), other_task.clone());
+        }
+        assert_eq!(inventory.history_run_stats(&task_id), None);
+        assert_eq!(
+            inventory
+                .history_run_stats(&other_task_id)
+                .map(|stats| stats.run_count),
+            Some(1)
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
@@ -1,23 +1,39 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use collections::HashMap;
 use fs::Fs;
 use futures::StreamExt as _;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use gpui::{AppContext, AsyncAppContext, EventEmitter, Model, ModelContext, Task, WeakModel};
 use language::{
     proto::{deserialize_anchor, serialize_anchor},
     ContextProvider as _, LanguageToolchainStore, Location,
 };
 use rpc::{proto, AnyProtoClient, TypedEnvelope};
-use settings::{watch_config_file, SettingsLocation};
-use task::{TaskContext, TaskVariables, VariableName};
+use settings::{watch_config_file, Settings as _, SettingsLocation};
+use task::{
+    SpawnInTerminal, TaskConcurrency, TaskContext, TaskId, TaskTemplate, TaskVariables, TaskWatch,
+    VariableName,
+};
+use terminal::{TaskStatus, Terminal};
 use text::BufferId;
 use util::ResultExt;
+use worktree::{UpdatedEntriesSet, Worktree, WorktreeId};
 
 use crate::{
-    buffer_store::BufferStore, worktree_store::WorktreeStore, BasicContextProvider, Inventory,
-    ProjectEnvironment,
+    buffer_store::BufferStore,
+    detected_tasks::{
+        is_justfile, is_makefile, justfile_recipes, makefile_targets, package_json_scripts,
+        PackageManager, JUSTFILES, MAKEFILES, PACKAGE_JSON,
+    },
+    project_settings::ProjectSettings,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+    BasicContextProvider, Inventory, ProjectEnvironment, ProjectPath,
 };
 
 #[expect(clippy::large_enum_variant)]
@@ -28,13 +44,79 @@ pub enum TaskStore {
 
 pub struct StoreState {
     mode: StoreMode,
+    fs: Arc<dyn Fs>,
     task_inventory: Model<Inventory>,
     buffer_store: WeakModel<BufferStore>,
     worktree_store: Model<WorktreeStore>,
     toolchain_store: Arc<dyn LanguageToolchainStore>,
+    running_tasks: HashMap<TaskId, RunningTask>,
+    watched_tasks: HashMap<TaskId, WatchedTask>,
     _global_task_config_watcher: Task<()>,
 }
 
+/// The exit code of shells, and of most programs, interrupted with ctrl-c.
+const SIGINT_EXIT_CODE: i32 = 130;
+
+/// A task that finished running in a terminal of this project.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinishedTask {
+    pub task_id: TaskId,
+    pub label: String,
+    /// The exit code of the task's process, if it reported one.
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    /// Whether the task was stopped rather than finishing on its own: killed by Zed, as when a
+    /// watched task restarts, or interrupted with ctrl-c.
+    pub cancelled: bool,
+    pub duration: Duration,
+    /// Whether the terminal the task ran in was focused when it finished.
+    pub terminal_focused: bool,
+}
+
+/// A task in watch mode, restarted in its terminal when the files it watches change.
+struct WatchedTask {
+    spawn_in_terminal: SpawnInTerminal,
+    paths: WatchedPaths,
+    debounce: Duration,
+    /// The terminal the task was last spawned in, the task is watched for as long as it is open.
+    terminal: Option<WeakModel<Terminal>>,
+    pending_restart: Option<Task<()>>,
+}
+
+/// The files a task in watch mode watches.
+struct WatchedPaths {
+    /// The worktree the globs are relative to.
+    worktree_id: WorktreeId,
+    /// Matched against whole paths, `*` doesn't match across directories.
+    globs: GlobSet,
+}
+
+impl WatchedPaths {
+    fn new(worktree_id: WorktreeId, globs: &[String]) -> Result<Self, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            builder.add(GlobBuilder::new(glob).literal_separator(true).build()?);
+        }
+        Ok(Self {
+            worktree_id,
+            globs: builder.build()?,
+        })
+    }
+
+    /// Whether any of the paths changed in the worktree are watched.
+    fn any_changed(&self, worktree_id: WorktreeId, changes: &UpdatedEntriesSet) -> bool {
+        self.worktree_id == worktree_id
+            && changes.iter().any(|(path, _, _)| self.globs.is_match(path))
+    }
+}
+
+/// A task spawned in a terminal of this project, that did not finish yet.
+#[derive(Clone)]
+pub struct RunningTask {
+    pub terminal: WeakModel<Terminal>,
+    pub started_at: Instant,
+}
+
 enum StoreMode {
     Local {
         downstream_client: Option<(AnyProtoClient, u64)>,
@@ -150,6 +232,11 @@ impl TaskStore {
                 .into_iter()
                 .map(|(variable_name, variable_value)| (variable_name.to_string(), variable_value))
                 .collect(),
+            os: Some(
+                task_context
+                    .os
+                    .unwrap_or_else(|| std::env::consts::OS.to_string()),
+            ),
         })
     }
 
@@ -161,6 +248,63 @@ impl TaskStore {
         environment: Model<ProjectEnvironment>,
         cx: &mut ModelContext<'_, Self>,
     ) -> Self {
+        cx.subscribe(&worktree_store, {
+            let fs = fs.clone();
+            move |task_store, _, event, cx| match event {
+                WorktreeStoreEvent::WorktreeAdded(worktree) => {
+                    let fs = fs.clone();
+                    cx.subscribe(worktree, move |task_store, worktree, event, cx| {
+                        if let worktree::Event::UpdatedEntries(changes) = event {
+                            task_store.restart_watched_tasks(worktree.read(cx).id(), changes, cx);
+                            if changes
+                                .iter()
+                                .any(|(path, _, _)| PackageManager::affects_scripts(path))
+                            {
+                                task_store.detect_package_json_scripts(
+                                    fs.clone(),
+                                    worktree.clone(),
+                                    cx,
+                                );
+                            }
+                            if changes.iter().any(|(path, _, _)| is_makefile(path)) {
+                                task_store.detect_build_file_tasks(
+                                    fs.clone(),
+                                    worktree.clone(),
+                                    &MAKEFILES,
+                                    makefile_targets,
+                                    Inventory::update_makefile_targets,
+                                    cx,
+                                );
+                            }
+                            if changes.iter().any(|(path, _, _)| is_justfile(path)) {
+                                task_store.detect_build_file_tasks(
+                                    fs.clone(),
+                                    worktree,
+                                    &JUSTFILES,
+                                    justfile_recipes,
+                                    Inventory::update_justfile_recipes,
+                                    cx,
+                                );
+                            }
+                        }
+                    })
+                    .detach();
+                }
+                WorktreeStoreEvent::WorktreeRemoved(_, worktree_id) => {
+                    if let Some(task_inventory) = task_store.task_inventory() {
+                        task_inventory.update(cx, |inventory, cx| {
+                            inventory.update_detected_scripts(*worktree_id, None);
+                            inventory.update_makefile_targets(*worktree_id, Vec::new());
+                            inventory.update_justfile_recipes(*worktree_id, Vec::new());
+                            cx.notify();
+                        });
+                    }
+                }
+                _ => {}
+            }
+        })
+        .detach();
+
         Self::Functional(StoreState {
             mode: StoreMode::Local {
                 downstream_client: None,
@@ -170,7 +314,13 @@ impl TaskStore {
             buffer_store,
             toolchain_store,
             worktree_store,
-            _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(fs, cx),
+            running_tasks: HashMap::default(),
+            watched_tasks: HashMap::default(),
+            _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(
+                fs.clone(),
+                cx,
+            ),
+            fs,
         })
     }
 
@@ -183,6 +333,19 @@ impl TaskStore {
         project_id: u64,
         cx: &mut ModelContext<'_, Self>,
     ) -> Self {
+        // Remote worktrees receive the file changes of the host, which restart the watched tasks too.
+        cx.subscribe(&worktree_store, |_, _, event, cx| {
+            if let WorktreeStoreEvent::WorktreeAdded(worktree) = event {
+                cx.subscribe(worktree, |task_store, worktree, event, cx| {
+                    if let worktree::Event::UpdatedEntries(changes) = event {
+                        task_store.restart_watched_tasks(worktree.read(cx).id(), changes, cx);
+                    }
+                })
+                .detach();
+            }
+        })
+        .detach();
+
         Self::Functional(StoreState {
             mode: StoreMode::Remote {
                 upstream_client,
@@ -192,7 +355,13 @@ impl TaskStore {
             buffer_store,
             toolchain_store,
             worktree_store,
-            _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(fs, cx),
+            running_tasks: HashMap::default(),
+            watched_tasks: HashMap::default(),
+            _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(
+                fs.clone(),
+                cx,
+            ),
+            fs,
         })
     }
 
@@ -205,6 +374,7 @@ impl TaskStore {
         match self {
             TaskStore::Functional(state) => match &state.mode {
                 StoreMode::Local { environment, .. } => local_task_context_for_location(
+                    state.fs.clone(),
                     state.worktree_store.clone(),
                     state.toolchain_store.clone(),
                     environment.clone(),
@@ -229,6 +399,70 @@ impl TaskStore {
         }
     }
 
+    /// Reads the variables of the env files at the given absolute paths, from the machine the project is on.
+    /// The files that do not exist or cannot be read have no variables.
+    pub fn load_env_files(
+        &self,
+        paths: Vec<PathBuf>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<HashMap<PathBuf, HashMap<String, String>>> {
+        let TaskStore::Functional(state) = self else {
+            return Task::ready(HashMap::default());
+        };
+        match &state.mode {
+            StoreMode::Local { .. } => {
+                let fs = state.fs.clone();
+                cx.background_executor()
+                    .spawn(async move { load_env_files(fs.as_ref(), paths).await })
+            }
+            StoreMode::Remote { .. } => {
+                let buffer_store = state.buffer_store.upgrade();
+                let buffers = paths
+                    .into_iter()
+                    .map(|path| {
+                        let project_path =
+                            state.worktree_store.read(cx).find_worktree(&path, cx).map(
+                                |(worktree, relative_path)| ProjectPath {
+                                    worktree_id: worktree.read(cx).id(),
+                                    path: relative_path.into(),
+                                },
+                            );
+                        let buffer = project_path.zip(buffer_store.as_ref()).map(
+                            |(project_path, buffer_store)| {
+                                buffer_store.update(cx, |buffer_store, cx| {
+                                    buffer_store.open_buffer(project_path, cx)
+                                })
+                            },
+                        );
+                        (path, buffer)
+                    })
+                    .collect::<Vec<_>>();
+                cx.spawn(|_, cx| async move {
+                    let mut env_files = HashMap::default();
+                    for (path, buffer) in buffers {
+                        let variables = match buffer {
+                            Some(buffer) => buffer
+                                .await
+                                .with_context(|| format!("opening env file {path:?}"))
+                                .log_err()
+                                .and_then(|buffer| {
+                                    buffer
+                                        .read_with(&cx, |buffer, _| {
+                                            task::parse_env_file(&buffer.text())
+                                        })
+                                        .ok()
+                                })
+                                .unwrap_or_default(),
+                            None => HashMap::default(),
+                        };
+                        env_files.insert(path, variables);
+                    }
+                    env_files
+                })
+            }
+        }
+    }
+
     pub fn task_inventory(&self) -> Option<&Model<Inventory>> {
         match self {
             TaskStore::Functional(state) => Some(&state.task_inventory),
@@ -236,6 +470,264 @@ impl TaskStore {
         }
     }
 
+    /// Tracks the task with the given id as running in the terminal, until the task finishes
+    /// or the terminal is closed.
+    pub fn task_terminal_spawned(
+        &mut self,
+        task_id: TaskId,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let terminal_id = terminal.entity_id();
+        if let Some(watched_task) = state.watched_tasks.get_mut(&task_id) {
+            watched_task.terminal = Some(terminal.downgrade());
+            let task_id = task_id.clone();
+            cx.observe_release(terminal, move |task_store, _, cx| {
+                let TaskStore::Functional(state) = task_store else {
+                    return;
+                };
+                // A rerun of the task replaces its terminal, and keeps watching in the new one.
+                if state
+                    .watched_tasks
+                    .get(&task_id)
+                    .is_some_and(|watched_task| {
+                        watched_task
+                            .terminal
+                            .as_ref()
+                            .is_some_and(|terminal| terminal.entity_id() == terminal_id)
+                    })
+                {
+                    state.watched_tasks.remove(&task_id);
+                    cx.notify();
+                }
+            })
+            .detach();
+        }
+        let task_completed = terminal.read(cx).wait_for_completed_task(cx);
+        let terminal = terminal.downgrade();
+        let started_at = Instant::now();
+        state.running_tasks.insert(
+            task_id.clone(),
+            RunningTask {
+                terminal: terminal.clone(),
+                started_at,
+            },
+        );
+        cx.notify();
+        cx.spawn(|task_store, mut cx| async move {
+            task_completed.await;
+            task_store.update(&mut cx, |task_store, cx| {
+                let TaskStore::Functional(state) = task_store else {
+                    return;
+                };
+                // The task could have been rerun in another terminal meanwhile.
+                if state
+                    .running_tasks
+                    .get(&task_id)
+                    .is_some_and(|running_task| running_task.terminal.entity_id() == terminal_id)
+                {
+                    state.running_tasks.remove(&task_id);
+                    cx.notify();
+                }
+
+                // Nothing to report for the tasks whose terminal got closed.
+                let Some(terminal) = terminal.upgrade() else {
+                    return;
+                };
+                let terminal = terminal.read(cx);
+                let Some(task) = terminal.task() else {
+                    return;
+                };
+                let finished_task = FinishedTask {
+                    task_id,
+                    label: task.full_label.clone(),
+                    exit_code: task.exit_code,
+                    success: task.status == TaskStatus::Completed { success: true },
+                    cancelled: task.killed || task.exit_code == Some(SIGINT_EXIT_CODE),
+                    duration: started_at.elapsed(),
+                    terminal_focused: terminal.has_focus(),
+                };
+                state.task_inventory.update(cx, |inventory, _| {
+                    inventory.task_finished(
+                        &finished_task.task_id,
+                        finished_task.exit_code,
+                        finished_task.success,
+                        finished_task.duration,
+                    )
+                });
+                cx.emit(crate::Event::TaskFinished(finished_task));
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn running_task(&self, task_id: &TaskId) -> Option<&RunningTask> {
+        match self {
+            TaskStore::Functional(state) => state.running_tasks.get(task_id),
+            TaskStore::Noop => None,
+        }
+    }
+
+    /// The tasks running in the terminals of this project, by their ids.
+    pub fn running_tasks(&self) -> impl Iterator<Item = (&TaskId, &RunningTask)> {
+        match self {
+            TaskStore::Functional(state) => Some(&state.running_tasks),
+            TaskStore::Noop => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Kills the process of the running task with the given id, leaving its terminal open.
+    pub fn kill_task(&self, task_id: &TaskId, cx: &mut AppContext) {
+        if let Some(terminal) = self
+            .running_task(task_id)
+            .and_then(|running_task| running_task.terminal.upgrade())
+        {
+            terminal.update(cx, |terminal, _| terminal.kill_active_task());
+        }
+    }
+
+    /// Applies the concurrency policy of a task about to be spawned again to its runs in progress,
+    /// returns whether the task can be spawned.
+    pub fn apply_concurrency(
+        &self,
+        task_ids: &[TaskId],
+        concurrency: TaskConcurrency,
+        cx: &mut AppContext,
+    ) -> bool {
+        let running_task_ids = task_ids
+            .iter()
+            .filter(|task_id| self.running_task(task_id).is_some())
+            .collect::<Vec<_>>();
+        if running_task_ids.is_empty() {
+            return true;
+        }
+        match concurrency {
+            // The terminal panel waits for the running task before spawning the new one.
+            TaskConcurrency::Queue => true,
+            TaskConcurrency::Singleton => false,
+            TaskConcurrency::KillPrevious => {
+                for task_id in running_task_ids {
+                    self.kill_task(task_id, cx);
+                }
+                true
+            }
+        }
+    }
+
+    /// Starts watching the files of the task about to be spawned, to restart it when they change.
+    ///
+    /// The globs are matched against the paths in the task's worktree, which is the worktree the
+    /// task is defined in, or else the one the task runs in.
+    pub fn watch_task(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        worktree_id: Option<WorktreeId>,
+        watch: &TaskWatch,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let Some(worktree_id) = worktree_id.or_else(|| {
+            let cwd = spawn_in_terminal.cwd.as_ref()?;
+            let (worktree, _) = state.worktree_store.read(cx).find_worktree(cwd, cx)?;
+            Some(worktree.read(cx).id())
+        }) else {
+            log::warn!(
+                "not watching task {}, as it doesn't run in a worktree",
+                spawn_in_terminal.label
+            );
+            return;
+        };
+        let Some(paths) = WatchedPaths::new(worktree_id, &watch.globs)
+            .with_context(|| format!("invalid watch globs of task {}", spawn_in_terminal.label))
+            .log_err()
+        else {
+            return;
+        };
+        let terminal = state
+            .watched_tasks
+            .remove(&spawn_in_terminal.id)
+            .and_then(|watched_task| watched_task.terminal);
+        state.watched_tasks.insert(
+            spawn_in_terminal.id.clone(),
+            WatchedTask {
+                spawn_in_terminal,
+                paths,
+                debounce: Duration::from_millis(watch.debounce_ms),
+                terminal,
+                pending_restart: None,
+            },
+        );
+        cx.notify();
+    }
+
+    /// Whether the task with the given id is restarted on changes of the files it watches.
+    pub fn is_watched(&self, task_id: &TaskId) -> bool {
+        match self {
+            TaskStore::Functional(state) => state
+                .watched_tasks
+                .get(task_id)
+                .is_some_and(|watched_task| watched_task.terminal.is_some()),
+            TaskStore::Noop => false,
+        }
+    }
+
+    /// Restarts the watched tasks that watch any of the paths changed in the worktree, once no
+    /// more changes come for their debounce time.
+    fn restart_watched_tasks(
+        &mut self,
+        worktree_id: WorktreeId,
+        changes: &UpdatedEntriesSet,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        for (task_id, watched_task) in &mut state.watched_tasks {
+            if watched_task.terminal.is_none()
+                || !watched_task.paths.any_changed(worktree_id, changes)
+            {
+                continue;
+            }
+            let task_id = task_id.clone();
+            let debounce = watched_task.debounce;
+            watched_task.pending_restart = Some(cx.spawn(|task_store, mut cx| async move {
+                cx.background_executor().timer(debounce).await;
+                task_store
+                    .update(&mut cx, |task_store, cx| {
+                        task_store.restart_watched_task(&task_id, cx)
+                    })
+                    .ok();
+            }));
+        }
+    }
+
+    fn restart_watched_task(&mut self, task_id: &TaskId, cx: &mut ModelContext<Self>) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let Some(watched_task) = state.watched_tasks.get_mut(task_id) else {
+            return;
+        };
+        watched_task.pending_restart = None;
+        let Some(terminal) = watched_task
+            .terminal
+            .as_ref()
+            .and_then(|terminal| terminal.upgrade())
+        else {
+            return;
+        };
+        let spawn_in_terminal = watched_task.spawn_in_terminal.clone();
+        terminal.update(cx, |terminal, _| terminal.kill_active_task());
+        cx.emit(crate::Event::RestartTask(Box::new(spawn_in_terminal)));
+    }
+
     pub fn shared(
         &mut self,
         remote_id: u64,
@@ -279,11 +771,145 @@ impl TaskStore {
             .map(|json| json.trim())
             .filter(|json| !json.is_empty());
 
-        task_inventory.update(cx, |inventory, _| {
-            inventory.update_file_based_tasks(location, raw_tasks_json)
+        task_inventory.update(cx, |inventory, cx| {
+            let result = inventory.update_file_based_tasks(location, raw_tasks_json);
+            cx.notify();
+            result
         })
     }
 
+    /// Re-reads the global tasks file and the tasks detected in the local worktrees (package.json scripts,
+    /// makefile targets and justfile recipes), regardless of whether their changes were noticed by the file watchers.
+    pub fn reload_tasks(&self, cx: &mut ModelContext<'_, Self>) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let fs = state.fs.clone();
+        if let StoreMode::Local { .. } = state.mode {
+            let worktrees = state
+                .worktree_store
+                .read(cx)
+                .worktrees()
+                .collect::<Vec<_>>();
+            for worktree in worktrees {
+                self.detect_package_json_scripts(fs.clone(), worktree.clone(), cx);
+                self.detect_build_file_tasks(
+                    fs.clone(),
+                    worktree.clone(),
+                    &MAKEFILES,
+                    makefile_targets,
+                    Inventory::update_makefile_targets,
+                    cx,
+                );
+                self.detect_build_file_tasks(
+                    fs.clone(),
+                    worktree,
+                    &JUSTFILES,
+                    justfile_recipes,
+                    Inventory::update_justfile_recipes,
+                    cx,
+                );
+            }
+        }
+        cx.spawn(move |task_store, mut cx| async move {
+            let tasks_file = paths::tasks_file();
+            let user_tasks_content = if fs.is_file(tasks_file).await {
+                fs.load(tasks_file)
+                    .await
+                    .with_context(|| format!("loading global tasks file {tasks_file:?}"))?
+            } else {
+                String::new()
+            };
+            task_store.update(&mut cx, |task_store, cx| {
+                let result = task_store.update_user_tasks(None, Some(&user_tasks_content), cx);
+                if let Err(err) = &result {
+                    cx.emit(crate::Event::Toast {
+                        notification_id: "load-user-tasks".into(),
+                        message: format!("Invalid global tasks file\n{err}"),
+                    });
+                }
+                result
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Reloads the scripts of the worktree's root package.json into the inventory,
+    /// to be run with the package manager of the lockfile next to it.
+    fn detect_package_json_scripts(
+        &self,
+        fs: Arc<dyn Fs>,
+        worktree: Model<Worktree>,
+        cx: &mut ModelContext<'_, Self>,
+    ) {
+        let Some(task_inventory) = self.task_inventory().cloned() else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let worktree_id = worktree.id();
+        let package_json_path = worktree
+            .entry_for_path(PACKAGE_JSON)
+            .and_then(|_| worktree.absolutize(Path::new(PACKAGE_JSON)).log_err());
+        let package_manager =
+            PackageManager::detect(|path| worktree.entry_for_path(path).is_some());
+        cx.spawn(move |_, mut cx| async move {
+            let scripts = match package_json_path {
+                Some(package_json_path) => fs
+                    .load(&package_json_path)
+                    .await
+                    .and_then(|content| package_json_scripts(&content, package_manager))
+                    .with_context(|| format!("detecting scripts in {package_json_path:?}"))
+                    .log_err()
+                    .map(|templates| (package_manager, templates)),
+                None => None,
+            };
+            task_inventory.update(&mut cx, |inventory, cx| {
+                inventory.update_detected_scripts(worktree_id, scripts);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Reloads the tasks parsed from the first of the build files named that is in the worktree's root
+    /// (e.g. the targets of its makefile) into the inventory.
+    fn detect_build_file_tasks(
+        &self,
+        fs: Arc<dyn Fs>,
+        worktree: Model<Worktree>,
+        file_names: &'static [&'static str],
+        parse: fn(&str) -> Vec<TaskTemplate>,
+        update_inventory: fn(&mut Inventory, WorktreeId, Vec<TaskTemplate>),
+        cx: &mut ModelContext<'_, Self>,
+    ) {
+        let Some(task_inventory) = self.task_inventory().cloned() else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let worktree_id = worktree.id();
+        let build_file_path = file_names
+            .iter()
+            .find(|file_name| worktree.entry_for_path(file_name).is_some())
+            .and_then(|file_name| worktree.absolutize(Path::new(file_name)).log_err());
+        cx.spawn(move |_, mut cx| async move {
+            let templates = match build_file_path {
+                Some(build_file_path) => fs
+                    .load(&build_file_path)
+                    .await
+                    .with_context(|| format!("loading {build_file_path:?}"))
+                    .log_err()
+                    .map(|content| parse(&content))
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            task_inventory.update(&mut cx, |inventory, cx| {
+                update_inventory(inventory, worktree_id, templates);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn subscribe_to_global_task_file_changes(
         fs: Arc<dyn Fs>,
         cx: &mut ModelContext<'_, Self>,
@@ -320,7 +946,30 @@ impl TaskStore {
     }
 }
 
+/// Reads the variables of the local env files at the given paths, skipping the files that do not exist.
+async fn load_env_files(
+    fs: &dyn Fs,
+    paths: Vec<PathBuf>,
+) -> HashMap<PathBuf, HashMap<String, String>> {
+    let mut env_files = HashMap::default();
+    for path in paths {
+        let variables = if fs.is_file(&path).await {
+            fs.load(&path)
+                .await
+                .with_context(|| format!("reading env file {path:?}"))
+                .log_err()
+                .map(|contents| task::parse_env_file(&contents))
+                .unwrap_or_default()
+        } else {
+            HashMap::default()
+        };
+        env_files.insert(path, variables);
+    }
+    env_files
+}
+
 fn local_task_context_for_location(
+    fs: Arc<dyn Fs>,
     worktree_store: Model<WorktreeStore>,
     toolchain_store: Arc<dyn LanguageToolchainStore>,
     environment: Model<ProjectEnvironment>,
@@ -332,15 +981,38 @@ fn local_task_context_for_location(
     let worktree_abs_path = worktree_id
         .and_then(|worktree_id| worktree_store.read(cx).worktree_for_id(worktree_id, cx))
         .and_then(|worktree| worktree.read(cx).root_dir());
+    let env_files = ProjectSettings::get(
+        worktree_id.map(|worktree_id| SettingsLocation {
+            worktree_id,
+            path: Path::new(""),
+        }),
+        cx,
+    )
+    .task_env_files
+    .clone();
 
     cx.spawn(|mut cx| async move {
         let worktree_abs_path = worktree_abs_path.clone();
-        let project_env = environment
+        let mut project_env = environment
             .update(&mut cx, |environment, cx| {
                 environment.get_environment(worktree_id, worktree_abs_path.clone(), cx)
             })
             .ok()?
             .await;
+        if !env_files.is_empty() {
+            let paths = env_files
+                .iter()
+                .map(|path| match &worktree_abs_path {
+                    Some(root) => root.join(path),
+                    None => PathBuf::from(path),
+                })
+                .collect::<Vec<_>>();
+            let mut env_file_variables = load_env_files(fs.as_ref(), paths.clone()).await;
+            let project_env = project_env.get_or_insert_with(HashMap::default);
+            for path in paths {
+                project_env.extend(env_file_variables.remove(&path).unwrap_or_default());
+            }
+        }
 
         let mut task_variables = cx
             .update(|cx| {
@@ -363,6 +1035,8 @@ fn local_task_context_for_location(
             project_env: project_env.unwrap_or_default(),
             cwd: worktree_abs_path.map(|p| p.to_path_buf()),
             task_variables,
+            command_outputs: HashMap::default(),
+            os: None,
         })
     })
 }
@@ -426,6 +1100,8 @@ fn remote_task_context_for_location(
                 )
                 .collect(),
             project_env: task_context.project_env.into_iter().collect(),
+            command_outputs: HashMap::default(),
+            os: task_context.os,
         })
     })
 }
@@ -475,3 +1151,64 @@ fn combine_task_variables(
         Ok(captured_variables)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use worktree::{PathChange, ProjectEntryId};
+
+    use super::*;
+
+    fn changes(paths: &[&str]) -> UpdatedEntriesSet {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(ix, path)| {
+                (
+                    Arc::from(Path::new(path)),
+                    ProjectEntryId::from_proto(ix as u64),
+                    PathChange::Updated,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_watched_paths() {
+        let worktree_id = WorktreeId::from_usize(1);
+        let other_worktree_id = WorktreeId::from_usize(2);
+        let paths = WatchedPaths::new(
+            worktree_id,
+            &["src/**/*.rs".to_string(), "Cargo.toml".to_string()],
+        )
+        .unwrap();
+
+        assert!(paths.any_changed(worktree_id, &changes(&["src/main.rs"])));
+        assert!(paths.any_changed(worktree_id, &changes(&["src/a/b/c.rs"])));
+        assert!(paths.any_changed(worktree_id, &changes(&["README.md", "Cargo.toml"])));
+        assert!(
+            !paths.any_changed(other_worktree_id, &changes(&["src/main.rs"])),
+            "changes in other worktrees are not watched"
+        );
+        assert!(!paths.any_changed(worktree_id, &changes(&["src/main.js"])));
+        assert!(
+            !paths.any_changed(worktree_id, &changes(&["crates/a/Cargo.toml"])),
+            "globs match whole paths"
+        );
+        assert!(
+            !paths.any_changed(worktree_id, &changes(&["tests/src/main.rs"])),
+            "globs match whole paths"
+        );
+        assert!(!paths.any_changed(worktree_id, &changes(&[])));
+
+        let paths = WatchedPaths::new(worktree_id, &["src/*.rs".to_string()]).unwrap();
+        assert!(paths.any_changed(worktree_id, &changes(&["src/main.rs"])));
+        assert!(
+            !paths.any_changed(worktree_id, &changes(&["src/bin/main.rs"])),
+            "`*` doesn't match across directories"
+        );
+
+        assert!(WatchedPaths::new(worktree_id, &["src/[".to_string()]).is_err());
+    }
+}
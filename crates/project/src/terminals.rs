@@ -1,9 +1,11 @@
 use crate::Project;
 use anyhow::{Context as _, Result};
 use collections::HashMap;
+use futures::channel::oneshot;
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
 use language::LanguageName;
+use regex::Regex;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -13,7 +15,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, SpawnInTerminal};
+use task::{Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -25,6 +27,8 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// Whoever waits for the next terminal of a task to finish, by the task's id.
+    pub(crate) task_completion_listeners: HashMap<TaskId, Vec<oneshot::Sender<bool>>>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -122,6 +126,29 @@ impl Project {
         })
     }
 
+    /// Resolves once the next terminal started for the task with the given id
+    /// finishes, to whether the task succeeded.
+    pub fn wait_for_task(&mut self, task_id: TaskId) -> oneshot::Receiver<bool> {
+        let (tx, rx) = oneshot::channel();
+        let listeners = self
+            .terminals
+            .task_completion_listeners
+            .entry(task_id)
+            .or_default();
+        listeners.retain(|listener| !listener.is_canceled());
+        listeners.push(tx);
+        rx
+    }
+
+    /// Stops waiting for the task with the given id if no terminal was started for it yet,
+    /// canceling the receivers [`Self::wait_for_task`] returned. Returns whether any were waiting.
+    pub fn stop_waiting_for_task(&mut self, task_id: &TaskId) -> bool {
+        self.terminals
+            .task_completion_listeners
+            .remove(task_id)
+            .is_some()
+    }
+
     pub fn create_terminal_with_venv(
         &mut self,
         kind: TerminalKind,
@@ -130,6 +157,10 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Result<Model<Terminal>> {
         let this = &mut *self;
+        let task_id = match &kind {
+            TerminalKind::Shell(_) => None,
+            TerminalKind::Task(spawn_task) => Some(spawn_task.id.clone()),
+        };
         let path: Option<Arc<Path>> = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| Arc::from(path.as_ref())),
             TerminalKind::Task(spawn_task) => {
@@ -151,6 +182,10 @@ impl Project {
                 });
             }
         }
+        let toolchains = settings_location
+            .as_ref()
+            .map(|location| this.active_toolchain_environments(location.worktree_id, cx))
+            .unwrap_or_default();
         let settings = TerminalSettings::get(settings_location, cx).clone();
 
         let (completion_tx, completion_rx) = bounded(1);
@@ -161,6 +196,10 @@ impl Project {
             .read(cx)
             .get_cli_environment()
             .unwrap_or_default();
+        // Activate the toolchains selected for the worktree.
+        for (_, toolchain_env) in &toolchains {
+            activate_toolchain_environment(&mut env, toolchain_env);
+        }
         // Then extend it with the explicit env variables from the settings, so they take
         // precedence.
         env.extend(settings.env.clone());
@@ -214,8 +253,19 @@ impl Project {
                     command_label: spawn_task.command_label,
                     hide: spawn_task.hide,
                     status: TaskStatus::Running,
+                    exit_code: None,
+                    killed: false,
                     show_summary: spawn_task.show_summary,
                     show_command: spawn_task.show_command,
+                    file_link_patterns: spawn_task
+                        .file_link_patterns
+                        .iter()
+                        .filter_map(|pattern| {
+                            Regex::new(pattern)
+                                .with_context(|| format!("parsing file link pattern {pattern:?}"))
+                                .log_err()
+                        })
+                        .collect(),
                     completion_rx,
                 });
 
@@ -282,7 +332,16 @@ impl Project {
             cx,
         )
         .map(|builder| {
-            let terminal_handle = cx.new_model(|cx| builder.subscribe(cx));
+            let terminal_handle = cx.new_model(|cx| {
+                let mut terminal = builder.subscribe(cx);
+                terminal.set_active_toolchains(
+                    toolchains
+                        .into_iter()
+                        .map(|(toolchain, _)| toolchain.name)
+                        .collect(),
+                );
+                terminal
+            });
 
             this.terminals
                 .local_handles
@@ -302,9 +361,35 @@ impl Project {
             })
             .detach();
 
+            if let Some(task_id) = task_id.clone() {
+                this.task_store.update(cx, |task_store, cx| {
+                    task_store.task_terminal_spawned(task_id, &terminal_handle, cx)
+                });
+            }
+
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+
+            if let Some(listeners) = task_id
+                .and_then(|task_id| this.terminals.task_completion_listeners.remove(&task_id))
+            {
+                let task_completed = terminal_handle.read(cx).wait_for_completed_task(cx);
+                let terminal = terminal_handle.downgrade();
+                cx.spawn(|_, cx| async move {
+                    task_completed.await;
+                    let succeeded = terminal
+                        .read_with(&cx, |terminal, _| {
+                            terminal.task().map(|task| task.status)
+                                == Some(TaskStatus::Completed { success: true })
+                        })
+                        .unwrap_or(false);
+                    for listener in listeners {
+                        listener.send(succeeded).ok();
+                    }
+                })
+                .detach();
+            }
             terminal_handle
         })
     }
@@ -451,6 +536,80 @@ impl Project {
     pub fn local_terminal_handles(&self) -> &Vec<WeakModel<terminal::Terminal>> {
         &self.terminals.local_handles
     }
+
+    /// Runs a shell command to completion where the project's terminals would run it, over SSH for remote projects,
+    /// returning its output without the trailing whitespace.
+    pub fn run_shell_command(
+        &self,
+        command: String,
+        cwd: Option<PathBuf>,
+        env: HashMap<String, String>,
+        cx: &AppContext,
+    ) -> Task<Result<String>> {
+        if self.is_via_collab() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "cannot run commands in a project shared by another user"
+            )));
+        }
+        let mut process = match self.ssh_details(cx) {
+            Some((_, ssh_command)) => {
+                let mut script = match &cwd {
+                    Some(cwd) => format!("cd {cwd:?}; "),
+                    None => String::new(),
+                };
+                for (key, value) in &env {
+                    if let Some((key, value)) =
+                        shlex::try_quote(key).ok().zip(shlex::try_quote(value).ok())
+                    {
+                        script.push_str(&format!("{key}={value} "));
+                    }
+                }
+                script.push_str(&command);
+                let script = match shlex::try_quote(&script) {
+                    Ok(script) => script.into_owned(),
+                    Err(error) => return Task::ready(Err(error.into())),
+                };
+                let mut process = util::command::new_smol_command("ssh");
+                process
+                    .args(&ssh_command.arguments)
+                    .arg(format!("sh -c {script}"));
+                process
+            }
+            None => {
+                #[cfg(not(target_os = "windows"))]
+                let mut process = {
+                    let mut process = util::command::new_smol_command("sh");
+                    process.args(["-c", &command]);
+                    process
+                };
+                #[cfg(target_os = "windows")]
+                let mut process = {
+                    let mut process = util::command::new_smol_command("cmd");
+                    process.args(["/C", &command]);
+                    process
+                };
+                if let Some(cwd) = &cwd {
+                    process.current_dir(cwd);
+                }
+                process.envs(env);
+                process
+            }
+        };
+        cx.background_executor().spawn(async move {
+            let output = process
+                .output()
+                .await
+                .with_context(|| format!("running command `{command}`"))?;
+            anyhow::ensure!(
+                output.status.success(),
+                "command `{command}` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string())
+        })
+    }
 }
 
 fn wrap_for_ssh(
@@ -508,10 +667,28 @@ fn wrap_for_ssh(
     (program, args)
 }
 
+/// Adds the environment a toolchain is activated with to `env`, with the directories in its `PATH`
+/// put in front of the ones already there.
+pub fn activate_toolchain_environment(
+    env: &mut HashMap<String, String>,
+    toolchain_env: &HashMap<String, String>,
+) {
+    for (name, value) in toolchain_env {
+        if name == "PATH" {
+            for path in env::split_paths(value).collect::<Vec<_>>().iter().rev() {
+                add_environment_path(env, path).log_err();
+            }
+        } else {
+            env.insert(name.clone(), value.clone());
+        }
+    }
+}
+
 fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> Result<()> {
     let mut env_paths = vec![new_path.to_path_buf()];
     if let Some(path) = env.get("PATH").or(env::var("PATH").ok().as_ref()) {
         let mut paths = std::env::split_paths(&path).collect::<Vec<_>>();
+        paths.retain(|path| path != new_path);
         env_paths.append(&mut paths);
     }
 
@@ -560,4 +737,48 @@ mod tests {
         }
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
+
+    #[test]
+    fn test_add_environment_path_moves_existing_entry_to_front() {
+        let tmp_path = std::path::PathBuf::from("/tmp/new");
+        let mut env = HashMap::default();
+        let (old_path, new_path) = if cfg!(windows) {
+            ("/usr/bin;/tmp/new", "/tmp/new;/usr/bin")
+        } else {
+            ("/usr/bin:/tmp/new", "/tmp/new:/usr/bin")
+        };
+        env.insert("PATH".to_string(), old_path.to_string());
+
+        super::add_environment_path(&mut env, &tmp_path).unwrap();
+        assert_eq!(env.get("PATH").unwrap(), new_path);
+    }
+
+    #[test]
+    fn test_activate_toolchain_environment() {
+        let (old_path, toolchain_path, new_path) = if cfg!(windows) {
+            (
+                "/usr/bin;/toolchain/bin",
+                "/toolchain/bin;/toolchain/tools",
+                "/toolchain/bin;/toolchain/tools;/usr/bin",
+            )
+        } else {
+            (
+                "/usr/bin:/toolchain/bin",
+                "/toolchain/bin:/toolchain/tools",
+                "/toolchain/bin:/toolchain/tools:/usr/bin",
+            )
+        };
+        let mut env = HashMap::from_iter([
+            ("PATH".to_string(), old_path.to_string()),
+            ("RUSTUP_TOOLCHAIN".to_string(), "stable".to_string()),
+        ]);
+        let toolchain_env = HashMap::from_iter([
+            ("PATH".to_string(), toolchain_path.to_string()),
+            ("RUSTUP_TOOLCHAIN".to_string(), "1.81".to_string()),
+        ]);
+
+        super::activate_toolchain_environment(&mut env, &toolchain_env);
+        assert_eq!(env.get("PATH").unwrap(), new_path);
+        assert_eq!(env.get("RUSTUP_TOOLCHAIN").unwrap(), "1.81");
+    }
 }
@@ -1,14 +1,17 @@
 use std::{str::FromStr, sync::Arc};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
 
 use async_trait::async_trait;
-use collections::BTreeMap;
+use collections::{BTreeMap, HashMap};
 use gpui::{
     AppContext, AsyncAppContext, Context, EventEmitter, Model, ModelContext, Subscription, Task,
     WeakModel,
 };
-use language::{LanguageName, LanguageRegistry, LanguageToolchainStore, Toolchain, ToolchainList};
+use language::{
+    LanguageName, LanguageRegistry, LanguageToolchainStore, Toolchain, ToolchainDetails,
+    ToolchainList,
+};
 use rpc::{proto, AnyProtoClient, TypedEnvelope};
 use settings::WorktreeId;
 use util::ResultExt as _;
@@ -40,6 +43,7 @@ impl ToolchainStore {
             worktree_store,
             project_environment,
             active_toolchains: Default::default(),
+            activation_environments: Default::default(),
         });
         let subscription = cx.subscribe(&model, |_, _, e: &ToolchainStoreEvent, cx| {
             cx.emit(e.clone())
@@ -68,6 +72,46 @@ impl ToolchainStore {
             }
         }
     }
+    pub(crate) fn install_toolchain(
+        &self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &AppContext,
+    ) -> Task<Result<Toolchain>> {
+        match &self.0 {
+            ToolchainStoreInner::Local(local, _) => {
+                local.read(cx).install_toolchain(worktree_id, toolchain, cx)
+            }
+            ToolchainStoreInner::Remote(_) => Task::ready(Err(anyhow!(
+                "installing toolchains is not supported for remote projects"
+            ))),
+        }
+    }
+    pub(crate) fn active_toolchain_environments(
+        &self,
+        worktree_id: WorktreeId,
+        cx: &AppContext,
+    ) -> Vec<(Toolchain, HashMap<String, String>)> {
+        match &self.0 {
+            ToolchainStoreInner::Local(local, _) => {
+                local.read(cx).active_toolchain_environments(worktree_id)
+            }
+            ToolchainStoreInner::Remote(_) => Vec::new(),
+        }
+    }
+    pub(crate) fn toolchain_details(
+        &self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &AppContext,
+    ) -> Task<Option<ToolchainDetails>> {
+        match &self.0 {
+            ToolchainStoreInner::Local(local, _) => {
+                local.read(cx).toolchain_details(worktree_id, toolchain, cx)
+            }
+            ToolchainStoreInner::Remote(_) => Task::ready(None),
+        }
+    }
     pub(crate) fn list_toolchains(
         &self,
         worktree_id: WorktreeId,
@@ -210,6 +254,8 @@ struct LocalToolchainStore {
     worktree_store: Model<WorktreeStore>,
     project_environment: Model<ProjectEnvironment>,
     active_toolchains: BTreeMap<(WorktreeId, LanguageName), Toolchain>,
+    /// See [`language::ToolchainLister::activation_environment`].
+    activation_environments: BTreeMap<(WorktreeId, LanguageName), HashMap<String, String>>,
 }
 
 #[async_trait(?Send)]
@@ -264,6 +310,12 @@ struct RemoteStore(WeakModel<RemoteToolchainStore>);
 #[derive(Clone)]
 pub(crate) enum ToolchainStoreEvent {
     ToolchainActivated,
+    /// The environment that the toolchain activated for a language sets changed, so that the
+    /// language servers started in the old one need a restart.
+    ActivationEnvironmentChanged {
+        worktree_id: WorktreeId,
+        language_name: LanguageName,
+    },
 }
 
 impl EventEmitter<ToolchainStoreEvent> for LocalToolchainStore {}
@@ -275,13 +327,29 @@ impl LocalToolchainStore {
         toolchain: Toolchain,
         cx: &mut ModelContext<Self>,
     ) -> Task<Option<()>> {
+        let registry = self.languages.clone();
         cx.spawn(move |this, mut cx| async move {
+            let activation_environment = registry
+                .language_for_name(&toolchain.language_name.0)
+                .await
+                .ok()
+                .and_then(|language| language.toolchain_lister())
+                .map(|lister| lister.activation_environment(&toolchain))
+                .unwrap_or_default();
             this.update(&mut cx, |this, cx| {
-                this.active_toolchains.insert(
-                    (worktree_id, toolchain.language_name.clone()),
-                    toolchain.clone(),
-                );
+                let key = (worktree_id, toolchain.language_name.clone());
+                this.active_toolchains
+                    .insert(key.clone(), toolchain.clone());
+                let previous_environment = this
+                    .activation_environments
+                    .insert(key, activation_environment.clone());
                 cx.emit(ToolchainStoreEvent::ToolchainActivated);
+                if previous_environment.unwrap_or_default() != activation_environment {
+                    cx.emit(ToolchainStoreEvent::ActivationEnvironmentChanged {
+                        worktree_id,
+                        language_name: toolchain.language_name.clone(),
+                    });
+                }
             })
             .ok();
             Some(())
@@ -321,6 +389,99 @@ impl LocalToolchainStore {
                 .await
         })
     }
+    pub(crate) fn install_toolchain(
+        &self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &AppContext,
+    ) -> Task<Result<Toolchain>> {
+        let registry = self.languages.clone();
+        let Some(root) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+        else {
+            return Task::ready(Err(anyhow!("no worktree found for id {worktree_id:?}")));
+        };
+
+        let environment = self.project_environment.clone();
+        cx.spawn(|mut cx| async move {
+            let project_env = environment
+                .update(&mut cx, |environment, cx| {
+                    environment.get_environment(Some(worktree_id), Some(root.clone()), cx)
+                })?
+                .await;
+
+            cx.background_executor()
+                .spawn(async move {
+                    let language = registry
+                        .language_for_name(&toolchain.language_name.0)
+                        .await?;
+                    let lister = language.toolchain_lister().with_context(|| {
+                        format!("{} has no toolchains", toolchain.language_name.0)
+                    })?;
+                    lister
+                        .install(toolchain, root.to_path_buf(), project_env)
+                        .await
+                })
+                .await
+        })
+    }
+    pub(crate) fn active_toolchain_environments(
+        &self,
+        worktree_id: WorktreeId,
+    ) -> Vec<(Toolchain, HashMap<String, String>)> {
+        self.active_toolchains
+            .iter()
+            .filter(|((toolchain_worktree_id, _), _)| *toolchain_worktree_id == worktree_id)
+            .map(|(key, toolchain)| {
+                let environment = self
+                    .activation_environments
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_default();
+                (toolchain.clone(), environment)
+            })
+            .collect()
+    }
+    pub(crate) fn toolchain_details(
+        &self,
+        worktree_id: WorktreeId,
+        toolchain: Toolchain,
+        cx: &AppContext,
+    ) -> Task<Option<ToolchainDetails>> {
+        let registry = self.languages.clone();
+        let Some(root) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+        else {
+            return Task::ready(None);
+        };
+
+        let environment = self.project_environment.clone();
+        cx.spawn(|mut cx| async move {
+            let project_env = environment
+                .update(&mut cx, |environment, cx| {
+                    environment.get_environment(Some(worktree_id), Some(root), cx)
+                })
+                .ok()?
+                .await;
+
+            cx.background_executor()
+                .spawn(async move {
+                    let language = registry
+                        .language_for_name(&toolchain.language_name.0)
+                        .await
+                        .ok()?;
+                    let lister = language.toolchain_lister()?;
+                    Some(lister.details(toolchain, project_env).await)
+                })
+                .await
+        })
+    }
     pub(crate) fn active_toolchain(
         &self,
         worktree_id: WorktreeId,
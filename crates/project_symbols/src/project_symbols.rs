@@ -1,13 +1,17 @@
 use editor::{scroll::Autoscroll, styled_runs_for_code_label, Bias, Editor};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    rems, AppContext, DismissEvent, FontWeight, Model, ParentElement, StyledText, Task, View,
-    ViewContext, WeakView, WindowContext,
+    rems, AppContext, BackgroundExecutor, DismissEvent, FontWeight, Model, ParentElement,
+    StyledText, Task, View, ViewContext, WeakView, WindowContext,
 };
 use ordered_float::OrderedFloat;
 use picker::{Picker, PickerDelegate};
 use project::{Project, Symbol};
-use std::{borrow::Cow, cmp::Reverse, sync::Arc};
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    sync::{atomic::AtomicBool, Arc},
+};
 use theme::ActiveTheme;
 use util::ResultExt;
 use workspace::{
@@ -60,31 +64,15 @@ impl ProjectSymbolsDelegate {
 
     fn filter(&mut self, query: &str, cx: &mut ViewContext<Picker<Self>>) {
         const MAX_MATCHES: usize = 100;
-        let mut visible_matches = cx.background_executor().block(fuzzy::match_strings(
+        let mut matches = cx.background_executor().block(match_symbols(
+            &self.symbols,
             &self.visible_match_candidates,
-            query,
-            false,
-            MAX_MATCHES,
-            &Default::default(),
-            cx.background_executor().clone(),
-        ));
-        let mut external_matches = cx.background_executor().block(fuzzy::match_strings(
             &self.external_match_candidates,
             query,
-            false,
-            MAX_MATCHES - visible_matches.len().min(MAX_MATCHES),
+            MAX_MATCHES,
             &Default::default(),
             cx.background_executor().clone(),
         ));
-        let sort_key_for_match = |mat: &StringMatch| {
-            let symbol = &self.symbols[mat.candidate_id];
-            (Reverse(OrderedFloat(mat.score)), symbol.label.filter_text())
-        };
-
-        visible_matches.sort_unstable_by_key(sort_key_for_match);
-        external_matches.sort_unstable_by_key(sort_key_for_match);
-        let mut matches = visible_matches;
-        matches.append(&mut external_matches);
 
         for mat in &mut matches {
             let symbol = &self.symbols[mat.candidate_id];
@@ -99,6 +87,65 @@ impl ProjectSymbolsDelegate {
     }
 }
 
+/// Splits the candidates for fuzzy matching `symbols` into the ones from files visible in the
+/// project and the ones from ignored or external files.
+pub fn symbol_match_candidates(
+    project: &Project,
+    symbols: &[Symbol],
+    cx: &AppContext,
+) -> (Vec<StringMatchCandidate>, Vec<StringMatchCandidate>) {
+    symbols
+        .iter()
+        .enumerate()
+        .map(|(id, symbol)| StringMatchCandidate::new(id, &symbol.label.filter_text()))
+        .partition(|candidate| {
+            project
+                .entry_for_path(&symbols[candidate.id].path, cx)
+                .map_or(false, |e| !e.is_ignored)
+        })
+}
+
+/// Fuzzy matches the symbols against the query, ranking the symbols from visible files first.
+/// The match positions are relative to the filter text of the symbol labels.
+pub async fn match_symbols(
+    symbols: &[Symbol],
+    visible_match_candidates: &[StringMatchCandidate],
+    external_match_candidates: &[StringMatchCandidate],
+    query: &str,
+    max_matches: usize,
+    cancel_flag: &AtomicBool,
+    executor: BackgroundExecutor,
+) -> Vec<StringMatch> {
+    let mut visible_matches = fuzzy::match_strings(
+        visible_match_candidates,
+        query,
+        false,
+        max_matches,
+        cancel_flag,
+        executor.clone(),
+    )
+    .await;
+    let mut external_matches = fuzzy::match_strings(
+        external_match_candidates,
+        query,
+        false,
+        max_matches - visible_matches.len().min(max_matches),
+        cancel_flag,
+        executor,
+    )
+    .await;
+    let sort_key_for_match = |mat: &StringMatch| {
+        let symbol = &symbols[mat.candidate_id];
+        (Reverse(OrderedFloat(mat.score)), symbol.label.filter_text())
+    };
+
+    visible_matches.sort_unstable_by_key(sort_key_for_match);
+    external_matches.sort_unstable_by_key(sort_key_for_match);
+    let mut matches = visible_matches;
+    matches.append(&mut external_matches);
+    matches
+}
+
 impl PickerDelegate for ProjectSymbolsDelegate {
     type ListItem = ListItem;
     fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
@@ -170,17 +217,8 @@ impl PickerDelegate for ProjectSymbolsDelegate {
                 this.update(&mut cx, |this, cx| {
                     let delegate = &mut this.delegate;
                     let project = delegate.project.read(cx);
-                    let (visible_match_candidates, external_match_candidates) = symbols
-                        .iter()
-                        .enumerate()
-                        .map(|(id, symbol)| {
-                            StringMatchCandidate::new(id, &symbol.label.filter_text())
-                        })
-                        .partition(|candidate| {
-                            project
-                                .entry_for_path(&symbols[candidate.id].path, cx)
-                                .map_or(false, |e| !e.is_ignored)
-                        });
+                    let (visible_match_candidates, external_match_candidates) =
+                        symbol_match_candidates(project, &symbols, cx);
 
                     delegate.visible_match_candidates = visible_match_candidates;
                     delegate.external_match_candidates = external_match_candidates;
@@ -196,6 +196,8 @@ messages!(
     (GetDeclarationResponse, Background),
     (GetDocumentHighlights, Background),
     (GetDocumentHighlightsResponse, Background),
+    (GetGuestTasks, Foreground),
+    (GetGuestTasksResponse, Foreground),
     (GetHover, Background),
     (GetHoverResponse, Background),
     (GetNotifications, Foreground),
@@ -278,6 +280,7 @@ messages!(
     (RespondToChannelInvite, Foreground),
     (RespondToContactRequest, Foreground),
     (RoomUpdated, Foreground),
+    (RunGuestTask, Foreground),
     (SaveBuffer, Foreground),
     (SetChannelMemberRole, Foreground),
     (SetChannelVisibility, Foreground),
@@ -311,6 +314,7 @@ messages!(
     (UpdateProject, Foreground),
     (UpdateProjectCollaborator, Foreground),
     (UpdateUserPlan, Foreground),
+    (UpdateWhiteboard, Foreground),
     (UpdateWorktree, Foreground),
     (UpdateWorktreeSettings, Foreground),
     (UsersResponse, Foreground),
@@ -415,6 +419,8 @@ request_messages!(
     (GetReferences, GetReferencesResponse),
     (GetSignatureHelp, GetSignatureHelpResponse),
     (GetStagedText, GetStagedTextResponse),
+    (GetGuestTasks, GetGuestTasksResponse),
+    (RunGuestTask, Ack),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
@@ -526,6 +532,7 @@ entity_messages!(
     GetDeclaration,
     GetImplementation,
     GetDocumentHighlights,
+    GetGuestTasks,
     GetHover,
     GetProjectSymbols,
     GetReferences,
@@ -551,6 +558,7 @@ entity_messages!(
     RenameProjectEntry,
     ResolveCompletionDocumentation,
     ResolveInlayHint,
+    RunGuestTask,
     SaveBuffer,
     StartLanguageServer,
     SynchronizeBuffers,
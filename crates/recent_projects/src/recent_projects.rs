@@ -273,69 +273,12 @@ impl PickerDelegate for RecentProjectsDelegate {
                     if workspace.database_id() == Some(*candidate_workspace_id) {
                         Task::ready(Ok(()))
                     } else {
-                        match candidate_workspace_location {
-                            SerializedWorkspaceLocation::Local(paths, _) => {
-                                let paths = paths.paths().to_vec();
-                                if replace_current_window {
-                                    cx.spawn(move |workspace, mut cx| async move {
-                                        let continue_replacing = workspace
-                                            .update(&mut cx, |workspace, cx| {
-                                                workspace.prepare_to_close(
-                                                    CloseIntent::ReplaceWindow,
-                                                    cx,
-                                                )
-                                            })?
-                                            .await?;
-                                        if continue_replacing {
-                                            workspace
-                                                .update(&mut cx, |workspace, cx| {
-                                                    workspace
-                                                        .open_workspace_for_paths(true, paths, cx)
-                                                })?
-                                                .await
-                                        } else {
-                                            Ok(())
-                                        }
-                                    })
-                                } else {
-                                    workspace.open_workspace_for_paths(false, paths, cx)
-                                }
-                            }
-                            SerializedWorkspaceLocation::Ssh(ssh_project) => {
-                                let app_state = workspace.app_state().clone();
-
-                                let replace_window = if replace_current_window {
-                                    cx.window_handle().downcast::<Workspace>()
-                                } else {
-                                    None
-                                };
-
-                                let open_options = OpenOptions {
-                                    replace_window,
-                                    ..Default::default()
-                                };
-
-                                let connection_options = SshSettings::get_global(cx)
-                                    .connection_options_for(
-                                        ssh_project.host.clone(),
-                                        ssh_project.port,
-                                        ssh_project.user.clone(),
-                                    );
-
-                                let paths = ssh_project.paths.iter().map(PathBuf::from).collect();
-
-                                cx.spawn(|_, mut cx| async move {
-                                    open_ssh_project(
-                                        connection_options,
-                                        paths,
-                                        app_state,
-                                        open_options,
-                                        &mut cx,
-                                    )
-                                    .await
-                                })
-                            }
-                        }
+                        open_recent_workspace(
+                            workspace,
+                            candidate_workspace_location.clone(),
+                            replace_current_window,
+                            cx,
+                        )
                     }
                 })
                 .detach_and_log_err(cx);
@@ -477,6 +420,67 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 }
 
+/// Opens a workspace from the recent projects list, either in a new window or in place of the
+/// given one.
+pub fn open_recent_workspace(
+    workspace: &mut Workspace,
+    location: SerializedWorkspaceLocation,
+    replace_current_window: bool,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    match location {
+        SerializedWorkspaceLocation::Local(paths, _) => {
+            let paths = paths.paths().to_vec();
+            if replace_current_window {
+                cx.spawn(move |workspace, mut cx| async move {
+                    let continue_replacing = workspace
+                        .update(&mut cx, |workspace, cx| {
+                            workspace.prepare_to_close(CloseIntent::ReplaceWindow, cx)
+                        })?
+                        .await?;
+                    if continue_replacing {
+                        workspace
+                            .update(&mut cx, |workspace, cx| {
+                                workspace.open_workspace_for_paths(true, paths, cx)
+                            })?
+                            .await
+                    } else {
+                        Ok(())
+                    }
+                })
+            } else {
+                workspace.open_workspace_for_paths(false, paths, cx)
+            }
+        }
+        SerializedWorkspaceLocation::Ssh(ssh_project) => {
+            let app_state = workspace.app_state().clone();
+
+            let replace_window = if replace_current_window {
+                cx.window_handle().downcast::<Workspace>()
+            } else {
+                None
+            };
+
+            let open_options = OpenOptions {
+                replace_window,
+                ..Default::default()
+            };
+
+            let connection_options = SshSettings::get_global(cx).connection_options_for(
+                ssh_project.host.clone(),
+                ssh_project.port,
+                ssh_project.user.clone(),
+            );
+
+            let paths = ssh_project.paths.iter().map(PathBuf::from).collect();
+
+            cx.spawn(|_, mut cx| async move {
+                open_ssh_project(connection_options, paths, app_state, open_options, &mut cx).await
+            })
+        }
+    }
+}
+
 // Compute the highlighted text for the name and path
 fn highlights_for_path(
     path: &Path,
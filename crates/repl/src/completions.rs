@@ -0,0 +1,490 @@
+//! Completions and hovers from the kernel of a REPL session, merged with the ones from language
+//! servers. The kernel knows about the objects the code has created, like the columns of a
+//! dataframe or attributes set at runtime, which language servers can't see.
+
+use std::{cell::RefCell, ops::Range, rc::Rc, time::Duration};
+
+use anyhow::Result;
+use collections::HashSet;
+use editor::{CompletionProvider, Editor, GotoDefinitionKind, SemanticsProvider};
+use gpui::{AppContext, Model, Task, ViewContext, WeakView};
+use language::{
+    Anchor, Buffer, BufferSnapshot, CodeLabel, Documentation, LanguageServerId, Point,
+    ToOffset as _, Transaction,
+};
+use project::{
+    Completion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint, LocationLink,
+    Project, ProjectTransaction,
+};
+use runtimelib::{CompleteReply, MimeType};
+use serde_json::Value;
+use util::ResultExt as _;
+
+use crate::Session;
+
+/// Stands in for a language server in completions from the kernel, so that they are never
+/// resolved by a language server.
+const KERNEL_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 1);
+
+/// How long to wait for the kernel before showing completions and hovers without its results.
+/// An idle kernel answers well within it, and a busy one isn't asked at all.
+const KERNEL_REPLY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Adds the session's kernel to the completions and hovers of the editor it runs code from.
+pub(crate) fn register_kernel_providers(
+    editor: &mut Editor,
+    session: WeakView<Session>,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.semantics_provider().is_none() {
+        return;
+    }
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let project = workspace.read(cx).project().clone();
+
+    editor.set_completion_provider(Some(Box::new(KernelCompletionProvider {
+        project: project.clone(),
+        session: session.clone(),
+    })));
+    editor.set_semantics_provider(Some(Rc::new(KernelSemanticsProvider { project, session })));
+}
+
+struct KernelCompletionProvider {
+    project: Model<Project>,
+    session: WeakView<Session>,
+}
+
+impl CompletionProvider for KernelCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        trigger: editor::CompletionContext,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let project_completions = self
+            .project
+            .completions(buffer, buffer_position, trigger, cx);
+        let kernel_completions = self.kernel_completions(buffer, buffer_position, cx);
+        cx.background_executor().spawn(async move {
+            let mut completions = project_completions.await.log_err().unwrap_or_default();
+            let known = completions
+                .iter()
+                .map(|completion| completion.new_text.clone())
+                .collect::<HashSet<_>>();
+            completions.extend(
+                kernel_completions
+                    .await
+                    .into_iter()
+                    .filter(|completion| !known.contains(&completion.new_text)),
+            );
+            Ok(completions)
+        })
+    }
+
+    fn resolve_completions(
+        &self,
+        buffer: Model<Buffer>,
+        completion_indices: Vec<usize>,
+        completions: Rc<RefCell<Box<[Completion]>>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<bool>> {
+        let (kernel_indices, project_indices): (Vec<_>, Vec<_>) = {
+            let completions = completions.borrow();
+            completion_indices
+                .into_iter()
+                .partition(|ix| completions[*ix].server_id == KERNEL_SERVER_ID)
+        };
+        let project_resolve = self.project.resolve_completions(
+            buffer.clone(),
+            project_indices,
+            completions.clone(),
+            cx,
+        );
+
+        // Kernel completions are documented with what the kernel knows about the completed
+        // object, such as a function's signature and docstring.
+        let snapshot = buffer.read(cx).snapshot();
+        let session = self.session.upgrade();
+        let inspections = kernel_indices
+            .into_iter()
+            .filter_map(|ix| {
+                let session = session.as_ref()?;
+                let completions = completions.borrow();
+                let completion = &completions[ix];
+                if completion.documentation.is_some() {
+                    return None;
+                }
+                let start = completion.old_range.start.to_offset(&snapshot);
+                let line_start =
+                    snapshot.point_to_offset(Point::new(snapshot.offset_to_point(start).row, 0));
+                let code = snapshot
+                    .text_for_range(line_start..start)
+                    .collect::<String>()
+                    + &completion.new_text;
+                let cursor_pos = code.chars().count();
+                let reply = session.read(cx).inspect(code, cursor_pos);
+                Some(async move { (ix, reply.await) })
+            })
+            .collect::<Vec<_>>();
+        let timeout = cx.background_executor().timer(KERNEL_REPLY_TIMEOUT);
+
+        cx.spawn(|_, _| async move {
+            let mut resolved = project_resolve.await?;
+            let replies = smol::future::or(
+                async { Some(futures::future::join_all(inspections).await) },
+                async {
+                    timeout.await;
+                    None
+                },
+            )
+            .await
+            .unwrap_or_default();
+            let mut completions = completions.borrow_mut();
+            for (ix, reply) in replies {
+                completions[ix].documentation = Some(
+                    reply
+                        .and_then(|reply| inspection_text(reply.found, &reply.data.content))
+                        .map(Documentation::MultiLinePlainText)
+                        .unwrap_or(Documentation::Undocumented),
+                );
+                resolved = true;
+            }
+            Ok(resolved)
+        })
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        buffer: Model<Buffer>,
+        completion: Completion,
+        push_to_history: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<Transaction>>> {
+        if completion.server_id == KERNEL_SERVER_ID {
+            return Task::ready(Ok(None));
+        }
+        self.project
+            .apply_additional_edits_for_completion(buffer, completion, push_to_history, cx)
+    }
+
+    fn is_completion_trigger(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        text: &str,
+        trigger_in_words: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> bool {
+        // Kernels complete dictionary keys, like the columns of a dataframe.
+        (text == "[" && self.session.upgrade().is_some())
+            || self
+                .project
+                .is_completion_trigger(buffer, position, text, trigger_in_words, cx)
+    }
+}
+
+impl KernelCompletionProvider {
+    fn kernel_completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Vec<Completion>> {
+        let Some(session) = self.session.upgrade() else {
+            return Task::ready(Vec::new());
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        if !is_kernel_language(&snapshot, buffer_position, session.read(cx)) {
+            return Task::ready(Vec::new());
+        }
+
+        let text = snapshot.text();
+        let offset = buffer_position.to_offset(&snapshot);
+        let reply = session
+            .read(cx)
+            .complete(text.clone(), text[..offset].chars().count());
+        let timeout = cx.background_executor().timer(KERNEL_REPLY_TIMEOUT);
+        cx.background_executor().spawn(async move {
+            let reply = smol::future::or(reply, async {
+                timeout.await;
+                None
+            })
+            .await;
+            reply
+                .map(|reply| completions_from_reply(&snapshot, &text, reply))
+                .unwrap_or_default()
+        })
+    }
+}
+
+struct KernelSemanticsProvider {
+    project: Model<Project>,
+    session: WeakView<Session>,
+}
+
+impl SemanticsProvider for KernelSemanticsProvider {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Vec<Hover>>> {
+        let project_hover = self.project.hover(buffer, position, cx);
+        let kernel_hover = self.kernel_hover(buffer, position, cx);
+        Some(cx.spawn(|_| async move {
+            let mut hovers = match project_hover {
+                Some(project_hover) => project_hover.await,
+                None => Vec::new(),
+            };
+            hovers.extend(kernel_hover.await);
+            hovers
+        }))
+    }
+
+    fn inlay_hints(
+        &self,
+        buffer_handle: Model<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.project.inlay_hints(buffer_handle, range, cx)
+    }
+
+    fn resolve_inlay_hint(
+        &self,
+        hint: InlayHint,
+        buffer_handle: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<InlayHint>>> {
+        self.project
+            .resolve_inlay_hint(hint, buffer_handle, server_id, cx)
+    }
+
+    fn supports_inlay_hints(&self, buffer: &Model<Buffer>, cx: &AppContext) -> bool {
+        self.project.supports_inlay_hints(buffer, cx)
+    }
+
+    fn document_highlights(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<DocumentHighlight>>>> {
+        self.project.document_highlights(buffer, position, cx)
+    }
+
+    fn definitions(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        kind: GotoDefinitionKind,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<LocationLink>>>> {
+        self.project.definitions(buffer, position, kind, cx)
+    }
+
+    fn range_for_rename(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Option<Range<Anchor>>>>> {
+        self.project.range_for_rename(buffer, position, cx)
+    }
+
+    fn perform_rename(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        new_name: String,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        self.project.perform_rename(buffer, position, new_name, cx)
+    }
+}
+
+impl KernelSemanticsProvider {
+    /// What the kernel knows about the word under the cursor, if the kernel has a value for it.
+    fn kernel_hover(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut AppContext,
+    ) -> Task<Option<Hover>> {
+        let Some(session) = self.session.upgrade() else {
+            return Task::ready(None);
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        if !is_kernel_language(&snapshot, position, session.read(cx)) {
+            return Task::ready(None);
+        }
+
+        let (word_range, _) = snapshot.surrounding_word(position);
+        if word_range.is_empty() {
+            return Task::ready(None);
+        }
+        let line_start =
+            snapshot.point_to_offset(Point::new(snapshot.offset_to_point(word_range.end).row, 0));
+        let code = snapshot
+            .text_for_range(line_start..word_range.end)
+            .collect::<String>();
+        let reply = session.read(cx).inspect(code.clone(), code.chars().count());
+        let range = snapshot.anchor_before(word_range.start)..snapshot.anchor_after(word_range.end);
+        let timeout = cx.background_executor().timer(KERNEL_REPLY_TIMEOUT);
+        cx.background_executor().spawn(async move {
+            let reply = smol::future::or(reply, async {
+                timeout.await;
+                None
+            })
+            .await?;
+            let text = inspection_text(reply.found, &reply.data.content)?;
+            Some(Hover {
+                contents: vec![HoverBlock {
+                    text,
+                    kind: HoverBlockKind::PlainText,
+                }],
+                range: Some(range),
+                language: None,
+            })
+        })
+    }
+}
+
+fn is_kernel_language(snapshot: &BufferSnapshot, position: Anchor, session: &Session) -> bool {
+    snapshot.language_at(position).map_or(false, |language| {
+        language.code_fence_block_name().to_lowercase()
+            == session.kernel_specification.language().to_lowercase()
+    })
+}
+
+fn completions_from_reply(
+    snapshot: &BufferSnapshot,
+    text: &str,
+    reply: CompleteReply,
+) -> Vec<Completion> {
+    let old_range = snapshot.anchor_before(byte_offset(text, reply.cursor_start))
+        ..snapshot.anchor_after(byte_offset(text, reply.cursor_end));
+
+    // IPython describes its matches in experimental metadata.
+    let types = reply
+        .metadata
+        .get("_jupyter_types_experimental")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let text = item.get("text")?.as_str()?;
+            let kind = item.get("type").and_then(Value::as_str).unwrap_or_default();
+            let signature = item
+                .get("signature")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            Some((text, (kind, signature)))
+        })
+        .collect::<collections::HashMap<_, _>>();
+
+    reply
+        .matches
+        .into_iter()
+        .map(|new_text| {
+            let (kind, signature) = types.get(new_text.as_str()).copied().unwrap_or_default();
+            Completion {
+                old_range: old_range.clone(),
+                label: CodeLabel::plain(format!("{new_text}{signature}"), Some(&new_text)),
+                server_id: KERNEL_SERVER_ID,
+                documentation: None,
+                lsp_completion: lsp::CompletionItem {
+                    label: new_text.clone(),
+                    kind: completion_kind(kind),
+                    ..Default::default()
+                },
+                new_text,
+                confirm: None,
+            }
+        })
+        .collect()
+}
+
+/// The byte offset of a position in Jupyter messages, which count characters rather than bytes.
+fn byte_offset(text: &str, chars: usize) -> usize {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+fn completion_kind(kind: &str) -> Option<lsp::CompletionItemKind> {
+    Some(match kind {
+        "function" => lsp::CompletionItemKind::FUNCTION,
+        "class" => lsp::CompletionItemKind::CLASS,
+        "module" => lsp::CompletionItemKind::MODULE,
+        "keyword" => lsp::CompletionItemKind::KEYWORD,
+        "property" => lsp::CompletionItemKind::PROPERTY,
+        "instance" | "statement" | "param" => lsp::CompletionItemKind::VARIABLE,
+        "path" => lsp::CompletionItemKind::FILE,
+        _ => return None,
+    })
+}
+
+/// The plain text of an inspection, without the terminal colors kernels add to it.
+fn inspection_text(found: bool, data: &[MimeType]) -> Option<String> {
+    if !found {
+        return None;
+    }
+    data.iter().find_map(|mimetype| match mimetype {
+        MimeType::Plain(text) => Some(strip_ansi_escapes(text)),
+        _ => None,
+    })
+}
+
+fn strip_ansi_escapes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char == '\x1b' {
+            if chars.next() == Some('[') {
+                // Skip the parameters up to the sequence's final byte.
+                chars.find(|char| ('@'..='~').contains(char));
+            }
+        } else {
+            result.push(char);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_escapes() {
+        assert_eq!(
+            strip_ansi_escapes("\x1b[0;31mSignature:\x1b[0m len(obj, /)"),
+            "Signature: len(obj, /)"
+        );
+        assert_eq!(strip_ansi_escapes("plain"), "plain");
+    }
+
+    #[test]
+    fn test_byte_offset() {
+        let text = "naïve = \"日本\"\nnaï";
+        assert_eq!(byte_offset(text, 0), 0);
+        assert_eq!(byte_offset(text, 3), "naï".len());
+        assert_eq!(byte_offset(text, 11), "naïve = \"日本".len());
+        let cursor = text.len();
+        assert_eq!(
+            byte_offset(text, text[..cursor].chars().count()),
+            cursor,
+            "Converting the cursor position to characters and back should give the same offset"
+        );
+        assert_eq!(
+            byte_offset(text, 100),
+            text.len(),
+            "Positions past the end should be clamped to it"
+        );
+    }
+}
@@ -16,7 +16,7 @@ use gpui::SharedString;
 use gpui::Task;
 use ui::{prelude::*, ListItem, PopoverMenu, PopoverMenuHandle, PopoverTrigger};
 
-type OnSelect = Box<dyn Fn(KernelSpecification, &mut WindowContext)>;
+pub type OnSelect = Box<dyn Fn(KernelSpecification, &mut WindowContext)>;
 
 #[derive(IntoElement)]
 pub struct KernelSelector<T: PopoverTrigger> {
@@ -25,6 +25,8 @@ pub struct KernelSelector<T: PopoverTrigger> {
     trigger: T,
     info_text: Option<SharedString>,
     worktree_id: WorktreeId,
+    current_kernelspec: Option<KernelSpecification>,
+    language: Option<SharedString>,
 }
 
 pub struct KernelPickerDelegate {
@@ -52,6 +54,8 @@ impl<T: PopoverTrigger> KernelSelector<T> {
             trigger,
             info_text: None,
             worktree_id,
+            current_kernelspec: None,
+            language: None,
         }
     }
 
@@ -64,6 +68,30 @@ impl<T: PopoverTrigger> KernelSelector<T> {
         self.info_text = Some(text.into());
         self
     }
+
+    /// Marks the kernel that code currently runs on.
+    pub fn with_current_kernelspec(mut self, kernelspec: Option<KernelSpecification>) -> Self {
+        self.current_kernelspec = kernelspec;
+        self
+    }
+
+    /// Lists the kernels for the language first.
+    pub fn with_language(mut self, language: impl Into<SharedString>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
+impl KernelPickerDelegate {
+    /// A picker among the given kernels, as when more than one of them could run some code.
+    pub fn new(kernels: Vec<KernelSpecification>, on_select: OnSelect) -> Self {
+        Self {
+            selected_kernelspec: kernels.first().cloned(),
+            all_kernels: kernels.clone(),
+            filtered_kernels: kernels,
+            on_select,
+        }
+    }
 }
 
 impl PickerDelegate for KernelPickerDelegate {
@@ -133,7 +161,11 @@ impl PickerDelegate for KernelPickerDelegate {
         let icon = kernelspec.icon(cx);
 
         let (name, kernel_type, path_or_url) = match kernelspec {
-            KernelSpecification::Jupyter(_) => (kernelspec.name(), "Jupyter", None),
+            KernelSpecification::Jupyter(_) => (
+                kernelspec.name(),
+                "Jupyter",
+                Some(truncate_path(&kernelspec.path(), 42)),
+            ),
             KernelSpecification::PythonEnv(_) => (
                 kernelspec.name(),
                 "Python Env",
@@ -229,12 +261,19 @@ impl<T: PopoverTrigger> RenderOnce for KernelSelector<T> {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
         let store = ReplStore::global(cx).read(cx);
 
-        let all_kernels: Vec<KernelSpecification> = store
+        let mut all_kernels: Vec<KernelSpecification> = store
             .kernel_specifications_for_worktree(self.worktree_id)
             .cloned()
             .collect();
+        let language = self
+            .language
+            .or_else(|| self.current_kernelspec.as_ref().map(|spec| spec.language()));
+        if let Some(language) = language {
+            let language = language.to_lowercase();
+            all_kernels.sort_by_key(|kernel| kernel.language().to_lowercase() != language);
+        }
 
-        let selected_kernelspec = store.active_kernelspec(self.worktree_id, None, cx);
+        let selected_kernelspec = self.current_kernelspec;
 
         let delegate = KernelPickerDelegate {
             on_select: self.on_select,
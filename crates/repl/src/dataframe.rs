@@ -0,0 +1,531 @@
+//! # Data Viewer
+//!
+//! Shows a pandas or polars dataframe that lives in a kernel as a table, with sorting and
+//! filtering. Rows are read from the kernel a page at a time as they are scrolled into view, so
+//! that frames with hundreds of thousands of rows can be browsed.
+
+use std::ops::Range;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    impl_actions, uniform_list, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView,
+    ScrollStrategy, Subscription, Task, UniformListScrollHandle, View, WeakView,
+};
+use serde::{Deserialize, Serialize};
+use ui::{prelude::*, Tooltip};
+use util::ResultExt as _;
+use uuid::Uuid;
+use workspace::item::{Item, ItemEvent};
+use workspace::WorkspaceId;
+
+use crate::repl_store::ReplStore;
+use crate::Session;
+
+#[derive(Clone, Default, Deserialize, PartialEq)]
+pub struct ViewDataFrame {
+    /// The expression that evaluates to the dataframe. The selection, or else the word under the
+    /// cursor, is used when it is not given.
+    #[serde(default)]
+    pub expression: Option<String>,
+}
+
+impl_actions!(repl, [ViewDataFrame]);
+
+const PAGE_SIZE: usize = 200;
+const COLUMN_WIDTH: Pixels = px(160.);
+
+/// Defines `_zed_dataframe_page`, which displays a page of a dataframe as JSON.
+const PAGE_SCRIPT: &str = include_str!("dataframe/page.py");
+
+#[derive(Serialize)]
+struct PageRequest<'a> {
+    view_id: &'a str,
+    start: usize,
+    count: usize,
+    /// The column to sort by, and whether to sort in descending order.
+    sort: Option<(usize, bool)>,
+    filter: &'a str,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct DataFramePage {
+    columns: Vec<String>,
+    dtypes: Vec<String>,
+    total_rows: usize,
+    filtered_rows: usize,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+fn page_code(expression: &str, request: &PageRequest) -> Result<String> {
+    // A JSON string is also a valid Python string literal.
+    let request = serde_json::to_string(&serde_json::to_string(request)?)?;
+    let call =
+        format!("_zed_dataframe_page(({expression}), __import__(\"json\").loads({request}))");
+    Ok(format!(
+        "{PAGE_SCRIPT}\ntry:\n    {call}\nfinally:\n    del _zed_dataframe_page\n"
+    ))
+}
+
+/// Forgets the sorted and filtered frame the kernel keeps for a viewer.
+fn release_view_code(view_id: &str) -> Result<String> {
+    let view_id = serde_json::to_string(view_id)?;
+    Ok(format!(
+        "globals().get(\"_zed_dataframe_views\", {{}}).pop({view_id}, None)\n"
+    ))
+}
+
+/// Opens the dataframe the expression evaluates to in the editor's session.
+pub fn view_dataframe(
+    editor: WeakView<Editor>,
+    expression: Option<String>,
+    cx: &mut WindowContext,
+) -> Result<()> {
+    let editor = editor.upgrade().context("editor was dropped")?;
+    let session = ReplStore::global(cx)
+        .read(cx)
+        .get_session(editor.entity_id())
+        .cloned()
+        .context("no kernel is running for this editor")?;
+    let expression = expression.unwrap_or_else(|| {
+        editor.update(cx, |editor, cx| {
+            let selection = editor.selections.newest::<usize>(cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let range = if selection.is_empty() {
+                snapshot.surrounding_word(selection.head(), false).0
+            } else {
+                selection.range()
+            };
+            snapshot.text_for_range(range).collect::<String>()
+        })
+    });
+    let expression = expression.trim();
+    anyhow::ensure!(!expression.is_empty(), "no dataframe expression to view");
+
+    let workspace = editor
+        .read(cx)
+        .workspace()
+        .context("editor is not in a workspace")?;
+    let viewer =
+        cx.new_view(|cx| DataFrameViewer::new(session.downgrade(), expression.to_string(), cx));
+    workspace.update(cx, |workspace, cx| {
+        workspace.add_item_to_active_pane(Box::new(viewer), None, true, cx);
+    });
+    Ok(())
+}
+
+pub struct DataFrameViewer {
+    session: WeakView<Session>,
+    expression: SharedString,
+    /// Identifies this viewer's sorted and filtered frame, which the kernel keeps between pages.
+    view_id: String,
+    focus_handle: FocusHandle,
+    filter_editor: View<Editor>,
+    sort: Option<(usize, bool)>,
+    columns: Vec<(SharedString, SharedString)>,
+    total_rows: usize,
+    filtered_rows: usize,
+    pages: HashMap<usize, Vec<Vec<Option<SharedString>>>>,
+    loading_page: Option<usize>,
+    error: Option<SharedString>,
+    scroll_handle: UniformListScrollHandle,
+    load_task: Task<()>,
+    filter_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DataFrameViewer {
+    pub fn new(session: WeakView<Session>, expression: String, cx: &mut ViewContext<Self>) -> Self {
+        let filter_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter rows…", cx);
+            editor
+        });
+        let subscription = cx.subscribe(&filter_editor, |this, _, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.filter_task = cx.spawn(|this, mut cx| async move {
+                    cx.background_executor()
+                        .timer(Duration::from_millis(300))
+                        .await;
+                    this.update(&mut cx, |this, cx| this.reload(cx)).ok();
+                });
+            }
+        });
+
+        let release_subscription = cx.on_release(|this: &mut Self, window, cx| {
+            let Some(session) = this.session.upgrade() else {
+                return;
+            };
+            let Some(code) = release_view_code(&this.view_id).log_err() else {
+                return;
+            };
+            window
+                .update(cx, |_, cx| {
+                    session.update(cx, |session, cx| {
+                        // Nothing is displayed, so there is no result to wait for.
+                        drop(session.evaluate(code, cx));
+                    })
+                })
+                .ok();
+        });
+
+        let mut this = Self {
+            session,
+            expression: expression.into(),
+            view_id: Uuid::new_v4().to_string(),
+            focus_handle: cx.focus_handle(),
+            filter_editor,
+            sort: None,
+            columns: Vec::new(),
+            total_rows: 0,
+            filtered_rows: 0,
+            pages: HashMap::default(),
+            loading_page: None,
+            error: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            load_task: Task::ready(()),
+            filter_task: Task::ready(()),
+            _subscriptions: vec![subscription, release_subscription],
+        };
+        this.load_page(0, cx);
+        this
+    }
+
+    /// Drops the loaded pages, and loads the first page again with the current sorting and filter.
+    fn reload(&mut self, cx: &mut ViewContext<Self>) {
+        self.pages.clear();
+        self.loading_page = None;
+        self.error = None;
+        self.load_task = Task::ready(());
+        self.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
+        self.load_page(0, cx);
+        cx.notify();
+    }
+
+    fn load_page(&mut self, page: usize, cx: &mut ViewContext<Self>) {
+        if self.pages.contains_key(&page) || self.loading_page.is_some() {
+            return;
+        }
+        let Some(session) = self.session.upgrade() else {
+            self.error = Some("The REPL session was shut down".into());
+            return;
+        };
+
+        let filter = self.filter_editor.read(cx).text(cx);
+        let request = PageRequest {
+            view_id: &self.view_id,
+            start: page * PAGE_SIZE,
+            count: PAGE_SIZE,
+            sort: self.sort,
+            filter: &filter,
+        };
+        let code = match page_code(&self.expression, &request) {
+            Ok(code) => code,
+            Err(error) => {
+                self.error = Some(format!("{error:#}").into());
+                return;
+            }
+        };
+
+        let evaluation = session.update(cx, |session, cx| session.evaluate(code, cx));
+        self.loading_page = Some(page);
+        self.load_task = cx.spawn(|this, mut cx| async move {
+            let page_data = evaluation
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("The kernel was stopped")))
+                .and_then(|value| {
+                    serde_json::from_value::<DataFramePage>(value)
+                        .context("The kernel sent an unexpected page of data")
+                });
+            this.update(&mut cx, |this, cx| {
+                this.loading_page = None;
+                match page_data {
+                    Ok(page_data) => this.insert_page(page, page_data),
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn insert_page(&mut self, page: usize, page_data: DataFramePage) {
+        self.columns = page_data
+            .columns
+            .into_iter()
+            .zip(page_data.dtypes)
+            .map(|(name, dtype)| (name.into(), dtype.into()))
+            .collect();
+        self.total_rows = page_data.total_rows;
+        self.filtered_rows = page_data.filtered_rows;
+        self.error = None;
+        let rows = page_data
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|cell| cell.map(Into::into)).collect())
+            .collect();
+        self.pages.insert(page, rows);
+    }
+
+    /// Sorts by the column in ascending order, then in descending order, and then not at all.
+    fn toggle_sort(&mut self, column: usize, cx: &mut ViewContext<Self>) {
+        self.sort = match self.sort {
+            Some((sorted_column, false)) if sorted_column == column => Some((column, true)),
+            Some((sorted_column, true)) if sorted_column == column => None,
+            _ => Some((column, false)),
+        };
+        self.reload(cx);
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(self.columns.iter().enumerate().map(|(ix, (name, dtype))| {
+                let sort_icon = match self.sort {
+                    Some((column, descending)) if column == ix => Some(if descending {
+                        IconName::ArrowDown
+                    } else {
+                        IconName::ArrowUp
+                    }),
+                    _ => None,
+                };
+                h_flex()
+                    .id(("dataframe-column", ix))
+                    .w(COLUMN_WIDTH)
+                    .flex_none()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .overflow_hidden()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .child(Label::new(name.clone()).single_line())
+                    .child(
+                        Label::new(dtype.clone())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .single_line(),
+                    )
+                    .children(sort_icon.map(|icon| Icon::new(icon).size(IconSize::XSmall)))
+                    .tooltip(|cx| Tooltip::text("Sort by Column", cx))
+                    .on_click(cx.listener(move |this, _, cx| this.toggle_sort(ix, cx)))
+            }))
+    }
+
+    fn render_rows(&mut self, range: Range<usize>, cx: &mut ViewContext<Self>) -> Vec<AnyElement> {
+        if self.error.is_none() {
+            if let Some(page) = (range.start / PAGE_SIZE..=range.end.saturating_sub(1) / PAGE_SIZE)
+                .find(|page| !self.pages.contains_key(page))
+            {
+                self.load_page(page, cx);
+            }
+        }
+
+        range
+            .map(|ix| {
+                let row = self
+                    .pages
+                    .get(&(ix / PAGE_SIZE))
+                    .and_then(|page| page.get(ix % PAGE_SIZE));
+                h_flex()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .font_buffer(cx)
+                    .text_buffer(cx)
+                    .children((0..self.columns.len()).map(|column| {
+                        let cell = row.and_then(|row| row.get(column).cloned().flatten());
+                        div()
+                            .w(COLUMN_WIDTH)
+                            .flex_none()
+                            .px_2()
+                            .truncate()
+                            .map(|el| match cell {
+                                Some(cell) => el.child(cell),
+                                None if row.is_some() => {
+                                    el.text_color(cx.theme().colors().text_muted).child("null")
+                                }
+                                None => el
+                                    .text_color(cx.theme().colors().text_placeholder)
+                                    .child("…"),
+                            })
+                    }))
+                    .into_any_element()
+            })
+            .collect()
+    }
+}
+
+impl EventEmitter<ItemEvent> for DataFrameViewer {}
+
+impl FocusableView for DataFrameViewer {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for DataFrameViewer {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(self.expression.clone())
+    }
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Maximize))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("repl data viewer")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for DataFrameViewer {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let row_count = if self.filtered_rows == self.total_rows {
+            format!("{} rows, {} columns", self.total_rows, self.columns.len())
+        } else {
+            format!(
+                "{} of {} rows, {} columns",
+                self.filtered_rows,
+                self.total_rows,
+                self.columns.len()
+            )
+        };
+        let table_width = COLUMN_WIDTH * self.columns.len() as f32;
+
+        v_flex()
+            .key_context("DataFrameViewer")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_2()
+            .gap_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(self.expression.clone()))
+                    .child(
+                        Label::new(row_count)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .when(self.loading_page.is_some(), |el| {
+                        el.child(
+                            Label::new("Loading…")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .child(div().flex_1())
+                    .child(
+                        div()
+                            .w(rems(20.))
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(cx.theme().colors().border)
+                            .rounded_md()
+                            .child(self.filter_editor.clone()),
+                    ),
+            )
+            .when_some(self.error.clone(), |el, error| {
+                el.child(Label::new(error).color(Color::Error))
+            })
+            .child(
+                div()
+                    .id("dataframe-table")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_x_scroll()
+                    .child(
+                        v_flex()
+                            .w(table_width)
+                            .h_full()
+                            .child(self.render_header(cx))
+                            .child(
+                                uniform_list(
+                                    cx.view().clone(),
+                                    "dataframe-rows",
+                                    self.filtered_rows,
+                                    Self::render_rows,
+                                )
+                                .flex_1()
+                                .track_scroll(self.scroll_handle.clone()),
+                            ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_page_code() {
+        let code = page_code(
+            "df[df[\"name\"] == 'x']",
+            &PageRequest {
+                view_id: "view",
+                start: 400,
+                count: 200,
+                sort: Some((2, true)),
+                filter: "it's \"quoted\"",
+            },
+        )
+        .unwrap();
+        assert!(code.starts_with(PAGE_SCRIPT));
+        assert!(code.ends_with(
+            r#"_zed_dataframe_page((df[df["name"] == 'x']), __import__("json").loads("{\"view_id\":\"view\",\"start\":400,\"count\":200,\"sort\":[2,true],\"filter\":\"it's \\\"quoted\\\"\"}"))
+finally:
+    del _zed_dataframe_page
+"#
+        ));
+    }
+
+    #[test]
+    fn test_release_view_code() {
+        assert_eq!(
+            release_view_code("view").unwrap(),
+            "globals().get(\"_zed_dataframe_views\", {}).pop(\"view\", None)\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_page() {
+        let page = serde_json::from_value::<DataFramePage>(json!({
+            "columns": ["", "name", "age"],
+            "dtypes": ["int64", "object", "float64"],
+            "total_rows": 3,
+            "filtered_rows": 1,
+            "rows": [["1", "Bob", null]],
+        }))
+        .unwrap();
+        assert_eq!(page.columns, vec!["", "name", "age"]);
+        assert_eq!(page.filtered_rows, 1);
+        assert_eq!(
+            page.rows,
+            vec![vec![Some("1".to_string()), Some("Bob".to_string()), None]]
+        );
+    }
+}
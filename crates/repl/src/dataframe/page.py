@@ -0,0 +1,86 @@
+def _zed_dataframe_page(value, request):
+    import json
+    from IPython.display import display
+
+    module = type(value).__module__.split(".")[0]
+    if module not in ("pandas", "polars") or not hasattr(value, "columns"):
+        raise TypeError(f"{type(value).__name__} is not a pandas or polars dataframe")
+
+    # The sorted and filtered frame is kept between requests for pages of the same view.
+    views = globals().setdefault("_zed_dataframe_views", {})
+    key = (request["sort"] and tuple(request["sort"]), request["filter"])
+    cached = views.get(request["view_id"])
+    if cached is not None and cached[0] == key and cached[1] is value:
+        frame = cached[2]
+    else:
+        frame = value
+        query = request["filter"].lower()
+        sort = request["sort"]
+        if module == "pandas":
+            if query:
+                matches = frame.astype(str).apply(
+                    lambda column: column.str.lower().str.contains(query, regex=False)
+                )
+                index_matches = frame.index.astype(str).str.lower().str.contains(query, regex=False)
+                frame = frame[matches.any(axis=1) | index_matches]
+            if sort is not None:
+                column, descending = sort
+                if column == 0:
+                    frame = frame.sort_index(ascending=not descending)
+                else:
+                    frame = frame.sort_values(
+                        frame.columns[column - 1], ascending=not descending, na_position="last"
+                    )
+        else:
+            import polars as pl
+
+            if query:
+                frame = frame.filter(
+                    pl.any_horizontal(
+                        [
+                            pl.col(name)
+                            .cast(pl.Utf8, strict=False)
+                            .str.to_lowercase()
+                            .str.contains(query, literal=True)
+                            .fill_null(False)
+                            for name in frame.columns
+                        ]
+                    )
+                )
+            if sort is not None:
+                column, descending = sort
+                frame = frame.sort(frame.columns[column], descending=descending, nulls_last=True)
+        views[request["view_id"]] = (key, value, frame)
+
+    def cell(value):
+        if value is None or (isinstance(value, float) and value != value):
+            return None
+        return str(value)
+
+    start = request["start"]
+    end = start + request["count"]
+    if module == "pandas":
+        columns = [str(value.index.name or "")] + [str(name) for name in value.columns]
+        dtypes = [str(value.index.dtype)] + [str(dtype) for dtype in value.dtypes]
+        page = frame.iloc[start:end]
+        rows = [
+            [cell(index)] + [cell(item) for item in row]
+            for index, row in zip(page.index, page.itertuples(index=False, name=None))
+        ]
+    else:
+        columns = list(value.columns)
+        dtypes = [str(dtype) for dtype in value.dtypes]
+        rows = [[cell(item) for item in row] for row in frame.slice(start, end - start).iter_rows()]
+
+    display(
+        {
+            "application/json": {
+                "columns": columns,
+                "dtypes": dtypes,
+                "total_rows": len(value),
+                "filtered_rows": len(frame),
+                "rows": rows,
+            }
+        },
+        raw=True,
+    )
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use editor::EditorSettings;
 use gpui::AppContext;
@@ -6,9 +6,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
 
+use crate::KernelSpecification;
+
 #[derive(Debug, Default)]
 pub struct JupyterSettings {
     pub kernel_selections: HashMap<String, String>,
+    pub servers: Vec<JupyterServer>,
+    pub startup_code: HashMap<String, Vec<String>>,
 }
 
 impl JupyterSettings {
@@ -18,6 +22,33 @@ impl JupyterSettings {
         // This allows the editor to set up context for key bindings/actions.
         EditorSettings::jupyter_enabled(cx)
     }
+
+    /// The code to run when the kernel starts, set for the kernel's name or else for its
+    /// language. Both are matched case-insensitively.
+    pub fn startup_code(&self, kernel_specification: &KernelSpecification) -> Option<String> {
+        let name = kernel_specification.name().to_lowercase();
+        let language = kernel_specification.language().to_lowercase();
+        let lines = self
+            .startup_code
+            .get(&name)
+            .or_else(|| self.startup_code.get(&language))?;
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// A Jupyter server or gateway to run kernels on.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum JupyterServer {
+    /// A server at the given URL, such as `http://gpu-box:8888`.
+    Url {
+        url: String,
+        #[serde(default)]
+        token: String,
+    },
+    /// A server described by the `jpserver-<pid>.json` file it writes to the Jupyter runtime
+    /// directory.
+    ConnectionFile { connection_file: PathBuf },
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -26,12 +57,24 @@ pub struct JupyterSettingsContent {
     ///
     /// Default: `{}`
     pub kernel_selections: Option<HashMap<String, String>>,
+    /// Jupyter servers whose kernels are offered alongside the local ones, either by URL and
+    /// token or by connection file.
+    ///
+    /// Default: `[]`
+    pub servers: Option<Vec<JupyterServer>>,
+    /// Code to run whenever a kernel starts, given as lines of code for each kernel name or
+    /// language. Code for a kernel name is run instead of the code for its language.
+    ///
+    /// Default: `{}`
+    pub startup_code: Option<HashMap<String, Vec<String>>>,
 }
 
 impl Default for JupyterSettingsContent {
     fn default() -> Self {
         JupyterSettingsContent {
             kernel_selections: Some(HashMap::new()),
+            servers: Some(Vec::new()),
+            startup_code: Some(HashMap::new()),
         }
     }
 }
@@ -56,8 +99,170 @@ impl Settings for JupyterSettings {
                     settings.kernel_selections.insert(k.clone(), v.clone());
                 }
             }
+            if let Some(servers) = &value.servers {
+                settings.servers = servers.clone();
+            }
+            if let Some(source) = &value.startup_code {
+                // Keys are lowercased so that `Python` and `python` from different sources
+                // override each other instead of both being kept.
+                for (k, v) in source {
+                    settings.startup_code.insert(k.to_lowercase(), v.clone());
+                }
+            }
         }
 
         Ok(settings)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernels::LocalKernelSpecification;
+    use jupyter_protocol::JupyterKernelspec;
+    use settings::SettingsStore;
+
+    fn kernelspec(name: &str, language: &str) -> KernelSpecification {
+        KernelSpecification::Jupyter(LocalKernelSpecification {
+            name: name.to_string(),
+            path: PathBuf::from("/kernels").join(name),
+            kernelspec: JupyterKernelspec {
+                argv: vec![name.to_string()],
+                display_name: name.to_string(),
+                language: language.to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        })
+    }
+
+    #[gpui::test]
+    fn test_startup_code(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        JupyterSettings::register(cx);
+        let startup_code = |kernelspec: &KernelSpecification, cx: &AppContext| {
+            JupyterSettings::get_global(cx).startup_code(kernelspec)
+        };
+
+        cx.update_global::<SettingsStore, _>(|settings_store, cx| {
+            settings_store
+                .set_user_settings(
+                    r#"{
+                        "jupyter": {
+                            "startup_code": {
+                                "Python": ["%matplotlib inline", "import numpy as np"],
+                                "Conda-Base": ["import pandas as pd"],
+                                "deno": []
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+
+        // Languages and kernel names are matched regardless of case, and a kernel's name wins
+        // over its language.
+        assert_eq!(
+            startup_code(&kernelspec("python3", "python"), cx).as_deref(),
+            Some("%matplotlib inline\nimport numpy as np")
+        );
+        assert_eq!(
+            startup_code(&kernelspec("conda-base", "python"), cx).as_deref(),
+            Some("import pandas as pd")
+        );
+        assert_eq!(startup_code(&kernelspec("deno", "typescript"), cx), None);
+        assert_eq!(startup_code(&kernelspec("ark", "r"), cx), None);
+
+        // Keys differing only in case replace each other, with the more specific source winning.
+        let content = |json: &str| serde_json::from_str::<JupyterSettingsContent>(json).unwrap();
+        let default = JupyterSettingsContent::default();
+        let user = content(r#"{ "startup_code": { "Python": ["%matplotlib inline"] } }"#);
+        let project = content(r#"{ "startup_code": { "python": ["%matplotlib widget"] } }"#);
+        let settings = JupyterSettings::load(
+            SettingsSources {
+                default: &default,
+                extensions: None,
+                user: Some(&user),
+                release_channel: None,
+                server: None,
+                project: &[&project],
+            },
+            cx,
+        )
+        .unwrap();
+        assert_eq!(
+            settings
+                .startup_code(&kernelspec("python3", "python"))
+                .as_deref(),
+            Some("%matplotlib widget")
+        );
+    }
+
+    #[gpui::test]
+    fn test_servers(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        JupyterSettings::register(cx);
+
+        cx.update_global::<SettingsStore, _>(|settings_store, cx| {
+            settings_store
+                .set_user_settings(
+                    r#"{
+                        "jupyter": {
+                            "servers": [
+                                { "url": "http://gpu-box:8888", "token": "secret" },
+                                { "url": "http://localhost:8889" },
+                                { "connection_file": "/runtime/jpserver-1.json" }
+                            ]
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+
+        let servers = JupyterSettings::get_global(cx).servers.clone();
+        assert_eq!(
+            servers,
+            [
+                JupyterServer::Url {
+                    url: "http://gpu-box:8888".into(),
+                    token: "secret".into(),
+                },
+                JupyterServer::Url {
+                    url: "http://localhost:8889".into(),
+                    token: String::new(),
+                },
+                JupyterServer::ConnectionFile {
+                    connection_file: PathBuf::from("/runtime/jpserver-1.json"),
+                },
+            ]
+        );
+
+        // Servers are written back in the same shape they were read in.
+        let json = serde_json::to_value(&servers).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "url": "http://gpu-box:8888", "token": "secret" },
+                { "url": "http://localhost:8889", "token": "" },
+                { "connection_file": "/runtime/jpserver-1.json" }
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<Vec<JupyterServer>>(json).unwrap(),
+            servers
+        );
+
+        // An empty list removes the servers instead of leaving the earlier ones in place.
+        cx.update_global::<SettingsStore, _>(|settings_store, cx| {
+            settings_store
+                .set_user_settings(r#"{ "jupyter": { "servers": [] } }"#, cx)
+                .unwrap();
+        });
+        assert!(JupyterSettings::get_global(cx).servers.is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+//! Conversion between Jupyter notebooks and scripts in the Jupytext "percent" format, where
+//! `# %%` comments start code cells and `# %% [markdown]` comments start Markdown cells.
+
+use std::ops::Range;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::KernelSpecification;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptCellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+/// A cell of a script, as byte ranges into the script's text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptCell {
+    pub kind: ScriptCellKind,
+    /// The whole cell, including its marker line.
+    pub range: Range<usize>,
+    /// The cell's source, without its marker line and trailing blank lines.
+    pub source_range: Range<usize>,
+}
+
+/// Splits a script into cells at its `%%` markers. Code before the first marker, or the whole
+/// script if it has no markers, is a code cell of its own.
+pub fn script_cells(text: &str, comment_prefix: &str) -> Vec<ScriptCell> {
+    let marker = format!("{comment_prefix}%%");
+    let mut cells = Vec::new();
+    let mut current: Option<(ScriptCellKind, usize, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if let Some(rest) = line.strip_prefix(marker.as_str()) {
+            if let Some((kind, start, source_start)) = current.take() {
+                cells.push(script_cell(text, kind, start..offset, source_start));
+            } else if !text[..offset].trim().is_empty() {
+                cells.push(script_cell(text, ScriptCellKind::Code, 0..offset, 0));
+            }
+            let kind = if rest.contains("[markdown]") || rest.contains("[md]") {
+                ScriptCellKind::Markdown
+            } else if rest.contains("[raw]") {
+                ScriptCellKind::Raw
+            } else {
+                ScriptCellKind::Code
+            };
+            current = Some((kind, offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    match current {
+        Some((kind, start, source_start)) => {
+            cells.push(script_cell(text, kind, start..text.len(), source_start))
+        }
+        None if !text.trim().is_empty() => {
+            cells.push(script_cell(text, ScriptCellKind::Code, 0..text.len(), 0))
+        }
+        None => {}
+    }
+    cells
+}
+
+fn script_cell(
+    text: &str,
+    kind: ScriptCellKind,
+    range: Range<usize>,
+    source_start: usize,
+) -> ScriptCell {
+    let source_start = source_start.min(range.end);
+    let source_end = source_start + text[source_start..range.end].trim_end().len();
+    ScriptCell {
+        kind,
+        range,
+        source_range: source_start..source_end,
+    }
+}
+
+/// The source of a cell as it goes into a notebook, with the comment prefixes of Markdown and raw
+/// cells removed.
+pub fn cell_source(text: &str, cell: &ScriptCell, comment_prefix: &str) -> String {
+    let source = &text[cell.source_range.clone()];
+    match cell.kind {
+        ScriptCellKind::Code => source.to_string(),
+        ScriptCellKind::Markdown | ScriptCellKind::Raw => {
+            let comment_prefix = comment_prefix.trim_end();
+            source
+                .lines()
+                .map(|line| {
+                    line.strip_prefix(comment_prefix)
+                        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+                        .unwrap_or(line)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+/// A cell to be written to a notebook.
+pub struct NotebookCell {
+    pub kind: ScriptCellKind,
+    pub source: String,
+    pub execution_count: Option<u64>,
+    /// Outputs in the notebook format.
+    pub outputs: Vec<Value>,
+}
+
+/// Builds an nbformat 4.5 notebook from the given cells.
+pub fn notebook_json(
+    cells: Vec<NotebookCell>,
+    kernel_specification: Option<&KernelSpecification>,
+    language: &str,
+) -> Value {
+    let cells = cells
+        .into_iter()
+        .map(|cell| {
+            let id = Uuid::new_v4().to_string();
+            let source = source_lines(&cell.source);
+            match cell.kind {
+                ScriptCellKind::Code => json!({
+                    "cell_type": "code",
+                    "id": id,
+                    "metadata": {},
+                    "execution_count": cell.execution_count,
+                    "source": source,
+                    "outputs": cell.outputs,
+                }),
+                ScriptCellKind::Markdown => json!({
+                    "cell_type": "markdown",
+                    "id": id,
+                    "metadata": {},
+                    "source": source,
+                }),
+                ScriptCellKind::Raw => json!({
+                    "cell_type": "raw",
+                    "id": id,
+                    "metadata": {},
+                    "source": source,
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut metadata = json!({ "language_info": { "name": language.to_lowercase() } });
+    if let Some(kernel_specification) = kernel_specification {
+        metadata["kernelspec"] = json!({
+            "name": kernel_specification.name(),
+            "display_name": kernel_specification.name(),
+            "language": kernel_specification.language(),
+        });
+    }
+
+    json!({
+        "cells": cells,
+        "metadata": metadata,
+        "nbformat": 4,
+        "nbformat_minor": 5,
+    })
+}
+
+/// Notebooks store sources as lists of lines, each with its line ending.
+fn source_lines(source: &str) -> Vec<&str> {
+    source.split_inclusive('\n').collect()
+}
+
+/// A script made from a notebook.
+pub struct ImportedScript {
+    pub text: String,
+    /// The source range of each code cell in the script, with the cell's execution count and
+    /// outputs.
+    pub code_cells: Vec<(Range<usize>, Option<i32>, Vec<nbformat::v4::Output>)>,
+}
+
+pub fn notebook_to_script(
+    notebook: &nbformat::v4::Notebook,
+    comment_prefix: &str,
+) -> ImportedScript {
+    let marker = format!("{comment_prefix}%%");
+    let mut text = String::new();
+    let mut code_cells = Vec::new();
+    for cell in &notebook.cells {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        match cell {
+            nbformat::v4::Cell::Code {
+                source,
+                execution_count,
+                outputs,
+                ..
+            } => {
+                text.push_str(&marker);
+                text.push('\n');
+                let source = source.join("");
+                let source = source.trim_end();
+                let start = text.len();
+                text.push_str(source);
+                code_cells.push((start..text.len(), *execution_count, outputs.clone()));
+                text.push('\n');
+            }
+            nbformat::v4::Cell::Markdown { source, .. } => {
+                text.push_str(&format!("{marker} [markdown]\n"));
+                push_commented(&mut text, &source.join(""), comment_prefix);
+            }
+            nbformat::v4::Cell::Raw { source, .. } => {
+                text.push_str(&format!("{marker} [raw]\n"));
+                push_commented(&mut text, &source.join(""), comment_prefix);
+            }
+        }
+    }
+    ImportedScript { text, code_cells }
+}
+
+fn push_commented(text: &mut String, source: &str, comment_prefix: &str) {
+    for line in source.trim_end().lines() {
+        if line.is_empty() {
+            text.push_str(comment_prefix.trim_end());
+        } else {
+            text.push_str(comment_prefix);
+            text.push_str(line);
+        }
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_script_cells() {
+        let text = indoc! {"
+            import math
+
+            # %% [markdown]
+            # # Circles
+            #
+            # The area of a circle.
+
+            # %%
+            print(math.pi * 2 ** 2)
+
+
+        "};
+        let cells = script_cells(text, "# ");
+        let sources = cells
+            .iter()
+            .map(|cell| (cell.kind, cell_source(text, cell, "# ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                (ScriptCellKind::Code, "import math".to_string()),
+                (
+                    ScriptCellKind::Markdown,
+                    "# Circles\n\nThe area of a circle.".to_string()
+                ),
+                (ScriptCellKind::Code, "print(math.pi * 2 ** 2)".to_string()),
+            ]
+        );
+        assert_eq!(cells[2].range.end, text.len());
+
+        let cells = script_cells("print(1)\nprint(2)\n", "# ");
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].source_range, 0..17);
+        assert!(script_cells("\n\n", "# ").is_empty());
+    }
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let notebook = notebook_json(
+            vec![
+                NotebookCell {
+                    kind: ScriptCellKind::Markdown,
+                    source: "# Sums\n\nAdding numbers.".to_string(),
+                    execution_count: None,
+                    outputs: Vec::new(),
+                },
+                NotebookCell {
+                    kind: ScriptCellKind::Code,
+                    source: "x = 1\nx + 1".to_string(),
+                    execution_count: Some(3),
+                    outputs: vec![json!({
+                        "output_type": "execute_result",
+                        "execution_count": 3,
+                        "data": { "text/plain": "2" },
+                        "metadata": {},
+                    })],
+                },
+            ],
+            None,
+            "Python",
+        );
+        assert_eq!(notebook["cells"][1]["source"], json!(["x = 1\n", "x + 1"]));
+        assert_eq!(notebook["metadata"]["language_info"]["name"], "python");
+
+        let nbformat::Notebook::V4(notebook) =
+            nbformat::parse_notebook(&notebook.to_string()).unwrap()
+        else {
+            panic!("expected an nbformat 4.5 notebook");
+        };
+        let script = notebook_to_script(&notebook, "# ");
+        assert_eq!(
+            script.text,
+            indoc! {"
+                # %% [markdown]
+                # # Sums
+                #
+                # Adding numbers.
+
+                # %%
+                x = 1
+                x + 1
+            "}
+        );
+        assert_eq!(script.code_cells.len(), 1);
+        let (range, execution_count, outputs) = &script.code_cells[0];
+        assert_eq!(&script.text[range.clone()], "x = 1\nx + 1");
+        assert_eq!(*execution_count, Some(3));
+        assert_eq!(outputs.len(), 1);
+    }
+}
@@ -1,5 +1,5 @@
 mod native_kernel;
-use std::{fmt::Debug, future::Future, path::PathBuf};
+use std::{collections::HashMap, fmt::Debug, future::Future, path::PathBuf};
 
 use futures::{
     channel::mpsc::{self, Receiver},
@@ -53,6 +53,19 @@ impl KernelSpecification {
         })
     }
 
+    /// The Python environment the kernel runs in, if it was launched from one of the project's
+    /// toolchains.
+    pub fn environment(&self) -> Option<SharedString> {
+        let Self::PythonEnv(spec) = self else {
+            return None;
+        };
+        let env = spec.kernelspec.env.as_ref();
+        env.and_then(|env| env.get("VIRTUAL_ENV").or_else(|| env.get("CONDA_PREFIX")))
+            .cloned()
+            .or_else(|| Some(spec.path.to_string_lossy().to_string()))
+            .map(SharedString::from)
+    }
+
     pub fn language(&self) -> SharedString {
         SharedString::from(match self {
             Self::Jupyter(spec) => spec.kernelspec.language.clone(),
@@ -81,19 +94,49 @@ pub fn python_env_kernel_specifications(
     cx: &mut AppContext,
 ) -> impl Future<Output = Result<Vec<KernelSpecification>>> {
     let python_language = LanguageName::new("Python");
-    let toolchains = project
-        .read(cx)
-        .available_toolchains(worktree_id, python_language, cx);
+    let shell_environment =
+        project.update(cx, |project, cx| project.shell_environment(worktree_id, cx));
+    let project = project.read(cx);
+    let toolchains = project.available_toolchains(worktree_id, python_language.clone(), cx);
+    let active_toolchain = project.active_toolchain(worktree_id, python_language.clone(), cx);
+    let languages = project.languages().clone();
     let background_executor = cx.background_executor().clone();
 
     async move {
-        let toolchains = if let Some(toolchains) = toolchains.await {
-            toolchains
+        let toolchain_lister = languages
+            .language_for_name(&python_language.0)
+            .await
+            .ok()
+            .and_then(|language| language.toolchain_lister());
+
+        let mut toolchains = if let Some(toolchains) = toolchains.await {
+            toolchains.toolchains
         } else {
             return Ok(Vec::new());
         };
 
-        let kernelspecs = toolchains.toolchains.into_iter().map(|toolchain| {
+        // List the environment selected in the toolchain selector first, so that it
+        // is the one used for this worktree unless a kernel is picked explicitly.
+        if let Some(active_toolchain) = active_toolchain.await {
+            if let Some(ix) = toolchains
+                .iter()
+                .position(|toolchain| *toolchain == active_toolchain)
+            {
+                let toolchain = toolchains.remove(ix);
+                toolchains.insert(0, toolchain);
+            }
+        }
+
+        let shell_environment = shell_environment.await;
+        let kernelspecs = toolchains.into_iter().map(|toolchain| {
+            // Launch the kernel the way a terminal activating the environment would, so that
+            // subprocesses and `pip` calls from the kernel use the same environment.
+            let environment = toolchain_lister.as_ref().map(|lister| {
+                kernel_environment(
+                    shell_environment.clone(),
+                    &lister.activation_environment(&toolchain),
+                )
+            });
             background_executor.spawn(async move {
                 let python_path = toolchain.path.to_string();
 
@@ -117,7 +160,7 @@ pub fn python_env_kernel_specifications(
                         language: "python".to_string(),
                         interrupt_mode: None,
                         metadata: None,
-                        env: None,
+                        env: environment,
                     };
 
                     Some(KernelSpecification::PythonEnv(LocalKernelSpecification {
@@ -141,6 +184,18 @@ pub fn python_env_kernel_specifications(
     }
 }
 
+/// Turns a toolchain's activation environment into the environment of a kernel: the project's
+/// shell environment, or Zed's own when it has none, with the toolchain activated in it.
+fn kernel_environment(
+    shell_environment: Option<collections::HashMap<String, String>>,
+    activation_environment: &collections::HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut environment = shell_environment
+        .unwrap_or_else(|| std::env::vars().collect::<collections::HashMap<_, _>>());
+    project::terminals::activate_toolchain_environment(&mut environment, activation_environment);
+    environment.into_iter().collect()
+}
+
 pub trait RunningKernel: Send + Debug {
     fn request_tx(&self) -> mpsc::Sender<JupyterMessage>;
     fn working_directory(&self) -> &PathBuf;
@@ -222,6 +277,16 @@ impl Kernel {
         }
     }
 
+    /// Whether the kernel is IPython, which keeps the result of each execution in `Out`.
+    pub fn is_ipython(&self) -> bool {
+        match self {
+            Kernel::RunningKernel(kernel) => kernel
+                .kernel_info()
+                .is_some_and(|info| info.implementation == "ipython"),
+            _ => false,
+        }
+    }
+
     pub fn set_kernel_info(&mut self, kernel_info: &KernelInfoReply) {
         if let Kernel::RunningKernel(running_kernel) = self {
             running_kernel.set_kernel_info(kernel_info.clone());
@@ -238,3 +303,33 @@ impl Kernel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_environment() {
+        let path = |paths: &[&str]| std::env::join_paths(paths).unwrap().into_string().unwrap();
+        let shell_environment = collections::HashMap::from_iter([
+            ("PATH".to_string(), path(&["/usr/local/bin", "/usr/bin"])),
+            ("LANG".to_string(), "C.UTF-8".to_string()),
+        ]);
+        let activation_environment = collections::HashMap::from_iter([
+            ("PATH".to_string(), "/project/.venv/bin".to_string()),
+            ("VIRTUAL_ENV".to_string(), "/project/.venv".to_string()),
+        ]);
+
+        let environment = kernel_environment(Some(shell_environment), &activation_environment);
+        assert_eq!(
+            environment["PATH"],
+            path(&["/project/.venv/bin", "/usr/local/bin", "/usr/bin"]),
+            "The environment's binaries should come before the ones of the shell's PATH"
+        );
+        assert_eq!(environment["VIRTUAL_ENV"], "/project/.venv");
+        assert_eq!(
+            environment["LANG"], "C.UTF-8",
+            "The rest of the shell environment should be kept"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context as _, Result};
+use collections::HashSet;
 use futures::{
     channel::mpsc::{self},
     io::BufReader,
@@ -17,9 +18,11 @@ use std::{
     env,
     fmt::Debug,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
-    sync::Arc,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::Duration,
 };
+use util::ResultExt as _;
 use uuid::Uuid;
 
 use crate::Session;
@@ -418,7 +421,8 @@ async fn read_kernels_dir(path: PathBuf, fs: &dyn Fs) -> Result<Vec<LocalKernelS
     Ok(valid_kernelspecs)
 }
 
-pub async fn local_kernel_specifications(fs: Arc<dyn Fs>) -> Result<Vec<LocalKernelSpecification>> {
+/// The directories that kernelspecs are looked for in.
+pub async fn kernelspec_dirs(fs: &dyn Fs) -> Vec<PathBuf> {
     let mut data_dirs = dirs::data_dirs();
 
     // Pick up any kernels from conda or conda environment
@@ -446,10 +450,173 @@ pub async fn local_kernel_specifications(fs: Arc<dyn Fs>) -> Result<Vec<LocalKer
         }
     }
 
-    let kernel_dirs = data_dirs
+    // Pick up kernels installed into any conda environment, not only the active one
+    for conda_prefix in conda_environments(fs).await {
+        data_dirs.push(conda_prefix.join("share").join("jupyter"));
+    }
+
+    // IJulia installs Jupyter into the Julia depot with Conda.jl when it can't find one
+    for depot in julia_depots() {
+        let conda_root = depot.join("conda").join("3");
+        data_dirs.push(conda_root.join("share").join("jupyter"));
+        data_dirs.push(
+            conda_root
+                .join(env::consts::ARCH)
+                .join("share")
+                .join("jupyter"),
+        );
+    }
+
+    let mut kernel_dirs = Vec::new();
+    for data_dir in data_dirs {
+        let kernel_dir = data_dir.join("kernels");
+        if !kernel_dirs.contains(&kernel_dir) {
+            kernel_dirs.push(kernel_dir);
+        }
+    }
+    kernel_dirs
+}
+
+/// The prefixes of the conda environments: the ones conda lists in `~/.conda/environments.txt`,
+/// and the base environment and its `envs` of the conda that's in use.
+async fn conda_environments(fs: &dyn Fs) -> Vec<PathBuf> {
+    conda_environments_in(
+        fs,
+        util::paths::home_dir(),
+        env::var("CONDA_EXE").ok().map(PathBuf::from),
+    )
+    .await
+}
+
+async fn conda_environments_in(
+    fs: &dyn Fs,
+    home_dir: &Path,
+    conda_exe: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let environments_txt = home_dir.join(".conda").join("environments.txt");
+    let mut prefixes = match fs.load(&environments_txt).await {
+        Ok(contents) => parse_conda_environments(&contents),
+        Err(_) => Vec::new(),
+    };
+
+    // `CONDA_EXE` is `<root>/bin/conda`, or `<root>\Scripts\conda.exe` on Windows
+    let conda_root =
+        conda_exe.and_then(|conda_exe| Some(conda_exe.parent()?.parent()?.to_path_buf()));
+    if let Some(conda_root) = conda_root {
+        if let Ok(mut envs) = fs.read_dir(&conda_root.join("envs")).await {
+            while let Some(env) = envs.next().await {
+                if let Ok(env) = env {
+                    prefixes.push(env);
+                }
+            }
+        }
+        prefixes.push(conda_root);
+    }
+
+    prefixes
+}
+
+fn parse_conda_environments(contents: &str) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// The Julia depots, where Julia keeps packages and their data.
+///
+/// IJulia installs a kernel for each Julia version it's built with, as for each juliaup channel,
+/// into Jupyter's user data directory, which is searched already. The depots are only searched
+/// for the Jupyter that IJulia installs with Conda.jl when it can't find one.
+fn julia_depots() -> Vec<PathBuf> {
+    let depots = env::var_os("JULIA_DEPOT_PATH")
+        .map(|depot_path| {
+            env::split_paths(&depot_path)
+                .filter(|depot| !depot.as_os_str().is_empty())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if depots.is_empty() {
+        vec![util::paths::home_dir().join(".julia")]
+    } else {
+        depots
+    }
+}
+
+/// How long `Rscript` gets to tell where IRkernel is, as R can be slow to start.
+const IRKERNEL_LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where IRkernel's kernelspec is, which is looked up once per run of Zed as it takes starting R.
+static IRKERNEL_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The name that `IRkernel::installspec()` gives to IRkernel's kernel.
+pub const IRKERNEL_NAME: &str = "ir";
+
+/// The kernelspec that ships with R's IRkernel package, for when it hasn't been installed into
+/// Jupyter's directories with `IRkernel::installspec()`.
+pub async fn irkernel_specification(fs: &dyn Fs) -> Option<LocalKernelSpecification> {
+    let kernel_dir = match IRKERNEL_DIR.get() {
+        Some(kernel_dir) => kernel_dir.clone(),
+        None => {
+            let kernel_dir = irkernel_dir().await;
+            IRKERNEL_DIR.get_or_init(|| kernel_dir).clone()
+        }
+    }?;
+
+    let mut kernelspec = read_kernelspec_at(kernel_dir, fs).await.log_err()?;
+    // The directory is named `kernelspec`, and `installspec` names the kernel `ir`
+    kernelspec.name = IRKERNEL_NAME.to_string();
+    Some(kernelspec)
+}
+
+async fn irkernel_dir() -> Option<PathBuf> {
+    let rscript = which::which("Rscript").ok()?;
+    let output = util::command::new_smol_command(rscript)
+        .arg("-e")
+        .arg("cat(system.file('kernelspec', package = 'IRkernel'))")
+        .kill_on_drop(true)
+        .output();
+    let output = smol::future::or(async { output.await.log_err() }, async {
+        smol::Timer::after(IRKERNEL_LOOKUP_TIMEOUT).await;
+        log::warn!("timed out looking up IRkernel with Rscript");
+        None
+    })
+    .await?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let kernel_dir = String::from_utf8(output.stdout).ok()?;
+    let kernel_dir = kernel_dir.trim();
+    if kernel_dir.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(kernel_dir))
+}
+
+/// Adds IRkernel's kernelspec if no kernel for it was found, and drops the kernelspecs found more
+/// than once, as when the active conda environment is also listed among conda's environments.
+pub async fn complete_local_kernel_specifications(
+    mut specs: Vec<LocalKernelSpecification>,
+    fs: &dyn Fs,
+) -> Vec<LocalKernelSpecification> {
+    if !specs.iter().any(|spec| spec.name == IRKERNEL_NAME) {
+        specs.extend(irkernel_specification(fs).await);
+    }
+    let mut seen_paths = HashSet::default();
+    specs.retain(|spec| seen_paths.insert(spec.path.clone()));
+    specs
+}
+
+pub async fn local_kernel_specifications(
+    kernel_dirs: &[PathBuf],
+    fs: Arc<dyn Fs>,
+) -> Result<Vec<LocalKernelSpecification>> {
+    let kernel_dirs = kernel_dirs
         .iter()
-        .map(|dir| dir.join("kernels"))
-        .map(|path| read_kernels_dir(path, fs.as_ref()))
+        .map(|path| read_kernels_dir(path.clone(), fs.as_ref()))
         .collect::<Vec<_>>();
 
     let kernel_dirs = futures::future::join_all(kernel_dirs).await;
@@ -518,4 +685,109 @@ mod test {
             vec!["deno", "python"]
         );
     }
+
+    #[test]
+    fn test_parse_conda_environments() {
+        let environments = parse_conda_environments(
+            "/opt/conda\n\n/home/user/.conda/envs/data science\n# /removed/env\n",
+        );
+        assert_eq!(
+            environments,
+            vec![
+                PathBuf::from("/opt/conda"),
+                PathBuf::from("/home/user/.conda/envs/data science"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_conda_environments(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/home/user/.conda",
+            json!({ "environments.txt": "/opt/conda/envs/listed\n/home/user/envs/other\n" }),
+        )
+        .await;
+        fs.insert_tree(
+            "/opt/conda",
+            json!({
+                "bin": { "conda": "" },
+                "envs": { "listed": {}, "unlisted": {} }
+            }),
+        )
+        .await;
+
+        let environments = conda_environments_in(
+            fs.as_ref(),
+            Path::new("/home/user"),
+            Some(PathBuf::from("/opt/conda/bin/conda")),
+        )
+        .await;
+        assert_eq!(
+            environments,
+            vec![
+                PathBuf::from("/opt/conda/envs/listed"),
+                PathBuf::from("/home/user/envs/other"),
+                PathBuf::from("/opt/conda/envs/listed"),
+                PathBuf::from("/opt/conda/envs/unlisted"),
+                PathBuf::from("/opt/conda"),
+            ]
+        );
+
+        // Without conda in use, only the listed environments are found
+        let environments = conda_environments_in(fs.as_ref(), Path::new("/home/user"), None).await;
+        assert_eq!(
+            environments,
+            vec![
+                PathBuf::from("/opt/conda/envs/listed"),
+                PathBuf::from("/home/user/envs/other"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_kernels_found_twice_are_deduplicated(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let kernelspec = |name: &str, path: &str| LocalKernelSpecification {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            kernelspec: JupyterKernelspec {
+                argv: vec!["python".to_string()],
+                display_name: name.to_string(),
+                language: "python".to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        };
+
+        // A kernel for IRkernel is already there, so R isn't asked for it.
+        let specs = complete_local_kernel_specifications(
+            vec![
+                kernelspec(
+                    "python3",
+                    "/opt/conda/envs/ds/share/jupyter/kernels/python3",
+                ),
+                kernelspec("python3", "/usr/share/jupyter/kernels/python3"),
+                kernelspec(
+                    "python3",
+                    "/opt/conda/envs/ds/share/jupyter/kernels/python3",
+                ),
+                kernelspec(IRKERNEL_NAME, "/usr/share/jupyter/kernels/ir"),
+            ],
+            fs.as_ref(),
+        )
+        .await;
+        assert_eq!(
+            specs
+                .iter()
+                .map(|spec| spec.path.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("/opt/conda/envs/ds/share/jupyter/kernels/python3"),
+                PathBuf::from("/usr/share/jupyter/kernels/python3"),
+                PathBuf::from("/usr/share/jupyter/kernels/ir"),
+            ]
+        );
+    }
 }
@@ -12,6 +12,8 @@ use futures::StreamExt;
 use smol::io::AsyncReadExt as _;
 
 use crate::Session;
+use project::Fs;
+use serde::Deserialize;
 
 use super::RunningKernel;
 use anyhow::Result;
@@ -19,7 +21,7 @@ use jupyter_websocket_client::{
     JupyterWebSocket, JupyterWebSocketReader, JupyterWebSocketWriter, KernelLaunchRequest,
     KernelSpecsResponse, RemoteServer,
 };
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, path::Path, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub struct RemoteKernelSpecification {
@@ -27,6 +29,31 @@ pub struct RemoteKernelSpecification {
     pub url: String,
     pub token: String,
     pub kernelspec: JupyterKernelspec,
+    /// Set for kernels that are already running on the server, which are connected to instead
+    /// of launching a new kernel.
+    pub kernel_id: Option<String>,
+}
+
+pub fn remote_server(url: &str, token: String) -> RemoteServer {
+    RemoteServer {
+        base_url: url.trim_end_matches('/').to_string(),
+        token,
+    }
+}
+
+/// What a running Jupyter server writes to its `jpserver-<pid>.json` file in the Jupyter runtime
+/// directory about how to connect to it.
+#[derive(Deserialize)]
+struct ServerConnectionInfo {
+    url: String,
+    #[serde(default)]
+    token: String,
+}
+
+pub async fn read_server_connection_file(fs: &dyn Fs, path: &Path) -> Result<RemoteServer> {
+    let contents = fs.load(path).await?;
+    let info: ServerConnectionInfo = serde_json::from_str(&contents)?;
+    Ok(remote_server(&info.url, info.token))
 }
 
 pub async fn launch_remote_kernel(
@@ -89,7 +116,7 @@ pub async fn list_remote_kernelspecs(
 
         let kernel_specs: KernelSpecsResponse = serde_json::from_slice(&body_bytes)?;
 
-        let remote_kernelspecs = kernel_specs
+        let mut remote_kernelspecs = kernel_specs
             .kernelspecs
             .into_iter()
             .map(|(name, spec)| RemoteKernelSpecification {
@@ -97,9 +124,36 @@ pub async fn list_remote_kernelspecs(
                 url: remote_server.base_url.clone(),
                 token: remote_server.token.clone(),
                 kernelspec: spec.spec,
+                kernel_id: None,
             })
             .collect::<Vec<RemoteKernelSpecification>>();
 
+        match list_running_kernels(&remote_server, http_client).await {
+            Ok(running_kernels) => {
+                let running_kernelspecs = running_kernels
+                    .into_iter()
+                    .filter_map(|kernel| {
+                        let spec = remote_kernelspecs
+                            .iter()
+                            .find(|spec| spec.name == kernel.name)?;
+                        let short_id = kernel.id.chars().take(8).collect::<String>();
+                        Some(RemoteKernelSpecification {
+                            name: format!("{} (running {short_id})", kernel.name),
+                            kernel_id: Some(kernel.id),
+                            ..spec.clone()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                remote_kernelspecs.extend(running_kernelspecs);
+            }
+            Err(error) => {
+                log::warn!(
+                    "failed to list running kernels on {}: {error:#}",
+                    remote_server.base_url
+                );
+            }
+        }
+
         if remote_kernelspecs.is_empty() {
             Err(anyhow::anyhow!("No kernel specs found"))
         } else {
@@ -113,9 +167,30 @@ pub async fn list_remote_kernelspecs(
     }
 }
 
+/// The kernels already running on the server, which may have been started by another client.
+async fn list_running_kernels(
+    remote_server: &RemoteServer,
+    http_client: Arc<dyn HttpClient>,
+) -> Result<Vec<jupyter_websocket_client::Kernel>> {
+    let request = Request::builder()
+        .method("GET")
+        .uri(&remote_server.api_url("/kernels"))
+        .header("Authorization", format!("token {}", remote_server.token))
+        .body(AsyncBody::default())?;
+
+    let response = http_client.send(request).await?;
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to list kernels: {}", response.status());
+    }
+
+    let mut body = Vec::new();
+    response.into_body().read_to_end(&mut body).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
 impl PartialEq for RemoteKernelSpecification {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.url == other.url
+        self.name == other.name && self.url == other.url && self.kernel_id == other.kernel_id
     }
 }
 
@@ -148,13 +223,18 @@ impl RemoteRunningKernel {
         let http_client = cx.http_client();
 
         cx.spawn(|cx| async move {
-            let kernel_id = launch_remote_kernel(
-                &remote_server,
-                http_client.clone(),
-                &kernelspec.name,
-                working_directory.to_str().unwrap_or_default(),
-            )
-            .await?;
+            let kernel_id = match kernelspec.kernel_id {
+                Some(kernel_id) => kernel_id,
+                None => {
+                    launch_remote_kernel(
+                        &remote_server,
+                        http_client.clone(),
+                        &kernelspec.name,
+                        working_directory.to_str().unwrap_or_default(),
+                    )
+                    .await?
+                }
+            };
 
             let ws_url = format!(
                 "{}/api/kernels/{}/channels?token={}",
@@ -300,3 +380,193 @@ impl RunningKernel for RemoteRunningKernel {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_read_server_connection_file(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/runtime/jpserver-1.json",
+            json!({
+                "base_url": "/",
+                "pid": 1,
+                "port": 8888,
+                "token": "secret",
+                "url": "http://localhost:8888/",
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await;
+        fs.insert_file(
+            "/runtime/jpserver-2.json",
+            json!({ "url": "http://gpu-box:8889" })
+                .to_string()
+                .into_bytes(),
+        )
+        .await;
+        fs.insert_file("/runtime/jpserver-3.json", b"{ \"port\": 8890 }".to_vec())
+            .await;
+
+        let server =
+            read_server_connection_file(fs.as_ref(), Path::new("/runtime/jpserver-1.json"))
+                .await
+                .unwrap();
+        assert_eq!(server.base_url, "http://localhost:8888");
+        assert_eq!(server.token, "secret");
+
+        let server =
+            read_server_connection_file(fs.as_ref(), Path::new("/runtime/jpserver-2.json"))
+                .await
+                .unwrap();
+        assert_eq!(server.base_url, "http://gpu-box:8889");
+        assert_eq!(server.token, "");
+
+        assert!(
+            read_server_connection_file(fs.as_ref(), Path::new("/runtime/jpserver-3.json"))
+                .await
+                .is_err()
+        );
+        assert!(
+            read_server_connection_file(fs.as_ref(), Path::new("/runtime/jpserver-4.json"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_listing_running_kernels(cx: &mut TestAppContext) {
+        let kernelspecs = json!({
+            "default": "python3",
+            "kernelspecs": {
+                "python3": {
+                    "name": "python3",
+                    "spec": {
+                        "argv": ["python3", "-m", "ipykernel_launcher", "-f", "{connection_file}"],
+                        "display_name": "Python 3",
+                        "language": "python",
+                    },
+                    "resources": {},
+                },
+            },
+        });
+        let kernels = json!([
+            {
+                "id": "1234abcd-0000-0000-0000-000000000000",
+                "name": "python3",
+                "last_activity": "2024-01-01T00:00:00.000000Z",
+                "execution_state": "idle",
+                "connections": 1,
+            },
+            {
+                "id": "5678abcd-0000-0000-0000-000000000000",
+                "name": "julia-1.10",
+                "last_activity": "2024-01-01T00:00:00.000000Z",
+                "execution_state": "busy",
+                "connections": 0,
+            },
+        ]);
+        let http_client = FakeHttpClient::create(move |request| {
+            let response = match request.uri().path() {
+                "/api/kernelspecs" => Some(kernelspecs.to_string()),
+                "/api/kernels" => Some(kernels.to_string()),
+                _ => None,
+            };
+            async move {
+                Ok(match response {
+                    Some(body) => Response::builder().status(200).body(body.into()).unwrap(),
+                    None => Response::builder()
+                        .status(404)
+                        .body(Default::default())
+                        .unwrap(),
+                })
+            }
+        });
+
+        let specs = list_remote_kernelspecs(
+            remote_server("http://localhost:8888/", "secret".into()),
+            http_client,
+        )
+        .await
+        .unwrap();
+
+        // Running kernels are listed after the kernelspecs, and only when their kernelspec is
+        // known to the server.
+        let specs = specs
+            .iter()
+            .map(|spec| {
+                (
+                    spec.name.as_str(),
+                    spec.url.as_str(),
+                    spec.kernel_id.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            specs,
+            [
+                ("python3", "http://localhost:8888", None),
+                (
+                    "python3 (running 1234abcd)",
+                    "http://localhost:8888",
+                    Some("1234abcd-0000-0000-0000-000000000000")
+                ),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_listing_kernelspecs_when_running_kernels_cannot_be_listed() {
+        let http_client = FakeHttpClient::create(|request| {
+            let response = match request.uri().path() {
+                "/api/kernelspecs" => Response::builder()
+                    .status(200)
+                    .body(
+                        json!({
+                            "default": "python3",
+                            "kernelspecs": {
+                                "python3": {
+                                    "name": "python3",
+                                    "spec": {
+                                        "argv": ["python3"],
+                                        "display_name": "Python 3",
+                                        "language": "python",
+                                    },
+                                    "resources": {},
+                                },
+                            },
+                        })
+                        .to_string()
+                        .into(),
+                    )
+                    .unwrap(),
+                _ => Response::builder()
+                    .status(403)
+                    .body(Default::default())
+                    .unwrap(),
+            };
+            async move { Ok(response) }
+        });
+
+        let specs = list_remote_kernelspecs(
+            remote_server("http://localhost:8888", String::new()),
+            http_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            specs
+                .iter()
+                .map(|spec| (spec.name.as_str(), spec.kernel_id.as_deref()))
+                .collect::<Vec<_>>(),
+            [("python3", None)]
+        );
+    }
+}
@@ -14,7 +14,7 @@ use util::ResultExt;
 
 use crate::{
     notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
-    outputs::{plain::TerminalOutput, user_error::ErrorView, Output},
+    outputs::Output,
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -83,22 +83,7 @@ pub enum Cell {
 fn convert_outputs(outputs: &Vec<nbformat::v4::Output>, cx: &mut WindowContext) -> Vec<Output> {
     outputs
         .into_iter()
-        .map(|output| match output {
-            nbformat::v4::Output::Stream { text, .. } => Output::Stream {
-                content: cx.new_view(|cx| TerminalOutput::from(&text.0, cx)),
-            },
-            nbformat::v4::Output::DisplayData(display_data) => {
-                Output::new(&display_data.data, None, cx)
-            }
-            nbformat::v4::Output::ExecuteResult(execute_result) => {
-                Output::new(&execute_result.data, None, cx)
-            }
-            nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
-                ename: error.ename.clone(),
-                evalue: error.evalue.clone(),
-                traceback: cx.new_view(|cx| TerminalOutput::from(&error.traceback.join("\n"), cx)),
-            }),
-        })
+        .map(|output| Output::from_notebook_output(output, cx))
         .collect()
 }
 
@@ -614,6 +599,15 @@ impl Render for CodeCell {
                                             Output::Markdown { content, .. } => {
                                                 Some(content.clone().into_any_element())
                                             }
+                                            Output::Html { content, .. } => {
+                                                Some(content.clone().into_any_element())
+                                            }
+                                            Output::Svg { content, .. } => {
+                                                Some(content.clone().into_any_element())
+                                            }
+                                            Output::Plot { content, .. } => {
+                                                Some(content.clone().into_any_element())
+                                            }
                                             Output::Stream { content, .. } => {
                                                 Some(content.clone().into_any_element())
                                             }
@@ -14,8 +14,10 @@
 //! The module supports several output types, including:
 //! - Plain text
 //! - Markdown
-//! - Images (PNG and JPEG)
+//! - HTML, shown as Markdown
+//! - Images (PNG, JPEG and SVG)
 //! - Tables
+//! - Plotly and Vega plots, which can be opened in the browser
 //! - Error messages
 //!
 //! ## Clipboard Support
@@ -33,16 +35,25 @@
 //! This module is designed to work with Jupyter message protocols,
 //! interpreting and displaying various types of Jupyter output.
 
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context as _, Result};
 
 use editor::{Editor, MultiBuffer};
+use futures::StreamExt as _;
 use gpui::{
     percentage, Animation, AnimationExt, AnyElement, ClipboardItem, Model, Render, Transformation,
     View, WeakView,
 };
 use language::Buffer;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
+use serde_json::{json, Value};
 use ui::{div, prelude::*, v_flex, IntoElement, Styled, Tooltip, ViewContext};
+use util::ResultExt as _;
+use uuid::Uuid;
 
 mod image;
 use image::ImageView;
@@ -50,6 +61,15 @@ use image::ImageView;
 mod markdown;
 use markdown::MarkdownView;
 
+mod html;
+use html::HtmlView;
+
+mod svg;
+use svg::SvgView;
+
+mod plot;
+use plot::{PlotKind, PlotView};
+
 mod table;
 use table::TableView;
 
@@ -60,12 +80,33 @@ pub(crate) mod user_error;
 use user_error::ErrorView;
 use workspace::Workspace;
 
+use crate::dataframe::ViewDataFrame;
+
+/// Outputs taller than this many lines scroll, so that long running loops don't push the rest of
+/// the file out of view.
+const MAX_OUTPUT_LINES: f32 = 32.;
+
+/// Outputs opened in the browser are written to this directory in the system's temp dir.
+const BROWSER_DOCUMENTS_DIR: &str = "zed-repl-outputs";
+
+/// Outputs opened in the browser are removed once they're this old, long after the browser has
+/// loaded them.
+const BROWSER_DOCUMENT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
+        MimeType::Plotly(_)
+        | MimeType::VegaLiteV3(_)
+        | MimeType::VegaLiteV4(_)
+        | MimeType::VegaLiteV5(_)
+        | MimeType::VegaV3(_)
+        | MimeType::VegaV4(_)
+        | MimeType::VegaV5(_) => 7,
         MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
+        MimeType::Png(_) | MimeType::Svg(_) => 4,
         MimeType::Jpeg(_) => 3,
+        MimeType::Html(_) => 2,
         MimeType::Markdown(_) => 2,
         MimeType::Plain(_) => 1,
         // All other media types are not supported in Zed at this time
@@ -84,6 +125,13 @@ pub(crate) trait OutputContent {
     fn buffer_content(&mut self, _cx: &mut WindowContext) -> Option<Model<Buffer>> {
         None
     }
+    fn has_html_document(&self, _cx: &WindowContext) -> bool {
+        false
+    }
+    /// A standalone HTML page with the output, for outputs that need a browser to be shown fully.
+    fn html_document(&self, _cx: &WindowContext) -> Option<String> {
+        None
+    }
 }
 
 impl<V: OutputContent + 'static> OutputContent for View<V> {
@@ -102,6 +150,14 @@ impl<V: OutputContent + 'static> OutputContent for View<V> {
     fn buffer_content(&mut self, cx: &mut WindowContext) -> Option<Model<Buffer>> {
         self.update(cx, |item, cx| item.buffer_content(cx))
     }
+
+    fn has_html_document(&self, cx: &WindowContext) -> bool {
+        self.read(cx).has_html_document(cx)
+    }
+
+    fn html_document(&self, cx: &WindowContext) -> Option<String> {
+        self.read(cx).html_document(cx)
+    }
 }
 
 pub enum Output {
@@ -126,6 +182,18 @@ pub enum Output {
         content: View<MarkdownView>,
         display_id: Option<String>,
     },
+    Html {
+        content: View<HtmlView>,
+        display_id: Option<String>,
+    },
+    Svg {
+        content: View<SvgView>,
+        display_id: Option<String>,
+    },
+    Plot {
+        content: View<PlotView>,
+        display_id: Option<String>,
+    },
     ClearOutputWaitMarker,
 }
 
@@ -135,7 +203,7 @@ impl Output {
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<ExecutionView>,
     ) -> Option<AnyElement> {
-        if !v.has_clipboard_content(cx) && !v.has_buffer_content(cx) {
+        if !v.has_clipboard_content(cx) && !v.has_buffer_content(cx) && !v.has_html_document(cx) {
             return None;
         }
 
@@ -197,6 +265,30 @@ impl Output {
                         })),
                     )
                 })
+                .when(v.has_html_document(cx), |el| {
+                    let v = v.clone();
+                    el.child(
+                        IconButton::new(
+                            ElementId::Name("open-in-browser".into()),
+                            IconName::ExternalLink,
+                        )
+                        .style(ButtonStyle::Transparent)
+                        .tooltip(move |cx| Tooltip::text("Open in Browser", cx))
+                        .on_click(cx.listener(move |_, _, cx| {
+                            let Some(document) = v.html_document(cx) else {
+                                return;
+                            };
+                            cx.spawn(|_, mut cx| async move {
+                                let dir = std::env::temp_dir().join(BROWSER_DOCUMENTS_DIR);
+                                let path = write_browser_document(&dir, document).await?;
+                                let url = url::Url::from_file_path(&path)
+                                    .map_err(|_| anyhow!("{path:?} can't be opened as a URL"))?;
+                                cx.update(|cx| cx.open_url(url.as_str()))
+                            })
+                            .detach_and_log_err(cx);
+                        })),
+                    )
+                })
                 .into_any_element(),
         )
     }
@@ -210,6 +302,9 @@ impl Output {
         let content = match self {
             Self::Plain { content, .. } => Some(content.clone().into_any_element()),
             Self::Markdown { content, .. } => Some(content.clone().into_any_element()),
+            Self::Html { content, .. } => Some(content.clone().into_any_element()),
+            Self::Svg { content, .. } => Some(content.clone().into_any_element()),
+            Self::Plot { content, .. } => Some(content.clone().into_any_element()),
             Self::Stream { content, .. } => Some(content.clone().into_any_element()),
             Self::Image { content, .. } => Some(content.clone().into_any_element()),
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
@@ -229,6 +324,15 @@ impl Output {
                 Self::Markdown { content, .. } => {
                     Self::render_output_controls(content.clone(), workspace.clone(), cx)
                 }
+                Self::Html { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace.clone(), cx)
+                }
+                Self::Svg { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace.clone(), cx)
+                }
+                Self::Plot { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace.clone(), cx)
+                }
                 Self::Stream { content, .. } => {
                     Self::render_output_controls(content.clone(), workspace.clone(), cx)
                 }
@@ -246,6 +350,30 @@ impl Output {
             })
     }
 
+    /// The output as plain text, as it is handed over to the assistant.
+    pub fn text_content(&self, cx: &WindowContext) -> Option<String> {
+        match self {
+            Self::Plain { content, .. } | Self::Stream { content } => {
+                content.clipboard_content(cx)?.text()
+            }
+            Self::Markdown { content, .. } => content.clipboard_content(cx)?.text(),
+            Self::Table { content, .. } => content.clipboard_content(cx)?.text(),
+            Self::Html { content, .. } => Some(content.read(cx).markdown_text().to_string()),
+            Self::Image { .. } | Self::Svg { .. } => Some("[image]".to_string()),
+            Self::Plot { .. } => Some("[plot]".to_string()),
+            Self::ErrorOutput(error) => {
+                let traceback = error
+                    .traceback
+                    .clipboard_content(cx)
+                    .and_then(|traceback| traceback.text())
+                    .unwrap_or_default();
+                Some(format!("{}: {}\n{}", error.ename, error.evalue, traceback))
+            }
+            Self::Message(message) => Some(message.clone()),
+            Self::ClearOutputWaitMarker => None,
+        }
+    }
+
     pub fn display_id(&self) -> Option<String> {
         match self {
             Output::Plain { display_id, .. } => display_id.clone(),
@@ -255,10 +383,32 @@ impl Output {
             Output::Message(_) => None,
             Output::Table { display_id, .. } => display_id.clone(),
             Output::Markdown { display_id, .. } => display_id.clone(),
+            Output::Html { display_id, .. } => display_id.clone(),
+            Output::Svg { display_id, .. } => display_id.clone(),
+            Output::Plot { display_id, .. } => display_id.clone(),
             Output::ClearOutputWaitMarker => None,
         }
     }
 
+    pub fn from_notebook_output(output: &nbformat::v4::Output, cx: &mut WindowContext) -> Self {
+        match output {
+            nbformat::v4::Output::Stream { text, .. } => Output::Stream {
+                content: cx.new_view(|cx| TerminalOutput::from(&text.0, cx)),
+            },
+            nbformat::v4::Output::DisplayData(display_data) => {
+                Output::new(&display_data.data, None, cx)
+            }
+            nbformat::v4::Output::ExecuteResult(execute_result) => {
+                Output::new(&execute_result.data, None, cx)
+            }
+            nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
+                ename: error.ename.clone(),
+                evalue: error.evalue.clone(),
+                traceback: cx.new_view(|cx| TerminalOutput::from(&error.traceback.join("\n"), cx)),
+            }),
+        }
+    }
+
     pub fn new(data: &MimeBundle, display_id: Option<String>, cx: &mut WindowContext) -> Self {
         match data.richest(rank_mime_type) {
             Some(MimeType::Plain(text)) => Output::Plain {
@@ -272,6 +422,25 @@ impl Output {
                     display_id,
                 }
             }
+            Some(MimeType::Html(html)) => Output::Html {
+                content: cx.new_view(|cx| HtmlView::from(html.clone(), cx)),
+                display_id,
+            },
+            Some(MimeType::Svg(svg)) => Output::Svg {
+                content: cx.new_view(|_| SvgView::from(svg)),
+                display_id,
+            },
+            Some(MimeType::Plotly(spec)) => {
+                Self::plot(PlotKind::Plotly, spec, data, display_id, cx)
+            }
+            Some(
+                MimeType::VegaLiteV3(spec)
+                | MimeType::VegaLiteV4(spec)
+                | MimeType::VegaLiteV5(spec),
+            ) => Self::plot(PlotKind::VegaLite, spec, data, display_id, cx),
+            Some(MimeType::VegaV3(spec) | MimeType::VegaV4(spec) | MimeType::VegaV5(spec)) => {
+                Self::plot(PlotKind::Vega, spec, data, display_id, cx)
+            }
             Some(MimeType::Png(data)) | Some(MimeType::Jpeg(data)) => match ImageView::from(data) {
                 Ok(view) => Output::Image {
                     content: cx.new_view(|_| view),
@@ -287,6 +456,68 @@ impl Output {
             _ => Output::Message("Unsupported media type".to_string()),
         }
     }
+
+    fn plot(
+        kind: PlotKind,
+        spec: &serde_json::Value,
+        data: &MimeBundle,
+        display_id: Option<String>,
+        cx: &mut WindowContext,
+    ) -> Self {
+        // Kernels often send a static rendering of the plot along with it, which is shown inline.
+        let preview = data.content.iter().find_map(|mimetype| match mimetype {
+            MimeType::Png(image) | MimeType::Jpeg(image) => ImageView::from(image).ok(),
+            _ => None,
+        });
+        Output::Plot {
+            content: cx.new_view(|cx| PlotView::new(kind, spec.clone(), preview, cx)),
+            display_id,
+        }
+    }
+}
+
+/// Whether a result is a pandas or polars dataframe, going by the representations those libraries
+/// send.
+fn is_dataframe(data: &MimeBundle) -> bool {
+    data.content.iter().any(|mimetype| match mimetype {
+        MimeType::DataTable(_) => true,
+        MimeType::Html(html) => html.contains("class=\"dataframe"),
+        MimeType::Plain(text) => text.starts_with("shape: ("),
+        _ => false,
+    })
+}
+
+/// Writes a page to be opened in the browser to `dir`, removing the pages written there before
+/// that have gotten too old.
+async fn write_browser_document(dir: &Path, document: String) -> Result<PathBuf> {
+    smol::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("creating {dir:?}"))?;
+
+    let mut entries = smol::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("reading {dir:?}"))?;
+    while let Some(entry) = entries.next().await {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let is_stale = entry
+            .metadata()
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .map_or(false, |age| age > BROWSER_DOCUMENT_MAX_AGE);
+        if is_stale {
+            smol::fs::remove_file(entry.path()).await.log_err();
+        }
+    }
+
+    let path = dir.join(format!("{}.html", Uuid::new_v4()));
+    smol::fs::write(&path, document)
+        .await
+        .with_context(|| format!("writing {path:?}"))?;
+    Ok(path)
 }
 
 #[derive(Default, Clone, Debug)]
@@ -301,6 +532,8 @@ pub enum ExecutionStatus {
     Shutdown,
     KernelErrored(String),
     Restarting,
+    /// The execution was removed from the queue before the kernel started running it.
+    Cancelled,
 }
 
 /// An ExecutionView shows the outputs of an execution.
@@ -311,6 +544,19 @@ pub struct ExecutionView {
     workspace: WeakView<Workspace>,
     pub outputs: Vec<Output>,
     pub status: ExecutionStatus,
+    /// The outputs as they are written to a Jupyter notebook.
+    notebook_outputs: Vec<Value>,
+    execution_count: Option<u64>,
+    /// The expression for the dataframe this execution resulted in, which can be paged through
+    /// in the data viewer.
+    dataframe_expression: Option<String>,
+    /// Whether the kernel keeps the results of executions in `Out`, as IPython does, which is
+    /// where dataframe results are opened from.
+    results_in_out: bool,
+    collapsed: bool,
+    /// Whether the outputs are from a kernel that is no longer running, as when they are restored
+    /// from a previous session.
+    stale: bool,
 }
 
 impl ExecutionView {
@@ -323,17 +569,137 @@ impl ExecutionView {
             workspace,
             outputs: Default::default(),
             status,
+            notebook_outputs: Vec::new(),
+            execution_count: None,
+            dataframe_expression: None,
+            results_in_out: false,
+            collapsed: false,
+            stale: false,
         }
     }
 
+    /// Shows outputs that were saved in a notebook.
+    pub fn restore(
+        &mut self,
+        outputs: &[nbformat::v4::Output],
+        execution_count: Option<i32>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.outputs = outputs
+            .iter()
+            .map(|output| Output::from_notebook_output(output, cx))
+            .collect();
+        self.notebook_outputs = outputs
+            .iter()
+            .filter_map(|output| serde_json::to_value(output).ok())
+            .collect();
+        self.execution_count = execution_count.and_then(|count| count.try_into().ok());
+        cx.notify();
+    }
+
+    pub fn mark_stale(&mut self, cx: &mut ViewContext<Self>) {
+        self.stale = true;
+        cx.notify();
+    }
+
+    pub fn set_results_in_out(&mut self, results_in_out: bool) {
+        self.results_in_out = results_in_out;
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn toggle_collapsed(&mut self, cx: &mut ViewContext<Self>) {
+        self.collapsed = !self.collapsed;
+        cx.notify();
+    }
+
+    pub fn notebook_outputs(&self) -> &[Value] {
+        &self.notebook_outputs
+    }
+
+    pub fn execution_count(&self) -> Option<u64> {
+        self.execution_count
+    }
+
+    fn record_notebook_output(&mut self, message: &JupyterMessageContent) {
+        let output = match message {
+            JupyterMessageContent::ExecuteResult(result) => {
+                self.execution_count = serde_json::to_value(&result.execution_count)
+                    .ok()
+                    .and_then(|count| count.as_u64());
+                json!({
+                    "output_type": "execute_result",
+                    "execution_count": self.execution_count,
+                    "data": result.data,
+                    "metadata": result.metadata,
+                })
+            }
+            JupyterMessageContent::DisplayData(result) => json!({
+                "output_type": "display_data",
+                "data": result.data,
+                "metadata": result.metadata,
+            }),
+            JupyterMessageContent::StreamContent(result) => {
+                let name = serde_json::to_value(&result.name).unwrap_or_default();
+                // Consecutive writes to the same stream are a single output in notebooks.
+                if let Some(last) = self.notebook_outputs.last_mut() {
+                    if last["output_type"] == "stream" && last["name"] == name {
+                        let text =
+                            format!("{}{}", last["text"].as_str().unwrap_or(""), result.text);
+                        last["text"] = Value::String(text);
+                        return;
+                    }
+                }
+                json!({
+                    "output_type": "stream",
+                    "name": name,
+                    "text": result.text,
+                })
+            }
+            JupyterMessageContent::ErrorOutput(result) => json!({
+                "output_type": "error",
+                "ename": result.ename,
+                "evalue": result.evalue,
+                "traceback": result.traceback,
+            }),
+            _ => return,
+        };
+        self.notebook_outputs.push(output);
+    }
+
+    pub fn text_outputs(&self, cx: &WindowContext) -> Vec<String> {
+        self.outputs
+            .iter()
+            .filter_map(|output| output.text_content(cx))
+            .collect()
+    }
+
     /// Accept a Jupyter message belonging to this execution
     pub fn push_message(&mut self, message: &JupyterMessageContent, cx: &mut ViewContext<Self>) {
+        if !matches!(self.outputs.last(), Some(Output::ClearOutputWaitMarker)) {
+            self.record_notebook_output(message);
+        }
         let output: Output = match message {
-            JupyterMessageContent::ExecuteResult(result) => Output::new(
-                &result.data,
-                result.transient.as_ref().and_then(|t| t.display_id.clone()),
-                cx,
-            ),
+            JupyterMessageContent::ExecuteResult(result) => {
+                if self.results_in_out && is_dataframe(&result.data) {
+                    // IPython keeps results in `Out`, so the dataframe can be read again from there.
+                    self.dataframe_expression = serde_json::to_value(&result.execution_count)
+                        .ok()
+                        .and_then(|count| count.as_u64())
+                        .map(|count| format!("Out[{count}]"));
+                }
+                Output::new(
+                    &result.data,
+                    result.transient.as_ref().and_then(|t| t.display_id.clone()),
+                    cx,
+                )
+            }
             JupyterMessageContent::DisplayData(result) => Output::new(
                 &result.data,
                 result.transient.as_ref().and_then(|t| t.display_id.clone()),
@@ -358,6 +724,9 @@ impl ExecutionView {
                 })
             }
             JupyterMessageContent::ExecuteReply(reply) => {
+                self.execution_count = serde_json::to_value(&reply.execution_count)
+                    .ok()
+                    .and_then(|count| count.as_u64());
                 for payload in reply.payload.iter() {
                     if let runtimelib::Payload::Page { data, .. } = payload {
                         let output = Output::new(data, None, cx);
@@ -370,6 +739,7 @@ impl ExecutionView {
             JupyterMessageContent::ClearOutput(options) => {
                 if !options.wait {
                     self.outputs.clear();
+                    self.notebook_outputs.clear();
                     cx.notify();
                     return;
                 }
@@ -396,6 +766,8 @@ impl ExecutionView {
         if let Some(output) = self.outputs.last() {
             if let Output::ClearOutputWaitMarker = output {
                 self.outputs.clear();
+                self.notebook_outputs.clear();
+                self.record_notebook_output(message);
             }
         }
 
@@ -486,6 +858,9 @@ impl Render for ExecutionView {
             ExecutionStatus::Queued => Label::new("Queued...")
                 .color(Color::Muted)
                 .into_any_element(),
+            ExecutionStatus::Cancelled => Label::new("Cancelled")
+                .color(Color::Muted)
+                .into_any_element(),
             ExecutionStatus::KernelErrored(error) => Label::new(format!("Kernel error: {}", error))
                 .color(Color::Error)
                 .into_any_element(),
@@ -499,13 +874,64 @@ impl Render for ExecutionView {
                 .into_any_element();
         }
 
+        if self.collapsed {
+            let output_count = self
+                .outputs
+                .iter()
+                .filter(|output| !matches!(output, Output::ClearOutputWaitMarker))
+                .count();
+            return h_flex()
+                .id("collapsed-outputs")
+                .min_h(cx.line_height())
+                .gap_2()
+                .cursor_pointer()
+                .child(status)
+                .child(
+                    Label::new(if output_count == 1 {
+                        "1 output hidden".to_string()
+                    } else {
+                        format!("{output_count} outputs hidden")
+                    })
+                    .color(Color::Muted),
+                )
+                .on_click(cx.listener(|this, _, cx| this.toggle_collapsed(cx)))
+                .into_any_element();
+        }
+
         div()
             .w_full()
-            .children(
-                self.outputs
-                    .iter()
-                    .map(|output| output.render(self.workspace.clone(), cx)),
+            .when(self.stale, |el| {
+                el.child(
+                    Label::new("Outputs from a previous session")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(
+                div()
+                    .id("outputs")
+                    .max_h(cx.line_height() * MAX_OUTPUT_LINES)
+                    .overflow_y_scroll()
+                    .children(
+                        self.outputs
+                            .iter()
+                            .map(|output| output.render(self.workspace.clone(), cx)),
+                    ),
             )
+            .when_some(self.dataframe_expression.clone(), |el, expression| {
+                el.child(
+                    Button::new("open-dataframe", "Open in Data Viewer")
+                        .icon(IconName::Maximize)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .label_size(LabelSize::Small)
+                        .on_click(move |_, cx| {
+                            cx.dispatch_action(Box::new(ViewDataFrame {
+                                expression: Some(expression.clone()),
+                            }))
+                        }),
+                )
+            })
             .children(match self.status {
                 ExecutionStatus::Executing => vec![status],
                 ExecutionStatus::Queued => vec![status],
@@ -514,3 +940,30 @@ impl Render for ExecutionView {
             .into_any_element()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use util::test::temp_tree;
+
+    use super::*;
+
+    #[test]
+    fn test_write_browser_document() {
+        let dir = temp_tree(json!({ "old.html": "old", "recent.html": "recent" }));
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("old.html"))
+            .unwrap()
+            .set_modified(SystemTime::now() - BROWSER_DOCUMENT_MAX_AGE * 2)
+            .unwrap();
+
+        let path =
+            smol::block_on(write_browser_document(dir.path(), "<p>plot</p>".into())).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "<p>plot</p>");
+        assert!(!dir.path().join("old.html").exists());
+        assert!(dir.path().join("recent.html").exists());
+    }
+}
@@ -0,0 +1,122 @@
+//! # HTML Output for REPL
+//!
+//! Zed can't run the scripts and styles that `text/html` outputs often come with, so HTML is
+//! converted to Markdown to be shown inline. Headings, paragraphs, lists, code and tables, like
+//! the ones Pandas renders for DataFrames, come through. The original HTML can be opened in the
+//! browser when the inline rendering isn't enough.
+
+use std::{cell::RefCell, rc::Rc};
+
+use gpui::{ClipboardItem, View, WindowContext};
+use html_to_markdown::{convert_html_to_markdown, markdown, TagHandler};
+use ui::{prelude::*, IntoElement};
+
+use crate::outputs::{markdown::MarkdownView, OutputContent};
+
+pub struct HtmlView {
+    html: String,
+    markdown_text: String,
+    markdown: View<MarkdownView>,
+}
+
+impl HtmlView {
+    pub fn from(html: String, cx: &mut ViewContext<Self>) -> Self {
+        let markdown = html_to_markdown(&html)
+            .filter(|markdown| !markdown.trim().is_empty())
+            .unwrap_or_else(|| "_This output can only be shown in a browser._".to_string());
+        Self {
+            markdown: cx.new_view(|cx| MarkdownView::from(markdown.clone(), cx)),
+            markdown_text: markdown,
+            html,
+        }
+    }
+
+    /// The output as it's shown, which reads better than the HTML it came from.
+    pub fn markdown_text(&self) -> &str {
+        &self.markdown_text
+    }
+}
+
+fn html_to_markdown(html: &str) -> Option<String> {
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(markdown::WebpageChromeRemover)),
+        Rc::new(RefCell::new(markdown::ParagraphHandler)),
+        Rc::new(RefCell::new(markdown::HeadingHandler)),
+        Rc::new(RefCell::new(markdown::ListHandler)),
+        Rc::new(RefCell::new(markdown::TableHandler::new())),
+        Rc::new(RefCell::new(markdown::StyledTextHandler)),
+        Rc::new(RefCell::new(markdown::CodeHandler)),
+    ];
+    convert_html_to_markdown(html.as_bytes(), &mut handlers)
+        .map_err(|error| log::warn!("failed to convert HTML output to Markdown: {error:#}"))
+        .ok()
+}
+
+impl Render for HtmlView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        self.markdown.clone()
+    }
+}
+
+impl OutputContent for HtmlView {
+    fn clipboard_content(&self, _cx: &WindowContext) -> Option<ClipboardItem> {
+        Some(ClipboardItem::new_string(self.html.clone()))
+    }
+
+    fn has_clipboard_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn has_html_document(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn html_document(&self, _cx: &WindowContext) -> Option<String> {
+        Some(format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body>\n{}\n</body>\n</html>\n",
+            self.html
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = indoc! {r#"
+            <h2>Results</h2>
+            <p>Training took <strong>12s</strong> with <code>lr=0.1</code>.</p>
+            <ul><li>first</li><li>second</li></ul>
+        "#};
+        let markdown = html_to_markdown(html).unwrap();
+
+        assert!(markdown.contains("## Results"), "{markdown}");
+        assert!(markdown.contains("**12s**"), "{markdown}");
+        assert!(markdown.contains("`lr=0.1`"), "{markdown}");
+        assert!(markdown.contains("- first"), "{markdown}");
+        assert!(markdown.contains("- second"), "{markdown}");
+    }
+
+    #[test]
+    fn test_dataframe_html_to_markdown() {
+        // Pandas styles its tables, and notebooks often come with scripts, neither of which can
+        // be shown.
+        let html = concat!(
+            "<style scoped>.dataframe tbody tr th { vertical-align: top; }</style>",
+            "<script>console.log(\"rendered\")</script>",
+            "<table class=\"dataframe\">",
+            "<thead><tr><th>a</th><th>b</th></tr></thead>",
+            "<tbody><tr><td>1</td><td>2</td></tr><tr><td>3</td><td>4</td></tr></tbody>",
+            "</table>",
+        );
+
+        assert_eq!(
+            html_to_markdown(html).unwrap(),
+            "| a | b |\n| --- | --- |\n| 1 | 2 |\n| 3 | 4 |"
+        );
+    }
+}
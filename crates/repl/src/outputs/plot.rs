@@ -0,0 +1,170 @@
+//! # Plot Output for REPL
+//!
+//! Interactive plots from Plotly (`application/vnd.plotly.v1+json`) and Vega or Vega-Lite
+//! (`application/vnd.vega*+json`) are drawn by JavaScript libraries that Zed can't run. When the
+//! kernel also sent a static rendering of the plot, like Plotly does with its `png` renderer, it's
+//! shown inline. Either way, the plot can be opened in the browser to interact with it.
+
+use gpui::{ClipboardItem, View, WindowContext};
+use serde_json::Value;
+use ui::{prelude::*, IntoElement};
+
+use crate::outputs::{image::ImageView, OutputContent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotKind {
+    Plotly,
+    Vega,
+    VegaLite,
+}
+
+impl PlotKind {
+    fn name(&self) -> &'static str {
+        match self {
+            PlotKind::Plotly => "Plotly",
+            PlotKind::Vega => "Vega",
+            PlotKind::VegaLite => "Vega-Lite",
+        }
+    }
+
+    /// The libraries drawing the plot, pinned so that plots keep drawing the same way.
+    fn scripts(&self) -> &'static [&'static str] {
+        match self {
+            PlotKind::Plotly => &["https://cdn.plot.ly/plotly-2.35.2.min.js"],
+            PlotKind::Vega | PlotKind::VegaLite => &[
+                "https://cdn.jsdelivr.net/npm/vega@5.30.0",
+                "https://cdn.jsdelivr.net/npm/vega-lite@5.21.0",
+                "https://cdn.jsdelivr.net/npm/vega-embed@6.26.0",
+            ],
+        }
+    }
+
+    fn draw(&self) -> &'static str {
+        match self {
+            PlotKind::Plotly => {
+                "Plotly.newPlot(\"plot\", spec.data, spec.layout || {}, spec.config || { responsive: true });"
+            }
+            PlotKind::Vega | PlotKind::VegaLite => "vegaEmbed(\"#plot\", spec);",
+        }
+    }
+}
+
+pub struct PlotView {
+    kind: PlotKind,
+    spec: Value,
+    preview: Option<View<ImageView>>,
+}
+
+impl PlotView {
+    pub fn new(
+        kind: PlotKind,
+        spec: Value,
+        preview: Option<ImageView>,
+        cx: &mut WindowContext,
+    ) -> Self {
+        Self {
+            kind,
+            spec,
+            preview: preview.map(|preview| cx.new_view(|_| preview)),
+        }
+    }
+
+    /// A standalone page that draws the plot.
+    fn document(&self) -> String {
+        // The spec is embedded in a script, which must not be closed early by a string in it.
+        let spec = self.spec.to_string().replace("</", "<\\/");
+        let scripts = self
+            .kind
+            .scripts()
+            .iter()
+            .map(|src| format!("<script src=\"{src}\"></script>\n"))
+            .collect::<String>();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n{scripts}</head>\n<body>\n\
+            <div id=\"plot\" style=\"width: 100%; height: 95vh\"></div>\n\
+            <script>\nconst spec = {spec};\n{}\n</script>\n</body>\n</html>\n",
+            self.kind.draw()
+        )
+    }
+}
+
+impl Render for PlotView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        match self.preview.clone() {
+            Some(preview) => preview.into_any_element(),
+            None => h_flex()
+                .gap_1()
+                .child(Icon::new(IconName::Globe).color(Color::Muted))
+                .child(
+                    Label::new(format!(
+                        "Interactive {} plot, open it in the browser to view it",
+                        self.kind.name()
+                    ))
+                    .color(Color::Muted),
+                )
+                .into_any_element(),
+        }
+    }
+}
+
+impl OutputContent for PlotView {
+    fn clipboard_content(&self, _cx: &WindowContext) -> Option<ClipboardItem> {
+        serde_json::to_string_pretty(&self.spec)
+            .ok()
+            .map(ClipboardItem::new_string)
+    }
+
+    fn has_clipboard_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn has_html_document(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn html_document(&self, _cx: &WindowContext) -> Option<String> {
+        Some(self.document())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_document_escapes_spec() {
+        let plot = PlotView {
+            kind: PlotKind::VegaLite,
+            spec: json!({
+                "mark": "bar",
+                "title": "</script><script>alert(1)</script>",
+            }),
+            preview: None,
+        };
+        let document = plot.document();
+
+        assert!(!document.contains("</script><script>alert(1)"));
+        assert!(document.contains(r#""title":"<\/script><script>alert(1)<\/script>""#));
+        assert_eq!(document.matches("<script").count(), 5);
+        assert_eq!(document.matches("</script>").count(), 4);
+        assert!(document.contains("https://cdn.jsdelivr.net/npm/vega-lite@5.21.0"));
+        assert!(document.contains("vegaEmbed(\"#plot\", spec);"));
+    }
+
+    #[test]
+    fn test_document_draws_plotly() {
+        let plot = PlotView {
+            kind: PlotKind::Plotly,
+            spec: json!({ "data": [{ "type": "scatter", "y": [1, 2, 3] }] }),
+            preview: None,
+        };
+        let document = plot.document();
+
+        assert!(document.contains("https://cdn.plot.ly/plotly-2.35.2.min.js"));
+        assert!(!document.contains("vega"));
+        assert!(document.contains(r#"const spec = {"data":[{"type":"scatter","y":[1,2,3]}]};"#));
+        assert!(document.contains("Plotly.newPlot("));
+    }
+}
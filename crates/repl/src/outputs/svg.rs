@@ -0,0 +1,48 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use gpui::{img, ClipboardItem, Image, ImageFormat, WindowContext};
+use ui::{div, prelude::*, IntoElement, Styled};
+
+use crate::outputs::OutputContent;
+
+/// SvgView renders an `image/svg+xml` output, which is rasterized when it's painted.
+pub struct SvgView {
+    image: Arc<Image>,
+}
+
+impl SvgView {
+    pub fn from(svg: &str) -> Self {
+        let bytes = svg.as_bytes().to_vec();
+        // Rendered images are cached by their ID, so outputs with the same contents share one.
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Self {
+            image: Arc::new(Image {
+                format: ImageFormat::Svg,
+                bytes,
+                id: hasher.finish(),
+            }),
+        }
+    }
+}
+
+impl Render for SvgView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .max_w_full()
+            .child(img(self.image.clone()).max_w_full())
+    }
+}
+
+impl OutputContent for SvgView {
+    fn clipboard_content(&self, _cx: &WindowContext) -> Option<ClipboardItem> {
+        Some(ClipboardItem::new_image(self.image.as_ref()))
+    }
+
+    fn has_clipboard_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+}
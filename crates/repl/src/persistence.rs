@@ -0,0 +1,170 @@
+//! Saving the outputs and history of REPL sessions, so that they are shown again when a
+//! workspace is reopened.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+use editor::Editor;
+use gpui::{AppContext, View};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use workspace::{WorkspaceDb, WorkspaceId};
+
+/// The outputs of an execution are only saved when their JSON is smaller than this, so that
+/// large images or tables don't bloat the database.
+const MAX_SAVED_OUTPUTS_LEN: usize = 256 * 1024;
+
+/// The part of a session that outlives its kernel.
+#[derive(Serialize, Deserialize)]
+pub struct SerializedSession {
+    /// The code that has outputs below it in the editor.
+    pub cells: Vec<SerializedCell>,
+    /// The most recent executions, oldest first.
+    pub history: Vec<SerializedExecution>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedCell {
+    /// Where the code started, to find it again when the file has changed since.
+    pub row: u32,
+    pub column: u32,
+    pub execution: SerializedExecution,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedExecution {
+    pub code: String,
+    pub execution_count: Option<u64>,
+    /// Outputs in the notebook format.
+    pub outputs: Vec<Value>,
+}
+
+impl SerializedSession {
+    /// The JSON the session is saved as, without the outputs that are too large to save.
+    pub fn to_json(mut self) -> Result<String> {
+        for cell in &mut self.cells {
+            cell.execution.drop_large_outputs();
+        }
+        self.cells.retain(|cell| !cell.execution.outputs.is_empty());
+        for execution in &mut self.history {
+            execution.drop_large_outputs();
+        }
+        Ok(serde_json::to_string(&self)?)
+    }
+}
+
+impl SerializedExecution {
+    fn drop_large_outputs(&mut self) {
+        let len = self
+            .outputs
+            .iter()
+            .map(|output| serde_json::to_string(output).map_or(0, |json| json.len()))
+            .sum::<usize>();
+        if len > MAX_SAVED_OUTPUTS_LEN {
+            self.outputs.clear();
+        }
+    }
+
+    pub fn notebook_outputs(&self) -> Vec<nbformat::v4::Output> {
+        self.outputs
+            .iter()
+            .filter_map(|output| serde_json::from_value(output.clone()).ok())
+            .collect()
+    }
+}
+
+/// The workspace and file that the session of an editor is saved for.
+pub fn session_key(editor: &View<Editor>, cx: &AppContext) -> Option<(WorkspaceId, PathBuf)> {
+    let editor = editor.read(cx);
+    let workspace_id = editor.workspace()?.read(cx).database_id()?;
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some((workspace_id, path))
+}
+
+define_connection! {
+    pub static ref REPL_DB: ReplDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE repl_sessions (
+                workspace_id INTEGER,
+                path BLOB NOT NULL,
+                contents TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, path),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl ReplDb {
+    query! {
+        pub async fn save_session(
+            workspace_id: WorkspaceId,
+            path: PathBuf,
+            contents: String
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO repl_sessions(workspace_id, path, contents)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn delete_session(workspace_id: WorkspaceId, path: PathBuf) -> Result<()> {
+            DELETE FROM repl_sessions
+            WHERE workspace_id = ? AND path = ?
+        }
+    }
+
+    query! {
+        pub fn get_session(workspace_id: WorkspaceId, path: PathBuf) -> Result<Option<String>> {
+            SELECT contents
+            FROM repl_sessions
+            WHERE workspace_id = ? AND path = ?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_large_outputs_are_not_saved() {
+        let execution = |code: &str, text: String| SerializedExecution {
+            code: code.to_string(),
+            execution_count: Some(1),
+            outputs: vec![json!({ "output_type": "stream", "name": "stdout", "text": text })],
+        };
+        let large_text = "x".repeat(MAX_SAVED_OUTPUTS_LEN);
+        let session = SerializedSession {
+            cells: vec![
+                SerializedCell {
+                    row: 0,
+                    column: 0,
+                    execution: execution("small", "1\n".to_string()),
+                },
+                SerializedCell {
+                    row: 1,
+                    column: 0,
+                    execution: execution("large", large_text.clone()),
+                },
+            ],
+            history: vec![execution("large", large_text)],
+        };
+
+        let saved: SerializedSession = serde_json::from_str(&session.to_json().unwrap()).unwrap();
+        assert_eq!(
+            saved
+                .cells
+                .iter()
+                .map(|cell| cell.execution.code.as_str())
+                .collect::<Vec<_>>(),
+            ["small"]
+        );
+        assert_eq!(saved.history.len(), 1);
+        assert_eq!(saved.history[0].code, "large");
+        assert!(saved.history[0].outputs.is_empty());
+    }
+}
@@ -1,8 +1,12 @@
+mod completions;
 pub mod components;
+mod dataframe;
 mod jupyter_settings;
+mod jupytext;
 pub mod kernels;
 pub mod notebook;
 mod outputs;
+mod persistence;
 mod repl_editor;
 mod repl_sessions_ui;
 mod repl_store;
@@ -16,14 +20,16 @@ use project::Fs;
 pub use runtimelib::ExecutionState;
 use settings::Settings as _;
 
+pub use crate::dataframe::{view_dataframe, DataFrameViewer, ViewDataFrame};
 pub use crate::jupyter_settings::JupyterSettings;
 pub use crate::kernels::{Kernel, KernelSpecification, KernelStatus};
 pub use crate::repl_editor::*;
 pub use crate::repl_sessions_ui::{
-    ClearOutputs, Interrupt, ReplSessionsPage, Restart, Run, Sessions, Shutdown,
+    ClearOutputs, ExportNotebook, ImportNotebook, Interrupt, NextCell, PreviousCell,
+    ReplSessionsPage, Restart, RestartAndRunAll, Run, RunCellsAbove, Sessions, Shutdown,
 };
 use crate::repl_store::ReplStore;
-pub use crate::session::Session;
+pub use crate::session::{ExecutionRecord, Session};
 
 pub const KERNEL_DOCS_URL: &str = "https://zed.dev/docs/repl#changing-kernels";
 
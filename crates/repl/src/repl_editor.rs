@@ -4,15 +4,27 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use editor::Editor;
-use gpui::{prelude::*, Entity, View, WeakView, WindowContext};
+use editor::{scroll::Autoscroll, Editor};
+use futures::{channel::mpsc, StreamExt as _};
+use gpui::{
+    prelude::*, rems, Entity, PathPromptOptions, PromptLevel, Task, View, ViewContext, WeakView,
+    WindowContext,
+};
 use language::{BufferSnapshot, Language, LanguageName, Point};
-use project::{ProjectItem as _, WorktreeId};
-
+use picker::Picker;
+use project::{DirectoryLister, ProjectItem as _, WorktreeId};
+use theme::ActiveTheme as _;
+use util::{maybe, ResultExt as _};
+use workspace::{notifications::DetachAndPromptErr, Workspace};
+
+use crate::components::KernelPickerDelegate;
+use crate::jupytext::{self, NotebookCell, ScriptCellKind};
+use crate::persistence::{self, SerializedSession, REPL_DB};
 use crate::repl_store::ReplStore;
 use crate::session::SessionEvent;
 use crate::{
-    ClearOutputs, Interrupt, JupyterSettings, KernelSpecification, Restart, Session, Shutdown,
+    ClearOutputs, Interrupt, JupyterSettings, Kernel, KernelSpecification, Restart, Session,
+    Shutdown,
 };
 
 pub fn assign_kernelspec(
@@ -27,8 +39,10 @@ pub fn assign_kernelspec(
 
     let worktree_id = crate::repl_editor::worktree_id_for_editor(weak_editor.clone(), cx)
         .context("editor is not in a worktree")?;
+    let editor = weak_editor.upgrade().context("editor was dropped")?;
 
     store.update(cx, |store, cx| {
+        store.set_kernelspec_for_editor(&editor, kernel_specification.clone(), cx);
         store.set_active_kernelspec(worktree_id, kernel_specification.clone(), cx);
     });
 
@@ -80,6 +94,68 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
     let selected_range = editor
         .update(cx, |editor, cx| editor.selections.newest_adjusted(cx))
         .range();
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return Ok(());
+    };
+
+    let (runnable_ranges, next_cell_point) =
+        runnable_ranges(&buffer.read(cx).snapshot(), selected_range);
+    run_ranges(&editor, runnable_ranges, next_cell_point, move_down, cx)
+}
+
+/// Runs every `%%` cell above the one containing the cursor.
+pub fn run_cells_above(editor: WeakView<Editor>, cx: &mut WindowContext) -> Result<()> {
+    let store = ReplStore::global(cx);
+    if !store.read(cx).is_enabled() {
+        return Ok(());
+    }
+
+    let editor = editor.upgrade().context("editor was dropped")?;
+    let cursor_row = editor
+        .update(cx, |editor, cx| editor.selections.newest::<Point>(cx))
+        .head()
+        .row;
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return Ok(());
+    };
+
+    let snapshot = buffer.read(cx).snapshot();
+    let Some(range) = cells_above(&snapshot, cursor_row) else {
+        return Ok(());
+    };
+    let (runnable_ranges, _) = jupytext_cells(&snapshot, range);
+    run_ranges(&editor, runnable_ranges, None, false, cx)
+}
+
+/// Restarts the editor's kernel, or starts one if it has none, and runs all of the editor's
+/// cells in it.
+pub fn restart_and_run_all(editor: WeakView<Editor>, cx: &mut WindowContext) -> Result<()> {
+    let store = ReplStore::global(cx);
+    if !store.read(cx).is_enabled() {
+        return Ok(());
+    }
+
+    let editor = editor.upgrade().context("editor was dropped")?;
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return Ok(());
+    };
+
+    if let Some(session) = store.read(cx).get_session(editor.entity_id()).cloned() {
+        session.update(cx, |session, cx| session.restart(cx));
+    }
+
+    let runnable_ranges = all_cells(&buffer.read(cx).snapshot());
+    run_ranges(&editor, runnable_ranges, None, false, cx)
+}
+
+fn run_ranges(
+    editor: &View<Editor>,
+    runnable_ranges: Vec<Range<Point>>,
+    next_cell_point: Option<Point>,
+    move_down: bool,
+    cx: &mut WindowContext,
+) -> Result<()> {
+    let store = ReplStore::global(cx);
     let multibuffer = editor.read(cx).buffer().clone();
     let Some(buffer) = multibuffer.read(cx).as_singleton() else {
         return Ok(());
@@ -89,50 +165,43 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
         return Ok(());
     };
 
-    let (runnable_ranges, next_cell_point) =
-        runnable_ranges(&buffer.read(cx).snapshot(), selected_range);
-
-    for runnable_range in runnable_ranges {
+    for (ix, runnable_range) in runnable_ranges.iter().cloned().enumerate() {
         let Some(language) = multibuffer.read(cx).language_at(runnable_range.start, cx) else {
             continue;
         };
 
+        if store.read(cx).get_session(editor.entity_id()).is_none() {
+            let ambiguous_kernelspecs = store.read(cx).ambiguous_kernelspecs(
+                editor.entity_id(),
+                project_path.worktree_id,
+                &language,
+                cx,
+            );
+            if let Some(kernelspecs) = ambiguous_kernelspecs {
+                let runnable_ranges = runnable_ranges[ix..].to_vec();
+                pick_kernel_and_run(
+                    editor,
+                    kernelspecs,
+                    runnable_ranges,
+                    next_cell_point,
+                    move_down,
+                    cx,
+                );
+                return Ok(());
+            }
+        }
+
         let kernel_specification = store
             .read(cx)
-            .active_kernelspec(project_path.worktree_id, Some(language.clone()), cx)
+            .kernelspec_for_editor(
+                editor.entity_id(),
+                project_path.worktree_id,
+                language.clone(),
+                cx,
+            )
             .ok_or_else(|| anyhow::anyhow!("No kernel found for language: {}", language.name()))?;
 
-        let fs = store.read(cx).fs().clone();
-
-        let session = if let Some(session) = store.read(cx).get_session(editor.entity_id()).cloned()
-        {
-            session
-        } else {
-            let weak_editor = editor.downgrade();
-            let session = cx.new_view(|cx| Session::new(weak_editor, fs, kernel_specification, cx));
-
-            editor.update(cx, |_editor, cx| {
-                cx.notify();
-
-                cx.subscribe(&session, {
-                    let store = store.clone();
-                    move |_this, _session, event, cx| match event {
-                        SessionEvent::Shutdown(shutdown_event) => {
-                            store.update(cx, |store, _cx| {
-                                store.remove_session(shutdown_event.entity_id());
-                            });
-                        }
-                    }
-                })
-                .detach();
-            });
-
-            store.update(cx, |store, _cx| {
-                store.insert_session(editor.entity_id(), session.clone());
-            });
-
-            session
-        };
+        let session = get_or_start_session(editor, kernel_specification, cx);
 
         let selected_text;
         let anchor_range;
@@ -155,6 +224,542 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
     anyhow::Ok(())
 }
 
+/// Asks which of the kernels to run the code on, and runs it once one is picked.
+fn pick_kernel_and_run(
+    editor: &View<Editor>,
+    kernelspecs: Vec<KernelSpecification>,
+    runnable_ranges: Vec<Range<Point>>,
+    next_cell_point: Option<Point>,
+    move_down: bool,
+    cx: &mut WindowContext,
+) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+
+    let weak_editor = editor.downgrade();
+    let on_select = Box::new(move |kernelspec, cx: &mut WindowContext| {
+        let Some(editor) = weak_editor.upgrade() else {
+            return;
+        };
+        assign_kernelspec(kernelspec, weak_editor.clone(), cx).log_err();
+        run_ranges(
+            &editor,
+            runnable_ranges.clone(),
+            next_cell_point,
+            move_down,
+            cx,
+        )
+        .log_err();
+    });
+    workspace.update(cx, |workspace, cx| {
+        workspace.toggle_modal(cx, |cx| {
+            Picker::uniform_list(KernelPickerDelegate::new(kernelspecs, on_select), cx)
+                .width(rems(30.))
+                .max_height(Some(rems(20.).into()))
+        });
+    });
+}
+
+/// Moves the cursor to the `%%` marker of the cell after the one containing it.
+pub fn move_to_next_cell(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let cursor_row = editor.selections.newest::<Point>(cx).head().row;
+    if let Some(row) = next_cell_row(&buffer.read(cx).snapshot(), cursor_row) {
+        move_to_row(editor, row, cx);
+    }
+}
+
+/// Moves the cursor to the `%%` marker of the cell before the one containing it.
+pub fn move_to_previous_cell(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let cursor_row = editor.selections.newest::<Point>(cx).head().row;
+    if let Some(row) = previous_cell_row(&buffer.read(cx).snapshot(), cursor_row) {
+        move_to_row(editor, row, cx);
+    }
+}
+
+fn move_to_row(editor: &mut Editor, row: u32, cx: &mut ViewContext<Editor>) {
+    let point = Point::new(row, 0);
+    editor.change_selections(Some(Autoscroll::top_relative(8)), cx, |selections| {
+        selections.select_ranges([point..point]);
+    });
+}
+
+/// Highlights the lines of an editor's `%%` cell markers, so that its cells stand apart.
+///
+/// Only the buffers in a language that a kernel runs, or with a session, are scanned for markers,
+/// and only the rows edited since the last scan are scanned again.
+#[derive(Default)]
+pub struct CellSeparators {
+    scanned_version: Option<clock::Global>,
+}
+
+enum CellSeparator {}
+
+impl CellSeparators {
+    /// Scans the whole buffer again, e.g. when its language changed.
+    pub fn rescan(&mut self, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        self.scanned_version = None;
+        editor.clear_row_highlights::<CellSeparator>();
+        self.refresh(editor, cx);
+    }
+
+    pub fn refresh(&mut self, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let buffer = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .map(|buffer| buffer.read(cx).snapshot())
+            .filter(|buffer| {
+                let store = ReplStore::global(cx).read(cx);
+                store.get_session(cx.view().entity_id()).is_some()
+                    || buffer
+                        .language()
+                        .is_some_and(|language| store.has_kernel_for_language(language))
+            });
+        let Some(buffer) = buffer else {
+            if self.scanned_version.take().is_some() {
+                editor.clear_row_highlights::<CellSeparator>();
+                cx.notify();
+            }
+            return;
+        };
+
+        let edited_rows = match &self.scanned_version {
+            Some(scanned_version) => {
+                let mut edited_rows = Vec::<Range<u32>>::new();
+                let max_row = buffer.max_point().row;
+                for edit in buffer.edits_since::<Point>(scanned_version) {
+                    let rows = edit.new.start.row.min(max_row)..edit.new.end.row.min(max_row) + 1;
+                    match edited_rows.last_mut() {
+                        Some(last_rows) if last_rows.end >= rows.start => {
+                            last_rows.end = last_rows.end.max(rows.end)
+                        }
+                        _ => edited_rows.push(rows),
+                    }
+                }
+                edited_rows
+            }
+            None => {
+                editor.clear_row_highlights::<CellSeparator>();
+                vec![all_rows(&buffer)]
+            }
+        };
+        self.scanned_version = Some(buffer.version().clone());
+        if edited_rows.is_empty() {
+            return;
+        }
+
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let row_anchors = |rows: &Range<u32>| {
+            let end_row = rows.end - 1;
+            snapshot.anchor_before(Point::new(rows.start, 0))
+                ..snapshot.anchor_after(Point::new(end_row, buffer.line_len(end_row)))
+        };
+        editor.remove_highlighted_rows::<CellSeparator>(
+            edited_rows.iter().map(row_anchors).collect(),
+            cx,
+        );
+        let color = cx.theme().colors().editor_subheader_background;
+        for rows in edited_rows {
+            for row in cell_marker_rows(&buffer, rows) {
+                editor.highlight_rows::<CellSeparator>(
+                    row_anchors(&(row..row + 1)),
+                    color,
+                    false,
+                    cx,
+                );
+            }
+        }
+        cx.notify();
+    }
+}
+
+/// The editor's session, which is started with the given kernel if the editor has none yet.
+fn get_or_start_session(
+    editor: &View<Editor>,
+    kernel_specification: KernelSpecification,
+    cx: &mut WindowContext,
+) -> View<Session> {
+    let store = ReplStore::global(cx);
+    if let Some(session) = store.read(cx).get_session(editor.entity_id()).cloned() {
+        // Sessions restored from a previous run only start their kernel once code is run in them,
+        // on the kernel the code runs on now.
+        session.update(cx, |session, cx| {
+            if let Kernel::Shutdown = session.kernel {
+                session.kernel_specification = kernel_specification;
+                session.start_kernel(cx);
+            }
+        });
+        return session;
+    }
+
+    let fs = store.read(cx).fs().clone();
+    let weak_editor = editor.downgrade();
+    let session = cx.new_view(|cx| Session::new(weak_editor, fs, kernel_specification, cx));
+    insert_session(editor, session.clone(), cx);
+    session
+}
+
+fn insert_session(editor: &View<Editor>, session: View<Session>, cx: &mut WindowContext) {
+    let store = ReplStore::global(cx);
+    editor.update(cx, |_editor, cx| {
+        cx.notify();
+
+        cx.subscribe(&session, {
+            let store = store.clone();
+            move |_this, _session, event, cx| match event {
+                SessionEvent::Shutdown(shutdown_event) => {
+                    store.update(cx, |store, _cx| {
+                        store.remove_session(shutdown_event.entity_id());
+                    });
+                }
+            }
+        })
+        .detach();
+    });
+
+    store.update(cx, |store, _cx| {
+        store.insert_session(editor.entity_id(), session);
+    });
+}
+
+/// Shows the outputs and history that were saved for the editor's file the last time the
+/// workspace was open, without starting a kernel.
+///
+/// The session is only created once the editor's language is detected and a kernel is found
+/// for it, which can be after the editor is opened, so it is retried whenever the kernels or the
+/// editor's language change.
+pub fn restore_session(editor: WeakView<Editor>, cx: &mut WindowContext) -> Task<Result<()>> {
+    cx.spawn(|mut cx| async move {
+        let session_key = cx.update(|cx| {
+            editor
+                .upgrade()
+                .and_then(|editor| persistence::session_key(&editor, cx))
+        })?;
+        let Some((workspace_id, path)) = session_key else {
+            return Ok(());
+        };
+
+        let contents = cx
+            .background_executor()
+            .spawn(async move { REPL_DB.get_session(workspace_id, path) })
+            .await?;
+        let Some(contents) = contents else {
+            return Ok(());
+        };
+        let mut serialized = Some(serde_json::from_str::<SerializedSession>(&contents)?);
+
+        let (changed_tx, mut changed_rx) = mpsc::unbounded();
+        let _subscriptions = cx.update(|cx| {
+            let editor = editor.upgrade().context("editor was dropped")?;
+            let store = ReplStore::global(cx);
+            let buffer = editor.read(cx).buffer().clone();
+            anyhow::Ok([
+                cx.observe(&store, {
+                    let changed_tx = changed_tx.clone();
+                    move |_, _| {
+                        changed_tx.unbounded_send(()).ok();
+                    }
+                }),
+                cx.subscribe(&buffer, {
+                    let changed_tx = changed_tx.clone();
+                    move |_, event, _| {
+                        if let multi_buffer::Event::LanguageChanged(_) = event {
+                            changed_tx.unbounded_send(()).ok();
+                        }
+                    }
+                }),
+                cx.observe_release(&editor, move |_, _| {
+                    changed_tx.unbounded_send(()).ok();
+                }),
+            ])
+        })??;
+
+        loop {
+            let restored = cx.update(|cx| {
+                let store = ReplStore::global(cx);
+                let Some(editor) = editor.upgrade() else {
+                    return true;
+                };
+                if !store.read(cx).is_enabled()
+                    || store.read(cx).get_session(editor.entity_id()).is_some()
+                {
+                    return true;
+                }
+
+                let Some(kernel_specification) = maybe!({
+                    let language = get_language(editor.downgrade(), cx)?;
+                    let worktree_id = worktree_id_for_editor(editor.downgrade(), cx)?;
+                    store.read(cx).kernelspec_for_editor(
+                        editor.entity_id(),
+                        worktree_id,
+                        language,
+                        cx,
+                    )
+                }) else {
+                    return false;
+                };
+
+                let Some(serialized) = serialized.take() else {
+                    return true;
+                };
+                let fs = store.read(cx).fs().clone();
+                let weak_editor = editor.downgrade();
+                let session = cx.new_view(|cx| {
+                    Session::restored(weak_editor, fs, kernel_specification, serialized, cx)
+                });
+                insert_session(&editor, session, cx);
+                true
+            })?;
+            if restored || changed_rx.next().await.is_none() {
+                return Ok(());
+            }
+        }
+    })
+}
+
+/// Writes the editor's cells, along with the outputs of the code run in them, to a Jupyter
+/// notebook next to the editor's file.
+pub fn export_notebook(editor: WeakView<Editor>, cx: &mut WindowContext) -> Task<Result<()>> {
+    let notebook = maybe!({
+        let editor = editor.upgrade().context("editor was dropped")?;
+        let buffer = editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .context("editor has more than one buffer")?;
+        let path = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+            .context("buffer is not saved to a local file")?
+            .with_extension("ipynb");
+        let snapshot = buffer.read(cx).snapshot();
+        let language = snapshot
+            .language()
+            .cloned()
+            .context("buffer has no language")?;
+        let comment_prefix = comment_prefix(&language).with_context(|| {
+            format!(
+                "{} has no line comments to mark cells with",
+                language.name()
+            )
+        })?;
+        let text = snapshot.text();
+
+        let store = ReplStore::global(cx);
+        let session = store.read(cx).get_session(editor.entity_id()).cloned();
+        let cells = jupytext::script_cells(&text, &comment_prefix)
+            .into_iter()
+            .map(|cell| {
+                let (execution_count, outputs) = match (&session, cell.kind) {
+                    (Some(session), ScriptCellKind::Code) => {
+                        session.read(cx).notebook_outputs(cell.range.clone(), cx)
+                    }
+                    _ => (None, Vec::new()),
+                };
+                NotebookCell {
+                    kind: cell.kind,
+                    source: jupytext::cell_source(&text, &cell, &comment_prefix),
+                    execution_count,
+                    outputs,
+                }
+            })
+            .collect();
+
+        let kernel_specification = match &session {
+            Some(session) => Some(session.read(cx).kernel_specification.clone()),
+            None => worktree_id_for_editor(editor.downgrade(), cx).and_then(|worktree_id| {
+                store.read(cx).kernelspec_for_editor(
+                    editor.entity_id(),
+                    worktree_id,
+                    language.clone(),
+                    cx,
+                )
+            }),
+        };
+        let notebook =
+            jupytext::notebook_json(cells, kernel_specification.as_ref(), &language.name().0);
+        anyhow::Ok((path, serde_json::to_string_pretty(&notebook)?))
+    });
+
+    let fs = ReplStore::global(cx).read(cx).fs().clone();
+    cx.spawn(|mut cx| async move {
+        let (path, notebook) = notebook?;
+        if fs.is_file(&path).await {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let answer = cx.prompt(
+                PromptLevel::Warning,
+                &format!("Replace {file_name}?"),
+                Some("A notebook with this name already exists."),
+                &["Replace", "Cancel"],
+            );
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+        }
+        fs.atomic_write(path, notebook).await
+    })
+}
+
+/// Opens a Jupyter notebook as a script with cell markers, saved next to the notebook, and shows
+/// the notebook's outputs below its cells.
+pub fn import_notebook(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    let paths = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Project(project.clone()),
+        cx,
+    );
+    let fs = ReplStore::global(cx).read(cx).fs().clone();
+    let languages = project.read(cx).languages().clone();
+
+    cx.spawn(|workspace, mut cx| async move {
+        let Some(notebook_path) = paths
+            .await
+            .ok()
+            .flatten()
+            .and_then(|paths| paths.into_iter().next())
+        else {
+            return Ok(());
+        };
+
+        let notebook = match nbformat::parse_notebook(&fs.load(&notebook_path).await?) {
+            Ok(nbformat::Notebook::V4(notebook)) => notebook,
+            Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+                nbformat::upgrade_legacy_notebook(legacy_notebook)?
+            }
+            Err(error) => anyhow::bail!("Failed to parse notebook: {:?}", error),
+        };
+        let language_name = notebook
+            .metadata
+            .language_info
+            .as_ref()
+            .map(|language_info| language_info.name.clone())
+            .or_else(|| {
+                notebook
+                    .metadata
+                    .kernelspec
+                    .as_ref()
+                    .and_then(|kernelspec| kernelspec.language.clone())
+            })
+            .context("notebook doesn't say which language it's in")?;
+        let language = languages.language_for_name(&language_name).await?;
+        let comment_prefix = comment_prefix(&language).with_context(|| {
+            format!(
+                "{} has no line comments to mark cells with",
+                language.name()
+            )
+        })?;
+        let extension = language
+            .path_suffixes()
+            .first()
+            .with_context(|| format!("{} has no file extension", language.name()))?;
+
+        let script = jupytext::notebook_to_script(&notebook, &comment_prefix);
+        let script_path = notebook_path.with_extension(extension);
+        anyhow::ensure!(
+            !fs.is_file(&script_path).await,
+            "{} already exists",
+            script_path.display()
+        );
+
+        // Outputs are shown in a session, which needs a kernel to run the script's code later on,
+        // so a notebook whose outputs can't be shown isn't imported at all.
+        let kernel_specification = cx.update(|cx| {
+            let store = ReplStore::global(cx);
+            if !store.read(cx).is_enabled()
+                || script
+                    .code_cells
+                    .iter()
+                    .all(|(_, _, outputs)| outputs.is_empty())
+            {
+                return Ok(None);
+            }
+            let worktree_id = project
+                .read(cx)
+                .find_worktree(&notebook_path, cx)
+                .map(|(worktree, _)| worktree.read(cx).id())
+                .context("notebook is not in the project")?;
+            store
+                .read(cx)
+                .active_kernelspec(worktree_id, Some(language.clone()), cx)
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("No kernel found for language: {}", language.name()))
+        })??;
+
+        fs.atomic_write(script_path.clone(), script.text).await?;
+
+        let item = workspace
+            .update(&mut cx, |workspace, cx| {
+                workspace.open_abs_path(script_path, true, cx)
+            })?
+            .await?;
+        cx.update(|cx| {
+            let editor = item
+                .act_as::<Editor>(cx)
+                .context("notebook script didn't open in an editor")?;
+            if let Some(kernel_specification) = kernel_specification {
+                restore_outputs(&editor, kernel_specification, script.code_cells, cx);
+            }
+            anyhow::Ok(())
+        })?
+    })
+    .detach_and_prompt_err("Failed to import notebook", cx, |_, _| None);
+}
+
+/// Shows outputs below the editor's cells, in the editor's session or in a new one whose kernel is
+/// only started once code is run in it.
+fn restore_outputs(
+    editor: &View<Editor>,
+    kernel_specification: KernelSpecification,
+    code_cells: Vec<(Range<usize>, Option<i32>, Vec<nbformat::v4::Output>)>,
+    cx: &mut WindowContext,
+) {
+    let store = ReplStore::global(cx);
+    let session = match store.read(cx).get_session(editor.entity_id()).cloned() {
+        Some(session) => session,
+        None => {
+            let fs = store.read(cx).fs().clone();
+            let weak_editor = editor.downgrade();
+            let session = cx
+                .new_view(|cx| Session::without_kernel(weak_editor, fs, kernel_specification, cx));
+            insert_session(editor, session.clone(), cx);
+            session
+        }
+    };
+
+    let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+    let cells = code_cells
+        .into_iter()
+        .map(|(range, execution_count, outputs)| {
+            let anchor_range =
+                snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+            (anchor_range, execution_count, outputs)
+        })
+        .collect();
+    session.update(cx, |session, cx| session.restore_outputs(cells, cx));
+}
+
+fn comment_prefix(language: &Arc<Language>) -> Option<String> {
+    language
+        .default_scope()
+        .line_comment_prefixes()
+        .first()
+        .map(|prefix| prefix.to_string())
+}
+
 #[allow(clippy::large_enum_variant)]
 pub enum SessionSupport {
     ActiveSession(View<Session>),
@@ -197,9 +802,10 @@ pub fn session(editor: WeakView<Editor>, cx: &mut WindowContext) -> SessionSuppo
         return SessionSupport::Unsupported;
     };
 
-    let kernelspec = store
-        .read(cx)
-        .active_kernelspec(worktree_id, Some(language.clone()), cx);
+    let kernelspec =
+        store
+            .read(cx)
+            .kernelspec_for_editor(entity_id, worktree_id, language.clone(), cx);
 
     match kernelspec {
         Some(kernelspec) => SessionSupport::Inactive(kernelspec),
@@ -213,6 +819,32 @@ pub fn session(editor: WeakView<Editor>, cx: &mut WindowContext) -> SessionSuppo
     }
 }
 
+/// The session of the workspace's active editor, or else the one of the
+/// workspace's sessions that ran code most recently.
+pub fn session_for_workspace(
+    workspace: &View<Workspace>,
+    cx: &WindowContext,
+) -> Option<View<Session>> {
+    let store = ReplStore::global(cx);
+    let store = store.read(cx);
+    let active_editor = workspace
+        .read(cx)
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx));
+    if let Some(session) =
+        active_editor.and_then(|editor| store.get_session(editor.entity_id()).cloned())
+    {
+        return Some(session);
+    }
+
+    store
+        .sessions()
+        .filter(|session| session.read(cx).is_in_workspace(workspace, cx))
+        .filter_map(|session| Some((session.read(cx).last_executed_at()?, session)))
+        .max_by_key(|(last_executed_at, _)| *last_executed_at)
+        .map(|(_, session)| session.clone())
+}
+
 pub fn clear_outputs(editor: WeakView<Editor>, cx: &mut WindowContext) {
     let store = ReplStore::global(cx);
     let entity_id = editor.entity_id();
@@ -238,6 +870,18 @@ pub fn interrupt(editor: WeakView<Editor>, cx: &mut WindowContext) {
     });
 }
 
+pub fn cancel_queued_executions(editor: WeakView<Editor>, cx: &mut WindowContext) {
+    let store = ReplStore::global(cx);
+    let entity_id = editor.entity_id();
+    let Some(session) = store.read(cx).get_session(entity_id).cloned() else {
+        return;
+    };
+
+    session.update(cx, |session, cx| {
+        session.cancel_queued_executions(cx);
+    });
+}
+
 pub fn shutdown(editor: WeakView<Editor>, cx: &mut WindowContext) {
     let store = ReplStore::global(cx);
     let entity_id = editor.entity_id();
@@ -334,6 +978,81 @@ fn cell_range(buffer: &BufferSnapshot, start_row: u32, end_row: u32) -> Range<Po
     Point::new(start_row, 0)..Point::new(snippet_end_row, buffer.line_len(snippet_end_row))
 }
 
+fn jupytext_prefixes(buffer: &BufferSnapshot) -> Vec<String> {
+    let Some(language) = buffer.language() else {
+        return Vec::new();
+    };
+
+    language
+        .default_scope()
+        .line_comment_prefixes()
+        .iter()
+        .map(|comment_prefix| format!("{comment_prefix}%%"))
+        .collect()
+}
+
+// Returns the rows of the buffer's `%%` cell markers within the given rows
+fn cell_marker_rows(buffer: &BufferSnapshot, rows: Range<u32>) -> Vec<u32> {
+    let jupytext_prefixes = jupytext_prefixes(buffer);
+    if jupytext_prefixes.is_empty() {
+        return Vec::new();
+    }
+
+    (rows.start..rows.end.min(buffer.max_point().row + 1))
+        .filter(|row| {
+            jupytext_prefixes
+                .iter()
+                .any(|prefix| buffer.contains_str_at(Point::new(*row, 0), prefix))
+        })
+        .collect()
+}
+
+fn all_rows(buffer: &BufferSnapshot) -> Range<u32> {
+    0..buffer.max_point().row + 1
+}
+
+fn next_cell_row(buffer: &BufferSnapshot, row: u32) -> Option<u32> {
+    cell_marker_rows(buffer, all_rows(buffer))
+        .into_iter()
+        .find(|marker_row| *marker_row > row)
+}
+
+fn previous_cell_row(buffer: &BufferSnapshot, row: u32) -> Option<u32> {
+    let marker_rows = cell_marker_rows(buffer, all_rows(buffer));
+    let current_cell = marker_rows
+        .iter()
+        .rposition(|marker_row| *marker_row <= row)?;
+    marker_rows.get(current_cell.checked_sub(1)?).copied()
+}
+
+// Returns the range from the first cell up to the cell containing the row, if there are cells above it
+fn cells_above(buffer: &BufferSnapshot, row: u32) -> Option<Range<Point>> {
+    let marker_rows = cell_marker_rows(buffer, all_rows(buffer));
+    let first_row = *marker_rows.first()?;
+    let current_row = *marker_rows
+        .iter()
+        .rev()
+        .find(|marker_row| **marker_row <= row)?;
+    (current_row > first_row).then(|| Point::new(first_row, 0)..Point::new(current_row - 1, 0))
+}
+
+// Returns the ranges of all of the buffer's cells, including the code before the first `%%` marker
+fn all_cells(buffer: &BufferSnapshot) -> Vec<Range<Point>> {
+    let max_point = buffer.max_point();
+    let Some(first_row) = cell_marker_rows(buffer, all_rows(buffer)).first().copied() else {
+        let (runnable_ranges, _) = runnable_ranges(buffer, Point::zero()..max_point);
+        return runnable_ranges;
+    };
+
+    let mut cells = Vec::new();
+    if (0..first_row).any(|row| !buffer.is_line_blank(row)) {
+        cells.push(cell_range(buffer, 0, first_row - 1));
+    }
+    let (jupytext_cells, _) = jupytext_cells(buffer, Point::new(first_row, 0)..max_point);
+    cells.extend(jupytext_cells);
+    cells
+}
+
 // Returns the ranges of the snippets in the buffer and the next point for moving the cursor to
 fn jupytext_cells(
     buffer: &BufferSnapshot,
@@ -341,21 +1060,11 @@ fn jupytext_cells(
 ) -> (Vec<Range<Point>>, Option<Point>) {
     let mut current_row = range.start.row;
 
-    let Some(language) = buffer.language() else {
-        return (Vec::new(), None);
-    };
-
-    let default_scope = language.default_scope();
-    let comment_prefixes = default_scope.line_comment_prefixes();
-    if comment_prefixes.is_empty() {
+    let jupytext_prefixes = jupytext_prefixes(buffer);
+    if jupytext_prefixes.is_empty() {
         return (Vec::new(), None);
     }
 
-    let jupytext_prefixes = comment_prefixes
-        .iter()
-        .map(|comment_prefix| format!("{comment_prefix}%%"))
-        .collect::<Vec<_>>();
-
     let mut snippet_start_row = None;
     loop {
         if jupytext_prefixes
@@ -462,9 +1171,16 @@ fn get_language(editor: WeakView<Editor>, cx: &mut WindowContext) -> Option<Arc<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::{AppContext, Context};
+    use crate::kernels::LocalKernelSpecification;
+    use gpui::{AppContext, Context, Model};
     use indoc::indoc;
-    use language::{Buffer, Language, LanguageConfig, LanguageRegistry};
+    use jupyter_protocol::JupyterKernelspec;
+    use language::{Buffer, Language, LanguageConfig, LanguageMatcher, LanguageRegistry};
+    use picker::PickerDelegate as _;
+    use project::Project;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use workspace::AppState;
 
     #[gpui::test]
     fn test_snippet_ranges(cx: &mut AppContext) {
@@ -610,6 +1326,114 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_cell_navigation(cx: &mut AppContext) {
+        let test_language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "TestLang".into(),
+                line_comments: vec!["# ".into()],
+                ..Default::default()
+            },
+            None,
+        ));
+
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(
+                indoc! { r#"
+                    import math
+
+                    # %%
+                    x = 1
+
+                    # %% [markdown]
+                    # Some notes
+
+                    # %%
+                    print(x)
+                "# },
+                cx,
+            )
+            .with_language(test_language, cx)
+        });
+        let snapshot = buffer.read(cx).snapshot();
+
+        assert_eq!(
+            cell_marker_rows(&snapshot, all_rows(&snapshot)),
+            vec![2, 5, 8]
+        );
+        assert_eq!(cell_marker_rows(&snapshot, 3..6), vec![5]);
+        assert_eq!(cell_marker_rows(&snapshot, 8..100), vec![8]);
+
+        assert_eq!(next_cell_row(&snapshot, 0), Some(2));
+        assert_eq!(next_cell_row(&snapshot, 2), Some(5));
+        assert_eq!(next_cell_row(&snapshot, 9), None);
+
+        assert_eq!(previous_cell_row(&snapshot, 9), Some(5));
+        assert_eq!(previous_cell_row(&snapshot, 5), Some(2));
+        assert_eq!(previous_cell_row(&snapshot, 3), None);
+        assert_eq!(previous_cell_row(&snapshot, 0), None);
+
+        // Cells above the cursor's cell, leaving out the code before the first marker
+        let range = cells_above(&snapshot, 9).unwrap();
+        let (snippets, _) = jupytext_cells(&snapshot, range);
+        let snippets = snippets
+            .into_iter()
+            .map(|range| snapshot.text_for_range(range).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            snippets,
+            vec![
+                "# %%\nx = 1",
+                indoc! { r#"
+                    # %% [markdown]
+                    # Some notes"#
+                }
+            ]
+        );
+        assert_eq!(cells_above(&snapshot, 3), None);
+        assert_eq!(cells_above(&snapshot, 0), None);
+    }
+
+    #[gpui::test]
+    fn test_all_cells(cx: &mut AppContext) {
+        let test_language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "TestLang".into(),
+                line_comments: vec!["# ".into()],
+                ..Default::default()
+            },
+            None,
+        ));
+
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(
+                indoc! { r#"
+                    import math
+
+                    # %%
+                    x = 1
+
+                    # %%
+                    print(x)
+                "# },
+                cx,
+            )
+            .with_language(test_language.clone(), cx)
+        });
+        let snapshot = buffer.read(cx).snapshot();
+        let cells = all_cells(&snapshot)
+            .into_iter()
+            .map(|range| snapshot.text_for_range(range).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec!["import math", "# %%\nx = 1", "# %%\nprint(x)"]);
+
+        // Without code before the first marker, there are only the marked cells
+        let buffer = cx
+            .new_model(|cx| Buffer::local("\n# %%\nx = 1\n", cx).with_language(test_language, cx));
+        let snapshot = buffer.read(cx).snapshot();
+        assert_eq!(all_cells(&snapshot).len(), 1);
+    }
+
     #[gpui::test]
     fn test_markdown_code_blocks(cx: &mut AppContext) {
         let markdown = languages::language("markdown", tree_sitter_md::LANGUAGE.into());
@@ -747,4 +1571,140 @@ mod tests {
             },]
         );
     }
+
+    fn python_kernelspec(name: &str) -> KernelSpecification {
+        KernelSpecification::Jupyter(LocalKernelSpecification {
+            name: name.to_string(),
+            path: PathBuf::from("/kernels").join(name),
+            kernelspec: JupyterKernelspec {
+                argv: vec![name.to_string()],
+                display_name: name.to_string(),
+                language: "python".to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        })
+    }
+
+    /// A workspace with `/dir/a.py` open, and a REPL store with the given kernels.
+    async fn init_test(
+        kernelspecs: Vec<KernelSpecification>,
+        cx: &mut gpui::TestAppContext,
+    ) -> (
+        View<Workspace>,
+        Model<ReplStore>,
+        View<Editor>,
+        &mut gpui::VisualTestContext,
+    ) {
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+            editor::init(cx);
+            JupyterSettings::register(cx);
+        });
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/dir", json!({ "a.py": "print(1)\n" }))
+            .await;
+        let project = Project::test(app_state.fs.clone(), ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: "Python".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["py".into()],
+                        ..Default::default()
+                    },
+                    line_comments: vec!["# ".into()],
+                    ..Default::default()
+                },
+                None,
+            )))
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let store = cx.update(|cx| {
+            let store = cx.new_model(|cx| ReplStore::new(app_state.fs.clone(), cx));
+            ReplStore::set_global(store.clone(), cx);
+            store
+        });
+        store.update(cx, |store, _| store.set_kernel_specifications(kernelspecs));
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.py"), true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+        (workspace, store, editor, cx)
+    }
+
+    #[gpui::test]
+    async fn test_pick_kernel_and_run(cx: &mut gpui::TestAppContext) {
+        let (workspace, store, editor, cx) = init_test(
+            vec![
+                python_kernelspec("python3"),
+                python_kernelspec("conda-base"),
+            ],
+            cx,
+        )
+        .await;
+
+        // With two kernels for Python and none picked, running asks which one to run on.
+        cx.update(|cx| run(editor.downgrade(), true, cx)).unwrap();
+        let picker = workspace
+            .update(cx, |workspace, cx| {
+                workspace.active_modal::<Picker<KernelPickerDelegate>>(cx)
+            })
+            .expect("running with more than one kernel should open the kernel picker");
+        picker.update(cx, |picker, _| assert_eq!(picker.delegate.match_count(), 2));
+        store.update(cx, |store, _| {
+            assert!(
+                store.get_session(editor.entity_id()).is_none(),
+                "no kernel should be started before one is picked"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_restore_outputs_without_kernel(cx: &mut gpui::TestAppContext) {
+        let (_workspace, store, editor, cx) =
+            init_test(vec![python_kernelspec("python3")], cx).await;
+        let output = serde_json::from_value::<nbformat::v4::Output>(json!({
+            "output_type": "stream",
+            "name": "stdout",
+            "text": "1\n",
+        }))
+        .unwrap();
+
+        cx.update(|cx| {
+            restore_outputs(
+                &editor,
+                python_kernelspec("python3"),
+                vec![(0..8, Some(1), vec![output])],
+                cx,
+            )
+        });
+        let session = store.update(cx, |store, _| {
+            store
+                .get_session(editor.entity_id())
+                .cloned()
+                .expect("restoring outputs should create a session")
+        });
+        session.update(cx, |session, cx| {
+            assert!(
+                matches!(session.kernel, Kernel::Shutdown),
+                "restoring outputs shouldn't start a kernel"
+            );
+            let (execution_count, outputs) = session.notebook_outputs(0..9, cx);
+            assert_eq!(execution_count, Some(1));
+            assert_eq!(outputs.len(), 1);
+            assert_eq!(outputs[0]["output_type"], "stream");
+        });
+    }
 }
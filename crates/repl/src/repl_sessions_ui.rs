@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
 use editor::Editor;
 use gpui::{
     actions, prelude::*, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView,
@@ -7,23 +11,32 @@ use project::ProjectItem as _;
 use ui::{prelude::*, ButtonLike, ElevationIndex, KeyBinding};
 use util::ResultExt as _;
 use workspace::item::ItemEvent;
+use workspace::notifications::DetachAndPromptErr;
 use workspace::WorkspaceId;
 use workspace::{item::Item, Workspace};
 
+use crate::dataframe::ViewDataFrame;
 use crate::jupyter_settings::JupyterSettings;
 use crate::repl_store::ReplStore;
+use crate::Session;
 
 actions!(
     repl,
     [
         Run,
         RunInPlace,
+        RunCellsAbove,
+        RestartAndRunAll,
+        NextCell,
+        PreviousCell,
         ClearOutputs,
         Sessions,
         Interrupt,
         Shutdown,
         Restart,
-        RefreshKernelspecs
+        RefreshKernelspecs,
+        ExportNotebook,
+        ImportNotebook
     ]
 );
 
@@ -51,6 +64,14 @@ pub fn init(cx: &mut AppContext) {
                     store.refresh_kernelspecs(cx).detach();
                 });
             });
+
+            workspace.register_action(|workspace, _: &ImportNotebook, cx| {
+                if !JupyterSettings::enabled(cx) {
+                    return;
+                }
+
+                crate::import_notebook(workspace, cx);
+            });
         },
     )
     .detach();
@@ -121,6 +142,125 @@ pub fn init(cx: &mut AppContext) {
                     }
                 })
                 .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |_: &RunCellsAbove, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        crate::run_cells_above(editor_handle.clone(), cx).log_err();
+                    }
+                })
+                .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |_: &RestartAndRunAll, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        crate::restart_and_run_all(editor_handle.clone(), cx).log_err();
+                    }
+                })
+                .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |_: &NextCell, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        editor_handle
+                            .update(cx, |editor, cx| crate::move_to_next_cell(editor, cx))
+                            .ok();
+                    }
+                })
+                .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |_: &PreviousCell, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        editor_handle
+                            .update(cx, |editor, cx| crate::move_to_previous_cell(editor, cx))
+                            .ok();
+                    }
+                })
+                .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |action: &ViewDataFrame, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        crate::view_dataframe(editor_handle.clone(), action.expression.clone(), cx)
+                            .log_err();
+                    }
+                })
+                .detach();
+
+            editor
+                .register_action({
+                    let editor_handle = editor_handle.clone();
+                    move |_: &ExportNotebook, cx| {
+                        if !JupyterSettings::enabled(cx) {
+                            return;
+                        }
+
+                        crate::export_notebook(editor_handle.clone(), cx).detach_and_prompt_err(
+                            "Failed to export notebook",
+                            cx,
+                            |_, _| None,
+                        );
+                    }
+                })
+                .detach();
+
+            let cell_separators = Rc::new(RefCell::new(crate::CellSeparators::default()));
+            if JupyterSettings::enabled(cx) {
+                cell_separators.borrow_mut().refresh(editor, cx);
+                crate::restore_session(editor_handle.clone(), cx).detach_and_log_err(cx);
+            }
+            cx.subscribe(&editor.buffer().clone(), {
+                let cell_separators = cell_separators.clone();
+                move |editor, _, event, cx| {
+                    if !JupyterSettings::enabled(cx) {
+                        return;
+                    }
+                    match event {
+                        multi_buffer::Event::Edited { .. } => {
+                            cell_separators.borrow_mut().refresh(editor, cx)
+                        }
+                        multi_buffer::Event::LanguageChanged(_) => {
+                            cell_separators.borrow_mut().rescan(editor, cx)
+                        }
+                        _ => {}
+                    }
+                }
+            })
+            .detach();
+            // Buffers are scanned for cell markers once a kernel for their language is found, or
+            // once they have a session.
+            cx.observe(&ReplStore::global(cx), move |editor, _, cx| {
+                if JupyterSettings::enabled(cx) {
+                    cell_separators.borrow_mut().refresh(editor, cx);
+                }
+            })
+            .detach();
         });
     })
     .detach();
@@ -139,6 +279,7 @@ impl ReplSessionsPage {
             let subscriptions = vec![
                 cx.on_focus_in(&focus_handle, |_this, cx| cx.notify()),
                 cx.on_focus_out(&focus_handle, |_this, _event, cx| cx.notify()),
+                cx.observe(&ReplStore::global(cx), |_this, _store, cx| cx.notify()),
             ];
 
             Self {
@@ -189,37 +330,67 @@ impl Render for ReplSessionsPage {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let store = ReplStore::global(cx);
 
-        let (kernel_specifications, sessions) = store.update(cx, |store, _cx| {
+        let (kernel_specifications, sessions, diagnostics) = store.update(cx, |store, _cx| {
             (
                 store
                     .pure_jupyter_kernel_specifications()
                     .cloned()
                     .collect::<Vec<_>>(),
                 store.sessions().cloned().collect::<Vec<_>>(),
+                store.discovery_diagnostics().clone(),
             )
         });
 
         // When there are no kernel specifications, show a link to the Zed docs explaining how to
-        // install kernels. It can be assumed they don't have a running kernel if we have no
-        // specifications.
+        // install kernels, along with where kernels were looked for. It can be assumed they don't
+        // have a running kernel if we have no specifications.
         if kernel_specifications.is_empty() {
             let instructions = "To start interactively running code in your editor, you need to install and configure Jupyter kernels.";
 
             return ReplSessionsContainer::new("No Jupyter Kernels Available")
                 .child(Label::new(instructions))
+                .when(!diagnostics.searched_dirs.is_empty(), |container| {
+                    container
+                        .child(Label::new("Kernelspecs were looked for in:").color(Color::Muted))
+                        .children(diagnostics.searched_dirs.iter().map(|dir| {
+                            Label::new(dir.to_string_lossy().to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                        }))
+                })
+                .children(diagnostics.errors.iter().map(|error| {
+                    Label::new(error.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Error)
+                }))
                 .child(
-                    h_flex().w_full().p_4().justify_center().gap_2().child(
-                        ButtonLike::new("install-kernels")
-                            .style(ButtonStyle::Filled)
-                            .size(ButtonSize::Large)
-                            .layer(ElevationIndex::ModalSurface)
-                            .child(Label::new("Install Kernels"))
-                            .on_click(move |_, cx| {
-                                cx.open_url(
-                                    "https://zed.dev/docs/repl#language-specific-instructions",
-                                )
-                            }),
-                    ),
+                    h_flex()
+                        .w_full()
+                        .p_4()
+                        .justify_center()
+                        .gap_2()
+                        .child(
+                            ButtonLike::new("install-kernels")
+                                .style(ButtonStyle::Filled)
+                                .size(ButtonSize::Large)
+                                .layer(ElevationIndex::ModalSurface)
+                                .child(Label::new("Install Kernels"))
+                                .on_click(move |_, cx| {
+                                    cx.open_url(
+                                        "https://zed.dev/docs/repl#language-specific-instructions",
+                                    )
+                                }),
+                        )
+                        .child(
+                            ButtonLike::new("refresh-kernels")
+                                .style(ButtonStyle::Subtle)
+                                .size(ButtonSize::Large)
+                                .layer(ElevationIndex::ModalSurface)
+                                .child(Label::new("Refresh Kernels"))
+                                .on_click(move |_, cx| {
+                                    cx.dispatch_action(Box::new(RefreshKernelspecs))
+                                }),
+                        ),
                 );
         }
 
@@ -234,7 +405,39 @@ impl Render for ReplSessionsPage {
             );
         }
 
-        ReplSessionsContainer::new("Jupyter Kernel Sessions").children(sessions)
+        // Group the running kernels by the project they run code from.
+        let mut sessions_by_project = BTreeMap::<SharedString, Vec<View<Session>>>::new();
+        for session in sessions {
+            let project_name = session
+                .read(cx)
+                .project_name(cx)
+                .unwrap_or_else(|| "Other".into());
+            sessions_by_project
+                .entry(project_name)
+                .or_default()
+                .push(session);
+        }
+
+        ReplSessionsContainer::new("Jupyter Kernel Sessions")
+            .children(diagnostics.errors.iter().map(|error| {
+                Label::new(error.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Error)
+            }))
+            .children(
+                sessions_by_project
+                    .into_iter()
+                    .map(|(project_name, sessions)| {
+                        v_flex()
+                            .gap_1()
+                            .child(
+                                Label::new(project_name)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .children(sessions)
+                    }),
+            )
     }
 }
 
@@ -1,23 +1,29 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
+use editor::Editor;
 use gpui::{
-    prelude::*, AppContext, EntityId, Global, Model, ModelContext, Subscription, Task, View,
+    prelude::*, AppContext, EntityId, Global, Model, ModelContext, SharedString, Subscription,
+    Task, View,
 };
-use jupyter_websocket_client::RemoteServer;
-use language::Language;
+use language::{Language, LanguageName};
 use project::{Fs, Project, WorktreeId};
 use settings::{Settings, SettingsStore};
 
+use crate::jupyter_settings::JupyterServer;
 use crate::kernels::{
-    list_remote_kernelspecs, local_kernel_specifications, python_env_kernel_specifications,
+    complete_local_kernel_specifications, kernelspec_dirs, list_remote_kernelspecs,
+    local_kernel_specifications, python_env_kernel_specifications, read_server_connection_file,
+    remote_server, RemoteKernelSpecification,
 };
 use crate::{JupyterSettings, KernelSpecification, Session};
 
 struct GlobalReplStore(Model<ReplStore>);
 
+type KernelSelectionKey = (WorktreeId, SharedString, Option<SharedString>);
+
 impl Global for GlobalReplStore {}
 
 pub struct ReplStore {
@@ -25,11 +31,29 @@ pub struct ReplStore {
     enabled: bool,
     sessions: HashMap<EntityId, View<Session>>,
     kernel_specifications: Vec<KernelSpecification>,
-    selected_kernel_for_worktree: HashMap<WorktreeId, KernelSpecification>,
+    /// The kernel picked for each language in a worktree, keyed by the lowercased language name
+    /// and the path of the toolchain that was active for the language when it was picked.
+    selected_kernel_for_worktree: HashMap<KernelSelectionKey, KernelSpecification>,
+    /// The path of the active toolchain for each language in a worktree, keyed by the lowercased
+    /// language name.
+    active_toolchain_for_worktree: HashMap<(WorktreeId, SharedString), SharedString>,
+    /// The kernel picked for an editor, which its code keeps running on after the kernel is shut
+    /// down or restarted.
+    selected_kernel_for_editor: HashMap<EntityId, KernelSpecification>,
     kernel_specifications_for_worktree: HashMap<WorktreeId, Vec<KernelSpecification>>,
+    servers: Vec<JupyterServer>,
+    discovery_diagnostics: KernelDiscoveryDiagnostics,
     _subscriptions: Vec<Subscription>,
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct KernelDiscoveryDiagnostics {
+    /// The directories that local kernelspecs were looked for in.
+    pub searched_dirs: Vec<PathBuf>,
+    /// Errors from reading local kernelspecs or from listing the kernels of Jupyter servers.
+    pub errors: Vec<SharedString>,
+}
+
 impl ReplStore {
     const NAMESPACE: &'static str = "repl";
 
@@ -50,6 +74,12 @@ impl ReplStore {
     pub fn new(fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
         let subscriptions = vec![cx.observe_global::<SettingsStore>(move |this, cx| {
             this.set_enabled(JupyterSettings::enabled(cx), cx);
+
+            let servers = &JupyterSettings::get_global(cx).servers;
+            if *servers != this.servers {
+                this.servers = servers.clone();
+                this.refresh_kernelspecs(cx).detach_and_log_err(cx);
+            }
         })];
 
         let this = Self {
@@ -60,6 +90,10 @@ impl ReplStore {
             _subscriptions: subscriptions,
             kernel_specifications_for_worktree: HashMap::default(),
             selected_kernel_for_worktree: HashMap::default(),
+            active_toolchain_for_worktree: HashMap::default(),
+            selected_kernel_for_editor: HashMap::default(),
+            servers: JupyterSettings::get_global(cx).servers.clone(),
+            discovery_diagnostics: KernelDiscoveryDiagnostics::default(),
         };
         this.on_enabled_changed(cx);
         this
@@ -84,6 +118,15 @@ impl ReplStore {
             .chain(self.kernel_specifications.iter())
     }
 
+    /// Whether a kernel for the language was found, globally or in any worktree.
+    pub fn has_kernel_for_language(&self, language: &Language) -> bool {
+        let language = language.code_fence_block_name().to_lowercase();
+        self.kernel_specifications
+            .iter()
+            .chain(self.kernel_specifications_for_worktree.values().flatten())
+            .any(|kernelspec| kernelspec.language().to_lowercase() == language)
+    }
+
     pub fn pure_jupyter_kernel_specifications(&self) -> impl Iterator<Item = &KernelSpecification> {
         self.kernel_specifications.iter()
     }
@@ -124,12 +167,27 @@ impl ReplStore {
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<()>> {
         let kernel_specifications = python_env_kernel_specifications(project, worktree_id, cx);
+        let active_toolchain =
+            project
+                .read(cx)
+                .active_toolchain(worktree_id, LanguageName::new("Python"), cx);
         cx.spawn(move |this, mut cx| async move {
             let kernel_specifications = kernel_specifications
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to get python kernelspecs: {:?}", e))?;
+            let active_toolchain = active_toolchain.await;
 
             this.update(&mut cx, |this, cx| {
+                let key = (worktree_id, SharedString::from("python"));
+                match active_toolchain {
+                    Some(toolchain) => {
+                        this.active_toolchain_for_worktree
+                            .insert(key, toolchain.path);
+                    }
+                    None => {
+                        this.active_toolchain_for_worktree.remove(&key);
+                    }
+                }
                 this.kernel_specifications_for_worktree
                     .insert(worktree_id, kernel_specifications);
                 cx.notify();
@@ -140,71 +198,140 @@ impl ReplStore {
     fn get_remote_kernel_specifications(
         &self,
         cx: &mut ModelContext<Self>,
-    ) -> Option<Task<Result<Vec<KernelSpecification>>>> {
-        match (
+    ) -> Task<Vec<Result<Vec<RemoteKernelSpecification>>>> {
+        let mut servers = JupyterSettings::get_global(cx).servers.clone();
+        if let (Ok(url), Ok(token)) = (
             std::env::var("JUPYTER_SERVER"),
             std::env::var("JUPYTER_TOKEN"),
         ) {
-            (Ok(server), Ok(token)) => {
-                let remote_server = RemoteServer {
-                    base_url: server,
-                    token,
-                };
-                let http_client = cx.http_client();
-                Some(cx.spawn(|_, _| async move {
+            servers.push(JupyterServer::Url { url, token });
+        }
+
+        let fs = self.fs.clone();
+        let http_client = cx.http_client();
+        cx.spawn(|_, _| async move {
+            let kernel_specifications = servers.into_iter().map(|server| {
+                let fs = fs.clone();
+                let http_client = http_client.clone();
+                async move {
+                    let remote_server = match &server {
+                        JupyterServer::Url { url, token } => remote_server(url, token.clone()),
+                        JupyterServer::ConnectionFile { connection_file } => {
+                            read_server_connection_file(fs.as_ref(), connection_file)
+                                .await
+                                .with_context(|| {
+                                    format!("reading Jupyter server connection file {connection_file:?}")
+                                })?
+                        }
+                    };
+                    let base_url = remote_server.base_url.clone();
                     list_remote_kernelspecs(remote_server, http_client)
                         .await
-                        .map(|specs| specs.into_iter().map(KernelSpecification::Remote).collect())
-                }))
-            }
-            _ => None,
-        }
+                        .with_context(|| format!("listing kernels of Jupyter server {base_url}"))
+                }
+            });
+
+            futures::future::join_all(kernel_specifications).await
+        })
     }
 
     pub fn refresh_kernelspecs(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
-        let local_kernel_specifications = local_kernel_specifications(self.fs.clone());
+        let fs = self.fs.clone();
 
         let remote_kernel_specifications = self.get_remote_kernel_specifications(cx);
 
         let all_specs = cx.background_executor().spawn(async move {
-            let mut all_specs = local_kernel_specifications
-                .await?
+            let mut diagnostics = KernelDiscoveryDiagnostics {
+                searched_dirs: kernelspec_dirs(fs.as_ref()).await,
+                errors: Vec::new(),
+            };
+
+            let local_specs =
+                match local_kernel_specifications(&diagnostics.searched_dirs, fs.clone()).await {
+                    Ok(specs) => specs,
+                    Err(error) => {
+                        diagnostics.errors.push(format!("{error:#}").into());
+                        Vec::new()
+                    }
+                };
+            let local_specs = complete_local_kernel_specifications(local_specs, fs.as_ref()).await;
+
+            let mut all_specs = local_specs
                 .into_iter()
                 .map(KernelSpecification::Jupyter)
                 .collect::<Vec<_>>();
 
-            if let Some(remote_task) = remote_kernel_specifications {
-                if let Ok(remote_specs) = remote_task.await {
-                    all_specs.extend(remote_specs);
+            for remote_specs in remote_kernel_specifications.await {
+                match remote_specs {
+                    Ok(remote_specs) => {
+                        all_specs.extend(remote_specs.into_iter().map(KernelSpecification::Remote))
+                    }
+                    Err(error) => {
+                        log::error!("{error:#}");
+                        diagnostics.errors.push(format!("{error:#}").into());
+                    }
                 }
             }
 
-            anyhow::Ok(all_specs)
+            (all_specs, diagnostics)
         });
 
         cx.spawn(|this, mut cx| async move {
-            let all_specs = all_specs.await;
-
-            if let Ok(specs) = all_specs {
-                this.update(&mut cx, |this, cx| {
-                    this.kernel_specifications = specs;
-                    cx.notify();
-                })
-                .ok();
-            }
+            let (specs, diagnostics) = all_specs.await;
 
-            anyhow::Ok(())
+            this.update(&mut cx, |this, cx| {
+                this.kernel_specifications = specs;
+                this.discovery_diagnostics = diagnostics;
+                cx.notify();
+            })
         })
     }
 
+    /// Where kernels were looked for the last time they were refreshed, and what went wrong.
+    pub fn discovery_diagnostics(&self) -> &KernelDiscoveryDiagnostics {
+        &self.discovery_diagnostics
+    }
+
+    /// Makes the kernel the one used for its language in the worktree, for as long as the
+    /// language's active toolchain stays the same.
     pub fn set_active_kernelspec(
         &mut self,
         worktree_id: WorktreeId,
         kernelspec: KernelSpecification,
         _cx: &mut ModelContext<Self>,
     ) {
-        self.selected_kernel_for_worktree
-            .insert(worktree_id, kernelspec);
+        let key = self.selection_key(worktree_id, &kernelspec.language());
+        self.selected_kernel_for_worktree.insert(key, kernelspec);
+    }
+
+    fn selection_key(&self, worktree_id: WorktreeId, language: &str) -> KernelSelectionKey {
+        let language = SharedString::from(language.to_lowercase());
+        let toolchain = self
+            .active_toolchain_for_worktree
+            .get(&(worktree_id, language.clone()))
+            .cloned();
+        (worktree_id, language, toolchain)
+    }
+
+    /// Makes the kernel the one the editor's code runs on, regardless of the worktree's kernel
+    /// for its language.
+    pub fn set_kernelspec_for_editor(
+        &mut self,
+        editor: &View<Editor>,
+        kernelspec: KernelSpecification,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let editor_id = editor.entity_id();
+        if self
+            .selected_kernel_for_editor
+            .insert(editor_id, kernelspec)
+            .is_none()
+        {
+            cx.observe_release(editor, move |this, _, _| {
+                this.selected_kernel_for_editor.remove(&editor_id);
+            })
+            .detach();
+        }
     }
 
     pub fn active_kernelspec(
@@ -213,18 +340,85 @@ impl ReplStore {
         language_at_cursor: Option<Arc<Language>>,
         cx: &AppContext,
     ) -> Option<KernelSpecification> {
-        let selected_kernelspec = self.selected_kernel_for_worktree.get(&worktree_id).cloned();
+        let language_at_cursor = language_at_cursor?;
+        let key = self.selection_key(worktree_id, &language_at_cursor.code_fence_block_name());
+        self.selected_kernel_for_worktree
+            .get(&key)
+            .cloned()
+            .or_else(|| self.kernelspec_legacy_by_lang_only(worktree_id, language_at_cursor, cx))
+    }
+
+    /// The kernel for the editor's code in the given language: the one picked for the editor if
+    /// it is for that language, or else the worktree's kernel for the language.
+    pub fn kernelspec_for_editor(
+        &self,
+        editor_id: EntityId,
+        worktree_id: WorktreeId,
+        language_at_cursor: Arc<Language>,
+        cx: &AppContext,
+    ) -> Option<KernelSpecification> {
+        self.selected_kernel_for_editor
+            .get(&editor_id)
+            .filter(|kernelspec| {
+                kernelspec.language().to_lowercase()
+                    == language_at_cursor.code_fence_block_name().to_lowercase()
+            })
+            .cloned()
+            .or_else(|| self.active_kernelspec(worktree_id, Some(language_at_cursor), cx))
+    }
 
-        if let Some(language_at_cursor) = language_at_cursor {
-            selected_kernelspec
-                .or_else(|| self.kernelspec_legacy_by_lang_only(language_at_cursor, cx))
-        } else {
-            selected_kernelspec
+    /// The kernels for the language when more than one could run the editor's code and none has
+    /// been picked for it, so that the choice is left to the user.
+    pub fn ambiguous_kernelspecs(
+        &self,
+        editor_id: EntityId,
+        worktree_id: WorktreeId,
+        language_at_cursor: &Arc<Language>,
+        cx: &AppContext,
+    ) -> Option<Vec<KernelSpecification>> {
+        let language_name = language_at_cursor.code_fence_block_name();
+        let language = language_name.to_lowercase();
+        let is_for_language =
+            |kernelspec: &KernelSpecification| kernelspec.language().to_lowercase() == language;
+
+        let picked_for_editor = self
+            .selected_kernel_for_editor
+            .get(&editor_id)
+            .is_some_and(is_for_language);
+        let picked_for_worktree = self
+            .selected_kernel_for_worktree
+            .contains_key(&self.selection_key(worktree_id, &language));
+        let picked_in_settings = JupyterSettings::get_global(cx)
+            .kernel_selections
+            .contains_key(language_name.as_ref());
+        // The worktree's environments are listed with the active toolchain first, which is picked
+        // by the toolchain selector.
+        let has_worktree_environment = self
+            .kernel_specifications_for_worktree
+            .get(&worktree_id)
+            .into_iter()
+            .flatten()
+            .any(is_for_language);
+        if picked_for_editor
+            || picked_for_worktree
+            || picked_in_settings
+            || has_worktree_environment
+        {
+            return None;
         }
+
+        let kernelspecs = self
+            .kernel_specifications
+            .iter()
+            .filter(|kernelspec| is_for_language(kernelspec))
+            .cloned()
+            .collect::<Vec<_>>();
+        (kernelspecs.len() > 1).then_some(kernelspecs)
     }
 
     fn kernelspec_legacy_by_lang_only(
         &self,
+        worktree_id: WorktreeId,
         language_at_cursor: Arc<Language>,
         cx: &AppContext,
     ) -> Option<KernelSpecification> {
@@ -251,6 +445,23 @@ impl ReplStore {
             return Some(found_by_name);
         }
 
+        // Environments discovered for the worktree are ordered with the active
+        // toolchain first, so prefer them over globally installed kernels.
+        let found_in_worktree = self
+            .kernel_specifications_for_worktree
+            .get(&worktree_id)
+            .into_iter()
+            .flatten()
+            .find(|kernel_option| {
+                kernel_option.language().to_lowercase()
+                    == language_at_cursor.code_fence_block_name().to_lowercase()
+            })
+            .cloned();
+
+        if let Some(found_in_worktree) = found_in_worktree {
+            return Some(found_in_worktree);
+        }
+
         self.kernel_specifications
             .iter()
             .find(|kernel_option| match kernel_option {
@@ -281,4 +492,296 @@ impl ReplStore {
     pub fn remove_session(&mut self, entity_id: EntityId) {
         self.sessions.remove(&entity_id);
     }
+
+    #[cfg(test)]
+    pub(crate) fn set_global(store: Model<Self>, cx: &mut AppContext) {
+        cx.set_global(GlobalReplStore(store))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_kernel_specifications(&mut self, kernelspecs: Vec<KernelSpecification>) {
+        self.kernel_specifications = kernelspecs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernels::LocalKernelSpecification;
+    use gpui::Context as _;
+    use jupyter_protocol::JupyterKernelspec;
+    use language::LanguageConfig;
+    use project::FakeFs;
+
+    fn kernelspec(name: &str, language: &str) -> KernelSpecification {
+        KernelSpecification::Jupyter(LocalKernelSpecification {
+            name: name.to_string(),
+            path: PathBuf::from("/kernels").join(name),
+            kernelspec: JupyterKernelspec {
+                argv: vec![name.to_string()],
+                display_name: name.to_string(),
+                language: language.to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        })
+    }
+
+    fn names(kernelspecs: Option<Vec<KernelSpecification>>) -> Option<Vec<String>> {
+        kernelspecs.map(|kernelspecs| kernelspecs.iter().map(|k| k.name().to_string()).collect())
+    }
+
+    fn language(name: &str) -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: name.into(),
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    fn init_store(cx: &mut AppContext) -> Model<ReplStore> {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        JupyterSettings::register(cx);
+        let fs = FakeFs::new(cx.background_executor().clone());
+        cx.new_model(|cx| ReplStore::new(fs, cx))
+    }
+
+    #[gpui::test]
+    fn test_kernel_selection_per_toolchain(cx: &mut AppContext) {
+        let store = init_store(cx);
+        let editor_id = cx.new_model(|_| ()).entity_id();
+        let worktree_id = WorktreeId::from_usize(1);
+        let python = language("Python");
+        let typescript = language("TypeScript");
+        let set_active_toolchain = |path: &str, cx: &mut AppContext| {
+            store.update(cx, |store, _| {
+                store
+                    .active_toolchain_for_worktree
+                    .insert((worktree_id, "python".into()), path.to_string().into());
+            });
+        };
+        let active_kernelspec = |language: &Arc<Language>, cx: &AppContext| {
+            store
+                .read(cx)
+                .active_kernelspec(worktree_id, Some(language.clone()), cx)
+                .map(|kernelspec| kernelspec.name().to_string())
+        };
+
+        store.update(cx, |store, _| {
+            store.kernel_specifications = vec![
+                kernelspec("python3", "python"),
+                kernelspec("conda-base", "python"),
+                kernelspec("deno", "typescript"),
+            ];
+        });
+        assert_eq!(active_kernelspec(&python, cx).as_deref(), Some("python3"));
+
+        // A kernel picked while a toolchain is active is used for as long as it stays active.
+        set_active_toolchain("/project/.venv/bin/python", cx);
+        store.update(cx, |store, cx| {
+            store.set_active_kernelspec(worktree_id, kernelspec("conda-base", "python"), cx);
+            store.set_active_kernelspec(worktree_id, kernelspec("deno", "typescript"), cx);
+        });
+        assert_eq!(
+            active_kernelspec(&python, cx).as_deref(),
+            Some("conda-base")
+        );
+
+        // Another toolchain has its own kernel.
+        set_active_toolchain("/project/.venv-test/bin/python", cx);
+        assert_eq!(active_kernelspec(&python, cx).as_deref(), Some("python3"));
+        store.update(cx, |store, cx| {
+            store.set_active_kernelspec(worktree_id, kernelspec("python3", "python"), cx);
+        });
+
+        // Switching back to the first toolchain brings its kernel back.
+        set_active_toolchain("/project/.venv/bin/python", cx);
+        assert_eq!(
+            active_kernelspec(&python, cx).as_deref(),
+            Some("conda-base")
+        );
+
+        // Languages without a toolchain keep their kernel.
+        assert_eq!(active_kernelspec(&typescript, cx).as_deref(), Some("deno"));
+
+        // A kernel picked for an editor wins over the worktree's for its language only.
+        store.update(cx, |store, _| {
+            store
+                .selected_kernel_for_editor
+                .insert(editor_id, kernelspec("python3", "python"));
+        });
+        let kernelspec_for_editor = |language: &Arc<Language>, cx: &AppContext| {
+            store
+                .read(cx)
+                .kernelspec_for_editor(editor_id, worktree_id, language.clone(), cx)
+                .map(|kernelspec| kernelspec.name().to_string())
+        };
+        assert_eq!(
+            kernelspec_for_editor(&python, cx).as_deref(),
+            Some("python3")
+        );
+        assert_eq!(
+            kernelspec_for_editor(&typescript, cx).as_deref(),
+            Some("deno")
+        );
+    }
+
+    #[gpui::test]
+    fn test_ambiguous_kernelspecs(cx: &mut AppContext) {
+        let store = init_store(cx);
+        let editor_id = cx.new_model(|_| ()).entity_id();
+        let other_editor_id = cx.new_model(|_| ()).entity_id();
+        let worktree_id = WorktreeId::from_usize(1);
+        let other_worktree_id = WorktreeId::from_usize(2);
+        let python = language("Python");
+        let ambiguous_kernelspecs = |cx: &AppContext| {
+            names(
+                store
+                    .read(cx)
+                    .ambiguous_kernelspecs(editor_id, worktree_id, &python, cx),
+            )
+        };
+
+        // A single kernel for the language is used without asking.
+        store.update(cx, |store, _| {
+            store.kernel_specifications = vec![
+                kernelspec("python3", "python"),
+                kernelspec("deno", "typescript"),
+            ];
+        });
+        assert_eq!(ambiguous_kernelspecs(cx), None);
+
+        // More than one kernel for the language are left to the user.
+        store.update(cx, |store, _| {
+            store
+                .kernel_specifications
+                .push(kernelspec("conda-base", "python"));
+        });
+        assert_eq!(
+            ambiguous_kernelspecs(cx),
+            Some(vec!["python3".to_string(), "conda-base".to_string()])
+        );
+
+        // A kernel picked for another language, editor or worktree doesn't count.
+        store.update(cx, |store, _| {
+            store
+                .selected_kernel_for_editor
+                .insert(editor_id, kernelspec("deno", "typescript"));
+            store
+                .selected_kernel_for_editor
+                .insert(other_editor_id, kernelspec("python3", "python"));
+            store.selected_kernel_for_worktree.insert(
+                (other_worktree_id, "python".into(), None),
+                kernelspec("python3", "python"),
+            );
+            store
+                .kernel_specifications_for_worktree
+                .insert(other_worktree_id, vec![kernelspec(".venv", "python")]);
+        });
+        assert_eq!(
+            ambiguous_kernelspecs(cx),
+            Some(vec!["python3".to_string(), "conda-base".to_string()])
+        );
+
+        // A kernel picked for the editor.
+        store.update(cx, |store, _| {
+            store
+                .selected_kernel_for_editor
+                .insert(editor_id, kernelspec("conda-base", "python"));
+        });
+        assert_eq!(ambiguous_kernelspecs(cx), None);
+        store.update(cx, |store, _| {
+            store.selected_kernel_for_editor.remove(&editor_id);
+        });
+
+        // A kernel picked for the worktree.
+        store.update(cx, |store, cx| {
+            store.set_active_kernelspec(worktree_id, kernelspec("conda-base", "python"), cx);
+        });
+        assert_eq!(ambiguous_kernelspecs(cx), None);
+        store.update(cx, |store, _| {
+            store.selected_kernel_for_worktree.clear();
+        });
+
+        // An environment of the worktree, which the toolchain selector picks from.
+        store.update(cx, |store, _| {
+            store
+                .kernel_specifications_for_worktree
+                .insert(worktree_id, vec![kernelspec(".venv", "python")]);
+        });
+        assert_eq!(ambiguous_kernelspecs(cx), None);
+        store.update(cx, |store, _| {
+            store
+                .kernel_specifications_for_worktree
+                .remove(&worktree_id);
+        });
+
+        // A kernel picked in the settings.
+        cx.update_global::<SettingsStore, _>(|settings_store, cx| {
+            settings_store
+                .set_user_settings(
+                    r#"{ "jupyter": { "kernel_selections": { "python": "conda-base" } } }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(ambiguous_kernelspecs(cx), None);
+    }
+
+    #[gpui::test]
+    fn test_worktree_environments_preferred_over_global_kernels(cx: &mut AppContext) {
+        let store = init_store(cx);
+        let worktree_id = WorktreeId::from_usize(1);
+        let other_worktree_id = WorktreeId::from_usize(2);
+        let python = language("Python");
+        let active_kernelspec = |worktree_id: WorktreeId, cx: &AppContext| {
+            store
+                .read(cx)
+                .active_kernelspec(worktree_id, Some(python.clone()), cx)
+                .map(|kernelspec| kernelspec.name().to_string())
+        };
+
+        store.update(cx, |store, _| {
+            store.kernel_specifications = vec![
+                kernelspec("python3", "python"),
+                kernelspec("conda-base", "python"),
+            ];
+            // Environments are listed with the active toolchain first, then the ones in the
+            // worktree, then the global ones.
+            store.kernel_specifications_for_worktree.insert(
+                worktree_id,
+                vec![
+                    kernelspec("deno", "typescript"),
+                    kernelspec(".venv", "python"),
+                    kernelspec("/usr/bin/python3", "python"),
+                ],
+            );
+        });
+
+        // The first Python environment of the worktree wins over the globally installed
+        // kernels, but only in that worktree.
+        assert_eq!(active_kernelspec(worktree_id, cx).as_deref(), Some(".venv"));
+        assert_eq!(
+            active_kernelspec(other_worktree_id, cx).as_deref(),
+            Some("python3")
+        );
+
+        // A kernel picked in the settings still wins over the worktree's environments.
+        cx.update_global::<SettingsStore, _>(|settings_store, cx| {
+            settings_store
+                .set_user_settings(
+                    r#"{ "jupyter": { "kernel_selections": { "python": "conda-base" } } }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            active_kernelspec(worktree_id, cx).as_deref(),
+            Some("conda-base")
+        );
+    }
 }
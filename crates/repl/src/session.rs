@@ -1,9 +1,12 @@
-use crate::components::KernelListItem;
+use crate::completions::register_kernel_providers;
+use crate::components::{KernelListItem, KernelSelector};
 use crate::kernels::RemoteRunningKernel;
 use crate::setup_editor_session_actions;
 use crate::{
+    jupyter_settings::JupyterSettings,
     kernels::{Kernel, KernelSpecification, NativeRunningKernel},
     outputs::{ExecutionStatus, ExecutionView},
+    persistence::{self, SerializedCell, SerializedExecution, SerializedSession, REPL_DB},
     KernelStatus,
 };
 use collections::{HashMap, HashSet};
@@ -13,32 +16,93 @@ use editor::{
         RenderBlock,
     },
     scroll::Autoscroll,
-    Anchor, AnchorRangeExt as _, Editor, MultiBuffer, ToPoint,
+    Anchor, AnchorRangeExt as _, Editor, MultiBuffer, MultiBufferSnapshot, ToPoint,
 };
-use futures::FutureExt as _;
+use futures::{channel::oneshot, FutureExt as _};
 use gpui::{
-    div, prelude::*, EventEmitter, Model, Render, Subscription, Task, View, ViewContext, WeakView,
+    div, prelude::*, AppContext, EventEmitter, Model, Render, Subscription, Task, View,
+    ViewContext, WeakView,
 };
-use language::Point;
+use language::{Bias, Point};
 use project::Fs;
 use runtimelib::{
-    ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
+    CompleteReply, CompleteRequest, ExecuteRequest, ExecutionState, InspectReply, InspectRequest,
+    InterruptRequest, JupyterMessage, JupyterMessageContent, MimeType, ReplyStatus,
     ShutdownRequest,
 };
-use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
+use serde_json::Value;
+use settings::Settings as _;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    env::temp_dir,
+    future::Future,
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use theme::ActiveTheme;
 use ui::{prelude::*, IconButtonShape, Tooltip};
 use util::ResultExt as _;
+use uuid::Uuid;
+use workspace::Workspace;
+use zed_actions::assistant::AskAboutKernelHistory;
+
+/// How many of the most recent executions a session remembers.
+const MAX_HISTORY_LEN: usize = 20;
+
+/// How long a session waits for its outputs to stop changing before saving them.
+const PERSIST_DEBOUNCE: Duration = Duration::from_millis(500);
 
 pub struct Session {
     fs: Arc<dyn Fs>,
     editor: WeakView<Editor>,
     pub kernel: Kernel,
     blocks: HashMap<String, EditorBlock>,
+    history: VecDeque<HistoryEntry>,
+    /// Executions waiting for the kernel to finish the one it's running. They are sent one at a
+    /// time, so that the ones that haven't started yet can be cancelled.
+    execution_queue: VecDeque<QueuedExecution>,
+    current_execution: Option<QueuedExecution>,
+    /// The id of the request that ran the startup code from the settings on the kernel.
+    startup_request_id: Option<String>,
+    /// Code run with [`Session::evaluate`], keyed by the id of its execute request.
+    evaluations: HashMap<String, oneshot::Sender<anyhow::Result<Value>>>,
+    /// Completion and inspection requests, keyed by their id. These are sent while rendering
+    /// completions and hovers, where the session can only be read.
+    pending_replies: RefCell<HashMap<String, oneshot::Sender<JupyterMessageContent>>>,
     pub kernel_specification: KernelSpecification,
+    /// Saves the outputs and history once they stop changing, replaced whenever they change.
+    pending_persist: Option<Task<()>>,
     _buffer_subscription: Subscription,
 }
 
+struct QueuedExecution {
+    message: JupyterMessage,
+    code: String,
+    /// Whether the code raised an error while running.
+    failed: bool,
+}
+
+impl QueuedExecution {
+    fn id(&self) -> &str {
+        &self.message.header.msg_id
+    }
+}
+
+struct HistoryEntry {
+    code: String,
+    execution_view: View<ExecutionView>,
+    /// When the code was run, unless it was run in a previous session.
+    executed_at: Option<Instant>,
+}
+
+/// Code that was run in a session, along with the text of its outputs.
+pub struct ExecutionRecord {
+    pub code: String,
+    pub outputs: Vec<String>,
+}
+
 struct EditorBlock {
     code_range: Range<Anchor>,
     invalidation_anchor: Anchor,
@@ -108,8 +172,14 @@ impl EditorBlock {
         })
     }
 
-    fn handle_message(&mut self, message: &JupyterMessage, cx: &mut ViewContext<Session>) {
+    fn handle_message(
+        &mut self,
+        message: &JupyterMessage,
+        results_in_out: bool,
+        cx: &mut ViewContext<Session>,
+    ) {
         self.execution_view.update(cx, |execution_view, cx| {
+            execution_view.set_results_in_out(results_in_out);
             execution_view.push_message(&message.content, cx);
         });
     }
@@ -158,6 +228,68 @@ impl EditorBlock {
                         }),
                 );
 
+            let has_outputs = !execution_view.read(cx).outputs.is_empty();
+            let is_collapsed = execution_view.read(cx).is_collapsed();
+            let collapse_button = h_flex()
+                .flex_none()
+                .items_center()
+                .justify_center()
+                .absolute()
+                .top(text_line_height * 1.5)
+                .right(gutter.full_width() / 2.0 - text_line_height / 2.0 - px(2.))
+                .w(text_line_height)
+                .h(text_line_height)
+                .child(
+                    IconButton::new(
+                        "toggle_output_area",
+                        if is_collapsed {
+                            IconName::ChevronRight
+                        } else {
+                            IconName::ChevronDown
+                        },
+                    )
+                    .icon_size(IconSize::Small)
+                    .icon_color(Color::Muted)
+                    .size(ButtonSize::Compact)
+                    .shape(IconButtonShape::Square)
+                    .tooltip(move |cx| {
+                        Tooltip::text(
+                            if is_collapsed {
+                                "Expand Output Area"
+                            } else {
+                                "Collapse Output Area"
+                            },
+                            cx,
+                        )
+                    })
+                    .on_click({
+                        let execution_view = execution_view.clone();
+                        move |_, cx| {
+                            execution_view.update(cx, |execution_view, cx| {
+                                execution_view.toggle_collapsed(cx)
+                            })
+                        }
+                    }),
+                );
+            let assistant_button = h_flex()
+                .flex_none()
+                .items_center()
+                .justify_center()
+                .absolute()
+                .top(text_line_height * 2.5)
+                .right(gutter.full_width() / 2.0 - text_line_height / 2.0 - px(2.))
+                .w(text_line_height)
+                .h(text_line_height)
+                .child(
+                    IconButton::new("ask_about_output", IconName::ZedAssistant)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .size(ButtonSize::Compact)
+                        .shape(IconButtonShape::Square)
+                        .tooltip(|cx| Tooltip::text("Ask Assistant about Output", cx))
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(AskAboutKernelHistory))),
+                );
+
             div()
                 .id(cx.block_id)
                 .block_mouse_down()
@@ -172,8 +304,18 @@ impl EditorBlock {
                     div()
                         .relative()
                         .w(gutter.full_width())
-                        .h(text_line_height * 2)
-                        .child(close_button),
+                        .h(if !has_outputs {
+                            text_line_height * 2
+                        } else if is_collapsed {
+                            text_line_height * 3
+                        } else {
+                            text_line_height * 4
+                        })
+                        .child(close_button)
+                        .when(has_outputs, |el| el.child(collapse_button))
+                        .when(has_outputs && !is_collapsed, |el| {
+                            el.child(assistant_button)
+                        }),
                 )
                 .child(
                     div()
@@ -194,6 +336,32 @@ impl Session {
         fs: Arc<dyn Fs>,
         kernel_specification: KernelSpecification,
         cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut session = Self::without_kernel(editor, fs, kernel_specification, cx);
+        session.start_kernel(cx);
+        session
+    }
+
+    /// A session with the outputs and history that were saved for the editor's file, whose kernel
+    /// is only started once code is run in it.
+    pub(crate) fn restored(
+        editor: WeakView<Editor>,
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        serialized: SerializedSession,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut session = Self::without_kernel(editor, fs, kernel_specification, cx);
+        session.restore(serialized, cx);
+        session
+    }
+
+    /// A session whose kernel is only started once code is run in it.
+    pub(crate) fn without_kernel(
+        editor: WeakView<Editor>,
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        cx: &mut ViewContext<Self>,
     ) -> Self {
         let subscription = match editor.upgrade() {
             Some(editor) => {
@@ -204,27 +372,33 @@ impl Session {
         };
 
         let editor_handle = editor.clone();
+        let session_handle = cx.view().downgrade();
 
         editor
-            .update(cx, |editor, _cx| {
+            .update(cx, |editor, cx| {
                 setup_editor_session_actions(editor, editor_handle);
+                register_kernel_providers(editor, session_handle, cx);
             })
             .ok();
 
-        let mut session = Self {
+        Self {
             fs,
             editor,
-            kernel: Kernel::StartingKernel(Task::ready(()).shared()),
+            kernel: Kernel::Shutdown,
             blocks: HashMap::default(),
+            history: VecDeque::new(),
+            execution_queue: VecDeque::new(),
+            current_execution: None,
+            startup_request_id: None,
+            evaluations: HashMap::default(),
+            pending_replies: RefCell::default(),
             kernel_specification,
+            pending_persist: None,
             _buffer_subscription: subscription,
-        };
-
-        session.start_kernel(cx);
-        session
+        }
     }
 
-    fn start_kernel(&mut self, cx: &mut ViewContext<Self>) {
+    pub(crate) fn start_kernel(&mut self, cx: &mut ViewContext<Self>) {
         let kernel_language = self.kernel_specification.language();
         let entity_id = self.editor.entity_id();
         let working_directory = self
@@ -287,6 +461,9 @@ impl Session {
 
     pub fn kernel_errored(&mut self, error_message: String, cx: &mut ViewContext<Self>) {
         self.kernel(Kernel::ErroredLaunch(error_message.clone()), cx);
+        self.reject_evaluations(&error_message);
+        self.execution_queue.clear();
+        self.current_execution = None;
 
         self.blocks.values().for_each(|block| {
             block.execution_view.update(cx, |execution_view, cx| {
@@ -355,6 +532,175 @@ impl Session {
             .ok();
 
         self.blocks.clear();
+        self.history.clear();
+        self.persist(cx);
+    }
+
+    /// Shows the outputs and history of a previous session, marked as stale since the kernel that
+    /// produced them is gone.
+    fn restore(&mut self, serialized: SerializedSession, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let Some(workspace) = editor.read(cx).workspace() else {
+            return;
+        };
+
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let cells = serialized
+            .cells
+            .iter()
+            .filter_map(|cell| {
+                let code_range = find_code(&buffer, cell)?;
+                let execution_count = cell
+                    .execution
+                    .execution_count
+                    .and_then(|count| count.try_into().ok());
+                Some((
+                    buffer.anchor_before(code_range.start)..buffer.anchor_after(code_range.end),
+                    execution_count,
+                    cell.execution.notebook_outputs(),
+                ))
+            })
+            .collect();
+        self.restore_outputs(cells, cx);
+        for block in self.blocks.values() {
+            block
+                .execution_view
+                .update(cx, |execution_view, cx| execution_view.mark_stale(cx));
+        }
+
+        let skipped_history = serialized.history.len().saturating_sub(MAX_HISTORY_LEN);
+        for execution in serialized.history.into_iter().skip(skipped_history) {
+            let execution_view = cx.new_view(|cx| {
+                let mut execution_view =
+                    ExecutionView::new(ExecutionStatus::Finished, workspace.downgrade(), cx);
+                let execution_count = execution
+                    .execution_count
+                    .and_then(|count| count.try_into().ok());
+                execution_view.restore(&execution.notebook_outputs(), execution_count, cx);
+                execution_view.mark_stale(cx);
+                execution_view
+            });
+            self.history.push_back(HistoryEntry {
+                code: execution.code,
+                execution_view,
+                executed_at: None,
+            });
+        }
+    }
+
+    fn serialize(&self, editor: &View<Editor>, cx: &AppContext) -> SerializedSession {
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let mut cells = self
+            .blocks
+            .values()
+            .filter_map(|block| {
+                let execution_view = block.execution_view.read(cx);
+                if execution_view.notebook_outputs().is_empty() {
+                    return None;
+                }
+                let start = block.code_range.start.to_point(&buffer);
+                Some(SerializedCell {
+                    row: start.row,
+                    column: start.column,
+                    execution: SerializedExecution {
+                        code: buffer.text_for_range(block.code_range.clone()).collect(),
+                        execution_count: execution_view.execution_count(),
+                        outputs: execution_view.notebook_outputs().to_vec(),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|cell| (cell.row, cell.column));
+
+        let history = self
+            .history
+            .iter()
+            .map(|entry| {
+                let execution_view = entry.execution_view.read(cx);
+                SerializedExecution {
+                    code: entry.code.clone(),
+                    execution_count: execution_view.execution_count(),
+                    outputs: execution_view.notebook_outputs().to_vec(),
+                }
+            })
+            .collect();
+
+        SerializedSession { cells, history }
+    }
+
+    /// Saves the outputs and history of the session, to show them again when the workspace is
+    /// reopened, once they have stopped changing for a while.
+    fn persist(&mut self, cx: &mut ViewContext<Self>) {
+        self.pending_persist = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(PERSIST_DEBOUNCE).await;
+            let Some(save) = this
+                .update(&mut cx, |session, cx| {
+                    let editor = session.editor.upgrade()?;
+                    let (workspace_id, path) = persistence::session_key(&editor, cx)?;
+                    let serialized = session.serialize(&editor, cx);
+                    Some(cx.background_executor().spawn(async move {
+                        if serialized.cells.is_empty() && serialized.history.is_empty() {
+                            REPL_DB.delete_session(workspace_id, path).await
+                        } else {
+                            let contents = serialized.to_json()?;
+                            REPL_DB.save_session(workspace_id, path, contents).await
+                        }
+                    }))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+            save.await.log_err();
+        }));
+    }
+
+    /// The most recent executions in this session, oldest first.
+    pub fn history(&self, cx: &WindowContext) -> Vec<ExecutionRecord> {
+        self.history
+            .iter()
+            .map(|entry| ExecutionRecord {
+                code: entry.code.clone(),
+                outputs: entry.execution_view.read(cx).text_outputs(cx),
+            })
+            .collect()
+    }
+
+    pub fn last_executed_at(&self) -> Option<Instant> {
+        self.history
+            .iter()
+            .rev()
+            .find_map(|entry| entry.executed_at)
+    }
+
+    /// The name of the file the session runs code from.
+    pub fn file_name(&self, cx: &AppContext) -> Option<SharedString> {
+        let editor = self.editor.upgrade()?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        let file = buffer.read(cx).file()?;
+        Some(file.file_name(cx).to_string_lossy().to_string().into())
+    }
+
+    /// The names of the worktrees of the project the session's editor is in.
+    pub fn project_name(&self, cx: &AppContext) -> Option<SharedString> {
+        let workspace = self.editor.upgrade()?.read(cx).workspace()?;
+        let project = workspace.read(cx).project().read(cx);
+        let name = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).root_name().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        (!name.is_empty()).then(|| name.into())
+    }
+
+    pub fn is_in_workspace(&self, workspace: &View<Workspace>, cx: &AppContext) -> bool {
+        self.editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+            .is_some_and(|editor_workspace| &editor_workspace == workspace)
     }
 
     pub fn execute(
@@ -374,7 +720,7 @@ impl Session {
         }
 
         let execute_request = ExecuteRequest {
-            code,
+            code: code.clone(),
             ..ExecuteRequest::default()
         };
 
@@ -408,27 +754,7 @@ impl Session {
             Kernel::Shutdown => ExecutionStatus::Shutdown,
         };
 
-        let parent_message_id = message.header.msg_id.clone();
-        let session_view = cx.view().downgrade();
-        let weak_editor = self.editor.clone();
-
-        let on_close: CloseBlockFn =
-            Arc::new(move |block_id: CustomBlockId, cx: &mut WindowContext| {
-                if let Some(session) = session_view.upgrade() {
-                    session.update(cx, |session, cx| {
-                        session.blocks.remove(&parent_message_id);
-                        cx.notify();
-                    });
-                }
-
-                if let Some(editor) = weak_editor.upgrade() {
-                    editor.update(cx, |editor, cx| {
-                        let mut block_ids = HashSet::default();
-                        block_ids.insert(block_id);
-                        editor.remove_blocks(block_ids, None, cx);
-                    });
-                }
-            });
+        let on_close = self.close_block_fn(message.header.msg_id.clone(), cx);
 
         let Ok(editor_block) =
             EditorBlock::new(self.editor.clone(), anchor_range, status, on_close, cx)
@@ -442,28 +768,28 @@ impl Session {
             editor_block.invalidation_anchor
         };
 
+        if self.history.len() == MAX_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry {
+            code: code.clone(),
+            execution_view: editor_block.execution_view.clone(),
+            executed_at: Some(Instant::now()),
+        });
+
         self.blocks
             .insert(message.header.msg_id.clone(), editor_block);
 
-        match &self.kernel {
-            Kernel::RunningKernel(_) => {
-                self.send(message, cx).ok();
-            }
-            Kernel::StartingKernel(task) => {
-                // Queue up the execution as a task to run after the kernel starts
-                let task = task.clone();
-                let message = message.clone();
-
-                cx.spawn(|this, mut cx| async move {
-                    task.await;
-                    this.update(&mut cx, |session, cx| {
-                        session.send(message, cx).ok();
-                    })
-                    .ok();
-                })
-                .detach();
-            }
-            _ => {}
+        // Executions are sent once the kernel has started and is done with the previous ones.
+        if let Kernel::RunningKernel(_) | Kernel::StartingKernel(_) | Kernel::Restarting =
+            &self.kernel
+        {
+            self.execution_queue.push_back(QueuedExecution {
+                message,
+                code,
+                failed: false,
+            });
+            self.send_next_execution(cx);
         }
 
         if move_down {
@@ -475,6 +801,264 @@ impl Session {
         }
     }
 
+    fn send_next_execution(&mut self, cx: &mut ViewContext<Self>) {
+        if self.current_execution.is_some() || !matches!(self.kernel, Kernel::RunningKernel(_)) {
+            return;
+        }
+        if let Some(execution) = self.execution_queue.pop_front() {
+            self.send(execution.message.clone(), cx).ok();
+            self.current_execution = Some(execution);
+        }
+        cx.notify();
+    }
+
+    /// Removes an execution from the queue before the kernel starts running it.
+    pub fn cancel_execution(&mut self, message_id: &str, cx: &mut ViewContext<Self>) {
+        let queue_len = self.execution_queue.len();
+        self.execution_queue
+            .retain(|execution| execution.id() != message_id);
+        if self.execution_queue.len() == queue_len {
+            return;
+        }
+        if let Some(block) = self.blocks.get(message_id) {
+            block.execution_view.update(cx, |execution_view, cx| {
+                execution_view.status = ExecutionStatus::Cancelled;
+                cx.notify();
+            });
+        }
+        cx.notify();
+    }
+
+    /// Cancels all the executions that the kernel hasn't started running yet.
+    pub fn cancel_queued_executions(&mut self, cx: &mut ViewContext<Self>) {
+        let message_ids = self
+            .execution_queue
+            .iter()
+            .map(|execution| execution.id().to_string())
+            .collect::<Vec<_>>();
+        for message_id in message_ids {
+            self.cancel_execution(&message_id, cx);
+        }
+    }
+
+    pub fn queued_execution_count(&self) -> usize {
+        self.execution_queue.len()
+    }
+
+    fn close_block_fn(&self, block_key: String, cx: &ViewContext<Self>) -> CloseBlockFn {
+        let session_view = cx.view().downgrade();
+        let weak_editor = self.editor.clone();
+
+        Arc::new(move |block_id: CustomBlockId, cx: &mut WindowContext| {
+            if let Some(session) = session_view.upgrade() {
+                session.update(cx, |session, cx| {
+                    session
+                        .execution_queue
+                        .retain(|execution| execution.id() != block_key);
+                    session.blocks.remove(&block_key);
+                    session.persist(cx);
+                    cx.notify();
+                });
+            }
+
+            if let Some(editor) = weak_editor.upgrade() {
+                editor.update(cx, |editor, cx| {
+                    let mut block_ids = HashSet::default();
+                    block_ids.insert(block_id);
+                    editor.remove_blocks(block_ids, None, cx);
+                });
+            }
+        })
+    }
+
+    /// Shows outputs that were saved in a notebook below the code they came from.
+    pub fn restore_outputs(
+        &mut self,
+        cells: Vec<(Range<Anchor>, Option<i32>, Vec<nbformat::v4::Output>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        for (code_range, execution_count, outputs) in cells {
+            if outputs.is_empty() {
+                continue;
+            }
+
+            // Restored outputs don't belong to a request, so they are keyed by an ID of their own.
+            let block_key = Uuid::new_v4().to_string();
+            let on_close = self.close_block_fn(block_key.clone(), cx);
+            let Ok(editor_block) = EditorBlock::new(
+                self.editor.clone(),
+                code_range,
+                ExecutionStatus::Finished,
+                on_close,
+                cx,
+            ) else {
+                continue;
+            };
+            editor_block
+                .execution_view
+                .update(cx, |execution_view, cx| {
+                    execution_view.restore(&outputs, execution_count, cx);
+                });
+            self.blocks.insert(block_key, editor_block);
+        }
+    }
+
+    /// The outputs of the code that was run within the given range of the editor's buffer, as they
+    /// are written to a notebook, along with the most recent execution count among them.
+    pub fn notebook_outputs(
+        &self,
+        range: Range<usize>,
+        cx: &AppContext,
+    ) -> (Option<u64>, Vec<serde_json::Value>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return (None, Vec::new());
+        };
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+
+        let mut blocks = self
+            .blocks
+            .values()
+            .map(|block| (block.code_range.to_offset(&buffer), block))
+            .filter(|(code_range, _)| {
+                range.start <= code_range.start && code_range.end <= range.end
+            })
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|(code_range, _)| code_range.start);
+
+        let mut execution_count = None;
+        let mut outputs = Vec::new();
+        for (_, block) in blocks {
+            let execution_view = block.execution_view.read(cx);
+            execution_count = execution_view.execution_count().or(execution_count);
+            outputs.extend(execution_view.notebook_outputs().iter().cloned());
+        }
+        (execution_count, outputs)
+    }
+
+    /// Runs code in the kernel without showing it in the editor or adding it to the kernel's
+    /// history, and resolves with the first JSON object the code displays.
+    pub fn evaluate(
+        &mut self,
+        code: String,
+        cx: &mut ViewContext<Self>,
+    ) -> oneshot::Receiver<anyhow::Result<Value>> {
+        let (tx, rx) = oneshot::channel();
+        if !matches!(self.kernel, Kernel::RunningKernel(_)) {
+            tx.send(Err(anyhow::anyhow!("The kernel is not running")))
+                .ok();
+            return rx;
+        }
+
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            store_history: false,
+            ..ExecuteRequest::default()
+        }
+        .into();
+        self.evaluations.insert(message.header.msg_id.clone(), tx);
+        self.send(message, cx).ok();
+        rx
+    }
+
+    /// Asks the kernel for completions of the code at the given position, in characters.
+    pub fn complete(
+        &self,
+        code: String,
+        cursor_pos: usize,
+    ) -> impl Future<Output = Option<CompleteReply>> {
+        let reply = self.request_reply(CompleteRequest { code, cursor_pos }.into());
+        async move {
+            match reply?.await.ok()? {
+                JupyterMessageContent::CompleteReply(reply) => Some(reply),
+                _ => None,
+            }
+        }
+    }
+
+    /// Asks the kernel about the object at the given position in the code, in characters.
+    pub fn inspect(
+        &self,
+        code: String,
+        cursor_pos: usize,
+    ) -> impl Future<Output = Option<InspectReply>> {
+        let reply = self.request_reply(
+            InspectRequest {
+                code,
+                cursor_pos,
+                detail_level: Some(0),
+            }
+            .into(),
+        );
+        async move {
+            match reply?.await.ok()? {
+                JupyterMessageContent::InspectReply(reply) => Some(reply),
+                _ => None,
+            }
+        }
+    }
+
+    /// Sends a request to the kernel, unless it's busy running code: the kernel handles requests
+    /// in order, so the reply would only come once the code is done.
+    fn request_reply(
+        &self,
+        message: JupyterMessage,
+    ) -> Option<oneshot::Receiver<JupyterMessageContent>> {
+        let Kernel::RunningKernel(kernel) = &self.kernel else {
+            return None;
+        };
+        if !matches!(kernel.execution_state(), ExecutionState::Idle) {
+            return None;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let message_id = message.header.msg_id.clone();
+        {
+            let mut pending_replies = self.pending_replies.borrow_mut();
+            // Forget the requests that timed out, as nothing waits for their replies anymore.
+            pending_replies.retain(|_, tx| !tx.is_canceled());
+            pending_replies.insert(message_id.clone(), tx);
+        }
+        if kernel.request_tx().try_send(message).is_err() {
+            self.pending_replies.borrow_mut().remove(&message_id);
+            return None;
+        }
+        Some(rx)
+    }
+
+    fn route_evaluation(&mut self, parent_message_id: &str, content: &JupyterMessageContent) {
+        let result = match content {
+            JupyterMessageContent::DisplayData(display_data) => {
+                let Some(value) =
+                    display_data
+                        .data
+                        .content
+                        .iter()
+                        .find_map(|mimetype| match mimetype {
+                            MimeType::Json(value) => Some(value.clone()),
+                            _ => None,
+                        })
+                else {
+                    return;
+                };
+                Ok(value)
+            }
+            JupyterMessageContent::ErrorOutput(error) => {
+                Err(anyhow::anyhow!("{}: {}", error.ename, error.evalue))
+            }
+            // Outputs are published before the kernel goes back to idle, so the code displayed
+            // nothing if it is still waited for.
+            JupyterMessageContent::Status(status)
+                if matches!(status.execution_state, ExecutionState::Idle) =>
+            {
+                Err(anyhow::anyhow!("The kernel did not return any data"))
+            }
+            _ => return,
+        };
+        if let Some(tx) = self.evaluations.remove(parent_message_id) {
+            tx.send(result).ok();
+        }
+    }
+
     pub fn route(&mut self, message: &JupyterMessage, cx: &mut ViewContext<Self>) {
         let parent_message_id = match message.parent_header.as_ref() {
             Some(header) => &header.msg_id,
@@ -515,18 +1099,85 @@ impl Session {
             _ => {}
         }
 
-        if let Some(block) = self.blocks.get_mut(parent_message_id) {
-            block.handle_message(message, cx);
+        if let JupyterMessageContent::CompleteReply(_) | JupyterMessageContent::InspectReply(_) =
+            &message.content
+        {
+            if let Some(tx) = self.pending_replies.borrow_mut().remove(parent_message_id) {
+                tx.send(message.content.clone()).ok();
+            }
+        } else if self.evaluations.contains_key(parent_message_id) {
+            self.route_evaluation(parent_message_id, &message.content);
+        } else if let Some(block) = self.blocks.get_mut(parent_message_id) {
+            block.handle_message(message, self.kernel.is_ipython(), cx);
+        }
+
+        if let JupyterMessageContent::ExecuteReply(reply) = &message.content {
+            if self.startup_request_id.as_ref() == Some(parent_message_id) {
+                self.startup_request_id = None;
+                if !matches!(reply.status, ReplyStatus::Ok) {
+                    log::error!(
+                        "startup code of kernel {} failed: {:?}",
+                        self.kernel_specification.name(),
+                        reply
+                    );
+                }
+            }
+            self.finish_execution(
+                parent_message_id,
+                matches!(reply.status, ReplyStatus::Ok),
+                cx,
+            );
+        }
+
+        // The execute reply comes on another channel than the outputs and the kernel status, so
+        // it may come after the kernel went idle: either one finishes the execution.
+        match &message.content {
+            JupyterMessageContent::ErrorOutput(_) => {
+                if let Some(execution) = self
+                    .current_execution
+                    .as_mut()
+                    .filter(|execution| execution.id() == parent_message_id)
+                {
+                    execution.failed = true;
+                }
+            }
+            JupyterMessageContent::Status(status)
+                if matches!(status.execution_state, ExecutionState::Idle) =>
+            {
+                self.finish_execution(parent_message_id, true, cx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends the next queued execution once the kernel is done running the given one.
+    fn finish_execution(&mut self, message_id: &str, succeeded: bool, cx: &mut ViewContext<Self>) {
+        let Some(execution) = self
+            .current_execution
+            .take_if(|execution| execution.id() == message_id)
+        else {
+            return;
+        };
+        // Like notebooks, stop running the queued code once some code fails or is interrupted.
+        if !succeeded || execution.failed {
+            self.cancel_queued_executions(cx);
         }
+        self.send_next_execution(cx);
+        self.persist(cx);
     }
 
     pub fn interrupt(&mut self, cx: &mut ViewContext<Self>) {
         match &mut self.kernel {
             Kernel::RunningKernel(_kernel) => {
                 self.send(InterruptRequest {}.into(), cx).ok();
+                // The interrupted code won't finish normally, so don't wait for it to send
+                // the code run after the interruption.
+                self.cancel_queued_executions(cx);
+                self.current_execution = None;
+                cx.notify();
             }
             Kernel::StartingKernel(_task) => {
-                // NOTE: If we switch to a literal queue instead of chaining on to the task, clear all queued executions
+                self.cancel_queued_executions(cx);
             }
             _ => {}
         }
@@ -548,9 +1199,34 @@ impl Session {
         );
 
         self.kernel = kernel;
+        if let Kernel::RunningKernel(_) = &self.kernel {
+            self.run_startup_code(cx);
+        }
+        self.send_next_execution(cx);
+    }
+
+    /// Runs the startup code from the settings, which is sent before any queued executions so that
+    /// they run with it.
+    fn run_startup_code(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(code) = JupyterSettings::get_global(cx).startup_code(&self.kernel_specification)
+        else {
+            return;
+        };
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            silent: true,
+            store_history: false,
+            ..ExecuteRequest::default()
+        }
+        .into();
+        self.startup_request_id = Some(message.header.msg_id.clone());
+        self.send(message, cx).ok();
     }
 
     pub fn shutdown(&mut self, cx: &mut ViewContext<Self>) {
+        self.reject_evaluations("The kernel was shut down");
+        self.cancel_queued_executions(cx);
+        self.current_execution = None;
         let kernel = std::mem::replace(&mut self.kernel, Kernel::ShuttingDown);
 
         match kernel {
@@ -584,7 +1260,21 @@ impl Session {
         cx.notify();
     }
 
+    /// Fails the evaluations that are still waiting for the kernel's reply, which won't come.
+    fn reject_evaluations(&mut self, reason: &str) {
+        for (_, tx) in self.evaluations.drain() {
+            tx.send(Err(anyhow::anyhow!("{reason}"))).ok();
+        }
+    }
+
     pub fn restart(&mut self, cx: &mut ViewContext<Self>) {
+        self.reject_evaluations("The kernel was restarted");
+        self.cancel_queued_executions(cx);
+        self.current_execution = None;
+        // Outputs are cleared right away, so that code run while the kernel restarts keeps its
+        // outputs and runs once the new kernel has started.
+        // TODO: Differentiate between restart and restart+clear-outputs
+        self.clear_outputs(cx);
         let kernel = std::mem::replace(&mut self.kernel, Kernel::Restarting);
 
         match kernel {
@@ -610,8 +1300,6 @@ impl Session {
 
                     // Start a new kernel
                     this.update(&mut cx, |session, cx| {
-                        // TODO: Differentiate between restart and restart+clear-outputs
-                        session.clear_outputs(cx);
                         session.start_kernel(cx);
                     })
                     .ok();
@@ -619,12 +1307,130 @@ impl Session {
                 .detach();
             }
             _ => {
-                self.clear_outputs(cx);
                 self.start_kernel(cx);
             }
         }
         cx.notify();
     }
+
+    /// The execution the kernel is running and the ones waiting for it, so that it's clear why
+    /// code hasn't run yet.
+    fn render_execution_queue(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        if self.execution_queue.is_empty() {
+            return None;
+        }
+
+        let current_execution = self.current_execution.as_ref().map(|execution| {
+            Self::render_queued_execution(
+                execution,
+                "Running",
+                Button::new("interrupt-execution", "Interrupt")
+                    .style(ButtonStyle::Subtle)
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(|session, _, cx| session.interrupt(cx))),
+            )
+        });
+        let queued_executions = self.execution_queue.iter().map(|execution| {
+            let message_id = execution.id().to_string();
+            Self::render_queued_execution(
+                execution,
+                "Queued",
+                Button::new(
+                    SharedString::from(format!("cancel-execution-{message_id}")),
+                    "Cancel",
+                )
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .on_click(
+                    cx.listener(move |session, _, cx| session.cancel_execution(&message_id, cx)),
+                ),
+            )
+        });
+
+        Some(
+            v_flex()
+                .pl_6()
+                .pb_1()
+                .children(current_execution)
+                .children(queued_executions),
+        )
+    }
+
+    fn render_queued_execution(
+        execution: &QueuedExecution,
+        status: &'static str,
+        button: Button,
+    ) -> impl IntoElement {
+        let code = execution.code.trim();
+        let first_line = code.lines().next().unwrap_or_default();
+        let summary = if first_line.len() < code.len() {
+            format!("{first_line} …")
+        } else {
+            first_line.to_string()
+        };
+
+        h_flex()
+            .gap_2()
+            .child(
+                Label::new(status)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .overflow_hidden()
+                    .child(Label::new(summary).size(LabelSize::Small).single_line()),
+            )
+            .child(button)
+    }
+}
+
+/// How many rows away from where a saved cell's code was, the code is looked for when the file
+/// has changed since.
+const MAX_MOVED_CODE_ROWS: u32 = 100;
+
+/// The range of a saved cell's code: where the code started before, or else where it is now near
+/// there, as long as it is there only once.
+fn find_code(buffer: &MultiBufferSnapshot, cell: &SerializedCell) -> Option<Range<usize>> {
+    let code = &cell.execution.code;
+    if code.is_empty() {
+        return None;
+    }
+
+    let start =
+        buffer.point_to_offset(buffer.clip_point(Point::new(cell.row, cell.column), Bias::Left));
+    if buffer.contains_str_at(start, code) {
+        return Some(start..start + code.len());
+    }
+
+    let code_rows = code.matches('\n').count() as u32;
+    let search_start = buffer.point_to_offset(buffer.clip_point(
+        Point::new(cell.row.saturating_sub(MAX_MOVED_CODE_ROWS), 0),
+        Bias::Left,
+    ));
+    let search_end = buffer.point_to_offset(
+        buffer.clip_point(
+            Point::new(
+                cell.row
+                    .saturating_add(code_rows)
+                    .saturating_add(MAX_MOVED_CODE_ROWS),
+                u32::MAX,
+            ),
+            Bias::Left,
+        ),
+    );
+    let text = buffer
+        .text_for_range(search_start..search_end)
+        .collect::<String>();
+    let mut matches = text.match_indices(code.as_str());
+    let (start, _) = matches.next()?;
+    if matches.next().is_some() {
+        return None;
+    }
+    let start = search_start + start;
+    Some(start..start + code.len())
 }
 
 pub enum SessionEvent {
@@ -656,7 +1462,7 @@ impl Render for Session {
             Kernel::Restarting => (Some("Restarting".into()), None),
         };
 
-        KernelListItem::new(self.kernel_specification.clone())
+        let kernel_list_item = KernelListItem::new(self.kernel_specification.clone())
             .status_color(match &self.kernel {
                 Kernel::RunningKernel(kernel) => match kernel.execution_state() {
                     ExecutionState::Idle => Color::Success,
@@ -670,6 +1476,24 @@ impl Render for Session {
             })
             .child(Label::new(self.kernel_specification.name()))
             .children(status_text.map(|status_text| Label::new(format!("({status_text})"))))
+            .children(
+                self.kernel_specification
+                    .environment()
+                    .map(|environment| Label::new(environment).color(Color::Muted)),
+            )
+            .children(
+                self.file_name(cx)
+                    .map(|file_name| Label::new(file_name).color(Color::Muted)),
+            )
+            .buttons(interrupt_button)
+            .button(
+                Button::new("restart", "Restart")
+                    .style(ButtonStyle::Subtle)
+                    .disabled(self.kernel.is_shutting_down())
+                    .on_click(cx.listener(move |session, _, cx| {
+                        session.restart(cx);
+                    })),
+            )
             .button(
                 Button::new("shutdown", "Shutdown")
                     .style(ButtonStyle::Subtle)
@@ -678,6 +1502,435 @@ impl Render for Session {
                         session.shutdown(cx);
                     })),
             )
-            .buttons(interrupt_button)
+            .buttons(
+                crate::worktree_id_for_editor(self.editor.clone(), cx).map(|worktree_id| {
+                    let editor = self.editor.clone();
+                    KernelSelector::new(
+                        Box::new(move |kernel_specification, cx| {
+                            crate::assign_kernelspec(kernel_specification, editor.clone(), cx)
+                                .log_err();
+                        }),
+                        worktree_id,
+                        Button::new("change-kernel", "Change Kernel").style(ButtonStyle::Subtle),
+                    )
+                    .with_current_kernelspec(Some(self.kernel_specification.clone()))
+                }),
+            );
+
+        v_flex()
+            .child(kernel_list_item)
+            .children(self.render_execution_queue(cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernels::{LocalKernelSpecification, RunningKernel};
+    use futures::channel::mpsc;
+    use gpui::{TestAppContext, VisualTestContext};
+    use jupyter_protocol::JupyterKernelspec;
+    use language::Buffer;
+    use project::Project;
+    use runtimelib::{ErrorOutput, KernelInfoReply, Status};
+    use serde_json::json;
+    use std::{iter, path::PathBuf};
+    use workspace::AppState;
+
+    fn python_kernelspec() -> KernelSpecification {
+        KernelSpecification::Jupyter(LocalKernelSpecification {
+            name: "python3".to_string(),
+            path: PathBuf::from("/kernels/python3"),
+            kernelspec: JupyterKernelspec {
+                argv: vec!["python3".to_string()],
+                display_name: "Python 3".to_string(),
+                language: "python".to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        })
+    }
+
+    fn stream_output(text: &str) -> Value {
+        json!({ "output_type": "stream", "name": "stdout", "text": text })
+    }
+
+    fn serialized_session() -> SerializedSession {
+        SerializedSession {
+            cells: vec![SerializedCell {
+                row: 1,
+                column: 0,
+                execution: SerializedExecution {
+                    code: "print(x)".to_string(),
+                    execution_count: Some(2),
+                    outputs: vec![stream_output("1\n")],
+                },
+            }],
+            history: vec![
+                SerializedExecution {
+                    code: "x = 1".to_string(),
+                    execution_count: Some(1),
+                    outputs: Vec::new(),
+                },
+                SerializedExecution {
+                    code: "print(x)".to_string(),
+                    execution_count: Some(2),
+                    outputs: vec![stream_output("1\n")],
+                },
+            ],
+        }
+    }
+
+    async fn init_test(
+        text: &str,
+        cx: &mut TestAppContext,
+    ) -> (View<Editor>, Arc<dyn Fs>, &mut VisualTestContext) {
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+            editor::init(cx);
+            JupyterSettings::register(cx);
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+        let editor = workspace.update(cx, |workspace, cx| {
+            let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+            editor
+        });
+        (editor, app_state.fs.clone(), cx)
+    }
+
+    #[gpui::test]
+    async fn test_restored_session(cx: &mut TestAppContext) {
+        let (editor, fs, cx) = init_test("x = 1\nprint(x)\n", cx).await;
+
+        let session = cx.new_view(|cx| {
+            Session::restored(
+                editor.downgrade(),
+                fs.clone(),
+                python_kernelspec(),
+                serialized_session(),
+                cx,
+            )
+        });
+        session.update(cx, |session, cx| {
+            assert!(matches!(session.kernel, Kernel::Shutdown));
+            assert_eq!(session.blocks.len(), 1);
+            assert!(session
+                .blocks
+                .values()
+                .all(|block| block.execution_view.read(cx).is_stale()));
+            assert!(session
+                .history
+                .iter()
+                .all(|entry| entry.execution_view.read(cx).is_stale()));
+            assert_eq!(session.last_executed_at(), None);
+        });
+
+        // Saving the restored session and restoring it again gives back the same session
+        let serialized = session.update(cx, |session, cx| session.serialize(&editor, cx));
+        assert_eq!(serialized.cells.len(), 1);
+        assert_eq!(serialized.cells[0].row, 1);
+        assert_eq!(serialized.history.len(), 2);
+        let json = serialized.to_json().unwrap();
+        let restored = cx.new_view(|cx| {
+            Session::restored(
+                editor.downgrade(),
+                fs.clone(),
+                python_kernelspec(),
+                serde_json::from_str(&json).unwrap(),
+                cx,
+            )
+        });
+        let restored_json = restored.update(cx, |session, cx| {
+            session.serialize(&editor, cx).to_json().unwrap()
+        });
+        assert_eq!(restored_json, json);
+    }
+
+    #[gpui::test]
+    async fn test_executions_queued_while_restarting(cx: &mut TestAppContext) {
+        let (editor, fs, cx) = init_test("x = 1\nprint(x)\n", cx).await;
+
+        let session = cx.new_view(|cx| {
+            Session::restored(
+                editor.downgrade(),
+                fs.clone(),
+                python_kernelspec(),
+                serialized_session(),
+                cx,
+            )
+        });
+        session.update(cx, |session, cx| {
+            session.clear_outputs(cx);
+            session.kernel = Kernel::Restarting;
+
+            let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+            for (code, range) in [("x = 1", 0..5), ("print(x)", 6..14)] {
+                let anchor_range =
+                    buffer.anchor_before(range.start)..buffer.anchor_after(range.end);
+                session.execute(code.to_string(), anchor_range, None, false, cx);
+            }
+
+            assert!(session.current_execution.is_none());
+            assert_eq!(
+                session
+                    .execution_queue
+                    .iter()
+                    .map(|execution| execution.code.as_str())
+                    .collect::<Vec<_>>(),
+                ["x = 1", "print(x)"]
+            );
+            assert_eq!(session.blocks.len(), 2);
+            assert!(session.blocks.values().all(|block| matches!(
+                block.execution_view.read(cx).status,
+                ExecutionStatus::Restarting
+            )));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_execution_queue(cx: &mut TestAppContext) {
+        let (editor, fs, cx) = init_test("x = 1\nprint(x)\nprint(y)\nx += 1\n", cx).await;
+        let (session, mut requests) = running_session(&editor, fs, cx);
+
+        session.update(cx, |session, cx| {
+            execute(session, &editor, "x = 1", 0..5, cx);
+            execute(session, &editor, "print(x)", 6..14, cx);
+            execute(session, &editor, "print(y)", 15..23, cx);
+            execute(session, &editor, "x += 1", 24..30, cx);
+        });
+        assert_eq!(sent_requests(&mut requests), ["x = 1"]);
+        assert_eq!(
+            queued_codes(&session, cx),
+            ["print(x)", "print(y)", "x += 1"]
+        );
+
+        // Cancelling queued code takes it off the queue
+        session.update(cx, |session, cx| {
+            let message_id = execution_id(session, "x += 1");
+            session.cancel_execution(&message_id, cx);
+            assert!(is_cancelled(session, &message_id, cx));
+        });
+        assert_eq!(queued_codes(&session, cx), ["print(x)", "print(y)"]);
+
+        // The next code is sent once the kernel is idle, even before the execute reply comes
+        session.update(cx, |session, cx| {
+            let current = session.current_execution.as_ref().unwrap().message.clone();
+            session.route(&idle_status(&current), cx);
+        });
+        assert_eq!(sent_requests(&mut requests), ["print(x)"]);
+        assert_eq!(queued_codes(&session, cx), ["print(y)"]);
+
+        // Code that raises an error cancels the code queued after it
+        session.update(cx, |session, cx| {
+            let queued_id = execution_id(session, "print(y)");
+            let current = session.current_execution.as_ref().unwrap().message.clone();
+            let error = ErrorOutput {
+                ename: "NameError".to_string(),
+                evalue: "name 'y' is not defined".to_string(),
+                traceback: Vec::new(),
+            };
+            session.route(&JupyterMessage::new(error, Some(&current)), cx);
+            session.route(&idle_status(&current), cx);
+
+            assert!(session.current_execution.is_none());
+            assert!(is_cancelled(session, &queued_id, cx));
+        });
+        assert_eq!(sent_requests(&mut requests), Vec::<String>::new());
+        assert_eq!(queued_codes(&session, cx), Vec::<String>::new());
+    }
+
+    #[gpui::test]
+    async fn test_interrupt_cancels_queued_executions(cx: &mut TestAppContext) {
+        let (editor, fs, cx) = init_test("x = 1\nprint(x)\n", cx).await;
+        let (session, mut requests) = running_session(&editor, fs, cx);
+
+        session.update(cx, |session, cx| {
+            execute(session, &editor, "x = 1", 0..5, cx);
+            execute(session, &editor, "print(x)", 6..14, cx);
+            let queued_id = execution_id(session, "print(x)");
+            session.interrupt(cx);
+
+            assert!(session.current_execution.is_none());
+            assert!(is_cancelled(session, &queued_id, cx));
+        });
+        assert_eq!(sent_requests(&mut requests), ["x = 1", "interrupt"]);
+        assert_eq!(queued_codes(&session, cx), Vec::<String>::new());
+
+        // Code run after the interruption doesn't wait for the interrupted code
+        session.update(cx, |session, cx| {
+            execute(session, &editor, "print(x)", 6..14, cx);
+        });
+        assert_eq!(sent_requests(&mut requests), ["print(x)"]);
+    }
+
+    #[derive(Debug)]
+    struct FakeKernel {
+        request_tx: mpsc::Sender<JupyterMessage>,
+        working_directory: PathBuf,
+        execution_state: ExecutionState,
+        kernel_info: Option<KernelInfoReply>,
+    }
+
+    impl RunningKernel for FakeKernel {
+        fn request_tx(&self) -> mpsc::Sender<JupyterMessage> {
+            self.request_tx.clone()
+        }
+
+        fn working_directory(&self) -> &PathBuf {
+            &self.working_directory
+        }
+
+        fn execution_state(&self) -> &ExecutionState {
+            &self.execution_state
+        }
+
+        fn set_execution_state(&mut self, state: ExecutionState) {
+            self.execution_state = state;
+        }
+
+        fn kernel_info(&self) -> Option<&KernelInfoReply> {
+            self.kernel_info.as_ref()
+        }
+
+        fn set_kernel_info(&mut self, info: KernelInfoReply) {
+            self.kernel_info = Some(info);
+        }
+
+        fn force_shutdown(&mut self, _: &mut WindowContext) -> Task<anyhow::Result<()>> {
+            Task::ready(Ok(()))
+        }
+    }
+
+    /// A session without outputs whose kernel sends its requests to the returned receiver.
+    fn running_session(
+        editor: &View<Editor>,
+        fs: Arc<dyn Fs>,
+        cx: &mut VisualTestContext,
+    ) -> (View<Session>, mpsc::Receiver<JupyterMessage>) {
+        let (request_tx, requests) = mpsc::channel(16);
+        let session = cx.new_view(|cx| {
+            let mut session = Session::restored(
+                editor.downgrade(),
+                fs,
+                python_kernelspec(),
+                serialized_session(),
+                cx,
+            );
+            session.clear_outputs(cx);
+            session.kernel = Kernel::RunningKernel(Box::new(FakeKernel {
+                request_tx,
+                working_directory: PathBuf::from("/"),
+                execution_state: ExecutionState::Idle,
+                kernel_info: None,
+            }));
+            session
+        });
+        (session, requests)
+    }
+
+    fn execute(
+        session: &mut Session,
+        editor: &View<Editor>,
+        code: &str,
+        range: Range<usize>,
+        cx: &mut ViewContext<Session>,
+    ) {
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let anchor_range = buffer.anchor_before(range.start)..buffer.anchor_after(range.end);
+        session.execute(code.to_string(), anchor_range, None, false, cx);
+    }
+
+    /// The id of the running or queued execution of the given code.
+    fn execution_id(session: &Session, code: &str) -> String {
+        session
+            .current_execution
+            .iter()
+            .chain(&session.execution_queue)
+            .find(|execution| execution.code == code)
+            .map(|execution| execution.id().to_string())
+            .unwrap()
+    }
+
+    fn is_cancelled(session: &Session, message_id: &str, cx: &AppContext) -> bool {
+        matches!(
+            session.blocks[message_id].execution_view.read(cx).status,
+            ExecutionStatus::Cancelled
+        )
+    }
+
+    fn idle_status(parent: &JupyterMessage) -> JupyterMessage {
+        JupyterMessage::new(
+            Status {
+                execution_state: ExecutionState::Idle,
+            },
+            Some(parent),
+        )
+    }
+
+    fn queued_codes(session: &View<Session>, cx: &mut VisualTestContext) -> Vec<String> {
+        session.read_with(cx, |session, _| {
+            session
+                .execution_queue
+                .iter()
+                .map(|execution| execution.code.clone())
+                .collect()
+        })
+    }
+
+    /// The code of the execute requests sent to the kernel since the last call, and "interrupt"
+    /// for the interrupt requests.
+    fn sent_requests(requests: &mut mpsc::Receiver<JupyterMessage>) -> Vec<String> {
+        iter::from_fn(|| requests.try_next().ok().flatten())
+            .map(|message| match message.content {
+                JupyterMessageContent::ExecuteRequest(request) => request.code,
+                JupyterMessageContent::InterruptRequest(_) => "interrupt".to_string(),
+                content => format!("{content:?}"),
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_find_code(cx: &mut AppContext) {
+        let cell = |row, code: &str| SerializedCell {
+            row,
+            column: 0,
+            execution: SerializedExecution {
+                code: code.to_string(),
+                execution_count: None,
+                outputs: Vec::new(),
+            },
+        };
+
+        let buffer = MultiBuffer::build_simple("x = 1\nprint(x)\n", cx);
+        let snapshot = buffer.read(cx).snapshot(cx);
+        // Where the code was before
+        assert_eq!(find_code(&snapshot, &cell(1, "print(x)")), Some(6..14));
+        // Where the code moved to
+        assert_eq!(find_code(&snapshot, &cell(0, "print(x)")), Some(6..14));
+        // Code that is gone
+        assert_eq!(find_code(&snapshot, &cell(1, "print(y)")), None);
+        assert_eq!(find_code(&snapshot, &cell(1, "")), None);
+
+        // Code that moved and is now there more than once
+        let buffer = MultiBuffer::build_simple("print(x)\nx = 1\nprint(x)\n", cx);
+        let snapshot = buffer.read(cx).snapshot(cx);
+        assert_eq!(find_code(&snapshot, &cell(1, "print(x)")), None);
+
+        // Code that moved too far away
+        let text = format!(
+            "{}print(x)\n",
+            "\n".repeat(MAX_MOVED_CODE_ROWS as usize + 10)
+        );
+        let buffer = MultiBuffer::build_simple(&text, cx);
+        let snapshot = buffer.read(cx).snapshot(cx);
+        assert_eq!(find_code(&snapshot, &cell(0, "print(x)")), None);
     }
 }
@@ -0,0 +1,353 @@
+use crate::{ProjectIndex, SearchResult, SemanticDb};
+use anyhow::Result;
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Bias, Editor};
+use fs::Fs;
+use gpui::{
+    actions, rems, AppContext, AsyncAppContext, DismissEvent, Model, Task, ViewContext, WeakView,
+    WindowContext,
+};
+use language::{Location, Point};
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectPath, Worktree};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::NotificationId, Toast, Workspace};
+
+actions!(semantic_index, [GoToCode]);
+
+const MAX_RESULTS: usize = 20;
+/// Searching sends the query to the embedding provider, so wait for the user
+/// to stop typing first.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &GoToCode, cx| toggle(workspace, cx));
+        },
+    )
+    .detach();
+}
+
+fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    let project_index = cx
+        .has_global::<SemanticDb>()
+        .then(|| cx.update_global(|db: &mut SemanticDb, cx| db.project_index(project.clone(), cx)))
+        .flatten();
+    let Some(project_index) = project_index else {
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<GoToCode>(),
+                "This project has no semantic index",
+            )
+            .autohide(),
+            cx,
+        );
+        return;
+    };
+
+    let handle = cx.view().downgrade();
+    workspace.toggle_modal(cx, move |cx| {
+        let delegate = GoToCodeDelegate::new(handle, project, project_index);
+        Picker::uniform_list(delegate, cx).width(rems(34.))
+    });
+}
+
+struct CodeMatch {
+    project_path: ProjectPath,
+    row_range: Range<u32>,
+    /// The first non-blank line of the matched code.
+    preview: SharedString,
+}
+
+pub struct GoToCodeDelegate {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    project_index: Model<ProjectIndex>,
+    query: String,
+    matches: Vec<CodeMatch>,
+    selected_index: usize,
+}
+
+impl GoToCodeDelegate {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        project_index: Model<ProjectIndex>,
+    ) -> Self {
+        Self {
+            workspace,
+            project,
+            project_index,
+            query: String::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn open_match(&self, code_match: &CodeMatch, cx: &mut ViewContext<Picker<Self>>) {
+        let position = Point::new(code_match.row_range.start, 0);
+        let open_task = self.workspace.update(cx, |workspace, cx| {
+            workspace.open_path(code_match.project_path.clone(), None, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task?.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    let position = editor
+                        .buffer()
+                        .read(cx)
+                        .snapshot(cx)
+                        .clip_point(position, Bias::Left);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens every match in a multibuffer, best match first.
+    fn open_all_matches(&self, cx: &mut ViewContext<Picker<Self>>) {
+        let buffers = self
+            .matches
+            .iter()
+            .map(|code_match| {
+                let buffer = self.project.update(cx, |project, cx| {
+                    project.open_buffer(code_match.project_path.clone(), cx)
+                });
+                (buffer, code_match.row_range.clone())
+            })
+            .collect::<Vec<_>>();
+        let title = format!("Code for \u{201c}{}\u{201d}", self.query);
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let mut locations = Vec::new();
+            for (buffer, row_range) in buffers {
+                let Some(buffer) = buffer.await.log_err() else {
+                    continue;
+                };
+                let range = buffer.update(&mut cx, |buffer, _| {
+                    buffer.anchor_before(Point::new(row_range.start, 0))
+                        ..buffer.anchor_after(Point::new(row_range.end, 0))
+                })?;
+                locations.push(Location { buffer, range });
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                Editor::open_locations_in_multibuffer(workspace, locations, title, false, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for GoToCodeDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Describe the code you're looking for...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.query.trim().is_empty() {
+            "Type a description, e.g. \u{201c}where are file events debounced\u{201d}".into()
+        } else {
+            "No matching code".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        if query.trim().is_empty() {
+            self.matches.clear();
+            return Task::ready(());
+        }
+
+        let project_index = self.project_index.clone();
+        let fs = project_index.read(cx).fs();
+        cx.spawn(|picker, mut cx| async move {
+            cx.background_executor().timer(SEARCH_DEBOUNCE).await;
+            let Some(search) = project_index
+                .read_with(&cx, |index, cx| index.search(vec![query], MAX_RESULTS, cx))
+                .log_err()
+            else {
+                return;
+            };
+            let Some(results) = search.await.log_err() else {
+                return;
+            };
+            let matches = load_matches(results, &fs, &cx).await;
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.matches = matches;
+                    picker.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    /// Opens the selected match, or every match when `secondary` is set.
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(code_match) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        if secondary {
+            self.open_all_matches(cx);
+        } else {
+            self.open_match(code_match, cx);
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let code_match = self.matches.get(ix)?;
+        let location = format!(
+            "{}:{}",
+            code_match.project_path.path.to_string_lossy(),
+            code_match.row_range.start + 1
+        );
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex()
+                        .child(Label::new(code_match.preview.clone()))
+                        .child(
+                            Label::new(location)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+/// Resolves the rows of each search result, dropping results that overlap a
+/// better one in the same file.
+async fn load_matches(
+    mut results: Vec<SearchResult>,
+    fs: &Arc<dyn Fs>,
+    cx: &AsyncAppContext,
+) -> Vec<CodeMatch> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut contents = HashMap::<(Model<Worktree>, Arc<Path>), Option<String>>::default();
+    let mut matches = Vec::<CodeMatch>::new();
+    for result in results {
+        let key = (result.worktree.clone(), result.path.clone());
+        if !contents.contains_key(&key) {
+            let content = load_file(&result, fs, cx).await.log_err();
+            contents.insert(key.clone(), content);
+        }
+        let Some(Some(content)) = contents.get(&key) else {
+            continue;
+        };
+        let Some(worktree_id) = result
+            .worktree
+            .read_with(cx, |worktree, _| worktree.id())
+            .log_err()
+        else {
+            continue;
+        };
+        let Some((row_range, preview)) = resolve_rows(content, &result.range) else {
+            continue;
+        };
+        let overlaps_better_match = matches.iter().any(|code_match| {
+            code_match.project_path.worktree_id == worktree_id
+                && code_match.project_path.path == result.path
+                && code_match.row_range.start < row_range.end
+                && row_range.start < code_match.row_range.end
+        });
+        if !overlaps_better_match {
+            matches.push(CodeMatch {
+                project_path: ProjectPath {
+                    worktree_id,
+                    path: result.path,
+                },
+                row_range,
+                preview: preview.into(),
+            });
+        }
+    }
+    matches
+}
+
+async fn load_file(
+    result: &SearchResult,
+    fs: &Arc<dyn Fs>,
+    cx: &AsyncAppContext,
+) -> Result<String> {
+    let abs_path = result
+        .worktree
+        .read_with(cx, |worktree, _| worktree.abs_path().join(&result.path))?;
+    fs.load(&abs_path).await
+}
+
+/// The rows spanned by `range` within `content` (end exclusive), and the first
+/// non-blank line in it.
+fn resolve_rows(content: &str, range: &Range<usize>) -> Option<(Range<u32>, String)> {
+    let mut start = range.start.min(content.len());
+    let mut end = range.end.min(content.len());
+    while !content.is_char_boundary(start) {
+        start += 1;
+    }
+    while !content.is_char_boundary(end) {
+        end += 1;
+    }
+    let first_row = content[..start].matches('\n').count();
+    let (preview_offset, _) = content[start..end]
+        .split('\n')
+        .enumerate()
+        .find(|(_, line)| !line.trim().is_empty())?;
+    let preview_row = first_row + preview_offset;
+    let preview = content.lines().nth(preview_row)?.trim().to_string();
+    let end_row = first_row + content[start..end].trim_end().matches('\n').count() + 1;
+    Some((preview_row as u32..end_row as u32, preview))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_rows() {
+        let content = "fn a() {}\n\n\nfn debounce() {\n    wait();\n}\n";
+        let start = content.find("\n\nfn debounce").unwrap();
+        assert_eq!(
+            resolve_rows(content, &(start..content.len())),
+            Some((3..6, "fn debounce() {".to_string()))
+        );
+        assert_eq!(
+            resolve_rows(content, &(0..4)),
+            Some((0..1, "fn a() {}".to_string()))
+        );
+        assert_eq!(resolve_rows(content, &(10..12)), None);
+    }
+}
@@ -1,6 +1,7 @@
 mod chunking;
 mod embedding;
 mod embedding_index;
+mod go_to_code;
 mod indexing;
 mod project_index;
 mod project_index_debug_view;
@@ -24,10 +25,15 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
+pub use go_to_code::GoToCode;
 pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
 pub use summary_index::FileSummary;
 
+pub fn init(cx: &mut AppContext) {
+    go_to_code::init(cx);
+}
+
 pub struct SemanticDb {
     embedding_provider: Arc<dyn EmbeddingProvider>,
     db_connection: Option<heed::Env>,
@@ -0,0 +1,66 @@
+use collections::HashMap;
+
+const COMMAND_VARIABLE_PREFIX: &str = "${command:";
+
+/// The commands of the `${command:...}` variables in the string, in the order they appear.
+pub(crate) fn variable_commands(template_str: &str) -> impl Iterator<Item = &str> {
+    let mut rest = template_str;
+    std::iter::from_fn(move || {
+        let start = rest.find(COMMAND_VARIABLE_PREFIX)? + COMMAND_VARIABLE_PREFIX.len();
+        let end = start + rest[start..].find('}')?;
+        let command = rest[start..end].trim();
+        rest = &rest[end + 1..];
+        Some(command)
+    })
+    .filter(|command| !command.is_empty())
+}
+
+/// Replaces the `${command:...}` variables in the string with the outputs of their commands,
+/// leaving the ones without an output as is.
+pub(crate) fn substitute_commands(template_str: &str, outputs: &HashMap<String, String>) -> String {
+    let mut substituted = String::with_capacity(template_str.len());
+    let mut rest = template_str;
+    while let Some(start) = rest.find(COMMAND_VARIABLE_PREFIX) {
+        let command_start = start + COMMAND_VARIABLE_PREFIX.len();
+        let Some(end) = rest[command_start..]
+            .find('}')
+            .map(|end| command_start + end)
+        else {
+            break;
+        };
+        substituted.push_str(&rest[..start]);
+        match outputs.get(rest[command_start..end].trim()) {
+            Some(output) => substituted.push_str(output),
+            None => substituted.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_variables() {
+        let template = "build ${command:git rev-parse --short HEAD} on ${command: uname }, $ZED_FILE ${command:missing} ${command:unclosed";
+        assert_eq!(
+            variable_commands(template).collect::<Vec<_>>(),
+            vec!["git rev-parse --short HEAD", "uname", "missing"]
+        );
+
+        let outputs = HashMap::from_iter([
+            (
+                "git rev-parse --short HEAD".to_string(),
+                "1a2b3c4".to_string(),
+            ),
+            ("uname".to_string(), "Linux".to_string()),
+        ]);
+        assert_eq!(
+            substitute_commands(template, &outputs),
+            "build 1a2b3c4 on Linux, $ZED_FILE ${command:missing} ${command:unclosed"
+        );
+    }
+}
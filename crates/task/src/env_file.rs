@@ -0,0 +1,162 @@
+use collections::HashMap;
+
+/// Parses the `KEY=value` lines of a `.env` file, skipping the comments and the malformed lines.
+/// Keys may be prefixed with `export`, values may be single quoted (taken literally),
+/// double quoted (with `\n`-like escapes) or unquoted (up to a ` #` comment).
+/// Quoted values may span multiple lines.
+pub fn parse_env_file(contents: &str) -> HashMap<String, String> {
+    let mut variables = HashMap::default();
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            continue;
+        }
+
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut quoted = value[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote_index(&quoted, quote) {
+                        break Some(end);
+                    }
+                    let Some(next_line) = lines.next() else {
+                        break None;
+                    };
+                    quoted.push('\n');
+                    quoted.push_str(next_line);
+                };
+                // An unterminated quote would take the rest of the file as a value.
+                let Some(end) = end else {
+                    break;
+                };
+                quoted.truncate(end);
+                if quote == '"' {
+                    unescape(&quoted)
+                } else {
+                    quoted
+                }
+            }
+            _ => strip_inline_comment(value).trim_end().to_string(),
+        };
+        variables.insert(key.to_string(), value);
+    }
+    variables
+}
+
+fn is_valid_key(key: &str) -> bool {
+    key.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn closing_quote_index(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (ix, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(ix);
+        }
+    }
+    None
+}
+
+fn strip_inline_comment(value: &str) -> &str {
+    let comment_start = value
+        .char_indices()
+        .find(|&(ix, c)| c == '#' && value[..ix].ends_with(char::is_whitespace))
+        .map(|(ix, _)| ix);
+    match comment_start {
+        Some(ix) => &value[..ix],
+        None => value,
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(escaped @ ('"' | '\\' | '$')) => unescaped.push(escaped),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let variables = parse_env_file(
+            r#"
+# A comment
+PLAIN=value
+SPACED = spaced value   # an inline comment
+export EXPORTED=1
+HASH=a#b
+EMPTY=
+SINGLE='literal \n $HOME # not a comment'
+DOUBLE="line\nnext \"quoted\""
+MULTILINE="first
+second"
+not a variable
+1INVALID=skipped
+"#,
+        );
+        let expected = [
+            ("PLAIN", "value"),
+            ("SPACED", "spaced value"),
+            ("EXPORTED", "1"),
+            ("HASH", "a#b"),
+            ("EMPTY", ""),
+            ("SINGLE", "literal \\n $HOME # not a comment"),
+            ("DOUBLE", "line\nnext \"quoted\""),
+            ("MULTILINE", "first\nsecond"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+        assert_eq!(variables, expected);
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        let variables = parse_env_file("FIRST=1\nBROKEN=\"never closed\nSECOND=2\n");
+        assert_eq!(
+            variables,
+            HashMap::from_iter([("FIRST".to_string(), "1".to_string())])
+        );
+    }
+}
@@ -1,21 +1,27 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod command_variables;
+mod env_file;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
 
 use collections::{hash_map, HashMap, HashSet};
 use gpui::SharedString;
-use schemars::JsonSchema;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use env_file::parse_env_file;
+pub use task_template::{
+    CompoundMode, HideStrategy, TaskConcurrency, TaskGroup, TaskInput, TaskInputType,
+    TaskPlatformOverrides, TaskPresentation, TaskTemplate, TaskTemplates, TaskWatch,
+};
 pub use vscode_format::VsCodeTaskFile;
-pub use zed_actions::RevealTarget;
+pub use zed_actions::{RevealStrategy, RevealTarget};
 
 /// Task identifier, unique within the application.
 /// Based on it, task reruns and terminal tabs are managed.
@@ -53,11 +59,13 @@ pub struct SpawnInTerminal {
     /// What to do with the terminal pane and tab, after the command had finished.
     pub hide: HideStrategy,
     /// Which shell to use when spawning the task.
-    pub shell: Shell,
+    pub shell: TaskShell,
     /// Whether to show the task summary line in the task output (sucess/failure).
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Regexes of the file locations in the task output to turn into links.
+    pub file_link_patterns: Vec<String>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -71,6 +79,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// A context the task got resolved with, the one its dependencies get resolved with too.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -78,6 +88,11 @@ pub struct ResolvedTask {
     /// Further actions that need to take place after the resolved task is spawned,
     /// with all task variables resolved.
     pub resolved: Option<SpawnInTerminal>,
+    /// The rest of the commands of a parallel compound task, each spawned in its own
+    /// terminal along with [`Self::resolved`], which runs the first one.
+    pub parallel: Vec<SpawnInTerminal>,
+    /// The env files of the task, resolved against its working directory, until their variables are applied.
+    env_files: Vec<PathBuf>,
 }
 
 impl ResolvedTask {
@@ -86,13 +101,92 @@ impl ResolvedTask {
         &self.original_task
     }
 
+    /// A context the task template got resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
     /// Variables that were substituted during the task template resolution.
     pub fn substituted_variables(&self) -> &HashSet<VariableName> {
         &self.substituted_variables
     }
 
+    /// The env files whose variables still have to be added with [`Self::apply_env_files`] before the task is spawned.
+    pub fn env_files(&self) -> &[PathBuf] {
+        &self.env_files
+    }
+
+    /// Adds the variables of the task's env files, loaded by their paths, to the environment of every command to spawn.
+    /// Variables of the later files override the ones of the earlier files, and they all override the project's environment,
+    /// but neither the task's own `env`, its inputs nor the task variables.
+    pub fn apply_env_files(&mut self, loaded: &HashMap<PathBuf, HashMap<String, String>>) {
+        let mut variables = HashMap::default();
+        for path in std::mem::take(&mut self.env_files) {
+            if let Some(file_variables) = loaded.get(&path) {
+                variables.extend(file_variables.clone());
+            }
+        }
+        variables.retain(|name, _| {
+            !name.starts_with(ZED_VARIABLE_NAME_PREFIX)
+                && !self.original_task.env.contains_key(name)
+                && !self
+                    .original_task
+                    .inputs
+                    .iter()
+                    .any(|input| &input.env == name)
+        });
+        if variables.is_empty() {
+            return;
+        }
+        for spawn_in_terminal in self.spawns_mut() {
+            spawn_in_terminal.env.extend(variables.clone());
+        }
+    }
+
+    /// Everything to spawn for the task: [`Self::resolved`], followed by the
+    /// other commands of a parallel compound task.
+    pub fn spawns_mut(&mut self) -> impl Iterator<Item = &mut SpawnInTerminal> {
+        self.resolved.iter_mut().chain(self.parallel.iter_mut())
+    }
+
+    /// Appends the arguments given to the ones of every command to spawn,
+    /// e.g. the arguments asked for with [`TaskTemplate::prompt_for_args`].
+    pub fn append_args(&mut self, args: &[String]) {
+        if args.is_empty() {
+            return;
+        }
+        for spawn_in_terminal in self.spawns_mut() {
+            spawn_in_terminal.args.extend_from_slice(args);
+            spawn_in_terminal.command_label =
+                format!("{} {}", spawn_in_terminal.command_label, args.join(" "));
+        }
+    }
+
+    /// Passes the value typed for the input to every command to spawn, in the input's environment variable.
+    pub fn set_input(&mut self, input: &TaskInput, value: &str) {
+        for spawn_in_terminal in self.spawns_mut() {
+            spawn_in_terminal
+                .env
+                .insert(input.env.clone(), value.to_string());
+        }
+    }
+
+    /// The task without the values of its [`TaskInputType::Password`] inputs, as it is kept in the task history.
+    pub fn without_password_inputs(&self) -> Self {
+        let mut task = self.clone();
+        for input in self.original_task.password_inputs() {
+            for spawn_in_terminal in task.spawns_mut() {
+                spawn_in_terminal.env.remove(&input.env);
+            }
+        }
+        task
+    }
+
     /// A human-readable label to display in the UI.
     pub fn display_label(&self) -> &str {
+        if !self.parallel.is_empty() {
+            return self.resolved_label.as_str();
+        }
         self.resolved
             .as_ref()
             .map(|resolved| resolved.label.as_str())
@@ -128,6 +222,12 @@ pub enum VariableName {
     SelectedText,
     /// The symbol selected by the symbol tagging system, specifically the @run capture in a runnables.scm
     RunnableSymbol,
+    /// The current branch of the git repository that contains the currently opened file.
+    Branch,
+    /// The short SHA of the commit checked out in the git repository that contains the currently opened file.
+    CommitSha,
+    /// An absolute path of the root of the git repository that contains the currently opened file.
+    RepoRoot,
     /// Custom variable, provided by the plugin or other external source.
     /// Will be printed with `CUSTOM_` prefix to avoid potential conflicts with other variables.
     Custom(Cow<'static, str>),
@@ -161,6 +261,9 @@ impl FromStr for VariableName {
             "SELECTED_TEXT" => Self::SelectedText,
             "ROW" => Self::Row,
             "COLUMN" => Self::Column,
+            "BRANCH" => Self::Branch,
+            "COMMIT_SHA" => Self::CommitSha,
+            "REPO_ROOT" => Self::RepoRoot,
             _ => {
                 if let Some(custom_name) =
                     without_prefix.strip_prefix(ZED_CUSTOM_VARIABLE_NAME_PREFIX)
@@ -193,6 +296,9 @@ impl std::fmt::Display for VariableName {
             Self::Column => write!(f, "{ZED_VARIABLE_NAME_PREFIX}COLUMN"),
             Self::SelectedText => write!(f, "{ZED_VARIABLE_NAME_PREFIX}SELECTED_TEXT"),
             Self::RunnableSymbol => write!(f, "{ZED_VARIABLE_NAME_PREFIX}RUNNABLE_SYMBOL"),
+            Self::Branch => write!(f, "{ZED_VARIABLE_NAME_PREFIX}BRANCH"),
+            Self::CommitSha => write!(f, "{ZED_VARIABLE_NAME_PREFIX}COMMIT_SHA"),
+            Self::RepoRoot => write!(f, "{ZED_VARIABLE_NAME_PREFIX}REPO_ROOT"),
             Self::Custom(s) => write!(
                 f,
                 "{ZED_VARIABLE_NAME_PREFIX}{ZED_CUSTOM_VARIABLE_NAME_PREFIX}{s}"
@@ -259,6 +365,106 @@ pub struct TaskContext {
     /// This is the environment one would get when `cd`ing in a terminal
     /// into the project's root directory.
     pub project_env: HashMap<String, String>,
+    /// Outputs of the commands of the `${command:...}` task variables, by command.
+    /// The variables of the commands missing here are replaced with empty strings.
+    pub command_outputs: HashMap<String, String>,
+    /// The operating system the task runs on, as in [`std::env::consts::OS`], when it is not the one Zed runs on,
+    /// e.g. the remote host's for projects opened over SSH.
+    pub os: Option<String>,
+}
+
+/// Which shell a task's command runs in.
+/// In task files, it is either `"system"`, `"none"`, or a program with optional arguments,
+/// e.g. `{ "program": "nu", "args": ["--no-config-file"] }`; the terminal's [`Shell`] format is accepted too.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "TaskShellContent", into = "TaskShellContent")]
+pub enum TaskShell {
+    /// Run the command in the system's default shell.
+    #[default]
+    System,
+    /// Spawn the command directly with its arguments, without any shell.
+    None,
+    /// Run the command in a specific shell program.
+    Program {
+        /// The program to run.
+        program: String,
+        /// The arguments to pass to the program, before the ones running the command.
+        args: Vec<String>,
+    },
+}
+
+impl TaskShell {
+    /// A human readable form of the shell, e.g. `nu --no-config-file`.
+    pub fn label(&self) -> String {
+        match self {
+            Self::System => "system".to_string(),
+            Self::None => "none".to_string(),
+            Self::Program { program, args } => {
+                args.iter().fold(program.clone(), |mut label, arg| {
+                    label.push(' ');
+                    label.push_str(arg);
+                    label
+                })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum TaskShellKind {
+    System,
+    None,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum TaskShellContent {
+    Kind(TaskShellKind),
+    Program {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    Terminal(Shell),
+}
+
+impl From<TaskShellContent> for TaskShell {
+    fn from(content: TaskShellContent) -> Self {
+        match content {
+            TaskShellContent::Kind(TaskShellKind::System)
+            | TaskShellContent::Terminal(Shell::System) => Self::System,
+            TaskShellContent::Kind(TaskShellKind::None) => Self::None,
+            TaskShellContent::Program { program, args }
+            | TaskShellContent::Terminal(Shell::WithArguments { program, args, .. }) => {
+                Self::Program { program, args }
+            }
+            TaskShellContent::Terminal(Shell::Program(program)) => Self::Program {
+                program,
+                args: Vec::new(),
+            },
+        }
+    }
+}
+
+impl From<TaskShell> for TaskShellContent {
+    fn from(shell: TaskShell) -> Self {
+        match shell {
+            TaskShell::System => Self::Kind(TaskShellKind::System),
+            TaskShell::None => Self::Kind(TaskShellKind::None),
+            TaskShell::Program { program, args } => Self::Program { program, args },
+        }
+    }
+}
+
+impl JsonSchema for TaskShell {
+    fn schema_name() -> String {
+        "TaskShell".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        TaskShellContent::json_schema(generator)
+    }
 }
 
 /// This is a new type representing a 'tag' on a 'runnable symbol', typically a test of main() function, found via treesitter.
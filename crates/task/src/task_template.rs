@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, iter, path::PathBuf};
 use util::serde::default_true;
 
 use anyhow::{bail, Context};
@@ -9,8 +9,9 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    command_variables::{substitute_commands, variable_commands},
+    ResolvedTask, RevealStrategy, RevealTarget, SpawnInTerminal, TaskContext, TaskId, TaskShell,
+    VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,13 +26,27 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
     pub args: Vec<String>,
+    /// Commands to run instead of `command` and `args`, which makes the task a compound one.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// How the `commands` of a compound task run:
+    /// * `sequential` — one after another in a single terminal, stopping at the first one that fails (default)
+    /// * `parallel` — all at once, each in its own terminal
+    #[serde(default)]
+    pub mode: CompoundMode,
     /// Env overrides for the command, will be appended to the terminal's environment from the settings.
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Env files, e.g. `.env`, whose variables are added to the command's environment, relative to its `cwd`.
+    /// They are read when the task is spawned, on the machine the project is on.
+    /// Later files override the earlier ones, `env` overrides them all; missing files are skipped.
+    #[serde(default)]
+    pub env_files: Vec<String>,
     /// Current working directory to spawn the command into, defaults to current project root.
     #[serde(default)]
     pub cwd: Option<String>,
@@ -41,6 +56,12 @@ pub struct TaskTemplate {
     /// Whether to allow multiple instances of the same task to be run, or rather wait for the existing ones to finish.
     #[serde(default)]
     pub allow_concurrent_runs: bool,
+    /// What spawning the task again does while it is still running, unless `allow_concurrent_runs` lets the runs overlap:
+    /// * `queue` — start it once the running one finishes (default)
+    /// * `singleton` — do not start it again
+    /// * `kill_previous` — kill the running one and start it again
+    #[serde(default)]
+    pub concurrency: TaskConcurrency,
     /// What to do with the terminal pane and tab, after the command was started:
     /// * `always` — always show the task's pane, and focus the corresponding tab in it (default)
     // * `no_focus` — always show the task's pane, add the task's tab in it, but don't focus it
@@ -50,39 +71,185 @@ pub struct TaskTemplate {
     /// Where to place the task's terminal item after starting the task.
     /// * `dock` — in the terminal dock, "regular" terminal items' place (default).
     /// * `center` — in the central pane group, "main" editor area.
+    /// * `split` — in a new pane of the central pane group, split to the right of the active one.
+    /// * `window` — in a new window of the project.
     #[serde(default)]
     pub reveal_target: RevealTarget,
+    /// Where to place the task's terminal and whether to focus it, overriding `reveal_target` and `reveal`.
+    #[serde(default)]
+    pub presentation: Option<TaskPresentation>,
     /// What to do with the terminal pane and tab, after the command had finished:
     /// * `never` — do nothing when the command finishes (default)
     /// * `always` — always hide the terminal tab, hide the pane also if it was the last tab in it
     /// * `on_success` — hide the terminal tab on task success only, otherwise behaves similar to `always`.
     #[serde(default)]
     pub hide: HideStrategy,
-    /// Represents the tags which this template attaches to. Adding this removes this task from other UI.
+    /// Represents the tags which this template attaches to: runnables with these tags spawn this task,
+    /// and the tasks modal can be filtered by them with `#tag` words in its query.
     #[serde(default)]
     pub tags: Vec<String>,
-    /// Which shell to use when spawning the task.
+    /// The group of tasks this task belongs to, whose default task is spawned by a dedicated action:
+    /// * `build` — `task::RunBuildTask`
+    /// * `test` — `task::RunTestTask`
+    /// * `run` — `task::RunDefaultTask`
     #[serde(default)]
-    pub shell: Shell,
+    pub group: Option<TaskGroup>,
+    /// Which shell to run the command in: `"system"` (default), `"none"` to spawn it directly,
+    /// or a specific program with arguments, e.g. `{ "program": "nu", "args": ["--no-config-file"] }`.
+    #[serde(default)]
+    pub shell: TaskShell,
     /// Whether to show the task line in the task output.
     #[serde(default = "default_true")]
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Regexes finding file locations in the task output, turned into links that open the file at the location
+    /// when clicked with the secondary modifier held. The `path` named group captures the file path,
+    /// the optional `line` and `column` groups the position in it, e.g. `"(?<path>\\S+) line (?<line>\\d+)"`.
+    /// Locations written as `path:line:column` are linked without any pattern.
+    #[serde(default)]
+    pub file_link_patterns: Vec<String>,
+    /// Whether to start the task when a project with it is opened.
+    /// Tasks from a project's `.zed/tasks.json` only start once the project is trusted.
+    #[serde(default)]
+    pub run_on_open: bool,
+    /// Labels of the tasks that have to run, and succeed, before this task starts.
+    /// They run one after another, each after its own dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Whether to ask for more arguments, appended to `args`, when the task is spawned from the tasks modal.
+    #[serde(default)]
+    pub prompt_for_args: bool,
+    /// Values to ask for in the tasks modal before the task is spawned, passed to its process as environment variables.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+    /// Which file changes restart the task: while its terminal is open, the task is killed
+    /// and spawned again whenever files matching the globs change.
+    #[serde(default)]
+    pub watch: Option<TaskWatch>,
+    /// Overrides of the `command`, `args` and `shell` used when the task runs on Windows.
+    #[serde(default)]
+    pub windows: Option<TaskPlatformOverrides>,
+    /// Overrides of the `command`, `args` and `shell` used when the task runs on Linux.
+    #[serde(default)]
+    pub linux: Option<TaskPlatformOverrides>,
+    /// Overrides of the `command`, `args` and `shell` used when the task runs on macOS.
+    #[serde(default)]
+    pub macos: Option<TaskPlatformOverrides>,
+}
+
+/// Where the terminal of a task is placed and whether it gets focused, the missing ones are taken from
+/// the `reveal_target` and `reveal` of the template.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskPresentation {
+    /// Where to place the task's terminal: `dock`, `center`, `split` or `window`, as `reveal_target`.
+    #[serde(default)]
+    pub placement: Option<RevealTarget>,
+    /// Whether to focus the task's terminal: `true` reveals it as `"reveal": "always"` does,
+    /// `false` as `"reveal": "no_focus"` does.
+    #[serde(default)]
+    pub focus: Option<bool>,
 }
 
-/// What to do with the terminal pane and tab, after the command was started.
+/// The fields of a task template that differ on a specific platform, the missing ones are taken from the template.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskPlatformOverrides {
+    /// Executable command to spawn on this platform.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Arguments to the command on this platform.
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Which shell to run the command in on this platform.
+    #[serde(default)]
+    pub shell: Option<TaskShell>,
+}
+
+/// A value asked for in the tasks modal before the task is spawned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// The environment variable the typed value is passed to the task's process in.
+    pub env: String,
+    /// What to show in the prompt, the variable name if not set.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How the value is typed in and remembered.
+    #[serde(default, rename = "type")]
+    pub input_type: TaskInputType,
+}
+
+/// How the value of a [`TaskInput`] is typed in and remembered.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum RevealStrategy {
-    /// Always show the task's pane, and focus the corresponding tab in it.
+pub enum TaskInputType {
+    /// Shown as typed, kept in the task history to be reused on reruns.
     #[default]
-    Always,
-    /// Always show the task's pane, add the task's tab in it, but don't focus it.
-    NoFocus,
-    /// Do not alter focus, but still add/reuse the task's tab in its pane.
-    Never,
+    Text,
+    /// Masked while typed, never kept in the task history: asked for again on every run.
+    Password,
+}
+
+/// The files a task is restarted on changes of.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskWatch {
+    /// Globs of the paths to watch, matched against whole paths relative to the root of the
+    /// task's worktree, e.g. `src/**/*.rs`.
+    pub globs: Vec<String>,
+    /// How long to wait after the last change before restarting the task, in milliseconds.
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+fn default_watch_debounce_ms() -> u64 {
+    300
+}
+
+/// What spawning a task again does while it is still running.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskConcurrency {
+    /// Start the task once its running instance finishes.
+    #[default]
+    Queue,
+    /// Do not start the task while it is running.
+    Singleton,
+    /// Kill the running instance and start the task again.
+    KillPrevious,
+}
+
+/// A group of tasks with a default one, spawned by a dedicated action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskGroup {
+    Build,
+    Test,
+    Run,
+}
+
+impl TaskGroup {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Build => "Build",
+            Self::Test => "Test",
+            Self::Run => "Run",
+        }
+    }
+}
+
+/// How the commands of a compound task run.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompoundMode {
+    /// Run the commands one after another in a single terminal, stopping at the first one that fails.
+    #[default]
+    Sequential,
+    /// Run all commands at once, each in its own terminal.
+    Parallel,
 }
 
 /// What to do with the terminal pane and tab, after the command has finished.
@@ -119,10 +286,116 @@ impl TaskTemplate {
     /// If any replacement fails or the new string substitutions still have [`ZED_VARIABLE_NAME_PREFIX`],
     /// `None` is returned.
     ///
+    /// The `${command:...}` variables are replaced with the outputs of their commands, taken from the [`TaskContext`];
+    /// the commands are never run here, so a variable without an output there is replaced with an empty string.
+    ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        let template = self.with_platform_overrides(cx);
+        let variable_commands = template.variable_commands();
+        let mut resolved_task = if variable_commands.is_empty() {
+            template.resolve_task_variables(id_base, cx)?
+        } else {
+            let command_outputs = variable_commands
+                .into_iter()
+                .map(|command| {
+                    let output = cx.command_outputs.get(&command).cloned();
+                    (command, output.unwrap_or_default())
+                })
+                .collect::<HashMap<_, _>>();
+            template
+                .substitute_commands(&command_outputs)
+                .resolve_task_variables(id_base, cx)?
+        };
+        resolved_task.original_task = self.clone();
+        Some(resolved_task)
+    }
+
+    /// The overrides of the template for the operating system given, as in [`std::env::consts::OS`], if any.
+    pub fn platform_overrides(&self, os: &str) -> Option<&TaskPlatformOverrides> {
+        match os {
+            "windows" => self.windows.as_ref(),
+            "macos" => self.macos.as_ref(),
+            "linux" => self.linux.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The template with its `command`, `args` and `shell` overridden for the operating system the task runs on.
+    fn with_platform_overrides(&self, cx: &TaskContext) -> Cow<'_, Self> {
+        let os = cx.os.as_deref().unwrap_or(std::env::consts::OS);
+        let Some(overrides) = self.platform_overrides(os) else {
+            return Cow::Borrowed(self);
+        };
+        let mut template = self.clone();
+        if let Some(command) = &overrides.command {
+            template.command = command.clone();
+        }
+        if let Some(args) = &overrides.args {
+            template.args = args.clone();
+        }
+        if let Some(shell) = &overrides.shell {
+            template.shell = shell.clone();
+        }
+        Cow::Owned(template)
+    }
+
+    /// The inputs whose values are not kept in the task history.
+    pub fn password_inputs(&self) -> impl Iterator<Item = &TaskInput> {
+        self.inputs
+            .iter()
+            .filter(|input| input.input_type == TaskInputType::Password)
+    }
+
+    /// The distinct commands of the template's `${command:...}` variables.
+    pub fn variable_commands(&self) -> Vec<String> {
+        let mut commands = Vec::new();
+        for template_str in self.command_variable_strs() {
+            for command in variable_commands(template_str) {
+                if !commands.iter().any(|existing| existing == command) {
+                    commands.push(command.to_string());
+                }
+            }
+        }
+        commands
+    }
+
+    fn command_variable_strs(&self) -> impl Iterator<Item = &String> {
+        iter::once(&self.label)
+            .chain(iter::once(&self.command))
+            .chain(&self.args)
+            .chain(&self.commands)
+            .chain(&self.cwd)
+            .chain(self.env.values())
+    }
+
+    fn substitute_commands(&self, outputs: &HashMap<String, String>) -> Self {
+        let substitute = |template_str: &String| substitute_commands(template_str, outputs);
+        Self {
+            label: substitute(&self.label),
+            command: substitute(&self.command),
+            args: self.args.iter().map(substitute).collect(),
+            commands: self.commands.iter().map(substitute).collect(),
+            cwd: self.cwd.as_ref().map(substitute),
+            env: self
+                .env
+                .iter()
+                .map(|(name, value)| (name.clone(), substitute(value)))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    fn resolve_task_variables(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
+        let has_command = if self.commands.is_empty() {
+            !self.command.trim().is_empty()
+        } else {
+            self.commands
+                .iter()
+                .all(|command| !command.trim().is_empty())
+        };
+        if self.label.trim().is_empty() || !has_command {
             return None;
         }
 
@@ -188,6 +461,18 @@ impl TaskTemplate {
             &variable_names,
             &mut substituted_variables,
         )?;
+        let commands = substitute_all_template_variables_in_vec(
+            &self.commands,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
+        let env_files = substitute_all_template_variables_in_vec(
+            &self.env_files,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
 
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
@@ -201,7 +486,8 @@ impl TaskTemplate {
             // Start with the project environment as the base.
             let mut env = cx.project_env.clone();
 
-            // Extend that environment with what's defined in the TaskTemplate
+            // Extend that environment with what's defined in the TaskTemplate.
+            // The variables of its env files are added when the task is spawned, see `ResolvedTask::apply_env_files`.
             env.extend(self.env.clone());
 
             // Then we replace all task variables that could be set in environment variables
@@ -217,38 +503,144 @@ impl TaskTemplate {
             env
         };
 
-        Some(ResolvedTask {
-            id: id.clone(),
-            substituted_variables,
-            original_task: self.clone(),
-            resolved_label: full_label.clone(),
-            resolved: Some(SpawnInTerminal {
-                id,
-                cwd,
-                full_label,
-                label: human_readable_label,
-                command_label: args_with_substitutions.iter().fold(
-                    command.clone(),
-                    |mut command_label, arg| {
+        let (command, args, command_label) = if commands.is_empty() {
+            let command_label =
+                args_with_substitutions
+                    .iter()
+                    .fold(command.clone(), |mut command_label, arg| {
                         command_label.push(' ');
                         command_label.push_str(arg);
                         command_label
-                    },
-                ),
-                command,
-                args: self.args.clone(),
-                env,
-                use_new_terminal: self.use_new_terminal,
-                allow_concurrent_runs: self.allow_concurrent_runs,
-                reveal: self.reveal,
-                reveal_target: self.reveal_target,
-                hide: self.hide,
-                shell: self.shell.clone(),
-                show_summary: self.show_summary,
-                show_command: self.show_command,
-            }),
+                    });
+            // Without a shell, nothing substitutes the variables in the arguments when the task is spawned.
+            let args = if self.shell == TaskShell::None {
+                args_with_substitutions
+            } else {
+                self.args.clone()
+            };
+            (command, args, command_label)
+        } else {
+            let command = commands.join(" && ");
+            (command.clone(), Vec::new(), command)
+        };
+        let spawn_in_terminal = SpawnInTerminal {
+            id: id.clone(),
+            cwd,
+            full_label: full_label.clone(),
+            label: human_readable_label,
+            command_label,
+            command,
+            args,
+            env,
+            use_new_terminal: self.use_new_terminal,
+            allow_concurrent_runs: self.allow_concurrent_runs,
+            reveal: self
+                .presentation
+                .and_then(|presentation| presentation.focus)
+                .map_or(self.reveal, |focus| {
+                    if focus {
+                        RevealStrategy::Always
+                    } else {
+                        RevealStrategy::NoFocus
+                    }
+                }),
+            reveal_target: self
+                .presentation
+                .and_then(|presentation| presentation.placement)
+                .unwrap_or(self.reveal_target),
+            hide: self.hide,
+            shell: self.shell.clone(),
+            show_summary: self.show_summary,
+            show_command: self.show_command,
+            file_link_patterns: self.file_link_patterns.clone(),
+        };
+        let (resolved, parallel) = match self.mode {
+            CompoundMode::Parallel if commands.len() > 1 => {
+                let mut spawns = commands
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, command)| SpawnInTerminal {
+                        id: TaskId(format!("{}_{ix}", id.0)),
+                        full_label: format!("{full_label}: {command}"),
+                        label: format!("{}: {command}", spawn_in_terminal.label),
+                        command_label: command.clone(),
+                        command: command.clone(),
+                        args: Vec::new(),
+                        ..spawn_in_terminal.clone()
+                    });
+                (spawns.next(), spawns.collect())
+            }
+            _ => (Some(spawn_in_terminal), Vec::new()),
+        };
+
+        Some(ResolvedTask {
+            id,
+            task_context: cx.clone(),
+            substituted_variables,
+            original_task: self.clone(),
+            resolved_label: full_label,
+            resolved,
+            parallel,
+            env_files: env_files
+                .iter()
+                .map(|path| match &cwd {
+                    Some(cwd) => cwd.join(path),
+                    None => PathBuf::from(path),
+                })
+                .collect(),
         })
     }
+
+    /// Lists the tasks this task depends on, directly or through other dependencies,
+    /// in the order they have to run: every task comes after its own dependencies.
+    /// `find_task` looks a task up by its label, along with its source.
+    ///
+    /// Fails when a dependency can't be found, or when the tasks depend on each other in a cycle.
+    pub fn dependency_chain<S>(
+        &self,
+        mut find_task: impl FnMut(&str) -> Option<(S, TaskTemplate)>,
+    ) -> anyhow::Result<Vec<(S, TaskTemplate)>> {
+        fn visit<S>(
+            task: &TaskTemplate,
+            find_task: &mut impl FnMut(&str) -> Option<(S, TaskTemplate)>,
+            stack: &mut Vec<String>,
+            chain: &mut Vec<(S, TaskTemplate)>,
+        ) -> anyhow::Result<()> {
+            for label in &task.depends_on {
+                if chain
+                    .iter()
+                    .any(|(_, dependency)| &dependency.label == label)
+                {
+                    continue;
+                }
+                if let Some(ix) = stack.iter().position(|visited| visited == label) {
+                    let mut cycle = stack[ix..].to_vec();
+                    cycle.push(label.clone());
+                    bail!("tasks depend on each other: {}", cycle.join(" -> "));
+                }
+                let (source, dependency) = find_task(label).with_context(|| {
+                    format!(
+                        "task \"{}\" depends on unknown task \"{label}\"",
+                        task.label
+                    )
+                })?;
+                stack.push(label.clone());
+                visit(&dependency, find_task, stack, chain)?;
+                stack.pop();
+                chain.push((source, dependency));
+            }
+            Ok(())
+        }
+
+        let mut chain = Vec::new();
+        visit(
+            self,
+            &mut find_task,
+            &mut vec![self.label.clone()],
+            &mut chain,
+        )?;
+        Ok(chain)
+    }
 }
 
 const MAX_DISPLAY_VARIABLE_LENGTH: usize = 15;
@@ -422,6 +814,8 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
         };
         assert_eq!(
             resolved_task(&task_without_cwd, &cx).cwd,
@@ -434,6 +828,8 @@ mod tests {
             cwd: Some(context_cwd.clone()),
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
         };
         assert_eq!(
             resolved_task(&task_without_cwd, &cx).cwd,
@@ -450,6 +846,8 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
         };
         assert_eq!(
             resolved_task(&task_with_cwd, &cx).cwd,
@@ -461,6 +859,8 @@ mod tests {
             cwd: Some(context_cwd.clone()),
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
         };
         assert_eq!(
             resolved_task(&task_with_cwd, &cx).cwd,
@@ -537,6 +937,8 @@ mod tests {
                     cwd: None,
                     task_variables: TaskVariables::from_iter(all_variables.clone()),
                     project_env: HashMap::default(),
+                    command_outputs: HashMap::default(),
+                    os: None,
                 },
             ).unwrap_or_else(|| panic!("Should successfully resolve task {task_with_all_variables:?} with variables {all_variables:?}"));
 
@@ -625,6 +1027,8 @@ mod tests {
                     cwd: None,
                     task_variables: TaskVariables::from_iter(not_all_variables),
                     project_env: HashMap::default(),
+                    command_outputs: HashMap::default(),
+                    os: None,
                 },
             );
             assert_eq!(resolved_task_attempt, None, "If any of the Zed task variables is not substituted, the task should not be resolved, but got some resolution without the variable {removed_variable:?} (index {i})");
@@ -678,6 +1082,8 @@ mod tests {
                 "test_symbol".to_string(),
             ))),
             project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
         };
 
         for (i, symbol_dependent_task) in [
@@ -806,6 +1212,8 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::from_iter(all_variables.clone()),
             project_env,
+            command_outputs: HashMap::default(),
+            os: None,
         };
 
         let resolved = template
@@ -822,4 +1230,370 @@ mod tests {
             "overwritten"
         );
     }
+
+    #[test]
+    fn test_env_files() {
+        let template = TaskTemplate {
+            label: "my task".to_string(),
+            command: "echo".to_string(),
+            env: HashMap::from_iter([("TASK_ENV".to_string(), "from task".to_string())]),
+            env_files: vec![".env".to_string(), ".env.local".to_string()],
+            ..TaskTemplate::default()
+        };
+        let context = TaskContext {
+            cwd: Some(PathBuf::from("/project")),
+            task_variables: TaskVariables::default(),
+            project_env: HashMap::from_iter([(
+                "PROJECT_ENV".to_string(),
+                "from project".to_string(),
+            )]),
+            command_outputs: HashMap::default(),
+            os: None,
+        };
+
+        let mut resolved_task = template.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert_eq!(
+            resolved_task.env_files(),
+            [
+                PathBuf::from("/project/.env"),
+                PathBuf::from("/project/.env.local")
+            ]
+        );
+        resolved_task.apply_env_files(&HashMap::from_iter([
+            (
+                PathBuf::from("/project/.env"),
+                HashMap::from_iter([
+                    ("FILE_ENV".to_string(), "from .env".to_string()),
+                    ("OVERRIDDEN_ENV".to_string(), "from .env".to_string()),
+                    ("PROJECT_ENV".to_string(), "from .env".to_string()),
+                    ("TASK_ENV".to_string(), "from .env".to_string()),
+                    ("ZED_FILE".to_string(), "from .env".to_string()),
+                ]),
+            ),
+            (
+                PathBuf::from("/project/.env.local"),
+                HashMap::from_iter([("OVERRIDDEN_ENV".to_string(), "from .env.local".to_string())]),
+            ),
+        ]));
+        assert!(resolved_task.env_files().is_empty());
+
+        let env = &resolved_task.resolved.unwrap().env;
+        assert_eq!(env["FILE_ENV"], "from .env");
+        assert_eq!(env["OVERRIDDEN_ENV"], "from .env.local");
+        assert_eq!(env["PROJECT_ENV"], "from .env");
+        assert_eq!(env["TASK_ENV"], "from task");
+        assert!(!env.contains_key("ZED_FILE"));
+    }
+
+    #[test]
+    fn test_resolving_compound_tasks() {
+        let context = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([(
+                VariableName::Filename,
+                "main.rs".to_string(),
+            )]),
+            project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
+        };
+        let sequential = TaskTemplate {
+            label: "check".to_string(),
+            commands: vec![
+                "cargo fmt --check".to_string(),
+                "rustc $ZED_FILENAME".to_string(),
+            ],
+            ..TaskTemplate::default()
+        };
+        let resolved_task = sequential.resolve_task(TEST_ID_BASE, &context).unwrap();
+        let resolved = resolved_task.resolved.as_ref().unwrap();
+        assert_eq!(resolved.command, "cargo fmt --check && rustc main.rs");
+        assert!(resolved.args.is_empty());
+        assert!(resolved_task.parallel.is_empty());
+
+        let parallel = TaskTemplate {
+            label: "dev".to_string(),
+            commands: vec!["npm run web".to_string(), "npm run api".to_string()],
+            mode: CompoundMode::Parallel,
+            ..TaskTemplate::default()
+        };
+        let resolved_task = parallel.resolve_task(TEST_ID_BASE, &context).unwrap();
+        let resolved = resolved_task.resolved.as_ref().unwrap();
+        assert_eq!(resolved.command, "npm run web");
+        assert_eq!(resolved.label, "dev: npm run web");
+        assert_eq!(resolved_task.parallel.len(), 1);
+        assert_eq!(resolved_task.parallel[0].command, "npm run api");
+        assert_eq!(resolved_task.parallel[0].label, "dev: npm run api");
+        assert_ne!(
+            resolved.id, resolved_task.parallel[0].id,
+            "Each command of a parallel task should get a terminal of its own"
+        );
+        assert_eq!(resolved_task.display_label(), "dev");
+
+        let with_blank_command = TaskTemplate {
+            commands: vec!["npm run web".to_string(), " ".to_string()],
+            ..parallel
+        };
+        assert_eq!(
+            with_blank_command.resolve_task(TEST_ID_BASE, &context),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compound_task_json_round_trip() {
+        let template = serde_json_lenient::from_str::<TaskTemplate>(
+            r#"{
+                "label": "dev",
+                "commands": ["npm run web", "npm run api"],
+                "mode": "parallel"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(template.command, "");
+        assert_eq!(template.commands, vec!["npm run web", "npm run api"]);
+        assert_eq!(template.mode, CompoundMode::Parallel);
+
+        let json = serde_json_lenient::to_string(&template).unwrap();
+        assert_eq!(
+            serde_json_lenient::from_str::<TaskTemplate>(&json).unwrap(),
+            template
+        );
+    }
+
+    #[test]
+    fn test_dependency_chain() {
+        let task = |label: &str, depends_on: &[&str]| TaskTemplate {
+            label: label.to_string(),
+            command: "echo".to_string(),
+            depends_on: depends_on.iter().map(|label| label.to_string()).collect(),
+            ..TaskTemplate::default()
+        };
+        let tasks = [
+            task("build", &["fetch", "codegen"]),
+            task("fetch", &[]),
+            task("codegen", &["fetch"]),
+            task("test", &["build", "fetch"]),
+            task("ping", &["pong"]),
+            task("pong", &["ping"]),
+            task("broken", &["missing"]),
+        ];
+        let find_task = |label: &str| {
+            tasks
+                .iter()
+                .position(|task| task.label == label)
+                .map(|ix| (ix, tasks[ix].clone()))
+        };
+        let chain_labels = |label: &str| {
+            let (_, task) = find_task(label).unwrap();
+            task.dependency_chain(find_task).map(|chain| {
+                chain
+                    .into_iter()
+                    .map(|(_, task)| task.label)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(chain_labels("fetch").unwrap(), Vec::<String>::new());
+        assert_eq!(chain_labels("codegen").unwrap(), vec!["fetch"]);
+        assert_eq!(
+            chain_labels("test").unwrap(),
+            vec!["fetch", "codegen", "build"],
+            "Every dependency should run once, after its own dependencies"
+        );
+        assert_eq!(
+            chain_labels("ping").unwrap_err().to_string(),
+            "tasks depend on each other: ping -> pong -> ping"
+        );
+        assert_eq!(
+            chain_labels("broken").unwrap_err().to_string(),
+            "task \"broken\" depends on unknown task \"missing\""
+        );
+    }
+
+    #[test]
+    fn test_task_shell() {
+        let shell = |json: &str| serde_json_lenient::from_str::<TaskShell>(json).unwrap();
+        assert_eq!(shell(r#""system""#), TaskShell::System);
+        assert_eq!(shell(r#""none""#), TaskShell::None);
+        assert_eq!(
+            shell(r#"{ "program": "nu", "args": ["--no-config-file"] }"#),
+            TaskShell::Program {
+                program: "nu".to_string(),
+                args: vec!["--no-config-file".to_string()],
+            }
+        );
+        assert_eq!(
+            shell(r#"{ "program": "fish" }"#),
+            TaskShell::Program {
+                program: "fish".to_string(),
+                args: Vec::new(),
+            }
+        );
+        assert_eq!(
+            shell(
+                r#"{ "with_arguments": { "program": "zsh", "args": ["-l"], "title_override": null } }"#
+            ),
+            TaskShell::Program {
+                program: "zsh".to_string(),
+                args: vec!["-l".to_string()],
+            }
+        );
+
+        let template = TaskTemplate {
+            label: "test".to_string(),
+            command: "cargo".to_string(),
+            args: vec!["test".to_string(), "$ZED_SYMBOL".to_string()],
+            ..TaskTemplate::default()
+        };
+        let context = TaskContext {
+            task_variables: TaskVariables::from_iter([(VariableName::Symbol, "it_works".into())]),
+            ..TaskContext::default()
+        };
+        let resolved_args = |template: &TaskTemplate| {
+            template
+                .resolve_task(TEST_ID_BASE, &context)
+                .unwrap()
+                .resolved
+                .unwrap()
+                .args
+        };
+        assert_eq!(
+            resolved_args(&template),
+            vec!["test", "$ZED_SYMBOL"],
+            "The shell substitutes the variables of the arguments"
+        );
+        assert_eq!(
+            resolved_args(&TaskTemplate {
+                shell: TaskShell::None,
+                ..template.clone()
+            }),
+            vec!["test", "it_works"],
+            "Without a shell, the arguments get substituted on resolution"
+        );
+    }
+
+    #[test]
+    fn test_presentation() {
+        let resolve = |json: &str| {
+            serde_json_lenient::from_str::<TaskTemplate>(json)
+                .unwrap()
+                .resolve_task(TEST_ID_BASE, &TaskContext::default())
+                .unwrap()
+                .resolved
+                .unwrap()
+        };
+
+        let resolved = resolve(
+            r#"{
+                "label": "dev server",
+                "command": "npm run dev",
+                "reveal": "never",
+                "reveal_target": "center",
+                "presentation": { "placement": "window", "focus": false }
+            }"#,
+        );
+        assert_eq!(resolved.reveal_target, RevealTarget::Window);
+        assert_eq!(resolved.reveal, RevealStrategy::NoFocus);
+
+        let resolved = resolve(
+            r#"{
+                "label": "dev server",
+                "command": "npm run dev",
+                "reveal": "never",
+                "reveal_target": "center",
+                "presentation": { "placement": "split" }
+            }"#,
+        );
+        assert_eq!(resolved.reveal_target, RevealTarget::Split);
+        assert_eq!(
+            resolved.reveal,
+            RevealStrategy::Never,
+            "Without a focus, the template's reveal should be kept"
+        );
+
+        let resolved = resolve(
+            r#"{
+                "label": "dev server",
+                "command": "npm run dev",
+                "reveal_target": "center",
+                "presentation": { "focus": true }
+            }"#,
+        );
+        assert_eq!(
+            resolved.reveal_target,
+            RevealTarget::Center,
+            "Without a placement, the template's reveal target should be kept"
+        );
+        assert_eq!(resolved.reveal, RevealStrategy::Always);
+    }
+
+    #[test]
+    fn test_platform_overrides() {
+        let template = serde_json_lenient::from_str::<TaskTemplate>(
+            r#"{
+                "label": "open $ZED_FILENAME",
+                "command": "xdg-open",
+                "args": ["$ZED_FILE"],
+                "windows": { "command": "start", "shell": { "program": "cmd", "args": ["/C"] } },
+                "linux": { "args": ["--", "$ZED_FILE"] },
+                "macos": { "command": "open" }
+            }"#,
+        )
+        .unwrap();
+        let context = TaskContext {
+            task_variables: TaskVariables::from_iter([
+                (VariableName::Filename, "a.txt".into()),
+                (VariableName::File, "/dir/a.txt".into()),
+            ]),
+            ..TaskContext::default()
+        };
+        let resolved_task = template.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert_eq!(
+            resolved_task.original_task(),
+            &template,
+            "The original task should keep the overrides of all platforms"
+        );
+        let local = resolved_task.resolved.unwrap();
+        assert_eq!(local.label, "open a.txt");
+
+        let resolved_on = |os: &str| {
+            let context = TaskContext {
+                os: Some(os.to_string()),
+                ..context.clone()
+            };
+            template
+                .resolve_task(TEST_ID_BASE, &context)
+                .unwrap()
+                .resolved
+                .unwrap()
+        };
+        assert_eq!(
+            local.command,
+            resolved_on(std::env::consts::OS).command,
+            "Without an operating system in the context, the one Zed runs on should be used"
+        );
+
+        let windows = resolved_on("windows");
+        assert_eq!(windows.command, "start");
+        assert_eq!(windows.args, vec!["$ZED_FILE"]);
+        assert_eq!(
+            windows.shell,
+            TaskShell::Program {
+                program: "cmd".to_string(),
+                args: vec!["/C".to_string()],
+            }
+        );
+        let macos = resolved_on("macos");
+        assert_eq!(macos.command, "open");
+        assert_eq!(macos.args, vec!["$ZED_FILE"]);
+        assert_eq!(macos.shell, TaskShell::System);
+        let linux = resolved_on("linux");
+        assert_eq!(linux.command, "xdg-open");
+        assert_eq!(linux.args, vec!["--", "$ZED_FILE"]);
+        assert_eq!(linux.shell, TaskShell::System);
+        let freebsd = resolved_on("freebsd");
+        assert_eq!(freebsd.command, "xdg-open");
+        assert_eq!(freebsd.args, vec!["$ZED_FILE"]);
+    }
 }
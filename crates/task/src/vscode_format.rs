@@ -3,7 +3,7 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates, VariableName};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +13,41 @@ struct TaskOptions {
     env: HashMap<String, String>,
 }
 
+/// How the terminal of a task is presented, see https://code.visualstudio.com/docs/editor/tasks#_output-behavior
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct PresentationOptions {
+    reveal: Option<RevealKind>,
+    #[serde(default)]
+    focus: bool,
+    panel: Option<PanelKind>,
+    echo: Option<bool>,
+    #[serde(default)]
+    close: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum RevealKind {
+    Always,
+    Silent,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum PanelKind {
+    Shared,
+    Dedicated,
+    New,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RunOptions {
+    run_on: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 struct VsCodeTaskDefinition {
@@ -22,6 +57,10 @@ struct VsCodeTaskDefinition {
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
+    #[serde(default)]
+    presentation: Option<PresentationOptions>,
+    #[serde(default)]
+    run_options: Option<RunOptions>,
 }
 
 #[derive(Clone, Deserialize, PartialEq, Debug)]
@@ -36,6 +75,11 @@ enum Command {
         #[serde(default)]
         args: Vec<String>,
     },
+    Process {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
     Gulp {
         task: String,
     },
@@ -98,7 +142,9 @@ impl VsCodeTaskDefinition {
 
         let (command, args) = match command {
             Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
+            Command::Shell { command, args } | Command::Process { command, args } => {
+                (command, args)
+            }
             Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
@@ -114,6 +160,25 @@ impl VsCodeTaskDefinition {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
         }
+        if let Some(presentation) = self.presentation {
+            ret.reveal = match presentation.reveal.unwrap_or(RevealKind::Always) {
+                RevealKind::Always if presentation.focus => RevealStrategy::Always,
+                RevealKind::Always => RevealStrategy::NoFocus,
+                // Code reveals silent tasks' terminals only when their output has problems, which Zed does not track.
+                RevealKind::Silent | RevealKind::Never => RevealStrategy::Never,
+            };
+            ret.use_new_terminal = presentation.panel == Some(PanelKind::New);
+            if let Some(echo) = presentation.echo {
+                ret.show_command = echo;
+            }
+            if presentation.close {
+                ret.hide = HideStrategy::Always;
+            }
+        }
+        ret.run_on_open = self
+            .run_options
+            .and_then(|run_options| run_options.run_on)
+            .is_some_and(|run_on| run_on == "folderOpen");
         Ok(ret)
     }
 }
@@ -134,6 +199,19 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::WorktreeRoot.to_string(),
             ),
             ("file".to_owned(), VariableName::File.to_string()),
+            (
+                "relativeFile".to_owned(),
+                VariableName::RelativeFile.to_string(),
+            ),
+            (
+                "fileBasename".to_owned(),
+                VariableName::Filename.to_string(),
+            ),
+            (
+                "fileBasenameNoExtension".to_owned(),
+                VariableName::Stem.to_string(),
+            ),
+            ("fileDirname".to_owned(), VariableName::Dirname.to_string()),
             ("lineNumber".to_owned(), VariableName::Row.to_string()),
             (
                 "selectedText".to_owned(),
@@ -155,7 +233,7 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -212,6 +290,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -226,6 +306,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -234,6 +316,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -242,6 +326,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                presentation: None,
+                run_options: None,
             },
         ];
 
@@ -301,6 +387,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Extension".to_string(),
@@ -309,6 +397,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server".to_string(),
@@ -318,6 +408,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release)".to_string(),
@@ -327,6 +419,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Pretest".to_string(),
@@ -335,18 +429,24 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                presentation: None,
+                run_options: None,
             },
         ];
         assert_eq!(vscode_definitions.tasks.len(), expected.len());
@@ -388,4 +488,53 @@ mod tests {
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_convert_presentation_and_run_options() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "version": "2.0.0",
+                "tasks": [
+                    {
+                        "label": "watch",
+                        "type": "process",
+                        "command": "tsc",
+                        "args": ["--watch", "${relativeFile}"],
+                        "presentation": { "reveal": "silent", "panel": "new", "echo": false },
+                        "runOptions": { "runOn": "folderOpen" }
+                    },
+                    {
+                        "label": "lint",
+                        "type": "shell",
+                        "command": "eslint ${fileDirname}",
+                        "presentation": { "focus": true, "close": true }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "watch".to_string(),
+                    command: "tsc".to_string(),
+                    args: vec!["--watch".to_string(), "${ZED_RELATIVE_FILE}".to_string()],
+                    reveal: RevealStrategy::Never,
+                    use_new_terminal: true,
+                    show_command: false,
+                    run_on_open: true,
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "lint".to_string(),
+                    command: "eslint ${ZED_DIRNAME}".to_string(),
+                    reveal: RevealStrategy::Always,
+                    hide: HideStrategy::Always,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use task::ResolvedTask;
+
+/// A part of a task's resolution that differs between two of its runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ContextChange {
+    pub(crate) name: String,
+    /// `None` when the value was not set in the earlier run.
+    pub(crate) previous: Option<String>,
+    /// `None` when the value is not set anymore.
+    pub(crate) current: Option<String>,
+}
+
+/// Lists what would run differently if `current`, the task resolved against the
+/// current context, is spawned instead of `previous`, the task as it ran before:
+/// its command line, working directory, the variables of `env` and the task
+/// variables the template substitutes. The other `ZED_*` variables, which change
+/// with the cursor and the file, are left out.
+pub(crate) fn context_changes(
+    previous: &ResolvedTask,
+    current: &ResolvedTask,
+) -> Vec<ContextChange> {
+    let (Some(previous_spawn), Some(current_spawn)) = (&previous.resolved, &current.resolved)
+    else {
+        return Vec::new();
+    };
+
+    let mut changes = Vec::new();
+    if previous_spawn.command_label != current_spawn.command_label {
+        changes.push(ContextChange {
+            name: "Command".to_string(),
+            previous: Some(previous_spawn.command_label.clone()),
+            current: Some(current_spawn.command_label.clone()),
+        });
+    }
+    if previous_spawn.cwd != current_spawn.cwd {
+        let display = |cwd: &std::path::PathBuf| cwd.to_string_lossy().into_owned();
+        changes.push(ContextChange {
+            name: "Working Directory".to_string(),
+            previous: previous_spawn.cwd.as_ref().map(display),
+            current: current_spawn.cwd.as_ref().map(display),
+        });
+    }
+
+    let mut variables = previous
+        .substituted_variables()
+        .iter()
+        .chain(current.substituted_variables())
+        .collect::<Vec<_>>();
+    variables.sort_by_key(|variable| variable.to_string());
+    variables.dedup();
+    for variable in variables {
+        let previous = previous.task_context().task_variables.get(variable);
+        let current = current.task_context().task_variables.get(variable);
+        if previous != current {
+            changes.push(ContextChange {
+                name: format!("${variable}"),
+                previous: previous.map(str::to_string),
+                current: current.map(str::to_string),
+            });
+        }
+    }
+
+    let mut env_names = previous
+        .original_task()
+        .env
+        .keys()
+        .chain(current.original_task().env.keys())
+        .collect::<Vec<_>>();
+    env_names.sort();
+    env_names.dedup();
+    for name in env_names {
+        let previous = previous_spawn.env.get(name);
+        let current = current_spawn.env.get(name);
+        if previous != current {
+            changes.push(ContextChange {
+                name: format!("${name}"),
+                previous: previous.cloned(),
+                current: current.cloned(),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use collections::HashMap;
+    use task::{TaskContext, TaskTemplate, TaskVariables, VariableName};
+
+    use super::*;
+
+    fn resolve(template: &TaskTemplate, file: &str, row: &str) -> ResolvedTask {
+        let context = TaskContext {
+            cwd: Some("/dir".into()),
+            task_variables: TaskVariables::from_iter([
+                (VariableName::File, file.to_string()),
+                (VariableName::Row, row.to_string()),
+                (VariableName::Column, "1".to_string()),
+            ]),
+            project_env: HashMap::default(),
+            command_outputs: HashMap::default(),
+            os: None,
+        };
+        template.resolve_task("test", &context).unwrap()
+    }
+
+    #[test]
+    fn test_context_changes() {
+        let template = TaskTemplate {
+            label: "test".to_string(),
+            command: "cargo".to_string(),
+            args: vec!["test".to_string(), VariableName::File.template_value()],
+            env: HashMap::from_iter([("LINE".to_string(), VariableName::Row.template_value())]),
+            ..TaskTemplate::default()
+        };
+        let previous = resolve(&template, "/dir/a.rs", "1");
+        assert_eq!(context_changes(&previous, &previous), Vec::new());
+
+        let current = resolve(&template, "/dir/b.rs", "4");
+        assert_eq!(
+            context_changes(&previous, &current),
+            vec![
+                ContextChange {
+                    name: "Command".to_string(),
+                    previous: Some("cargo test /dir/a.rs".to_string()),
+                    current: Some("cargo test /dir/b.rs".to_string()),
+                },
+                ContextChange {
+                    name: "$ZED_FILE".to_string(),
+                    previous: Some("/dir/a.rs".to_string()),
+                    current: Some("/dir/b.rs".to_string()),
+                },
+                ContextChange {
+                    name: "$ZED_ROW".to_string(),
+                    previous: Some("1".to_string()),
+                    current: Some("4".to_string()),
+                },
+                ContextChange {
+                    name: "$LINE".to_string(),
+                    previous: Some("1".to_string()),
+                    current: Some("4".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_variables_are_not_changes() {
+        let template = TaskTemplate {
+            label: "test".to_string(),
+            command: "cargo".to_string(),
+            args: vec!["test".to_string()],
+            ..TaskTemplate::default()
+        };
+        // The file and row are in the environment of both runs, but the task does not use them.
+        let previous = resolve(&template, "/dir/a.rs", "1");
+        let current = resolve(&template, "/dir/b.rs", "4");
+        assert_eq!(context_changes(&previous, &current), Vec::new());
+    }
+}
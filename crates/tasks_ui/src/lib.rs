@@ -1,23 +1,50 @@
 use ::settings::Settings;
+use collections::HashSet;
 use editor::{tasks::task_context, Editor};
-use gpui::{AppContext, Task as AsyncTask, ViewContext, WindowContext};
+use gpui::{AppContext, PromptLevel, Task as AsyncTask, ViewContext, WindowContext};
 use modal::{TaskOverrides, TasksModal};
-use project::{Location, WorktreeId};
-use task::{RevealTarget, TaskId};
+use project::{Location, TaskSourceKind, WorktreeId};
+use task::{ResolvedTask, RevealTarget, TaskContext, TaskGroup, TaskId, TaskInput, TaskTemplate};
+use util::ResultExt;
 use workspace::tasks::schedule_task;
-use workspace::{tasks::schedule_resolved_task, Workspace};
+use workspace::{tasks::schedule_resolved_task, Workspace, WORKSPACE_DB};
+use zed_actions::{ClearHistory, ReloadAll, RunBuildTask, RunDefaultTask, RunTestTask};
 
+mod context_diff;
 mod modal;
+mod oneshot_history;
+mod pinned_tasks;
 mod settings;
+mod startup_tasks;
+mod task_notifications;
 
 pub use modal::{Rerun, Spawn};
 
 pub fn init(cx: &mut AppContext) {
     settings::TaskSettings::register(cx);
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            startup_tasks::start_on_open(workspace, cx);
+            oneshot_history::restore(workspace, cx);
+            pinned_tasks::restore(workspace, cx);
+            task_notifications::notify_on_finish(workspace, cx);
             workspace
                 .register_action(spawn_task_or_modal)
+                .register_action(|_, _: &RunBuildTask, cx| {
+                    spawn_group_default(TaskGroup::Build, cx).detach_and_log_err(cx)
+                })
+                .register_action(|_, _: &RunTestTask, cx| {
+                    spawn_group_default(TaskGroup::Test, cx).detach_and_log_err(cx)
+                })
+                .register_action(|_, _: &RunDefaultTask, cx| {
+                    spawn_group_default(TaskGroup::Run, cx).detach_and_log_err(cx)
+                })
+                .register_action(|workspace, _: &ReloadAll, cx| {
+                    workspace
+                        .project()
+                        .update(cx, |project, cx| project.reload_tasks(cx))
+                })
+                .register_action(|workspace, _: &ClearHistory, cx| clear_history(workspace, cx))
                 .register_action(move |workspace, action: &modal::Rerun, cx| {
                     if let Some((task_source_kind, mut last_scheduled_task)) = workspace
                         .project()
@@ -35,7 +62,15 @@ pub fn init(cx: &mut AppContext) {
                             )
                         })
                     {
-                        if action.reevaluate_context {
+                        if action.edit {
+                            edit_in_modal(
+                                workspace,
+                                task_source_kind,
+                                last_scheduled_task,
+                                action.reevaluate_context,
+                                cx,
+                            );
+                        } else if action.reevaluate_context {
                             let mut original_task = last_scheduled_task.original_task().clone();
                             if let Some(allow_concurrent_runs) = action.allow_concurrent_runs {
                                 original_task.allow_concurrent_runs = allow_concurrent_runs;
@@ -48,12 +83,11 @@ pub fn init(cx: &mut AppContext) {
                                 let task_context = context_task.await;
                                 workspace
                                     .update(&mut cx, |workspace, cx| {
-                                        schedule_task(
+                                        schedule_task_with_inputs(
                                             workspace,
                                             task_source_kind,
                                             &original_task,
                                             &task_context,
-                                            false,
                                             cx,
                                         )
                                     })
@@ -61,7 +95,7 @@ pub fn init(cx: &mut AppContext) {
                             })
                             .detach()
                         } else {
-                            if let Some(resolved) = last_scheduled_task.resolved.as_mut() {
+                            for resolved in last_scheduled_task.spawns_mut() {
                                 if let Some(allow_concurrent_runs) = action.allow_concurrent_runs {
                                     resolved.allow_concurrent_runs = allow_concurrent_runs;
                                 }
@@ -70,13 +104,29 @@ pub fn init(cx: &mut AppContext) {
                                 }
                             }
 
-                            schedule_resolved_task(
-                                workspace,
-                                task_source_kind,
-                                last_scheduled_task,
-                                false,
-                                cx,
-                            );
+                            // The values of the password inputs are not kept in the history, so they are asked for again.
+                            let inputs = last_scheduled_task
+                                .original_task()
+                                .password_inputs()
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            if inputs.is_empty() {
+                                schedule_resolved_task(
+                                    workspace,
+                                    task_source_kind,
+                                    last_scheduled_task,
+                                    false,
+                                    cx,
+                                );
+                            } else {
+                                ask_for_inputs_in_modal(
+                                    workspace,
+                                    task_source_kind,
+                                    last_scheduled_task,
+                                    inputs,
+                                    cx,
+                                );
+                            }
                         }
                     } else {
                         toggle_modal(workspace, None, cx).detach();
@@ -92,9 +142,11 @@ fn spawn_task_or_modal(workspace: &mut Workspace, action: &Spawn, cx: &mut ViewC
         Spawn::ByName {
             task_name,
             reveal_target,
+            reveal,
         } => {
-            let overrides = reveal_target.map(|reveal_target| TaskOverrides {
-                reveal_target: Some(reveal_target),
+            let overrides = (reveal_target.is_some() || reveal.is_some()).then(|| TaskOverrides {
+                reveal_target: *reveal_target,
+                reveal: *reveal,
             });
             spawn_task_with_name(task_name.clone(), overrides, cx).detach_and_log_err(cx)
         }
@@ -116,6 +168,20 @@ fn toggle_modal(
         let context_task = task_context(workspace, cx);
         cx.spawn(|workspace, mut cx| async move {
             let task_context = context_task.await;
+            let Ok(command_outputs_task) = workspace.update(&mut cx, |workspace, cx| {
+                run_variable_commands(workspace, task_context, cx)
+            }) else {
+                return;
+            };
+            let task_context = command_outputs_task.await;
+            let global_history = WORKSPACE_DB
+                .global_oneshot_tasks()
+                .await
+                .log_err()
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .collect::<Vec<_>>();
             workspace
                 .update(&mut cx, |workspace, cx| {
                     workspace.toggle_modal(cx, |cx| {
@@ -124,7 +190,9 @@ fn toggle_modal(
                             task_context,
                             reveal_target.map(|target| TaskOverrides {
                                 reveal_target: Some(target),
+                                reveal: None,
                             }),
+                            global_history,
                             workspace_handle,
                             cx,
                         )
@@ -137,6 +205,128 @@ fn toggle_modal(
     }
 }
 
+/// Opens the tasks modal with the resolved command of the task in its prompt, to be edited before it is spawned.
+fn edit_in_modal(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    reevaluate_context: bool,
+    cx: &mut ViewContext<Workspace>,
+) {
+    update_modal(workspace, cx, move |modal, cx| {
+        modal.edit_task(&task_source_kind, &task, reevaluate_context, cx)
+    });
+}
+
+fn ask_for_inputs_in_modal(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    inputs: Vec<TaskInput>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    update_modal(workspace, cx, move |modal, cx| {
+        modal.ask_for_inputs(task_source_kind, task, inputs, cx)
+    });
+}
+
+/// Spawns the task in the context given, after asking for the values of its inputs in the tasks modal if it has any.
+fn schedule_task_with_inputs(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    template: &TaskTemplate,
+    task_context: &TaskContext,
+    cx: &mut ViewContext<Workspace>,
+) {
+    if template.inputs.is_empty() {
+        schedule_task(
+            workspace,
+            task_source_kind,
+            template,
+            task_context,
+            false,
+            cx,
+        );
+    } else if let Some(task) = template.resolve_task(&task_source_kind.to_id_base(), task_context) {
+        let inputs = template.inputs.clone();
+        ask_for_inputs_in_modal(workspace, task_source_kind, task, inputs, cx);
+    }
+}
+
+/// Updates the tasks modal of the workspace, opening it first if it is not open.
+fn update_modal(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+    update: impl FnOnce(&mut TasksModal, &mut ViewContext<TasksModal>) + 'static,
+) {
+    if let Some(modal) = workspace.active_modal::<TasksModal>(cx) {
+        modal.update(cx, update);
+        return;
+    }
+    let open_modal = toggle_modal(workspace, None, cx);
+    cx.spawn(|workspace, mut cx| async move {
+        open_modal.await;
+        workspace.update(&mut cx, |workspace, cx| {
+            if let Some(modal) = workspace.active_modal::<TasksModal>(cx) {
+                modal.update(cx, update);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Clears the task history of the active item's worktree, after asking for confirmation.
+/// The global history of oneshot commands, shared by all workspaces, is kept.
+fn clear_history(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let (worktree_id, _) = active_item_selection_properties(workspace, cx);
+    let project = workspace.project().read(cx);
+    let Some(inventory) = project.task_store().read(cx).task_inventory().cloned() else {
+        return;
+    };
+    let worktree = worktree_id.and_then(|worktree_id| {
+        let worktree = project.worktree_for_id(worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        Some((
+            worktree_id,
+            worktree.root_name().to_string(),
+            worktree.abs_path(),
+        ))
+    });
+    let message = match &worktree {
+        Some((_, root_name, _)) => format!("Clear the task history of {root_name}?"),
+        None => "Clear the history of the tasks spawned outside of worktrees?".to_string(),
+    };
+    let answer = cx.prompt(
+        PromptLevel::Warning,
+        &message,
+        Some("The recently used tasks will be removed from the tasks modal and can no longer be rerun."),
+        &["Clear", "Cancel"],
+    );
+    cx.spawn(|workspace, mut cx| async move {
+        if answer.await.log_err() != Some(0) {
+            return anyhow::Ok(());
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            inventory.update(cx, |inventory, cx| {
+                inventory.clear_history(
+                    worktree
+                        .as_ref()
+                        .map(|(worktree_id, _, abs_path)| (*worktree_id, abs_path.as_ref())),
+                );
+                cx.notify();
+            });
+            if let (Some(workspace_id), Some((_, _, abs_path))) =
+                (workspace.database_id(), worktree)
+            {
+                cx.background_executor()
+                    .spawn(WORKSPACE_DB.delete_worktree_oneshot_tasks(workspace_id, abs_path))
+                    .detach_and_log_err(cx);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 fn spawn_task_with_name(
     name: String,
     overrides: Option<TaskOverrides>,
@@ -146,31 +336,12 @@ fn spawn_task_with_name(
         let context_task =
             workspace.update(&mut cx, |workspace, cx| task_context(workspace, cx))?;
         let task_context = context_task.await;
-        let tasks = workspace.update(&mut cx, |workspace, cx| {
-            let Some(task_inventory) = workspace
-                .project()
-                .read(cx)
-                .task_store()
-                .read(cx)
-                .task_inventory()
-                .cloned()
-            else {
-                return Vec::new();
-            };
-            let (worktree, location) = active_item_selection_properties(workspace, cx);
-            let (file, language) = location
-                .map(|location| {
-                    let buffer = location.buffer.read(cx);
-                    (
-                        buffer.file().cloned(),
-                        buffer.language_at(location.range.start),
-                    )
-                })
-                .unwrap_or_default();
-            task_inventory
-                .read(cx)
-                .list_tasks(file, language, worktree, cx)
-        })?;
+        let task_context = workspace
+            .update(&mut cx, |workspace, cx| {
+                run_variable_commands(workspace, task_context, cx)
+            })?
+            .await;
+        let tasks = workspace.update(&mut cx, |workspace, cx| available_tasks(workspace, cx))?;
 
         let did_spawn = workspace
             .update(&mut cx, |workspace, cx| {
@@ -180,13 +351,15 @@ fn spawn_task_with_name(
                     if let Some(target_override) = overrides.reveal_target {
                         target_task.reveal_target = target_override;
                     }
+                    if let Some(reveal_override) = overrides.reveal {
+                        target_task.reveal = reveal_override;
+                    }
                 }
-                schedule_task(
+                schedule_task_with_inputs(
                     workspace,
                     task_source_kind,
                     &target_task,
                     &task_context,
-                    false,
                     cx,
                 );
                 Some(())
@@ -210,6 +383,179 @@ fn spawn_task_with_name(
     })
 }
 
+/// Spawns the default task of the group in the current context, or opens the tasks modal if the group has no tasks.
+fn spawn_group_default(
+    group: TaskGroup,
+    cx: &mut ViewContext<Workspace>,
+) -> AsyncTask<anyhow::Result<()>> {
+    cx.spawn(|workspace, mut cx| async move {
+        let context_task =
+            workspace.update(&mut cx, |workspace, cx| task_context(workspace, cx))?;
+        let task_context = context_task.await;
+        let task_context = workspace
+            .update(&mut cx, |workspace, cx| {
+                run_variable_commands(workspace, task_context, cx)
+            })?
+            .await;
+        workspace.update(&mut cx, |workspace, cx| {
+            match group_default_task(workspace, group, cx) {
+                Some((task_source_kind, template)) => {
+                    schedule_task_with_inputs(
+                        workspace,
+                        task_source_kind,
+                        &template,
+                        &task_context,
+                        cx,
+                    );
+                }
+                None => toggle_modal(workspace, None, cx).detach(),
+            }
+        })
+    })
+}
+
+/// The default task of the group among the tasks available for the active item: the one set as
+/// the group's default in the worktree, or else the first task of the group.
+pub(crate) fn group_default_task(
+    workspace: &Workspace,
+    group: TaskGroup,
+    cx: &mut WindowContext,
+) -> Option<(TaskSourceKind, TaskTemplate)> {
+    let inventory = workspace
+        .project()
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .cloned()?;
+    let mut tasks = available_tasks(workspace, cx);
+    let (worktree, _) = active_item_selection_properties(workspace, cx);
+    let ix = inventory.read(cx).group_default_index(
+        worktree,
+        group,
+        tasks.iter().map(|(kind, template)| (kind, template)),
+    )?;
+    Some(tasks.swap_remove(ix))
+}
+
+fn available_tasks(
+    workspace: &Workspace,
+    cx: &mut WindowContext,
+) -> Vec<(TaskSourceKind, TaskTemplate)> {
+    let Some(task_inventory) = workspace
+        .project()
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .cloned()
+    else {
+        return Vec::new();
+    };
+    let (worktree, location) = active_item_selection_properties(workspace, cx);
+    let (file, language) = location
+        .map(|location| {
+            let buffer = location.buffer.read(cx);
+            (
+                buffer.file().cloned(),
+                buffer.language_at(location.range.start),
+            )
+        })
+        .unwrap_or_default();
+    task_inventory
+        .read(cx)
+        .list_tasks(file, language, worktree, cx)
+}
+
+/// Runs the commands of the `${command:...}` variables of the available tasks once, in the background,
+/// so that all tasks of the modal are resolved with the same outputs.
+///
+/// `.vscode/tasks.json` variables name VS Code commands and are never run; the ones of other worktree tasks
+/// only run once the user trusts the worktree.
+fn run_variable_commands(
+    workspace: &Workspace,
+    mut task_context: TaskContext,
+    cx: &mut ViewContext<Workspace>,
+) -> AsyncTask<TaskContext> {
+    let project = workspace.project().clone();
+    let mut commands = HashSet::<String>::default();
+    let mut untrusted_commands = HashSet::<String>::default();
+    let mut untrusted_worktrees = HashSet::default();
+    for (task_source_kind, template) in available_tasks(workspace, cx) {
+        let variable_commands = template
+            .variable_commands()
+            .into_iter()
+            .filter(|command| !task_context.command_outputs.contains_key(command));
+        match task_source_kind {
+            TaskSourceKind::VsCode { .. } => {}
+            TaskSourceKind::Worktree { id, .. } if !startup_tasks::is_trusted(&project, id, cx) => {
+                let len = untrusted_commands.len();
+                untrusted_commands.extend(variable_commands);
+                if untrusted_commands.len() > len {
+                    untrusted_worktrees.insert(id);
+                }
+            }
+            _ => commands.extend(variable_commands),
+        }
+    }
+    untrusted_commands.retain(|command| !commands.contains(command));
+    if commands.is_empty() && untrusted_commands.is_empty() {
+        return AsyncTask::ready(task_context);
+    }
+
+    let trust_answer = (!untrusted_commands.is_empty()).then(|| {
+        let worktree_names = untrusted_worktrees
+            .iter()
+            .filter_map(|worktree_id| project.read(cx).worktree_for_id(*worktree_id, cx))
+            .map(|worktree| worktree.read(cx).root_name().to_string())
+            .collect::<Vec<_>>();
+        let message = format!(
+            "Do you trust {} to run the commands of its task variables?",
+            worktree_names.join(", ")
+        );
+        let detail = untrusted_commands
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        cx.prompt(
+            PromptLevel::Warning,
+            &message,
+            Some(&detail),
+            &["Trust and Run", "Don't Run"],
+        )
+    });
+    cx.spawn(|workspace, mut cx| async move {
+        if let Some(answer) = trust_answer {
+            if answer.await.log_err() == Some(0) {
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        for worktree_id in untrusted_worktrees {
+                            startup_tasks::trust(workspace.project(), worktree_id, cx);
+                        }
+                    })
+                    .ok();
+                commands.extend(untrusted_commands);
+            }
+        }
+        for command in commands {
+            let Ok(output) = project.update(&mut cx, |project, cx| {
+                project.run_shell_command(
+                    command.clone(),
+                    task_context.cwd.clone(),
+                    task_context.project_env.clone(),
+                    cx,
+                )
+            }) else {
+                break;
+            };
+            let output = output.await.log_err().unwrap_or_default();
+            task_context.command_outputs.insert(command, output);
+        }
+        task_context
+    })
+}
+
 fn active_item_selection_properties(
     workspace: &Workspace,
     cx: &mut WindowContext,
@@ -241,7 +587,7 @@ fn active_item_selection_properties(
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, sync::Arc};
+    use std::{collections::HashMap, path::Path, sync::Arc};
 
     use editor::Editor;
     use gpui::{Entity, TestAppContext};
@@ -374,6 +720,8 @@ mod tests {
                     (VariableName::Column, "1".into()),
                 ]),
                 project_env: HashMap::default(),
+                command_outputs: HashMap::default(),
+                os: None,
             }
         );
 
@@ -401,6 +749,8 @@ mod tests {
                     (VariableName::Symbol, "this_is_a_rust_file".into()),
                 ]),
                 project_env: HashMap::default(),
+                command_outputs: HashMap::default(),
+                os: None,
             }
         );
 
@@ -426,10 +776,71 @@ mod tests {
                     (VariableName::Symbol, "this_is_a_test".into()),
                 ]),
                 project_env: HashMap::default(),
+                command_outputs: HashMap::default(),
+                os: None,
             }
         );
     }
 
+    #[gpui::test]
+    async fn test_git_task_variables(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/repo",
+            json!({
+                ".git": {},
+                "src": {
+                    "main.rs": "fn main() {}",
+                },
+            }),
+        )
+        .await;
+        fs.set_branch_name(Path::new("/repo/.git"), Some("release"));
+        let project = Project::test(fs, ["/repo".as_ref()], cx).await;
+        let worktree_store = project.update(cx, |project, _| project.worktree_store().clone());
+        let rust_language = Arc::new(
+            Language::new(
+                LanguageConfig::default(),
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_context_provider(Some(Arc::new(BasicContextProvider::new(worktree_store)))),
+        );
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        cx.executor().run_until_parked();
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let buffer = workspace
+            .update(cx, |workspace, cx| {
+                workspace.project().update(cx, |project, cx| {
+                    project.open_buffer((worktree_id, "src/main.rs"), cx)
+                })
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.set_language(Some(rust_language), cx)
+        });
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+
+        let task_variables = workspace
+            .update(cx, |workspace, cx| {
+                workspace.add_item_to_center(Box::new(editor), cx);
+                task_context(workspace, cx)
+            })
+            .await
+            .task_variables;
+        assert_eq!(task_variables.get(&VariableName::Branch), Some("release"));
+        assert_eq!(task_variables.get(&VariableName::RepoRoot), Some("/repo"));
+        assert_eq!(
+            task_variables.get(&VariableName::CommitSha),
+            None,
+            "The fake repository has no commits"
+        );
+    }
+
     pub(crate) fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let state = AppState::test(cx);
@@ -1,31 +1,73 @@
-use std::sync::Arc;
+use std::{borrow::Cow, cmp::Reverse, iter, mem, path::PathBuf, sync::Arc, time::Duration};
 
-use crate::active_item_selection_properties;
+use crate::{
+    active_item_selection_properties,
+    context_diff::{context_changes, ContextChange},
+    group_default_task,
+    settings::{TaskSettings, TaskSortMode},
+};
+use collections::{HashMap, HashSet};
+use editor::{scroll::Autoscroll, Editor};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusableView,
-    InteractiveElement, Model, ParentElement, Render, SharedString, Styled, Subscription, Task,
-    View, ViewContext, VisualContext, WeakView,
+    actions, percentage, rems, Action, Animation, AnimationExt as _, AnyElement, AppContext,
+    DismissEvent, EventEmitter, FocusableView, InteractiveElement, KeyContext, Model,
+    ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Subscription, Task,
+    Transformation, View, ViewContext, VisualContext, WeakView,
 };
 use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
-use project::{task_store::TaskStore, TaskSourceKind};
-use task::{ResolvedTask, RevealTarget, TaskContext, TaskTemplate};
+use project::{task_store::TaskStore, ProjectPath, TaskRunStats, TaskSourceKind, WorktreeId};
+use settings::Settings;
+use task::{
+    ResolvedTask, RevealStrategy, RevealTarget, SpawnInTerminal, TaskContext, TaskGroup, TaskId,
+    TaskInput, TaskInputType, TaskShell, TaskTemplate, ZED_VARIABLE_NAME_PREFIX,
+};
 use ui::{
     div, h_flex, v_flex, ActiveTheme, Button, ButtonCommon, ButtonSize, Clickable, Color,
     FluentBuilder as _, Icon, IconButton, IconButtonShape, IconName, IconSize, IntoElement,
-    KeyBinding, LabelSize, ListItem, ListItemSpacing, RenderOnce, Toggleable, Tooltip,
-    WindowContext,
+    KeyBinding, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing, RenderOnce, Toggleable,
+    Tooltip, WindowContext,
 };
-use util::ResultExt;
-use workspace::{tasks::schedule_resolved_task, ModalView, Workspace};
+use util::{truncate_and_trailoff, ResultExt};
+use workspace::{tasks::schedule_resolved_task, ModalView, Workspace, WORKSPACE_DB};
+use zed_actions::{ClearHistory, ReloadAll, RevealTaskOutput};
 pub use zed_actions::{Rerun, Spawn};
 
+actions!(
+    tasks_modal,
+    [
+        CycleSortMode,
+        DeleteFromHistory,
+        EditBeforeRun,
+        OpenDefinition,
+        RevealOutput,
+        ToggleMarked,
+        TogglePreview,
+        SetGroupDefault
+    ]
+);
+
+/// How many of the changed context values are listed before a rerun.
+const MAX_LISTED_CONTEXT_CHANGES: usize = 6;
+const MAX_CONTEXT_VALUE_CHARS: usize = 40;
+
 /// A modal used to spawn new tasks.
 pub(crate) struct TasksModalDelegate {
     task_store: Model<TaskStore>,
     candidates: Option<Vec<(TaskSourceKind, ResolvedTask)>>,
     task_overrides: Option<TaskOverrides>,
     last_used_candidate_index: Option<usize>,
+    /// The commands of the oneshot tasks spawned in any workspace, from the most to the least recently used.
+    global_history: Vec<String>,
+    /// The index of the first candidate from the global history, which come after all other candidates.
+    global_history_start_index: Option<usize>,
+    global_history_divider_index: Option<usize>,
+    /// The candidates pinned in the inventory for the worktree the tasks were listed for.
+    pinned_candidates: HashSet<usize>,
+    /// The candidates that are the default tasks of their groups, see [`TaskGroup`].
+    group_default_candidates: HashSet<usize>,
+    worktree: Option<WorktreeId>,
+    pinned_divider_index: Option<usize>,
     divider_index: Option<usize>,
     matches: Vec<StringMatch>,
     selected_index: usize,
@@ -33,6 +75,51 @@ pub(crate) struct TasksModalDelegate {
     prompt: String,
     task_context: TaskContext,
     placeholder_text: Arc<str>,
+    pending_rerun: Option<PendingRerun>,
+    pending_args: Option<PendingArgs>,
+    pending_inputs: Option<PendingInputs>,
+    edited_task: Option<EditedTask>,
+    /// The candidates marked to be spawned together on confirm, in the order they were marked.
+    marked_candidates: Vec<usize>,
+    /// How the matches that are not pinned, recently used or from the global history are sorted.
+    sort_mode: TaskSortMode,
+    /// The key bindings that spawn the candidates by name, looked up once when the candidates are read.
+    task_key_bindings: HashMap<String, KeyBinding>,
+}
+
+/// A recently used task which would resolve differently in the current context,
+/// waiting for the user to pick which of the two resolutions to run.
+struct PendingRerun {
+    candidate_index: usize,
+    task_source_kind: TaskSourceKind,
+    previous: ResolvedTask,
+    current: ResolvedTask,
+    omit_history_entry: bool,
+    changes: Vec<ContextChange>,
+}
+
+/// A task that asks for arguments before it is spawned, waiting for them to be typed in the query editor.
+struct PendingArgs {
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+}
+
+/// A task that asks for the values of its inputs before it is spawned,
+/// waiting for them to be typed in the query editor one by one.
+struct PendingInputs {
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    /// The inputs left to ask for, starting with the one being typed.
+    inputs: Vec<TaskInput>,
+    /// Whether to ask for the task's arguments after its inputs.
+    ask_for_args: bool,
+}
+
+/// A task whose resolved command was copied into the query editor, to be edited and spawned as a oneshot
+/// in the same working directory and environment.
+struct EditedTask {
+    cwd: Option<PathBuf>,
+    env: HashMap<String, String>,
 }
 
 /// Task template amendments to do before resolving the context.
@@ -40,6 +127,8 @@ pub(crate) struct TasksModalDelegate {
 pub(crate) struct TaskOverrides {
     /// See [`RevealTarget`].
     pub(crate) reveal_target: Option<RevealTarget>,
+    /// See [`RevealStrategy`].
+    pub(crate) reveal: Option<RevealStrategy>,
 }
 
 impl TasksModalDelegate {
@@ -47,15 +136,22 @@ impl TasksModalDelegate {
         task_store: Model<TaskStore>,
         task_context: TaskContext,
         task_overrides: Option<TaskOverrides>,
+        global_history: Vec<String>,
+        sort_mode: TaskSortMode,
         workspace: WeakView<Workspace>,
     ) -> Self {
-        let placeholder_text = if let Some(TaskOverrides {
-            reveal_target: Some(RevealTarget::Center),
-        }) = &task_overrides
+        let placeholder_text = match task_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.reveal_target)
         {
-            Arc::from("Find a task, or run a command in the central pane")
-        } else {
-            Arc::from("Find a task, or run a command")
+            Some(RevealTarget::Center) => {
+                Arc::from("Find a task, or run a command in the central pane")
+            }
+            Some(RevealTarget::Split) => Arc::from("Find a task, or run a command in a split pane"),
+            Some(RevealTarget::Window) => {
+                Arc::from("Find a task, or run a command in a new window")
+            }
+            Some(RevealTarget::Dock) | None => Arc::from("Find a task, or run a command"),
         };
         Self {
             task_store,
@@ -63,15 +159,329 @@ impl TasksModalDelegate {
             candidates: None,
             matches: Vec::new(),
             last_used_candidate_index: None,
+            global_history,
+            global_history_start_index: None,
+            global_history_divider_index: None,
+            pinned_candidates: HashSet::default(),
+            group_default_candidates: HashSet::default(),
+            worktree: None,
+            pinned_divider_index: None,
             divider_index: None,
             selected_index: 0,
             prompt: String::default(),
             task_context,
             task_overrides,
             placeholder_text,
+            pending_rerun: None,
+            pending_args: None,
+            pending_inputs: None,
+            edited_task: None,
+            marked_candidates: Vec::new(),
+            sort_mode,
+            task_key_bindings: HashMap::default(),
+        }
+    }
+
+    fn apply_overrides(&self, task: &mut ResolvedTask) {
+        let Some(overrides) = &self.task_overrides else {
+            return;
+        };
+        for spawn_in_terminal in task.spawns_mut() {
+            if let Some(reveal_target) = overrides.reveal_target {
+                spawn_in_terminal.reveal_target = reveal_target;
+            }
+            if let Some(reveal) = overrides.reveal {
+                spawn_in_terminal.reveal = reveal;
+            }
+        }
+    }
+
+    /// Resolves a previously used task against the current context, if that
+    /// changes what the task would run.
+    fn rerun_in_current_context(
+        &self,
+        candidate_index: usize,
+        task_source_kind: &TaskSourceKind,
+        task: &ResolvedTask,
+        omit_history_entry: bool,
+    ) -> Option<PendingRerun> {
+        let mut current = task
+            .original_task()
+            .resolve_task(&task_source_kind.to_id_base(), &self.task_context)?;
+        self.apply_overrides(&mut current);
+        let changes = context_changes(task, &current);
+        if changes.is_empty() {
+            return None;
+        }
+        Some(PendingRerun {
+            candidate_index,
+            task_source_kind: task_source_kind.clone(),
+            previous: task.clone(),
+            current,
+            omit_history_entry,
+            changes,
+        })
+    }
+
+    fn confirm_pending_rerun(
+        &mut self,
+        in_current_context: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let Some(pending_rerun) = self.pending_rerun.take() else {
+            return;
+        };
+        let (task, inputs) = if in_current_context {
+            let inputs = pending_rerun.current.original_task().inputs.clone();
+            (pending_rerun.current, inputs)
+        } else {
+            let inputs = password_inputs(&pending_rerun.previous);
+            (pending_rerun.previous, inputs)
+        };
+        self.schedule_with_inputs(
+            pending_rerun.task_source_kind,
+            task,
+            inputs,
+            false,
+            pending_rerun.omit_history_entry,
+            cx,
+        );
+    }
+
+    /// Spawns the task once the values of the inputs given are typed in, followed by its arguments if `ask_for_args`.
+    fn schedule_with_inputs(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        inputs: Vec<TaskInput>,
+        ask_for_args: bool,
+        omit_history_entry: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        if !inputs.is_empty() {
+            self.pending_inputs = Some(PendingInputs {
+                task_source_kind,
+                task,
+                inputs,
+                ask_for_args,
+            });
+        } else if ask_for_args {
+            self.pending_args = Some(PendingArgs {
+                task_source_kind,
+                task,
+            });
+        } else {
+            self.schedule(task_source_kind, task, omit_history_entry, cx);
+            return;
+        }
+        self.matches.clear();
+        cx.defer(|picker, cx| {
+            let masked = picker.delegate.is_typing_password();
+            picker.set_masked(masked, cx);
+            picker.set_query("", cx);
+            picker.refresh_placeholder(cx);
+        });
+    }
+
+    fn confirm_pending_input(
+        &mut self,
+        omit_history_entry: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let Some(PendingInputs {
+            task_source_kind,
+            mut task,
+            mut inputs,
+            ask_for_args,
+        }) = self.pending_inputs.take()
+        else {
+            return;
+        };
+        if inputs.is_empty() {
+            return;
+        }
+        let input = inputs.remove(0);
+        task.set_input(&input, &mem::take(&mut self.prompt));
+        self.schedule_with_inputs(
+            task_source_kind,
+            task,
+            inputs,
+            ask_for_args,
+            omit_history_entry,
+            cx,
+        );
+    }
+
+    fn is_typing_password(&self) -> bool {
+        self.pending_inputs
+            .as_ref()
+            .and_then(|pending_inputs| pending_inputs.inputs.first())
+            .is_some_and(|input| input.input_type == TaskInputType::Password)
+    }
+
+    fn confirm_pending_args(
+        &mut self,
+        omit_history_entry: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let Some(PendingArgs {
+            task_source_kind,
+            mut task,
+        }) = self.pending_args.take()
+        else {
+            return;
+        };
+        let args = shlex::split(&self.prompt).unwrap_or_else(|| {
+            self.prompt
+                .split_whitespace()
+                .map(ToString::to_string)
+                .collect()
+        });
+        task.append_args(&args);
+        self.schedule(task_source_kind, task, omit_history_entry, cx);
+    }
+
+    fn schedule(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        omit_history_entry: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                schedule_resolved_task(workspace, task_source_kind, task, omit_history_entry, cx);
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn toggle_marked(&mut self, ix: usize) {
+        match self
+            .marked_candidates
+            .iter()
+            .position(|&marked| marked == ix)
+        {
+            Some(position) => {
+                self.marked_candidates.remove(position);
+            }
+            None => self.marked_candidates.push(ix),
         }
     }
 
+    /// Spawns all marked tasks one after another, as they would be spawned when confirmed one by one,
+    /// except that the recently used ones are rerun in their previous context and no arguments or inputs are asked for.
+    fn spawn_marked(&mut self, omit_history_entry: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(candidates) = self.candidates.as_ref() else {
+            return;
+        };
+        let tasks = self
+            .marked_candidates
+            .drain(..)
+            .filter_map(|ix| candidates.get(ix).cloned())
+            .collect::<Vec<_>>();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                for (task_source_kind, mut task) in tasks {
+                    self.apply_overrides(&mut task);
+                    schedule_resolved_task(
+                        workspace,
+                        task_source_kind,
+                        task,
+                        omit_history_entry,
+                        cx,
+                    );
+                }
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn render_pending_rerun(
+        &self,
+        pending_rerun: &PendingRerun,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> AnyElement {
+        let hidden_changes = pending_rerun
+            .changes
+            .len()
+            .saturating_sub(MAX_LISTED_CONTEXT_CHANGES);
+        let value_label = |value: &Option<String>, color: Color| {
+            Label::new(
+                value
+                    .as_deref()
+                    .map(|value| truncate_and_trailoff(value, MAX_CONTEXT_VALUE_CHARS))
+                    .unwrap_or_else(|| "(unset)".to_string()),
+            )
+            .size(LabelSize::Small)
+            .color(color)
+            .single_line()
+        };
+
+        v_flex()
+            .w_full()
+            .p_2()
+            .gap_1()
+            .rounded_b_md()
+            .bg(cx.theme().colors().ghost_element_selected)
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new("The task would run differently in the current context")
+                    .size(LabelSize::Small),
+            )
+            .children(
+                pending_rerun
+                    .changes
+                    .iter()
+                    .take(MAX_LISTED_CONTEXT_CHANGES)
+                    .map(|change| {
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new(change.name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(value_label(&change.previous, Color::Deleted))
+                            .child(Label::new("→").size(LabelSize::Small).color(Color::Muted))
+                            .child(value_label(&change.current, Color::Created))
+                    }),
+            )
+            .when(hidden_changes > 0, |this| {
+                this.child(
+                    Label::new(format!("and {hidden_changes} more"))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(
+                h_flex()
+                    .pt_1()
+                    .gap_2()
+                    .justify_end()
+                    .child(
+                        Button::new("rerun-as-before", "Rerun as Before")
+                            .label_size(LabelSize::Small)
+                            .when_some(
+                                KeyBinding::for_action(&menu::Confirm, cx),
+                                |this, keybind| this.key_binding(keybind),
+                            )
+                            .on_click(cx.listener(|picker, _, cx| {
+                                picker.delegate.confirm_pending_rerun(false, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("rerun-in-current-context", "Rerun in Current Context")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|picker, _, cx| {
+                                picker.delegate.confirm_pending_rerun(true, cx)
+                            })),
+                    ),
+            )
+            .into_any_element()
+    }
+
     fn spawn_oneshot(&mut self) -> Option<(TaskSourceKind, ResolvedTask)> {
         if self.prompt.trim().is_empty() {
             return None;
@@ -84,11 +494,20 @@ impl TasksModalDelegate {
             command: self.prompt.clone(),
             ..TaskTemplate::default()
         };
-        if let Some(TaskOverrides {
-            reveal_target: Some(reveal_target),
-        }) = &self.task_overrides
-        {
-            new_oneshot.reveal_target = *reveal_target;
+        if let Some(edited_task) = &self.edited_task {
+            new_oneshot.cwd = edited_task
+                .cwd
+                .as_ref()
+                .map(|cwd| cwd.to_string_lossy().into_owned());
+            new_oneshot.env = edited_task.env.clone();
+        }
+        if let Some(overrides) = &self.task_overrides {
+            if let Some(reveal_target) = overrides.reveal_target {
+                new_oneshot.reveal_target = reveal_target;
+            }
+            if let Some(reveal) = overrides.reveal {
+                new_oneshot.reveal = reveal;
+            }
         }
         Some((
             source_kind,
@@ -96,28 +515,390 @@ impl TasksModalDelegate {
         ))
     }
 
+    /// Starts editing the selected task as a oneshot, returning its resolved command to put in the query editor.
+    fn edit_selected(&mut self) -> Option<String> {
+        let task_index = self.matches.get(self.selected_index)?.candidate_id;
+        let (_, task) = self.candidates.as_ref()?.get(task_index)?;
+        let resolved = task.resolved.clone()?;
+        Some(self.edit_resolved(&resolved))
+    }
+
+    /// Starts editing the resolved task as a oneshot, returning its command to put in the query editor.
+    ///
+    /// The command is kept as is, same as when it is run over SSH, while the arguments are quoted so that
+    /// the oneshot passes them to the command unchanged.
+    /// `ZED_` variables are not kept in the environment: the oneshot is resolved against the current context.
+    fn edit_resolved(&mut self, resolved: &SpawnInTerminal) -> String {
+        self.edited_task = Some(EditedTask {
+            cwd: resolved.cwd.clone(),
+            env: resolved
+                .env
+                .iter()
+                .filter(|(name, _)| !name.starts_with(ZED_VARIABLE_NAME_PREFIX))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        });
+        let args = resolved
+            .args
+            .iter()
+            .filter_map(|arg| shlex::try_quote(arg).ok());
+        iter::once(Cow::Borrowed(resolved.command.as_str()))
+            .chain(args)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Opens the file that defines the task, with the cursor on its definition.
+    fn open_definition(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some((task_source_kind, task)) = self
+            .candidates
+            .as_ref()
+            .and_then(|candidates| candidates.get(ix))
+            .cloned()
+        else {
+            return;
+        };
+        let template = task.original_task().clone();
+        let definition_paths = task_source_kind.definition_paths();
+        let opened = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                let open_task = match task_source_kind.worktree() {
+                    Some(worktree_id) => {
+                        let worktree = workspace
+                            .project()
+                            .read(cx)
+                            .worktree_for_id(worktree_id, cx)?;
+                        let path = definition_paths
+                            .into_iter()
+                            .find(|path| worktree.read(cx).entry_for_path(path).is_some())?;
+                        workspace.open_path(
+                            ProjectPath {
+                                worktree_id,
+                                path: path.into(),
+                            },
+                            None,
+                            true,
+                            cx,
+                        )
+                    }
+                    None => workspace.open_abs_path(definition_paths.into_iter().next()?, true, cx),
+                };
+                cx.spawn(|_, mut cx| async move {
+                    let item = open_task.await?;
+                    if let Some(editor) = item.downcast::<Editor>() {
+                        editor.update(&mut cx, |editor, cx| {
+                            let text = editor.buffer().read(cx).snapshot(cx).text();
+                            if let Some(offset) =
+                                task_source_kind.definition_offset(&template, &text)
+                            {
+                                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                    s.select_ranges([offset..offset])
+                                });
+                            }
+                        })?;
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+                Some(())
+            })
+            .ok()
+            .flatten();
+        if opened.is_some() {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn is_from_global_history(&self, candidate_index: usize) -> bool {
+        self.global_history_start_index
+            .is_some_and(|start_index| candidate_index >= start_index)
+    }
+
+    /// Resolves the oneshot tasks of the global history in the current context, leaving out the ones
+    /// that are among the candidates already.
+    fn global_history_candidates(
+        &self,
+        candidates: &[(TaskSourceKind, ResolvedTask)],
+    ) -> Vec<(TaskSourceKind, ResolvedTask)> {
+        let id_base = TaskSourceKind::UserInput.to_id_base();
+        self.global_history
+            .iter()
+            .filter(|command| {
+                !candidates.iter().any(|(kind, task)| {
+                    kind == &TaskSourceKind::UserInput && &task.original_task().command == *command
+                })
+            })
+            .filter_map(|command| {
+                let template = TaskTemplate {
+                    label: command.clone(),
+                    command: command.clone(),
+                    ..TaskTemplate::default()
+                };
+                let task = template.resolve_task(&id_base, &self.task_context)?;
+                Some((TaskSourceKind::UserInput, task))
+            })
+            .collect()
+    }
+
+    fn is_recently_used(&self, candidate_index: usize) -> bool {
+        self.last_used_candidate_index
+            .is_some_and(|last_used_index| candidate_index <= last_used_index)
+    }
+
+    fn pinned_candidates(&self, cx: &AppContext) -> HashSet<usize> {
+        let (Some(candidates), Some(inventory)) = (
+            self.candidates.as_ref(),
+            self.task_store.read(cx).task_inventory(),
+        ) else {
+            return HashSet::default();
+        };
+        let inventory = inventory.read(cx);
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (task_source_kind, task))| {
+                inventory.is_pinned(self.worktree, task_source_kind, &task.original_task().label)
+            })
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// The first candidate that is the default task of each group, the one spawned by the group's
+    /// action, see [`group_default_task`].
+    fn group_default_candidates(&self, cx: &mut WindowContext) -> HashSet<usize> {
+        let Some(candidates) = self.candidates.as_ref() else {
+            return HashSet::default();
+        };
+        let Ok(group_defaults) = self.workspace.update(cx, |workspace, cx| {
+            [TaskGroup::Build, TaskGroup::Test, TaskGroup::Run]
+                .into_iter()
+                .filter_map(|group| group_default_task(workspace, group, cx))
+                .collect::<Vec<_>>()
+        }) else {
+            return HashSet::default();
+        };
+        group_defaults
+            .iter()
+            .filter_map(|(default_kind, default_template)| {
+                candidates.iter().position(|(task_source_kind, task)| {
+                    task_source_kind == default_kind
+                        && task.original_task().label == default_template.label
+                })
+            })
+            .collect()
+    }
+
+    /// Sorts the matches of the candidates that are not pinned, recently used or from the global history
+    /// with the current [`TaskSortMode`], keeping the fuzzy match order between the tasks that compare equal.
+    fn sort_current_matches(&self, matches: &mut [StringMatch], cx: &AppContext) {
+        let (Some(candidates), Some(inventory)) = (
+            self.candidates.as_ref(),
+            self.task_store.read(cx).task_inventory(),
+        ) else {
+            return;
+        };
+        let inventory = inventory.read(cx);
+        let candidate = |m: &StringMatch| &candidates[m.candidate_id];
+        match self.sort_mode {
+            TaskSortMode::Default => {}
+            TaskSortMode::Alphabetical => {
+                matches.sort_by_cached_key(|m| candidate(m).1.resolved_label.to_lowercase())
+            }
+            TaskSortMode::Recency => matches.sort_by_cached_key(|m| {
+                let (task_source_kind, task) = candidate(m);
+                inventory
+                    .scheduled_since(task_source_kind, &task.original_task().label)
+                    .unwrap_or(usize::MAX)
+            }),
+            TaskSortMode::Frequency => matches.sort_by_cached_key(|m| {
+                let (task_source_kind, task) = candidate(m);
+                Reverse(
+                    inventory
+                        .run_stats(task_source_kind, &task.original_task().label)
+                        .map_or(0, |run_stats| run_stats.run_count),
+                )
+            }),
+        }
+    }
+
+    fn set_group_default(&mut self, ix: usize, cx: &mut WindowContext) {
+        let Some((task_source_kind, task)) = self
+            .candidates
+            .as_ref()
+            .and_then(|candidates| candidates.get(ix))
+        else {
+            return;
+        };
+        let Some(group) = task.original_task().group else {
+            return;
+        };
+        if let Some(inventory) = self.task_store.read(cx).task_inventory().cloned() {
+            let task_source_kind = task_source_kind.clone();
+            let label = task.original_task().label.clone();
+            let worktree = self.worktree;
+            inventory.update(cx, |inventory, _| {
+                inventory.set_group_default(worktree, group, task_source_kind, label);
+            });
+        }
+        self.group_default_candidates = self.group_default_candidates(cx);
+    }
+
+    fn toggle_pinned(&mut self, ix: usize, cx: &mut AppContext) {
+        let Some((task_source_kind, task)) = self
+            .candidates
+            .as_ref()
+            .and_then(|candidates| candidates.get(ix))
+        else {
+            return;
+        };
+        let Some(inventory) = self.task_store.read(cx).task_inventory().cloned() else {
+            return;
+        };
+        let source_name = task_source_kind.source_name().into_owned();
+        let label = task.original_task().label.clone();
+        let pinned = inventory.update(cx, |inventory, _| {
+            inventory.toggle_pinned(self.worktree, task_source_kind, label.clone())
+        });
+
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let workspace = workspace.read(cx);
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let worktree_root_path = self.worktree.and_then(|worktree| {
+            Some(
+                workspace
+                    .project()
+                    .read(cx)
+                    .worktree_for_id(worktree, cx)?
+                    .read(cx)
+                    .abs_path(),
+            )
+        });
+        cx.background_executor()
+            .spawn(async move {
+                if pinned {
+                    WORKSPACE_DB
+                        .save_pinned_task(workspace_id, worktree_root_path, source_name, label)
+                        .await
+                } else {
+                    WORKSPACE_DB
+                        .delete_pinned_task(workspace_id, worktree_root_path, source_name, label)
+                        .await
+                }
+            })
+            .detach_and_log_err(cx);
+    }
+
+    /// The id of the selected task, if it was run already and its output can be revealed.
+    fn selected_output_task_id(&self, cx: &AppContext) -> Option<TaskId> {
+        let candidate_id = self.matches.get(self.selected_index)?.candidate_id;
+        let (_, resolved_task) = self.candidates.as_ref()?.get(candidate_id)?;
+        let has_output = self.is_recently_used(candidate_id)
+            || self
+                .task_store
+                .read(cx)
+                .running_task(&resolved_task.id)
+                .is_some();
+        has_output.then(|| resolved_task.id.clone())
+    }
+
+    fn has_running_tasks(&self, cx: &AppContext) -> bool {
+        let task_store = self.task_store.read(cx);
+        self.candidates
+            .iter()
+            .flatten()
+            .any(|(_, resolved_task)| task_store.running_task(&resolved_task.id).is_some())
+    }
+
+    fn kill_task(&self, ix: usize, cx: &mut AppContext) {
+        let Some((_, resolved_task)) = self
+            .candidates
+            .as_ref()
+            .and_then(|candidates| candidates.get(ix))
+        else {
+            return;
+        };
+        let task_id = resolved_task.id.clone();
+        self.task_store
+            .update(cx, |task_store, cx| task_store.kill_task(&task_id, cx));
+    }
+
+    /// Drops the resolved candidates, for them to be re-read from the task sources on the next update.
+    fn invalidate_candidates(&mut self) {
+        self.candidates = None;
+        self.marked_candidates.clear();
+    }
+
     fn delete_previously_used(&mut self, ix: usize, cx: &mut AppContext) {
         let Some(candidates) = self.candidates.as_mut() else {
             return;
         };
-        let Some(task) = candidates.get(ix).map(|(_, task)| task.clone()) else {
+        let Some((kind, task)) = candidates.get(ix).cloned() else {
             return;
         };
         // We remove this candidate manually instead of .taking() the candidates, as we already know the index;
         // it doesn't make sense to requery the inventory for new candidates, as that's potentially costly and more often than not it should just return back
         // the original list without a removed entry.
         candidates.remove(ix);
+        self.marked_candidates.retain(|&marked| marked != ix);
+        for marked in &mut self.marked_candidates {
+            if *marked > ix {
+                *marked -= 1;
+            }
+        }
+        let from_global_history = self.is_from_global_history(ix);
+        if self.is_recently_used(ix) {
+            self.last_used_candidate_index =
+                self.last_used_candidate_index.unwrap_or(0).checked_sub(1);
+        }
+        if let Some(start_index) = self.global_history_start_index.as_mut() {
+            if ix < *start_index {
+                *start_index -= 1;
+            }
+        }
+        if from_global_history {
+            let command = task.original_task().command.clone();
+            self.global_history
+                .retain(|global_command| global_command != &command);
+            cx.background_executor()
+                .spawn(WORKSPACE_DB.delete_global_oneshot_task(command))
+                .detach_and_log_err(cx);
+            return;
+        }
         if let Some(inventory) = self.task_store.read(cx).task_inventory().cloned() {
             inventory.update(cx, |inventory, _| {
                 inventory.delete_previously_used(&task.id);
             })
         };
+        if kind == TaskSourceKind::UserInput {
+            let workspace_id = self
+                .workspace
+                .upgrade()
+                .and_then(|workspace| workspace.read(cx).database_id());
+            if let Some(workspace_id) = workspace_id {
+                cx.background_executor()
+                    .spawn(
+                        WORKSPACE_DB.delete_oneshot_task(
+                            workspace_id,
+                            task.original_task().command.clone(),
+                        ),
+                    )
+                    .detach_and_log_err(cx);
+            }
+        }
     }
 }
 
 pub(crate) struct TasksModal {
     picker: View<Picker<TasksModalDelegate>>,
-    _subscription: Subscription,
+    /// Whether the full resolution of the selected task is shown below the list.
+    show_preview: bool,
+    _subscriptions: Vec<Subscription>,
+    _refresh_running_tasks: Task<()>,
 }
 
 impl TasksModal {
@@ -125,72 +906,352 @@ impl TasksModal {
         task_store: Model<TaskStore>,
         task_context: TaskContext,
         task_overrides: Option<TaskOverrides>,
+        global_history: Vec<String>,
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let picker = cx.new_view(|cx| {
             Picker::uniform_list(
-                TasksModalDelegate::new(task_store, task_context, task_overrides, workspace),
+                TasksModalDelegate::new(
+                    task_store.clone(),
+                    task_context,
+                    task_overrides,
+                    global_history,
+                    TaskSettings::get_global(cx).sort_mode,
+                    workspace,
+                ),
                 cx,
             )
         });
-        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
-            cx.emit(DismissEvent);
+        let mut subscriptions = vec![
+            cx.subscribe(&picker, |_, _, _, cx| {
+                cx.emit(DismissEvent);
+            }),
+            cx.observe(&task_store, |modal, _, cx| {
+                modal.picker.update(cx, |_, cx| cx.notify());
+            }),
+        ];
+        // The inventory is notified when its task sources change (e.g. tasks.json files are edited on disk).
+        if let Some(task_inventory) = task_store.read(cx).task_inventory().cloned() {
+            subscriptions.push(cx.observe(&task_inventory, |modal, _, cx| {
+                modal.picker.update(cx, |picker, cx| {
+                    picker.delegate.invalidate_candidates();
+                    picker.refresh(cx);
+                });
+            }));
+        }
+        // Keep the elapsed time of the running tasks up to date.
+        let _refresh_running_tasks = cx.spawn(|modal, mut cx| async move {
+            loop {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                let updated = modal.update(&mut cx, |modal, cx| {
+                    modal.picker.update(cx, |picker, cx| {
+                        if picker.delegate.has_running_tasks(cx) {
+                            cx.notify();
+                        }
+                    })
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
         });
         Self {
             picker,
-            _subscription,
+            show_preview: TaskSettings::get_global(cx).show_preview,
+            _subscriptions: subscriptions,
+            _refresh_running_tasks,
         }
     }
-}
 
-impl Render for TasksModal {
-    fn render(&mut self, _: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
-        v_flex()
-            .key_context("TasksModal")
-            .w(rems(34.))
-            .child(self.picker.clone())
+    fn open_definition(&mut self, _: &OpenDefinition, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if let Some(selected_match) =
+                picker.delegate.matches.get(picker.delegate.selected_index)
+            {
+                picker
+                    .delegate
+                    .open_definition(selected_match.candidate_id, cx);
+            }
+        });
     }
-}
 
-impl EventEmitter<DismissEvent> for TasksModal {}
-
-impl FocusableView for TasksModal {
-    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
-        self.picker.read(cx).focus_handle(cx)
+    fn reveal_output(&mut self, _: &RevealOutput, cx: &mut ViewContext<Self>) {
+        let Some(task_id) = self.picker.read(cx).delegate.selected_output_task_id(cx) else {
+            return;
+        };
+        cx.dispatch_action(Box::new(RevealTaskOutput {
+            task_id: Some(task_id.0),
+        }));
+        cx.emit(DismissEvent);
     }
-}
-
-impl ModalView for TasksModal {}
 
-impl PickerDelegate for TasksModalDelegate {
-    type ListItem = ListItem;
+    fn toggle_marked(&mut self, _: &ToggleMarked, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if let Some(selected_match) =
+                picker.delegate.matches.get(picker.delegate.selected_index)
+            {
+                picker.delegate.toggle_marked(selected_match.candidate_id);
+                cx.notify();
+            }
+        });
+    }
 
-    fn match_count(&self) -> usize {
-        self.matches.len()
+    fn set_group_default(&mut self, _: &SetGroupDefault, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if let Some(selected_match) =
+                picker.delegate.matches.get(picker.delegate.selected_index)
+            {
+                picker
+                    .delegate
+                    .set_group_default(selected_match.candidate_id, cx);
+                cx.notify();
+            }
+        });
     }
 
-    fn selected_index(&self) -> usize {
-        self.selected_index
+    fn toggle_preview(&mut self, _: &TogglePreview, cx: &mut ViewContext<Self>) {
+        self.show_preview = !self.show_preview;
+        cx.notify();
     }
 
-    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<picker::Picker<Self>>) {
-        self.selected_index = ix;
+    /// Deletes the marked tasks from the history, or the selected one if none are marked.
+    /// Tasks that are not recently used or from the global history are left as they are.
+    fn delete_from_history(&mut self, _: &DeleteFromHistory, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &mut picker.delegate;
+            let mut deleted = if delegate.marked_candidates.is_empty() {
+                delegate
+                    .matches
+                    .get(delegate.selected_index)
+                    .map(|selected_match| selected_match.candidate_id)
+                    .into_iter()
+                    .collect()
+            } else {
+                delegate.marked_candidates.clone()
+            };
+            deleted
+                .retain(|&ix| delegate.is_recently_used(ix) || delegate.is_from_global_history(ix));
+            if deleted.is_empty() {
+                return;
+            }
+            // Deleting from the last candidate keeps the indices of the ones before it valid.
+            deleted.sort_unstable_by(|a, b| b.cmp(a));
+            for ix in deleted {
+                delegate.delete_previously_used(ix, cx);
+            }
+            picker.refresh(cx);
+        });
     }
 
-    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
-        self.placeholder_text.clone()
+    fn cycle_sort_mode(&mut self, _: &CycleSortMode, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.sort_mode = picker.delegate.sort_mode.next();
+            picker.refresh(cx);
+        });
     }
 
-    fn update_matches(
-        &mut self,
-        query: String,
+    fn render_preview(&self, cx: &mut ViewContext<Self>) -> Option<AnyElement> {
+        let delegate = &self.picker.read(cx).delegate;
+        let candidate_id = delegate.matches.get(delegate.selected_index)?.candidate_id;
+        let (source_kind, resolved_task) = delegate.candidates.as_ref()?.get(candidate_id)?;
+        let resolved = resolved_task.resolved.as_ref()?;
+
+        let preview_row = |name: &'static str, value: String| {
+            h_flex()
+                .items_start()
+                .gap_2()
+                .child(
+                    div()
+                        .w(rems(6.))
+                        .flex_none()
+                        .child(Label::new(name).size(LabelSize::Small).color(Color::Muted)),
+                )
+                .child(Label::new(value).size(LabelSize::Small))
+        };
+        let commands = iter::once(resolved)
+            .chain(&resolved_task.parallel)
+            .map(|spawn_in_terminal| spawn_in_terminal.command_label.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let cwd = resolved.cwd.as_ref().map_or_else(
+            || "Project root".to_string(),
+            |cwd| cwd.display().to_string(),
+        );
+        let env = task_env(resolved_task, resolved)
+            .into_iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        Some(
+            v_flex()
+                .p_2()
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(preview_row("Command", commands))
+                .child(preview_row("Directory", cwd))
+                .when(resolved.shell != TaskShell::System, |preview| {
+                    preview.child(preview_row("Shell", resolved.shell.label()))
+                })
+                .when(!env.is_empty(), |preview| {
+                    preview.child(preview_row("Environment", env.join("\n")))
+                })
+                .child(preview_row("Source", task_source_description(source_kind)))
+                .into_any_element(),
+        )
+    }
+
+    fn edit_before_run(&mut self, _: &EditBeforeRun, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if let Some(command) = picker.delegate.edit_selected() {
+                picker.set_query(command, cx);
+            }
+        });
+    }
+
+    /// Puts the resolved command of a previously scheduled task into the query editor, to be edited and spawned as a oneshot.
+    /// With `reevaluate_context`, the task is resolved in the modal's context instead of the one it was scheduled in.
+    pub(crate) fn edit_task(
+        &mut self,
+        task_source_kind: &TaskSourceKind,
+        task: &ResolvedTask,
+        reevaluate_context: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            let reevaluated = reevaluate_context
+                .then(|| {
+                    task.original_task().resolve_task(
+                        &task_source_kind.to_id_base(),
+                        &picker.delegate.task_context,
+                    )
+                })
+                .flatten();
+            let Some(resolved) = reevaluated.as_ref().unwrap_or(task).resolved.clone() else {
+                return;
+            };
+            let command = picker.delegate.edit_resolved(&resolved);
+            picker.set_query(command, cx);
+        });
+    }
+
+    /// Asks for the values of the inputs given in the query editor, then spawns the previously scheduled task.
+    pub(crate) fn ask_for_inputs(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        inputs: Vec<TaskInput>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker
+                .delegate
+                .schedule_with_inputs(task_source_kind, task, inputs, false, false, cx)
+        });
+    }
+}
+
+impl Render for TasksModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+        let mut key_context = KeyContext::new_with_defaults();
+        key_context.add("TasksModal");
+        if self.picker.read(cx).delegate.prompt.is_empty() {
+            key_context.add("empty_query");
+        }
+        v_flex()
+            .key_context(key_context)
+            .on_action(cx.listener(Self::edit_before_run))
+            .on_action(cx.listener(Self::open_definition))
+            .on_action(cx.listener(Self::reveal_output))
+            .on_action(cx.listener(Self::toggle_marked))
+            .on_action(cx.listener(Self::toggle_preview))
+            .on_action(cx.listener(Self::cycle_sort_mode))
+            .on_action(cx.listener(Self::delete_from_history))
+            .on_action(cx.listener(Self::set_group_default))
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .when(self.show_preview, |modal| {
+                modal.children(self.render_preview(cx))
+            })
+    }
+}
+
+impl EventEmitter<DismissEvent> for TasksModal {}
+
+impl FocusableView for TasksModal {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TasksModal {}
+
+impl PickerDelegate for TasksModalDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<picker::Picker<Self>>) {
+        if ix != self.selected_index {
+            self.pending_rerun = None;
+        }
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        if let Some(pending_inputs) = &self.pending_inputs {
+            if let Some(input) = pending_inputs.inputs.first() {
+                return Arc::from(format!(
+                    "{} for \"{}\"",
+                    input.description.as_deref().unwrap_or(&input.env),
+                    pending_inputs.task.display_label()
+                ));
+            }
+        }
+        match &self.pending_args {
+            Some(pending_args) => Arc::from(format!(
+                "Arguments for \"{}\"",
+                pending_args.task.display_label()
+            )),
+            None => self.placeholder_text.clone(),
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.pending_inputs.is_some() {
+            "Type the input's value, then press enter to continue".into()
+        } else if self.pending_args.is_some() {
+            "Type the task's arguments, then press enter to spawn it".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
         cx: &mut ViewContext<picker::Picker<Self>>,
     ) -> Task<()> {
+        if self.pending_args.is_some() || self.pending_inputs.is_some() {
+            self.prompt = query;
+            self.matches.clear();
+            self.selected_index = 0;
+            return Task::ready(());
+        }
+        if query.is_empty() {
+            self.edited_task = None;
+        }
+        let (tags, fuzzy_query) = split_tag_filter(&query);
         cx.spawn(move |picker, mut cx| async move {
             let Some(candidates) = picker
                 .update(&mut cx, |picker, cx| {
-                    match &mut picker.delegate.candidates {
+                    let match_candidates = match &mut picker.delegate.candidates {
                         Some(candidates) => string_match_candidates(candidates.iter()),
                         None => {
                             let Ok((worktree, location)) =
@@ -225,10 +1286,32 @@ impl PickerDelegate for TasksModalDelegate {
 
                             let mut new_candidates = used;
                             new_candidates.extend(current);
+                            let global_candidates =
+                                picker.delegate.global_history_candidates(&new_candidates);
+                            picker.delegate.global_history_start_index =
+                                (!global_candidates.is_empty()).then_some(new_candidates.len());
+                            new_candidates.extend(global_candidates);
+                            picker.delegate.task_key_bindings =
+                                task_key_bindings(&new_candidates, cx);
                             let match_candidates = string_match_candidates(new_candidates.iter());
                             let _ = picker.delegate.candidates.insert(new_candidates);
+                            picker.delegate.worktree = worktree;
                             match_candidates
                         }
+                    };
+                    picker.delegate.pinned_candidates = picker.delegate.pinned_candidates(cx);
+                    picker.delegate.group_default_candidates =
+                        picker.delegate.group_default_candidates(cx);
+                    match &picker.delegate.candidates {
+                        Some(candidates) if !tags.is_empty() => match_candidates
+                            .into_iter()
+                            .filter(|match_candidate| {
+                                candidates.get(match_candidate.id).is_some_and(|(_, task)| {
+                                    has_tags(&task.original_task().tags, &tags)
+                                })
+                            })
+                            .collect(),
+                        _ => match_candidates,
                     }
                 })
                 .ok()
@@ -237,7 +1320,7 @@ impl PickerDelegate for TasksModalDelegate {
             };
             let matches = fuzzy::match_strings(
                 &candidates,
-                &query,
+                &fuzzy_query,
                 true,
                 1000,
                 &Default::default(),
@@ -245,20 +1328,53 @@ impl PickerDelegate for TasksModalDelegate {
             )
             .await;
             picker
-                .update(&mut cx, |picker, _| {
+                .update(&mut cx, |picker, cx| {
                     let delegate = &mut picker.delegate;
+                    let mut matches = matches;
+                    let pinned_candidates = &delegate.pinned_candidates;
+                    let last_used_candidate_index = delegate.last_used_candidate_index;
+                    let global_history_start_index = delegate.global_history_start_index;
+                    let is_from_global_history = |candidate_id: usize| {
+                        global_history_start_index
+                            .is_some_and(|start_index| candidate_id >= start_index)
+                    };
+                    let section = |m: &StringMatch| {
+                        (
+                            !pinned_candidates.contains(&m.candidate_id),
+                            last_used_candidate_index.map_or(true, |index| m.candidate_id > index),
+                            is_from_global_history(m.candidate_id),
+                        )
+                    };
+                    matches.sort_by_key(section);
+                    let current_start =
+                        matches.partition_point(|m| section(m) < (true, true, false));
+                    let current_end =
+                        matches.partition_point(|m| section(m) <= (true, true, false));
+                    delegate.sort_current_matches(&mut matches[current_start..current_end], cx);
                     delegate.matches = matches;
-                    if let Some(index) = delegate.last_used_candidate_index {
-                        delegate.matches.sort_by_key(|m| m.candidate_id > index);
-                    }
+                    let pinned_candidates = &delegate.pinned_candidates;
 
                     delegate.prompt = query;
+                    delegate.pending_rerun = None;
+                    let pinned_count = delegate.matches.partition_point(|matching_task| {
+                        pinned_candidates.contains(&matching_task.candidate_id)
+                    });
+                    delegate.pinned_divider_index = pinned_count.checked_sub(1);
                     delegate.divider_index = delegate.last_used_candidate_index.and_then(|index| {
-                        let index = delegate
-                            .matches
-                            .partition_point(|matching_task| matching_task.candidate_id <= index);
-                        Some(index).and_then(|index| (index != 0).then(|| index - 1))
+                        let index = pinned_count
+                            + delegate.matches[pinned_count..].partition_point(|matching_task| {
+                                matching_task.candidate_id <= index
+                            });
+                        Some(index).and_then(|index| (index != pinned_count).then(|| index - 1))
                     });
+                    delegate.global_history_divider_index = delegate
+                        .matches
+                        .iter()
+                        .position(|m| {
+                            !pinned_candidates.contains(&m.candidate_id)
+                                && is_from_global_history(m.candidate_id)
+                        })
+                        .and_then(|index| index.checked_sub(1));
 
                     if delegate.matches.is_empty() {
                         delegate.selected_index = 0;
@@ -272,34 +1388,79 @@ impl PickerDelegate for TasksModalDelegate {
     }
 
     fn confirm(&mut self, omit_history_entry: bool, cx: &mut ViewContext<picker::Picker<Self>>) {
+        if self.pending_inputs.is_some() {
+            self.confirm_pending_input(omit_history_entry, cx);
+            return;
+        }
+        if self.pending_args.is_some() {
+            self.confirm_pending_args(omit_history_entry, cx);
+            return;
+        }
+        if self.edited_task.is_some() {
+            self.confirm_input(omit_history_entry, cx);
+            return;
+        }
+        if !self.marked_candidates.is_empty() {
+            self.spawn_marked(omit_history_entry, cx);
+            return;
+        }
         let current_match_index = self.selected_index();
-        let task = self
+        let Some(ix) = self
             .matches
             .get(current_match_index)
-            .and_then(|current_match| {
-                let ix = current_match.candidate_id;
-                self.candidates
-                    .as_ref()
-                    .map(|candidates| candidates[ix].clone())
-            });
-        let Some((task_source_kind, mut task)) = task else {
+            .map(|current_match| current_match.candidate_id)
+        else {
             return;
         };
-        if let Some(TaskOverrides {
-            reveal_target: Some(reveal_target),
-        }) = &self.task_overrides
+        if let Some(pending_rerun) = self
+            .pending_rerun
+            .as_mut()
+            .filter(|pending_rerun| pending_rerun.candidate_index == ix)
         {
-            if let Some(resolved_task) = &mut task.resolved {
-                resolved_task.reveal_target = *reveal_target;
+            pending_rerun.omit_history_entry = omit_history_entry;
+            self.confirm_pending_rerun(false, cx);
+            return;
+        }
+        let Some((task_source_kind, mut task)) = self
+            .candidates
+            .as_ref()
+            .map(|candidates| candidates[ix].clone())
+        else {
+            return;
+        };
+        self.apply_overrides(&mut task);
+
+        // Rerunning a task from history reuses the context it was resolved in
+        // back then, so confirm first if the current one would change the run.
+        if self.is_recently_used(ix) {
+            self.pending_rerun =
+                self.rerun_in_current_context(ix, &task_source_kind, &task, omit_history_entry);
+            if self.pending_rerun.is_some() {
+                cx.notify();
+                return;
             }
+            // The values of the password inputs are not kept in the history, so they are asked for again.
+            let inputs = password_inputs(&task);
+            self.schedule_with_inputs(
+                task_source_kind,
+                task,
+                inputs,
+                false,
+                omit_history_entry,
+                cx,
+            );
+        } else {
+            let inputs = task.original_task().inputs.clone();
+            let ask_for_args = task.original_task().prompt_for_args;
+            self.schedule_with_inputs(
+                task_source_kind,
+                task,
+                inputs,
+                ask_for_args,
+                omit_history_entry,
+                cx,
+            );
         }
-
-        self.workspace
-            .update(cx, |workspace, cx| {
-                schedule_resolved_task(workspace, task_source_kind, task, omit_history_entry, cx);
-            })
-            .ok();
-        cx.emit(DismissEvent);
     }
 
     fn dismissed(&mut self, cx: &mut ViewContext<picker::Picker<Self>>) {
@@ -333,6 +1494,48 @@ impl PickerDelegate for TasksModalDelegate {
                 tooltip_label_text.push_str(&resolved.command_label);
             }
         }
+        for spawn_in_terminal in &resolved_task.parallel {
+            tooltip_label_text.push('\n');
+            tooltip_label_text.push_str(&spawn_in_terminal.command_label);
+        }
+        if let Some(resolved) = resolved_task.resolved.as_ref() {
+            if resolved.shell != TaskShell::System {
+                if !tooltip_label_text.trim().is_empty() {
+                    tooltip_label_text.push('\n');
+                }
+                tooltip_label_text.push_str("Shell: ");
+                tooltip_label_text.push_str(&resolved.shell.label());
+            }
+            for (name, value) in task_env(resolved_task, resolved) {
+                if !tooltip_label_text.trim().is_empty() {
+                    tooltip_label_text.push('\n');
+                }
+                tooltip_label_text.push_str(&format!("{name}={value}"));
+            }
+        }
+        if !template.depends_on.is_empty() {
+            if !tooltip_label_text.trim().is_empty() {
+                tooltip_label_text.push('\n');
+            }
+            tooltip_label_text.push_str("Runs first: ");
+            tooltip_label_text.push_str(&template.depends_on.join(", "));
+        }
+        let run_stats = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .and_then(|inventory| {
+                inventory
+                    .read(cx)
+                    .run_stats(source_kind, &template.label)
+                    .cloned()
+            });
+        if let Some(run_stats) = &run_stats {
+            if !tooltip_label_text.trim().is_empty() {
+                tooltip_label_text.push('\n');
+            }
+            tooltip_label_text.push_str(&run_stats_summary(run_stats));
+        }
         let tooltip_label = if tooltip_label_text.trim().is_empty() {
             None
         } else {
@@ -349,71 +1552,348 @@ impl PickerDelegate for TasksModalDelegate {
             TaskSourceKind::UserInput => Some(Icon::new(IconName::Terminal)),
             TaskSourceKind::AbsPath { .. } => Some(Icon::new(IconName::Settings)),
             TaskSourceKind::Worktree { .. } => Some(Icon::new(IconName::FileTree)),
-            TaskSourceKind::Language { name } => file_icons::FileIcons::get(cx)
-                .get_type_icon(&name.to_lowercase())
+            TaskSourceKind::VsCode { .. } => Some(Icon::new(IconName::FileCode)),
+            TaskSourceKind::DetectedScript {
+                package_manager, ..
+            } => file_icons::FileIcons::get(cx)
+                .get_type_icon(package_manager.name())
+                .map(Icon::from_path),
+            TaskSourceKind::MakefileTarget { .. } => file_icons::FileIcons::get(cx)
+                .get_type_icon("make")
+                .map(Icon::from_path),
+            TaskSourceKind::JustfileRecipe { .. } => file_icons::FileIcons::get(cx)
+                .get_type_icon("just")
                 .map(Icon::from_path),
+            TaskSourceKind::Language { name } | TaskSourceKind::LanguageSettings { name } => {
+                file_icons::FileIcons::get(cx)
+                    .get_type_icon(&name.to_lowercase())
+                    .map(Icon::from_path)
+            }
         }
         .map(|icon| icon.color(Color::Muted).size(IconSize::Small));
-        let history_run_icon = if Some(ix) <= self.divider_index {
-            Some(
-                Icon::new(IconName::HistoryRerun)
-                    .color(Color::Muted)
-                    .size(IconSize::Small)
-                    .into_any_element(),
-            )
+        let is_recently_used = self.is_recently_used(hit.candidate_id);
+        let is_from_global_history = self.is_from_global_history(hit.candidate_id);
+        let is_pinned = self.pinned_candidates.contains(&hit.candidate_id);
+        let is_marked = self.marked_candidates.contains(&hit.candidate_id);
+        let default_of_group = template
+            .group
+            .filter(|_| self.group_default_candidates.contains(&hit.candidate_id));
+        let running_task = self
+            .task_store
+            .read(cx)
+            .running_task(&resolved_task.id)
+            .cloned();
+        // The last run of a history entry is shown in its place, unless the entry is running again.
+        let history_run_stats = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .filter(|_| is_recently_used && running_task.is_none())
+            .and_then(|inventory| {
+                inventory
+                    .read(cx)
+                    .history_run_stats(&resolved_task.id)
+                    .cloned()
+            });
+        let history_run_icon = if let Some(history_run_stats) = &history_run_stats {
+            let (icon, color) = if history_run_stats.last_success {
+                (IconName::Check, Color::Success)
+            } else {
+                (IconName::XCircle, Color::Error)
+            };
+            let summary = run_stats_summary(history_run_stats);
+            h_flex()
+                .id("last-run")
+                .gap_1()
+                .child(
+                    Label::new(format_elapsed_time(history_run_stats.last_duration))
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .child(Icon::new(icon).color(color).size(IconSize::Small))
+                .tooltip(move |cx| Tooltip::text(summary.clone(), cx))
+                .into_any_element()
+        } else if is_recently_used {
+            Icon::new(IconName::HistoryRerun)
+                .color(Color::Muted)
+                .size(IconSize::Small)
+                .into_any_element()
+        } else if is_from_global_history {
+            div()
+                .id("global-history")
+                .child(
+                    Icon::new(IconName::Globe)
+                        .color(Color::Muted)
+                        .size(IconSize::Small),
+                )
+                .tooltip(|cx| Tooltip::text("From Global History", cx))
+                .into_any_element()
         } else {
-            Some(
-                v_flex()
-                    .flex_none()
-                    .size(IconSize::Small.rems())
-                    .into_any_element(),
-            )
+            v_flex()
+                .flex_none()
+                .size(IconSize::Small.rems())
+                .into_any_element()
         };
+        let task_index = hit.candidate_id;
+        let running_badge = running_task.map(|running_task| {
+            h_flex()
+                .gap_1()
+                .child(
+                    Icon::new(IconName::ArrowCircle)
+                        .color(Color::Accent)
+                        .size(IconSize::XSmall)
+                        .with_animation(
+                            "running-task",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        ),
+                )
+                .child(
+                    Label::new(format_elapsed_time(running_task.started_at.elapsed()))
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .child(
+                    IconButton::new("stop", IconName::Stop)
+                        .shape(IconButtonShape::Square)
+                        .icon_color(Color::Error)
+                        .size(ButtonSize::None)
+                        .icon_size(IconSize::XSmall)
+                        .on_click(cx.listener(move |picker, _event, cx| {
+                            cx.stop_propagation();
+                            cx.prevent_default();
+
+                            picker.delegate.kill_task(task_index, cx);
+                        }))
+                        .tooltip(|cx| Tooltip::text("Stop Task", cx)),
+                )
+        });
+        let run_stats_badge = run_stats
+            .filter(|_| {
+                running_badge.is_none()
+                    && history_run_stats.is_none()
+                    && TaskSettings::get_global(cx).show_run_stats
+            })
+            .map(|run_stats| {
+                Label::new(format!(
+                    "{}× {}",
+                    run_stats.run_count,
+                    format_elapsed_time(run_stats.last_duration)
+                ))
+                .size(LabelSize::XSmall)
+                .color(if run_stats.last_success {
+                    Color::Muted
+                } else {
+                    Color::Error
+                })
+            });
+        let is_watched = self.task_store.read(cx).is_watched(&resolved_task.id);
+        let end_slot = h_flex()
+            .gap_2()
+            .when(is_marked, |end_slot| {
+                end_slot.child(
+                    Icon::new(IconName::Check)
+                        .color(Color::Accent)
+                        .size(IconSize::Small),
+                )
+            })
+            .when(is_watched, |end_slot| {
+                end_slot.child(
+                    div()
+                        .id("watching")
+                        .child(
+                            Icon::new(IconName::Eye)
+                                .color(Color::Accent)
+                                .size(IconSize::Small),
+                        )
+                        .tooltip(|cx| Tooltip::text("Restarted on File Changes", cx)),
+                )
+            })
+            .children(running_badge)
+            .children(run_stats_badge)
+            .children(self.task_key_bindings.get(&template.label).cloned())
+            .children(default_of_group.map(|group| {
+                div()
+                    .id("group-default")
+                    .px_1()
+                    .rounded_md()
+                    .bg(cx.theme().colors().element_background)
+                    .child(
+                        Label::new(group.label())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Accent),
+                    )
+                    .tooltip(move |cx| Tooltip::text(format!("Default {} Task", group.label()), cx))
+            }))
+            .when(is_pinned, |end_slot| {
+                end_slot.child(
+                    Icon::new(IconName::StarFilled)
+                        .color(Color::Accent)
+                        .size(IconSize::Small),
+                )
+            })
+            .child(history_run_icon)
+            .into_any_element();
+        let pin_button = IconButton::new(
+            "pin",
+            if is_pinned {
+                IconName::StarFilled
+            } else {
+                IconName::Star
+            },
+        )
+        .shape(IconButtonShape::Square)
+        .icon_color(if is_pinned {
+            Color::Accent
+        } else {
+            Color::Muted
+        })
+        .size(ButtonSize::None)
+        .icon_size(IconSize::XSmall)
+        .on_click(cx.listener(move |picker, _event, cx| {
+            cx.stop_propagation();
+            cx.prevent_default();
+
+            picker.delegate.toggle_pinned(task_index, cx);
+            picker.refresh(cx);
+        }))
+        .tooltip(move |cx| Tooltip::text(if is_pinned { "Unpin Task" } else { "Pin Task" }, cx));
+        let mark_button = IconButton::new("mark", IconName::Check)
+            .shape(IconButtonShape::Square)
+            .icon_color(if is_marked {
+                Color::Accent
+            } else {
+                Color::Muted
+            })
+            .size(ButtonSize::None)
+            .icon_size(IconSize::XSmall)
+            .on_click(cx.listener(move |picker, _event, cx| {
+                cx.stop_propagation();
+                cx.prevent_default();
+
+                picker.delegate.toggle_marked(task_index);
+                cx.notify();
+            }))
+            .tooltip(move |cx| {
+                let label = if is_marked {
+                    "Unmark Task"
+                } else {
+                    "Mark Task to Spawn"
+                };
+                Tooltip::for_action(label, &ToggleMarked, cx)
+            });
+        let group_default_button =
+            template
+                .group
+                .filter(|_| default_of_group.is_none())
+                .map(|group| {
+                    IconButton::new("set-group-default", IconName::Bolt)
+                        .shape(IconButtonShape::Square)
+                        .icon_color(Color::Muted)
+                        .size(ButtonSize::None)
+                        .icon_size(IconSize::XSmall)
+                        .on_click(cx.listener(move |picker, _event, cx| {
+                            cx.stop_propagation();
+                            cx.prevent_default();
+
+                            picker.delegate.set_group_default(task_index, cx);
+                            cx.notify();
+                        }))
+                        .tooltip(move |cx| {
+                            Tooltip::for_action(
+                                format!("Set as Default {} Task", group.label()),
+                                &SetGroupDefault,
+                                cx,
+                            )
+                        })
+                });
+        let definition_button = (!source_kind.definition_paths().is_empty()).then(|| {
+            IconButton::new("open-definition", IconName::ArrowUpRight)
+                .shape(IconButtonShape::Square)
+                .icon_color(Color::Muted)
+                .size(ButtonSize::None)
+                .icon_size(IconSize::XSmall)
+                .on_click(cx.listener(move |picker, _event, cx| {
+                    cx.stop_propagation();
+                    cx.prevent_default();
+
+                    picker.delegate.open_definition(task_index, cx);
+                }))
+                .tooltip(|cx| Tooltip::for_action("Open Task Definition", &OpenDefinition, cx))
+        });
 
         Some(
             ListItem::new(SharedString::from(format!("tasks-modal-{ix}")))
                 .inset(true)
                 .start_slot::<Icon>(icon)
-                .end_slot::<AnyElement>(history_run_icon)
+                .end_slot::<AnyElement>(end_slot)
                 .spacing(ListItemSpacing::Sparse)
                 .when_some(tooltip_label, |list_item, item_label| {
                     list_item.tooltip(move |_| item_label.clone())
                 })
                 .map(|item| {
-                    let item = if matches!(source_kind, TaskSourceKind::UserInput)
-                        || Some(ix) <= self.divider_index
-                    {
-                        let task_index = hit.candidate_id;
-                        let delete_button = div().child(
-                            IconButton::new("delete", IconName::Close)
-                                .shape(IconButtonShape::Square)
-                                .icon_color(Color::Muted)
-                                .size(ButtonSize::None)
-                                .icon_size(IconSize::XSmall)
-                                .on_click(cx.listener(move |picker, _event, cx| {
-                                    cx.stop_propagation();
-                                    cx.prevent_default();
-
-                                    picker.delegate.delete_previously_used(task_index, cx);
-                                    picker.delegate.last_used_candidate_index = picker
-                                        .delegate
-                                        .last_used_candidate_index
-                                        .unwrap_or(0)
-                                        .checked_sub(1);
-                                    picker.refresh(cx);
-                                }))
-                                .tooltip(|cx| {
-                                    Tooltip::text("Delete Previously Scheduled Task", cx)
-                                }),
-                        );
-                        item.end_hover_slot(delete_button)
-                    } else {
-                        item
-                    };
+                    let item =
+                        if matches!(source_kind, TaskSourceKind::UserInput) || is_recently_used {
+                            let delete_button = div().child(
+                                IconButton::new("delete", IconName::Close)
+                                    .shape(IconButtonShape::Square)
+                                    .icon_color(Color::Muted)
+                                    .size(ButtonSize::None)
+                                    .icon_size(IconSize::XSmall)
+                                    .on_click(cx.listener(move |picker, _event, cx| {
+                                        cx.stop_propagation();
+                                        cx.prevent_default();
+
+                                        picker.delegate.delete_previously_used(task_index, cx);
+                                        picker.refresh(cx);
+                                    }))
+                                    .tooltip(move |cx| {
+                                        let label = if is_from_global_history {
+                                            "Delete from Global History"
+                                        } else {
+                                            "Delete Previously Scheduled Task"
+                                        };
+                                        Tooltip::for_action(label, &DeleteFromHistory, cx)
+                                    }),
+                            );
+                            item.end_hover_slot(
+                                h_flex()
+                                    .gap_1()
+                                    .children(definition_button)
+                                    .children(group_default_button)
+                                    .child(mark_button)
+                                    .child(pin_button)
+                                    .child(delete_button),
+                            )
+                        } else {
+                            item.end_hover_slot(
+                                h_flex()
+                                    .gap_1()
+                                    .children(definition_button)
+                                    .children(group_default_button)
+                                    .child(mark_button)
+                                    .child(pin_button),
+                            )
+                        };
                     item
                 })
                 .toggle_state(selected)
-                .child(highlighted_location.render(cx)),
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(highlighted_location.render(cx))
+                        .children(template.tags.iter().map(|tag| {
+                            div()
+                                .px_1()
+                                .rounded_md()
+                                .bg(cx.theme().colors().element_background)
+                                .child(
+                                    Label::new(format!("#{tag}"))
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                        })),
+                ),
         )
     }
 
@@ -429,35 +1909,70 @@ impl PickerDelegate for TasksModalDelegate {
     }
 
     fn confirm_input(&mut self, omit_history_entry: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if self.pending_inputs.is_some() {
+            self.confirm_pending_input(omit_history_entry, cx);
+            return;
+        }
+        if self.pending_args.is_some() {
+            self.confirm_pending_args(omit_history_entry, cx);
+            return;
+        }
         let Some((task_source_kind, mut task)) = self.spawn_oneshot() else {
             return;
         };
-
-        if let Some(TaskOverrides {
-            reveal_target: Some(reveal_target),
-        }) = self.task_overrides
-        {
-            if let Some(resolved_task) = &mut task.resolved {
-                resolved_task.reveal_target = reveal_target;
-            }
-        }
-        self.workspace
-            .update(cx, |workspace, cx| {
-                schedule_resolved_task(workspace, task_source_kind, task, omit_history_entry, cx);
-            })
-            .ok();
-        cx.emit(DismissEvent);
+        self.apply_overrides(&mut task);
+        self.schedule(task_source_kind, task, omit_history_entry, cx);
     }
 
     fn separators_after_indices(&self) -> Vec<usize> {
-        if let Some(i) = self.divider_index {
-            vec![i]
-        } else {
-            Vec::new()
-        }
+        self.pinned_divider_index
+            .into_iter()
+            .chain(self.divider_index)
+            .chain(self.global_history_divider_index)
+            .collect()
     }
     fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<gpui::AnyElement> {
-        let is_recent_selected = self.divider_index >= Some(self.selected_index);
+        if let Some(pending_rerun) = &self.pending_rerun {
+            return Some(self.render_pending_rerun(pending_rerun, cx));
+        }
+        let pending_prompt_label = match &self.pending_inputs {
+            Some(pending_inputs)
+                if pending_inputs.inputs.len() > 1 || pending_inputs.ask_for_args =>
+            {
+                Some("Next Input")
+            }
+            Some(_) => Some("Spawn"),
+            None => self
+                .pending_args
+                .is_some()
+                .then_some("Spawn with Arguments"),
+        };
+        if let Some(pending_prompt_label) = pending_prompt_label {
+            return Some(
+                h_flex()
+                    .w_full()
+                    .h_8()
+                    .p_2()
+                    .justify_end()
+                    .rounded_b_md()
+                    .bg(cx.theme().colors().ghost_element_selected)
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .children(KeyBinding::for_action(&menu::Confirm, cx).map(|keybind| {
+                        Button::new("spawn-with-args", pending_prompt_label)
+                            .label_size(LabelSize::Small)
+                            .key_binding(keybind)
+                            .on_click(|_, cx| cx.dispatch_action(menu::Confirm.boxed_clone()))
+                    }))
+                    .into_any_element(),
+            );
+        }
+        let is_recent_selected = self
+            .matches
+            .get(self.selected_index)
+            .is_some_and(|selected_match| self.is_recently_used(selected_match.candidate_id));
+        let has_selected_output = self.selected_output_task_id(cx).is_some();
+        let marked_count = self.marked_candidates.len();
         let current_modifiers = cx.modifiers();
         let left_button = if self
             .task_store
@@ -482,8 +1997,9 @@ impl PickerDelegate for TasksModalDelegate {
                 .border_t_1()
                 .border_color(cx.theme().colors().border_variant)
                 .child(
-                    left_button
-                        .map(|(label, action)| {
+                    h_flex()
+                        .gap_1()
+                        .children(left_button.map(|(label, action)| {
                             let keybind = KeyBinding::for_action(&*action, cx);
 
                             Button::new("edit-current-task", label)
@@ -492,9 +2008,47 @@ impl PickerDelegate for TasksModalDelegate {
                                 .on_click(move |_, cx| {
                                     cx.dispatch_action(action.boxed_clone());
                                 })
-                                .into_any_element()
+                        }))
+                        .when(has_selected_output, |this| {
+                            let keybind = KeyBinding::for_action(&RevealOutput, cx);
+                            this.child(
+                                Button::new("reveal-output", "Reveal Output")
+                                    .label_size(LabelSize::Small)
+                                    .when_some(keybind, |this, keybind| this.key_binding(keybind))
+                                    .on_click(|_, cx| {
+                                        cx.dispatch_action(RevealOutput.boxed_clone());
+                                    }),
+                            )
+                        })
+                        .child({
+                            let keybind = KeyBinding::for_action(&CycleSortMode, cx);
+                            Button::new("cycle-sort-mode", self.sort_mode.label())
+                                .label_size(LabelSize::Small)
+                                .when_some(keybind, |this, keybind| this.key_binding(keybind))
+                                .on_click(|_, cx| {
+                                    cx.dispatch_action(CycleSortMode.boxed_clone());
+                                })
+                        })
+                        .when(self.last_used_candidate_index.is_some(), |this| {
+                            let keybind = KeyBinding::for_action(&ClearHistory, cx);
+                            this.child(
+                                Button::new("clear-history", "Clear History")
+                                    .label_size(LabelSize::Small)
+                                    .when_some(keybind, |this, keybind| this.key_binding(keybind))
+                                    .on_click(|_, cx| {
+                                        cx.dispatch_action(ClearHistory.boxed_clone());
+                                    }),
+                            )
                         })
-                        .unwrap_or_else(|| h_flex().into_any_element()),
+                        .child({
+                            let keybind = KeyBinding::for_action(&ReloadAll, cx);
+                            Button::new("reload-tasks", "Reload Tasks")
+                                .label_size(LabelSize::Small)
+                                .when_some(keybind, |this, keybind| this.key_binding(keybind))
+                                .on_click(|_, cx| {
+                                    cx.dispatch_action(ReloadAll.boxed_clone());
+                                })
+                        }),
                 )
                 .map(|this| {
                     if (current_modifiers.alt || self.matches.is_empty()) && !self.prompt.is_empty()
@@ -504,11 +2058,13 @@ impl PickerDelegate for TasksModalDelegate {
                         }
                         .boxed_clone();
                         this.children(KeyBinding::for_action(&*action, cx).map(|keybind| {
-                            let spawn_oneshot_label = if current_modifiers.secondary() {
-                                "Spawn Oneshot Without History"
-                            } else {
-                                "Spawn Oneshot"
-                            };
+                            let spawn_oneshot_label =
+                                match (self.edited_task.is_some(), current_modifiers.secondary()) {
+                                    (true, true) => "Spawn Edited Task Without History",
+                                    (true, false) => "Spawn Edited Task",
+                                    (false, true) => "Spawn Oneshot Without History",
+                                    (false, false) => "Spawn Oneshot",
+                                };
 
                             Button::new("spawn-onehshot", spawn_oneshot_label)
                                 .label_size(LabelSize::Small)
@@ -518,10 +2074,12 @@ impl PickerDelegate for TasksModalDelegate {
                     } else if current_modifiers.secondary() {
                         this.children(KeyBinding::for_action(&menu::SecondaryConfirm, cx).map(
                             |keybind| {
-                                let label = if is_recent_selected {
-                                    "Rerun Without History"
+                                let label: SharedString = if marked_count > 0 {
+                                    format!("Spawn {marked_count} Marked Without History").into()
+                                } else if is_recent_selected {
+                                    "Rerun Without History".into()
                                 } else {
-                                    "Spawn Without History"
+                                    "Spawn Without History".into()
                                 };
                                 Button::new("spawn", label)
                                     .label_size(LabelSize::Small)
@@ -533,8 +2091,13 @@ impl PickerDelegate for TasksModalDelegate {
                         ))
                     } else {
                         this.children(KeyBinding::for_action(&menu::Confirm, cx).map(|keybind| {
-                            let run_entry_label =
-                                if is_recent_selected { "Rerun" } else { "Spawn" };
+                            let run_entry_label: SharedString = if marked_count > 0 {
+                                format!("Spawn {marked_count} Marked").into()
+                            } else if is_recent_selected {
+                                "Rerun".into()
+                            } else {
+                                "Spawn".into()
+                            };
 
                             Button::new("spawn", run_entry_label)
                                 .label_size(LabelSize::Small)
@@ -550,6 +2113,11 @@ impl PickerDelegate for TasksModalDelegate {
     }
 }
 
+/// The inputs of the task that have to be typed in again on its reruns.
+fn password_inputs(task: &ResolvedTask) -> Vec<TaskInput> {
+    task.original_task().password_inputs().cloned().collect()
+}
+
 fn string_match_candidates<'a>(
     candidates: impl Iterator<Item = &'a (TaskSourceKind, ResolvedTask)> + 'a,
 ) -> Vec<StringMatchCandidate> {
@@ -563,29 +2131,168 @@ fn string_match_candidates<'a>(
         .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{path::PathBuf, sync::Arc};
+/// Splits the `#tag` words out of a query, returning the tags and the rest of the query to fuzzy match.
+fn split_tag_filter(query: &str) -> (Vec<String>, String) {
+    let mut tags = Vec::new();
+    let mut rest = Vec::new();
+    for word in query.split_whitespace() {
+        match word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            Some(tag) => tags.push(tag.to_lowercase()),
+            None => rest.push(word),
+        }
+    }
+    (tags, rest.join(" "))
+}
 
-    use editor::Editor;
-    use gpui::{TestAppContext, VisualTestContext};
-    use language::{Language, LanguageConfig, LanguageMatcher, Point};
-    use project::{ContextProviderWithTasks, FakeFs, Project};
-    use serde_json::json;
-    use task::TaskTemplates;
-    use workspace::CloseInactiveTabsAndPanes;
+/// Whether each of the (lowercase) tags filtered by starts one of the task's tags.
+fn has_tags(task_tags: &[String], filter_tags: &[String]) -> bool {
+    filter_tags.iter().all(|filter_tag| {
+        task_tags
+            .iter()
+            .any(|task_tag| task_tag.to_lowercase().starts_with(filter_tag.as_str()))
+    })
+}
 
-    use crate::{modal::Spawn, tests::init_test};
+/// Where the tasks of the source kind are defined, e.g. `.zed/tasks.json` or `package.json (npm)`.
+fn task_source_description(source_kind: &TaskSourceKind) -> String {
+    match source_kind {
+        TaskSourceKind::UserInput => "Oneshot task".to_string(),
+        TaskSourceKind::AbsPath { abs_path, .. } => abs_path.display().to_string(),
+        TaskSourceKind::Worktree { .. } | TaskSourceKind::VsCode { .. } => source_kind
+            .definition_paths()
+            .first()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default(),
+        TaskSourceKind::Language { name } => format!("{name} language tasks"),
+        TaskSourceKind::LanguageSettings { name } => format!("{name} language settings"),
+        TaskSourceKind::DetectedScript {
+            package_manager, ..
+        } => format!("package.json ({})", package_manager.name()),
+        TaskSourceKind::MakefileTarget { .. } => "Makefile".to_string(),
+        TaskSourceKind::JustfileRecipe { .. } => "justfile".to_string(),
+    }
+}
 
-    use super::*;
+/// The environment variables the task sets on top of the project's environment, from its `env`,
+/// sorted by name.
+fn task_env<'a>(
+    resolved_task: &ResolvedTask,
+    resolved: &'a SpawnInTerminal,
+) -> Vec<(&'a String, &'a String)> {
+    let task_context = resolved_task.task_context();
+    let mut env = resolved
+        .env
+        .iter()
+        .filter(|(name, value)| {
+            task_context.project_env.get(*name) != Some(*value)
+                && !name.starts_with(ZED_VARIABLE_NAME_PREFIX)
+        })
+        .collect::<Vec<_>>();
+    env.sort();
+    env
+}
 
-    #[gpui::test]
-    async fn test_spawn_tasks_modal_query_reuse(cx: &mut TestAppContext) {
-        init_test(cx);
-        let fs = FakeFs::new(cx.executor());
-        fs.insert_tree(
-            "/dir",
-            json!({
+/// Summarizes the finished runs of a task, e.g. `Ran 3 times, last run succeeded after 5s`.
+fn run_stats_summary(run_stats: &TaskRunStats) -> String {
+    let runs = if run_stats.run_count == 1 {
+        "Ran once".to_string()
+    } else {
+        format!("Ran {} times", run_stats.run_count)
+    };
+    let status = match (run_stats.last_success, run_stats.last_exit_code) {
+        (true, _) => "succeeded".to_string(),
+        (false, Some(exit_code)) => format!("failed with exit code {exit_code}"),
+        (false, None) => "failed".to_string(),
+    };
+    format!(
+        "{runs}, last run {status} after {}",
+        format_elapsed_time(run_stats.last_duration)
+    )
+}
+
+/// Formats how long a task has been running for, e.g. `42s`, `3m 05s` or `1h 02m`.
+pub(crate) fn format_elapsed_time(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else if seconds < 60 * 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h {:02}m", seconds / (60 * 60), seconds / 60 % 60)
+    }
+}
+
+/// The key bindings of the candidates given, by their labels.
+fn task_key_bindings(
+    candidates: &[(TaskSourceKind, ResolvedTask)],
+    cx: &mut WindowContext,
+) -> HashMap<String, KeyBinding> {
+    candidates
+        .iter()
+        .map(|(_, task)| task.original_task().label.as_str())
+        .filter_map(|label| Some((label.to_string(), task_key_binding(label, cx)?)))
+        .collect()
+}
+
+/// The key binding of a `task::Spawn` action for the task with the label given, whichever overrides it has.
+fn task_key_binding(label: &str, cx: &mut WindowContext) -> Option<KeyBinding> {
+    let reveal_targets = [
+        None,
+        Some(RevealTarget::Dock),
+        Some(RevealTarget::Center),
+        Some(RevealTarget::Split),
+        Some(RevealTarget::Window),
+    ];
+    let reveals = [
+        None,
+        Some(RevealStrategy::Always),
+        Some(RevealStrategy::NoFocus),
+        Some(RevealStrategy::Never),
+    ];
+    reveal_targets
+        .into_iter()
+        .flat_map(|reveal_target| reveals.map(|reveal| (reveal_target, reveal)))
+        .find_map(|(reveal_target, reveal)| {
+            let spawn = Spawn::ByName {
+                task_name: label.to_string(),
+                reveal_target,
+                reveal,
+            };
+            KeyBinding::for_action(&spawn, cx)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        path::{Path, PathBuf},
+        rc::Rc,
+        sync::Arc,
+    };
+
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{
+        language_settings::{AllLanguageSettings, LanguageSettingsContent, LanguageTaskConfig},
+        Language, LanguageConfig, LanguageMatcher, Point,
+    };
+    use project::{ContextProviderWithTasks, FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use task::TaskTemplates;
+    use workspace::CloseInactiveTabsAndPanes;
+
+    use crate::{modal::Spawn, tests::init_test};
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_spawn_tasks_modal_query_reuse(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
                 ".zed": {
                     "tasks.json": r#"[
                         {
@@ -677,69 +2384,1065 @@ mod tests {
             "New oneshot should match custom command query"
         );
 
-        cx.dispatch_action(picker::ConfirmInput { secondary: false });
+        cx.dispatch_action(picker::ConfirmInput { secondary: false });
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "",
+            "Query should be reset after confirming"
+        );
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![query_str, "another one", "example task"],
+            "Last recently used one show task should be listed first"
+        );
+
+        cx.dispatch_action(picker::ConfirmCompletion);
+        assert_eq!(
+            query(&tasks_picker, cx),
+            query_str,
+            "Query should be set to the custom task's name"
+        );
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![query_str],
+            "Only custom task should be listed"
+        );
+
+        let query_str = "0";
+        cx.simulate_input(query_str);
+        assert_eq!(query(&tasks_picker, cx), "echo 40");
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            Vec::<String>::new(),
+            "New oneshot should not match any command query"
+        );
+
+        cx.dispatch_action(picker::ConfirmInput { secondary: true });
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "",
+            "Query should be reset after confirming"
+        );
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["echo 4", "another one", "example task"],
+            "No query should be added to the list, as it was submitted with secondary action (that maps to omit_history = true)"
+        );
+
+        cx.dispatch_action(Spawn::ByName {
+            task_name: "example task".to_string(),
+            reveal_target: None,
+            reveal: None,
+        });
+        let tasks_picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<TasksModal>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["echo 4", "another one", "example task"],
+        );
+    }
+
+    #[test]
+    fn test_elapsed_time_format() {
+        assert_eq!(format_elapsed_time(Duration::from_millis(900)), "0s");
+        assert_eq!(format_elapsed_time(Duration::from_secs(42)), "42s");
+        assert_eq!(format_elapsed_time(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_elapsed_time(Duration::from_secs(3720)), "1h 02m");
+    }
+
+    #[test]
+    fn test_run_stats_summary() {
+        assert_eq!(
+            run_stats_summary(&TaskRunStats {
+                run_count: 1,
+                last_duration: Duration::from_secs(5),
+                last_success: true,
+                last_exit_code: Some(0),
+            }),
+            "Ran once, last run succeeded after 5s"
+        );
+        assert_eq!(
+            run_stats_summary(&TaskRunStats {
+                run_count: 3,
+                last_duration: Duration::from_secs(75),
+                last_success: false,
+                last_exit_code: Some(101),
+            }),
+            format!(
+                "Ran 3 times, last run failed with exit code 101 after {}",
+                format_elapsed_time(Duration::from_secs(75))
+            )
+        );
+    }
+
+    #[test]
+    fn test_task_source_description() {
+        let worktree = WorktreeId::from_usize(1);
+        assert_eq!(
+            task_source_description(&TaskSourceKind::Worktree {
+                id: worktree,
+                directory_in_worktree: PathBuf::from(".zed"),
+                id_base: "local worktree tasks".into(),
+            }),
+            Path::new(".zed").join("tasks.json").display().to_string()
+        );
+        assert_eq!(
+            task_source_description(&TaskSourceKind::DetectedScript {
+                id: worktree,
+                package_manager: project::PackageManager::Pnpm,
+            }),
+            "package.json (pnpm)"
+        );
+        assert_eq!(
+            task_source_description(&TaskSourceKind::Language {
+                name: "Rust".into()
+            }),
+            "Rust language tasks"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_task_tag_filtering(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build release",
+                            "command": "cargo",
+                            "args": ["build", "--release"],
+                            "tags": ["build"]
+                        },
+                        {
+                            "label": "test all",
+                            "command": "cargo",
+                            "args": ["test"],
+                            "tags": ["test", "Build"]
+                        },
+                        {
+                            "label": "lint",
+                            "command": "cargo",
+                            "args": ["clippy"]
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        cx.simulate_input("#build");
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["build release", "test all"],
+            "Tags should be matched regardless of their case"
+        );
+
+        cx.simulate_input(" tes");
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["test all"],
+            "The rest of the query should be fuzzy matched among the tagged tasks"
+        );
+
+        tasks_picker.update(cx, |picker, cx| picker.set_query("#te #bu", cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["test all"],
+            "Partially typed tags should match, and all of them are required"
+        );
+
+        tasks_picker.update(cx, |picker, cx| picker.set_query("#deploy", cx));
+        cx.executor().run_until_parked();
+        assert_eq!(task_names(&tasks_picker, cx), Vec::<String>::new());
+    }
+
+    #[gpui::test]
+    async fn test_pinned_tasks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build",
+                            "command": "cargo build"
+                        },
+                        {
+                            "label": "lint",
+                            "command": "cargo clippy"
+                        },
+                        {
+                            "label": "test",
+                            "command": "cargo test"
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["build", "lint", "test"]);
+        emulate_task_schedule(tasks_picker, &project, "lint", cx);
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["lint", "build", "test"]);
+        toggle_pinned(&tasks_picker, "test", cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["test", "lint", "build"],
+            "Pinned tasks should be listed above the recently used ones"
+        );
+        tasks_picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.separators_after_indices(), vec![0, 1]);
+        });
+        tasks_picker.update(cx, |_, cx| cx.emit(DismissEvent));
+        drop(tasks_picker);
+        cx.executor().run_until_parked();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["test", "lint", "build"],
+            "Pins should be kept in the inventory when the modal is reopened"
+        );
+        toggle_pinned(&tasks_picker, "test", cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["lint", "build", "test"]);
+    }
+
+    #[gpui::test]
+    async fn test_task_sort_modes(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build",
+                            "command": "cargo build"
+                        },
+                        {
+                            "label": "check $ZED_FILENAME",
+                            "command": "cargo check"
+                        },
+                        {
+                            "label": "lint $ZED_FILENAME",
+                            "command": "cargo clippy"
+                        },
+                        {
+                            "label": "test $ZED_FILENAME",
+                            "command": "cargo test"
+                        }
+                    ]"#,
+                },
+                "a.ts": "a",
+                "b.ts": "b"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        emulate_task_schedule(tasks_picker, &project, "lint a.ts", cx);
+        emulate_last_task_finished(&project, 2, cx);
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        emulate_task_schedule(tasks_picker, &project, "test a.ts", cx);
+        emulate_last_task_finished(&project, 1, cx);
+
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/b.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![
+                "test a.ts",
+                "lint a.ts",
+                "check b.ts",
+                "lint b.ts",
+                "test b.ts",
+                "build"
+            ],
+            "By default, tasks using more variables should be listed first"
+        );
+
+        cx.dispatch_action(CycleSortMode);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![
+                "test a.ts",
+                "lint a.ts",
+                "build",
+                "check b.ts",
+                "lint b.ts",
+                "test b.ts"
+            ],
+            "Alphabetical mode should only sort the tasks below the recently used ones"
+        );
+
+        cx.dispatch_action(CycleSortMode);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![
+                "test a.ts",
+                "lint a.ts",
+                "test b.ts",
+                "lint b.ts",
+                "check b.ts",
+                "build"
+            ],
+            "Recency mode should list the tasks scheduled last first"
+        );
+
+        cx.dispatch_action(CycleSortMode);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec![
+                "test a.ts",
+                "lint a.ts",
+                "lint b.ts",
+                "test b.ts",
+                "check b.ts",
+                "build"
+            ],
+            "Frequency mode should list the tasks that finished most often first"
+        );
+
+        cx.simulate_input("b.ts");
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["lint b.ts", "test b.ts", "check b.ts"],
+            "Sort mode should be applied to the fuzzy matches"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_delete_and_clear_task_history(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build",
+                            "command": "cargo build"
+                        },
+                        {
+                            "label": "check",
+                            "command": "cargo check"
+                        },
+                        {
+                            "label": "lint",
+                            "command": "cargo clippy"
+                        },
+                        {
+                            "label": "test",
+                            "command": "cargo test"
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+        for label in ["lint", "test", "check"] {
+            let tasks_picker = open_spawn_tasks(&workspace, cx);
+            emulate_task_schedule(tasks_picker, &project, label, cx);
+        }
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["check", "test", "lint", "build"]
+        );
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(menu::SelectLast);
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(DeleteFromHistory);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["lint", "build"],
+            "Marked recently used tasks should be deleted, other marked tasks should be kept"
+        );
+        tasks_picker.update(cx, |tasks_picker, _| {
+            assert_eq!(tasks_picker.delegate.marked_candidates, vec![1]);
+        });
+
+        cx.dispatch_action(ClearHistory);
+        cx.simulate_prompt_answer(1);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["lint", "build"],
+            "History should be kept when clearing it is cancelled"
+        );
+
+        cx.dispatch_action(ClearHistory);
+        cx.simulate_prompt_answer(0);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["build", "check", "lint", "test"],
+            "Open modal should list no recently used tasks after the history is cleared"
+        );
+        tasks_picker.update(cx, |tasks_picker, _| {
+            assert_eq!(tasks_picker.delegate.last_used_candidate_index, None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_tasks_reloaded_in_open_modal(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build",
+                            "command": "cargo build"
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["build"]);
+
+        fs.insert_file(
+            "/dir/.zed/tasks.json",
+            r#"[
+                {
+                    "label": "build",
+                    "command": "cargo build"
+                },
+                {
+                    "label": "test",
+                    "command": "cargo test"
+                }
+            ]"#
+            .as_bytes()
+            .to_vec(),
+        )
+        .await;
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["build", "test"],
+            "Open modal should list the tasks of the changed tasks file"
+        );
+
+        cx.dispatch_action(ReloadAll);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["build", "test"],
+            "Reloading the task files should keep the tasks that did not change"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_spawn_marked_tasks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "backend",
+                            "command": "cargo run"
+                        },
+                        {
+                            "label": "frontend",
+                            "command": "npm run dev"
+                        },
+                        {
+                            "label": "worker",
+                            "command": "cargo run --bin worker"
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["backend", "frontend", "worker"]
+        );
+        cx.dispatch_action(menu::SelectLast);
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(menu::SelectFirst);
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(ToggleMarked);
+        cx.dispatch_action(ToggleMarked);
+        tasks_picker.update(cx, |tasks_picker, _| {
+            assert_eq!(
+                tasks_picker.delegate.marked_candidates,
+                vec![2, 0],
+                "Toggling a marked task again should unmark it"
+            );
+        });
+
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["backend", "worker", "frontend"],
+            "Both marked tasks should have been spawned, in the order they were marked"
+        );
+        tasks_picker.update(cx, |tasks_picker, _| {
+            assert!(tasks_picker.delegate.marked_candidates.is_empty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_edit_task_before_run(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "unit tests",
+                            "command": "cargo",
+                            "args": ["test", "--lib"],
+                            "cwd": "$ZED_WORKTREE_ROOT/crates",
+                            "env": { "RUST_LOG": "info" }
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["unit tests"]);
+        cx.dispatch_action(EditBeforeRun);
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "cargo test --lib",
+            "Query should be set to the selected task's resolved command"
+        );
+
+        cx.simulate_input(" -- --nocapture");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let (task_source_kind, task) = project
+            .update(cx, |project, cx| {
+                project
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .unwrap()
+                    .read(cx)
+                    .last_scheduled_task(None)
+            })
+            .expect("the edited task should have been spawned");
+        assert_eq!(task_source_kind, TaskSourceKind::UserInput);
+        let resolved = task.resolved.unwrap();
+        assert_eq!(resolved.command, "cargo test --lib -- --nocapture");
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/dir/crates")));
+        assert_eq!(
+            resolved.env.get("RUST_LOG").map(String::as_str),
+            Some("info")
+        );
+
+        cx.dispatch_action(Rerun {
+            edit: true,
+            ..Rerun::default()
+        });
+        cx.executor().run_until_parked();
+        let tasks_picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<TasksModal>(cx)
+                .expect("no task modal after `Rerun` with editing was dispatched")
+                .read(cx)
+                .picker
+                .clone()
+        });
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "cargo test --lib -- --nocapture",
+            "Query should be set to the last scheduled task's resolved command"
+        );
+        cx.simulate_input(" --test-threads 1");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let (_, task) = project
+            .update(cx, |project, cx| {
+                project
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .unwrap()
+                    .read(cx)
+                    .last_scheduled_task(None)
+            })
+            .expect("the edited rerun should have been spawned");
+        let resolved = task.resolved.unwrap();
+        assert_eq!(
+            resolved.command,
+            "cargo test --lib -- --nocapture --test-threads 1"
+        );
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/dir/crates")));
+    }
+
+    #[gpui::test]
+    async fn test_edit_task_quotes_arguments(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "filtered tests",
+                            "command": "cargo test",
+                            "args": ["--", "two words", "a;b"],
+                            "env": { "RUST_LOG": "info" }
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["filtered tests"]);
+        cx.dispatch_action(EditBeforeRun);
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "cargo test -- 'two words' 'a;b'",
+            "Arguments with spaces or shell syntax should be quoted in the query"
+        );
+        tasks_picker.update(cx, |tasks_picker, _| {
+            let edited_task = tasks_picker.delegate.edited_task.as_ref().unwrap();
+            assert_eq!(
+                edited_task.env.keys().collect::<Vec<_>>(),
+                vec!["RUST_LOG"],
+                "Context variables should not be copied into the edited task's environment"
+            );
+        });
+
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let (_, task) = project
+            .update(cx, |project, cx| {
+                project
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .unwrap()
+                    .read(cx)
+                    .last_scheduled_task(None)
+            })
+            .expect("the edited task should have been spawned");
+        let resolved = task.resolved.unwrap();
+        assert_eq!(resolved.command, "cargo test -- 'two words' 'a;b'");
+        assert_eq!(
+            resolved.env.get("ZED_FILE").map(String::as_str),
+            Some("/dir/a.ts"),
+            "The edited task should be resolved against the current context"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_edit_rerun_quotes_arguments(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "filtered tests",
+                            "command": "cargo test",
+                            "args": ["--", "two words"],
+                            "env": { "RUST_LOG": "info" }
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let _tasks_picker = open_spawn_tasks(&workspace, cx);
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+
+        cx.dispatch_action(Rerun {
+            edit: true,
+            ..Rerun::default()
+        });
+        cx.executor().run_until_parked();
+        let tasks_picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<TasksModal>(cx)
+                .expect("no task modal after `Rerun` with editing was dispatched")
+                .read(cx)
+                .picker
+                .clone()
+        });
+        assert_eq!(
+            query(&tasks_picker, cx),
+            "cargo test -- 'two words'",
+            "Arguments of the rerun task should be quoted in the query"
+        );
+        tasks_picker.update(cx, |tasks_picker, _| {
+            let edited_task = tasks_picker.delegate.edited_task.as_ref().unwrap();
+            assert_eq!(
+                edited_task.env.keys().collect::<Vec<_>>(),
+                vec!["RUST_LOG"],
+                "Context variables should not be copied into the edited task's environment"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_inputs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "deploy",
+                            "command": "./deploy.sh",
+                            "inputs": [
+                                { "env": "DEPLOY_TOKEN", "description": "API token", "type": "password" },
+                                { "env": "TARGET" }
+                            ]
+                        }
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned = spawned.clone();
+            cx.subscribe(&workspace, move |_, event: &workspace::Event, _| {
+                if let workspace::Event::SpawnTask { action } = event {
+                    spawned.borrow_mut().push(action.env.clone());
+                }
+            })
+            .detach();
+        });
+        let is_typing_password = |tasks_picker: &View<Picker<TasksModalDelegate>>,
+                                  cx: &mut VisualTestContext| {
+            tasks_picker.update(cx, |picker, _| picker.delegate.is_typing_password())
+        };
+
         let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["deploy"]);
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        assert!(is_typing_password(&tasks_picker, cx));
+        cx.simulate_input("secret");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        assert!(!is_typing_password(&tasks_picker, cx));
         assert_eq!(
             query(&tasks_picker, cx),
             "",
-            "Query should be reset after confirming"
-        );
-        assert_eq!(
-            task_names(&tasks_picker, cx),
-            vec![query_str, "another one", "example task"],
-            "Last recently used one show task should be listed first"
+            "Query should be cleared after each input"
         );
+        cx.simulate_input("staging");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
 
-        cx.dispatch_action(picker::ConfirmCompletion);
+        let env = spawned
+            .borrow_mut()
+            .pop()
+            .expect("the task should have been spawned");
+        assert_eq!(env.get("DEPLOY_TOKEN").map(String::as_str), Some("secret"));
+        assert_eq!(env.get("TARGET").map(String::as_str), Some("staging"));
+        let (_, task) = project
+            .update(cx, |project, cx| {
+                project
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .unwrap()
+                    .read(cx)
+                    .last_scheduled_task(None)
+            })
+            .expect("the task should be in the history");
+        let history_env = task.resolved.unwrap().env;
         assert_eq!(
-            query(&tasks_picker, cx),
-            query_str,
-            "Query should be set to the custom task's name"
+            history_env.get("DEPLOY_TOKEN"),
+            None,
+            "Password inputs should never be kept in the task history"
         );
         assert_eq!(
-            task_names(&tasks_picker, cx),
-            vec![query_str],
-            "Only custom task should be listed"
+            history_env.get("TARGET").map(String::as_str),
+            Some("staging")
         );
 
-        let query_str = "0";
-        cx.simulate_input(query_str);
-        assert_eq!(query(&tasks_picker, cx), "echo 40");
-        assert_eq!(
-            task_names(&tasks_picker, cx),
-            Vec::<String>::new(),
-            "New oneshot should not match any command query"
+        cx.dispatch_action(Rerun::default());
+        cx.executor().run_until_parked();
+        assert!(
+            spawned.borrow().is_empty(),
+            "Rerun should wait for the password to be typed in again"
         );
-
-        cx.dispatch_action(picker::ConfirmInput { secondary: true });
-        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        let tasks_picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<TasksModal>(cx)
+                .expect("no task modal to type the password in")
+                .read(cx)
+                .picker
+                .clone()
+        });
+        assert!(is_typing_password(&tasks_picker, cx));
+        cx.simulate_input("another secret");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let env = spawned
+            .borrow_mut()
+            .pop()
+            .expect("the task should have been rerun");
         assert_eq!(
-            query(&tasks_picker, cx),
-            "",
-            "Query should be reset after confirming"
+            env.get("DEPLOY_TOKEN").map(String::as_str),
+            Some("another secret")
         );
         assert_eq!(
-            task_names(&tasks_picker, cx),
-            vec!["echo 4", "another one", "example task"],
-            "No query should be added to the list, as it was submitted with secondary action (that maps to omit_history = true)"
+            env.get("TARGET").map(String::as_str),
+            Some("staging"),
+            "Text inputs should be reused on reruns"
         );
 
         cx.dispatch_action(Spawn::ByName {
-            task_name: "example task".to_string(),
+            task_name: "deploy".to_string(),
             reveal_target: None,
+            reveal: None,
         });
+        cx.executor().run_until_parked();
+        assert!(
+            spawned.borrow().is_empty(),
+            "Spawning by name should wait for the inputs to be typed in"
+        );
         let tasks_picker = workspace.update(cx, |workspace, cx| {
             workspace
                 .active_modal::<TasksModal>(cx)
-                .unwrap()
+                .expect("no task modal to type the inputs in")
                 .read(cx)
                 .picker
                 .clone()
         });
+        assert!(is_typing_password(&tasks_picker, cx));
+        cx.simulate_input("third secret");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        assert!(!is_typing_password(&tasks_picker, cx));
+        cx.simulate_input("production");
+        cx.dispatch_action(menu::Confirm);
+        cx.executor().run_until_parked();
+        let env = spawned
+            .borrow_mut()
+            .pop()
+            .expect("the task should have been spawned by name");
         assert_eq!(
-            task_names(&tasks_picker, cx),
-            vec!["echo 4", "another one", "example task"],
+            env.get("DEPLOY_TOKEN").map(String::as_str),
+            Some("third secret")
         );
+        assert_eq!(env.get("TARGET").map(String::as_str), Some("production"));
+    }
+
+    #[gpui::test]
+    async fn test_open_task_definition(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let tasks_json = r#"[
+    {
+        "label": "build",
+        "command": "cargo build"
+    },
+    {
+        "label": "lint",
+        "command": "cargo clippy"
+    }
+]"#;
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": tasks_json,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        cx.simulate_input("lint");
+        assert_eq!(task_names(&tasks_picker, cx), vec!["lint"]);
+        cx.dispatch_action(OpenDefinition);
+        cx.executor().run_until_parked();
+
+        workspace.update(cx, |workspace, cx| {
+            assert!(
+                workspace.active_modal::<TasksModal>(cx).is_none(),
+                "Opening the definition should dismiss the modal"
+            );
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let editor = editor.read(cx);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap().read(cx);
+            assert_eq!(
+                buffer.file().unwrap().path().as_ref(),
+                Path::new(".zed/tasks.json")
+            );
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            assert_eq!(Some(cursor), tasks_json.find(r#""lint""#));
+        });
     }
 
     #[gpui::test]
@@ -1002,6 +3705,109 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_language_tasks_from_settings(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.rs": "// a" })).await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(
+                Language::new(
+                    LanguageConfig {
+                        name: "Rust".into(),
+                        matcher: LanguageMatcher {
+                            path_suffixes: vec!["rs".to_string()],
+                            ..LanguageMatcher::default()
+                        },
+                        ..LanguageConfig::default()
+                    },
+                    None,
+                )
+                .with_context_provider(Some(Arc::new(
+                    ContextProviderWithTasks::new(TaskTemplates(vec![
+                        TaskTemplate {
+                            label: "check".to_string(),
+                            command: "cargo check".into(),
+                            ..TaskTemplate::default()
+                        },
+                        TaskTemplate {
+                            label: "test".to_string(),
+                            command: "cargo test".into(),
+                            ..TaskTemplate::default()
+                        },
+                    ])),
+                ))),
+            ));
+        });
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.languages.insert(
+                        "Rust".into(),
+                        LanguageSettingsContent {
+                            tasks: Some(LanguageTaskConfig {
+                                variables: HashMap::default(),
+                                templates: vec![
+                                    TaskTemplate {
+                                        label: "check".to_string(),
+                                        command: "cargo clippy".into(),
+                                        ..TaskTemplate::default()
+                                    },
+                                    TaskTemplate {
+                                        label: "doc".to_string(),
+                                        command: "cargo doc".into(),
+                                        ..TaskTemplate::default()
+                                    },
+                                ],
+                            }),
+                            ..LanguageSettingsContent::default()
+                        },
+                    );
+                });
+            });
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.rs"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(task_names(&tasks_picker, cx), vec!["check", "doc", "test"]);
+        tasks_picker.update(cx, |picker, _| {
+            let candidates = picker.delegate.candidates.as_ref().unwrap();
+            let (check_kind, check) = candidates
+                .iter()
+                .find(|(_, task)| task.resolved_label == "check")
+                .unwrap();
+            assert_eq!(
+                check.resolved.as_ref().unwrap().command,
+                "cargo clippy",
+                "Tasks from the language settings should replace the provided ones with the same labels"
+            );
+            assert_eq!(
+                check_kind,
+                &TaskSourceKind::LanguageSettings {
+                    name: "Rust".into()
+                }
+            );
+            let (test_kind, _) = candidates
+                .iter()
+                .find(|(_, task)| task.resolved_label == "test")
+                .unwrap();
+            assert_eq!(
+                test_kind,
+                &TaskSourceKind::Language {
+                    name: "Rust".into()
+                }
+            );
+        });
+    }
+
     fn emulate_task_schedule(
         tasks_picker: View<Picker<TasksModalDelegate>>,
         project: &Model<Project>,
@@ -1033,6 +3839,42 @@ mod tests {
         cx.executor().run_until_parked()
     }
 
+    fn emulate_last_task_finished(
+        project: &Model<Project>,
+        run_count: usize,
+        cx: &mut VisualTestContext,
+    ) {
+        project.update(cx, |project, cx| {
+            if let Some(task_inventory) = project.task_store().read(cx).task_inventory().cloned() {
+                task_inventory.update(cx, |inventory, _| {
+                    let (_, task) = inventory.last_scheduled_task(None).unwrap();
+                    for _ in 0..run_count {
+                        inventory.task_finished(&task.id, Some(0), true, Duration::from_secs(1));
+                    }
+                });
+            }
+        });
+    }
+
+    fn toggle_pinned(
+        tasks_picker: &View<Picker<TasksModalDelegate>>,
+        label: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        tasks_picker.update(cx, |tasks_picker, cx| {
+            let ix = tasks_picker
+                .delegate
+                .candidates
+                .iter()
+                .flatten()
+                .position(|(_, task)| task.resolved_label == label)
+                .unwrap();
+            tasks_picker.delegate.toggle_pinned(ix, cx);
+            tasks_picker.refresh(cx);
+        });
+        cx.executor().run_until_parked();
+    }
+
     fn open_spawn_tasks(
         workspace: &View<Workspace>,
         cx: &mut VisualTestContext,
@@ -1052,6 +3894,125 @@ mod tests {
         spawn_tasks.update(cx, |spawn_tasks, cx| spawn_tasks.query(cx))
     }
 
+    #[gpui::test]
+    async fn test_group_defaults(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {
+                            "label": "build debug",
+                            "command": "make",
+                            "group": "build"
+                        },
+                        {
+                            "label": "build release",
+                            "command": "make release",
+                            "group": "build"
+                        },
+                        {
+                            "label": "run tests",
+                            "command": "make test",
+                            "group": "test"
+                        },
+                    ]"#,
+                },
+                "a.ts": "a"
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let _ = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/a.ts"), true, cx)
+            })
+            .await
+            .unwrap();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            group_default_names(&tasks_picker, cx),
+            vec!["build debug", "run tests"],
+            "The first task of each group should be its default one"
+        );
+        emulate_task_schedule(tasks_picker, &project, "build release", cx);
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["build release", "build debug", "run tests"],
+            "The recently run task should be listed first"
+        );
+        assert_eq!(
+            group_default_names(&tasks_picker, cx),
+            vec!["build debug", "run tests"],
+            "Running a task should not make it the default one of its group"
+        );
+        assert_eq!(
+            group_default_label(&workspace, TaskGroup::Build, cx).as_deref(),
+            Some("build debug"),
+            "The group action should spawn the task marked as the default one"
+        );
+
+        tasks_picker.update(cx, |picker, cx| {
+            let ix = picker
+                .delegate
+                .candidates
+                .iter()
+                .flatten()
+                .position(|(_, task)| task.resolved_label == "build release")
+                .unwrap();
+            picker.delegate.set_group_default(ix, cx);
+        });
+        assert_eq!(
+            group_default_names(&tasks_picker, cx),
+            vec!["build release", "run tests"],
+            "The task set as the default one should be marked"
+        );
+        assert_eq!(
+            group_default_label(&workspace, TaskGroup::Build, cx).as_deref(),
+            Some("build release"),
+            "The group action should spawn the task set as the default one"
+        );
+        assert_eq!(
+            group_default_label(&workspace, TaskGroup::Test, cx).as_deref(),
+            Some("run tests"),
+            "Other groups should keep their default task"
+        );
+    }
+
+    fn group_default_names(
+        spawn_tasks: &View<Picker<TasksModalDelegate>>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<String> {
+        spawn_tasks.update(cx, |spawn_tasks, _| {
+            let candidates = spawn_tasks.delegate.candidates.as_ref().unwrap();
+            let mut names = spawn_tasks
+                .delegate
+                .group_default_candidates
+                .iter()
+                .map(|&ix| candidates[ix].1.resolved_label.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        })
+    }
+
+    fn group_default_label(
+        workspace: &View<Workspace>,
+        group: TaskGroup,
+        cx: &mut VisualTestContext,
+    ) -> Option<String> {
+        workspace.update(cx, |workspace, cx| {
+            crate::group_default_task(workspace, group, cx).map(|(_, template)| template.label)
+        })
+    }
+
     fn task_names(
         spawn_tasks: &View<Picker<TasksModalDelegate>>,
         cx: &mut VisualTestContext,
@@ -0,0 +1,92 @@
+use std::{mem, path::PathBuf};
+
+use gpui::{Model, ViewContext, WindowContext};
+use project::TaskSourceKind;
+use task::{TaskContext, TaskTemplate, TaskVariables, VariableName};
+use workspace::{Workspace, WORKSPACE_DB};
+
+/// The oneshot tasks of earlier sessions that were not restored yet, with the root of the worktree
+/// each was spawned in and its working directory.
+type PendingOneshotTasks = Vec<(PathBuf, String, PathBuf)>;
+
+/// Restores the oneshot tasks spawned in the workspace's earlier sessions into the task history,
+/// as the worktrees they were spawned in get added to its project.
+pub(crate) fn restore(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    if workspace.project().read(cx).is_via_collab() {
+        return;
+    }
+
+    cx.spawn(|workspace, mut cx| async move {
+        let oneshot_tasks = WORKSPACE_DB.oneshot_tasks(workspace_id).await?;
+        if oneshot_tasks.is_empty() {
+            return Ok(());
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            let pending = cx.new_model(|_| oneshot_tasks);
+            restore_pending(workspace, &pending, cx);
+            cx.subscribe(workspace.project(), move |workspace, _, event, cx| {
+                if let project::Event::WorktreeAdded(_) = event {
+                    restore_pending(workspace, &pending, cx);
+                }
+            })
+            .detach();
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn restore_pending(
+    workspace: &Workspace,
+    pending: &Model<PendingOneshotTasks>,
+    cx: &mut WindowContext,
+) {
+    let project = workspace.project().read(cx);
+    let Some(inventory) = project.task_store().read(cx).task_inventory().cloned() else {
+        return;
+    };
+    let worktree_root_paths = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path())
+        .collect::<Vec<_>>();
+    let restored = pending.update(cx, |pending, _| {
+        let (restored, still_pending) =
+            mem::take(pending)
+                .into_iter()
+                .partition::<Vec<_>, _>(|(worktree_root_path, _, _)| {
+                    worktree_root_paths
+                        .iter()
+                        .any(|root_path| root_path.as_ref() == worktree_root_path.as_path())
+                });
+        *pending = still_pending;
+        restored
+    });
+    if restored.is_empty() {
+        return;
+    }
+
+    let id_base = TaskSourceKind::UserInput.to_id_base();
+    let tasks = restored
+        .into_iter()
+        .filter_map(|(worktree_root_path, command, cwd)| {
+            let template = TaskTemplate {
+                label: command.clone(),
+                command,
+                ..TaskTemplate::default()
+            };
+            let task_context = TaskContext {
+                cwd: Some(cwd),
+                task_variables: TaskVariables::from_iter([(
+                    VariableName::WorktreeRoot,
+                    worktree_root_path.to_string_lossy().into_owned(),
+                )]),
+                ..TaskContext::default()
+            };
+            let resolved_task = template.resolve_task(&id_base, &task_context)?;
+            Some((TaskSourceKind::UserInput, resolved_task))
+        })
+        .collect();
+    inventory.update(cx, |inventory, _| inventory.restore_scheduled_tasks(tasks));
+}
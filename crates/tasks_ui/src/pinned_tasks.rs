@@ -0,0 +1,88 @@
+use std::{mem, path::PathBuf};
+
+use collections::HashMap;
+use gpui::{Model, ViewContext, WindowContext};
+use workspace::{Workspace, WORKSPACE_DB};
+
+/// The tasks pinned in earlier sessions that were not restored yet, with the root of the worktree
+/// each was pinned for, the name of its source and its template label.
+type PendingPinnedTasks = Vec<(Option<PathBuf>, String, String)>;
+
+/// Restores the tasks pinned in the workspace's earlier sessions into the inventory,
+/// as the worktrees they were pinned for get added to its project.
+pub(crate) fn restore(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    if workspace.project().read(cx).is_via_collab() {
+        return;
+    }
+
+    cx.spawn(|workspace, mut cx| async move {
+        let pinned_tasks = WORKSPACE_DB.pinned_tasks(workspace_id).await?;
+        if pinned_tasks.is_empty() {
+            return Ok(());
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            let pending = cx.new_model(|_| pinned_tasks);
+            restore_pending(workspace, &pending, cx);
+            cx.subscribe(workspace.project(), move |workspace, _, event, cx| {
+                if let project::Event::WorktreeAdded(_) = event {
+                    restore_pending(workspace, &pending, cx);
+                }
+            })
+            .detach();
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn restore_pending(
+    workspace: &Workspace,
+    pending: &Model<PendingPinnedTasks>,
+    cx: &mut WindowContext,
+) {
+    let project = workspace.project().read(cx);
+    let Some(inventory) = project.task_store().read(cx).task_inventory().cloned() else {
+        return;
+    };
+    let worktrees = project
+        .visible_worktrees(cx)
+        .map(|worktree| {
+            let worktree = worktree.read(cx);
+            (worktree.abs_path(), worktree.id())
+        })
+        .collect::<Vec<_>>();
+    let restored = pending.update(cx, |pending, _| {
+        let mut restored = HashMap::<_, Vec<_>>::default();
+        pending.retain_mut(|(worktree_root_path, source_name, label)| {
+            let worktree = match worktree_root_path {
+                Some(worktree_root_path) => {
+                    let Some((_, worktree_id)) = worktrees
+                        .iter()
+                        .find(|(root_path, _)| root_path.as_ref() == worktree_root_path.as_path())
+                    else {
+                        return true;
+                    };
+                    Some(*worktree_id)
+                }
+                None => None,
+            };
+            restored
+                .entry(worktree)
+                .or_default()
+                .push((mem::take(source_name), mem::take(label)));
+            false
+        });
+        restored
+    });
+    if restored.is_empty() {
+        return;
+    }
+
+    inventory.update(cx, |inventory, _| {
+        for (worktree, pinned) in restored {
+            inventory.restore_pinned_tasks(worktree, pinned);
+        }
+    });
+}
@@ -5,6 +5,11 @@ use settings::{Settings, SettingsSources};
 #[derive(Serialize, Deserialize, PartialEq, Default)]
 pub(crate) struct TaskSettings {
     pub(crate) show_status_indicator: bool,
+    pub(crate) notify_on_finish: bool,
+    pub(crate) system_notification_on_finish: bool,
+    pub(crate) show_run_stats: bool,
+    pub(crate) show_preview: bool,
+    pub(crate) sort_mode: TaskSortMode,
 }
 
 /// Task-related settings.
@@ -12,6 +17,56 @@ pub(crate) struct TaskSettings {
 pub(crate) struct TaskSettingsContent {
     /// Whether to show task status indicator in the status bar. Default: true
     show_status_indicator: Option<bool>,
+    /// Whether to show a notification when a task finishes while its terminal is not focused.
+    /// Default: false
+    notify_on_finish: Option<bool>,
+    /// Whether to also show a notification of the operating system for finished tasks
+    /// while the Zed window is not active, on macOS and Linux. Default: false
+    system_notification_on_finish: Option<bool>,
+    /// Whether to show how many times each task ran and how long its last run took
+    /// next to it in the tasks modal. Default: false
+    show_run_stats: Option<bool>,
+    /// Whether the tasks modal shows the selected task's resolved command, working directory,
+    /// environment and source below the list when it opens. Default: false
+    show_preview: Option<bool>,
+    /// How the tasks that were not used recently are sorted in the tasks modal. Default: default
+    sort_mode: Option<TaskSortMode>,
+}
+
+/// How the tasks modal sorts the tasks listed below the recently used ones.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TaskSortMode {
+    /// More specific task sources and tasks using more variables first, then by label.
+    #[default]
+    Default,
+    /// By label, ignoring case.
+    Alphabetical,
+    /// Tasks run most recently first.
+    Recency,
+    /// Tasks run most often in this session first, see [`project::TaskRunStats`].
+    Frequency,
+}
+
+impl TaskSortMode {
+    /// The mode the footer toggle switches to from this one.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Alphabetical,
+            Self::Alphabetical => Self::Recency,
+            Self::Recency => Self::Frequency,
+            Self::Frequency => Self::Default,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Default => "Default Order",
+            Self::Alphabetical => "Alphabetical",
+            Self::Recency => "Recent First",
+            Self::Frequency => "Frequent First",
+        }
+    }
 }
 
 impl Settings for TaskSettings {
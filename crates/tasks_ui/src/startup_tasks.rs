@@ -0,0 +1,391 @@
+use std::{path::Path, sync::Arc};
+
+use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{AppContext, Global, Model, PromptLevel, ViewContext, WindowContext};
+use project::{Project, TaskSourceKind, WorktreeId};
+use task::{RevealStrategy, TaskContext, TaskTemplate, TaskVariables, VariableName};
+use util::ResultExt;
+use workspace::{tasks::schedule_resolved_task, Workspace};
+
+const TRUSTED_WORKTREE_KEY_PREFIX: &str = "startup_tasks_trusted";
+
+/// The `run_on_open` tasks of a workspace's project that were started already,
+/// and the worktrees the user was asked to trust.
+#[derive(Default)]
+struct StartupTasks {
+    started: HashSet<(TaskSourceKind, String)>,
+    /// Whatever the answer was, the user is not asked again until the project is reopened.
+    asked: HashSet<WorktreeId>,
+}
+
+/// Whether the worktrees are trusted to run their tasks, by their root paths,
+/// read from the database once for each.
+#[derive(Default)]
+struct TrustedWorktrees(HashMap<Arc<Path>, bool>);
+
+impl Global for TrustedWorktrees {}
+
+/// Starts the workspace project's `run_on_open` tasks, once its task files are loaded.
+pub(crate) fn start_on_open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    if project.read(cx).is_via_collab() {
+        return;
+    }
+    let Some(inventory) = project
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .cloned()
+    else {
+        return;
+    };
+
+    let startup_tasks = cx.new_model(|_| StartupTasks::default());
+    cx.observe(&inventory, {
+        let startup_tasks = startup_tasks.clone();
+        move |workspace, _, cx| start_pending_tasks(workspace, &startup_tasks, cx)
+    })
+    .detach();
+    cx.subscribe(&project, move |workspace, _, event, cx| {
+        if let project::Event::WorktreeAdded(_) = event {
+            start_pending_tasks(workspace, &startup_tasks, cx);
+        }
+    })
+    .detach();
+}
+
+fn start_pending_tasks(
+    workspace: &mut Workspace,
+    startup_tasks: &Model<StartupTasks>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let pending_tasks = pending_tasks(&project, startup_tasks, cx);
+    let mut untrusted_tasks = Vec::new();
+    for (worktree_id, task_source_kind, template) in pending_tasks {
+        let needs_trust = matches!(
+            task_source_kind,
+            TaskSourceKind::Worktree { .. } | TaskSourceKind::VsCode { .. }
+        ) && !is_trusted(&project, worktree_id, cx);
+        if needs_trust {
+            let newly_asked = startup_tasks.update(cx, |startup_tasks, _| {
+                startup_tasks.asked.insert(worktree_id)
+            });
+            if newly_asked || untrusted_tasks.iter().any(|(id, _, _)| *id == worktree_id) {
+                untrusted_tasks.push((worktree_id, task_source_kind, template));
+            }
+        } else {
+            start_task(
+                workspace,
+                startup_tasks,
+                worktree_id,
+                task_source_kind,
+                template,
+                cx,
+            );
+        }
+    }
+    if untrusted_tasks.is_empty() {
+        return;
+    }
+
+    let untrusted_worktrees = untrusted_tasks
+        .iter()
+        .map(|(worktree_id, _, _)| *worktree_id)
+        .collect::<HashSet<_>>();
+    let worktree_names = untrusted_worktrees
+        .iter()
+        .filter_map(|worktree_id| project.read(cx).worktree_for_id(*worktree_id, cx))
+        .map(|worktree| worktree.read(cx).root_name().to_string())
+        .collect::<Vec<_>>();
+    let message = format!(
+        "Do you trust {} to run tasks when it is opened?",
+        worktree_names.join(", ")
+    );
+    let detail = untrusted_tasks
+        .iter()
+        .map(|(_, _, template)| format!("{}: {}", template.label, template.command))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let answer = cx.prompt(
+        PromptLevel::Warning,
+        &message,
+        Some(&detail),
+        &["Trust and Run", "Don't Run"],
+    );
+    let startup_tasks = startup_tasks.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        if answer.await.log_err() != Some(0) {
+            return anyhow::Ok(());
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            for worktree_id in untrusted_worktrees {
+                trust(workspace.project(), worktree_id, cx);
+            }
+            for (worktree_id, task_source_kind, template) in untrusted_tasks {
+                start_task(
+                    workspace,
+                    &startup_tasks,
+                    worktree_id,
+                    task_source_kind,
+                    template,
+                    cx,
+                );
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The `run_on_open` tasks that were not started yet, with the worktree to run each in.
+/// Global tasks are run in the first worktree only.
+fn pending_tasks(
+    project: &Model<Project>,
+    startup_tasks: &Model<StartupTasks>,
+    cx: &WindowContext,
+) -> Vec<(WorktreeId, TaskSourceKind, TaskTemplate)> {
+    let project = project.read(cx);
+    let Some(inventory) = project.task_store().read(cx).task_inventory() else {
+        return Vec::new();
+    };
+    let inventory = inventory.read(cx);
+    let started = &startup_tasks.read(cx).started;
+    let mut pending_tasks = Vec::<(WorktreeId, TaskSourceKind, TaskTemplate)>::new();
+    for worktree in project.visible_worktrees(cx) {
+        let worktree_id = worktree.read(cx).id();
+        for (task_source_kind, template) in inventory.list_tasks(None, None, Some(worktree_id), cx)
+        {
+            if !template.run_on_open
+                || started.contains(&(task_source_kind.clone(), template.label.clone()))
+                || pending_tasks
+                    .iter()
+                    .any(|(_, pending_kind, pending_template)| {
+                        pending_kind == &task_source_kind
+                            && pending_template.label == template.label
+                    })
+            {
+                continue;
+            }
+            pending_tasks.push((worktree_id, task_source_kind, template));
+        }
+    }
+    pending_tasks
+}
+
+fn start_task(
+    workspace: &mut Workspace,
+    startup_tasks: &Model<StartupTasks>,
+    worktree_id: WorktreeId,
+    task_source_kind: TaskSourceKind,
+    mut template: TaskTemplate,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(worktree) = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(worktree_id, cx)
+    else {
+        return;
+    };
+    let is_new = startup_tasks.update(cx, |startup_tasks, _| {
+        startup_tasks
+            .started
+            .insert((task_source_kind.clone(), template.label.clone()))
+    });
+    if !is_new {
+        return;
+    }
+
+    // Keep the focus in the workspace that is being opened.
+    if template.reveal == RevealStrategy::Always {
+        template.reveal = RevealStrategy::NoFocus;
+    }
+    let root = worktree.read(cx).abs_path();
+    let task_context = TaskContext {
+        cwd: Some(root.to_path_buf()),
+        task_variables: TaskVariables::from_iter([(
+            VariableName::WorktreeRoot,
+            root.to_string_lossy().into_owned(),
+        )]),
+        ..TaskContext::default()
+    };
+    match template.resolve_task(&task_source_kind.to_id_base(), &task_context) {
+        Some(resolved_task) => {
+            schedule_resolved_task(workspace, task_source_kind, resolved_task, false, cx)
+        }
+        None => log::warn!(
+            "not running task {:?} on open, as its variables cannot be resolved without an open file",
+            template.label
+        ),
+    }
+}
+
+fn worktree_root(
+    project: &Model<Project>,
+    worktree_id: WorktreeId,
+    cx: &AppContext,
+) -> Option<Arc<Path>> {
+    let worktree = project.read(cx).worktree_for_id(worktree_id, cx)?;
+    Some(worktree.read(cx).abs_path())
+}
+
+fn trusted_worktree_key(worktree_root: &Path) -> String {
+    format!(
+        "{TRUSTED_WORKTREE_KEY_PREFIX}:{}",
+        worktree_root.to_string_lossy()
+    )
+}
+
+pub(crate) fn is_trusted(
+    project: &Model<Project>,
+    worktree_id: WorktreeId,
+    cx: &mut AppContext,
+) -> bool {
+    let Some(worktree_root) = worktree_root(project, worktree_id, cx) else {
+        return false;
+    };
+    *cx.default_global::<TrustedWorktrees>()
+        .0
+        .entry(worktree_root)
+        .or_insert_with_key(|worktree_root| {
+            KEY_VALUE_STORE
+                .read_kvp(&trusted_worktree_key(worktree_root))
+                .log_err()
+                .flatten()
+                .is_some()
+        })
+}
+
+pub(crate) fn trust(project: &Model<Project>, worktree_id: WorktreeId, cx: &mut WindowContext) {
+    let Some(worktree_root) = worktree_root(project, worktree_id, cx) else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(KEY_VALUE_STORE.write_kvp(trusted_worktree_key(&worktree_root), "true".to_string()))
+        .detach_and_log_err(cx);
+    cx.default_global::<TrustedWorktrees>()
+        .0
+        .insert(worktree_root, true);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use gpui::{TestAppContext, View, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+
+    use super::*;
+    use crate::tests::init_test;
+
+    const TASKS: &str = r#"[
+        { "label": "dev server", "command": "npm", "args": ["run", "dev"], "run_on_open": true },
+        { "label": "test", "command": "npm", "args": ["test"] }
+    ]"#;
+
+    #[gpui::test]
+    async fn test_running_tasks_on_open_after_trust(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/trusted", json!({ ".zed": { "tasks.json": TASKS } }))
+            .await;
+        let project = Project::test(fs, ["/trusted".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawned = spawned_tasks(&workspace, cx);
+        cx.run_until_parked();
+
+        assert!(
+            cx.has_pending_prompt(),
+            "the user should be asked to trust the worktree"
+        );
+        assert!(spawned.borrow().is_empty());
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(*spawned.borrow(), ["dev server"]);
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        assert!(cx.update(|cx| is_trusted(&project, worktree_id, cx)));
+    }
+
+    #[gpui::test]
+    async fn test_not_running_tasks_on_open_without_trust(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/untrusted", json!({ ".zed": { "tasks.json": TASKS } }))
+            .await;
+        let project = Project::test(fs.clone(), ["/untrusted".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawned = spawned_tasks(&workspace, cx);
+        cx.run_until_parked();
+
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        assert!(spawned.borrow().is_empty());
+
+        // The user is not asked again while the project stays open.
+        fs.insert_file(
+            "/untrusted/.zed/tasks.json",
+            TASKS.replace("\"test\"", "\"unit tests\"").into_bytes(),
+        )
+        .await;
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(spawned.borrow().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_running_tasks_once_per_open(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/reopened", json!({ ".zed": { "tasks.json": TASKS } }))
+            .await;
+        let project = Project::test(fs.clone(), ["/reopened".as_ref()], cx).await;
+        let (workspace, window_cx) =
+            cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawned = spawned_tasks(&workspace, window_cx);
+        window_cx.run_until_parked();
+        window_cx.simulate_prompt_answer(0);
+        window_cx.run_until_parked();
+        assert_eq!(*spawned.borrow(), ["dev server"]);
+
+        // Reloading the task files does not start the tasks again.
+        fs.insert_file(
+            "/reopened/.zed/tasks.json",
+            TASKS.replace("\"test\"", "\"unit tests\"").into_bytes(),
+        )
+        .await;
+        window_cx.run_until_parked();
+        assert_eq!(*spawned.borrow(), ["dev server"]);
+
+        // Reopening the project starts them once more, without asking for the trust again.
+        let project = Project::test(fs, ["/reopened".as_ref()], cx).await;
+        let (workspace, window_cx) =
+            cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawned = spawned_tasks(&workspace, window_cx);
+        window_cx.run_until_parked();
+        assert!(!window_cx.has_pending_prompt());
+        assert_eq!(*spawned.borrow(), ["dev server"]);
+    }
+
+    fn spawned_tasks(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Rc<RefCell<Vec<String>>> {
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned = spawned.clone();
+            cx.subscribe(workspace, move |_, event, _| {
+                if let workspace::Event::SpawnTask { action } = event {
+                    spawned.borrow_mut().push(action.label.clone());
+                }
+            })
+            .detach();
+        });
+        spawned
+    }
+}
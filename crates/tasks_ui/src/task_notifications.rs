@@ -0,0 +1,216 @@
+use anyhow::Context as _;
+use gpui::{AppContext, ViewContext};
+use project::task_store::FinishedTask;
+use settings::Settings;
+use ui::prelude::*;
+use workspace::{
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    Workspace,
+};
+use zed_actions::Rerun;
+
+use crate::{modal::format_elapsed_time, settings::TaskSettings};
+
+struct TaskFinishedNotification;
+
+/// Notifies about the tasks of the workspace's project that finish while their terminal is not focused.
+/// The tasks that were stopped, rather than finishing on their own, are not notified about.
+pub(crate) fn notify_on_finish(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    cx.subscribe(&project, |workspace, _, event, cx| {
+        if let project::Event::TaskFinished(finished_task) = event {
+            if finished_task.terminal_focused || finished_task.cancelled {
+                return;
+            }
+            let settings = TaskSettings::get_global(cx);
+            let (notify, notify_system) = (
+                settings.notify_on_finish,
+                settings.system_notification_on_finish,
+            );
+            if notify {
+                show_notification(workspace, finished_task, cx);
+            }
+            if notify_system && !cx.is_window_active() {
+                show_system_notification(finished_task, cx);
+            }
+        }
+    })
+    .detach();
+}
+
+fn finished_message(finished_task: &FinishedTask) -> String {
+    let status = match (finished_task.success, finished_task.exit_code) {
+        (true, _) => "finished successfully".to_string(),
+        (false, Some(exit_code)) => format!("failed with exit code {exit_code}"),
+        (false, None) => "failed".to_string(),
+    };
+    format!(
+        "Task '{}' {status} after {}",
+        finished_task.label,
+        format_elapsed_time(finished_task.duration)
+    )
+}
+
+fn show_notification(
+    workspace: &mut Workspace,
+    finished_task: &FinishedTask,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let message = finished_message(finished_task);
+    let task_id = finished_task.task_id.0.clone();
+    workspace.show_notification(
+        NotificationId::composite::<TaskFinishedNotification>(SharedString::from(task_id.clone())),
+        cx,
+        |cx| {
+            cx.new_view(|_| {
+                MessageNotification::new(message)
+                    .with_click_message("Rerun")
+                    .on_click(move |cx| {
+                        cx.dispatch_action(Box::new(Rerun {
+                            task_id: Some(task_id.clone()),
+                            ..Rerun::default()
+                        }))
+                    })
+            })
+        },
+    );
+}
+
+/// Shows a notification of the operating system, for the finished tasks to be noticed
+/// while working in another application: with `osascript` on macOS and `notify-send`
+/// on Linux and FreeBSD.
+fn show_system_notification(finished_task: &FinishedTask, cx: &mut AppContext) {
+    let Some(mut command) =
+        system_notification_command(&finished_task.label, &finished_message(finished_task))
+    else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(async move {
+            let output = command
+                .output()
+                .await
+                .context("running the system notification command")?;
+            anyhow::ensure!(
+                output.status.success(),
+                "system notification command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+}
+
+#[cfg(target_os = "macos")]
+fn system_notification_command(title: &str, message: &str) -> Option<smol::process::Command> {
+    let mut command = util::command::new_smol_command("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        applescript_string(message),
+        applescript_string(title)
+    ));
+    Some(command)
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+fn system_notification_command(title: &str, message: &str) -> Option<smol::process::Command> {
+    let mut command = util::command::new_smol_command("notify-send");
+    command
+        .arg("--app-name=Zed")
+        .arg("--")
+        .arg(title)
+        .arg(message);
+    Some(command)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "freebsd")))]
+fn system_notification_command(_: &str, _: &str) -> Option<smol::process::Command> {
+    None
+}
+
+/// Quotes the text as an AppleScript string literal.
+#[cfg(any(target_os = "macos", test))]
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use gpui::TestAppContext;
+    use project::Project;
+    use settings::SettingsStore;
+    use task::TaskId;
+    use workspace::Workspace;
+
+    use crate::tests::init_test;
+
+    use super::*;
+
+    fn finished_task(success: bool, exit_code: Option<i32>, cancelled: bool) -> FinishedTask {
+        FinishedTask {
+            task_id: TaskId("test".to_string()),
+            label: "cargo test".to_string(),
+            exit_code,
+            success,
+            cancelled,
+            duration: Duration::from_secs(3),
+            terminal_focused: false,
+        }
+    }
+
+    #[test]
+    fn test_finished_message() {
+        assert_eq!(
+            finished_message(&finished_task(true, Some(0), false)),
+            "Task 'cargo test' finished successfully after 3s"
+        );
+        assert_eq!(
+            finished_message(&finished_task(false, Some(101), false)),
+            "Task 'cargo test' failed with exit code 101 after 3s"
+        );
+        assert_eq!(
+            finished_message(&finished_task(false, None, false)),
+            "Task 'cargo test' failed after 3s"
+        );
+    }
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(
+            applescript_string(r#"Task 'a "b" \c'"#),
+            r#""Task 'a \"b\" \\c'""#
+        );
+    }
+
+    #[gpui::test]
+    async fn test_notify_on_finish(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let finish = |task: FinishedTask, cx: &mut gpui::VisualTestContext| {
+            project.update(cx, |_, cx| cx.emit(project::Event::TaskFinished(task)));
+            cx.run_until_parked();
+            workspace.update(cx, |workspace, _| workspace.notification_ids().len())
+        };
+
+        // Off by default
+        assert_eq!(finish(finished_task(false, Some(1), false), cx), 0);
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store
+                    .set_user_settings(r#"{ "task": { "notify_on_finish": true } }"#, cx)
+                    .unwrap();
+            })
+        });
+        // Not for the tasks that were stopped, or whose terminal is focused
+        assert_eq!(finish(finished_task(false, Some(130), true), cx), 0);
+        let mut focused_task = finished_task(true, Some(0), false);
+        focused_task.terminal_focused = true;
+        assert_eq!(finish(focused_task, cx), 0);
+
+        assert_eq!(finish(finished_task(false, Some(1), false), cx), 1);
+    }
+}
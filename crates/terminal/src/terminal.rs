@@ -13,7 +13,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -39,6 +39,7 @@ use mappings::mouse::{
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
 use pty_info::PtyProcessInfo;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use smol::channel::{Receiver, Sender};
@@ -50,7 +51,7 @@ use util::{paths::home_dir, truncate_and_trailoff};
 use std::{
     cmp::{self, min},
     fmt::Display,
-    ops::{Deref, Index, RangeInclusive},
+    ops::{Deref, Index, Range, RangeInclusive},
     path::PathBuf,
     sync::Arc,
     time::Duration,
@@ -472,7 +473,9 @@ impl TerminalBuilder {
             word_regex: RegexSearch::new(WORD_REGEX).unwrap(),
             vi_mode_enabled: false,
             is_ssh_terminal,
+            has_focus: false,
             python_venv_directory,
+            active_toolchains: Vec::new(),
         };
 
         Ok(TerminalBuilder {
@@ -622,6 +625,7 @@ pub struct Terminal {
     pub pty_info: PtyProcessInfo,
     title_override: Option<SharedString>,
     pub python_venv_directory: Option<PathBuf>,
+    active_toolchains: Vec<SharedString>,
     scroll_px: Pixels,
     next_link_id: usize,
     selection_phase: SelectionPhase,
@@ -632,6 +636,7 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    has_focus: bool,
 }
 
 pub struct TaskState {
@@ -640,10 +645,17 @@ pub struct TaskState {
     pub label: String,
     pub command_label: String,
     pub status: TaskStatus,
+    /// The exit code the task's process reported, once it finished.
+    pub exit_code: Option<i32>,
+    /// Whether the task's process was killed by Zed, as when the task is restarted,
+    /// rather than exiting on its own.
+    pub killed: bool,
     pub completion_rx: Receiver<()>,
     pub hide: HideStrategy,
     pub show_summary: bool,
     pub show_command: bool,
+    /// Patterns of the file locations in the task's output to turn into links, with `path`, `line` and `column` named groups.
+    pub file_link_patterns: Vec<Regex>,
 }
 
 /// A status of the current terminal tab's task.
@@ -894,6 +906,10 @@ impl Terminal {
                 .grid_clamp(term, Boundary::Grid);
 
                 let link = term.grid().index(point).hyperlink();
+                let task_file_link = self
+                    .task
+                    .as_ref()
+                    .and_then(|task| task_file_link_at(term, point, &task.file_link_patterns));
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
@@ -923,6 +939,8 @@ impl Terminal {
                     let url_match = min_index..=max_index;
 
                     Some((url, true, url_match))
+                } else if let Some((file_link, file_link_match)) = task_file_link {
+                    Some((file_link, false, file_link_match))
                 } else if let Some(url_match) = regex_match_at(term, point, &mut self.url_regex) {
                     let url = term.bounds_to_string(*url_match.start(), *url_match.end());
                     Some((url, true, url_match))
@@ -1364,13 +1382,39 @@ impl Terminal {
         lines
     }
 
-    pub fn focus_in(&self) {
+    /// The last lines of the terminal's content, up to the given count. Unlike [`Self::last_n_non_empty_lines`],
+    /// the blank lines between the output are kept and the rows wrapped at the terminal's width are joined back.
+    pub fn last_n_lines(&self, n: usize) -> Vec<String> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        let last_column = Column(terminal.columns().saturating_sub(1));
+        let rows = (terminal.topmost_line().0..=terminal.bottommost_line().0)
+            .rev()
+            .map(|line| {
+                let row = &terminal.grid()[Line(line)];
+                let text = row
+                    .into_iter()
+                    .filter(|cell| {
+                        !cell
+                            .flags
+                            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+                    })
+                    .map(|cell| cell.c)
+                    .collect::<String>();
+                (text, row[last_column].flags.contains(Flags::WRAPLINE))
+            });
+        last_lines_of_rows(rows, n)
+    }
+
+    pub fn focus_in(&mut self) {
+        self.has_focus = true;
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
         }
     }
 
     pub fn focus_out(&mut self) {
+        self.has_focus = false;
         self.last_mouse_position = None;
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[O".to_string());
@@ -1667,6 +1711,15 @@ impl Terminal {
             .map(|process| process.cwd.clone())
     }
 
+    /// Names of the toolchains whose environment the terminal was started with.
+    pub fn active_toolchains(&self) -> &[SharedString] {
+        &self.active_toolchains
+    }
+
+    pub fn set_active_toolchains(&mut self, toolchains: Vec<SharedString>) {
+        self.active_toolchains = toolchains;
+    }
+
     pub fn title(&self, truncate: bool) -> String {
         const MAX_CHARS: usize = 25;
         match &self.task {
@@ -1721,10 +1774,25 @@ impl Terminal {
         self.secondary_pressed && self.hovered_word
     }
 
+    /// Whether a view of this terminal is focused.
+    pub fn has_focus(&self) -> bool {
+        self.has_focus
+    }
+
     pub fn task(&self) -> Option<&TaskState> {
         self.task.as_ref()
     }
 
+    /// Kills the process of the task run in this terminal, if the task is still running.
+    pub fn kill_active_task(&mut self) {
+        if let Some(task) = &mut self.task {
+            if task.status == TaskStatus::Running {
+                task.killed = true;
+                self.pty_info.kill_current_process();
+            }
+        }
+    }
+
     pub fn wait_for_completed_task(&self, cx: &AppContext) -> Task<()> {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -1755,6 +1823,7 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        task.exit_code = error_code;
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -1859,6 +1928,70 @@ impl Drop for Terminal {
 
 impl EventEmitter<Event> for Terminal {}
 
+/// Finds a file location matching one of the task's link patterns in the grid line of the point given, covering the point.
+fn task_file_link_at<T>(
+    term: &Term<T>,
+    point: AlacPoint,
+    patterns: &[Regex],
+) -> Option<(String, Match)> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let row = &term.grid()[point.line];
+    let mut line_text = String::new();
+    // The column of each character of the line text, by its byte offset.
+    let mut char_columns = Vec::new();
+    for column in 0..term.columns() {
+        let cell = &row[Column(column)];
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        char_columns.push((line_text.len(), column));
+        line_text.push(cell.c);
+    }
+    let column_at = |offset: usize| {
+        char_columns
+            .iter()
+            .rev()
+            .find(|(char_offset, _)| *char_offset <= offset)
+            .map_or(0, |(_, column)| *column)
+    };
+    let point_offset = char_columns
+        .iter()
+        .find(|(_, column)| *column >= point.column.0)
+        .map(|(offset, _)| *offset)?;
+    let (file_link, range) = task_file_link(&line_text, point_offset, patterns)?;
+    let file_link_match = Match::new(
+        AlacPoint::new(point.line, Column(column_at(range.start))),
+        AlacPoint::new(point.line, Column(column_at(range.end.saturating_sub(1)))),
+    );
+    Some((file_link, file_link_match))
+}
+
+/// Finds a file location matching one of the patterns in the text, covering the byte offset given.
+/// The pattern's `path` group captures the file path, the optional `line` and `column` groups its position.
+/// Returns the location in the `path:line:column` form, along with the byte range of the whole match.
+fn task_file_link(text: &str, offset: usize, patterns: &[Regex]) -> Option<(String, Range<usize>)> {
+    patterns.iter().find_map(|pattern| {
+        pattern.captures_iter(text).find_map(|captures| {
+            let whole_match = captures.get(0)?;
+            if !whole_match.range().contains(&offset) {
+                return None;
+            }
+            let mut file_link = captures.name("path")?.as_str().to_string();
+            if let Some(line) = captures.name("line") {
+                file_link.push(':');
+                file_link.push_str(line.as_str());
+                if let Some(column) = captures.name("column") {
+                    file_link.push(':');
+                    file_link.push_str(column.as_str());
+                }
+            }
+            Some((file_link, whole_match.range()))
+        })
+    })
+}
+
 /// Based on alacritty/src/display/hint.rs > regex_match_at
 /// Retrieve the match, if the specified point is inside the content matching the regex.
 fn regex_match_at<T>(term: &Term<T>, point: AlacPoint, regex: &mut RegexSearch) -> Option<Match> {
@@ -1990,6 +2123,39 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
     .into()
 }
 
+/// Joins the terminal's rows, given from the bottom up along with whether each one wraps into the row below it,
+/// back into the last `n` lines, leaving out the blank lines at the bottom.
+fn last_lines_of_rows(rows: impl Iterator<Item = (String, bool)>, n: usize) -> Vec<String> {
+    fn push_line(lines: &mut Vec<String>, line: &str) {
+        let line = line.trim_end();
+        if !line.is_empty() || !lines.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    let mut lines = Vec::new();
+    // The line that the rows below the current one make up.
+    let mut line_below = None::<String>;
+    for (row, wraps) in rows {
+        if lines.len() == n {
+            break;
+        }
+        line_below = Some(match line_below.take() {
+            Some(line_below) if wraps => row + &line_below,
+            Some(line_below) => {
+                push_line(&mut lines, &line_below);
+                row
+            }
+            None => row,
+        });
+    }
+    if let Some(line_below) = line_below.filter(|_| lines.len() < n) {
+        push_line(&mut lines, &line_below);
+    }
+    lines.reverse();
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
@@ -1999,8 +2165,11 @@ mod tests {
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
+    use regex::Regex;
+
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, last_lines_of_rows, rgb_for_index, task_file_link, IndexedCell,
+        TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -2178,4 +2347,52 @@ mod tests {
             vec!["Main.cs:20:5:Error", "desc"],
         );
     }
+
+    #[test]
+    fn test_task_file_link() {
+        let patterns = [
+            Regex::new(r"(?<path>[\w./]+)\((?<line>\d+),(?<column>\d+)\)").unwrap(),
+            Regex::new(r"--> (?<path>[\w./]+) line (?<line>\d+)").unwrap(),
+        ];
+        let text = "error: src/main.rs(12,5) and --> lib/util.rs line 7";
+        assert_eq!(
+            task_file_link(text, 10, &patterns),
+            Some(("src/main.rs:12:5".to_string(), 7..24))
+        );
+        assert_eq!(
+            task_file_link(text, 33, &patterns),
+            Some(("lib/util.rs:7".to_string(), 29..51))
+        );
+        assert_eq!(
+            task_file_link(text, 2, &patterns),
+            None,
+            "Text outside of the matches should not be linked"
+        );
+        assert_eq!(task_file_link(text, 10, &[]), None);
+    }
+
+    #[test]
+    fn test_last_lines_of_rows() {
+        // From the bottom up, with whether each row wraps into the one below it.
+        let rows = || {
+            [
+                ("     ", false),
+                ("     ", false),
+                ("$    ", false),
+                ("world", false),
+                ("hello", true),
+                ("     ", false),
+                ("$ ls ", false),
+            ]
+            .into_iter()
+            .map(|(row, wraps)| (row.to_string(), wraps))
+        };
+        assert_eq!(
+            last_lines_of_rows(rows(), 10),
+            vec!["$ ls", "", "helloworld", "$"],
+            "Wrapped rows should be joined, and only the blank lines at the bottom left out"
+        );
+        assert_eq!(last_lines_of_rows(rows(), 2), vec!["helloworld", "$"]);
+        assert_eq!(last_lines_of_rows(rows(), 0), Vec::<String>::new());
+    }
 }
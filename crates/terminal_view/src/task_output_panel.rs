@@ -0,0 +1,505 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use editor::{Editor, MultiBuffer};
+use gpui::{
+    actions, Action, AppContext, AsyncWindowContext, Context, EntityId, EventEmitter, FocusHandle,
+    FocusableView, IntoElement, Model, ParentElement, Pixels, Render, Styled, Subscription, Task,
+    View, ViewContext, VisualContext, WeakModel, WeakView, WindowContext,
+};
+use language::Buffer;
+use project::Project;
+use search::{buffer_search::DivRegistrar, BufferSearchBar};
+use task::TaskId;
+use terminal::Terminal;
+use ui::{prelude::*, Tooltip};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    item::ItemHandle,
+    pane, Pane, Workspace,
+};
+use zed_actions::RevealTaskOutput;
+
+use crate::TerminalView;
+
+actions!(task_output_panel, [ToggleFocus, ClearOutput]);
+
+/// How many runs are kept in the panel, the oldest ones are dropped first.
+const MAX_TASK_RUNS: usize = 10;
+
+/// How many of the last lines of a finished run's output are kept.
+const MAX_OUTPUT_LINES: usize = 10_000;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<TaskOutputPanel>(cx);
+            })
+            .register_action(|workspace, action: &RevealTaskOutput, cx| {
+                let task_id = action.task_id.clone().map(TaskId);
+                if let Some(panel) = workspace.focus_panel::<TaskOutputPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.reveal(task_id.as_ref(), cx));
+                }
+            });
+    })
+    .detach();
+}
+
+/// Keeps the output of the recent task runs of the project, each in its own tab,
+/// for as long as the panel shows it, even after the task's terminal tab was closed.
+///
+/// A running task is shown in its terminal; once it finishes, the terminal is replaced with
+/// a read-only copy of its output, so that the panel does not keep the terminal alive.
+pub struct TaskOutputPanel {
+    pane: View<Pane>,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    position: DockPosition,
+    size: Option<Pixels>,
+    /// The terminals of all runs added so far, so that closed runs are not added again.
+    added_terminals: HashSet<EntityId>,
+    /// The task of each run, by the id of its tab.
+    run_tasks: HashMap<EntityId, TaskId>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TaskOutputPanel {
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let workspace = workspace.weak_handle();
+                cx.new_view(|cx| Self::new(workspace, project, cx))
+            })
+        })
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let pane = cx.new_view(|cx| {
+            let mut pane = Pane::new(
+                workspace.clone(),
+                project.clone(),
+                Default::default(),
+                Some(Arc::new(|_, _| false)),
+                ToggleFocus.boxed_clone(),
+                cx,
+            );
+            pane.set_can_split(Some(Arc::new(|_, _, _| false)));
+            pane.set_can_navigate(false, cx);
+            pane.display_nav_history_buttons(None);
+            pane.set_should_display_tab_bar(|_| true);
+            pane.set_zoom_out_on_close(false);
+            pane.set_render_tab_bar_buttons(cx, |pane, cx| {
+                if pane.items_len() == 0 {
+                    return (None, None);
+                }
+                let clear_button = IconButton::new("clear-task-output", IconName::Eraser)
+                    .icon_size(IconSize::Small)
+                    .on_click(|_, cx| cx.dispatch_action(ClearOutput.boxed_clone()))
+                    .tooltip(|cx| Tooltip::for_action("Clear Output", &ClearOutput, cx));
+                (None, Some(clear_button.into_any_element()))
+            });
+            let buffer_search_bar = cx.new_view(BufferSearchBar::new);
+            pane.toolbar()
+                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+            pane
+        });
+        let task_store = project.read(cx).task_store().clone();
+        let _subscriptions = vec![
+            cx.observe(&task_store, |panel, _, cx| panel.add_new_runs(cx)),
+            cx.subscribe(&pane, Self::handle_pane_event),
+        ];
+        let mut panel = Self {
+            pane,
+            project,
+            workspace,
+            position: DockPosition::Bottom,
+            size: None,
+            added_terminals: HashSet::default(),
+            run_tasks: HashMap::default(),
+            _subscriptions,
+        };
+        panel.add_new_runs(cx);
+        panel
+    }
+
+    /// Adds a tab for each task that started running since the last check.
+    fn add_new_runs(&mut self, cx: &mut ViewContext<Self>) {
+        let new_terminals = self
+            .project
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .running_tasks()
+            .filter_map(|(_, running_task)| running_task.terminal.upgrade())
+            .filter(|terminal| !self.added_terminals.contains(&terminal.entity_id()))
+            .collect::<Vec<_>>();
+        for terminal in new_terminals {
+            self.add_run(terminal, cx);
+        }
+    }
+
+    fn add_run(&mut self, terminal: Model<Terminal>, cx: &mut ViewContext<Self>) {
+        self.added_terminals.insert(terminal.entity_id());
+        let Some(task_id) = terminal.read(cx).task().map(|task| task.id.clone()) else {
+            return;
+        };
+        let completed = terminal.read(cx).wait_for_completed_task(cx);
+        let weak_terminal = terminal.downgrade();
+        let workspace = self.workspace.clone();
+        let project = self.project.downgrade();
+        let terminal_view =
+            cx.new_view(|cx| TerminalView::new(terminal, workspace, None, project, cx));
+        let terminal_view_id = terminal_view.entity_id();
+        self.add_item(Box::new(terminal_view), task_id, cx);
+
+        cx.spawn(|panel, mut cx| async move {
+            completed.await;
+            panel.update(&mut cx, |panel, cx| {
+                panel.replace_with_output(terminal_view_id, weak_terminal, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the terminal of a finished run with a copy of its output.
+    fn replace_with_output(
+        &mut self,
+        terminal_view_id: EntityId,
+        terminal: WeakModel<Terminal>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((label, output)) = terminal.upgrade().and_then(|terminal| {
+            let terminal = terminal.read(cx);
+            let label = terminal.task()?.full_label.clone();
+            Some((label, terminal.last_n_lines(MAX_OUTPUT_LINES).join("\n")))
+        }) else {
+            return;
+        };
+        self.replace_with_output_view(terminal_view_id, label, output, cx);
+    }
+
+    /// Replaces the tab of a run with a read-only view of the given output, keeping its position.
+    fn replace_with_output_view(
+        &mut self,
+        item_id: EntityId,
+        label: String,
+        output: String,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(task_id) = self.run_tasks.get(&item_id).cloned() else {
+            return;
+        };
+        let (index, was_active) = {
+            let pane = self.pane.read(cx);
+            let Some(index) = pane.items().position(|item| item.item_id() == item_id) else {
+                return;
+            };
+            (index, pane.active_item_index() == index)
+        };
+        let output_view = Self::output_view(label, output, cx);
+        self.run_tasks.remove(&item_id);
+        self.run_tasks.insert(output_view.entity_id(), task_id);
+        self.pane.update(cx, |pane, cx| {
+            pane.add_item(Box::new(output_view), false, false, Some(index), cx);
+            pane.remove_item(item_id, false, false, cx);
+            if was_active {
+                pane.activate_item(index, false, false, cx);
+            }
+        });
+    }
+
+    fn output_view(label: String, output: String, cx: &mut ViewContext<Self>) -> View<Editor> {
+        let buffer = cx.new_model(|cx| Buffer::local(output, cx));
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(label));
+        cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(buffer, None, false, cx);
+            editor.set_read_only(true);
+            editor
+        })
+    }
+
+    /// Adds the tab of a run after the other ones, dropping the oldest runs past [`MAX_TASK_RUNS`].
+    fn add_item(&mut self, item: Box<dyn ItemHandle>, task_id: TaskId, cx: &mut ViewContext<Self>) {
+        self.run_tasks.insert(item.item_id(), task_id);
+        self.pane.update(cx, |pane, cx| {
+            while pane.items_len() >= MAX_TASK_RUNS {
+                let Some(oldest_item_id) = pane.item_for_index(0).map(|item| item.item_id()) else {
+                    break;
+                };
+                pane.remove_item(oldest_item_id, false, false, cx);
+            }
+            let destination_index = pane.items_len();
+            pane.add_item(item, false, false, Some(destination_index), cx);
+        });
+        cx.notify();
+    }
+
+    /// Activates the tab of the latest run of the task with the given id, or of any task.
+    fn reveal(&mut self, task_id: Option<&TaskId>, cx: &mut ViewContext<Self>) {
+        let run_tasks = &self.run_tasks;
+        self.pane.update(cx, |pane, cx| {
+            let run_index = pane
+                .items()
+                .enumerate()
+                .filter(|(_, item)| {
+                    task_id.map_or(true, |task_id| {
+                        run_tasks.get(&item.item_id()) == Some(task_id)
+                    })
+                })
+                .map(|(ix, _)| ix)
+                .last();
+            if let Some(run_index) = run_index {
+                pane.activate_item(run_index, true, true, cx);
+            }
+        });
+    }
+
+    fn clear_output(&mut self, _: &ClearOutput, cx: &mut ViewContext<Self>) {
+        let Some(active_item) = self.pane.read(cx).active_item() else {
+            return;
+        };
+        if let Some(terminal_view) = active_item.downcast::<TerminalView>() {
+            terminal_view.update(cx, |terminal_view, cx| {
+                terminal_view
+                    .terminal()
+                    .update(cx, |terminal, _| terminal.clear());
+            });
+        } else if let Some(output_view) = active_item.downcast::<Editor>() {
+            let label = output_view.read(cx).buffer().read(cx).title(cx).to_string();
+            self.replace_with_output_view(output_view.entity_id(), label, String::new(), cx);
+        }
+    }
+
+    fn handle_pane_event(
+        &mut self,
+        _: View<Pane>,
+        event: &pane::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            pane::Event::AddItem { item } => {
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        item.added_to_pane(workspace, self.pane.clone(), cx)
+                    })
+                    .ok();
+            }
+            pane::Event::RemovedItem { item_id } => {
+                self.run_tasks.remove(item_id);
+            }
+            pane::Event::Remove { .. } => cx.emit(PanelEvent::Close),
+            pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
+            pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
+            _ => {}
+        }
+    }
+}
+
+impl EventEmitter<PanelEvent> for TaskOutputPanel {}
+
+impl Render for TaskOutputPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut registrar = DivRegistrar::new(
+            |panel, cx| {
+                panel
+                    .pane
+                    .read(cx)
+                    .toolbar()
+                    .read(cx)
+                    .item_of_type::<BufferSearchBar>()
+            },
+            cx,
+        );
+        BufferSearchBar::register(&mut registrar);
+        registrar
+            .into_div()
+            .key_context("TaskOutputPanel")
+            .size_full()
+            .on_action(cx.listener(Self::clear_output))
+            .child(self.pane.clone())
+    }
+}
+
+impl FocusableView for TaskOutputPanel {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.pane.focus_handle(cx)
+    }
+}
+
+impl Panel for TaskOutputPanel {
+    fn persistent_name() -> &'static str {
+        "TaskOutputPanel"
+    }
+
+    fn position(&self, _: &WindowContext) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &WindowContext) -> Pixels {
+        self.size.unwrap_or(px(320.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.size = size;
+        cx.notify();
+    }
+
+    fn is_zoomed(&self, cx: &WindowContext) -> bool {
+        self.pane.read(cx).is_zoomed()
+    }
+
+    fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
+        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        (self.pane.read(cx).items_len() > 0).then_some(IconName::FileText)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Task Output")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn pane(&self) -> Option<View<Pane>> {
+        Some(self.pane.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_task_runs(cx: &mut TestAppContext) {
+        let (panel, cx) = init_test(cx).await;
+
+        add_run(&panel, "build", "Compiling", cx);
+        add_run(&panel, "test", "running 2 tests", cx);
+        add_run(&panel, "build", "Finished", cx);
+        assert_eq!(
+            run_outputs(&panel, cx),
+            ["Compiling", "running 2 tests", "Finished"]
+        );
+
+        let build = TaskId("build".to_string());
+        let test = TaskId("test".to_string());
+        panel.update(cx, |panel, cx| panel.reveal(Some(&test), cx));
+        assert_eq!(active_output(&panel, cx), "running 2 tests");
+        panel.update(cx, |panel, cx| panel.reveal(Some(&build), cx));
+        assert_eq!(
+            active_output(&panel, cx),
+            "Finished",
+            "The latest run of the task should be revealed"
+        );
+
+        panel.update(cx, |panel, cx| panel.reveal(Some(&test), cx));
+        panel.update(cx, |panel, cx| panel.clear_output(&ClearOutput, cx));
+        cx.run_until_parked();
+        assert_eq!(run_outputs(&panel, cx), ["Compiling", "", "Finished"]);
+        assert_eq!(active_output(&panel, cx), "");
+        panel.update(cx, |panel, cx| panel.reveal(Some(&build), cx));
+        panel.update(cx, |panel, cx| panel.reveal(Some(&test), cx));
+        assert_eq!(
+            active_output(&panel, cx),
+            "",
+            "A cleared run should still be revealed for its task"
+        );
+
+        for ix in 0..MAX_TASK_RUNS {
+            add_run(&panel, "lint", &format!("lint {ix}"), cx);
+        }
+        cx.run_until_parked();
+        let outputs = run_outputs(&panel, cx);
+        assert_eq!(outputs.len(), MAX_TASK_RUNS);
+        assert_eq!(outputs[0], "lint 0");
+        panel.update(cx, |panel, cx| {
+            assert_eq!(
+                panel.run_tasks.len(),
+                MAX_TASK_RUNS,
+                "The tasks of dropped runs should be forgotten"
+            );
+            panel.reveal(Some(&build), cx);
+        });
+        assert_eq!(
+            active_output(&panel, cx),
+            format!("lint {}", MAX_TASK_RUNS - 1),
+            "Revealing a dropped run should not change the active tab"
+        );
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (View<TaskOutputPanel>, &mut VisualTestContext) {
+        let params = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+            editor::init(cx);
+        });
+
+        let project = Project::test(params.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let panel = workspace.update(cx, |workspace, cx| {
+            let workspace = workspace.weak_handle();
+            cx.new_view(|cx| TaskOutputPanel::new(workspace, project, cx))
+        });
+        (panel, cx)
+    }
+
+    fn add_run(
+        panel: &View<TaskOutputPanel>,
+        task_id: &str,
+        output: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        panel.update(cx, |panel, cx| {
+            let output_view =
+                TaskOutputPanel::output_view(task_id.to_string(), output.to_string(), cx);
+            panel.add_item(Box::new(output_view), TaskId(task_id.to_string()), cx);
+        });
+    }
+
+    fn run_outputs(panel: &View<TaskOutputPanel>, cx: &mut VisualTestContext) -> Vec<String> {
+        panel.update(cx, |panel, cx| {
+            panel
+                .pane
+                .read(cx)
+                .items()
+                .filter_map(|item| item.downcast::<Editor>())
+                .map(|editor| editor.read(cx).text(cx))
+                .collect()
+        })
+    }
+
+    fn active_output(panel: &View<TaskOutputPanel>, cx: &mut VisualTestContext) -> String {
+        panel.update(cx, |panel, cx| {
+            let active_item = panel.pane.read(cx).active_item().unwrap();
+            active_item.downcast::<Editor>().unwrap().read(cx).text(cx)
+        })
+    }
+}
@@ -20,7 +20,7 @@ use itertools::Itertools;
 use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use task::{RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
+use task::{RevealStrategy, RevealTarget, SpawnInTerminal, TaskId, TaskShell};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
     Terminal,
@@ -521,15 +521,16 @@ impl TerminalPanel {
         spawn_task: &mut SpawnInTerminal,
     ) -> ControlFlow<()> {
         let Some((shell, mut user_args)) = (match spawn_in_terminal.shell.clone() {
-            Shell::System => {
+            TaskShell::System => {
                 if is_local {
                     retrieve_system_shell().map(|shell| (shell, Vec::new()))
                 } else {
                     Some(("\"${SHELL:-sh}\"".to_string(), Vec::new()))
                 }
             }
-            Shell::Program(shell) => Some((shell, Vec::new())),
-            Shell::WithArguments { program, args, .. } => Some((program, args)),
+            // The command is spawned as is.
+            TaskShell::None => return ControlFlow::Continue(()),
+            TaskShell::Program { program, args } => Some((program, args)),
         }) else {
             return ControlFlow::Break(());
         };
@@ -605,6 +606,18 @@ impl TerminalPanel {
                     Self::add_center_terminal(workspace, kind, cx)
                 })
                 .unwrap_or_else(|e| Task::ready(Err(e))),
+            RevealTarget::Split => self
+                .workspace
+                .update(cx, |workspace, cx| {
+                    Self::add_split_terminal(workspace, kind, reveal, cx)
+                })
+                .unwrap_or_else(|e| Task::ready(Err(e))),
+            RevealTarget::Window => self
+                .workspace
+                .update(cx, |workspace, cx| {
+                    Self::add_window_terminal(workspace, kind, reveal, cx)
+                })
+                .unwrap_or_else(|e| Task::ready(Err(e))),
             RevealTarget::Dock => self.add_terminal(kind, reveal, cx),
         }
     }
@@ -717,6 +730,99 @@ impl TerminalPanel {
         })
     }
 
+    /// Creates a new terminal in a pane split to the right of the active one,
+    /// focusing it only when the task is revealed with [`RevealStrategy::Always`].
+    fn add_split_terminal(
+        workspace: &mut Workspace,
+        kind: TerminalKind,
+        reveal_strategy: RevealStrategy,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Task<Result<Model<Terminal>>> {
+        if !is_enabled_in_workspace(workspace, cx) {
+            return Task::ready(Err(anyhow!(
+                "terminal not yet supported for remote projects"
+            )));
+        }
+        let window = cx.window_handle();
+        let project = workspace.project().downgrade();
+        cx.spawn(move |workspace, mut cx| async move {
+            let terminal = project
+                .update(&mut cx, |project, cx| {
+                    project.create_terminal(kind, window, cx)
+                })?
+                .await?;
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let view = cx.new_view(|cx| {
+                    TerminalView::new(
+                        terminal.clone(),
+                        workspace.weak_handle(),
+                        workspace.database_id(),
+                        workspace.project().downgrade(),
+                        cx,
+                    )
+                });
+                let focus = reveal_strategy == RevealStrategy::Always;
+                let active_pane = workspace.active_pane().clone();
+                let split_pane =
+                    workspace.split_pane(active_pane.clone(), SplitDirection::Right, cx);
+                split_pane.update(cx, |pane, cx| {
+                    pane.add_item(Box::new(view), focus, focus, None, cx)
+                });
+                if !focus {
+                    cx.focus_view(&active_pane);
+                }
+            })?;
+            Ok(terminal)
+        })
+    }
+
+    /// Creates a new terminal in a new window of the workspace's project,
+    /// focusing the window only when the task is revealed with [`RevealStrategy::Always`].
+    fn add_window_terminal(
+        workspace: &mut Workspace,
+        kind: TerminalKind,
+        reveal_strategy: RevealStrategy,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Task<Result<Model<Terminal>>> {
+        if !is_enabled_in_workspace(workspace, cx) {
+            return Task::ready(Err(anyhow!(
+                "terminal not yet supported for remote projects"
+            )));
+        }
+        let project = workspace.project().clone();
+        let app_state = workspace.app_state().clone();
+        let mut options = (app_state.build_window_options)(None, cx);
+        options.focus = reveal_strategy == RevealStrategy::Always;
+        let window = match cx.open_window(options, |cx| {
+            cx.new_view(|cx| Workspace::new(None, project.clone(), app_state, cx))
+        }) {
+            Ok(window) => window,
+            Err(e) => return Task::ready(Err(e)),
+        };
+        cx.spawn(move |_, mut cx| async move {
+            let terminal = project
+                .update(&mut cx, |project, cx| {
+                    project.create_terminal(kind, window.into(), cx)
+                })?
+                .await?;
+
+            window.update(&mut cx, |workspace, cx| {
+                let view = cx.new_view(|cx| {
+                    TerminalView::new(
+                        terminal.clone(),
+                        workspace.weak_handle(),
+                        workspace.database_id(),
+                        workspace.project().downgrade(),
+                        cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+            })?;
+            Ok(terminal)
+        })
+    }
+
     fn add_terminal(
         &mut self,
         kind: TerminalKind,
@@ -867,6 +973,18 @@ impl TerminalPanel {
                             })
                             .ok()??;
                     }
+                    RevealTarget::Split | RevealTarget::Window => {
+                        terminal_panel
+                            .update(&mut cx, |terminal_panel, cx| {
+                                terminal_panel.activate_terminal_view(
+                                    &task_pane,
+                                    terminal_item_index,
+                                    true,
+                                    cx,
+                                )
+                            })
+                            .ok()?;
+                    }
                     RevealTarget::Dock => {
                         terminal_panel
                             .update(&mut cx, |terminal_panel, cx| {
@@ -895,6 +1013,18 @@ impl TerminalPanel {
                             })
                             .ok()?;
                     }
+                    RevealTarget::Split | RevealTarget::Window => {
+                        terminal_panel
+                            .update(&mut cx, |terminal_panel, cx| {
+                                terminal_panel.activate_terminal_view(
+                                    &task_pane,
+                                    terminal_item_index,
+                                    false,
+                                    cx,
+                                )
+                            })
+                            .ok()?;
+                    }
                     RevealTarget::Dock => {
                         terminal_panel
                             .update(&mut cx, |terminal_panel, cx| {
@@ -1,4 +1,5 @@
 mod persistence;
+pub mod task_output_panel;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -73,6 +74,7 @@ impl_actions!(terminal, [SendText, SendKeystroke]);
 
 pub fn init(cx: &mut AppContext) {
     terminal_panel::init(cx);
+    task_output_panel::init(cx);
     terminal::init(cx);
 
     register_serializable_item::<TerminalView>(cx);
@@ -999,7 +1001,13 @@ impl Item for TerminalView {
     type Event = ItemEvent;
 
     fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
-        Some(self.terminal().read(cx).title(false).into())
+        let terminal = self.terminal().read(cx);
+        let title = terminal.title(false);
+        if terminal.active_toolchains().is_empty() {
+            Some(title.into())
+        } else {
+            Some(format!("{title} ({})", terminal.active_toolchains().join(", ")).into())
+        }
     }
 
     fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
@@ -1018,6 +1026,7 @@ impl Item for TerminalView {
                         allow_concurrent_runs: Some(true),
                         use_new_terminal: Some(false),
                         reevaluate_context: false,
+                        edit: false,
                     }));
                 })
         };
@@ -1069,6 +1078,13 @@ impl Item for TerminalView {
                     }),
             )
             .child(Label::new(title).color(params.text_color()))
+            .when(!terminal.active_toolchains().is_empty(), |this| {
+                this.child(
+                    Label::new(terminal.active_toolchains().join(", "))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
             .into_any()
     }
 
@@ -425,6 +425,15 @@ impl TitleBar {
             );
         }
 
+        children.push(
+            IconButton::new("whiteboard", ui::IconName::Pencil)
+                .style(ButtonStyle::Subtle)
+                .icon_size(IconSize::Small)
+                .tooltip(|cx| Tooltip::text("Open Shared Whiteboard", cx))
+                .on_click(|_, cx| cx.dispatch_action(Box::new(workspace::OpenWhiteboard)))
+                .into_any_element(),
+        );
+
         if screen_sharing_supported {
             children.push(
                 IconButton::new("screen-share", ui::IconName::Screen)
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{actions, AppContext, DismissEvent, Task, ViewContext, WeakView};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{layout_presets::LayoutPresets, Workspace};
+
+actions!(layout_switcher, [Toggle]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(toggle);
+    })
+    .detach();
+}
+
+fn toggle(workspace: &mut Workspace, _: &Toggle, cx: &mut ViewContext<Workspace>) {
+    let delegate = LayoutSwitcherDelegate::new(workspace, cx);
+    workspace.toggle_modal(cx, |cx| Picker::uniform_list(delegate, cx).width(rems(34.)));
+}
+
+enum LayoutEntry {
+    Preset(StringMatch),
+    /// Saves the current layout under the name typed into the query.
+    SaveAs(String),
+}
+
+/// Switches between the saved layout presets, and saves new ones.
+pub struct LayoutSwitcherDelegate {
+    workspace: WeakView<Workspace>,
+    current_preset: Option<String>,
+    preset_names: Vec<String>,
+    entries: Vec<LayoutEntry>,
+    selected_index: usize,
+}
+
+impl LayoutSwitcherDelegate {
+    fn new(workspace: &Workspace, cx: &AppContext) -> Self {
+        Self {
+            workspace: workspace.weak_handle(),
+            current_preset: workspace.layout_preset().map(ToString::to_string),
+            preset_names: preset_names(cx),
+            entries: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for LayoutSwitcherDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Switch to a layout, or name the current one to save it...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .preset_names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatchCandidate::new(id, name))
+            .collect::<Vec<_>>();
+        let preset_names = self.preset_names.clone();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            let mut entries = matches
+                .into_iter()
+                .map(LayoutEntry::Preset)
+                .collect::<Vec<_>>();
+            let name = query.trim();
+            if !name.is_empty() && !preset_names.iter().any(|preset_name| preset_name == name) {
+                entries.push(LayoutEntry::SaveAs(name.to_string()));
+            }
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.entries = entries;
+                    picker.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    /// Applies the selected preset, or with `secondary`, saves the current layout over it.
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| match entry {
+                LayoutEntry::Preset(preset) if secondary => {
+                    workspace.save_layout(preset.string.clone(), cx)
+                }
+                LayoutEntry::Preset(preset) => workspace.apply_layout(&preset.string, cx),
+                LayoutEntry::SaveAs(name) => workspace.save_layout(name.clone(), cx),
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let list_item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+        match self.entries.get(ix)? {
+            LayoutEntry::Preset(preset) => {
+                let name = preset.string.clone();
+                let is_current = self.current_preset.as_ref() == Some(&name);
+                Some(
+                    list_item
+                        .start_slot(Icon::new(IconName::Check).color(if is_current {
+                            Color::Accent
+                        } else {
+                            Color::Hidden
+                        }))
+                        .child(HighlightedLabel::new(
+                            preset.string.clone(),
+                            preset.positions.clone(),
+                        ))
+                        .end_hover_slot(
+                            IconButton::new(("delete-layout", ix), IconName::Trash)
+                                .icon_size(IconSize::Small)
+                                .tooltip(|cx| Tooltip::text("Delete Layout", cx))
+                                .on_click(cx.listener(move |picker, _, cx| {
+                                    cx.stop_propagation();
+                                    picker
+                                        .delegate
+                                        .workspace
+                                        .update(cx, |workspace, cx| {
+                                            workspace.delete_layout(&name, cx)
+                                        })
+                                        .log_err();
+                                    picker.delegate.preset_names = preset_names(cx);
+                                    picker.refresh(cx);
+                                })),
+                        ),
+                )
+            }
+            LayoutEntry::SaveAs(name) => Some(
+                list_item
+                    .start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!("Save Current Layout as \"{name}\""))),
+            ),
+        }
+    }
+}
+
+fn preset_names(cx: &AppContext) -> Vec<String> {
+    LayoutPresets::presets(cx)
+        .iter()
+        .map(|preset| preset.name.clone())
+        .collect()
+}
@@ -1,5 +1,6 @@
 mod application_menu;
 mod collab;
+mod layout_switcher;
 mod platforms;
 mod window_controls;
 
@@ -28,7 +29,7 @@ use ui::{
     IconSize, IconWithIndicator, Indicator, PopoverMenu, Tooltip,
 };
 use util::ResultExt;
-use workspace::{notifications::NotifyResultExt, Workspace};
+use workspace::{layout_presets::LayoutPresets, notifications::NotifyResultExt, Workspace};
 use zed_actions::{OpenBrowser, OpenRecent, OpenRemote};
 
 #[cfg(feature = "stories")]
@@ -51,6 +52,7 @@ actions!(
 );
 
 pub fn init(cx: &mut AppContext) {
+    layout_switcher::init(cx);
     cx.observe_new_views(|workspace: &mut Workspace, cx| {
         let item = cx.new_view(|cx| TitleBar::new("title-bar", workspace, cx));
         workspace.set_titlebar_item(item.into(), cx)
@@ -156,6 +158,7 @@ impl Render for TitleBar {
                             .gap_1()
                             .pr_1()
                             .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
+                            .child(self.render_layout_menu(cx))
                             .children(self.render_call_controls(cx))
                             .map(|el| {
                                 let status = self.client.status();
@@ -248,6 +251,7 @@ impl TitleBar {
         subscriptions.push(cx.observe(&active_call, |this, _, cx| this.active_call_changed(cx)));
         subscriptions.push(cx.observe_window_activation(Self::window_activation_changed));
         subscriptions.push(cx.observe(&user_store, |_, _, cx| cx.notify()));
+        subscriptions.push(cx.observe_global::<LayoutPresets>(|_, cx| cx.notify()));
 
         Self {
             platform_style,
@@ -575,6 +579,67 @@ impl TitleBar {
             })
     }
 
+    pub fn render_layout_menu(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let current_preset = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).layout_preset().map(SharedString::from));
+        let workspace = self.workspace.clone();
+        PopoverMenu::new("layout-menu")
+            .menu({
+                let current_preset = current_preset.clone();
+                move |cx| {
+                    let workspace = workspace.clone();
+                    let current_preset = current_preset.clone();
+                    ContextMenu::build(cx, move |mut menu, cx| {
+                        for preset in LayoutPresets::presets(cx) {
+                            let name = preset.name.clone();
+                            let is_current = current_preset.as_deref() == Some(name.as_str());
+                            let workspace = workspace.clone();
+                            menu = menu.toggleable_entry(
+                                name.clone(),
+                                is_current,
+                                IconPosition::Start,
+                                None,
+                                move |cx| {
+                                    workspace
+                                        .update(cx, |workspace, cx| {
+                                            workspace.apply_layout(&name, cx)
+                                        })
+                                        .ok();
+                                },
+                            );
+                        }
+                        if !LayoutPresets::presets(cx).is_empty() {
+                            menu = menu.separator();
+                        }
+                        menu.action("Save Layout…", layout_switcher::Toggle.boxed_clone())
+                    })
+                    .into()
+                }
+            })
+            .trigger(
+                ButtonLike::new("layout-menu")
+                    .child(
+                        h_flex()
+                            .gap_0p5()
+                            .child(
+                                Icon::new(IconName::Split)
+                                    .size(IconSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .children(current_preset.map(|name| {
+                                Label::new(name).size(LabelSize::Small).color(Color::Muted)
+                            })),
+                    )
+                    .style(ButtonStyle::Subtle)
+                    .tooltip(|cx| {
+                        Tooltip::for_action("Switch Layout", &layout_switcher::Toggle, cx)
+                    }),
+            )
+            .anchor(gpui::Corner::TopRight)
+    }
+
     pub fn render_user_menu_button(&mut self, cx: &mut ViewContext<Self>) -> impl Element {
         let user_store = self.user_store.read(cx);
         if let Some(user) = user_store.current_user() {
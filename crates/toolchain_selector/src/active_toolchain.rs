@@ -114,6 +114,12 @@ impl ActiveToolchain {
                 let project = workspace
                     .update(&mut cx, |this, _| this.project().clone())
                     .ok()?;
+                let worktree_root_path = project
+                    .update(&mut cx, |this, cx| {
+                        Some(this.worktree_for_id(worktree_id, cx)?.read(cx).abs_path())
+                    })
+                    .ok()
+                    .flatten()?;
                 let toolchains = cx
                     .update(|cx| {
                         project
@@ -122,10 +128,19 @@ impl ActiveToolchain {
                     })
                     .ok()?
                     .await?;
-                if let Some(toolchain) = toolchains.toolchains.first() {
-                    // Since we don't have a selected toolchain, pick one for user here.
+                // Since we don't have a selected toolchain, pick one for user here,
+                // preferring the one the lister deemed the best fit for this worktree.
+                let toolchain = toolchains
+                    .default_toolchain()
+                    .or_else(|| toolchains.toolchains.first().cloned());
+                if let Some(toolchain) = &toolchain {
                     workspace::WORKSPACE_DB
-                        .set_toolchain(workspace_id, worktree_id, toolchain.clone())
+                        .set_toolchain(
+                            workspace_id,
+                            worktree_id,
+                            worktree_root_path,
+                            toolchain.clone(),
+                        )
                         .await
                         .ok()?;
                     project
@@ -136,7 +151,7 @@ impl ActiveToolchain {
                         .await;
                 }
 
-                toolchains.toolchains.first().cloned()
+                toolchain
             }
         })
     }
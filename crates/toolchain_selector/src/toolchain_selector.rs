@@ -1,16 +1,21 @@
 mod active_toolchain;
 
 pub use active_toolchain::ActiveToolchain;
+use anyhow::anyhow;
 use editor::Editor;
 use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
-    ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+    actions, AnyElement, AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Model, ParentElement, Render, Styled, Task, View, ViewContext, VisualContext,
+    WeakView,
 };
-use language::{LanguageName, Toolchain, ToolchainList};
+use language::{LanguageName, Toolchain, ToolchainDetails, ToolchainList};
 use picker::{Picker, PickerDelegate};
 use project::{Project, WorktreeId};
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
 use util::ResultExt;
 use workspace::{ModalView, Workspace};
@@ -51,7 +56,12 @@ impl ToolchainSelector {
         let weak = workspace.weak_handle();
         cx.spawn(move |workspace, mut cx| async move {
             let active_toolchain = workspace::WORKSPACE_DB
-                .toolchain(workspace_id, worktree_id, language_name.clone())
+                .toolchain(
+                    workspace_id,
+                    worktree_id,
+                    worktree_root_path.clone(),
+                    language_name.clone(),
+                )
                 .await
                 .ok()
                 .flatten();
@@ -127,7 +137,22 @@ pub struct ToolchainSelectorDelegate {
     worktree_id: WorktreeId,
     worktree_abs_path_root: Arc<Path>,
     placeholder_text: Arc<str>,
+    project: Model<Project>,
+    language_name: LanguageName,
+    query: String,
+    adding_toolchain: Option<AddingToolchain>,
+    /// Details of the highlighted toolchain, once they've been gathered.
+    details: Option<(Toolchain, ToolchainDetails)>,
     _fetch_candidates_task: Task<Option<()>>,
+    _fetch_details_task: Task<Option<()>>,
+}
+
+/// Steps of registering a toolchain that was not detected automatically.
+enum AddingToolchain {
+    /// Waiting for the path of the interpreter or SDK.
+    Path,
+    /// Waiting for a display name for the given path.
+    Name(PathBuf),
 }
 
 impl ToolchainSelectorDelegate {
@@ -144,6 +169,7 @@ impl ToolchainSelectorDelegate {
     ) -> Self {
         let _fetch_candidates_task = cx.spawn({
             let project = project.clone();
+            let language_name = language_name.clone();
             move |this, mut cx| async move {
                 let term = project
                     .update(&mut cx, |this, _| {
@@ -156,12 +182,26 @@ impl ToolchainSelectorDelegate {
                     this.delegate.placeholder_text = placeholder_text;
                     this.refresh_placeholder(cx);
                 });
-                let available_toolchains = project
+                let mut available_toolchains = project
                     .update(&mut cx, |this, cx| {
-                        this.available_toolchains(worktree_id, language_name, cx)
+                        this.available_toolchains(worktree_id, language_name.clone(), cx)
                     })
                     .ok()?
                     .await?;
+                let custom_toolchains = workspace::WORKSPACE_DB
+                    .custom_toolchains(language_name)
+                    .await
+                    .log_err()
+                    .unwrap_or_default();
+                if !custom_toolchains.is_empty() {
+                    let mut groups = available_toolchains.groups.into_vec();
+                    groups.push((
+                        available_toolchains.toolchains.len(),
+                        SharedString::new_static("Custom"),
+                    ));
+                    available_toolchains.groups = groups.into();
+                    available_toolchains.toolchains.extend(custom_toolchains);
+                }
 
                 let _ = this.update(&mut cx, move |this, cx| {
                     this.delegate.candidates = available_toolchains;
@@ -193,9 +233,112 @@ impl ToolchainSelectorDelegate {
             worktree_id,
             worktree_abs_path_root,
             placeholder_text,
+            project,
+            language_name,
+            query: String::new(),
+            adding_toolchain: None,
+            details: None,
             _fetch_candidates_task,
+            _fetch_details_task: Task::ready(None),
         }
     }
+    fn selected_toolchain(&self) -> Option<&Toolchain> {
+        let string_match = self.matches.get(self.selected_index)?;
+        self.candidates.toolchains.get(string_match.candidate_id)
+    }
+
+    fn refresh_details(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(toolchain) = self.selected_toolchain().cloned() else {
+            self.details = None;
+            return;
+        };
+        if self
+            .details
+            .as_ref()
+            .map_or(false, |(shown, _)| *shown == toolchain)
+        {
+            return;
+        }
+        let project = self.project.read(cx);
+        let details = project.toolchain_details(self.worktree_id, toolchain.clone(), cx);
+        self._fetch_details_task = cx.spawn(|this, mut cx| async move {
+            let details = details.await?;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.details = Some((toolchain, details));
+                cx.notify();
+            })
+            .ok()
+        });
+    }
+
+    fn start_adding_toolchain(picker: &mut Picker<Self>, cx: &mut ViewContext<Picker<Self>>) {
+        picker.delegate.adding_toolchain = Some(AddingToolchain::Path);
+        picker.delegate.details = None;
+        picker.set_query("", cx);
+        picker.refresh_placeholder(cx);
+        picker.refresh(cx);
+        picker.focus(cx);
+    }
+
+    fn confirm_adding_toolchain(
+        &mut self,
+        step: AddingToolchain,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let query = self.query.trim().to_owned();
+        match step {
+            AddingToolchain::Path => {
+                if query.is_empty() {
+                    self.adding_toolchain = Some(AddingToolchain::Path);
+                    return;
+                }
+                // Relative paths are resolved against the worktree, e.g. `./tools/python`.
+                let path = self.worktree_abs_path_root.join(query);
+                self.adding_toolchain = Some(AddingToolchain::Name(path));
+                cx.defer(|picker, cx| {
+                    picker.set_query("", cx);
+                    picker.refresh_placeholder(cx);
+                });
+            }
+            AddingToolchain::Name(path) => {
+                let name = if query.is_empty() {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+                } else {
+                    query
+                };
+                let toolchain = Toolchain {
+                    name: name.into(),
+                    path: path.to_string_lossy().into_owned().into(),
+                    language_name: self.language_name.clone(),
+                    as_json: serde_json::json!({ "custom": true }),
+                };
+                let workspace = self.workspace.clone();
+                let worktree_id = self.worktree_id;
+                let worktree_root_path = self.worktree_abs_path_root.clone();
+                let path_exists = cx.background_executor().spawn(async move { path.exists() });
+                cx.spawn(|_, mut cx| async move {
+                    if !path_exists.await {
+                        let error = anyhow!("{} does not exist", toolchain.path);
+                        workspace
+                            .update(&mut cx, |this, cx| this.show_error(&error, cx))
+                            .ok();
+                        return None;
+                    }
+                    workspace::WORKSPACE_DB
+                        .add_custom_toolchain(toolchain.clone())
+                        .await
+                        .log_err()?;
+                    activate_toolchain(workspace, worktree_id, worktree_root_path, toolchain, cx)
+                        .await
+                })
+                .detach();
+                self.dismissed(cx);
+            }
+        }
+    }
+
     fn relativize_path(path: SharedString, worktree_root: &Path) -> SharedString {
         Path::new(&path.as_ref())
             .strip_prefix(&worktree_root)
@@ -210,7 +353,21 @@ impl PickerDelegate for ToolchainSelectorDelegate {
     type ListItem = ListItem;
 
     fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
-        self.placeholder_text.clone()
+        match &self.adding_toolchain {
+            Some(AddingToolchain::Path) => "Path to an interpreter or SDK…".into(),
+            Some(AddingToolchain::Name(path)) => {
+                format!("Name for {}…", path.to_string_lossy()).into()
+            }
+            None => self.placeholder_text.clone(),
+        }
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        match &self.adding_toolchain {
+            Some(AddingToolchain::Path) => "Press enter to choose a name".into(),
+            Some(AddingToolchain::Name(_)) => "Press enter to add the toolchain".into(),
+            None => "No matches".into(),
+        }
     }
 
     fn match_count(&self) -> usize {
@@ -218,33 +375,37 @@ impl PickerDelegate for ToolchainSelectorDelegate {
     }
 
     fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(step) = self.adding_toolchain.take() {
+            self.confirm_adding_toolchain(step, cx);
+            return;
+        }
         if let Some(string_match) = self.matches.get(self.selected_index) {
-            let toolchain = self.candidates.toolchains[string_match.candidate_id].clone();
-            if let Some(workspace_id) = self
-                .workspace
-                .update(cx, |this, _| this.database_id())
-                .ok()
-                .flatten()
-            {
-                let workspace = self.workspace.clone();
-                let worktree_id = self.worktree_id;
-                cx.spawn(|_, mut cx| async move {
-                    workspace::WORKSPACE_DB
-                        .set_toolchain(workspace_id, worktree_id, toolchain.clone())
-                        .await
-                        .log_err();
-                    workspace
+            let mut toolchain = self.candidates.toolchains[string_match.candidate_id].clone();
+            let workspace = self.workspace.clone();
+            let worktree_id = self.worktree_id;
+            let worktree_root_path = self.worktree_abs_path_root.clone();
+            cx.spawn(|_, mut cx| async move {
+                if !toolchain.is_installed() {
+                    let install = workspace
                         .update(&mut cx, |this, cx| {
                             this.project().update(cx, |this, cx| {
-                                this.activate_toolchain(worktree_id, toolchain, cx)
+                                this.install_toolchain(worktree_id, toolchain.clone(), cx)
                             })
                         })
-                        .ok()?
-                        .await;
-                    Some(())
-                })
-                .detach();
-            }
+                        .ok()?;
+                    toolchain = match install.await {
+                        Ok(installed) => installed,
+                        Err(error) => {
+                            workspace
+                                .update(&mut cx, |this, cx| this.show_error(&error, cx))
+                                .ok();
+                            return None;
+                        }
+                    };
+                }
+                activate_toolchain(workspace, worktree_id, worktree_root_path, toolchain, cx).await
+            })
+            .detach();
         }
         self.dismissed(cx);
     }
@@ -259,8 +420,9 @@ impl PickerDelegate for ToolchainSelectorDelegate {
         self.selected_index
     }
 
-    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
         self.selected_index = ix;
+        self.refresh_details(cx);
     }
 
     fn update_matches(
@@ -268,6 +430,12 @@ impl PickerDelegate for ToolchainSelectorDelegate {
         query: String,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> gpui::Task<()> {
+        self.query = query.clone();
+        if self.adding_toolchain.is_some() {
+            self.matches.clear();
+            self.selected_index = 0;
+            return Task::ready(());
+        }
         let background = cx.background_executor().clone();
         let candidates = self.candidates.clone();
         let worktree_root_path = self.worktree_abs_path_root.clone();
@@ -316,6 +484,7 @@ impl PickerDelegate for ToolchainSelectorDelegate {
                 delegate.selected_index = delegate
                     .selected_index
                     .min(delegate.matches.len().saturating_sub(1));
+                delegate.refresh_details(cx);
                 cx.notify();
             })
             .log_err();
@@ -351,7 +520,147 @@ impl PickerDelegate for ToolchainSelectorDelegate {
                     HighlightedLabel::new(path, path_highlights)
                         .size(LabelSize::Small)
                         .color(Color::Muted),
-                ),
+                )
+                .when(!toolchain.is_installed(), |item| {
+                    item.end_slot(
+                        Label::new("Not Installed – Select to Install")
+                            .size(LabelSize::Small)
+                            .color(Color::Warning),
+                    )
+                }),
         )
     }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.adding_toolchain.is_some() {
+            return None;
+        }
+        let add_toolchain = h_flex().w_full().justify_end().child(
+            Button::new("add-toolchain", "Add Toolchain…")
+                .icon(IconName::Plus)
+                .icon_size(IconSize::Small)
+                .icon_color(Color::Muted)
+                .icon_position(IconPosition::Start)
+                .on_click(cx.listener(|picker, _, cx| Self::start_adding_toolchain(picker, cx))),
+        );
+        let Some(toolchain) = self.selected_toolchain() else {
+            return Some(
+                h_flex()
+                    .w_full()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .p_1()
+                    .child(add_toolchain)
+                    .into_any(),
+            );
+        };
+        let details = self
+            .details
+            .as_ref()
+            .filter(|(shown, _)| shown == toolchain)
+            .map(|(_, details)| details);
+
+        let mut environment = details
+            .map(|details| details.environment.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        environment.sort();
+        let packages = details.and_then(|details| details.packages.as_ref());
+
+        Some(
+            v_flex()
+                .w_full()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .p_2()
+                .gap_1()
+                .child(Label::new(toolchain.name.clone()))
+                .child(
+                    Label::new(toolchain.path.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .when_some(
+                    details.and_then(|details| details.version.clone()),
+                    |this, version| this.child(Label::new(version).size(LabelSize::Small)),
+                )
+                .children(environment.into_iter().map(|(name, value)| {
+                    Label::new(format!("{name}={value}"))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                }))
+                .when_some(packages, |this, packages| {
+                    this.child(
+                        Label::new(packages_summary(packages))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
+                .when(details.is_none(), |this| {
+                    this.child(
+                        Label::new("Loading details…")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
+                .child(add_toolchain)
+                .into_any(),
+        )
+    }
+}
+
+/// Remembers the toolchain for the worktree and makes the project use it.
+async fn activate_toolchain(
+    workspace: WeakView<Workspace>,
+    worktree_id: WorktreeId,
+    worktree_root_path: Arc<Path>,
+    toolchain: Toolchain,
+    mut cx: AsyncWindowContext,
+) -> Option<()> {
+    let workspace_id = workspace
+        .update(&mut cx, |this, _| this.database_id())
+        .ok()?;
+    if let Some(workspace_id) = workspace_id {
+        workspace::WORKSPACE_DB
+            .set_toolchain(
+                workspace_id,
+                worktree_id,
+                worktree_root_path,
+                toolchain.clone(),
+            )
+            .await
+            .log_err();
+    }
+    workspace
+        .update(&mut cx, |this, cx| {
+            this.project().update(cx, |this, cx| {
+                this.activate_toolchain(worktree_id, toolchain, cx)
+            })
+        })
+        .ok()?
+        .await
+}
+
+/// Summarizes installed packages as e.g. `42 packages: numpy 2.1.0, pandas 2.2.3, …`.
+fn packages_summary(packages: &[SharedString]) -> String {
+    const SHOWN_PACKAGES: usize = 5;
+    if packages.is_empty() {
+        return "No packages installed".to_string();
+    }
+    let mut summary = format!(
+        "{} package{}: ",
+        packages.len(),
+        if packages.len() == 1 { "" } else { "s" }
+    );
+    summary.push_str(
+        &packages
+            .iter()
+            .take(SHOWN_PACKAGES)
+            .map(SharedString::as_ref)
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    if packages.len() > SHOWN_PACKAGES {
+        summary.push_str(", …");
+    }
+    summary
 }
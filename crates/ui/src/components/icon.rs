@@ -238,6 +238,7 @@ pub enum IconName {
     Public,
     PullRequest,
     Quote,
+    Record,
     RefreshTitle,
     Regex,
     ReplNeutral,
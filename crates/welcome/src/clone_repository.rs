@@ -0,0 +1,387 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use editor::Editor;
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, PathPromptOptions,
+    Render, Task, View, ViewContext, WeakView,
+};
+use ui::{prelude::*, ElevationIndex, Modal, ModalFooter, ModalHeader, Section, SectionHeader};
+use util::{paths::home_dir, ResultExt};
+use workspace::{ModalView, Workspace};
+
+use crate::{process_output::run_with_output, project_templates::open_created_project};
+
+actions!(welcome, [CloneRepository]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(|workspace, _: &CloneRepository, cx| toggle(workspace, cx));
+    })
+    .detach();
+}
+
+pub fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let weak_workspace = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| CloneRepositoryModal::new(weak_workspace, cx));
+}
+
+/// Expands `owner/repo`, `gh:owner/repo` and `gl:group/repo` shorthands into
+/// clone URLs. Anything that already looks like a URL is returned as is.
+fn expand_repository_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() || input.contains(char::is_whitespace) {
+        return None;
+    }
+    if input.contains("://") || input.starts_with("git@") {
+        return Some(input.to_string());
+    }
+
+    let (host, path) = if let Some(path) = input
+        .strip_prefix("gh:")
+        .or_else(|| input.strip_prefix("github:"))
+    {
+        ("github.com", path)
+    } else if let Some(path) = input
+        .strip_prefix("gl:")
+        .or_else(|| input.strip_prefix("gitlab:"))
+    {
+        ("gitlab.com", path)
+    } else if let Some(path) = input.strip_prefix("github.com/") {
+        ("github.com", path)
+    } else if let Some(path) = input.strip_prefix("gitlab.com/") {
+        ("gitlab.com", path)
+    } else if input.contains(':') {
+        return None;
+    } else {
+        ("github.com", input)
+    };
+
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let segments = path.split('/').collect::<Vec<_>>();
+    // GitLab projects can be nested in subgroups, GitHub ones can't.
+    let valid_len = if host == "gitlab.com" {
+        segments.len() >= 2
+    } else {
+        segments.len() == 2
+    };
+    if !valid_len || segments.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+    Some(format!("https://{host}/{path}.git"))
+}
+
+/// The directory name `git clone` picks for a repository URL.
+fn repository_name(url: &str) -> Option<&str> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()?
+        .trim_end_matches(".git");
+    (!name.is_empty()).then_some(name)
+}
+
+enum CloneStep {
+    Configure,
+    Cloning { url: String },
+    Failed { error: SharedString },
+}
+
+pub struct CloneRepositoryModal {
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    url_editor: View<Editor>,
+    directory: PathBuf,
+    step: CloneStep,
+    progress: Option<SharedString>,
+    _task: Option<Task<()>>,
+}
+
+impl CloneRepositoryModal {
+    fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let url_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("https://github.com/owner/repo.git or gh:owner/repo", cx);
+            editor
+        });
+        Self {
+            workspace,
+            focus_handle: cx.focus_handle(),
+            url_editor,
+            directory: home_dir().clone(),
+            step: CloneStep::Configure,
+            progress: None,
+            _task: None,
+        }
+    }
+
+    fn choose_directory(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        self._task = Some(cx.spawn(|this, mut cx| async move {
+            let Some(directory) = paths
+                .await
+                .log_err()
+                .and_then(|paths| paths.log_err())
+                .flatten()
+                .and_then(|paths| paths.into_iter().next())
+            else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.directory = directory;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn fail(&mut self, error: impl Into<SharedString>, cx: &mut ViewContext<Self>) {
+        self.step = CloneStep::Failed {
+            error: error.into(),
+        };
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if !matches!(self.step, CloneStep::Configure) {
+            return;
+        }
+        let input = self.url_editor.read(cx).text(cx);
+        if input.trim().is_empty() {
+            return;
+        }
+        let Some((url, name)) = expand_repository_url(&input)
+            .and_then(|url| Some((url.clone(), repository_name(&url)?.to_string())))
+        else {
+            self.fail(format!("`{}` is not a repository URL.", input.trim()), cx);
+            return;
+        };
+        let repository_dir = self.directory.join(name);
+        if repository_dir.exists() {
+            self.fail(format!("{} already exists.", repository_dir.display()), cx);
+            return;
+        }
+
+        self.progress = None;
+        self.step = CloneStep::Cloning { url: url.clone() };
+        cx.focus(&self.focus_handle);
+        cx.notify();
+
+        let directory = self.directory.clone();
+        self._task = Some(cx.spawn(|this, mut cx| async move {
+            let mut command = util::command::new_smol_command("git");
+            command
+                .args(["clone", "--progress", "--", &url])
+                .arg(&repository_dir)
+                .current_dir(&directory);
+            let result = run_with_output("git", &mut command, {
+                let this = this.clone();
+                let mut cx = cx.clone();
+                move |line| {
+                    this.update(&mut cx, |this, cx| {
+                        this.progress = Some(line.into());
+                        cx.notify();
+                    })
+                    .ok();
+                }
+            })
+            .await
+            .and_then(|_| {
+                if repository_dir.is_dir() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("git did not create {}", repository_dir.display()))
+                }
+            });
+            this.update(&mut cx, |this, cx| match result {
+                Ok(()) => {
+                    open_created_project(&this.workspace, repository_dir, cx);
+                    cx.emit(DismissEvent);
+                }
+                Err(error) => this.fail(format!("{error:#}"), cx),
+            })
+            .ok();
+        }));
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        match self.step {
+            CloneStep::Failed { .. } => {
+                self.step = CloneStep::Configure;
+                cx.focus_view(&self.url_editor);
+                cx.notify();
+            }
+            // Dropping the task kills `git clone`.
+            CloneStep::Configure | CloneStep::Cloning { .. } => cx.emit(DismissEvent),
+        }
+    }
+
+    fn render_configure(&self, cx: &mut ViewContext<Self>) -> Section {
+        Section::new()
+            .header(SectionHeader::new("Repository"))
+            .child(
+                v_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(cx.theme().colors().border)
+                            .rounded_md()
+                            .child(self.url_editor.clone()),
+                    )
+                    .child(Label::new("Clone Into").size(LabelSize::Small))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .justify_between()
+                            .child(
+                                Label::new(self.directory.to_string_lossy().into_owned())
+                                    .color(Color::Muted)
+                                    .text_ellipsis(),
+                            )
+                            .child(
+                                Button::new("choose-directory", "Choose…")
+                                    .layer(ElevationIndex::ModalSurface)
+                                    .on_click(cx.listener(|this, _, cx| this.choose_directory(cx))),
+                            ),
+                    ),
+            )
+    }
+
+    fn render_progress(&self) -> impl IntoElement {
+        div().children(self.progress.clone().map(|line| {
+            Label::new(line)
+                .size(LabelSize::XSmall)
+                .color(Color::Muted)
+                .text_ellipsis()
+        }))
+    }
+}
+
+impl EventEmitter<DismissEvent> for CloneRepositoryModal {}
+
+impl FocusableView for CloneRepositoryModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.url_editor.focus_handle(cx)
+    }
+}
+
+impl ModalView for CloneRepositoryModal {}
+
+impl Render for CloneRepositoryModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let configuring = matches!(self.step, CloneStep::Configure);
+        let section = match &self.step {
+            CloneStep::Configure => self.render_configure(cx),
+            CloneStep::Cloning { url } => Section::new()
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Icon::new(IconName::ArrowCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(Label::new(format!("Cloning {url}…")).text_ellipsis()),
+                )
+                .child(self.render_progress()),
+            CloneStep::Failed { error } => Section::new()
+                .child(Label::new(error.clone()).color(Color::Error))
+                .child(self.render_progress()),
+        };
+
+        div()
+            .track_focus(&self.focus_handle)
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .occlude()
+            .w(rems(34.))
+            .child(
+                Modal::new("clone-repository", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline("Clone Repository")
+                            .show_back_button(matches!(self.step, CloneStep::Failed { .. }))
+                            .show_dismiss_button(configuring),
+                    )
+                    .section(section)
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("cancel", "Cancel")
+                                        .layer(ElevationIndex::ModalSurface)
+                                        .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                                )
+                                .when(configuring, |this| {
+                                    this.child(
+                                        Button::new("clone", "Clone")
+                                            .style(ButtonStyle::Filled)
+                                            .layer(ElevationIndex::ModalSurface)
+                                            .on_click(cx.listener(|this, _, cx| {
+                                                this.confirm(&menu::Confirm, cx)
+                                            })),
+                                    )
+                                }),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_repository_url() {
+        let github = Some("https://github.com/zed-industries/zed.git".to_string());
+        assert_eq!(expand_repository_url("zed-industries/zed"), github);
+        assert_eq!(expand_repository_url("gh:zed-industries/zed"), github);
+        assert_eq!(
+            expand_repository_url(" github:zed-industries/zed.git "),
+            github
+        );
+        assert_eq!(
+            expand_repository_url("github.com/zed-industries/zed/"),
+            github
+        );
+        assert_eq!(
+            expand_repository_url("gl:group/subgroup/project"),
+            Some("https://gitlab.com/group/subgroup/project.git".to_string())
+        );
+        assert_eq!(
+            expand_repository_url("git@github.com:zed-industries/zed.git"),
+            Some("git@github.com:zed-industries/zed.git".to_string())
+        );
+        assert_eq!(
+            expand_repository_url("https://example.com/repo"),
+            Some("https://example.com/repo".to_string())
+        );
+        assert_eq!(expand_repository_url("zed"), None);
+        assert_eq!(expand_repository_url("gh:a/b/c"), None);
+        assert_eq!(expand_repository_url("owner//repo"), None);
+        assert_eq!(expand_repository_url("foo:bar/baz"), None);
+    }
+
+    #[test]
+    fn test_repository_name() {
+        assert_eq!(
+            repository_name("https://github.com/zed-industries/zed.git"),
+            Some("zed")
+        );
+        assert_eq!(repository_name("git@host:project"), Some("project"));
+        assert_eq!(repository_name("https://example.com/repo/"), Some("repo"));
+        assert_eq!(repository_name("https://example.com/"), Some("example.com"));
+    }
+}
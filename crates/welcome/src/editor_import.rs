@@ -0,0 +1,973 @@
+//! Detection of other editors' configuration and translation of their key
+//! bindings and theme preference into Zed's keymap and settings.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use gpui::SharedString;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use settings::parse_json_with_comments;
+use util::paths::home_dir;
+
+use crate::BaseKeymap;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SourceEditor {
+    VsCode,
+    SublimeText,
+    JetBrains,
+}
+
+impl Display for SourceEditor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceEditor::VsCode => write!(f, "VS Code"),
+            SourceEditor::SublimeText => write!(f, "Sublime Text"),
+            SourceEditor::JetBrains => write!(f, "JetBrains"),
+        }
+    }
+}
+
+impl SourceEditor {
+    pub const ALL: [Self; 3] = [Self::VsCode, Self::SublimeText, Self::JetBrains];
+
+    pub fn base_keymap(&self) -> BaseKeymap {
+        match self {
+            SourceEditor::VsCode => BaseKeymap::VSCode,
+            SourceEditor::SublimeText => BaseKeymap::SublimeText,
+            SourceEditor::JetBrains => BaseKeymap::JetBrains,
+        }
+    }
+
+    /// The directories holding the editor's user configuration, most preferred first.
+    fn config_dir_candidates(&self) -> Vec<PathBuf> {
+        let root = app_config_root();
+        match self {
+            SourceEditor::VsCode => ["Code", "Code - Insiders", "VSCodium"]
+                .iter()
+                .map(|name| root.join(name).join("User"))
+                .collect(),
+            SourceEditor::SublimeText => {
+                let name = if cfg!(target_os = "linux") {
+                    "sublime-text"
+                } else {
+                    "Sublime Text"
+                };
+                vec![root.join(name).join("Packages").join("User")]
+            }
+            SourceEditor::JetBrains => {
+                // Each product and version has its own directory, e.g. `IntelliJIdea2024.2`;
+                // pick the most recently used one.
+                let Ok(entries) = std::fs::read_dir(root.join("JetBrains")) else {
+                    return Vec::new();
+                };
+                let mut dirs = entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().join("options").is_dir())
+                    .filter_map(|entry| {
+                        let modified = entry.metadata().ok()?.modified().ok()?;
+                        Some((modified, entry.path()))
+                    })
+                    .collect::<Vec<_>>();
+                dirs.sort_by(|(a, _), (b, _)| b.cmp(a));
+                dirs.into_iter().map(|(_, path)| path).collect()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DetectedEditor {
+    pub editor: SourceEditor,
+    pub config_dir: PathBuf,
+}
+
+/// Returns the editors whose user configuration could be found on this machine.
+pub fn detect_editors() -> Vec<DetectedEditor> {
+    SourceEditor::ALL
+        .iter()
+        .filter_map(|editor| {
+            let config_dir = editor
+                .config_dir_candidates()
+                .into_iter()
+                .find(|dir| dir.is_dir())?;
+            Some(DetectedEditor {
+                editor: *editor,
+                config_dir,
+            })
+        })
+        .collect()
+}
+
+fn app_config_root() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home_dir().join("Library").join("Application Support")
+    } else if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir().join("AppData").join("Roaming"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir().join(".config"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportedBinding {
+    /// The keystrokes in Zed's format, e.g. `ctrl-k ctrl-c`.
+    pub keystrokes: String,
+    pub action: &'static str,
+    pub context: Option<&'static str>,
+    /// The command or action name in the source editor.
+    pub source_command: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EditorImport {
+    pub bindings: Vec<ImportedBinding>,
+    /// Source commands that have no Zed equivalent.
+    pub unsupported: Vec<String>,
+    pub source_theme: Option<String>,
+}
+
+/// Reads the key bindings and theme preference of a detected editor.
+pub fn load_import(detected: &DetectedEditor) -> Result<EditorImport> {
+    let dir = &detected.config_dir;
+    match detected.editor {
+        SourceEditor::VsCode => {
+            let mut import = match read_optional(&dir.join("keybindings.json"))? {
+                Some(content) => parse_vscode_keybindings(&content)?,
+                None => EditorImport::default(),
+            };
+            if let Some(content) = read_optional(&dir.join("settings.json"))? {
+                import.source_theme = settings_string(&content, "workbench.colorTheme");
+            }
+            Ok(import)
+        }
+        SourceEditor::SublimeText => {
+            let platform = if cfg!(target_os = "macos") {
+                "OSX"
+            } else if cfg!(target_os = "windows") {
+                "Windows"
+            } else {
+                "Linux"
+            };
+            let keymap_path = dir.join(format!("Default ({platform}).sublime-keymap"));
+            let mut import = match read_optional(&keymap_path)? {
+                Some(content) => parse_sublime_keymap(&content)?,
+                None => EditorImport::default(),
+            };
+            if let Some(content) = read_optional(&dir.join("Preferences.sublime-settings"))? {
+                import.source_theme = settings_string(&content, "color_scheme").map(|scheme| {
+                    // `Packages/Color Scheme - Default/Monokai.sublime-color-scheme` -> `Monokai`
+                    Path::new(&scheme)
+                        .file_stem()
+                        .map_or(scheme.clone(), |stem| stem.to_string_lossy().into_owned())
+                });
+            }
+            Ok(import)
+        }
+        SourceEditor::JetBrains => {
+            let mut import = EditorImport::default();
+            if let Ok(entries) = std::fs::read_dir(dir.join("keymaps")) {
+                for entry in entries.filter_map(|entry| entry.ok()) {
+                    let path = entry.path();
+                    if path.extension().map_or(false, |ext| ext == "xml") {
+                        let content = std::fs::read_to_string(&path)
+                            .with_context(|| format!("reading {path:?}"))?;
+                        let keymap = parse_jetbrains_keymap(&content)?;
+                        import.bindings.extend(keymap.bindings);
+                        import.unsupported.extend(keymap.unsupported);
+                    }
+                }
+            }
+            if let Some(content) = read_optional(&dir.join("options").join("laf.xml"))? {
+                let document = roxmltree::Document::parse(&content)
+                    .context("parsing JetBrains appearance settings")?;
+                let laf = document
+                    .descendants()
+                    .filter(|node| node.has_tag_name("laf"))
+                    .collect::<Vec<_>>();
+                import.source_theme = laf
+                    .iter()
+                    .find_map(|node| node.attribute("themeId"))
+                    .or_else(|| laf.iter().find_map(|node| node.attribute("class-name")))
+                    .map(str::to_string);
+            }
+            Ok(import)
+        }
+    }
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("reading {path:?}")),
+    }
+}
+
+fn settings_string(content: &str, key: &str) -> Option<String> {
+    let settings: Value = parse_json_with_comments(content).ok()?;
+    Some(settings.get(key)?.as_str()?.to_string())
+}
+
+/// VS Code command -> (Zed action, context).
+const VSCODE_COMMANDS: &[(&str, &str, Option<&str>)] = &[
+    ("workbench.action.quickOpen", "file_finder::Toggle", None),
+    (
+        "workbench.action.showCommands",
+        "command_palette::Toggle",
+        None,
+    ),
+    ("workbench.action.files.save", "workspace::Save", None),
+    ("workbench.action.files.saveAs", "workspace::SaveAs", None),
+    (
+        "workbench.action.files.newUntitledFile",
+        "workspace::NewFile",
+        None,
+    ),
+    ("workbench.action.files.openFolder", "workspace::Open", None),
+    ("workbench.action.newWindow", "workspace::NewWindow", None),
+    (
+        "workbench.action.closeActiveEditor",
+        "pane::CloseActiveItem",
+        None,
+    ),
+    (
+        "workbench.action.reopenClosedEditor",
+        "pane::ReopenClosedItem",
+        None,
+    ),
+    (
+        "workbench.action.nextEditor",
+        "pane::ActivateNextItem",
+        None,
+    ),
+    (
+        "workbench.action.previousEditor",
+        "pane::ActivatePrevItem",
+        None,
+    ),
+    ("workbench.action.splitEditor", "pane::SplitRight", None),
+    (
+        "workbench.action.toggleSidebarVisibility",
+        "workspace::ToggleLeftDock",
+        None,
+    ),
+    (
+        "workbench.action.togglePanel",
+        "workspace::ToggleBottomDock",
+        None,
+    ),
+    (
+        "workbench.action.toggleMaximizedPanel",
+        "workspace::ToggleZoom",
+        None,
+    ),
+    (
+        "workbench.action.terminal.toggleTerminal",
+        "terminal_panel::ToggleFocus",
+        None,
+    ),
+    (
+        "workbench.view.explorer",
+        "project_panel::ToggleFocus",
+        None,
+    ),
+    ("workbench.action.findInFiles", "pane::DeploySearch", None),
+    (
+        "workbench.action.showAllSymbols",
+        "project_symbols::Toggle",
+        None,
+    ),
+    ("workbench.action.gotoSymbol", "outline::Toggle", None),
+    ("workbench.action.gotoLine", "go_to_line::Toggle", None),
+    ("actions.find", "buffer_search::Deploy", None),
+    (
+        "editor.action.commentLine",
+        "editor::ToggleComments",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.formatDocument",
+        "editor::Format",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.revealDefinition",
+        "editor::GoToDefinition",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.goToTypeDefinition",
+        "editor::GoToTypeDefinition",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.goToImplementation",
+        "editor::GoToImplementation",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.goToReferences",
+        "editor::FindAllReferences",
+        Some("Editor"),
+    ),
+    ("editor.action.rename", "editor::Rename", Some("Editor")),
+    ("editor.action.showHover", "editor::Hover", Some("Editor")),
+    (
+        "editor.action.quickFix",
+        "editor::ToggleCodeActions",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.triggerSuggest",
+        "editor::ShowCompletions",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.triggerParameterHints",
+        "editor::ShowSignatureHelp",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.marker.next",
+        "editor::GoToDiagnostic",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.marker.prev",
+        "editor::GoToPrevDiagnostic",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.addSelectionToNextFindMatch",
+        "editor::SelectNext",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.copyLinesDownAction",
+        "editor::DuplicateLineDown",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.moveLinesUpAction",
+        "editor::MoveLineUp",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.moveLinesDownAction",
+        "editor::MoveLineDown",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.deleteLines",
+        "editor::DeleteLine",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.insertCursorAbove",
+        "editor::AddSelectionAbove",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.insertCursorBelow",
+        "editor::AddSelectionBelow",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.insertCursorAtEndOfEachLineSelected",
+        "editor::SplitSelectionIntoLines",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.smartSelect.expand",
+        "editor::SelectLargerSyntaxNode",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.joinLines",
+        "editor::JoinLines",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.transformToUppercase",
+        "editor::ConvertToUpperCase",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.transformToLowercase",
+        "editor::ConvertToLowerCase",
+        Some("Editor"),
+    ),
+    (
+        "editor.action.sortLinesAscending",
+        "editor::SortLinesCaseSensitive",
+        Some("Editor"),
+    ),
+    ("editor.fold", "editor::Fold", Some("Editor")),
+    ("editor.unfold", "editor::UnfoldLines", Some("Editor")),
+    ("editor.foldAll", "editor::FoldAll", Some("Editor")),
+    ("expandLineSelection", "editor::SelectLine", Some("Editor")),
+];
+
+/// Sublime Text command -> (Zed action, context).
+const SUBLIME_COMMANDS: &[(&str, &str, Option<&str>)] = &[
+    (
+        "show_overlay:command_palette",
+        "command_palette::Toggle",
+        None,
+    ),
+    ("show_overlay:goto", "file_finder::Toggle", None),
+    ("save", "workspace::Save", None),
+    ("prompt_save_as", "workspace::SaveAs", None),
+    ("new_file", "workspace::NewFile", None),
+    ("new_window", "workspace::NewWindow", None),
+    ("close", "pane::CloseActiveItem", None),
+    ("reopen_last_file", "pane::ReopenClosedItem", None),
+    ("next_view", "pane::ActivateNextItem", None),
+    ("prev_view", "pane::ActivatePrevItem", None),
+    ("toggle_side_bar", "workspace::ToggleLeftDock", None),
+    ("goto_symbol_in_project", "project_symbols::Toggle", None),
+    ("show_panel:find", "buffer_search::Deploy", None),
+    ("show_panel:find_in_files", "pane::DeploySearch", None),
+    ("toggle_comment", "editor::ToggleComments", Some("Editor")),
+    ("goto_definition", "editor::GoToDefinition", Some("Editor")),
+    ("find_under_expand", "editor::SelectNext", Some("Editor")),
+    (
+        "duplicate_line",
+        "editor::DuplicateLineDown",
+        Some("Editor"),
+    ),
+    ("swap_line_up", "editor::MoveLineUp", Some("Editor")),
+    ("swap_line_down", "editor::MoveLineDown", Some("Editor")),
+    ("expand_selection", "editor::SelectLine", Some("Editor")),
+    (
+        "split_selection_into_lines",
+        "editor::SplitSelectionIntoLines",
+        Some("Editor"),
+    ),
+    ("join_lines", "editor::JoinLines", Some("Editor")),
+    ("upper_case", "editor::ConvertToUpperCase", Some("Editor")),
+    ("lower_case", "editor::ConvertToLowerCase", Some("Editor")),
+    (
+        "sort_lines",
+        "editor::SortLinesCaseSensitive",
+        Some("Editor"),
+    ),
+    ("fold", "editor::Fold", Some("Editor")),
+    ("unfold", "editor::UnfoldLines", Some("Editor")),
+    ("auto_complete", "editor::ShowCompletions", Some("Editor")),
+];
+
+/// JetBrains action id -> (Zed action, context).
+const JETBRAINS_ACTIONS: &[(&str, &str, Option<&str>)] = &[
+    ("GotoFile", "file_finder::Toggle", None),
+    ("GotoAction", "command_palette::Toggle", None),
+    ("SaveAll", "workspace::SaveAll", None),
+    ("CloseContent", "pane::CloseActiveItem", None),
+    ("ReopenClosedTab", "pane::ReopenClosedItem", None),
+    ("NextTab", "pane::ActivateNextItem", None),
+    ("PreviousTab", "pane::ActivatePrevItem", None),
+    (
+        "ActivateProjectToolWindow",
+        "project_panel::ToggleFocus",
+        None,
+    ),
+    (
+        "ActivateTerminalToolWindow",
+        "terminal_panel::ToggleFocus",
+        None,
+    ),
+    ("FindInPath", "pane::DeploySearch", None),
+    ("GotoSymbol", "project_symbols::Toggle", None),
+    ("FileStructurePopup", "outline::Toggle", None),
+    ("GotoLine", "go_to_line::Toggle", None),
+    ("Find", "buffer_search::Deploy", None),
+    ("HideAllWindows", "workspace::CloseAllDocks", None),
+    (
+        "CommentByLineComment",
+        "editor::ToggleComments",
+        Some("Editor"),
+    ),
+    ("ReformatCode", "editor::Format", Some("Editor")),
+    ("GotoDeclaration", "editor::GoToDefinition", Some("Editor")),
+    (
+        "GotoTypeDeclaration",
+        "editor::GoToTypeDefinition",
+        Some("Editor"),
+    ),
+    (
+        "GotoImplementation",
+        "editor::GoToImplementation",
+        Some("Editor"),
+    ),
+    ("FindUsages", "editor::FindAllReferences", Some("Editor")),
+    ("RenameElement", "editor::Rename", Some("Editor")),
+    ("QuickJavaDoc", "editor::Hover", Some("Editor")),
+    (
+        "ShowIntentionActions",
+        "editor::ToggleCodeActions",
+        Some("Editor"),
+    ),
+    ("CodeCompletion", "editor::ShowCompletions", Some("Editor")),
+    ("ParameterInfo", "editor::ShowSignatureHelp", Some("Editor")),
+    ("GotoNextError", "editor::GoToDiagnostic", Some("Editor")),
+    (
+        "GotoPreviousError",
+        "editor::GoToPrevDiagnostic",
+        Some("Editor"),
+    ),
+    ("SelectNextOccurrence", "editor::SelectNext", Some("Editor")),
+    (
+        "EditorDuplicate",
+        "editor::DuplicateLineDown",
+        Some("Editor"),
+    ),
+    ("MoveLineUp", "editor::MoveLineUp", Some("Editor")),
+    ("MoveLineDown", "editor::MoveLineDown", Some("Editor")),
+    ("EditorDeleteLine", "editor::DeleteLine", Some("Editor")),
+    (
+        "EditorSelectWord",
+        "editor::SelectLargerSyntaxNode",
+        Some("Editor"),
+    ),
+    ("EditorJoinLines", "editor::JoinLines", Some("Editor")),
+    ("CollapseRegion", "editor::Fold", Some("Editor")),
+    ("ExpandRegion", "editor::UnfoldLines", Some("Editor")),
+    ("CollapseAllRegions", "editor::FoldAll", Some("Editor")),
+];
+
+fn lookup(
+    table: &[(&str, &'static str, Option<&'static str>)],
+    command: &str,
+) -> Option<(&'static str, Option<&'static str>)> {
+    table
+        .iter()
+        .find(|(source, _, _)| *source == command)
+        .map(|(_, action, context)| (*action, *context))
+}
+
+fn push_binding(
+    import: &mut EditorImport,
+    table: &[(&str, &'static str, Option<&'static str>)],
+    command: &str,
+    keystrokes: Option<String>,
+) {
+    match (lookup(table, command), keystrokes) {
+        (Some((action, context)), Some(keystrokes)) => import.bindings.push(ImportedBinding {
+            keystrokes,
+            action,
+            context,
+            source_command: command.to_string(),
+        }),
+        _ => {
+            if !import
+                .unsupported
+                .iter()
+                .any(|unsupported| unsupported == command)
+            {
+                import.unsupported.push(command.to_string());
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VsCodeKeybinding {
+    key: String,
+    command: String,
+}
+
+pub fn parse_vscode_keybindings(content: &str) -> Result<EditorImport> {
+    let keybindings: Vec<VsCodeKeybinding> =
+        parse_json_with_comments(content).context("parsing VS Code keybindings")?;
+    let mut import = EditorImport::default();
+    for keybinding in keybindings {
+        // A leading `-` removes one of VS Code's default bindings, which has no
+        // counterpart in a Zed user keymap.
+        if keybinding.command.is_empty() || keybinding.command.starts_with('-') {
+            continue;
+        }
+        let keystrokes = convert_keystrokes(keybinding.key.split_whitespace());
+        push_binding(
+            &mut import,
+            VSCODE_COMMANDS,
+            &keybinding.command,
+            keystrokes,
+        );
+    }
+    Ok(import)
+}
+
+#[derive(Deserialize)]
+struct SublimeKeybinding {
+    keys: Vec<String>,
+    command: String,
+    #[serde(default)]
+    args: Value,
+}
+
+pub fn parse_sublime_keymap(content: &str) -> Result<EditorImport> {
+    let keybindings: Vec<SublimeKeybinding> =
+        parse_json_with_comments(content).context("parsing Sublime Text keymap")?;
+    let mut import = EditorImport::default();
+    for keybinding in keybindings {
+        // `show_overlay` opens either the command palette or Goto Anything, and
+        // `show_panel` one of the find panels or the console.
+        let argument = match keybinding.command.as_str() {
+            "show_overlay" => keybinding.args.get("overlay"),
+            "show_panel" => keybinding.args.get("panel"),
+            _ => None,
+        };
+        let command = match argument.and_then(Value::as_str) {
+            Some(argument) => format!("{}:{argument}", keybinding.command),
+            None => keybinding.command,
+        };
+        let keystrokes = convert_keystrokes(keybinding.keys.iter().map(String::as_str));
+        push_binding(&mut import, SUBLIME_COMMANDS, &command, keystrokes);
+    }
+    Ok(import)
+}
+
+pub fn parse_jetbrains_keymap(content: &str) -> Result<EditorImport> {
+    let document = roxmltree::Document::parse(content).context("parsing JetBrains keymap")?;
+    let mut import = EditorImport::default();
+    for action in document
+        .descendants()
+        .filter(|node| node.has_tag_name("action"))
+    {
+        let Some(action_id) = action.attribute("id") else {
+            continue;
+        };
+        for shortcut in action
+            .children()
+            .filter(|node| node.has_tag_name("keyboard-shortcut"))
+        {
+            let keystrokes = ["first-keystroke", "second-keystroke"]
+                .iter()
+                .filter_map(|attribute| shortcut.attribute(*attribute))
+                .map(convert_jetbrains_keystroke)
+                .collect::<Option<Vec<_>>>()
+                .filter(|keystrokes| !keystrokes.is_empty())
+                .map(|keystrokes| keystrokes.join(" "));
+            push_binding(&mut import, JETBRAINS_ACTIONS, action_id, keystrokes);
+        }
+    }
+    Ok(import)
+}
+
+/// Converts keystrokes such as `ctrl+shift+p` into Zed's `ctrl-shift-p`.
+fn convert_keystrokes<'a>(keystrokes: impl Iterator<Item = &'a str>) -> Option<String> {
+    let keystrokes = keystrokes
+        .map(|keystroke| {
+            // `ctrl++` binds the plus key itself.
+            let (modifiers, key) = match keystroke.strip_suffix("++") {
+                Some(modifiers) => (modifiers, "+"),
+                None => keystroke.rsplit_once('+').unwrap_or(("", keystroke)),
+            };
+            let mut parts = modifiers
+                .split('+')
+                .filter(|modifier| !modifier.is_empty())
+                .map(|modifier| convert_modifier(&modifier.to_lowercase()))
+                .collect::<Option<Vec<_>>>()?;
+            parts.push(convert_key(&key.to_lowercase())?);
+            Some(parts.join("-"))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!keystrokes.is_empty()).then(|| keystrokes.join(" "))
+}
+
+/// Converts a JetBrains keystroke such as `control shift N` into `ctrl-shift-n`.
+fn convert_jetbrains_keystroke(keystroke: &str) -> Option<String> {
+    let mut parts = keystroke.split_whitespace().collect::<Vec<_>>();
+    let key = parts.pop()?;
+    let mut converted = parts
+        .into_iter()
+        .map(|modifier| convert_modifier(&modifier.to_lowercase()))
+        .collect::<Option<Vec<_>>>()?;
+    let lowercase = key.to_lowercase();
+    let key = match key {
+        "BACK_SPACE" => "backspace",
+        "OPEN_BRACKET" => "[",
+        "CLOSE_BRACKET" => "]",
+        "SLASH" => "/",
+        "BACK_SLASH" => "\\",
+        "COMMA" => ",",
+        "PERIOD" => ".",
+        "SEMICOLON" => ";",
+        "QUOTE" => "'",
+        "MINUS" => "-",
+        "EQUALS" => "=",
+        "PAGE_UP" => "pageup",
+        "PAGE_DOWN" => "pagedown",
+        "ESCAPE" => "escape",
+        "BACK_QUOTE" => "`",
+        _ => &lowercase,
+    };
+    converted.push(convert_key(key)?);
+    Some(converted.join("-"))
+}
+
+fn convert_modifier(modifier: &str) -> Option<String> {
+    let modifier = match modifier {
+        "ctrl" | "control" => "ctrl",
+        "shift" => "shift",
+        "alt" | "option" => "alt",
+        "cmd" | "super" | "meta" | "win" => "cmd",
+        _ => return None,
+    };
+    Some(modifier.to_string())
+}
+
+fn convert_key(key: &str) -> Option<String> {
+    let key = match key {
+        "" => return None,
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "ins" => "insert",
+        "page_up" => "pageup",
+        "page_down" => "pagedown",
+        "forward_slash" => "/",
+        "numpad_add" => "+",
+        "numpad_subtract" => "-",
+        key if key.chars().count() == 1 => key,
+        key @ ("enter" | "tab" | "space" | "backspace" | "delete" | "insert" | "escape"
+        | "home" | "end" | "pageup" | "pagedown" | "up" | "down" | "left" | "right") => key,
+        key if key.starts_with('f') && key[1..].parse::<u8>().map_or(false, |n| n >= 1) => key,
+        _ => return None,
+    };
+    Some(key.to_string())
+}
+
+/// Picks the Zed theme closest to the source editor's theme: one with the same
+/// name if it exists, otherwise the default light or dark theme.
+pub fn matching_theme(source_theme: &str, available: &[SharedString]) -> SharedString {
+    let normalized = |name: &str| {
+        name.to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+    };
+    let source = normalized(source_theme);
+    if let Some(theme) = available.iter().find(|theme| normalized(theme) == source) {
+        return theme.clone();
+    }
+    if source.contains("light") {
+        "One Light".into()
+    } else {
+        "One Dark".into()
+    }
+}
+
+/// Appends key bindings to the contents of a user keymap file, grouped into one
+/// block per context. The file is parsed and written back as a whole, so its
+/// comments are not kept.
+pub fn append_to_keymap(content: &str, bindings: &[ImportedBinding]) -> Result<String> {
+    let mut blocks = BTreeMap::<Option<&str>, serde_json::Map<String, Value>>::new();
+    for binding in bindings {
+        blocks
+            .entry(binding.context)
+            .or_default()
+            .insert(binding.keystrokes.clone(), json!(binding.action));
+    }
+    if blocks.is_empty() {
+        return Ok(content.to_string());
+    }
+
+    let mut keymap: Vec<Value> = if content.trim().is_empty() {
+        Vec::new()
+    } else {
+        parse_json_with_comments(content).context("parsing the keymap file")?
+    };
+    keymap.extend(blocks.into_iter().map(|(context, bindings)| match context {
+        Some(context) => json!({ "context": context, "bindings": bindings }),
+        None => json!({ "bindings": bindings }),
+    }));
+    let mut content = serde_json::to_string_pretty(&keymap)?;
+    content.push('\n');
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_keystrokes() {
+        assert_eq!(
+            convert_keystrokes(["ctrl+shift+p"].into_iter()).as_deref(),
+            Some("ctrl-shift-p")
+        );
+        assert_eq!(
+            convert_keystrokes("ctrl+k ctrl+c".split_whitespace()).as_deref(),
+            Some("ctrl-k ctrl-c")
+        );
+        assert_eq!(
+            convert_keystrokes(["super+alt+Up"].into_iter()).as_deref(),
+            Some("cmd-alt-up")
+        );
+        assert_eq!(
+            convert_keystrokes(["ctrl++"].into_iter()).as_deref(),
+            Some("ctrl-+")
+        );
+        assert_eq!(convert_keystrokes(["hyper+x"].into_iter()), None);
+        assert_eq!(
+            convert_jetbrains_keystroke("control shift N").as_deref(),
+            Some("ctrl-shift-n")
+        );
+        assert_eq!(
+            convert_jetbrains_keystroke("meta BACK_SPACE").as_deref(),
+            Some("cmd-backspace")
+        );
+    }
+
+    #[test]
+    fn test_parse_vscode_keybindings() {
+        let import = parse_vscode_keybindings(
+            r#"
+            // Place your key bindings in this file
+            [
+                { "key": "ctrl+shift+p", "command": "workbench.action.quickOpen" },
+                { "key": "ctrl+k ctrl+c", "command": "editor.action.commentLine", "when": "editorTextFocus" },
+                { "key": "ctrl+k", "command": "-editor.action.deleteLines" },
+                { "key": "ctrl+alt+g", "command": "gitlens.showQuickCommitFileDetails" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            import.bindings,
+            vec![
+                ImportedBinding {
+                    keystrokes: "ctrl-shift-p".into(),
+                    action: "file_finder::Toggle",
+                    context: None,
+                    source_command: "workbench.action.quickOpen".into(),
+                },
+                ImportedBinding {
+                    keystrokes: "ctrl-k ctrl-c".into(),
+                    action: "editor::ToggleComments",
+                    context: Some("Editor"),
+                    source_command: "editor.action.commentLine".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            import.unsupported,
+            vec!["gitlens.showQuickCommitFileDetails"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sublime_keymap() {
+        let import = parse_sublime_keymap(
+            r#"[
+                { "keys": ["super+p"], "command": "show_overlay", "args": {"overlay": "goto", "show_files": true} },
+                { "keys": ["ctrl+shift+d"], "command": "duplicate_line" },
+                { "keys": ["ctrl+f"], "command": "show_panel", "args": {"panel": "find", "reverse": false} },
+                { "keys": ["ctrl+`"], "command": "show_panel", "args": {"panel": "console"} },
+            ]"#,
+        )
+        .unwrap();
+        let bindings = import
+            .bindings
+            .iter()
+            .map(|binding| (binding.keystrokes.as_str(), binding.action))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bindings,
+            vec![
+                ("cmd-p", "file_finder::Toggle"),
+                ("ctrl-shift-d", "editor::DuplicateLineDown"),
+                ("ctrl-f", "buffer_search::Deploy")
+            ]
+        );
+        assert_eq!(import.unsupported, vec!["show_panel:console"]);
+    }
+
+    #[test]
+    fn test_parse_jetbrains_keymap() {
+        let import = parse_jetbrains_keymap(
+            r#"<keymap version="1" name="Custom" parent="$default">
+              <action id="GotoFile">
+                <keyboard-shortcut first-keystroke="control shift N" />
+              </action>
+              <action id="CommentByLineComment">
+                <keyboard-shortcut first-keystroke="control K" second-keystroke="control C" />
+              </action>
+              <action id="SaveAll">
+                <keyboard-shortcut first-keystroke="control S" />
+              </action>
+              <action id="Vcs.Push" />
+            </keymap>"#,
+        )
+        .unwrap();
+        let bindings = import
+            .bindings
+            .iter()
+            .map(|binding| (binding.keystrokes.as_str(), binding.action))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bindings,
+            vec![
+                ("ctrl-shift-n", "file_finder::Toggle"),
+                ("ctrl-k ctrl-c", "editor::ToggleComments"),
+                ("ctrl-s", "workspace::SaveAll")
+            ]
+        );
+
+        assert!(parse_jetbrains_keymap("<keymap><action id=\"GotoFile\"></keymap>").is_err());
+    }
+
+    #[test]
+    fn test_matching_theme() {
+        let available: [SharedString; 3] =
+            ["One Dark".into(), "One Light".into(), "Gruvbox Dark".into()];
+        assert_eq!(
+            matching_theme("gruvbox-dark", &available).as_ref(),
+            "Gruvbox Dark"
+        );
+        assert_eq!(
+            matching_theme("Default Light Modern", &available).as_ref(),
+            "One Light"
+        );
+        assert_eq!(matching_theme("Darcula", &available).as_ref(), "One Dark");
+    }
+
+    #[test]
+    fn test_append_to_keymap() {
+        let binding = ImportedBinding {
+            keystrokes: "ctrl-shift-p".into(),
+            action: "file_finder::Toggle",
+            context: None,
+            source_command: "workbench.action.quickOpen".into(),
+        };
+        let appended = "[\n  {\n    \"bindings\": {\n      \"ctrl-shift-p\": \"file_finder::Toggle\"\n    }\n  }\n]\n";
+        assert_eq!(append_to_keymap("", &[binding.clone()]).unwrap(), appended);
+        assert_eq!(
+            append_to_keymap("// My keymap\n[\n]\n", &[binding.clone()]).unwrap(),
+            appended
+        );
+        assert_eq!(
+            append_to_keymap(
+                "[\n  { \"bindings\": {} } // existing\n]\n// The end ]\n",
+                &[binding.clone()]
+            )
+            .unwrap(),
+            "[\n  {\n    \"bindings\": {}\n  },\n  {\n    \"bindings\": {\n      \"ctrl-shift-p\": \"file_finder::Toggle\"\n    }\n  }\n]\n"
+        );
+        assert!(
+            append_to_keymap("[\n  { \"bindings\": ", &[binding]).is_err(),
+            "An unreadable keymap should not be overwritten"
+        );
+    }
+}
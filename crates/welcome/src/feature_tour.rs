@@ -0,0 +1,411 @@
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{
+    actions, Action, AppContext, DismissEvent, EventEmitter, Render, View, ViewContext, WeakView,
+};
+use ui::{prelude::*, KeyBinding};
+use workspace::{notifications::NotificationId, Workspace};
+
+actions!(welcome, [StartFeatureTour]);
+
+const SKIPPED_AT_STEP_KEY: &str = "feature_tour_skipped_at_step";
+
+struct TourStep {
+    title: &'static str,
+    description: &'static str,
+    /// The action demonstrated by this step, by name, so that the welcome
+    /// crate doesn't need to depend on every feature it points to.
+    action: &'static str,
+    /// The action's arguments as JSON.
+    action_data: Option<&'static str>,
+    /// The persistent name of the panel whose status bar button is highlighted during this step.
+    panel: Option<&'static str>,
+}
+
+const TOUR_STEPS: &[TourStep] = &[
+    TourStep {
+        title: "Find Files",
+        description: "Jump to any file in your project by typing a few letters of its path.",
+        action: "file_finder::Toggle",
+        action_data: None,
+        panel: None,
+    },
+    TourStep {
+        title: "Command Palette",
+        description: "Every command in Zed is one search away, along with its key binding.",
+        action: "command_palette::Toggle",
+        action_data: None,
+        panel: None,
+    },
+    TourStep {
+        title: "Tasks",
+        description: "Run builds, tests and scripts from `tasks.json` or detected from your code, \
+            with the output in a terminal.",
+        action: "task::Spawn",
+        action_data: Some("{}"),
+        panel: None,
+    },
+    TourStep {
+        title: "Assistant",
+        description: "Chat with a language model about your code, or transform a selection \
+            with an inline prompt.",
+        action: "assistant::ToggleFocus",
+        action_data: None,
+        panel: Some("AssistantPanel"),
+    },
+    TourStep {
+        title: "Collaboration",
+        description: "Share your project, talk in channels and edit together in real time.",
+        action: "collab_panel::ToggleFocus",
+        action_data: None,
+        panel: Some("CollabPanel"),
+    },
+];
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(|workspace, _: &StartFeatureTour, cx| start(workspace, cx));
+    })
+    .detach();
+}
+
+pub fn start(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let steps = TOUR_STEPS
+        .iter()
+        .filter_map(|step| {
+            let data = step
+                .action_data
+                .and_then(|data| serde_json::from_str(data).ok());
+            let action = cx.build_action(step.action, data).ok()?;
+            Some((step, action))
+        })
+        .collect::<Vec<_>>();
+    show(workspace, steps, skipped_at_step(), cx);
+}
+
+/// The step the tour was skipped at, where it picks up when it's started again.
+fn skipped_at_step() -> usize {
+    KEY_VALUE_STORE
+        .read_kvp(SKIPPED_AT_STEP_KEY)
+        .ok()
+        .flatten()
+        .and_then(|step| step.parse().ok())
+        .unwrap_or(0)
+}
+
+fn show(
+    workspace: &mut Workspace,
+    steps: Vec<(&'static TourStep, Box<dyn Action>)>,
+    current_step: usize,
+    cx: &mut ViewContext<Workspace>,
+) -> Option<View<FeatureTour>> {
+    if steps.is_empty() {
+        return None;
+    }
+    let current_step = current_step.min(steps.len() - 1);
+    let panel = steps[current_step].0.panel;
+    let workspace_handle = workspace.weak_handle();
+    let tour = cx.new_view(|cx| {
+        // Whichever way the tour is closed, stop pointing at the UI it was showing, unless
+        // it was replaced by a tour that was started again.
+        cx.on_release(|tour: &mut FeatureTour, window, cx| {
+            let workspace = tour.workspace.clone();
+            cx.defer(move |cx| {
+                window
+                    .update(cx, |_, cx| {
+                        workspace.update(cx, |workspace, cx| {
+                            let id = NotificationId::unique::<FeatureTour>();
+                            if !workspace.has_notification(&id) {
+                                workspace.highlight_panel_button(None, cx)
+                            }
+                        })
+                    })
+                    .ok();
+            });
+        })
+        .detach();
+        FeatureTour {
+            workspace: workspace_handle,
+            steps,
+            current_step,
+        }
+    });
+    workspace.show_notification(NotificationId::unique::<FeatureTour>(), cx, |_| {
+        tour.clone()
+    });
+    workspace.highlight_panel_button(panel, cx);
+    Some(tour)
+}
+
+/// A card walking through Zed's core features one at a time. It lives in the
+/// notification area, so that the UI it demonstrates can open next to it, and
+/// highlights the status bar button of the panel a step is about.
+pub struct FeatureTour {
+    workspace: WeakView<Workspace>,
+    steps: Vec<(&'static TourStep, Box<dyn Action>)>,
+    current_step: usize,
+}
+
+impl FeatureTour {
+    fn go_to_step(&mut self, step: usize, cx: &mut ViewContext<Self>) {
+        self.current_step = step.min(self.steps.len() - 1);
+        let panel = self.steps[self.current_step].0.panel;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.highlight_panel_button(panel, cx)
+            })
+            .ok();
+        cx.notify();
+    }
+
+    fn show_me(&mut self, cx: &mut ViewContext<Self>) {
+        let (_, action) = &self.steps[self.current_step];
+        cx.dispatch_action(action.boxed_clone());
+    }
+
+    fn skip(&mut self, cx: &mut ViewContext<Self>) {
+        let step = self.current_step;
+        db::write_and_log(cx, move || {
+            KEY_VALUE_STORE.write_kvp(SKIPPED_AT_STEP_KEY.to_string(), step.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn finish(&mut self, cx: &mut ViewContext<Self>) {
+        db::write_and_log(cx, || {
+            KEY_VALUE_STORE.delete_kvp(SKIPPED_AT_STEP_KEY.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for FeatureTour {}
+
+impl Render for FeatureTour {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let (step, action) = &self.steps[self.current_step];
+        let is_first = self.current_step == 0;
+        let is_last = self.current_step + 1 == self.steps.len();
+        let key_binding = KeyBinding::for_action(action.as_ref(), cx);
+
+        v_flex()
+            .elevation_3(cx)
+            .p_4()
+            .gap_2()
+            .w(rems(24.))
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        Label::new(format!(
+                            "Tour · {} of {}",
+                            self.current_step + 1,
+                            self.steps.len()
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        IconButton::new("skip-tour", IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(|this, _, cx| this.skip(cx))),
+                    ),
+            )
+            .child(Headline::new(step.title).size(HeadlineSize::Small))
+            .child(Label::new(step.description).color(Color::Muted))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new("Shortcut").size(LabelSize::Small))
+                    .child(match key_binding {
+                        Some(key_binding) => key_binding.into_any_element(),
+                        None => Label::new("Not bound, run it from the command palette")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .into_any_element(),
+                    }),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        Button::new("show-me", "Show Me")
+                            .icon(IconName::Eye)
+                            .icon_size(IconSize::XSmall)
+                            .icon_position(IconPosition::Start)
+                            .on_click(cx.listener(|this, _, cx| this.show_me(cx))),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .when(!is_first, |this| {
+                                this.child(Button::new("previous-step", "Back").on_click(
+                                    cx.listener(|this, _, cx| {
+                                        this.go_to_step(this.current_step - 1, cx)
+                                    }),
+                                ))
+                            })
+                            .child(if is_last {
+                                Button::new("finish-tour", "Finish")
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(cx.listener(|this, _, cx| this.finish(cx)))
+                            } else {
+                                Button::new("next-step", "Next")
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(cx.listener(|this, _, cx| {
+                                        this.go_to_step(this.current_step + 1, cx)
+                                    }))
+                            }),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use workspace::AppState;
+
+    actions!(feature_tour_test, [First, Second, Third]);
+
+    const TEST_STEPS: &[TourStep] = &[
+        TourStep {
+            title: "First",
+            description: "Not about a panel.",
+            action: "feature_tour_test::First",
+            action_data: None,
+            panel: None,
+        },
+        TourStep {
+            title: "Second",
+            description: "About the assistant panel.",
+            action: "feature_tour_test::Second",
+            action_data: None,
+            panel: Some("AssistantPanel"),
+        },
+        TourStep {
+            title: "Third",
+            description: "About the collab panel.",
+            action: "feature_tour_test::Third",
+            action_data: None,
+            panel: Some("CollabPanel"),
+        },
+    ];
+
+    fn test_steps() -> Vec<(&'static TourStep, Box<dyn Action>)> {
+        let actions: [Box<dyn Action>; 3] = [Box::new(First), Box::new(Second), Box::new(Third)];
+        TEST_STEPS.iter().zip(actions).collect()
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (View<Workspace>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        cx.add_window_view(|cx| Workspace::test_new(project, cx))
+    }
+
+    /// The panel highlighted in every dock, which is the same for all of them.
+    fn highlighted_panel(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Option<&'static str> {
+        workspace.update(cx, |workspace, cx| {
+            let highlighted = [
+                workspace.left_dock(),
+                workspace.bottom_dock(),
+                workspace.right_dock(),
+            ]
+            .map(|dock| dock.read(cx).highlighted_panel());
+            assert!(highlighted.iter().all(|panel| *panel == highlighted[0]));
+            highlighted[0]
+        })
+    }
+
+    #[gpui::test]
+    async fn test_tour_steps(cx: &mut TestAppContext) {
+        let (workspace, cx) = init_test(cx).await;
+        let tour = workspace
+            .update(cx, |workspace, cx| show(workspace, test_steps(), 0, cx))
+            .unwrap();
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 0);
+        assert_eq!(highlighted_panel(&workspace, cx), None);
+
+        // Each step highlights the status bar button of the panel it's about, if any.
+        tour.update(cx, |tour, cx| tour.go_to_step(1, cx));
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 1);
+        assert_eq!(highlighted_panel(&workspace, cx), Some("AssistantPanel"));
+        tour.update(cx, |tour, cx| tour.go_to_step(2, cx));
+        assert_eq!(highlighted_panel(&workspace, cx), Some("CollabPanel"));
+        tour.update(cx, |tour, cx| tour.go_to_step(3, cx));
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 2);
+        tour.update(cx, |tour, cx| tour.go_to_step(0, cx));
+        assert_eq!(highlighted_panel(&workspace, cx), None);
+        drop(tour);
+
+        // A tour started again replaces the open one, and one started past its last step starts
+        // at the last one.
+        let tour = workspace
+            .update(cx, |workspace, cx| show(workspace, test_steps(), 5, cx))
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 2);
+        assert_eq!(highlighted_panel(&workspace, cx), Some("CollabPanel"));
+        assert_eq!(
+            workspace.update(cx, |workspace, _| workspace.notification_ids()),
+            [NotificationId::unique::<FeatureTour>()]
+        );
+
+        // Closing the tour in any way stops the highlighting.
+        workspace.update(cx, |workspace, cx| {
+            workspace.dismiss_notification(&NotificationId::unique::<FeatureTour>(), cx)
+        });
+        drop(tour);
+        cx.run_until_parked();
+        assert!(workspace.update(cx, |workspace, _| workspace.notification_ids().is_empty()));
+        assert_eq!(highlighted_panel(&workspace, cx), None);
+    }
+
+    #[gpui::test]
+    async fn test_resuming_skipped_tour(cx: &mut TestAppContext) {
+        let (workspace, cx) = init_test(cx).await;
+        let tour = workspace
+            .update(cx, |workspace, cx| {
+                show(workspace, test_steps(), skipped_at_step(), cx)
+            })
+            .unwrap();
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 0);
+
+        tour.update(cx, |tour, cx| {
+            tour.go_to_step(1, cx);
+            tour.skip(cx);
+        });
+        drop(tour);
+        cx.run_until_parked();
+        assert!(workspace.update(cx, |workspace, _| workspace.notification_ids().is_empty()));
+        assert_eq!(highlighted_panel(&workspace, cx), None);
+        assert_eq!(skipped_at_step(), 1);
+
+        // Starting the tour again picks up where it was skipped, until it's finished.
+        let tour = workspace
+            .update(cx, |workspace, cx| {
+                show(workspace, test_steps(), skipped_at_step(), cx)
+            })
+            .unwrap();
+        assert_eq!(tour.update(cx, |tour, _| tour.current_step), 1);
+        assert_eq!(highlighted_panel(&workspace, cx), Some("AssistantPanel"));
+
+        tour.update(cx, |tour, cx| {
+            tour.go_to_step(2, cx);
+            tour.finish(cx);
+        });
+        drop(tour);
+        cx.run_until_parked();
+        assert!(workspace.update(cx, |workspace, _| workspace.notification_ids().is_empty()));
+        assert_eq!(skipped_at_step(), 0);
+    }
+}
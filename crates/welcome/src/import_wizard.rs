@@ -0,0 +1,446 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    ScrollHandle, SharedString, Task, ViewContext, WeakView,
+};
+use project::Fs;
+use settings::update_settings_file;
+use theme::{Appearance, ThemeRegistry, ThemeSettings};
+use ui::{
+    prelude::*, CheckboxWithLabel, ElevationIndex, ListItem, ListItemSpacing, Modal, ModalFooter,
+    ModalHeader, Section, SectionHeader,
+};
+use util::ResultExt;
+use workspace::{
+    notifications::{NotificationId, NotifyTaskExt},
+    ModalView, Toast, Workspace,
+};
+
+use crate::{
+    editor_import::{
+        append_to_keymap, detect_editors, load_import, matching_theme, DetectedEditor, EditorImport,
+    },
+    BaseKeymap,
+};
+
+actions!(welcome, [ImportFromOtherEditor]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(|workspace, _: &ImportFromOtherEditor, cx| {
+            toggle(workspace, None, cx)
+        });
+    })
+    .detach();
+}
+
+pub fn toggle(
+    workspace: &mut Workspace,
+    editors: Option<Vec<DetectedEditor>>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let weak_workspace = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| ImportWizard::new(weak_workspace, fs, editors, cx));
+}
+
+/// Opens the wizard on first run, but only when there is something to import.
+pub fn open_if_editors_detected(cx: &mut ViewContext<Workspace>) {
+    cx.spawn(|workspace, mut cx| async move {
+        let editors = cx
+            .background_executor()
+            .spawn(async { detect_editors() })
+            .await;
+        if editors.is_empty() {
+            return;
+        }
+        workspace
+            .update(&mut cx, |workspace, cx| {
+                toggle(workspace, Some(editors), cx)
+            })
+            .log_err();
+    })
+    .detach();
+}
+
+enum WizardStep {
+    Detecting,
+    ChooseEditor(Vec<DetectedEditor>),
+    Loading(DetectedEditor),
+    Review(ImportReview),
+}
+
+struct ImportReview {
+    editor: DetectedEditor,
+    import: EditorImport,
+    included_bindings: Vec<bool>,
+    theme: Option<SharedString>,
+    import_theme: bool,
+    set_base_keymap: bool,
+}
+
+pub struct ImportWizard {
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    detected_editors: Vec<DetectedEditor>,
+    step: WizardStep,
+    error: Option<SharedString>,
+    _task: Option<Task<()>>,
+}
+
+impl ImportWizard {
+    fn new(
+        workspace: WeakView<Workspace>,
+        fs: Arc<dyn Fs>,
+        editors: Option<Vec<DetectedEditor>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            workspace,
+            fs,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            detected_editors: Vec::new(),
+            step: WizardStep::Detecting,
+            error: None,
+            _task: None,
+        };
+        match editors {
+            Some(editors) => this.set_detected_editors(editors),
+            None => {
+                this._task = Some(cx.spawn(|this, mut cx| async move {
+                    let editors = cx
+                        .background_executor()
+                        .spawn(async { detect_editors() })
+                        .await;
+                    this.update(&mut cx, |this, cx| {
+                        this.set_detected_editors(editors);
+                        cx.notify();
+                    })
+                    .ok();
+                }));
+            }
+        }
+        this
+    }
+
+    fn set_detected_editors(&mut self, editors: Vec<DetectedEditor>) {
+        self.detected_editors = editors.clone();
+        self.step = WizardStep::ChooseEditor(editors);
+    }
+
+    fn choose_editor(&mut self, editor: DetectedEditor, cx: &mut ViewContext<Self>) {
+        self.error = None;
+        self.step = WizardStep::Loading(editor.clone());
+        let available_themes = ThemeRegistry::global(cx).list_names();
+        self._task = Some(cx.spawn(|this, mut cx| async move {
+            let import = cx
+                .background_executor()
+                .spawn({
+                    let editor = editor.clone();
+                    async move { load_import(&editor) }
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                match import {
+                    Ok(import) => {
+                        let theme = import
+                            .source_theme
+                            .as_deref()
+                            .map(|source_theme| matching_theme(source_theme, &available_themes));
+                        this.step = WizardStep::Review(ImportReview {
+                            included_bindings: vec![true; import.bindings.len()],
+                            import_theme: theme.is_some(),
+                            theme,
+                            set_base_keymap: true,
+                            editor,
+                            import,
+                        });
+                    }
+                    Err(error) => {
+                        this.error = Some(format!("Failed to read settings: {error}").into());
+                        this.step = WizardStep::ChooseEditor(this.detected_editors.clone());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn import(&mut self, cx: &mut ViewContext<Self>) {
+        let WizardStep::Review(review) = &self.step else {
+            return;
+        };
+
+        if review.set_base_keymap {
+            let base_keymap = review.editor.editor.base_keymap();
+            update_settings_file::<BaseKeymap>(self.fs.clone(), cx, move |setting, _| {
+                *setting = Some(base_keymap)
+            });
+        }
+
+        if let Some(theme) = review.theme.clone().filter(|_| review.import_theme) {
+            let appearance = Appearance::from(cx.appearance());
+            update_settings_file::<ThemeSettings>(self.fs.clone(), cx, move |settings, _| {
+                settings.set_theme(theme.to_string(), appearance);
+            });
+        }
+
+        let bindings = review
+            .import
+            .bindings
+            .iter()
+            .zip(&review.included_bindings)
+            .filter(|(_, included)| **included)
+            .map(|(binding, _)| binding.clone())
+            .collect::<Vec<_>>();
+        let message = format!(
+            "Imported {} key binding{} from {}.",
+            bindings.len(),
+            if bindings.len() == 1 { "" } else { "s" },
+            review.editor.editor
+        );
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            if !bindings.is_empty() {
+                let keymap_path = paths::keymap_file();
+                let content = if fs.is_file(keymap_path).await {
+                    fs.load(keymap_path)
+                        .await
+                        .context("reading the keymap file")?
+                } else {
+                    String::new()
+                };
+                fs.atomic_write(keymap_path.clone(), append_to_keymap(&content, &bindings)?)
+                    .await?;
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                struct ImportedFromOtherEditor;
+                workspace.show_toast(
+                    Toast::new(NotificationId::unique::<ImportedFromOtherEditor>(), message)
+                        .autohide(),
+                    cx,
+                );
+            })
+        })
+        .detach_and_notify_err(cx);
+
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        match self.step {
+            WizardStep::Review(_) | WizardStep::Loading(_) => {
+                self._task = None;
+                self.step = WizardStep::ChooseEditor(self.detected_editors.clone());
+                cx.notify();
+            }
+            WizardStep::Detecting | WizardStep::ChooseEditor(_) => cx.emit(DismissEvent),
+        }
+    }
+
+    fn render_choose_editor(
+        &self,
+        editors: &[DetectedEditor],
+        cx: &mut ViewContext<Self>,
+    ) -> Section {
+        if editors.is_empty() {
+            return Section::new().child(
+                Label::new("No VS Code, Sublime Text or JetBrains settings were found.")
+                    .color(Color::Muted),
+            );
+        }
+        Section::new()
+            .header(SectionHeader::new("Detected Editors"))
+            .children(editors.iter().enumerate().map(|(ix, editor)| {
+                let detected = editor.clone();
+                ListItem::new(("detected-editor", ix))
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(Icon::new(IconName::Keyboard).color(Color::Muted))
+                    .child(
+                        v_flex().child(Label::new(editor.editor.to_string())).child(
+                            Label::new(editor.config_dir.to_string_lossy().into_owned())
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .on_click(
+                        cx.listener(move |this, _, cx| this.choose_editor(detected.clone(), cx)),
+                    )
+            }))
+    }
+
+    fn render_review(&self, review: &ImportReview, cx: &mut ViewContext<Self>) -> Vec<Section> {
+        let mut sections = Vec::new();
+
+        let mut preferences = Section::new()
+            .header(SectionHeader::new("Preferences"))
+            .child(CheckboxWithLabel::new(
+                "set-base-keymap",
+                Label::new(format!(
+                    "Use the {} base keymap",
+                    review.editor.editor.base_keymap()
+                )),
+                review.set_base_keymap.into(),
+                cx.listener(|this, state: &ToggleState, cx| {
+                    if let WizardStep::Review(review) = &mut this.step {
+                        review.set_base_keymap = *state == ToggleState::Selected;
+                        cx.notify();
+                    }
+                }),
+            ));
+        if let Some(theme) = &review.theme {
+            let source_theme = review.import.source_theme.clone().unwrap_or_default();
+            preferences = preferences.child(CheckboxWithLabel::new(
+                "import-theme",
+                Label::new(format!("Use the {theme} theme (from \"{source_theme}\")")),
+                review.import_theme.into(),
+                cx.listener(|this, state: &ToggleState, cx| {
+                    if let WizardStep::Review(review) = &mut this.step {
+                        review.import_theme = *state == ToggleState::Selected;
+                        cx.notify();
+                    }
+                }),
+            ));
+        }
+        sections.push(preferences);
+
+        let mut bindings = Section::new().header(SectionHeader::new(format!(
+            "Key Bindings ({} of {})",
+            review
+                .included_bindings
+                .iter()
+                .filter(|included| **included)
+                .count(),
+            review.import.bindings.len()
+        )));
+        if review.import.bindings.is_empty() {
+            bindings = bindings
+                .child(Label::new("No customized key bindings to import.").color(Color::Muted));
+        }
+        for (ix, (binding, included)) in review
+            .import
+            .bindings
+            .iter()
+            .zip(&review.included_bindings)
+            .enumerate()
+        {
+            bindings = bindings.child(
+                h_flex()
+                    .gap_2()
+                    .child(CheckboxWithLabel::new(
+                        ("import-binding", ix),
+                        Label::new(format!("{} → {}", binding.keystrokes, binding.action)),
+                        (*included).into(),
+                        cx.listener(move |this, state: &ToggleState, cx| {
+                            if let WizardStep::Review(review) = &mut this.step {
+                                review.included_bindings[ix] = *state == ToggleState::Selected;
+                                cx.notify();
+                            }
+                        }),
+                    ))
+                    .child(
+                        Label::new(binding.source_command.clone())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            );
+        }
+        sections.push(bindings);
+
+        if !review.import.unsupported.is_empty() {
+            sections.push(
+                Section::new()
+                    .header(SectionHeader::new(format!(
+                        "Not Imported ({})",
+                        review.import.unsupported.len()
+                    )))
+                    .child(
+                        Label::new(review.import.unsupported.join(", "))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            );
+        }
+
+        sections
+    }
+}
+
+impl EventEmitter<DismissEvent> for ImportWizard {}
+
+impl FocusableView for ImportWizard {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for ImportWizard {}
+
+impl Render for ImportWizard {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let reviewing = matches!(self.step, WizardStep::Review(_));
+        let sections =
+            match &self.step {
+                WizardStep::Detecting => vec![Section::new()
+                    .child(Label::new("Looking for other editors…").color(Color::Muted))],
+                WizardStep::Loading(editor) => vec![Section::new().child(
+                    Label::new(format!("Reading {} settings…", editor.editor)).color(Color::Muted),
+                )],
+                WizardStep::ChooseEditor(editors) => vec![self.render_choose_editor(editors, cx)],
+                WizardStep::Review(review) => self.render_review(review, cx),
+            };
+
+        let mut modal = Modal::new("import-wizard", Some(self.scroll_handle.clone()))
+            .header(
+                ModalHeader::new()
+                    .headline("Import from Another Editor")
+                    .show_back_button(reviewing)
+                    .show_dismiss_button(!reviewing),
+            )
+            .when_some(self.error.clone(), |modal, error| {
+                modal.section(Section::new().child(Label::new(error).color(Color::Error)))
+            });
+        for section in sections {
+            modal = modal.section(section);
+        }
+
+        div()
+            .track_focus(&self.focus_handle(cx))
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::cancel))
+            .occlude()
+            .w(rems(34.))
+            .max_h(rems(40.))
+            .child(
+                modal.footer(
+                    ModalFooter::new().end_slot(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Button::new("cancel", "Cancel")
+                                    .layer(ElevationIndex::ModalSurface)
+                                    .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                            )
+                            .when(reviewing, |this| {
+                                this.child(
+                                    Button::new("import", "Import")
+                                        .style(ButtonStyle::Filled)
+                                        .layer(ElevationIndex::ModalSurface)
+                                        .on_click(cx.listener(|this, _, cx| this.import(cx))),
+                                )
+                            }),
+                    ),
+                ),
+            )
+    }
+}
@@ -0,0 +1,80 @@
+use std::process::Stdio;
+
+use anyhow::{anyhow, Context as _, Result};
+use smol::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+    stream::{self, Stream, StreamExt},
+};
+
+/// Runs `command` to completion, reporting each line it prints to stdout or
+/// stderr. Carriage returns end a line too, so that progress indicators which
+/// redraw a single line (like `git clone --progress`) are reported as they
+/// update.
+pub(crate) async fn run_with_output(
+    program: &str,
+    command: &mut Command,
+    mut on_output: impl FnMut(String),
+) -> Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run {program}"))?;
+
+    let stdout = output_lines(child.stdout.take().context("no stdout")?);
+    let stderr = output_lines(child.stderr.take().context("no stderr")?);
+    // Lines are reported as either stream prints them, until both have ended.
+    let mut output = futures::stream::select(stdout, stderr);
+    while let Some(line) = output.next().await {
+        on_output(line);
+    }
+
+    let status = child.status().await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("{program} exited with {status}"))
+    }
+}
+
+fn output_lines(reader: impl AsyncRead + Unpin) -> impl Stream<Item = String> {
+    BufReader::new(reader).split(b'\r').flat_map(|chunk| {
+        let lines = chunk
+            .map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .map(str::trim_end)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        stream::iter(lines)
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_of_both_streams_is_reported() {
+        let mut lines = Vec::new();
+        let result = smol::block_on(run_with_output(
+            "sh",
+            Command::new("sh")
+                .arg("-c")
+                .arg("printf 'Cloning\\rReceiving 50%%\\rReceiving 100%%\\n' >&2; exec 2>&-; sleep 0.1; echo done"),
+            |line| lines.push(line),
+        ));
+
+        assert!(result.is_ok());
+        assert_eq!(
+            lines,
+            ["Cloning", "Receiving 50%", "Receiving 100%", "done"]
+        );
+    }
+}
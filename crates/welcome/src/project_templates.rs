@@ -0,0 +1,508 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, PathPromptOptions,
+    Render, Task, View, ViewContext, WeakView, WindowContext,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use ui::{
+    prelude::*, ElevationIndex, ListItem, ListItemSpacing, Modal, ModalFooter, ModalHeader,
+    Section, SectionHeader,
+};
+use util::{paths::home_dir, ResultExt};
+use workspace::{ModalView, Workspace};
+
+use crate::process_output::run_with_output;
+
+actions!(welcome, [NewProjectFromTemplate]);
+
+/// The variable replaced with the new project's name in template arguments.
+const PROJECT_NAME_VARIABLE: &str = "$ZED_PROJECT_NAME";
+const MAX_OUTPUT_LINES: usize = 8;
+
+/// A command that scaffolds a new project.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectTemplate {
+    /// The name shown in the list of templates.
+    pub name: String,
+    /// The program to run, e.g. `cargo`.
+    pub command: String,
+    /// The program's arguments. `$ZED_PROJECT_NAME` is replaced with the name
+    /// of the new project.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl ProjectTemplate {
+    fn new(name: &str, command: &str, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn args_for(&self, project_name: &str) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| arg.replace(PROJECT_NAME_VARIABLE, project_name))
+            .collect()
+    }
+
+    fn command_line(&self, project_name: &str) -> String {
+        std::iter::once(self.command.clone())
+            .chain(self.args_for(project_name))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// User-defined project templates.
+#[derive(Clone, Debug, Default)]
+pub struct ProjectTemplates(pub Vec<ProjectTemplate>);
+
+impl Settings for ProjectTemplates {
+    const KEY: Option<&'static str> = Some("project_templates");
+
+    type FileContent = Option<Vec<ProjectTemplate>>;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        let templates = match sources.user {
+            Some(Some(templates)) => templates.clone(),
+            _ => sources.default.clone().unwrap_or_default(),
+        };
+        Ok(Self(templates))
+    }
+}
+
+fn built_in_templates() -> Vec<ProjectTemplate> {
+    vec![
+        ProjectTemplate::new("Rust Binary", "cargo", &["new", PROJECT_NAME_VARIABLE]),
+        ProjectTemplate::new(
+            "Rust Library",
+            "cargo",
+            &["new", "--lib", PROJECT_NAME_VARIABLE],
+        ),
+        ProjectTemplate::new(
+            "Vite (TypeScript)",
+            "npm",
+            &[
+                "create",
+                "--yes",
+                "vite@latest",
+                PROJECT_NAME_VARIABLE,
+                "--",
+                "--template",
+                "vanilla-ts",
+            ],
+        ),
+        ProjectTemplate::new(
+            "Next.js",
+            "npm",
+            &[
+                "create",
+                "--yes",
+                "next-app@latest",
+                PROJECT_NAME_VARIABLE,
+                "--",
+                "--yes",
+            ],
+        ),
+    ]
+}
+
+pub fn init(cx: &mut AppContext) {
+    ProjectTemplates::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace
+            .register_action(|workspace, _: &NewProjectFromTemplate, cx| toggle(workspace, cx));
+    })
+    .detach();
+}
+
+pub fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let weak_workspace = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| NewProjectModal::new(weak_workspace, cx));
+}
+
+enum NewProjectStep {
+    ChooseTemplate,
+    Configure(ProjectTemplate),
+    Running {
+        command_line: String,
+    },
+    Failed {
+        template: ProjectTemplate,
+        error: SharedString,
+    },
+}
+
+pub struct NewProjectModal {
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    name_editor: View<Editor>,
+    directory: PathBuf,
+    step: NewProjectStep,
+    output: VecDeque<SharedString>,
+    _task: Option<Task<()>>,
+}
+
+impl NewProjectModal {
+    fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let name_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("my-project", cx);
+            editor
+        });
+        Self {
+            workspace,
+            focus_handle: cx.focus_handle(),
+            name_editor,
+            directory: home_dir().clone(),
+            step: NewProjectStep::ChooseTemplate,
+            output: VecDeque::new(),
+            _task: None,
+        }
+    }
+
+    fn choose_template(&mut self, template: ProjectTemplate, cx: &mut ViewContext<Self>) {
+        self.step = NewProjectStep::Configure(template);
+        cx.focus_view(&self.name_editor);
+        cx.notify();
+    }
+
+    fn choose_directory(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        self._task = Some(cx.spawn(|this, mut cx| async move {
+            let Some(directory) = paths
+                .await
+                .log_err()
+                .and_then(|paths| paths.log_err())
+                .flatten()
+                .and_then(|paths| paths.into_iter().next())
+            else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.directory = directory;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let NewProjectStep::Configure(template) = &self.step else {
+            return;
+        };
+        let template = template.clone();
+        let name = self.name_editor.read(cx).text(cx).trim().to_string();
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return;
+        }
+        let project_dir = self.directory.join(&name);
+        if project_dir.exists() {
+            self.step = NewProjectStep::Failed {
+                template,
+                error: format!("{} already exists.", project_dir.display()).into(),
+            };
+            cx.notify();
+            return;
+        }
+
+        self.output.clear();
+        self.step = NewProjectStep::Running {
+            command_line: template.command_line(&name),
+        };
+        cx.notify();
+
+        let directory = self.directory.clone();
+        self._task = Some(cx.spawn(|this, mut cx| async move {
+            let result = run_template(&template, &name, &directory, {
+                let this = this.clone();
+                let mut cx = cx.clone();
+                move |line| {
+                    this.update(&mut cx, |this, cx| {
+                        if this.output.len() == MAX_OUTPUT_LINES {
+                            this.output.pop_front();
+                        }
+                        this.output.push_back(line.into());
+                        cx.notify();
+                    })
+                    .ok();
+                }
+            })
+            .await
+            .and_then(|_| {
+                if project_dir.is_dir() {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "the template did not create {}",
+                        project_dir.display()
+                    ))
+                }
+            });
+            this.update(&mut cx, |this, cx| match result {
+                Ok(()) => this.open_project(project_dir, cx),
+                Err(error) => {
+                    this.step = NewProjectStep::Failed {
+                        template,
+                        error: format!("{error:#}").into(),
+                    };
+                    cx.notify();
+                }
+            })
+            .ok();
+        }));
+    }
+
+    fn open_project(&mut self, project_dir: PathBuf, cx: &mut ViewContext<Self>) {
+        open_created_project(&self.workspace, project_dir, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        match &self.step {
+            NewProjectStep::Configure(_) => {
+                self.step = NewProjectStep::ChooseTemplate;
+                cx.focus_self();
+                cx.notify();
+            }
+            NewProjectStep::Failed { template, .. } => {
+                self.step = NewProjectStep::Configure(template.clone());
+                cx.focus_view(&self.name_editor);
+                cx.notify();
+            }
+            // Dropping the task kills the generator.
+            NewProjectStep::ChooseTemplate | NewProjectStep::Running { .. } => {
+                cx.emit(DismissEvent)
+            }
+        }
+    }
+
+    fn render_templates(&self, cx: &mut ViewContext<Self>) -> Vec<Section> {
+        let user_templates = ProjectTemplates::get_global(cx).0.clone();
+        let mut sections =
+            vec![self.render_template_list("built-in", "Built-in", built_in_templates(), cx)];
+        if !user_templates.is_empty() {
+            sections.push(self.render_template_list("user", "From Settings", user_templates, cx));
+        }
+        sections
+    }
+
+    fn render_template_list(
+        &self,
+        id: &'static str,
+        header: &'static str,
+        templates: Vec<ProjectTemplate>,
+        cx: &mut ViewContext<Self>,
+    ) -> Section {
+        Section::new().header(SectionHeader::new(header)).children(
+            templates.into_iter().enumerate().map(|(ix, template)| {
+                let command_line = template.command_line("<name>");
+                ListItem::new((id, ix))
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .child(
+                        v_flex().child(Label::new(template.name.clone())).child(
+                            Label::new(command_line)
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .on_click(
+                        cx.listener(move |this, _, cx| this.choose_template(template.clone(), cx)),
+                    )
+            }),
+        )
+    }
+
+    fn render_configure(&self, template: &ProjectTemplate, cx: &mut ViewContext<Self>) -> Section {
+        Section::new()
+            .header(SectionHeader::new(template.name.clone()))
+            .child(
+                v_flex()
+                    .gap_2()
+                    .child(Label::new("Name").size(LabelSize::Small))
+                    .child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(cx.theme().colors().border)
+                            .rounded_md()
+                            .child(self.name_editor.clone()),
+                    )
+                    .child(Label::new("Location").size(LabelSize::Small))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .justify_between()
+                            .child(
+                                Label::new(self.directory.to_string_lossy().into_owned())
+                                    .color(Color::Muted)
+                                    .text_ellipsis(),
+                            )
+                            .child(
+                                Button::new("choose-directory", "Choose…")
+                                    .layer(ElevationIndex::ModalSurface)
+                                    .on_click(cx.listener(|this, _, cx| this.choose_directory(cx))),
+                            ),
+                    ),
+            )
+    }
+
+    fn render_output(&self) -> impl IntoElement {
+        v_flex().children(self.output.iter().map(|line| {
+            Label::new(line.clone())
+                .size(LabelSize::XSmall)
+                .color(Color::Muted)
+                .text_ellipsis()
+        }))
+    }
+}
+
+/// Opens a freshly created project, reusing the window when it only shows the
+/// welcome page.
+pub(crate) fn open_created_project(
+    workspace: &WeakView<Workspace>,
+    project_dir: PathBuf,
+    cx: &mut WindowContext,
+) {
+    workspace
+        .update(cx, |workspace, cx| {
+            let replace_current_window = workspace
+                .project()
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+                .is_none();
+            workspace.open_workspace_for_paths(replace_current_window, vec![project_dir], cx)
+        })
+        .map(|task| task.detach_and_log_err(cx))
+        .log_err();
+}
+
+/// Runs the template's generator in `directory`, reporting each line it prints.
+async fn run_template(
+    template: &ProjectTemplate,
+    project_name: &str,
+    directory: &Path,
+    on_output: impl FnMut(String),
+) -> Result<()> {
+    let mut command = util::command::new_smol_command(&template.command);
+    command
+        .args(template.args_for(project_name))
+        .current_dir(directory);
+    run_with_output(&template.command, &mut command, on_output).await
+}
+
+impl EventEmitter<DismissEvent> for NewProjectModal {}
+
+impl FocusableView for NewProjectModal {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for NewProjectModal {}
+
+impl Render for NewProjectModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let configuring = matches!(self.step, NewProjectStep::Configure(_));
+        let choosing = matches!(self.step, NewProjectStep::ChooseTemplate);
+        let running = matches!(self.step, NewProjectStep::Running { .. });
+        let sections = match &self.step {
+            NewProjectStep::ChooseTemplate => self.render_templates(cx),
+            NewProjectStep::Configure(template) => vec![self.render_configure(template, cx)],
+            NewProjectStep::Running { command_line } => vec![Section::new()
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Icon::new(IconName::ArrowCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(Label::new(format!("Running `{command_line}`…"))),
+                )
+                .child(self.render_output())],
+            NewProjectStep::Failed { error, .. } => vec![Section::new()
+                .child(Label::new(error.clone()).color(Color::Error))
+                .child(self.render_output())],
+        };
+
+        let mut modal = Modal::new("new-project", None).header(
+            ModalHeader::new()
+                .headline("New Project from Template")
+                .show_back_button(!choosing && !running)
+                .show_dismiss_button(choosing),
+        );
+        for section in sections {
+            modal = modal.section(section);
+        }
+
+        div()
+            .track_focus(&self.focus_handle(cx))
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .occlude()
+            .w(rems(34.))
+            .child(
+                modal.footer(
+                    ModalFooter::new().end_slot(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Button::new("cancel", "Cancel")
+                                    .layer(ElevationIndex::ModalSurface)
+                                    .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                            )
+                            .when(configuring, |this| {
+                                this.child(
+                                    Button::new("create", "Create")
+                                        .style(ButtonStyle::Filled)
+                                        .layer(ElevationIndex::ModalSurface)
+                                        .on_click(cx.listener(|this, _, cx| {
+                                            this.confirm(&menu::Confirm, cx)
+                                        })),
+                                )
+                            }),
+                    ),
+                ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_command_line() {
+        let template = ProjectTemplate::new(
+            "Rust Library",
+            "cargo",
+            &["new", "--lib", "$ZED_PROJECT_NAME"],
+        );
+        assert_eq!(template.command_line("tree"), "cargo new --lib tree");
+        assert_eq!(
+            ProjectTemplate::new("Prefixed", "make", &["new-$ZED_PROJECT_NAME"]).args_for("app"),
+            vec!["new-app".to_string()]
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+
+use gpui::{Render, Task, ViewContext, WeakView};
+use itertools::Itertools;
+use recent_projects::{open_ssh_project, SshSettings};
+use settings::Settings;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{OpenOptions, SerializedWorkspaceLocation, Workspace, WORKSPACE_DB};
+
+use crate::recent_workspaces::location_label;
+
+const MAX_RECENT_SESSIONS: usize = 4;
+
+struct RecentSession {
+    /// Whether Zed was quit while this session was open, i.e. it's the one
+    /// `"restore_on_startup": "last_session"` restores.
+    is_last: bool,
+    /// The session's windows, frontmost first when known.
+    locations: Vec<SerializedWorkspaceLocation>,
+}
+
+/// The sets of windows that were open in earlier runs of Zed.
+pub struct RecentSessions {
+    workspace: WeakView<Workspace>,
+    sessions: Vec<RecentSession>,
+    _load_task: Task<()>,
+}
+
+impl RecentSessions {
+    pub fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let load_task = Self::load_sessions(&workspace, cx);
+        Self {
+            workspace,
+            sessions: Vec::new(),
+            _load_task: load_task,
+        }
+    }
+
+    fn load_sessions(workspace: &WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Task<()> {
+        let Some((session_id, last_session_id, last_session_window_stack)) = workspace
+            .update(cx, |workspace, cx| {
+                let session = workspace.app_state().session.read(cx);
+                (
+                    session.id().to_string(),
+                    session.last_session_id().map(|id| id.to_string()),
+                    session.last_session_window_stack(),
+                )
+            })
+            .log_err()
+        else {
+            return Task::ready(());
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let sessions = cx
+                .background_executor()
+                .spawn(async move {
+                    let session_ids = WORKSPACE_DB.recent_session_ids().log_err()?;
+                    let sessions = session_ids
+                        .into_iter()
+                        .filter(|id| id != &session_id)
+                        .filter_map(|id| {
+                            let is_last = last_session_id.as_ref() == Some(&id);
+                            let window_stack =
+                                last_session_window_stack.clone().filter(|_| is_last);
+                            let locations = WORKSPACE_DB
+                                .last_session_workspace_locations(&id, window_stack)
+                                .log_err()?;
+                            (!locations.is_empty()).then_some(RecentSession { is_last, locations })
+                        })
+                        .take(MAX_RECENT_SESSIONS)
+                        .collect::<Vec<_>>();
+                    Some(sessions)
+                })
+                .await
+                .unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.sessions = sessions;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    /// Opens each of the session's workspaces in its own window, restoring
+    /// their layouts. The current window is reused when it only shows the
+    /// welcome page.
+    fn restore(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.sessions.get(ix) else {
+            return;
+        };
+        // Open the frontmost window last, so that it ends up on top.
+        let locations = session.locations.iter().rev().cloned().collect::<Vec<_>>();
+        let Some((app_state, mut replace_window)) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                let is_empty = workspace
+                    .project()
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .is_none();
+                let window = cx.window_handle().downcast::<Workspace>();
+                (workspace.app_state().clone(), window.filter(|_| is_empty))
+            })
+            .log_err()
+        else {
+            return;
+        };
+
+        cx.spawn(|_, mut cx| async move {
+            for location in locations {
+                let open_options = OpenOptions {
+                    replace_window: replace_window.take(),
+                    ..Default::default()
+                };
+                match location {
+                    SerializedWorkspaceLocation::Local(paths, _) => {
+                        cx.update(|cx| {
+                            workspace::open_paths(
+                                paths.paths().as_ref(),
+                                app_state.clone(),
+                                open_options,
+                                cx,
+                            )
+                        })?
+                        .await?;
+                    }
+                    SerializedWorkspaceLocation::Ssh(ssh) => {
+                        let connection_options = cx.update(|cx| {
+                            SshSettings::get_global(cx)
+                                .connection_options_for(ssh.host, ssh.port, ssh.user)
+                        })?;
+                        open_ssh_project(
+                            connection_options,
+                            ssh.paths.into_iter().map(PathBuf::from).collect(),
+                            app_state.clone(),
+                            open_options,
+                            &mut cx,
+                        )
+                        .await
+                        .log_err();
+                    }
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_session(
+        &self,
+        ix: usize,
+        session: &RecentSession,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let title = if session.is_last {
+            "Last Session"
+        } else {
+            "Earlier Session"
+        };
+        let window_count = match session.locations.len() {
+            1 => "1 window".to_string(),
+            count => format!("{count} windows"),
+        };
+
+        ListItem::new(("recent-session", ix))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(
+                Icon::new(IconName::HistoryRerun)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                v_flex()
+                    .overflow_hidden()
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(Label::new(title).size(LabelSize::Small))
+                            .child(
+                                Label::new(window_count)
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        Label::new(session.locations.iter().map(location_label).join(" · "))
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .text_ellipsis(),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.restore(ix, cx)))
+    }
+}
+
+impl Render for RecentSessions {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.sessions.is_empty() {
+            return div().into_any_element();
+        }
+        let last_session_ix = self.sessions.iter().position(|session| session.is_last);
+        let items = self
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(ix, session)| self.render_session(ix, session, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                div()
+                    .pl_1()
+                    .font_buffer(cx)
+                    .text_color(Color::Muted.color(cx))
+                    .child(
+                        Label::new("Earlier Sessions")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .when_some(last_session_ix, |this, ix| {
+                this.child(
+                    Button::new("restore-last-session", "Restore All from Last Time")
+                        .icon(IconName::HistoryRerun)
+                        .icon_size(IconSize::XSmall)
+                        .icon_color(Color::Muted)
+                        .icon_position(IconPosition::Start)
+                        .full_width()
+                        .on_click(cx.listener(move |this, _, cx| this.restore(ix, cx))),
+                )
+            })
+            .children(items)
+            .into_any_element()
+    }
+}
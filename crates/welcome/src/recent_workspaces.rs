@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, EditorEvent};
+use gpui::{AppContext, Render, Task, View, ViewContext, WeakView};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::{paths::PathExt, ResultExt};
+use workspace::{SerializedWorkspaceLocation, Workspace, WorkspaceId, WORKSPACE_DB};
+
+const RECENT_WORKSPACE_PREFERENCES_KEY: &str = "welcome_recent_workspaces";
+const MAX_RECENT_WORKSPACES: usize = 8;
+
+/// Welcome page preferences for the recent projects list. Entries are keyed by the
+/// serialized location of the workspace, as workspace ids are not stable across
+/// removals and re-opens.
+#[derive(Default, Serialize, Deserialize)]
+struct RecentWorkspacePreferences {
+    #[serde(default)]
+    pinned: Vec<String>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+impl RecentWorkspacePreferences {
+    fn load() -> Self {
+        KEY_VALUE_STORE
+            .read_kvp(RECENT_WORKSPACE_PREFERENCES_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).log_err())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, cx: &mut AppContext) {
+        let Some(json) = serde_json::to_string(self).log_err() else {
+            return;
+        };
+        db::write_and_log(cx, move || {
+            KEY_VALUE_STORE.write_kvp(RECENT_WORKSPACE_PREFERENCES_KEY.to_string(), json)
+        });
+    }
+
+    fn is_pinned(&self, key: &str) -> bool {
+        self.pinned.iter().any(|pinned| pinned == key)
+    }
+}
+
+struct RecentWorkspace {
+    id: WorkspaceId,
+    key: String,
+    location: SerializedWorkspaceLocation,
+}
+
+/// The "Recent Projects" section of the welcome page, with pinning, renaming
+/// and removal of entries.
+pub struct RecentWorkspaces {
+    workspace: WeakView<Workspace>,
+    workspaces: Vec<RecentWorkspace>,
+    preferences: RecentWorkspacePreferences,
+    renaming: Option<(String, View<Editor>)>,
+    _load_task: Task<()>,
+}
+
+impl RecentWorkspaces {
+    pub fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            workspace,
+            workspaces: Vec::new(),
+            preferences: RecentWorkspacePreferences::load(),
+            renaming: None,
+            _load_task: Self::load_workspaces(cx),
+        }
+    }
+
+    fn load_workspaces(cx: &mut ViewContext<Self>) -> Task<()> {
+        cx.spawn(|this, mut cx| async move {
+            let workspaces = WORKSPACE_DB
+                .recent_workspaces_on_disk()
+                .await
+                .log_err()
+                .unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.workspaces = workspaces
+                    .into_iter()
+                    .map(|(id, location)| RecentWorkspace {
+                        id,
+                        key: location_key(&location),
+                        location,
+                    })
+                    .collect();
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    /// Pinned workspaces in the order they were pinned, followed by the most
+    /// recently opened ones.
+    fn visible_workspaces(&self) -> Vec<&RecentWorkspace> {
+        let pinned = self.preferences.pinned.iter().filter_map(|key| {
+            self.workspaces
+                .iter()
+                .find(|workspace| &workspace.key == key)
+        });
+        let unpinned = self
+            .workspaces
+            .iter()
+            .filter(|workspace| !self.preferences.is_pinned(&workspace.key));
+        let mut visible = pinned.collect::<Vec<_>>();
+        let remaining = MAX_RECENT_WORKSPACES.saturating_sub(visible.len());
+        visible.extend(unpinned.take(remaining));
+        visible
+    }
+
+    fn toggle_pinned(&mut self, key: String, cx: &mut ViewContext<Self>) {
+        if self.preferences.is_pinned(&key) {
+            self.preferences.pinned.retain(|pinned| pinned != &key);
+        } else {
+            self.preferences.pinned.push(key);
+        }
+        self.preferences.save(cx);
+        cx.notify();
+    }
+
+    fn remove(&mut self, id: WorkspaceId, key: String, cx: &mut ViewContext<Self>) {
+        self.preferences.pinned.retain(|pinned| pinned != &key);
+        self.preferences.labels.remove(&key);
+        self.preferences.save(cx);
+        self.workspaces.retain(|workspace| workspace.id != id);
+        cx.notify();
+
+        cx.background_executor()
+            .spawn(async move { WORKSPACE_DB.delete_workspace_by_id(id).await })
+            .detach_and_log_err(cx);
+    }
+
+    fn start_renaming(&mut self, key: String, default_label: String, cx: &mut ViewContext<Self>) {
+        let label = self
+            .preferences
+            .labels
+            .get(&key)
+            .cloned()
+            .unwrap_or(default_label);
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text(label, cx);
+            editor.select_all(&Default::default(), cx);
+            editor
+        });
+        cx.subscribe(&editor, |this, _, event, cx| {
+            if let EditorEvent::Blurred = event {
+                this.confirm_rename(&menu::Confirm, cx);
+            }
+        })
+        .detach();
+        cx.focus_view(&editor);
+        self.renaming = Some((key, editor));
+        cx.notify();
+    }
+
+    fn confirm_rename(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some((key, editor)) = self.renaming.take() else {
+            return;
+        };
+        let label = editor.read(cx).text(cx).trim().to_string();
+        if label.is_empty() {
+            self.preferences.labels.remove(&key);
+        } else {
+            self.preferences.labels.insert(key, label);
+        }
+        self.preferences.save(cx);
+        cx.notify();
+    }
+
+    fn cancel_rename(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.renaming.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn open(&mut self, location: SerializedWorkspaceLocation, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                // Reuse the window when it only shows the welcome page.
+                let replace_current_window = workspace
+                    .project()
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .is_none();
+                recent_projects::open_recent_workspace(
+                    workspace,
+                    location,
+                    replace_current_window,
+                    cx,
+                )
+            })
+            .map(|task| task.detach_and_log_err(cx))
+            .log_err();
+    }
+
+    fn render_workspace(
+        &self,
+        ix: usize,
+        recent: &RecentWorkspace,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let paths = location_paths(&recent.location);
+        let default_label = location_label(&recent.location);
+        let label = self
+            .preferences
+            .labels
+            .get(&recent.key)
+            .cloned()
+            .unwrap_or_else(|| default_label.clone());
+        let pinned = self.preferences.is_pinned(&recent.key);
+        let (icon, detail) = match &recent.location {
+            SerializedWorkspaceLocation::Local(_, _) => (IconName::Screen, paths.join(", ")),
+            SerializedWorkspaceLocation::Ssh(ssh_project) => (
+                IconName::Server,
+                format!(
+                    "{} · {}",
+                    ssh_host(
+                        &ssh_project.host,
+                        ssh_project.port,
+                        ssh_project.user.as_deref()
+                    ),
+                    ssh_project.paths.join(", ")
+                ),
+            ),
+        };
+        let renaming_editor = self
+            .renaming
+            .as_ref()
+            .filter(|(key, _)| key == &recent.key)
+            .map(|(_, editor)| editor.clone());
+
+        let id = recent.id;
+        let key = recent.key.clone();
+        let location = recent.location.clone();
+
+        ListItem::new(("recent-workspace", ix))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+            .child(
+                v_flex()
+                    .overflow_hidden()
+                    .child(match renaming_editor {
+                        Some(editor) => div()
+                            .w_64()
+                            .on_action(cx.listener(Self::confirm_rename))
+                            .on_action(cx.listener(Self::cancel_rename))
+                            .child(editor)
+                            .into_any_element(),
+                        None => h_flex()
+                            .gap_1()
+                            .child(Label::new(label).size(LabelSize::Small))
+                            .when(pinned, |this| {
+                                this.child(
+                                    Icon::new(IconName::Pin)
+                                        .size(IconSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                            })
+                            .into_any_element(),
+                    })
+                    .child(
+                        Label::new(detail)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .text_ellipsis(),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.open(location.clone(), cx)))
+            .end_hover_slot(
+                h_flex()
+                    .gap_0p5()
+                    .child(
+                        IconButton::new(
+                            ("pin", ix),
+                            if pinned {
+                                IconName::Unpin
+                            } else {
+                                IconName::Pin
+                            },
+                        )
+                        .icon_size(IconSize::XSmall)
+                        .icon_color(Color::Muted)
+                        .tooltip(move |cx| Tooltip::text(if pinned { "Unpin" } else { "Pin" }, cx))
+                        .on_click(cx.listener({
+                            let key = key.clone();
+                            move |this, _, cx| {
+                                cx.stop_propagation();
+                                this.toggle_pinned(key.clone(), cx);
+                            }
+                        })),
+                    )
+                    .child(
+                        IconButton::new(("rename", ix), IconName::Pencil)
+                            .icon_size(IconSize::XSmall)
+                            .icon_color(Color::Muted)
+                            .tooltip(|cx| Tooltip::text("Rename", cx))
+                            .on_click(cx.listener({
+                                let key = key.clone();
+                                move |this, _, cx| {
+                                    cx.stop_propagation();
+                                    this.start_renaming(key.clone(), default_label.clone(), cx);
+                                }
+                            })),
+                    )
+                    .child(
+                        IconButton::new(("remove", ix), IconName::Close)
+                            .icon_size(IconSize::XSmall)
+                            .icon_color(Color::Muted)
+                            .tooltip(|cx| Tooltip::text("Remove from Recent Projects", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                cx.stop_propagation();
+                                this.remove(id, key.clone(), cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+impl Render for RecentWorkspaces {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let visible = self.visible_workspaces();
+        if visible.is_empty() {
+            return div()
+                .pl_1()
+                .child(
+                    Label::new("No recent projects")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element();
+        }
+        let items = visible
+            .into_iter()
+            .enumerate()
+            .map(|(ix, recent)| self.render_workspace(ix, recent, cx).into_any_element())
+            .collect::<Vec<_>>();
+        v_flex().w_full().children(items).into_any_element()
+    }
+}
+
+fn location_paths(location: &SerializedWorkspaceLocation) -> Vec<String> {
+    match location {
+        SerializedWorkspaceLocation::Local(paths, order) => order
+            .order()
+            .iter()
+            .zip(paths.paths().iter())
+            .sorted_by_key(|(ix, _)| **ix)
+            .map(|(_, path)| path.compact().to_string_lossy().into_owned())
+            .collect(),
+        SerializedWorkspaceLocation::Ssh(ssh_project) => ssh_project.paths.clone(),
+    }
+}
+
+/// The file names of the workspace's folders, e.g. `zed, tree-sitter`.
+pub(crate) fn location_label(location: &SerializedWorkspaceLocation) -> String {
+    location_paths(location)
+        .iter()
+        .map(|path| {
+            std::path::Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned())
+        })
+        .join(", ")
+}
+
+fn ssh_host(host: &str, port: Option<u16>, user: Option<&str>) -> String {
+    let mut host = match user {
+        Some(user) => format!("{user}@{host}"),
+        None => host.to_string(),
+    };
+    if let Some(port) = port {
+        host.push_str(&format!(":{port}"));
+    }
+    host
+}
+
+fn location_key(location: &SerializedWorkspaceLocation) -> String {
+    match location {
+        SerializedWorkspaceLocation::Local(paths, _) => paths
+            .paths()
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .join("\n"),
+        SerializedWorkspaceLocation::Ssh(ssh_project) => format!(
+            "ssh://{}\n{}",
+            ssh_host(
+                &ssh_project.host,
+                ssh_project.port,
+                ssh_project.user.as_deref()
+            ),
+            ssh_project.paths.join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use workspace::AppState;
+
+    fn visible_keys(recent: &View<RecentWorkspaces>, cx: &mut VisualTestContext) -> Vec<String> {
+        recent.update(cx, |recent, _| {
+            recent
+                .visible_workspaces()
+                .into_iter()
+                .map(|workspace| workspace.key.clone())
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_pinning_renaming_and_removing(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init_settings(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let mut workspaces = Vec::new();
+        for ix in 0..10 {
+            let location = SerializedWorkspaceLocation::from_local_paths([format!("/p{ix}")]);
+            workspaces.push(RecentWorkspace {
+                id: WORKSPACE_DB.next_id().await.unwrap(),
+                key: location_key(&location),
+                location,
+            });
+        }
+        let removed_id = workspaces[3].id;
+        let recent = cx.new_view(|_| RecentWorkspaces {
+            workspace: workspace.downgrade(),
+            workspaces,
+            preferences: RecentWorkspacePreferences::default(),
+            renaming: None,
+            _load_task: Task::ready(()),
+        });
+        let keys = |ixs: &[usize]| ixs.iter().map(|ix| format!("/p{ix}")).collect::<Vec<_>>();
+        assert_eq!(visible_keys(&recent, cx), keys(&[0, 1, 2, 3, 4, 5, 6, 7]));
+
+        // Each change is saved before making the next one, as the saves could land out of order
+        // otherwise.
+        let toggle_pinned = |key: &str, cx: &mut VisualTestContext| {
+            recent.update(cx, |recent, cx| recent.toggle_pinned(key.into(), cx));
+            cx.run_until_parked();
+        };
+
+        // Pinned workspaces come first in the order they were pinned, even when they were
+        // opened too long ago to be listed otherwise.
+        toggle_pinned("/p9", cx);
+        toggle_pinned("/p3", cx);
+        toggle_pinned("/p5", cx);
+        assert_eq!(visible_keys(&recent, cx), keys(&[9, 3, 5, 0, 1, 2, 4, 6]));
+        toggle_pinned("/p9", cx);
+        assert_eq!(visible_keys(&recent, cx), keys(&[3, 5, 0, 1, 2, 4, 6, 7]));
+
+        // Renaming keeps the trimmed label, and an empty one goes back to the default label.
+        let rename = |key: &str, label: &str, cx: &mut VisualTestContext| {
+            let editor = recent.update(cx, |recent, cx| {
+                recent.start_renaming(key.into(), key.into(), cx);
+                recent.renaming.as_ref().unwrap().1.clone()
+            });
+            editor.update(cx, |editor, cx| editor.set_text(label, cx));
+            recent.update(cx, |recent, cx| recent.confirm_rename(&menu::Confirm, cx));
+            cx.run_until_parked();
+        };
+        rename("/p0", " Zed ", cx);
+        rename("/p3", "Scratch", cx);
+        rename("/p1", "Docs", cx);
+        rename("/p1", "  ", cx);
+        recent.update(cx, |recent, _| {
+            assert!(recent.renaming.is_none());
+            assert_eq!(
+                recent.preferences.labels,
+                HashMap::from_iter([
+                    ("/p0".to_string(), "Zed".to_string()),
+                    ("/p3".to_string(), "Scratch".to_string()),
+                ])
+            );
+        });
+
+        // Removing a workspace forgets its pin and label too.
+        recent.update(cx, |recent, cx| recent.remove(removed_id, "/p3".into(), cx));
+        cx.run_until_parked();
+        assert_eq!(visible_keys(&recent, cx), keys(&[5, 0, 1, 2, 4, 6, 7, 8]));
+
+        // The pins and labels are kept across restarts.
+        let preferences = RecentWorkspacePreferences::load();
+        assert_eq!(preferences.pinned, ["/p5"]);
+        assert_eq!(
+            preferences.labels,
+            HashMap::from_iter([("/p0".to_string(), "Zed".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_preferences_with_missing_fields() {
+        let preferences: RecentWorkspacePreferences =
+            serde_json::from_str(r#"{ "pinned": ["/p0"] }"#).unwrap();
+        assert_eq!(preferences.pinned, ["/p0"]);
+        assert!(preferences.labels.is_empty());
+
+        let preferences: RecentWorkspacePreferences = serde_json::from_str("{}").unwrap();
+        assert!(preferences.pinned.is_empty());
+        assert!(preferences.labels.is_empty());
+    }
+}
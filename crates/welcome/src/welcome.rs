@@ -1,6 +1,15 @@
 mod base_keymap_picker;
 mod base_keymap_setting;
+mod clone_repository;
+mod editor_import;
+mod feature_tour;
+mod import_wizard;
 mod multibuffer_hint;
+mod process_output;
+mod project_templates;
+mod recent_sessions;
+mod recent_workspaces;
+mod whats_new;
 
 use client::{telemetry::Telemetry, TelemetrySettings};
 use db::kvp::KEY_VALUE_STORE;
@@ -9,10 +18,13 @@ use gpui::{
     ParentElement, Render, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
     WindowContext,
 };
+use recent_sessions::RecentSessions;
+use recent_workspaces::RecentWorkspaces;
 use settings::{Settings, SettingsStore};
 use std::sync::Arc;
 use ui::{prelude::*, CheckboxWithLabel, Tooltip};
 use vim_mode_setting::VimModeSetting;
+use whats_new::WhatsNew;
 use workspace::{
     dock::DockPosition,
     item::{Item, ItemEvent},
@@ -20,7 +32,11 @@ use workspace::{
 };
 
 pub use base_keymap_setting::BaseKeymap;
+pub use clone_repository::CloneRepository;
+pub use feature_tour::StartFeatureTour;
+pub use import_wizard::ImportFromOtherEditor;
 pub use multibuffer_hint::*;
+pub use project_templates::{NewProjectFromTemplate, ProjectTemplate};
 
 actions!(welcome, [ResetHints]);
 
@@ -42,6 +58,10 @@ pub fn init(cx: &mut AppContext) {
     .detach();
 
     base_keymap_picker::init(cx);
+    clone_repository::init(cx);
+    feature_tour::init(cx);
+    import_wizard::init(cx);
+    project_templates::init(cx);
 }
 
 pub fn show_welcome_view(
@@ -53,6 +73,7 @@ pub fn show_welcome_view(
         let welcome_page = WelcomePage::new(workspace, cx);
         workspace.add_item_to_center(Box::new(welcome_page.clone()), cx);
         cx.focus_view(&welcome_page);
+        import_wizard::open_if_editors_detected(cx);
         cx.notify();
 
         db::write_and_log(cx, || {
@@ -65,6 +86,9 @@ pub struct WelcomePage {
     workspace: WeakView<Workspace>,
     focus_handle: FocusHandle,
     telemetry: Arc<Telemetry>,
+    recent_sessions: View<RecentSessions>,
+    recent_workspaces: View<RecentWorkspaces>,
+    whats_new: View<WhatsNew>,
     _settings_subscription: Subscription,
 }
 
@@ -122,6 +146,41 @@ impl Render for WelcomePage {
                                                 .color(Color::Muted),
                                         ),
                                     )
+                                    .child(
+                                        Button::new("new-project", "New Project from Template")
+                                            .icon(IconName::Plus)
+                                            .icon_size(IconSize::XSmall)
+                                            .icon_color(Color::Muted)
+                                            .icon_position(IconPosition::Start)
+                                            .on_click(cx.listener(|this, _, cx| {
+                                                this.telemetry.report_app_event(
+                                                    "welcome page: new project from template"
+                                                        .to_string(),
+                                                );
+                                                this.workspace
+                                                    .update(cx, |workspace, cx| {
+                                                        project_templates::toggle(workspace, cx)
+                                                    })
+                                                    .ok();
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new("clone-repository", "Clone Repository…")
+                                            .icon(IconName::FileGit)
+                                            .icon_size(IconSize::XSmall)
+                                            .icon_color(Color::Muted)
+                                            .icon_position(IconPosition::Start)
+                                            .on_click(cx.listener(|this, _, cx| {
+                                                this.telemetry.report_app_event(
+                                                    "welcome page: clone repository".to_string(),
+                                                );
+                                                this.workspace
+                                                    .update(cx, |workspace, cx| {
+                                                        clone_repository::toggle(workspace, cx)
+                                                    })
+                                                    .ok();
+                                            })),
+                                    )
                                     .child(
                                         Button::new("choose-theme", "Choose a Theme")
                                             .icon(IconName::SwatchBook)
@@ -160,6 +219,29 @@ impl Render for WelcomePage {
                                                     .ok();
                                             })),
                                     )
+                                    .child(
+                                        Button::new(
+                                            "import-from-other-editor",
+                                            "Import from Another Editor",
+                                        )
+                                        .icon(IconName::Download)
+                                        .icon_size(IconSize::XSmall)
+                                        .icon_color(Color::Muted)
+                                        .icon_position(IconPosition::Start)
+                                        .on_click(
+                                            cx.listener(|this, _, cx| {
+                                                this.telemetry.report_app_event(
+                                                    "welcome page: import from other editor"
+                                                        .to_string(),
+                                                );
+                                                this.workspace
+                                                    .update(cx, |workspace, cx| {
+                                                        import_wizard::toggle(workspace, None, cx)
+                                                    })
+                                                    .ok();
+                                            }),
+                                        ),
+                                    )
                                     .child(
                                         Button::new(
                                             "sign-in-to-copilot",
@@ -178,6 +260,23 @@ impl Render for WelcomePage {
                                             }),
                                         ),
                                     )
+                                    .child(
+                                        Button::new("feature-tour", "Take the Tour")
+                                            .icon(IconName::Route)
+                                            .icon_size(IconSize::XSmall)
+                                            .icon_color(Color::Muted)
+                                            .icon_position(IconPosition::Start)
+                                            .on_click(cx.listener(|this, _, cx| {
+                                                this.telemetry.report_app_event(
+                                                    "welcome page: start tour".to_string(),
+                                                );
+                                                this.workspace
+                                                    .update(cx, |workspace, cx| {
+                                                        feature_tour::start(workspace, cx)
+                                                    })
+                                                    .ok();
+                                            })),
+                                    )
                                     .child(
                                         Button::new("edit settings", "Edit Settings")
                                             .icon(IconName::Settings)
@@ -263,6 +362,20 @@ impl Render for WelcomePage {
                                     ),
                             ),
                     )
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .child(
+                                self.section_label(cx).child(
+                                    Label::new("Recent Projects")
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted),
+                                ),
+                            )
+                            .child(self.recent_workspaces.clone()),
+                    )
+                    .child(self.recent_sessions.clone())
+                    .child(self.whats_new.clone())
                     .child(
                         v_group()
                             .gap_2()
@@ -349,10 +462,14 @@ impl WelcomePage {
             })
             .detach();
 
+            let weak_workspace = workspace.weak_handle();
             WelcomePage {
                 focus_handle: cx.focus_handle(),
-                workspace: workspace.weak_handle(),
+                workspace: weak_workspace.clone(),
                 telemetry: workspace.client().telemetry().clone(),
+                recent_sessions: cx.new_view(|cx| RecentSessions::new(weak_workspace.clone(), cx)),
+                recent_workspaces: cx.new_view(|cx| RecentWorkspaces::new(weak_workspace, cx)),
+                whats_new: cx.new_view(WhatsNew::new),
                 _settings_subscription: cx
                     .observe_global::<SettingsStore>(move |_, cx| cx.notify()),
             }
@@ -422,6 +539,9 @@ impl Item for WelcomePage {
             focus_handle: cx.focus_handle(),
             workspace: self.workspace.clone(),
             telemetry: self.telemetry.clone(),
+            recent_sessions: cx.new_view(|cx| RecentSessions::new(self.workspace.clone(), cx)),
+            recent_workspaces: cx.new_view(|cx| RecentWorkspaces::new(self.workspace.clone(), cx)),
+            whats_new: cx.new_view(WhatsNew::new),
             _settings_subscription: cx.observe_global::<SettingsStore>(move |_, cx| cx.notify()),
         }))
     }
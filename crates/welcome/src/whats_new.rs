@@ -0,0 +1,319 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{Render, Task, ViewContext};
+use http_client::HttpClient;
+use release_channel::{AppVersion, ReleaseChannel};
+use serde::{Deserialize, Serialize};
+use smol::io::AsyncReadExt;
+use ui::{prelude::*, KeyBinding};
+use util::ResultExt;
+
+const RELEASE_NOTES_KEY: &str = "welcome_release_notes";
+const MAX_HIGHLIGHTS: usize = 4;
+
+struct Tip {
+    text: &'static str,
+    /// The action the tip is about, by name, so that the welcome crate doesn't
+    /// need to depend on every feature it points to.
+    action: &'static str,
+}
+
+const TIPS: &[Tip] = &[
+    Tip {
+        text: "Preview themes live while browsing through them.",
+        action: "theme_selector::Toggle",
+    },
+    Tip {
+        text: "Search the whole project, with regular expressions and path filters.",
+        action: "pane::DeploySearch",
+    },
+    Tip {
+        text: "Jump to any symbol in your project by name.",
+        action: "project_symbols::Toggle",
+    },
+    Tip {
+        text: "Switch between open tabs in the order you last used them.",
+        action: "tab_switcher::Toggle",
+    },
+    Tip {
+        text: "Open a terminal in your project's directory without leaving Zed.",
+        action: "workspace::NewTerminal",
+    },
+    Tip {
+        text: "Change your font, theme and every other preference in your settings file.",
+        action: "zed::OpenSettings",
+    },
+    Tip {
+        text: "Rebind any key, or add bindings for your own workflow, in your keymap file.",
+        action: "zed::OpenKeymap",
+    },
+];
+
+/// Release notes are fetched once per version and kept in the database, so
+/// that they can be shown offline.
+#[derive(Serialize, Deserialize)]
+struct CachedReleaseNotes {
+    version: String,
+    title: String,
+    highlights: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseNotesBody {
+    title: String,
+    release_notes: String,
+}
+
+/// Highlights of the current release and a rotating feature tip.
+pub struct WhatsNew {
+    release_notes: Option<CachedReleaseNotes>,
+    tip_ix: usize,
+    _fetch_task: Task<()>,
+}
+
+impl WhatsNew {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let release_notes = KEY_VALUE_STORE
+            .read_kvp(RELEASE_NOTES_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<CachedReleaseNotes>(&json).log_err());
+        let version = AppVersion::global(cx).to_string();
+        let fetch_task = if release_notes
+            .as_ref()
+            .map_or(true, |notes| notes.version != version)
+        {
+            Self::fetch_release_notes(version, cx)
+        } else {
+            Task::ready(())
+        };
+        // Show a different tip every day.
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() / (60 * 60 * 24));
+
+        Self {
+            release_notes,
+            tip_ix: days as usize % TIPS.len(),
+            _fetch_task: fetch_task,
+        }
+    }
+
+    fn fetch_release_notes(version: String, cx: &mut ViewContext<Self>) -> Task<()> {
+        // Nightly and dev builds don't have release notes.
+        let release_channel = ReleaseChannel::global(cx);
+        if !matches!(
+            release_channel,
+            ReleaseChannel::Stable | ReleaseChannel::Preview
+        ) {
+            return Task::ready(());
+        }
+        let client = client::Client::global(cx).http_client();
+        let url = client.build_url(&format!(
+            "/api/release_notes/v2/{}/{}",
+            release_channel.dev_name(),
+            version
+        ));
+
+        cx.spawn(|this, mut cx| async move {
+            let Some(body) = fetch_release_notes(client.as_ref(), &url).await.log_err() else {
+                return;
+            };
+            let release_notes = CachedReleaseNotes {
+                version,
+                title: body.title,
+                highlights: release_highlights(&body.release_notes),
+            };
+            if let Some(json) = serde_json::to_string(&release_notes).log_err() {
+                KEY_VALUE_STORE
+                    .write_kvp(RELEASE_NOTES_KEY.to_string(), json)
+                    .await
+                    .log_err();
+            }
+            this.update(&mut cx, |this, cx| {
+                this.release_notes = Some(release_notes);
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn next_tip(&mut self, cx: &mut ViewContext<Self>) {
+        self.tip_ix = (self.tip_ix + 1) % TIPS.len();
+        cx.notify();
+    }
+
+    fn render_release_notes(&self, release_notes: &CachedReleaseNotes) -> impl IntoElement {
+        v_flex()
+            .gap_1()
+            .child(
+                Label::new(format!("What's New in {}", release_notes.title)).size(LabelSize::Small),
+            )
+            .children(release_notes.highlights.iter().map(|highlight| {
+                Label::new(format!("• {highlight}"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
+    }
+
+    fn render_tip(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let tip = &TIPS[self.tip_ix];
+        let action = cx.build_action(tip.action, None).log_err();
+        let key_binding = action
+            .as_ref()
+            .and_then(|action| KeyBinding::for_action(action.as_ref(), cx));
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Tip").size(LabelSize::Small))
+                    .child(
+                        IconButton::new("next-tip", IconName::ArrowRight)
+                            .icon_size(IconSize::XSmall)
+                            .icon_color(Color::Muted)
+                            .on_click(cx.listener(|this, _, cx| this.next_tip(cx))),
+                    ),
+            )
+            .child(
+                Label::new(tip.text)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .children(key_binding)
+                    .children(action.map(|action| {
+                        Button::new("try-tip", "Try It")
+                            .label_size(LabelSize::Small)
+                            .on_click(move |_, cx| cx.dispatch_action(action.boxed_clone()))
+                    })),
+            )
+    }
+}
+
+impl Render for WhatsNew {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                div()
+                    .pl_1()
+                    .font_buffer(cx)
+                    .text_color(Color::Muted.color(cx))
+                    .child(
+                        Label::new("What's New")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .px_1()
+                    .gap_3()
+                    .children(
+                        self.release_notes
+                            .as_ref()
+                            .filter(|notes| !notes.highlights.is_empty())
+                            .map(|notes| self.render_release_notes(notes)),
+                    )
+                    .child(self.render_tip(cx)),
+            )
+    }
+}
+
+async fn fetch_release_notes(client: &dyn HttpClient, url: &str) -> Result<ReleaseNotesBody> {
+    let mut response = client.get(url, Default::default(), true).await?;
+    let mut body = Vec::new();
+    response.body_mut().read_to_end(&mut body).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "failed to fetch release notes: {}",
+            response.status()
+        ));
+    }
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// The top-level bullet points of the release notes, as plain text.
+fn release_highlights(markdown: &str) -> Vec<String> {
+    markdown
+        .lines()
+        .filter_map(|line| line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")))
+        .map(plain_text)
+        .filter(|highlight| !highlight.is_empty())
+        .take(MAX_HIGHLIGHTS)
+        .collect()
+}
+
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find('[') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let link = after.find("](").and_then(|label_end| {
+            let url_len = after[label_end + 2..].find(')')?;
+            Some((label_end, label_end + 2 + url_len))
+        });
+        match link {
+            Some((label_end, url_end)) => {
+                text.push_str(&after[..label_end]);
+                rest = &after[url_end + 1..];
+            }
+            None => {
+                text.push('[');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+
+    let mut text = text.replace(['`', '*'], "");
+    // Drop trailing pull request references, e.g. `(#1234)`.
+    let trimmed_len = text.trim_end().len();
+    if text[..trimmed_len].ends_with(')') {
+        if let Some(start) = text[..trimmed_len].rfind(" (#") {
+            text.truncate(start);
+        }
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_highlights() {
+        let markdown = "\
+This week's release brings a few highlights.
+
+### Features
+
+- Added `go to definition` for [TOML](https://toml.io) keys ([#1234](https://github.com/zed-industries/zed/pull/1234))
+  - Nested details are skipped
+* **Improved** startup time
+-
+
+### Bug Fixes
+
+- Fixed a crash when [closing] the last tab (#42)
+- One
+- Two";
+        assert_eq!(
+            release_highlights(markdown),
+            vec![
+                "Added go to definition for TOML keys",
+                "Improved startup time",
+                "Fixed a crash when [closing] the last tab",
+                "One",
+            ]
+        );
+    }
+}
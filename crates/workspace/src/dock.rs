@@ -169,6 +169,8 @@ pub struct Dock {
     focus_handle: FocusHandle,
     pub(crate) serialized_dock: Option<DockData>,
     resizeable: bool,
+    /// The persistent name of the panel whose button is highlighted to point the user at it.
+    highlighted_panel: Option<&'static str>,
     _subscriptions: [Subscription; 2],
 }
 
@@ -237,6 +239,7 @@ impl Dock {
                 _subscriptions: [focus_subscription, zoom_subscription],
                 serialized_dock: None,
                 resizeable: true,
+                highlighted_panel: None,
             }
         });
 
@@ -321,6 +324,21 @@ impl Dock {
             .position(|entry| entry.panel.remote_id() == Some(panel_id))
     }
 
+    pub fn set_highlighted_panel(
+        &mut self,
+        persistent_name: Option<&'static str>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.highlighted_panel != persistent_name {
+            self.highlighted_panel = persistent_name;
+            cx.notify();
+        }
+    }
+
+    pub fn highlighted_panel(&self) -> Option<&'static str> {
+        self.highlighted_panel
+    }
+
     pub fn active_panel_index(&self) -> usize {
         self.active_panel_index
     }
@@ -734,6 +752,7 @@ impl Render for PanelButtons {
                 let panel = entry.panel.clone();
 
                 let is_active_button = i == active_index && is_open;
+                let is_highlighted = dock.highlighted_panel == Some(name);
                 let (action, tooltip) = if is_active_button {
                     let action = dock.toggle_action();
 
@@ -779,7 +798,8 @@ impl Render for PanelButtons {
                         .trigger(
                             IconButton::new(name, icon)
                                 .icon_size(IconSize::Small)
-                                .toggle_state(is_active_button)
+                                .toggle_state(is_active_button || is_highlighted)
+                                .when(is_highlighted, |button| button.icon_color(Color::Accent))
                                 .on_click({
                                     let action = action.boxed_clone();
                                     move |_, cx| cx.dispatch_action(action.boxed_clone())
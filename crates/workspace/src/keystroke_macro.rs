@@ -0,0 +1,286 @@
+use anyhow::anyhow;
+use gpui::{actions, impl_actions, Action, AppContext, Global, Keystroke, WindowContext};
+use serde::Deserialize;
+use util::ResultExt;
+
+use crate::Workspace;
+
+actions!(
+    workspace,
+    [
+        ToggleMacroRecording,
+        StartMacroRecording,
+        StopMacroRecording,
+        ReplayMacro,
+    ]
+);
+
+/// Replays the last recorded macro the given number of times.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct ReplayMacroTimes(pub usize);
+
+impl_actions!(workspace, [ReplayMacroTimes]);
+
+/// Guards against macros that (directly or not) end up replaying themselves.
+const MAX_REPLAYED_STEPS: usize = 10_000;
+
+#[derive(Debug)]
+enum MacroStep {
+    /// A keystroke that resolved to an action through the keymap.
+    Action(Box<dyn Action>),
+    /// A keystroke that did not match any binding, e.g. plain text input.
+    Keystroke(Keystroke),
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
+        }
+    }
+}
+
+/// App-wide state of the keystroke macro recorder.
+#[derive(Default)]
+pub struct KeystrokeMacros {
+    recording: Option<Vec<MacroStep>>,
+    last_recorded: Vec<MacroStep>,
+    replaying: bool,
+}
+
+impl Global for KeystrokeMacros {}
+
+impl KeystrokeMacros {
+    pub fn is_recording(cx: &AppContext) -> bool {
+        cx.try_global::<Self>()
+            .map_or(false, |macros| macros.recording.is_some())
+    }
+
+    pub fn is_replaying(cx: &AppContext) -> bool {
+        cx.try_global::<Self>()
+            .map_or(false, |macros| macros.replaying)
+    }
+
+    pub fn has_recording(cx: &AppContext) -> bool {
+        cx.try_global::<Self>()
+            .map_or(false, |macros| !macros.last_recorded.is_empty())
+    }
+
+    pub fn start_recording(cx: &mut AppContext) {
+        if Self::is_replaying(cx) {
+            return;
+        }
+        cx.global_mut::<Self>().recording = Some(Vec::new());
+    }
+
+    pub fn stop_recording(cx: &mut AppContext) {
+        let macros = cx.global_mut::<Self>();
+        if let Some(steps) = macros.recording.take() {
+            macros.last_recorded = steps;
+        }
+    }
+
+    pub fn toggle_recording(cx: &mut AppContext) {
+        if Self::is_recording(cx) {
+            Self::stop_recording(cx);
+        } else {
+            Self::start_recording(cx);
+        }
+    }
+
+    /// Replays the last recorded macro `times` times in the given window.
+    pub fn replay(times: usize, cx: &mut WindowContext) {
+        if Self::is_recording(cx) || Self::is_replaying(cx) {
+            return;
+        }
+        let macros = cx.global_mut::<Self>();
+        if macros.last_recorded.is_empty() || times == 0 {
+            return;
+        }
+        let recorded = macros.last_recorded.clone();
+        // One step past the limit, for the replay to notice it is reached.
+        let step_count = recorded
+            .len()
+            .checked_mul(times)
+            .unwrap_or(usize::MAX)
+            .min(MAX_REPLAYED_STEPS + 1);
+        macros.replaying = true;
+
+        cx.spawn(|mut cx| async move {
+            let mut result = Ok(());
+            let steps = recorded.iter().cycle().take(step_count).cloned();
+            for (ix, step) in steps.enumerate() {
+                if ix >= MAX_REPLAYED_STEPS {
+                    result = Err(anyhow!(
+                        "aborting macro replay after {MAX_REPLAYED_STEPS} steps"
+                    ));
+                    break;
+                }
+                // Each step gets its own update so that deferred actions are
+                // flushed before the next keystroke is dispatched.
+                let dispatched = cx.update(|cx| {
+                    let focused = cx.focused();
+                    match step {
+                        MacroStep::Action(action) => cx.dispatch_action(action),
+                        MacroStep::Keystroke(keystroke) => {
+                            cx.dispatch_keystroke(keystroke);
+                        }
+                    }
+                    if cx.focused() != focused {
+                        cx.draw();
+                    }
+                });
+                if let Err(error) = dispatched {
+                    result = Err(error);
+                    break;
+                }
+            }
+            cx.update(|cx| cx.global_mut::<Self>().replaying = false)
+                .log_err();
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn observe_keystroke(keystroke: &Keystroke, action: Option<&dyn Action>, cx: &mut AppContext) {
+        let macros = cx.global::<Self>();
+        if macros.recording.is_none() || macros.replaying {
+            return;
+        }
+        let step = match action {
+            Some(action) if is_macro_action(action) => return,
+            Some(action) => MacroStep::Action(action.boxed_clone()),
+            None => MacroStep::Keystroke(keystroke.clone()),
+        };
+        if let Some(recording) = cx.global_mut::<Self>().recording.as_mut() {
+            recording.push(step);
+        }
+    }
+}
+
+fn is_macro_action(action: &dyn Action) -> bool {
+    let action = action.as_any();
+    action.is::<ToggleMacroRecording>()
+        || action.is::<StartMacroRecording>()
+        || action.is::<StopMacroRecording>()
+        || action.is::<ReplayMacro>()
+        || action.is::<ReplayMacroTimes>()
+}
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(KeystrokeMacros::default());
+
+    cx.observe_keystrokes(|event, cx| {
+        KeystrokeMacros::observe_keystroke(&event.keystroke, event.action.as_deref(), cx)
+    })
+    .detach();
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|_, _: &ToggleMacroRecording, cx| {
+                KeystrokeMacros::toggle_recording(cx)
+            })
+            .register_action(|_, _: &StartMacroRecording, cx| KeystrokeMacros::start_recording(cx))
+            .register_action(|_, _: &StopMacroRecording, cx| KeystrokeMacros::stop_recording(cx))
+            .register_action(|_, _: &ReplayMacro, cx| KeystrokeMacros::replay(1, cx))
+            .register_action(|_, action: &ReplayMacroTimes, cx| {
+                KeystrokeMacros::replay(action.0.max(1), cx)
+            });
+    })
+    .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{
+        div, FocusHandle, InteractiveElement, IntoElement, KeyBinding, Render, TestAppContext,
+        ViewContext,
+    };
+
+    use super::*;
+
+    actions!(test, [Increment]);
+
+    struct Counter {
+        focus_handle: FocusHandle,
+        count: usize,
+    }
+
+    impl Render for Counter {
+        fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .track_focus(&self.focus_handle)
+                .on_action(cx.listener(|counter, _: &Increment, _| counter.count += 1))
+        }
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.set_global(KeystrokeMacros::default());
+            cx.observe_keystrokes(|event, cx| {
+                KeystrokeMacros::observe_keystroke(&event.keystroke, event.action.as_deref(), cx)
+            })
+            .detach();
+            cx.bind_keys([KeyBinding::new("a", Increment, None)]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_recording_and_replaying(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (counter, cx) = cx.add_window_view(|cx| Counter {
+            focus_handle: cx.focus_handle(),
+            count: 0,
+        });
+        counter.update(cx, |counter, cx| cx.focus(&counter.focus_handle));
+
+        cx.update(|cx| KeystrokeMacros::start_recording(cx));
+        cx.simulate_keystrokes("a a");
+        cx.update(|cx| {
+            assert!(KeystrokeMacros::is_recording(cx));
+            assert!(!KeystrokeMacros::has_recording(cx));
+            KeystrokeMacros::stop_recording(cx);
+            assert!(KeystrokeMacros::has_recording(cx));
+        });
+        assert_eq!(counter.update(cx, |counter, _| counter.count), 2);
+
+        cx.update(|cx| KeystrokeMacros::replay(3, cx));
+        cx.run_until_parked();
+        assert_eq!(counter.update(cx, |counter, _| counter.count), 8);
+        cx.update(|cx| assert!(!KeystrokeMacros::is_replaying(cx)));
+
+        // Recording again replaces the last macro.
+        cx.update(|cx| KeystrokeMacros::start_recording(cx));
+        cx.simulate_keystrokes("a");
+        cx.update(|cx| {
+            KeystrokeMacros::stop_recording(cx);
+            KeystrokeMacros::replay(1, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(counter.update(cx, |counter, _| counter.count), 10);
+    }
+
+    #[gpui::test]
+    async fn test_replayed_steps_are_capped(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (counter, cx) = cx.add_window_view(|cx| Counter {
+            focus_handle: cx.focus_handle(),
+            count: 0,
+        });
+        counter.update(cx, |counter, cx| cx.focus(&counter.focus_handle));
+
+        cx.update(|cx| KeystrokeMacros::start_recording(cx));
+        cx.simulate_keystrokes("a");
+        cx.update(|cx| KeystrokeMacros::stop_recording(cx));
+
+        // Would overflow the step count without the cap.
+        cx.update(|cx| KeystrokeMacros::replay(usize::MAX, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            counter.update(cx, |counter, _| counter.count),
+            1 + MAX_REPLAYED_STEPS
+        );
+        cx.update(|cx| assert!(!KeystrokeMacros::is_replaying(cx)));
+    }
+}
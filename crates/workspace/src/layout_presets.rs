@@ -0,0 +1,321 @@
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{impl_actions, px, AppContext, Axis, Global, View, ViewContext};
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+use crate::{
+    dock::Dock, notifications::NotificationId, persistence::DB, Member, Pane, PaneAxis, PaneGroup,
+    Toast, Workspace,
+};
+
+const LAYOUT_PRESETS_KEY: &str = "workspace_layout_presets";
+
+/// Arranges the workspace as saved in the layout preset with the given name.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ApplyLayout {
+    pub name: String,
+}
+
+/// Saves the workspace's current layout as a preset, replacing the one with the same name.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct SaveLayout {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct DeleteLayout {
+    pub name: String,
+}
+
+impl_actions!(workspace, [ApplyLayout, SaveLayout, DeleteLayout]);
+
+/// A named arrangement of the workspace: how the center is split into panes,
+/// which docks are open with which panel and how large they are.
+/// The items open in the panes are not part of it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutPreset {
+    pub name: String,
+    center: PaneLayout,
+    left_dock: DockLayout,
+    right_dock: DockLayout,
+    bottom_dock: DockLayout,
+    #[serde(default)]
+    centered_layout: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PaneLayout {
+    Pane,
+    Split {
+        axis: Axis,
+        flexes: Vec<f32>,
+        children: Vec<PaneLayout>,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct DockLayout {
+    open: bool,
+    active_panel: Option<String>,
+    /// The size of the active panel, in pixels.
+    size: Option<f32>,
+}
+
+/// The layout presets saved so far, shared by all workspaces.
+#[derive(Default)]
+pub struct LayoutPresets {
+    presets: Vec<LayoutPreset>,
+}
+
+impl Global for LayoutPresets {}
+
+impl LayoutPresets {
+    pub fn presets(cx: &AppContext) -> &[LayoutPreset] {
+        cx.try_global::<Self>()
+            .map_or(&[], |layout_presets| &layout_presets.presets)
+    }
+
+    fn save(preset: LayoutPreset, cx: &mut AppContext) {
+        let presets = &mut cx.global_mut::<Self>().presets;
+        match presets
+            .iter_mut()
+            .find(|existing| existing.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+        }
+        Self::persist(cx);
+    }
+
+    fn delete(name: &str, cx: &mut AppContext) {
+        cx.global_mut::<Self>()
+            .presets
+            .retain(|preset| preset.name != name);
+        Self::persist(cx);
+    }
+
+    fn persist(cx: &mut AppContext) {
+        if let Some(json) = serde_json::to_string(&cx.global::<Self>().presets).log_err() {
+            cx.background_executor()
+                .spawn(KEY_VALUE_STORE.write_kvp(LAYOUT_PRESETS_KEY.to_string(), json))
+                .detach_and_log_err(cx);
+        }
+    }
+}
+
+impl PaneLayout {
+    fn capture(member: &Member) -> Self {
+        match member {
+            Member::Pane(_) => Self::Pane,
+            Member::Axis(axis) => Self::Split {
+                axis: axis.axis,
+                flexes: axis.flexes.lock().clone(),
+                children: axis.members.iter().map(Self::capture).collect(),
+            },
+        }
+    }
+
+    fn pane_count(&self) -> usize {
+        match self {
+            Self::Pane => 1,
+            Self::Split { children, .. } => children.iter().map(Self::pane_count).sum(),
+        }
+    }
+
+    /// Builds the pane group member with this layout, taking the panes from
+    /// `panes` in order. Expects as many panes as [`Self::pane_count`].
+    fn build(&self, panes: &mut impl Iterator<Item = View<Pane>>) -> Option<Member> {
+        match self {
+            Self::Pane => panes.next().map(Member::Pane),
+            Self::Split {
+                axis,
+                flexes,
+                children,
+            } => {
+                let members = children
+                    .iter()
+                    .map(|child| child.build(panes))
+                    .collect::<Option<Vec<_>>>()?;
+                let flexes = (flexes.len() == members.len()).then(|| flexes.clone());
+                Some(Member::Axis(PaneAxis::load(*axis, members, flexes)))
+            }
+        }
+    }
+}
+
+impl DockLayout {
+    fn capture(dock: &View<Dock>, cx: &AppContext) -> Self {
+        let dock = dock.read(cx);
+        let active_panel = dock.active_panel();
+        Self {
+            open: dock.is_open(),
+            active_panel: active_panel.map(|panel| panel.persistent_name().to_string()),
+            size: active_panel
+                .and_then(|panel| dock.panel_size(panel.as_ref(), cx))
+                .map(|size| size.0),
+        }
+    }
+
+    fn apply(&self, dock: &View<Dock>, cx: &mut ViewContext<Workspace>) {
+        dock.update(cx, |dock, cx| {
+            if let Some(active_panel) = &self.active_panel {
+                if let Some(ix) = dock.panel_index_for_persistent_name(active_panel, cx) {
+                    dock.activate_panel(ix, cx);
+                }
+            }
+            if let Some(size) = self.size {
+                dock.resize_active_panel(Some(px(size)), cx);
+            }
+            dock.set_open(self.open, cx);
+        });
+    }
+}
+
+impl Workspace {
+    /// The name of the layout preset that was last applied to or saved from this workspace.
+    pub fn layout_preset(&self) -> Option<&str> {
+        self.layout_preset.as_deref()
+    }
+
+    pub fn save_layout(&mut self, name: String, cx: &mut ViewContext<Self>) {
+        let preset = LayoutPreset {
+            name: name.clone(),
+            center: PaneLayout::capture(&self.center.root),
+            left_dock: DockLayout::capture(&self.left_dock, cx),
+            right_dock: DockLayout::capture(&self.right_dock, cx),
+            bottom_dock: DockLayout::capture(&self.bottom_dock, cx),
+            centered_layout: self.centered_layout,
+        };
+        LayoutPresets::save(preset, cx);
+        self.layout_preset = Some(name);
+        cx.notify();
+    }
+
+    pub fn apply_layout(&mut self, name: &str, cx: &mut ViewContext<Self>) {
+        let Some(preset) = LayoutPresets::presets(cx)
+            .iter()
+            .find(|preset| preset.name == name)
+            .cloned()
+        else {
+            self.show_toast(
+                Toast::new(
+                    NotificationId::unique::<LayoutPreset>(),
+                    format!("There is no layout named \"{name}\""),
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+
+        let previously_active_pane = self.active_pane.clone();
+        let mut panes = self.center.panes().into_iter().cloned().collect::<Vec<_>>();
+        let pane_count = preset.center.pane_count();
+        while panes.len() < pane_count {
+            panes.push(self.add_pane(cx));
+        }
+        // Panes that don't fit in the layout hand their items over to its last pane.
+        let extra_panes = panes.split_off(pane_count);
+        if let Some(last_pane) = panes.last().cloned() {
+            for extra_pane in extra_panes {
+                let items = extra_pane.read(cx).items().cloned().collect::<Vec<_>>();
+                for item in items {
+                    extra_pane.update(cx, |pane, cx| {
+                        pane.remove_item(item.item_id(), false, false, cx)
+                    });
+                    last_pane.update(cx, |pane, cx| pane.add_item(item, false, false, None, cx));
+                }
+                self.remove_pane(extra_pane, Some(last_pane.clone()), cx);
+            }
+        }
+        if let Some(root) = preset.center.build(&mut panes.into_iter()) {
+            self.center = PaneGroup::with_root(root);
+        }
+        if self.center.panes().contains(&&previously_active_pane) {
+            cx.focus_view(&previously_active_pane);
+        }
+
+        for (dock, dock_layout) in [
+            (self.left_dock.clone(), &preset.left_dock),
+            (self.right_dock.clone(), &preset.right_dock),
+            (self.bottom_dock.clone(), &preset.bottom_dock),
+        ] {
+            dock_layout.apply(&dock, cx);
+        }
+
+        if self.centered_layout != preset.centered_layout {
+            self.centered_layout = preset.centered_layout;
+            if let Some(database_id) = self.database_id() {
+                cx.background_executor()
+                    .spawn(DB.set_centered_layout(database_id, self.centered_layout))
+                    .detach_and_log_err(cx);
+            }
+        }
+
+        self.layout_preset = Some(preset.name);
+        self.serialize_workspace(cx);
+        cx.notify();
+    }
+
+    pub fn delete_layout(&mut self, name: &str, cx: &mut ViewContext<Self>) {
+        LayoutPresets::delete(name, cx);
+        if self.layout_preset.as_deref() == Some(name) {
+            self.layout_preset = None;
+        }
+        cx.notify();
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    let presets = KEY_VALUE_STORE
+        .read_kvp(LAYOUT_PRESETS_KEY)
+        .log_err()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).log_err())
+        .unwrap_or_default();
+    cx.set_global(LayoutPresets { presets });
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, action: &ApplyLayout, cx| {
+                workspace.apply_layout(&action.name, cx)
+            })
+            .register_action(|workspace, action: &SaveLayout, cx| {
+                workspace.save_layout(action.name.clone(), cx)
+            })
+            .register_action(|workspace, action: &DeleteLayout, cx| {
+                workspace.delete_layout(&action.name, cx)
+            });
+    })
+    .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_layout_serialization() {
+        let layout = PaneLayout::Split {
+            axis: Axis::Horizontal,
+            flexes: vec![1.5, 0.5],
+            children: vec![
+                PaneLayout::Pane,
+                PaneLayout::Split {
+                    axis: Axis::Vertical,
+                    flexes: vec![1., 1.],
+                    children: vec![PaneLayout::Pane, PaneLayout::Pane],
+                },
+            ],
+        };
+        assert_eq!(layout.pane_count(), 3);
+
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"split","axis":"Horizontal","flexes":[1.5,0.5],"children":[{"kind":"pane"},{"kind":"split","axis":"Vertical","flexes":[1.0,1.0],"children":[{"kind":"pane"},{"kind":"pane"}]}]}"#
+        );
+        assert_eq!(serde_json::from_str::<PaneLayout>(&json).unwrap(), layout);
+    }
+}
@@ -1,6 +1,10 @@
 pub mod model;
 
-use std::{path::Path, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use anyhow::{anyhow, bail, Context, Result};
 use client::DevServerProjectId;
@@ -383,9 +387,50 @@ define_connection! {
     sql!(
         ALTER TABLE toolchains ADD COLUMN raw_json TEXT DEFAULT "{}";
     ),
+    sql!(
+        ALTER TABLE toolchains ADD COLUMN worktree_root_path BLOB;
+    ),
+    sql!(
+        CREATE TABLE custom_toolchains (
+            language_name TEXT NOT NULL,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL,
+            raw_json TEXT NOT NULL,
+            PRIMARY KEY (language_name, path)
+        ) STRICT;
+    ),
+    sql!(
+        CREATE TABLE oneshot_tasks (
+            workspace_id INTEGER NOT NULL,
+            worktree_root_path BLOB NOT NULL,
+            command TEXT NOT NULL,
+            cwd BLOB NOT NULL,
+            PRIMARY KEY (workspace_id, worktree_root_path, command),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    ),
+    sql!(
+        CREATE TABLE global_oneshot_tasks (
+            command TEXT NOT NULL PRIMARY KEY
+        );
+    ),
+    sql!(
+        CREATE TABLE pinned_tasks (
+            workspace_id INTEGER NOT NULL,
+            worktree_root_path BLOB,
+            source_name TEXT NOT NULL,
+            label TEXT NOT NULL,
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
+/// How many oneshot tasks are kept in the database for each workspace, and in the global history.
+const MAX_ONESHOT_TASKS: usize = 50;
+
 impl WorkspaceDb {
     /// Returns a serialized workspace for the given worktree_roots. If the passed array
     /// is empty, the most recent workspace is returned instead. If no workspace for the
@@ -720,6 +765,16 @@ impl WorkspaceDb {
         }
     }
 
+    query! {
+        pub fn recent_session_ids() -> Result<Vec<String>> {
+            SELECT session_id
+            FROM workspaces
+            WHERE session_id IS NOT NULL
+            GROUP BY session_id
+            ORDER BY MAX(timestamp) DESC
+        }
+    }
+
     query! {
         fn ssh_projects() -> Result<Vec<SerializedSshProject>> {
             SELECT id, host, port, paths, user
@@ -1074,69 +1129,95 @@ impl WorkspaceDb {
         }
     }
 
+    /// Worktree ids are not stable across restarts, so toolchains are looked up by the
+    /// absolute path of the worktree root they were selected for. Toolchains saved before
+    /// the root path was stored are looked up by their worktree id instead.
     pub async fn toolchain(
         &self,
         workspace_id: WorkspaceId,
         worktree_id: WorktreeId,
+        worktree_root_path: Arc<Path>,
         language_name: LanguageName,
     ) -> Result<Option<Toolchain>> {
         self.write(move |this| {
             let mut select = this
                 .select_bound(sql!(
-                    SELECT name, path, raw_json FROM toolchains WHERE workspace_id = ? AND language_name = ? AND worktree_id = ?
+                    SELECT name, path, raw_json FROM toolchains
+                    WHERE workspace_id = ?1 AND language_name = ?2
+                        AND (worktree_root_path = ?3 OR (worktree_root_path IS NULL AND worktree_id = ?4))
+                    ORDER BY worktree_root_path IS NULL
                 ))
                 .context("Preparing insertion")?;
 
-            let toolchain: Vec<(String, String, String)> =
-                select((workspace_id, language_name.0.to_owned(), worktree_id.to_usize()))?;
+            let toolchain: Vec<(String, String, Option<String>)> = select((
+                workspace_id,
+                language_name.0.to_owned(),
+                worktree_root_path,
+                worktree_id.to_usize(),
+            ))?;
 
-            Ok(toolchain.into_iter().next().and_then(|(name, path, raw_json)| Some(Toolchain {
+            Ok(toolchain.into_iter().next().map(|(name, path, raw_json)| Toolchain {
                 name: name.into(),
                 path: path.into(),
                 language_name,
-                as_json: serde_json::Value::from_str(&raw_json).ok()?
-            })))
+                as_json: toolchain_json(raw_json),
+            }))
         })
         .await
     }
 
+    /// All toolchains selected in the workspace, with the worktree id they were saved with
+    /// and, unless they were saved before it was stored, their worktree root path.
     pub(crate) async fn toolchains(
         &self,
         workspace_id: WorkspaceId,
-    ) -> Result<Vec<(Toolchain, WorktreeId)>> {
+    ) -> Result<Vec<(Toolchain, WorktreeId, Option<PathBuf>)>> {
         self.write(move |this| {
             let mut select = this
                 .select_bound(sql!(
-                    SELECT name, path, worktree_id, language_name, raw_json FROM toolchains WHERE workspace_id = ?
+                    SELECT name, path, worktree_id, worktree_root_path, language_name, raw_json FROM toolchains WHERE workspace_id = ?
                 ))
                 .context("Preparing insertion")?;
 
-            let toolchain: Vec<(String, String, u64, String, String)> =
+            let toolchain: Vec<(String, String, u64, Option<PathBuf>, String, Option<String>)> =
                 select(workspace_id)?;
 
-            Ok(toolchain.into_iter().filter_map(|(name, path, worktree_id, language_name, raw_json)| Some((Toolchain {
+            Ok(toolchain.into_iter().map(|(name, path, worktree_id, worktree_root_path, language_name, raw_json)| (Toolchain {
                 name: name.into(),
                 path: path.into(),
                 language_name: LanguageName::new(&language_name),
-                as_json: serde_json::Value::from_str(&raw_json).ok()?
-            }, WorktreeId::from_proto(worktree_id)))).collect())
+                as_json: toolchain_json(raw_json),
+            }, WorktreeId::from_proto(worktree_id), worktree_root_path)).collect())
         })
         .await
     }
+
     pub async fn set_toolchain(
         &self,
         workspace_id: WorkspaceId,
         worktree_id: WorktreeId,
+        worktree_root_path: Arc<Path>,
         toolchain: Toolchain,
     ) -> Result<()> {
         self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM toolchains WHERE workspace_id = ? AND language_name = ? AND worktree_root_path = ?
+            ))?((
+                workspace_id,
+                toolchain.language_name.0.as_ref(),
+                worktree_root_path.clone(),
+            ))
+            .context("Clearing previous toolchain")?;
+
             let mut insert = conn
                 .exec_bound(sql!(
-                    INSERT INTO toolchains(workspace_id, worktree_id, language_name, name, path) VALUES (?, ?, ?, ?,  ?)
+                    INSERT INTO toolchains(workspace_id, worktree_id, language_name, name, path, raw_json, worktree_root_path) VALUES (?, ?, ?, ?, ?, ?, ?)
                     ON CONFLICT DO
                     UPDATE SET
                         name = ?4,
-                        path = ?5
+                        path = ?5,
+                        raw_json = ?6,
+                        worktree_root_path = ?7
 
                 ))
                 .context("Preparing insertion")?;
@@ -1147,11 +1228,176 @@ impl WorkspaceDb {
                 toolchain.language_name.0.as_ref(),
                 toolchain.name.as_ref(),
                 toolchain.path.as_ref(),
+                toolchain.as_json.to_string(),
+                worktree_root_path,
             ))?;
 
             Ok(())
         }).await
     }
+
+    /// Toolchains that were registered by hand for the given language, in registration order.
+    pub async fn custom_toolchains(&self, language_name: LanguageName) -> Result<Vec<Toolchain>> {
+        self.write(move |this| {
+            let mut select = this
+                .select_bound(sql!(
+                    SELECT name, path, raw_json FROM custom_toolchains WHERE language_name = ? ORDER BY rowid
+                ))
+                .context("Preparing selection")?;
+
+            let toolchains: Vec<(String, String, String)> = select(language_name.0.to_owned())?;
+
+            Ok(toolchains.into_iter().filter_map(|(name, path, raw_json)| Some(Toolchain {
+                name: name.into(),
+                path: path.into(),
+                language_name: language_name.clone(),
+                as_json: serde_json::Value::from_str(&raw_json).ok()?
+            })).collect())
+        })
+        .await
+    }
+
+    /// Registers a toolchain that couldn't be detected automatically. Registering the same path
+    /// again renames the existing entry.
+    pub async fn add_custom_toolchain(&self, toolchain: Toolchain) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT INTO custom_toolchains(language_name, name, path, raw_json) VALUES (?, ?, ?, ?)
+                ON CONFLICT DO
+                UPDATE SET
+                    name = ?2,
+                    raw_json = ?4
+            ))?((
+                toolchain.language_name.0.as_ref(),
+                toolchain.name.as_ref(),
+                toolchain.path.as_ref(),
+                toolchain.as_json.to_string(),
+            ))
+            .context("Adding custom toolchain")
+        })
+        .await
+    }
+
+    /// The oneshot tasks spawned in the workspace, from the least to the most recently used:
+    /// the root of the worktree each was spawned in, its command and its working directory.
+    pub async fn oneshot_tasks(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<(PathBuf, String, PathBuf)>> {
+        self.write(move |this| {
+            let mut select = this
+                .select_bound(sql!(
+                    SELECT worktree_root_path, command, cwd FROM oneshot_tasks WHERE workspace_id = ? ORDER BY rowid
+                ))
+                .context("Preparing selection")?;
+
+            select(workspace_id)
+        })
+        .await
+    }
+
+    /// Records the oneshot task as the most recently used one of the workspace, forgetting the
+    /// least recently used ones past [`MAX_ONESHOT_TASKS`].
+    pub async fn save_oneshot_task(
+        &self,
+        workspace_id: WorkspaceId,
+        worktree_root_path: Arc<Path>,
+        command: String,
+        cwd: PathBuf,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT OR REPLACE INTO oneshot_tasks(workspace_id, worktree_root_path, command, cwd) VALUES (?, ?, ?, ?)
+            ))?((workspace_id, worktree_root_path, command, cwd))
+            .context("Saving oneshot task")?;
+
+            conn.exec_bound(sql!(
+                DELETE FROM oneshot_tasks WHERE workspace_id = ?1 AND rowid NOT IN (
+                    SELECT rowid FROM oneshot_tasks WHERE workspace_id = ?1 ORDER BY rowid DESC LIMIT ?2
+                )
+            ))?((workspace_id, MAX_ONESHOT_TASKS))
+            .context("Forgetting least recently used oneshot tasks")
+        })
+        .await
+    }
+
+    query! {
+        pub async fn delete_oneshot_task(workspace_id: WorkspaceId, command: String) -> Result<()> {
+            DELETE FROM oneshot_tasks
+            WHERE workspace_id = ? AND command = ?
+        }
+    }
+
+    query! {
+        pub async fn delete_worktree_oneshot_tasks(workspace_id: WorkspaceId, worktree_root_path: Arc<Path>) -> Result<()> {
+            DELETE FROM oneshot_tasks
+            WHERE workspace_id = ? AND worktree_root_path = ?
+        }
+    }
+
+    // The commands of the oneshot tasks spawned in any workspace, from the least to the most recently used.
+    query! {
+        pub async fn global_oneshot_tasks() -> Result<Vec<String>> {
+            SELECT command FROM global_oneshot_tasks ORDER BY rowid
+        }
+    }
+
+    /// Records the oneshot task's command as the most recently used one of all workspaces, forgetting the
+    /// least recently used ones past [`MAX_ONESHOT_TASKS`].
+    pub async fn save_global_oneshot_task(&self, command: String) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT OR REPLACE INTO global_oneshot_tasks(command) VALUES (?)
+            ))?(command)
+            .context("Saving global oneshot task")?;
+
+            conn.exec_bound(sql!(
+                DELETE FROM global_oneshot_tasks WHERE rowid NOT IN (
+                    SELECT rowid FROM global_oneshot_tasks ORDER BY rowid DESC LIMIT ?
+                )
+            ))?(MAX_ONESHOT_TASKS)
+            .context("Forgetting least recently used global oneshot tasks")
+        })
+        .await
+    }
+
+    query! {
+        pub async fn delete_global_oneshot_task(command: String) -> Result<()> {
+            DELETE FROM global_oneshot_tasks
+            WHERE command = ?
+        }
+    }
+
+    // The tasks pinned in the tasks modal of the workspace, in the order they were pinned: the root of
+    // the worktree each was pinned for, if any, the name of its source and its template label.
+    query! {
+        pub async fn pinned_tasks(workspace_id: WorkspaceId) -> Result<Vec<(Option<PathBuf>, String, String)>> {
+            SELECT worktree_root_path, source_name, label FROM pinned_tasks
+            WHERE workspace_id = ?
+            ORDER BY rowid
+        }
+    }
+
+    query! {
+        pub async fn save_pinned_task(workspace_id: WorkspaceId, worktree_root_path: Option<Arc<Path>>, source_name: String, label: String) -> Result<()> {
+            INSERT INTO pinned_tasks(workspace_id, worktree_root_path, source_name, label)
+            VALUES (?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn delete_pinned_task(workspace_id: WorkspaceId, worktree_root_path: Option<Arc<Path>>, source_name: String, label: String) -> Result<()> {
+            DELETE FROM pinned_tasks
+            WHERE workspace_id = ? AND worktree_root_path IS ? AND source_name = ? AND label = ?
+        }
+    }
+}
+
+/// Parses the stored JSON of a toolchain, which is missing for toolchains saved before it was stored.
+fn toolchain_json(raw_json: Option<String>) -> serde_json::Value {
+    raw_json
+        .and_then(|raw_json| serde_json::Value::from_str(&raw_json).log_err())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -1592,6 +1838,59 @@ mod tests {
         assert_eq!(locations[0].2, Some(60));
     }
 
+    #[gpui::test]
+    async fn test_recent_session_ids() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_recent_session_ids").await);
+
+        let workspaces = [
+            (1, Some("session-id-1"), "2024-01-01 10:00:00"),
+            (2, Some("session-id-1"), "2024-01-01 12:00:00"),
+            (3, Some("session-id-2"), "2024-01-01 11:00:00"),
+            (4, None, "2024-01-01 13:00:00"),
+            (5, Some("session-id-3"), "2024-01-01 09:00:00"),
+        ];
+        for (id, session_id, timestamp) in workspaces {
+            db.save_workspace(SerializedWorkspace {
+                id: WorkspaceId(id),
+                location: SerializedWorkspaceLocation::from_local_paths([format!("/tmp{id}")]),
+                center_group: Default::default(),
+                window_bounds: Default::default(),
+                display: Default::default(),
+                docks: Default::default(),
+                centered_layout: false,
+                session_id: session_id.map(ToOwned::to_owned),
+                window_id: Some(id as u64),
+            })
+            .await;
+            db.write(move |conn| {
+                conn.exec_bound(sql!(UPDATE workspaces SET timestamp = ? WHERE workspace_id = ?))
+                    .unwrap()((timestamp, WorkspaceId(id)))
+                .unwrap()
+            })
+            .await;
+        }
+
+        // Sessions are ordered by their most recently used workspace, and workspaces without a
+        // session are left out.
+        assert_eq!(
+            db.recent_session_ids().unwrap(),
+            ["session-id-1", "session-id-2", "session-id-3"]
+        );
+
+        db.write(|conn| {
+            conn.exec_bound(sql!(UPDATE workspaces SET timestamp = ? WHERE workspace_id = ?))
+                .unwrap()(("2024-01-01 14:00:00", WorkspaceId(5)))
+            .unwrap()
+        })
+        .await;
+        assert_eq!(
+            db.recent_session_ids().unwrap(),
+            ["session-id-3", "session-id-1", "session-id-2"]
+        );
+    }
+
     fn default_workspace<P: AsRef<Path>>(
         workspace_id: &[P],
         center_group: &SerializedPaneGroup,
@@ -1853,6 +2152,328 @@ mod tests {
         assert_eq!(project.id, same_project.id);
     }
 
+    #[gpui::test]
+    async fn test_toolchains_are_keyed_by_worktree_root() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_toolchains_are_keyed_by_worktree_root").await);
+        let workspace_id = db.next_id().await.unwrap();
+        let worktree_root: Arc<Path> = Path::new("/tmp/project").into();
+        let toolchain = |name: &str| Toolchain {
+            name: name.to_string().into(),
+            path: format!("/tmp/project/{name}/bin/python").into(),
+            language_name: LanguageName::new("Python"),
+            as_json: serde_json::json!({ "name": name }),
+        };
+
+        db.set_toolchain(
+            workspace_id,
+            WorktreeId::from_usize(1),
+            worktree_root.clone(),
+            toolchain(".venv"),
+        )
+        .await
+        .unwrap();
+        // Worktree ids change between sessions; selecting again must replace the old entry.
+        db.set_toolchain(
+            workspace_id,
+            WorktreeId::from_usize(7),
+            worktree_root.clone(),
+            toolchain("venv"),
+        )
+        .await
+        .unwrap();
+
+        let restored = db
+            .toolchain(
+                workspace_id,
+                WorktreeId::from_usize(3),
+                worktree_root.clone(),
+                LanguageName::new("Python"),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored, toolchain("venv"));
+        assert_eq!(restored.as_json, serde_json::json!({ "name": "venv" }));
+
+        assert_eq!(
+            db.toolchains(workspace_id).await.unwrap(),
+            vec![(
+                toolchain("venv"),
+                WorktreeId::from_usize(7),
+                Some(worktree_root.to_path_buf())
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_toolchains_saved_without_worktree_root() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_toolchains_saved_without_worktree_root").await);
+        let workspace_id = db.next_id().await.unwrap();
+        // Toolchains saved before the worktree root path and raw JSON were stored.
+        db.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT INTO toolchains(workspace_id, worktree_id, language_name, name, path, raw_json) VALUES (?, ?, ?, ?, ?, NULL)
+            ))
+            .unwrap()((workspace_id, 1_usize, "Python", "venv", "/tmp/project/venv/bin/python"))
+            .unwrap()
+        })
+        .await;
+        let legacy_toolchain = Toolchain {
+            name: "venv".into(),
+            path: "/tmp/project/venv/bin/python".into(),
+            language_name: LanguageName::new("Python"),
+            as_json: serde_json::Value::Null,
+        };
+
+        assert_eq!(
+            db.toolchains(workspace_id).await.unwrap(),
+            vec![(legacy_toolchain.clone(), WorktreeId::from_usize(1), None)]
+        );
+        assert_eq!(
+            db.toolchain(
+                workspace_id,
+                WorktreeId::from_usize(1),
+                Path::new("/tmp/project").into(),
+                LanguageName::new("Python"),
+            )
+            .await
+            .unwrap(),
+            Some(legacy_toolchain)
+        );
+        assert_eq!(
+            db.toolchain(
+                workspace_id,
+                WorktreeId::from_usize(2),
+                Path::new("/tmp/other").into(),
+                LanguageName::new("Python"),
+            )
+            .await
+            .unwrap(),
+            None
+        );
+    }
+
+    #[gpui::test]
+    async fn test_oneshot_tasks() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_oneshot_tasks").await);
+        let workspace_id = db.next_id().await.unwrap();
+        let other_workspace_id = db.next_id().await.unwrap();
+        let worktree_root: Arc<Path> = Path::new("/tmp/project").into();
+        let cwd = PathBuf::from("/tmp/project/src");
+
+        for command in ["ls", "cargo check", "ls"] {
+            db.save_oneshot_task(
+                workspace_id,
+                worktree_root.clone(),
+                command.to_string(),
+                cwd.clone(),
+            )
+            .await
+            .unwrap();
+        }
+        db.save_oneshot_task(
+            other_workspace_id,
+            worktree_root.clone(),
+            "make".to_string(),
+            worktree_root.to_path_buf(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.oneshot_tasks(workspace_id).await.unwrap(),
+            vec![
+                (
+                    worktree_root.to_path_buf(),
+                    "cargo check".to_string(),
+                    cwd.clone()
+                ),
+                (worktree_root.to_path_buf(), "ls".to_string(), cwd.clone()),
+            ],
+            "Reused commands should become the most recently used ones"
+        );
+
+        db.delete_oneshot_task(workspace_id, "ls".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.oneshot_tasks(workspace_id).await.unwrap(),
+            vec![(
+                worktree_root.to_path_buf(),
+                "cargo check".to_string(),
+                cwd.clone()
+            )]
+        );
+
+        for ix in 0..MAX_ONESHOT_TASKS {
+            db.save_oneshot_task(
+                workspace_id,
+                worktree_root.clone(),
+                format!("echo {ix}"),
+                cwd.clone(),
+            )
+            .await
+            .unwrap();
+        }
+        let oneshot_tasks = db.oneshot_tasks(workspace_id).await.unwrap();
+        assert_eq!(oneshot_tasks.len(), MAX_ONESHOT_TASKS);
+        assert_eq!(oneshot_tasks[0].1, "echo 0");
+        assert_eq!(db.oneshot_tasks(other_workspace_id).await.unwrap().len(), 1);
+
+        db.delete_worktree_oneshot_tasks(workspace_id, worktree_root.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.oneshot_tasks(workspace_id).await.unwrap(), Vec::new());
+        assert_eq!(
+            db.oneshot_tasks(other_workspace_id).await.unwrap().len(),
+            1,
+            "Clearing a worktree's tasks should not affect other workspaces"
+        );
+
+        db.delete_workspace_by_id(other_workspace_id).await.unwrap();
+        assert_eq!(
+            db.oneshot_tasks(other_workspace_id).await.unwrap(),
+            Vec::new(),
+            "Deleting a workspace should delete its tasks"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_pinned_tasks() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_pinned_tasks").await);
+        let workspace_id = db.next_id().await.unwrap();
+        let worktree_root: Arc<Path> = Path::new("/tmp/project").into();
+
+        db.save_pinned_task(
+            workspace_id,
+            Some(worktree_root.clone()),
+            "make".to_string(),
+            "make all".to_string(),
+        )
+        .await
+        .unwrap();
+        db.save_pinned_task(
+            workspace_id,
+            None,
+            "global".to_string(),
+            "deploy".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.pinned_tasks(workspace_id).await.unwrap(),
+            vec![
+                (
+                    Some(worktree_root.to_path_buf()),
+                    "make".to_string(),
+                    "make all".to_string()
+                ),
+                (None, "global".to_string(), "deploy".to_string()),
+            ]
+        );
+
+        db.delete_pinned_task(
+            workspace_id,
+            None,
+            "global".to_string(),
+            "deploy".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.pinned_tasks(workspace_id).await.unwrap(),
+            vec![(
+                Some(worktree_root.to_path_buf()),
+                "make".to_string(),
+                "make all".to_string()
+            )],
+            "Tasks pinned without a worktree should be unpinned too"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_global_oneshot_tasks() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_global_oneshot_tasks").await);
+        db.save_global_oneshot_task("docker compose up".to_string())
+            .await
+            .unwrap();
+        db.save_global_oneshot_task("ls".to_string()).await.unwrap();
+        db.save_global_oneshot_task("docker compose up".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.global_oneshot_tasks().await.unwrap(),
+            vec!["ls".to_string(), "docker compose up".to_string()],
+            "Respawned commands should be deduplicated and become the most recent ones"
+        );
+
+        db.delete_global_oneshot_task("ls".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.global_oneshot_tasks().await.unwrap(),
+            vec!["docker compose up".to_string()]
+        );
+
+        for i in 0..MAX_ONESHOT_TASKS + 5 {
+            db.save_global_oneshot_task(format!("echo {i}"))
+                .await
+                .unwrap();
+        }
+        let global_oneshot_tasks = db.global_oneshot_tasks().await.unwrap();
+        assert_eq!(global_oneshot_tasks.len(), MAX_ONESHOT_TASKS);
+        assert_eq!(global_oneshot_tasks[0], "echo 5");
+    }
+
+    #[gpui::test]
+    async fn test_custom_toolchains() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_custom_toolchains").await);
+        let toolchain = |name: &str, path: &str| Toolchain {
+            name: name.to_string().into(),
+            path: path.to_string().into(),
+            language_name: LanguageName::new("Python"),
+            as_json: serde_json::json!({ "custom": true }),
+        };
+
+        db.add_custom_toolchain(toolchain("SDK", "/opt/sdk/bin/python"))
+            .await
+            .unwrap();
+        db.add_custom_toolchain(toolchain("Bazel", "/opt/bazel/python"))
+            .await
+            .unwrap();
+        // Registering a known path again only renames it.
+        db.add_custom_toolchain(toolchain("Cross SDK", "/opt/sdk/bin/python"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            db.custom_toolchains(LanguageName::new("Python"))
+                .await
+                .unwrap(),
+            vec![
+                toolchain("Cross SDK", "/opt/sdk/bin/python"),
+                toolchain("Bazel", "/opt/bazel/python"),
+            ]
+        );
+        assert_eq!(
+            db.custom_toolchains(LanguageName::new("Rust"))
+                .await
+                .unwrap(),
+            Vec::new()
+        );
+    }
+
     #[gpui::test]
     async fn test_get_ssh_projects() {
         let db = WorkspaceDb(open_test_db("test_get_ssh_projects").await);
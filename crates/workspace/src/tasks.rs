@@ -1,9 +1,16 @@
-use project::TaskSourceKind;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use gpui::AppContext;
+use project::{Project, TaskSourceKind};
 use remote::ConnectionState;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::ViewContext;
 
-use crate::Workspace;
+use crate::{notifications::NotificationId, persistence::DB, Toast, Workspace};
+
+/// How long to wait for a dependency's terminal to start before giving up on the tasks that depend on it.
+const TASK_START_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub fn schedule_task(
     workspace: &mut Workspace,
@@ -39,29 +46,371 @@ pub fn schedule_task(
     }
 }
 
+/// Spawns the resolved task, after running the tasks it depends on one by one.
+/// The task is not spawned if any of them fails.
 pub fn schedule_resolved_task(
     workspace: &mut Workspace,
     task_source_kind: TaskSourceKind,
-    mut resolved_task: ResolvedTask,
+    resolved_task: ResolvedTask,
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
-    if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
-        if !omit_history {
-            resolved_task.resolved = Some(spawn_in_terminal.clone());
-            workspace.project().update(cx, |project, cx| {
-                if let Some(task_inventory) =
-                    project.task_store().read(cx).task_inventory().cloned()
-                {
-                    task_inventory.update(cx, |inventory, _| {
-                        inventory.task_scheduled(task_source_kind, resolved_task);
+    if !resolved_task.original_task().allow_concurrent_runs {
+        let task_ids = resolved_task
+            .resolved
+            .iter()
+            .chain(&resolved_task.parallel)
+            .map(|spawn_in_terminal| spawn_in_terminal.id.clone())
+            .collect::<Vec<_>>();
+        let concurrency = resolved_task.original_task().concurrency;
+        let can_spawn = workspace.project().update(cx, |project, cx| {
+            project.task_store().update(cx, |task_store, cx| {
+                task_store.apply_concurrency(&task_ids, concurrency, cx)
+            })
+        });
+        if !can_spawn {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<ResolvedTask>(),
+                    format!(
+                        "Task \"{}\" is already running",
+                        resolved_task.display_label()
+                    ),
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        }
+    }
+
+    if resolved_task.original_task().depends_on.is_empty() {
+        spawn_resolved_task(workspace, task_source_kind, resolved_task, omit_history, cx);
+        return;
+    }
+
+    let dependencies = match resolve_dependencies(
+        workspace.project().read(cx),
+        &task_source_kind,
+        &resolved_task,
+        cx,
+    ) {
+        Ok(dependencies) => dependencies,
+        Err(e) => {
+            workspace.show_error(&e, cx);
+            return;
+        }
+    };
+    let project = workspace.project().clone();
+    cx.spawn(|workspace, mut cx| async move {
+        for (dependency_source_kind, dependency) in dependencies {
+            let dependency_label = dependency.display_label().to_string();
+            let task_ids = dependency
+                .resolved
+                .iter()
+                .chain(&dependency.parallel)
+                .map(|spawn_in_terminal| spawn_in_terminal.id.clone())
+                .collect::<Vec<_>>();
+            let dependency_finished = workspace.update(&mut cx, |workspace, cx| {
+                let dependency_finished = project.update(cx, |project, _| {
+                    task_ids
+                        .iter()
+                        .map(|task_id| project.wait_for_task(task_id.clone()))
+                        .collect::<Vec<_>>()
+                });
+                spawn_resolved_task(workspace, dependency_source_kind, dependency, true, cx);
+                dependency_finished
+            })?;
+            // A dependency may never start, e.g. when spawning it fails, and the wait would never end then.
+            let start_timeout = cx.background_executor().timer(TASK_START_TIMEOUT);
+            cx.spawn({
+                let project = project.clone();
+                |mut cx| async move {
+                    start_timeout.await;
+                    project.update(&mut cx, |project, _| {
+                        for task_id in &task_ids {
+                            project.stop_waiting_for_task(task_id);
+                        }
                     })
                 }
-            });
+            })
+            .detach();
+            let dependency_finished = futures::future::join_all(dependency_finished).await;
+            let failure = if dependency_finished.iter().any(Result::is_err) {
+                Some("did not start")
+            } else if !dependency_finished
+                .iter()
+                .all(|succeeded| succeeded == &Ok(true))
+            {
+                Some("did not succeed")
+            } else {
+                None
+            };
+            if let Some(failure) = failure {
+                workspace.update(&mut cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<ResolvedTask>(),
+                            format!(
+                                "Task \"{}\" was not started: \"{dependency_label}\" {failure}",
+                                resolved_task.display_label()
+                            ),
+                        ),
+                        cx,
+                    )
+                })?;
+                return anyhow::Ok(());
+            }
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            spawn_resolved_task(workspace, task_source_kind, resolved_task, omit_history, cx)
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Resolves the tasks the resolved task depends on, in the order they have to run,
+/// with the same context as the task itself.
+pub fn resolve_dependencies(
+    project: &Project,
+    task_source_kind: &TaskSourceKind,
+    resolved_task: &ResolvedTask,
+    cx: &AppContext,
+) -> anyhow::Result<Vec<(TaskSourceKind, ResolvedTask)>> {
+    let inventory = project
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .context("tasks are not available in this project")?
+        .read(cx);
+    // Look for the dependencies in the task's own worktree first.
+    let mut worktree_ids = project
+        .visible_worktrees(cx)
+        .map(|worktree| Some(worktree.read(cx).id()))
+        .collect::<Vec<_>>();
+    if let Some(id) = task_source_kind.worktree() {
+        worktree_ids.retain(|worktree_id| worktree_id != &Some(id));
+        worktree_ids.insert(0, Some(id));
+    }
+    if worktree_ids.is_empty() {
+        worktree_ids.push(None);
+    }
+
+    let chain = resolved_task.original_task().dependency_chain(|label| {
+        worktree_ids.iter().find_map(|worktree_id| {
+            inventory
+                .list_tasks(None, None, *worktree_id, cx)
+                .into_iter()
+                .find(|(_, template)| template.label == label)
+        })
+    })?;
+    chain
+        .into_iter()
+        .map(|(source_kind, template)| {
+            let resolved = template
+                .resolve_task(&source_kind.to_id_base(), resolved_task.task_context())
+                .with_context(|| {
+                    format!(
+                        "could not resolve task \"{}\", a dependency of \"{}\"",
+                        template.label,
+                        resolved_task.display_label()
+                    )
+                })?;
+            Ok((source_kind, resolved))
+        })
+        .collect()
+}
+
+fn spawn_resolved_task(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    omit_history: bool,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    if !resolved_task.env_files().is_empty() {
+        let env_files = workspace.project().update(cx, |project, cx| {
+            project.task_store().update(cx, |task_store, cx| {
+                task_store.load_env_files(resolved_task.env_files().to_vec(), cx)
+            })
+        });
+        let mut resolved_task = resolved_task;
+        cx.spawn(|workspace, mut cx| async move {
+            resolved_task.apply_env_files(&env_files.await);
+            workspace.update(&mut cx, |workspace, cx| {
+                spawn_resolved_task(workspace, task_source_kind, resolved_task, omit_history, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+        return;
+    }
+
+    let spawns = resolved_task
+        .resolved
+        .iter()
+        .chain(&resolved_task.parallel)
+        .cloned()
+        .collect::<Vec<_>>();
+    if spawns.is_empty() {
+        return;
+    }
+    let watch = resolved_task.original_task().watch.clone();
+    let worktree_id = task_source_kind.worktree();
+
+    if !omit_history {
+        if task_source_kind == TaskSourceKind::UserInput {
+            save_oneshot_task(workspace, &resolved_task, cx);
         }
+        workspace.project().update(cx, |project, cx| {
+            if let Some(task_inventory) = project.task_store().read(cx).task_inventory().cloned() {
+                task_inventory.update(cx, |inventory, _| {
+                    inventory
+                        .task_scheduled(task_source_kind, resolved_task.without_password_inputs());
+                })
+            }
+        });
+    }
 
+    if let Some(watch) = &watch {
+        workspace.project().update(cx, |project, cx| {
+            project.task_store().update(cx, |task_store, cx| {
+                for spawn_in_terminal in &spawns {
+                    task_store.watch_task(spawn_in_terminal.clone(), worktree_id, watch, cx);
+                }
+            })
+        });
+    }
+
+    for spawn_in_terminal in spawns {
         cx.emit(crate::Event::SpawnTask {
             action: Box::new(spawn_in_terminal),
         });
     }
 }
+
+/// Saves the oneshot task in the workspace database, to restore it in the task history
+/// of the worktree it was spawned in when the workspace is reopened, and in the global history
+/// of all workspaces.
+fn save_oneshot_task(workspace: &Workspace, resolved_task: &ResolvedTask, cx: &AppContext) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(DB.save_global_oneshot_task(resolved_task.original_task().command.clone()))
+        .detach_and_log_err(cx);
+    let Some(cwd) = resolved_task
+        .resolved
+        .as_ref()
+        .and_then(|spawn_in_terminal| spawn_in_terminal.cwd.clone())
+    else {
+        return;
+    };
+    let Some(worktree_root_path) = workspace
+        .project()
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path())
+        .find(|worktree_root_path| cwd.starts_with(worktree_root_path))
+    else {
+        return;
+    };
+    let command = resolved_task.original_task().command.clone();
+    cx.background_executor()
+        .spawn(DB.save_oneshot_task(workspace_id, worktree_root_path, command, cwd))
+        .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{tests::init_test, Event};
+
+    #[gpui::test]
+    async fn test_dependency_that_does_not_start(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        { "label": "build", "command": "make" },
+                        { "label": "test", "command": "make test", "depends_on": ["build"] }
+                    ]"#,
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+
+        // Nothing opens terminals for the spawned tasks here, so the dependency never starts.
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned = spawned.clone();
+            cx.subscribe(&workspace, move |_, event, _| {
+                if let Event::SpawnTask { action } = event {
+                    spawned.borrow_mut().push(action.clone());
+                }
+            })
+            .detach();
+        });
+        let (task_source_kind, test_task) = project.update(cx, |project, cx| {
+            let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+            let inventory = project.task_store().read(cx).task_inventory().unwrap();
+            inventory
+                .read(cx)
+                .list_tasks(None, None, Some(worktree_id), cx)
+                .into_iter()
+                .find(|(_, template)| template.label == "test")
+                .unwrap()
+        });
+        workspace.update(cx, |workspace, cx| {
+            schedule_task(
+                workspace,
+                task_source_kind,
+                &test_task,
+                &TaskContext::default(),
+                false,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        let build_id = {
+            let spawned = spawned.borrow();
+            assert_eq!(
+                spawned
+                    .iter()
+                    .map(|spawn_in_terminal| spawn_in_terminal.label.as_str())
+                    .collect::<Vec<_>>(),
+                ["build"]
+            );
+            spawned[0].id.clone()
+        };
+
+        cx.executor().advance_clock(TASK_START_TIMEOUT);
+        cx.run_until_parked();
+        assert_eq!(
+            spawned.borrow().len(),
+            1,
+            "The task should not be spawned when its dependency does not start"
+        );
+        assert!(
+            !project.update(cx, |project, _| project.stop_waiting_for_task(&build_id)),
+            "The wait for the dependency should be dropped"
+        );
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(
+                workspace.notification_ids(),
+                [NotificationId::unique::<ResolvedTask>()]
+            );
+        });
+    }
+}
@@ -1,5 +1,7 @@
 pub mod dock;
 pub mod item;
+pub mod keystroke_macro;
+pub mod layout_presets;
 mod modal_layer;
 pub mod notifications;
 pub mod pane;
@@ -336,6 +338,8 @@ pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
     init_settings(cx);
     notifications::init(cx);
     theme_preview::init(cx);
+    keystroke_macro::init(cx);
+    layout_presets::init(cx);
 
     cx.on_action(Workspace::close_global);
     cx.on_action(reload);
@@ -773,6 +777,7 @@ pub struct Workspace {
     pane_history_timestamp: Arc<AtomicUsize>,
     bounds: Bounds<Pixels>,
     centered_layout: bool,
+    layout_preset: Option<String>,
     bounds_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
     on_prompt_for_open_path: Option<PromptForOpenPath>,
@@ -862,6 +867,12 @@ impl Workspace {
                     |cx| cx.new_view(|_| MessageNotification::new(message.clone())),
                 ),
 
+                project::Event::RestartTask(spawn_in_terminal) => {
+                    cx.emit(Event::SpawnTask {
+                        action: spawn_in_terminal.clone(),
+                    });
+                }
+
                 project::Event::HideToast { notification_id } => {
                     this.dismiss_notification(&NotificationId::named(notification_id.clone()), cx)
                 }
@@ -1078,6 +1089,7 @@ impl Workspace {
             // This data will be incorrect, but it will be overwritten by the time it needs to be used.
             bounds: Default::default(),
             centered_layout: false,
+            layout_preset: None,
             bounds_save_task_queued: None,
             on_prompt_for_new_path: None,
             on_prompt_for_open_path: None,
@@ -1176,14 +1188,7 @@ impl Workspace {
                 DB.next_id().await.unwrap_or_else(|_| Default::default())
             };
 
-            let toolchains = DB.toolchains(workspace_id).await?;
-            for (toolchain, worktree_id) in toolchains {
-                project_handle
-                    .update(&mut cx, |this, cx| {
-                        this.activate_toolchain(worktree_id, toolchain, cx)
-                    })?
-                    .await;
-            }
+            restore_toolchains(workspace_id, &project_handle, &mut cx).await?;
             let window = if let Some(window) = requesting_window {
                 cx.update_window(window.into(), |_, cx| {
                     cx.replace_root_view(|cx| {
@@ -1269,6 +1274,19 @@ impl Workspace {
         &self.right_dock
     }
 
+    /// Highlights the status bar button of the panel with the given persistent name, or none.
+    pub fn highlight_panel_button(
+        &mut self,
+        persistent_name: Option<&'static str>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        for dock in [&self.left_dock, &self.bottom_dock, &self.right_dock] {
+            dock.update(cx, |dock, cx| {
+                dock.set_highlighted_panel(persistent_name, cx)
+            });
+        }
+    }
+
     pub fn is_edited(&self) -> bool {
         self.window_edited
     }
@@ -5225,7 +5243,7 @@ pub fn last_session_workspace_locations(
         .log_err()
 }
 
-actions!(collab, [OpenChannelNotes]);
+actions!(collab, [OpenChannelNotes, OpenWhiteboard]);
 actions!(zed, [OpenLog]);
 
 async fn join_channel_internal(
@@ -5675,14 +5693,6 @@ pub fn open_ssh_project(
             )
         })?;
 
-        let toolchains = DB.toolchains(workspace_id).await?;
-        for (toolchain, worktree_id) in toolchains {
-            project
-                .update(&mut cx, |this, cx| {
-                    this.activate_toolchain(worktree_id, toolchain, cx)
-                })?
-                .await;
-        }
         let mut project_paths_to_open = vec![];
         let mut project_path_errors = vec![];
 
@@ -5706,6 +5716,8 @@ pub fn open_ssh_project(
                 .unwrap_or_else(|| anyhow!("no paths given")));
         }
 
+        restore_toolchains(workspace_id, &project, &mut cx).await?;
+
         cx.update_window(window.into(), |_, cx| {
             cx.replace_root_view(|cx| {
                 let mut workspace =
@@ -5743,6 +5755,36 @@ pub fn open_ssh_project(
     })
 }
 
+/// Re-activates the toolchains that were selected for this workspace's worktrees.
+/// Must be called once the worktrees have been added to the project.
+async fn restore_toolchains(
+    workspace_id: WorkspaceId,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
+    let toolchains = DB.toolchains(workspace_id).await?;
+    for (toolchain, saved_worktree_id, worktree_root_path) in toolchains {
+        let worktree_id = project.update(cx, |this, cx| match &worktree_root_path {
+            Some(worktree_root_path) => this.worktrees(cx).find_map(|worktree| {
+                let worktree = worktree.read(cx);
+                (*worktree.abs_path() == **worktree_root_path).then(|| worktree.id())
+            }),
+            None => this
+                .worktree_for_id(saved_worktree_id, cx)
+                .map(|_| saved_worktree_id),
+        })?;
+        let Some(worktree_id) = worktree_id else {
+            continue;
+        };
+        project
+            .update(cx, |this, cx| {
+                this.activate_toolchain(worktree_id, toolchain, cx)
+            })?
+            .await;
+    }
+    Ok(())
+}
+
 fn serialize_ssh_project(
     connection_options: SshConnectionOptions,
     paths: Vec<PathBuf>,
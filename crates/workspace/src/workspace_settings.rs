@@ -81,6 +81,8 @@ pub enum RestoreOnStartupBehavior {
     /// Restore all workspaces that were open when quitting Zed.
     #[default]
     LastSession,
+    /// Show the welcome page, which lists earlier sessions to restore.
+    Welcome,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -483,6 +483,7 @@ fn main() {
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
+        semantic_index::init(cx);
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
         outline_panel::init(Assets, cx);
@@ -870,7 +871,14 @@ async fn restore_or_create_workspace(
                 }
             }
         }
-    } else if matches!(KEY_VALUE_STORE.read_kvp(FIRST_OPEN), Ok(None)) {
+    } else if matches!(KEY_VALUE_STORE.read_kvp(FIRST_OPEN), Ok(None))
+        || cx.update(|cx| {
+            matches!(
+                WorkspaceSettings::get(None, cx).restore_on_startup,
+                workspace::RestoreOnStartupBehavior::Welcome
+            )
+        })?
+    {
         cx.update(|cx| show_welcome_view(app_state, cx))?.await?;
     } else {
         cx.update(|cx| {
@@ -43,7 +43,10 @@ use settings::{
 use std::any::TypeId;
 use std::path::PathBuf;
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
-use terminal_view::terminal_panel::{self, TerminalPanel};
+use terminal_view::{
+    task_output_panel::TaskOutputPanel,
+    terminal_panel::{self, TerminalPanel},
+};
 use theme::ActiveTheme;
 use util::{asset_str, ResultExt};
 use uuid::Uuid;
@@ -309,6 +312,7 @@ fn initialize_panels(prompt_builder: Arc<PromptBuilder>, cx: &mut ViewContext<Wo
         let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
+        let task_output_panel = TaskOutputPanel::load(workspace_handle.clone(), cx.clone());
         let channels_panel =
             collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
         let chat_panel =
@@ -324,6 +328,7 @@ fn initialize_panels(prompt_builder: Arc<PromptBuilder>, cx: &mut ViewContext<Wo
             project_panel,
             outline_panel,
             terminal_panel,
+            task_output_panel,
             channels_panel,
             chat_panel,
             notification_panel,
@@ -332,6 +337,7 @@ fn initialize_panels(prompt_builder: Arc<PromptBuilder>, cx: &mut ViewContext<Wo
             project_panel,
             outline_panel,
             terminal_panel,
+            task_output_panel,
             channels_panel,
             chat_panel,
             notification_panel,
@@ -342,6 +348,7 @@ fn initialize_panels(prompt_builder: Arc<PromptBuilder>, cx: &mut ViewContext<Wo
             workspace.add_panel(project_panel, cx);
             workspace.add_panel(outline_panel, cx);
             workspace.add_panel(terminal_panel, cx);
+            workspace.add_panel(task_output_panel, cx);
             workspace.add_panel(channels_panel, cx);
             workspace.add_panel(chat_panel, cx);
             workspace.add_panel(notification_panel, cx);
@@ -3555,6 +3562,7 @@ mod tests {
             tasks_ui::init(cx);
             initialize_workspace(app_state.clone(), prompt_builder, cx);
             search::init(cx);
+            semantic_index::init(cx);
             app_state
         })
     }
@@ -1,3 +1,4 @@
+mod macro_controls;
 mod markdown_preview;
 mod repl_menu;
 
@@ -21,7 +22,8 @@ use ui::{
 };
 use vim_mode_setting::VimModeSetting;
 use workspace::{
-    item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+    item::ItemHandle, keystroke_macro::KeystrokeMacros, ToolbarItemEvent, ToolbarItemLocation,
+    ToolbarItemView, Workspace,
 };
 use zed_actions::InlineAssist;
 
@@ -29,6 +31,7 @@ pub struct QuickActionBar {
     _inlay_hints_enabled_subscription: Option<Subscription>,
     active_item: Option<Box<dyn ItemHandle>>,
     buffer_search_bar: View<BufferSearchBar>,
+    macro_menu_handle: PopoverMenuHandle<ContextMenu>,
     show: bool,
     toggle_selections_handle: PopoverMenuHandle<ContextMenu>,
     toggle_settings_handle: PopoverMenuHandle<ContextMenu>,
@@ -45,6 +48,7 @@ impl QuickActionBar {
             _inlay_hints_enabled_subscription: None,
             active_item: None,
             buffer_search_bar,
+            macro_menu_handle: Default::default(),
             show: true,
             toggle_selections_handle: Default::default(),
             toggle_settings_handle: Default::default(),
@@ -53,6 +57,8 @@ impl QuickActionBar {
         this.apply_settings(cx);
         cx.observe_global::<SettingsStore>(|this, cx| this.apply_settings(cx))
             .detach();
+        cx.observe_global::<KeystrokeMacros>(|_, cx| cx.notify())
+            .detach();
         this
     }
 
@@ -353,6 +359,7 @@ impl Render for QuickActionBar {
             .children(self.render_repl_menu(cx))
             .children(self.render_toggle_markdown_preview(self.workspace.clone(), cx))
             .children(search_button)
+            .children(self.render_macro_controls(cx))
             .when(
                 AssistantSettings::get_global(cx).enabled
                     && AssistantSettings::get_global(cx).button,
@@ -0,0 +1,72 @@
+use gpui::AnyElement;
+use ui::{prelude::*, ContextMenu, IconButtonShape, PopoverMenu, Tooltip};
+use workspace::keystroke_macro::{
+    KeystrokeMacros, ReplayMacro, ReplayMacroTimes, ToggleMacroRecording,
+};
+
+use super::QuickActionBar;
+
+impl QuickActionBar {
+    pub fn render_macro_controls(&self, cx: &mut ViewContext<Self>) -> Option<AnyElement> {
+        let editor = self.active_editor()?;
+        let focus_handle = editor.read(cx).focus_handle(cx);
+        let is_recording = KeystrokeMacros::is_recording(cx);
+        let is_replaying = KeystrokeMacros::is_replaying(cx);
+
+        let record_button = IconButton::new("toggle-macro-recording", IconName::Record)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::Small)
+            .style(ButtonStyle::Subtle)
+            .when(is_recording, |button| button.icon_color(Color::Error))
+            .toggle_state(is_recording)
+            .disabled(is_replaying)
+            .tooltip({
+                let focus_handle = focus_handle.clone();
+                move |cx| {
+                    let label = if is_recording {
+                        "Stop Recording Macro"
+                    } else {
+                        "Record Macro"
+                    };
+                    Tooltip::for_action_in(label, &ToggleMacroRecording, &focus_handle, cx)
+                }
+            })
+            .on_click(|_, cx| cx.dispatch_action(Box::new(ToggleMacroRecording)));
+
+        let replay_menu = KeystrokeMacros::has_recording(cx).then(|| {
+            PopoverMenu::new("macro-replay-menu")
+                .trigger(
+                    IconButton::new("replay-macro", IconName::Play)
+                        .shape(IconButtonShape::Square)
+                        .icon_size(IconSize::Small)
+                        .style(ButtonStyle::Subtle)
+                        .disabled(is_recording || is_replaying)
+                        .toggle_state(self.macro_menu_handle.is_deployed())
+                        .when(!self.macro_menu_handle.is_deployed(), |this| {
+                            this.tooltip(|cx| Tooltip::text("Replay Macro", cx))
+                        }),
+                )
+                .with_handle(self.macro_menu_handle.clone())
+                .anchor(gpui::Corner::TopRight)
+                .menu(move |cx| {
+                    let focus_handle = focus_handle.clone();
+                    let menu = ContextMenu::build(cx, move |menu, _| {
+                        menu.context(focus_handle)
+                            .action("Replay Once", Box::new(ReplayMacro))
+                            .action("Replay 5 Times", Box::new(ReplayMacroTimes(5)))
+                            .action("Replay 10 Times", Box::new(ReplayMacroTimes(10)))
+                            .action("Replay 100 Times", Box::new(ReplayMacroTimes(100)))
+                    });
+                    Some(menu)
+                })
+        });
+
+        Some(
+            h_flex()
+                .gap(DynamicSpacing::Base06.rems(cx))
+                .child(record_button)
+                .children(replay_menu)
+                .into_any_element(),
+        )
+    }
+}
@@ -29,6 +29,8 @@ struct ReplMenuState {
     status: KernelStatus,
     kernel_name: SharedString,
     kernel_language: SharedString,
+    kernel_environment: Option<SharedString>,
+    queued_execution_count: usize,
 }
 
 impl QuickActionBar {
@@ -88,6 +90,7 @@ impl QuickActionBar {
                 ContextMenu::build(cx, move |menu, cx| {
                     let menu_state = session_state(session, cx);
                     let status = menu_state.status;
+                    let queued_execution_count = menu_state.queued_execution_count;
                     let editor = editor.clone();
 
                     menu.map(|menu| {
@@ -106,6 +109,17 @@ impl QuickActionBar {
                                     )
                                     .into_any_element()
                             })
+                            .when_some(menu_state.kernel_environment, |menu, environment| {
+                                menu.custom_row(move |_cx| {
+                                    h_flex()
+                                        .child(
+                                            Label::new(format!("environment: {environment}"))
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .into_any_element()
+                                })
+                            })
                             .custom_row(move |_cx| {
                                 h_flex()
                                     .child(
@@ -159,6 +173,26 @@ impl QuickActionBar {
                             }
                         },
                     )
+                    .when(queued_execution_count > 0, |menu| {
+                        menu.custom_entry(
+                            move |_cx| {
+                                Label::new(if queued_execution_count == 1 {
+                                    "Cancel 1 Queued Execution".to_string()
+                                } else {
+                                    format!("Cancel {queued_execution_count} Queued Executions")
+                                })
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .into_any_element()
+                            },
+                            {
+                                let editor = editor.clone();
+                                move |cx| {
+                                    repl::cancel_queued_executions(editor.clone(), cx);
+                                }
+                            },
+                        )
+                    })
                     .custom_entry(
                         move |_cx| {
                             Label::new("Clear Outputs")
@@ -202,8 +236,23 @@ impl QuickActionBar {
                             }
                         },
                     )
+                    .custom_entry(
+                        move |_cx| {
+                            Label::new("Restart Kernel and Run All")
+                                .size(LabelSize::Small)
+                                .color(Color::Error)
+                                .into_any_element()
+                        },
+                        {
+                            let editor = editor.clone();
+                            move |cx| {
+                                repl::restart_and_run_all(editor.clone(), cx).log_err();
+                            }
+                        },
+                    )
                     .separator()
-                    .action("View Sessions", Box::new(repl::Sessions))
+                    .action("Export to Notebook", Box::new(repl::ExportNotebook))
+                    .action("Manage Kernels", Box::new(repl::Sessions))
                     // TODO: Add shut down all kernels action
                     // .action("Shut Down all Kernels", Box::new(gpui::NoAction))
                 })
@@ -289,11 +338,13 @@ impl QuickActionBar {
 
         let session = repl::session(editor.downgrade(), cx);
 
-        let current_kernelspec = match session {
-            SessionSupport::ActiveSession(view) => Some(view.read(cx).kernel_specification.clone()),
-            SessionSupport::Inactive(kernel_specification) => Some(kernel_specification),
-            SessionSupport::RequiresSetup(_language_name) => None,
-            SessionSupport::Unsupported => None,
+        let (current_kernelspec, language) = match session {
+            SessionSupport::ActiveSession(view) => {
+                (Some(view.read(cx).kernel_specification.clone()), None)
+            }
+            SessionSupport::Inactive(kernel_specification) => (Some(kernel_specification), None),
+            SessionSupport::RequiresSetup(language_name) => (None, Some(language_name)),
+            SessionSupport::Unsupported => (None, None),
         };
 
         let current_kernel_name = current_kernelspec.as_ref().map(|spec| spec.name());
@@ -341,6 +392,10 @@ impl QuickActionBar {
                 )
                 .tooltip(move |cx| Tooltip::text("Select Kernel", cx)),
         )
+        .with_current_kernelspec(current_kernelspec)
+        .when_some(language, |selector, language| {
+            selector.with_language(language.0)
+        })
         .with_handle(menu_handle.clone())
         .into_any_element()
     }
@@ -375,6 +430,8 @@ fn session_state(session: View<Session>, cx: &WindowContext) -> ReplMenuState {
 
     let kernel_name = session.kernel_specification.name();
     let kernel_language: SharedString = session.kernel_specification.language();
+    let kernel_environment = session.kernel_specification.environment();
+    let queued_execution_count = session.queued_execution_count();
 
     let fill_fields = || {
         ReplMenuState {
@@ -386,6 +443,8 @@ fn session_state(session: View<Session>, cx: &WindowContext) -> ReplMenuState {
             indicator: None,
             kernel_name: kernel_name.clone(),
             kernel_language: kernel_language.clone(),
+            kernel_environment: kernel_environment.clone(),
+            queued_execution_count,
             // TODO: Technically not shutdown, but indeterminate
             status: KernelStatus::Shutdown,
             // current_delta: Duration::default(),
@@ -404,7 +463,14 @@ fn session_state(session: View<Session>, cx: &WindowContext) -> ReplMenuState {
         },
         Kernel::RunningKernel(kernel) => match &kernel.execution_state() {
             ExecutionState::Idle => ReplMenuState {
-                tooltip: format!("Run code on {} ({})", kernel_name, kernel_language).into(),
+                tooltip: match &kernel_environment {
+                    Some(environment) => format!(
+                        "Run code on {} ({}) in {}",
+                        kernel_name, kernel_language, environment
+                    ),
+                    None => format!("Run code on {} ({})", kernel_name, kernel_language),
+                }
+                .into(),
                 indicator: Some(Indicator::dot().color(Color::Success)),
                 status: session.kernel.status(),
                 ..fill_fields()
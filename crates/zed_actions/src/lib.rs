@@ -76,6 +76,12 @@ pub mod theme_selector {
     impl_actions!(theme_selector, [Toggle]);
 }
 
+pub mod assistant {
+    use gpui::actions;
+
+    actions!(assistant, [AskAboutKernelHistory]);
+}
+
 #[derive(Clone, Default, Deserialize, PartialEq)]
 pub struct InlineAssist {
     pub prompt: Option<String>,
@@ -91,12 +97,29 @@ pub struct OpenRecent {
 gpui::impl_actions!(projects, [OpenRecent]);
 gpui::actions!(projects, [OpenRemote]);
 
+/// What to do with the terminal pane and tab, after the command was started.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealStrategy {
+    /// Always show the task's pane, and focus the corresponding tab in it.
+    #[default]
+    Always,
+    /// Always show the task's pane, add the task's tab in it, but don't focus it.
+    NoFocus,
+    /// Do not alter focus, but still add/reuse the task's tab in its pane.
+    Never,
+}
+
 /// Where to spawn the task in the UI.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RevealTarget {
     /// In the central pane group, "main" editor area.
     Center,
+    /// In a new pane of the central pane group, split to the right of the active one.
+    Split,
+    /// In a new window of the project.
+    Window,
     /// In the terminal dock, "regular" terminal items' place.
     #[default]
     Dock,
@@ -109,8 +132,12 @@ pub enum Spawn {
     /// Spawns a task by the name given.
     ByName {
         task_name: String,
+        /// Spawned task's `reveal_target` property override.
         #[serde(default)]
         reveal_target: Option<RevealTarget>,
+        /// Spawned task's `reveal` property override.
+        #[serde(default)]
+        reveal: Option<RevealStrategy>,
     },
     /// Spawns a task via modal's selection.
     ViaModal {
@@ -145,9 +172,33 @@ pub struct Rerun {
     /// Default: null
     #[serde(default)]
     pub use_new_terminal: Option<bool>,
+    /// Instead of rerunning the task right away, puts its resolved command into the task modal's prompt,
+    /// to be edited and spawned as a oneshot task.
+    /// default: false
+    #[serde(default)]
+    pub edit: bool,
 
     /// If present, rerun the task with this ID, otherwise rerun the last task.
     pub task_id: Option<String>,
 }
 
-impl_actions!(task, [Spawn, Rerun]);
+/// Reveal the output of a task in the task output panel
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct RevealTaskOutput {
+    /// If present, reveal the latest run of the task with this ID, otherwise the latest run of any task.
+    #[serde(default)]
+    pub task_id: Option<String>,
+}
+
+impl_actions!(task, [Spawn, Rerun, RevealTaskOutput]);
+
+actions!(
+    task,
+    [
+        RunBuildTask,
+        RunTestTask,
+        RunDefaultTask,
+        ReloadAll,
+        ClearHistory
+    ]
+);